@@ -1,16 +1,62 @@
-use crate::cli::{Cli, Commands};
+use crate::callbacks::progress::ProgressManager;
+use crate::cli::{Cli, Commands, LsSort, ProgressMode};
 use crate::commands;
+use crate::lock::{CacheLock, LockMode};
+use crate::onboarding;
+use crate::report::OutputFormat;
 use anyhow::Result;
 use clap::Parser;
+use colored::Colorize;
 use mutant_client::MutantClient;
+use mutant_protocol::SyncDirection;
+use std::io::IsTerminal;
 
 pub async fn run() -> Result<()> {
     let cli = Cli::parse();
 
+    let output = OutputFormat::new(cli.json, cli.no_color);
+    if !output.color {
+        colored::control::set_override(false);
+    }
+
+    // Every bar is finished when leaving, be it on an error or a panic
+    let progress = match cli.progress {
+        _ if cli.quiet || output.json => ProgressManager::hidden(),
+        ProgressMode::Never => ProgressManager::hidden(),
+        ProgressMode::Always => ProgressManager::always(),
+        ProgressMode::Plain => ProgressManager::plain(),
+        ProgressMode::Auto if std::io::stderr().is_terminal() => ProgressManager::new(),
+        ProgressMode::Auto => ProgressManager::plain(),
+    };
+    let _progress_guard = progress.guard();
+
+    // Held until the command returns, background tasks outlive it
+    let lock_mode = match &cli.command {
+        Some(command) => command.lock_mode(),
+        None => Some(LockMode::Shared),
+    };
+    let _cache_lock = match lock_mode {
+        Some(mode) => Some(CacheLock::acquire(mode, cli.no_wait, &progress).await?),
+        None => None,
+    };
+
     // If no command is provided, run the ls command without history
     if cli.command.is_none() {
-        commands::daemon::start_daemon().await?;
-        return commands::ls::handle_ls(false).await;
+        commands::daemon::start_daemon(cli.concurrency).await?;
+        return commands::ls::handle_ls(
+            None,
+            None,
+            false,
+            false,
+            None,
+            false,
+            false,
+            LsSort::Name,
+            None,
+            output,
+            &progress,
+        )
+        .await;
     }
 
     // We know command is Some at this point, so we can safely unwrap
@@ -18,7 +64,14 @@ pub async fn run() -> Result<()> {
 
     // Start daemon for all commands except Daemon
     if !matches!(command, Commands::Daemon { .. }) {
-        commands::daemon::start_daemon().await?;
+        let daemon_was_running = commands::daemon::is_running();
+        commands::daemon::start_daemon(cli.concurrency).await?;
+        if !cli.quiet && !output.json {
+            onboarding::print_hints_once(&progress).await;
+            if !daemon_was_running {
+                onboarding::print_incomplete_notice(&progress).await;
+            }
+        }
     }
 
     // Process the command
@@ -26,70 +79,204 @@ pub async fn run() -> Result<()> {
         Commands::Put {
             key,
             file,
+            from_file,
+            public,
+            mode,
+            dry_run: true,
+            ..
+        } => {
+            commands::put::handle_put_dry_run(
+                key,
+                file.or(from_file).unwrap_or_default(),
+                public,
+                mode.into(),
+                output,
+            )
+            .await?;
+        }
+        Commands::Put {
+            key,
+            file,
+            from_file,
             background,
             no_verify,
             public,
             mode,
+            dry_run: false,
         } => {
+            // Clap requires exactly one of them
             commands::put::handle_put(
                 key,
-                file,
+                file.or(from_file).unwrap_or_default(),
                 public,
                 mode.into(),
                 no_verify,
                 background,
-                cli.quiet,
+                &progress,
             )
             .await?;
         }
+        Commands::PutMany {
+            manifest,
+            mode,
+            fail_fast,
+        } => {
+            commands::put_many::handle_put_many(manifest, mode.into(), fail_fast, &progress)
+                .await?;
+        }
         Commands::Get {
             key,
             destination_path,
+            output,
+            parents,
+            force,
             background,
             public,
+            index_file,
         } => {
-            commands::get::handle_get(key, destination_path, public, background, cli.quiet).await?;
+            // Clap requires exactly one of them
+            commands::get::handle_get(
+                key,
+                destination_path.or(output).unwrap_or_default(),
+                public,
+                index_file,
+                parents,
+                force,
+                background,
+                &progress,
+            )
+            .await?;
         }
-        Commands::Rm { key } => {
-            commands::rm::handle_rm(key).await?;
+        Commands::GetMany {
+            keys,
+            dir,
+            jobs,
+            force,
+            fail_fast,
+        } => {
+            commands::get_many::handle_get_many(keys, dir, jobs, force, fail_fast, &progress)
+                .await?;
+        }
+        Commands::Rm { key, promote_alias } => {
+            commands::rm::handle_rm(key, promote_alias, &progress).await?;
+        }
+        Commands::Alias { key, alias } => {
+            commands::alias::handle_alias(key, alias, &progress).await?;
+        }
+        Commands::Mv { old_key, new_key } => {
+            commands::mv::handle_mv(old_key, new_key, &progress).await?;
+        }
+        Commands::Restore { key } => {
+            commands::restore::handle_restore(key, &progress).await?;
+        }
+        Commands::Recover { keys_from } => {
+            commands::recover::handle_recover(&keys_from, output, &progress).await?;
+        }
+        Commands::Ls {
+            prefix,
+            delimiter,
+            long,
+            history,
+            collection,
+            trash,
+            include_trash,
+            sort,
+            index_file,
+            remote,
+        } => {
+            if remote {
+                commands::ls::handle_ls_remote(long, sort, output, &progress).await?;
+            } else {
+                commands::ls::handle_ls(
+                    prefix,
+                    delimiter,
+                    long,
+                    history,
+                    collection,
+                    trash,
+                    include_trash,
+                    sort,
+                    index_file,
+                    output,
+                    &progress,
+                )
+                .await?;
+            }
+        }
+        Commands::Stat { key } => {
+            commands::stat::handle_stat(key, output, &progress).await?;
+        }
+        Commands::Stats { io } => {
+            commands::stats::handle_stats(io, output).await?;
+        }
+        Commands::Bench { size, mode, keep } => {
+            commands::bench::handle_bench(size, mode.into(), keep, output).await?;
         }
-        Commands::Ls { history } => {
-            commands::ls::handle_ls(history).await?;
+        Commands::Selftest { local } => {
+            commands::selftest::handle_selftest(local, output).await?;
         }
-        Commands::Stats => {
-            commands::stats::handle_stats().await?;
+        Commands::PadExists { address } => {
+            commands::pad_exists::handle_pad_exists(address, output).await?;
+        }
+        Commands::Verify { key } => {
+            commands::verify::handle_verify(key, output).await?;
+        }
+        Commands::Collection { command } => {
+            commands::collection::handle_collection(command, &progress).await?;
+        }
+        Commands::Mirror { command } => {
+            commands::mirror::handle_mirror(command, &progress).await?;
+        }
+        Commands::Audit { command } => {
+            commands::audit::handle_audit(command, output, &progress).await?;
         }
         Commands::Tasks { command } => {
-            commands::tasks::handle_tasks(command).await?;
+            commands::tasks::handle_tasks(command, &progress).await?;
+        }
+        Commands::Cache { command } => {
+            commands::cache::handle_cache(command, &progress).await?;
         }
         Commands::Daemon { command } => {
-            commands::daemon::handle_daemon(command).await?;
+            commands::daemon::handle_daemon(command, cli.concurrency).await?;
         }
         Commands::Sync {
             background,
             push_force,
+            only,
+            push,
+            pull,
         } => {
-            commands::sync::handle_sync(background, push_force, cli.quiet).await?;
+            let direction = match (push, pull) {
+                (true, _) => SyncDirection::Push,
+                (_, true) => SyncDirection::Pull,
+                _ => SyncDirection::Both,
+            };
+            commands::sync::handle_sync(background, push_force, only, direction, &progress).await?;
         }
         Commands::Purge {
             aggressive,
             background,
         } => {
-            commands::purge::handle_purge(aggressive, background, cli.quiet).await?;
+            commands::purge::handle_purge(aggressive, background, output, &progress).await?;
         }
         Commands::Import { file_path } => {
-            commands::import::handle_import(file_path).await?;
+            commands::import::handle_import(file_path, &progress).await?;
         }
-        Commands::Export { destination_path } => {
-            commands::export::handle_export(destination_path).await?;
+        Commands::Export {
+            destination_path,
+            public_catalogue,
+        } => {
+            commands::export::handle_export(destination_path, public_catalogue, &progress).await?;
         }
         Commands::HealthCheck {
             key_name,
             background,
             recycle,
         } => {
-            commands::health_check::handle_health_check(key_name, background, recycle, cli.quiet)
-                .await?;
+            commands::health_check::handle_health_check(
+                key_name, background, recycle, output, &progress,
+            )
+            .await?;
         }
     }
 
@@ -98,6 +285,16 @@ pub async fn run() -> Result<()> {
 
 pub async fn connect_to_daemon() -> Result<MutantClient> {
     let mut client = MutantClient::new();
-    client.connect("ws://localhost:3030/ws").await?;
+    if let Err(e) = client.connect("ws://localhost:3030/ws").await {
+        // A daemon refusing its wallet exits at once, the reason is all there is to show.
+        if !commands::daemon::is_running() {
+            if let Some(refusal) = commands::daemon::startup_error() {
+                eprintln!("{}", "The daemon refused to start".red().bold());
+                eprintln!("{}", refusal.trim());
+                return Err(anyhow::anyhow!("The daemon refused to start"));
+            }
+        }
+        return Err(e.into());
+    }
     Ok(client)
 }
@@ -1,5 +1,4 @@
-use super::progress::StyledProgressBar;
-use indicatif::MultiProgress;
+use super::progress::{ProgressManager, StyledProgressBar};
 use log::{error, trace, warn};
 use mutant_client::ProgressReceiver;
 use mutant_protocol::{GetCallback, GetEvent, TaskProgress};
@@ -8,22 +7,39 @@ use tokio::sync::Mutex;
 
 // Get the specific styles needed
 
-pub fn create_get_progress(mut progress_rx: ProgressReceiver, multi_progress: &MultiProgress) {
+pub fn create_get_progress(mut progress_rx: ProgressReceiver, progress: &ProgressManager) {
     let download_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
+    let callback = get_callback(download_pb_opt, progress);
 
-    let pb_clone = download_pb_opt.clone();
-    let mp_clone = multi_progress.clone();
+    tokio::spawn(async move {
+        while let Some(progress) = progress_rx.recv().await {
+            match progress {
+                Ok(TaskProgress::Get(event)) => {
+                    callback(event.clone()).await.unwrap();
+                }
+                Ok(_) => warn!("Unexpected progress type"),
+                Err(e) => error!("Progress error: {:?}", e),
+            }
+        }
+    });
+}
 
-    let callback: GetCallback = Arc::new(move |event: GetEvent| {
+fn get_callback(
+    pb_clone: Arc<Mutex<Option<StyledProgressBar>>>,
+    progress: &ProgressManager,
+) -> GetCallback {
+    let progress_clone = progress.clone();
+
+    Arc::new(move |event: GetEvent| {
         let pb_arc = pb_clone.clone();
-        let multi_progress = mp_clone.clone();
+        let progress = progress_clone.clone();
 
         Box::pin(async move {
             match event {
                 GetEvent::Starting { total_chunks } => {
                     let mut pb_guard = pb_arc.lock().await;
                     let _ = pb_guard.get_or_insert_with(|| {
-                        let pb = StyledProgressBar::new_for_steps(&multi_progress);
+                        let pb = StyledProgressBar::new_for_steps(&progress);
                         pb.set_message("Fetching pads...".to_string());
                         pb.set_length(total_chunks as u64);
                         pb.set_position(0);
@@ -32,11 +48,19 @@ pub fn create_get_progress(mut progress_rx: ProgressReceiver, multi_progress: &M
 
                     drop(pb_guard);
                 }
-                GetEvent::PadFetched => {
+                GetEvent::PadFetched {
+                    chunk_index,
+                    completed_count,
+                } => {
+                    trace!(
+                        "Get Callback: chunk {} fetched, {} fetched so far",
+                        chunk_index,
+                        completed_count
+                    );
                     let mut pb_guard = pb_arc.lock().await;
                     if let Some(pb) = pb_guard.as_mut() {
                         if !pb.is_finished() {
-                            pb.inc(1);
+                            pb.advance_to(completed_count as u64);
                         }
                     } else {
                         error!(
@@ -45,6 +69,12 @@ pub fn create_get_progress(mut progress_rx: ProgressReceiver, multi_progress: &M
                     }
                     drop(pb_guard);
                 }
+                GetEvent::ChunkReplication {
+                    chunk_index,
+                    copies,
+                } => {
+                    trace!("Get Callback: chunk {} has {} copies", chunk_index, copies);
+                }
                 GetEvent::Complete => {
                     let mut pb_guard = pb_arc.lock().await;
                     if let Some(pb) = pb_guard.take() {
@@ -63,17 +93,43 @@ pub fn create_get_progress(mut progress_rx: ProgressReceiver, multi_progress: &M
 
             Ok(true)
         })
-    });
+    })
+}
 
-    tokio::spawn(async move {
-        while let Some(progress) = progress_rx.recv().await {
-            match progress {
-                Ok(TaskProgress::Get(event)) => {
-                    callback(event.clone()).await.unwrap();
-                }
-                Ok(_) => warn!("Unexpected progress type"),
-                Err(e) => error!("Progress error: {:?}", e),
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicatif::ProgressDrawTarget;
+
+    #[tokio::test]
+    async fn test_shuffled_fetches_never_move_the_bar_backwards() {
+        let progress = ProgressManager::with_draw_target(ProgressDrawTarget::hidden());
+        let pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
+        let callback = get_callback(pb_opt.clone(), &progress);
+
+        callback(GetEvent::Starting { total_chunks: 6 })
+            .await
+            .unwrap();
+
+        // Chunks complete out of order, the counts too as they cross the daemon.
+        let fetches = [(4, 1), (0, 3), (5, 2), (1, 4), (3, 6), (2, 5)];
+        let mut last_position = 0;
+        for (chunk_index, completed_count) in fetches {
+            callback(GetEvent::PadFetched {
+                chunk_index,
+                completed_count,
+            })
+            .await
+            .unwrap();
+
+            let position = pb_opt.lock().await.as_ref().unwrap().position();
+            assert!(position >= last_position);
+            assert!(position <= 6);
+            last_position = position;
         }
-    });
+        assert_eq!(last_position, 6);
+
+        callback(GetEvent::Complete).await.unwrap();
+        assert!(pb_opt.lock().await.is_none());
+    }
 }
@@ -1,30 +1,26 @@
-use super::progress::StyledProgressBar;
-use indicatif::MultiProgress;
+use super::progress::{ProgressManager, StyledProgressBar};
 use log::{error, warn};
 use mutant_client::ProgressReceiver;
 use mutant_protocol::{HealthCheckCallback, HealthCheckEvent, TaskProgress};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-pub fn create_health_check_progress(
-    mut progress_rx: ProgressReceiver,
-    multi_progress: &MultiProgress,
-) {
+pub fn create_health_check_progress(mut progress_rx: ProgressReceiver, progress: &ProgressManager) {
     let download_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
 
     let pb_clone = download_pb_opt.clone();
-    let mp_clone = multi_progress.clone();
+    let progress_clone = progress.clone();
 
     let callback: HealthCheckCallback = Arc::new(move |event: HealthCheckEvent| {
         let pb_arc = pb_clone.clone();
-        let multi_progress = mp_clone.clone();
+        let progress = progress_clone.clone();
 
         Box::pin(async move {
             match event {
                 HealthCheckEvent::Starting { total_keys } => {
                     let mut pb_guard = pb_arc.lock().await;
                     let _ = pb_guard.get_or_insert_with(|| {
-                        let pb = StyledProgressBar::new_for_steps(&multi_progress);
+                        let pb = StyledProgressBar::new_for_steps(&progress);
                         pb.set_message("Health check: Processing keys...".to_string());
                         pb.set_length(total_keys as u64);
                         pb.set_position(0);
@@ -1,40 +1,164 @@
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use humansize::{format_size, BINARY};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-/// A simple wrapper for MultiProgress
-/// This replaces the ProgressWithDisabledStdin from the removed terminal module
-pub struct ProgressWrapper {
+/// Longest time without a plain progress line while the progress moves by less than a
+/// tenth, see [`ProgressManager::plain`].
+const PLAIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Owns the progress bars of the CLI and the terminal they are drawn on.
+///
+/// Clones share the same bars. Anything printed while bars may be drawn goes through
+/// [`ProgressManager::println`] or [`ProgressManager::suspend`], which hide the bars
+/// for the duration of the print so that lines never end up mixed with them.
+#[derive(Clone)]
+pub struct ProgressManager {
     multi_progress: MultiProgress,
+    bars: Arc<Mutex<Vec<ProgressBar>>>,
+    /// The progress is printed as lines instead of drawn, see [`Self::plain`].
+    plain: bool,
+    /// What the plain lines are about, see [`Self::set_label`].
+    label: Arc<Mutex<Option<String>>>,
 }
 
-impl ProgressWrapper {
-    /// Create a new MultiProgress wrapper
+impl ProgressManager {
+    /// Draws the bars on stderr, or nowhere if it is not a terminal.
     pub fn new() -> Self {
+        Self::with_draw_target(ProgressDrawTarget::stderr())
+    }
+
+    /// Draws the bars on stderr even when it is not a terminal, for tools reading the
+    /// output that handle the escape sequences.
+    pub fn always() -> Self {
+        Self::with_draw_target(ProgressDrawTarget::term_like(Box::new(
+            console::Term::stderr(),
+        )))
+    }
+
+    /// Keeps the bars off screen, for `--quiet` and machine readable output.
+    pub fn hidden() -> Self {
+        Self::with_draw_target(ProgressDrawTarget::hidden())
+    }
+
+    /// Prints the progress of each bar as lines on stderr instead of drawing it, for
+    /// logs: when it moved by a tenth, or at most every [`PLAIN_INTERVAL`] otherwise,
+    /// and once more when the bar is finished.
+    pub fn plain() -> Self {
+        Self {
+            plain: true,
+            ..Self::hidden()
+        }
+    }
+
+    pub fn with_draw_target(target: ProgressDrawTarget) -> Self {
         Self {
-            multi_progress: MultiProgress::new(),
+            multi_progress: MultiProgress::with_draw_target(target),
+            bars: Arc::new(Mutex::new(Vec::new())),
+            plain: false,
+            label: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Names what the bars added from now on are about, e.g. the key being stored.
+    /// Plain lines start with it, bars are not affected.
+    pub fn set_label(&self, label: impl Into<String>) {
+        *self.label.lock().unwrap() = Some(label.into());
+    }
+
+    /// Adds a bar below the current ones. It is finished when the [`ProgressGuard`]
+    /// of this manager is dropped, if it was not before.
+    pub fn add(&self, bar: ProgressBar) -> ProgressBar {
+        let bar = self.multi_progress.add(bar);
+        self.bars.lock().unwrap().push(bar.clone());
+        bar
+    }
+
+    /// Hides the bars while `f` runs, then draws them again.
+    pub fn suspend<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        self.multi_progress.suspend(f)
+    }
+
+    /// Prints a line on stdout above the bars.
+    pub fn println(&self, line: impl Display) {
+        self.suspend(|| println!("{}", line));
+    }
+
+    /// Prints a line on stderr above the bars.
+    pub fn eprintln(&self, line: impl Display) {
+        self.suspend(|| eprintln!("{}", line));
+    }
+
+    /// Removes the bars from the terminal, they are drawn again on their next update.
+    pub fn clear(&self) {
+        self.multi_progress.clear().unwrap_or_default();
+    }
+
+    /// Returns a guard that finishes every bar of this manager when dropped.
+    pub fn guard(&self) -> ProgressGuard {
+        ProgressGuard {
+            progress: self.clone(),
         }
     }
 
-    /// Get a reference to the underlying MultiProgress
-    pub fn multi_progress(&self) -> &MultiProgress {
-        &self.multi_progress
+    /// Clears the bars that are still running, or leaves them in place while
+    /// panicking so that the terminal shows where things stopped.
+    fn finish(&self) {
+        let bars = std::mem::take(&mut *self.bars.lock().unwrap_or_else(|e| e.into_inner()));
+        for bar in bars.iter().filter(|bar| !bar.is_finished()) {
+            if std::thread::panicking() {
+                bar.abandon();
+            } else {
+                bar.finish_and_clear();
+            }
+        }
+        if !std::thread::panicking() {
+            self.clear();
+        }
     }
 }
 
-impl std::ops::Deref for ProgressWrapper {
-    type Target = MultiProgress;
+impl Default for ProgressManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finishes the bars of a [`ProgressManager`] on drop, so that returning early or
+/// panicking never leaves a bar ticking over the output.
+pub struct ProgressGuard {
+    progress: ProgressManager,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.multi_progress
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        self.progress.finish();
     }
 }
 
 pub struct StyledProgressBar {
     progress_bar: ProgressBar,
+    /// Set when the progress manager prints plain lines.
+    plain: Option<PlainReport>,
 }
 
 impl StyledProgressBar {
-    pub fn new_for_steps(multi_progress: &MultiProgress) -> Self {
-        let pb = multi_progress.add(ProgressBar::new(100));
+    fn styled(progress: &ProgressManager, progress_bar: ProgressBar, in_bytes: bool) -> Self {
+        let plain = progress.plain.then(|| PlainReport {
+            label: progress.label.lock().unwrap().clone(),
+            in_bytes,
+            started: Instant::now(),
+            state: Mutex::new(PlainState::default()),
+        });
+        Self {
+            progress_bar,
+            plain,
+        }
+    }
+
+    pub fn new_for_steps(progress: &ProgressManager) -> Self {
+        let pb = progress.add(ProgressBar::new(100));
         pb.enable_steady_tick(std::time::Duration::from_millis(100));
         pb.set_style(
             ProgressStyle::default_bar()
@@ -44,7 +168,22 @@ impl StyledProgressBar {
                 .unwrap()
                 .progress_chars("#>-"),
         );
-        Self { progress_bar: pb }
+        Self::styled(progress, pb, false)
+    }
+
+    /// A bar counting bytes, for progress over several values of different sizes.
+    pub fn new_for_bytes(progress: &ProgressManager) -> Self {
+        let pb = progress.add(ProgressBar::new(0));
+        pb.enable_steady_tick(std::time::Duration::from_millis(100));
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}",
+                )
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        Self::styled(progress, pb, true)
     }
 
     pub fn set_message(&self, msg: String) {
@@ -53,10 +192,12 @@ impl StyledProgressBar {
 
     pub fn set_length(&self, len: u64) {
         self.progress_bar.set_length(len);
+        self.report_plain(false);
     }
 
     pub fn set_position(&self, pos: u64) {
         self.progress_bar.set_position(pos);
+        self.report_plain(false);
     }
 
     pub fn position(&self) -> u64 {
@@ -67,8 +208,17 @@ impl StyledProgressBar {
         self.progress_bar.length()
     }
 
+    /// Moves to `completed` items, never backwards: completions can arrive out of order.
+    pub fn advance_to(&self, completed: u64) {
+        if completed > self.progress_bar.position() {
+            self.progress_bar.set_position(completed);
+            self.report_plain(false);
+        }
+    }
+
     pub fn inc(&self, delta: u64) {
         self.progress_bar.inc(delta);
+        self.report_plain(false);
     }
 
     pub fn is_finished(&self) -> bool {
@@ -76,6 +226,179 @@ impl StyledProgressBar {
     }
 
     pub fn finish_and_clear(&self) {
+        self.report_plain(true);
         self.progress_bar.finish_and_clear();
     }
+
+    fn report_plain(&self, finished: bool) {
+        let Some(plain) = &self.plain else {
+            return;
+        };
+        let line = plain.next_line(
+            self.progress_bar.position(),
+            self.progress_bar.length().unwrap_or(0),
+            &self.progress_bar.message(),
+            finished,
+        );
+        if let Some(line) = line {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+/// The lines printed for a bar of a plain [`ProgressManager`].
+struct PlainReport {
+    label: Option<String>,
+    in_bytes: bool,
+    started: Instant,
+    state: Mutex<PlainState>,
+}
+
+#[derive(Default)]
+struct PlainState {
+    /// When the last line was printed, with the tenth and the position it showed.
+    last: Option<(Instant, u64, u64)>,
+    finished: bool,
+}
+
+impl PlainReport {
+    /// The line to print for the bar at `position` of `length`, if one is due.
+    fn next_line(
+        &self,
+        position: u64,
+        length: u64,
+        message: &str,
+        finished: bool,
+    ) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        if state.finished {
+            return None;
+        }
+        let percent = (position.min(length) * 100)
+            .checked_div(length)
+            .unwrap_or(0);
+        let due = finished
+            || match state.last {
+                // Nothing to show before the length is known.
+                None => length > 0,
+                Some((at, last_percent, last_position)) => {
+                    percent / 10 > last_percent / 10
+                        || (position != last_position && at.elapsed() >= PLAIN_INTERVAL)
+                }
+            };
+        if !due {
+            return None;
+        }
+        state.last = Some((Instant::now(), percent, position));
+        state.finished = finished;
+
+        let amount = |n: u64| {
+            if self.in_bytes {
+                format_size(n, BINARY)
+            } else {
+                n.to_string()
+            }
+        };
+        let mut line = format!(
+            "{} {}/{} ({}%)",
+            message,
+            amount(position),
+            amount(length),
+            percent
+        );
+        if let Some(label) = &self.label {
+            line = format!("{}: {}", label, line);
+        }
+        if finished {
+            line = format!(
+                "{}, done in {}",
+                line,
+                crate::utils::format_elapsed_time(self.started.elapsed())
+            );
+        }
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicatif::{InMemoryTerm, TermLike};
+
+    fn fake_tty() -> (InMemoryTerm, ProgressManager) {
+        let term = InMemoryTerm::new(10, 120);
+        let target = ProgressDrawTarget::term_like(Box::new(term.clone()));
+        (term, ProgressManager::with_draw_target(target))
+    }
+
+    #[test]
+    fn test_prints_do_not_interleave_with_bars() {
+        let (term, progress) = fake_tty();
+        let bar = StyledProgressBar::new_for_steps(&progress);
+        bar.set_length(10);
+        bar.set_message("Uploading pads...".to_string());
+        assert!(term.contents().contains("Uploading pads..."));
+
+        // The ticking bar cannot be drawn while the line is being written.
+        progress.suspend(|| {
+            assert!(!term.contents().contains("Uploading pads..."));
+            std::thread::sleep(std::time::Duration::from_millis(250));
+            assert!(!term.contents().contains("Uploading pads..."));
+            term.write_line("Warning: pad already existed").unwrap();
+        });
+
+        let contents = term.contents();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], "Warning: pad already existed");
+        assert!(lines[1].contains("Uploading pads..."));
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_plain_lines_every_tenth() {
+        let progress = ProgressManager::plain();
+        progress.set_label("photos/cat.jpg");
+        let bar = StyledProgressBar::new_for_steps(&progress);
+        let plain = bar.plain.as_ref().unwrap();
+
+        let lines: Vec<String> = (0..=200)
+            .filter_map(|position| plain.next_line(position, 200, "Uploading pads...", false))
+            .collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "photos/cat.jpg: Uploading pads... 0/200 (0%)");
+        assert_eq!(lines[1], "photos/cat.jpg: Uploading pads... 20/200 (10%)");
+        assert_eq!(
+            lines[10],
+            "photos/cat.jpg: Uploading pads... 200/200 (100%)"
+        );
+
+        let last = plain.next_line(200, 200, "Upload complete.", true).unwrap();
+        assert!(
+            last.starts_with("photos/cat.jpg: Upload complete. 200/200 (100%), done in"),
+            "{}",
+            last
+        );
+        assert!(plain
+            .next_line(200, 200, "Upload complete.", true)
+            .is_none());
+
+        // Bars only print lines with a plain manager.
+        let drawn = StyledProgressBar::new_for_steps(&ProgressManager::hidden());
+        assert!(drawn.plain.is_none());
+    }
+
+    #[test]
+    fn test_guard_finishes_bars_on_early_return() {
+        let (term, progress) = fake_tty();
+        let bar = {
+            let _guard = progress.guard();
+            let bar = StyledProgressBar::new_for_steps(&progress);
+            bar.set_message("Fetching pads...".to_string());
+            assert!(term.contents().contains("Fetching pads..."));
+            bar
+        };
+
+        assert!(bar.is_finished());
+        assert_eq!(term.contents().trim(), "");
+    }
 }
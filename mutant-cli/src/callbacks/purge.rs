@@ -1,27 +1,26 @@
-use super::progress::StyledProgressBar;
-use indicatif::MultiProgress;
+use super::progress::{ProgressManager, StyledProgressBar};
 use log::{error, trace, warn};
 use mutant_client::ProgressReceiver;
 use mutant_protocol::{PurgeCallback, PurgeEvent, TaskProgress};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-pub fn create_purge_progress(mut progress_rx: ProgressReceiver, multi_progress: &MultiProgress) {
+pub fn create_purge_progress(mut progress_rx: ProgressReceiver, progress: &ProgressManager) {
     let download_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
 
     let pb_clone = download_pb_opt.clone();
-    let mp_clone = multi_progress.clone();
+    let progress_clone = progress.clone();
 
     let callback: PurgeCallback = Arc::new(move |event: PurgeEvent| {
         let pb_arc = pb_clone.clone();
-        let multi_progress = mp_clone.clone();
+        let progress = progress_clone.clone();
 
         Box::pin(async move {
             match event {
                 PurgeEvent::Starting { total_count } => {
                     let mut pb_guard = pb_arc.lock().await;
                     let _ = pb_guard.get_or_insert_with(|| {
-                        let pb = StyledProgressBar::new_for_steps(&multi_progress);
+                        let pb = StyledProgressBar::new_for_steps(&progress);
                         pb.set_message("Purging pads...".to_string());
                         pb.set_length(total_count as u64);
                         pb.set_position(0);
@@ -60,10 +59,10 @@ pub fn create_purge_progress(mut progress_rx: ProgressReceiver, multi_progress:
                     }
                     drop(pb_guard);
 
-                    println!(
+                    progress.println(format!(
                         "Purge complete. Verified: {}, Discarded: {}",
                         verified_count, failed_count
-                    );
+                    ));
                 }
             }
             Ok(true)
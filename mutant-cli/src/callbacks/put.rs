@@ -1,9 +1,8 @@
-use super::progress::StyledProgressBar;
+use super::progress::{ProgressManager, StyledProgressBar};
 use colored::Colorize;
-use indicatif::MultiProgress;
 use log::{error, info, warn};
 use mutant_client::ProgressReceiver;
-use mutant_protocol::{PutCallback, PutEvent, TaskProgress};
+use mutant_protocol::{ProgressSnapshot, PutCallback, PutEvent, TaskProgress};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -12,10 +11,14 @@ struct PutCallbackContext {
     res_pb_opt: Arc<Mutex<Option<StyledProgressBar>>>,
     upload_pb_opt: Arc<Mutex<Option<StyledProgressBar>>>,
     confirm_pb_opt: Arc<Mutex<Option<StyledProgressBar>>>,
-    multi_progress: MultiProgress,
+    progress: ProgressManager,
     total_chunks: Arc<Mutex<usize>>,
     first_complete_seen: Arc<Mutex<bool>>,
     start_time: Arc<Mutex<std::time::Instant>>,
+    /// The events folded so far, to turn the chunks written into bytes.
+    snapshot: Arc<Mutex<ProgressSnapshot>>,
+    /// The upload bar counts bytes rather than chunks.
+    upload_in_bytes: bool,
 }
 
 impl PutCallbackContext {
@@ -42,8 +45,14 @@ impl PutCallbackContext {
     }
 }
 
+/// Renders the progress of a store. With the size of the value, `bytes_total`, the
+/// upload bar counts bytes instead of chunks.
 #[allow(clippy::type_complexity)]
-pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: MultiProgress) {
+pub fn create_put_progress(
+    mut progress_rx: ProgressReceiver,
+    bytes_total: Option<u64>,
+    progress: &ProgressManager,
+) {
     let res_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
     let upload_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
     let confirm_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
@@ -55,10 +64,12 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
         res_pb_opt: res_pb_opt.clone(),
         upload_pb_opt: upload_pb_opt.clone(),
         confirm_pb_opt: confirm_pb_opt.clone(),
-        multi_progress: multi_progress.clone(),
+        progress: progress.clone(),
         total_chunks: total_chunks_arc.clone(),
         first_complete_seen: first_complete_seen.clone(),
         start_time: start_time.clone(),
+        snapshot: Arc::new(Mutex::new(ProgressSnapshot::new(bytes_total.unwrap_or(0)))),
+        upload_in_bytes: bytes_total.is_some(),
     };
 
     let ctx_clone = context.clone();
@@ -67,6 +78,20 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
         let ctx = ctx_clone.clone();
 
         Box::pin(async move {
+            let snapshot = {
+                let mut snapshot = ctx.snapshot.lock().await;
+                snapshot.apply(&event);
+                *snapshot
+            };
+            // Where the upload bar stands for `written` chunks
+            let uploaded = |written: usize| {
+                if ctx.upload_in_bytes {
+                    snapshot.bytes_done
+                } else {
+                    written as u64
+                }
+            };
+
             match event {
                 PutEvent::Starting {
                     total_chunks,
@@ -85,7 +110,7 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
                         let mut res_pb_guard = ctx.res_pb_opt.lock().await;
                         let res_pb = res_pb_guard.get_or_insert_with(|| {
                             info!("Creating reservation progress bar");
-                            let pb = StyledProgressBar::new_for_steps(&ctx.multi_progress);
+                            let pb = StyledProgressBar::new_for_steps(&ctx.progress);
                             pb.set_message("Buying new pads...".to_string());
                             pb
                         });
@@ -98,19 +123,28 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
                     let mut upload_pb_guard = ctx.upload_pb_opt.lock().await;
                     let upload_pb = upload_pb_guard.get_or_insert_with(|| {
                         info!("Creating upload progress bar");
-                        let pb = StyledProgressBar::new_for_steps(&ctx.multi_progress);
+                        let pb = if ctx.upload_in_bytes {
+                            StyledProgressBar::new_for_bytes(&ctx.progress)
+                        } else {
+                            StyledProgressBar::new_for_steps(&ctx.progress)
+                        };
                         pb.set_message("Uploading pads...".to_string());
                         pb
                     });
-                    info!("Setting upload bar length to {}", total_u64);
-                    upload_pb.set_length(total_u64);
-                    upload_pb.set_position(initial_written_count as u64);
+                    let upload_length = if ctx.upload_in_bytes {
+                        snapshot.bytes_total
+                    } else {
+                        total_u64
+                    };
+                    info!("Setting upload bar length to {}", upload_length);
+                    upload_pb.set_length(upload_length);
+                    upload_pb.set_position(uploaded(initial_written_count));
                     drop(upload_pb_guard);
 
                     let mut confirm_pb_guard = ctx.confirm_pb_opt.lock().await;
                     let confirm_pb = confirm_pb_guard.get_or_insert_with(|| {
                         info!("Creating confirmation progress bar");
-                        let pb = StyledProgressBar::new_for_steps(&ctx.multi_progress);
+                        let pb = StyledProgressBar::new_for_steps(&ctx.progress);
                         pb.set_message("Confirming pads...".to_string());
                         pb
                     });
@@ -140,13 +174,19 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
                     drop(res_pb_guard);
                     Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
                 }
-                PutEvent::PadsWritten => {
-                    info!("Pads written event received");
+                PutEvent::PadsWritten {
+                    chunk_index,
+                    completed_count,
+                } => {
+                    info!(
+                        "Chunk {} written, {} written so far",
+                        chunk_index, completed_count
+                    );
                     let mut upload_pb_guard = ctx.upload_pb_opt.lock().await;
                     if let Some(pb) = upload_pb_guard.as_mut() {
                         if !pb.is_finished() {
-                            info!("Incrementing upload bar");
-                            pb.inc(1);
+                            info!("Advancing upload bar");
+                            pb.advance_to(uploaded(completed_count));
                         }
                     } else {
                         warn!("PadsWritten event but upload bar doesn't exist");
@@ -154,13 +194,19 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
                     drop(upload_pb_guard);
                     Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
                 }
-                PutEvent::PadsConfirmed => {
-                    info!("Pads confirmed event received");
+                PutEvent::PadsConfirmed {
+                    chunk_index,
+                    completed_count,
+                } => {
+                    info!(
+                        "Chunk {} confirmed, {} confirmed so far",
+                        chunk_index, completed_count
+                    );
                     let mut confirm_pb_guard = ctx.confirm_pb_opt.lock().await;
                     if let Some(pb) = confirm_pb_guard.as_mut() {
                         if !pb.is_finished() {
-                            info!("Incrementing confirmation bar");
-                            pb.inc(1);
+                            info!("Advancing confirmation bar");
+                            pb.advance_to(completed_count as u64);
                         }
                     } else {
                         warn!("PadsConfirmed event but confirmation bar doesn't exist");
@@ -168,6 +214,54 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
                     drop(confirm_pb_guard);
                     Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
                 }
+                PutEvent::CreateFellBackToUpdate { address } => {
+                    warn!("Pad {} already existed, it was updated instead of created", address);
+                    ctx.progress.eprintln(format!(
+                        "{} pad {} already existed on the network and was overwritten",
+                        "Warning:".bright_yellow(),
+                        address
+                    ));
+                    Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
+                }
+                PutEvent::UpdatePlanned {
+                    chunks_unchanged,
+                    chunks_rewritten,
+                } => {
+                    info!(
+                        "Update keeps {} chunks, rewrites {}",
+                        chunks_unchanged, chunks_rewritten
+                    );
+                    Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
+                }
+                PutEvent::ChunkWriteRetried {
+                    chunk_index,
+                    attempt,
+                } => {
+                    info!(
+                        "Retrying write of chunk {} (attempt {})",
+                        chunk_index, attempt
+                    );
+                    Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
+                }
+                PutEvent::Resuming {
+                    confirmed_chunks,
+                    total_chunks,
+                } => {
+                    info!(
+                        "Resuming with {} of {} chunks confirmed",
+                        confirmed_chunks, total_chunks
+                    );
+                    ctx.progress.println(format!(
+                        "{} Resuming interrupted upload at {}%",
+                        "•".bright_green(),
+                        confirmed_chunks * 100 / total_chunks.max(1)
+                    ));
+                    Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
+                }
+                PutEvent::KeyStarted { key, total_chunks } => {
+                    info!("Storing key '{}' ({} chunks)", key, total_chunks);
+                    Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true)
+                }
                 PutEvent::Complete => {
                     // Check if this is the first or second Complete event
                     let mut first_complete_seen_guard = ctx.first_complete_seen.lock().await;
@@ -190,7 +284,7 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
                         ctx.finish_progress_bar(&ctx.confirm_pb_opt, "Confirmation complete.", "confirmation").await;
 
                         // Ensure all progress bars are cleared
-                        crate::utils::ensure_progress_cleared(&ctx.multi_progress);
+                        ctx.progress.clear();
 
                         // Calculate elapsed time
                         let start_time = *ctx.start_time.lock().await;
@@ -201,10 +295,12 @@ pub fn create_put_progress(mut progress_rx: ProgressReceiver, multi_progress: Mu
 
                         // Display message about data pads being uploaded
                         let total_chunks = *ctx.total_chunks.lock().await;
-                        println!("{} {} data pads have been uploaded (took {})",
+                        ctx.progress.println(format!(
+                            "{} {} data pads have been uploaded (took {})",
                             "•".bright_green(),
                             total_chunks,
-                            time_str);
+                            time_str
+                        ));
 
                         return Ok::<bool, Box<dyn std::error::Error + Send + Sync>>(true);
                     }
@@ -1,27 +1,26 @@
-use super::progress::StyledProgressBar;
-use indicatif::MultiProgress;
+use super::progress::{ProgressManager, StyledProgressBar};
 use log::{error, trace, warn};
 use mutant_client::ProgressReceiver;
 use mutant_protocol::{SyncCallback, SyncEvent, TaskProgress};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-pub fn create_sync_progress(mut progress_rx: ProgressReceiver, multi_progress: &MultiProgress) {
+pub fn create_sync_progress(mut progress_rx: ProgressReceiver, progress: &ProgressManager) {
     let download_pb_opt = Arc::new(Mutex::new(None::<StyledProgressBar>));
 
     let pb_clone = download_pb_opt.clone();
-    let mp_clone = multi_progress.clone();
+    let progress_clone = progress.clone();
 
     let callback: SyncCallback = Arc::new(move |event: SyncEvent| {
         let pb_arc = pb_clone.clone();
-        let multi_progress = mp_clone.clone();
+        let progress = progress_clone.clone();
 
         Box::pin(async move {
             match event {
                 SyncEvent::FetchingRemoteIndex => {
                     let mut pb_guard = pb_arc.lock().await;
                     let _ = pb_guard.get_or_insert_with(|| {
-                        let pb = StyledProgressBar::new_for_steps(&multi_progress);
+                        let pb = StyledProgressBar::new_for_steps(&progress);
                         pb.set_message("Fetching remote index...".to_string());
                         pb.set_length(4);
                         pb.set_position(0);
@@ -61,7 +60,7 @@ pub fn create_sync_progress(mut progress_rx: ProgressReceiver, multi_progress: &
                     let mut pb_guard = pb_arc.lock().await;
                     if let Some(pb) = pb_guard.as_mut() {
                         if !pb.is_finished() {
-                            pb.set_message("Verifying remote index...".to_string());
+                            pb.set_message("Verifying remote index checksum...".to_string());
                             pb.inc(1);
                         }
                     } else {
@@ -1,6 +1,8 @@
+use crate::lock::LockMode;
 use clap::Parser;
 use clap::ValueEnum;
 use mutant_protocol::StorageMode;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -9,6 +11,32 @@ pub struct Cli {
     pub command: Option<Commands>,
     #[arg(short, long)]
     pub quiet: bool,
+    #[arg(long, global = true, help = "Print reports as JSON")]
+    pub json: bool,
+    #[arg(long, global = true, help = "Disable colored output")]
+    pub no_color: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ProgressMode::Auto,
+        help = "How progress is shown on stderr"
+    )]
+    pub progress: ProgressMode,
+    #[arg(
+        long,
+        global = true,
+        help = "Fail instead of waiting when another mutant process is using the local cache"
+    )]
+    pub no_wait: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Pads read or written at once by the daemon this command starts"
+    )]
+    pub concurrency: Option<u64>,
 }
 
 #[derive(clap::Subcommand)]
@@ -16,7 +44,15 @@ pub enum Commands {
     #[command(about = "Store a value associated with a key")]
     Put {
         key: String,
-        file: String,
+        #[arg(required_unless_present = "from_file", help = "File to store")]
+        file: Option<String>,
+        #[arg(
+            long = "file",
+            value_name = "PATH",
+            conflicts_with = "file",
+            help = "File to store, in place of the positional argument"
+        )]
+        from_file: Option<String>,
         #[arg(short, long)]
         public: bool,
         #[arg(value_enum, short, long, default_value_t = StorageModeCli::Heaviest)]
@@ -25,30 +61,207 @@ pub enum Commands {
         background: bool,
         #[arg(short, long)]
         no_verify: bool,
+        #[arg(
+            long,
+            conflicts_with = "background",
+            help = "Print the pads the store would reuse and create and their rough cost, without writing anything"
+        )]
+        dry_run: bool,
+    },
+    #[command(about = "Store many values at once, the index being saved a single time")]
+    PutMany {
+        #[arg(help = "File listing the values to store, one `key<TAB>file` per line")]
+        manifest: PathBuf,
+        #[arg(value_enum, short, long, default_value_t = StorageModeCli::Heaviest)]
+        mode: StorageModeCli,
+        #[arg(
+            long,
+            help = "Stop at the first failure instead of storing the remaining keys"
+        )]
+        fail_fast: bool,
     },
     #[command(about = "Retrieve a value associated with a key")]
     Get {
         key: String,
-        destination_path: String,
+        #[arg(required_unless_present = "output", help = "File the value is written to")]
+        destination_path: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            conflicts_with = "destination_path",
+            help = "File the value is written to, in place of the positional argument"
+        )]
+        output: Option<PathBuf>,
+        #[arg(long, help = "Create the missing parent directories of the file")]
+        parents: bool,
+        #[arg(short, long, help = "Overwrite the file if it exists")]
+        force: bool,
         #[arg(short, long)]
         background: bool,
         #[arg(short, long)]
         public: bool,
+        #[arg(
+            long,
+            requires = "public",
+            help = "Resolve the key by its name in this public catalogue, no wallet needed"
+        )]
+        index_file: Option<PathBuf>,
+    },
+    #[command(about = "Fetch every key matching a pattern into a directory")]
+    GetMany {
+        #[arg(
+            help = "Keys to fetch, `*` standing for any run of characters, or `-` to read their names from stdin"
+        )]
+        keys: String,
+        #[arg(
+            short,
+            long,
+            help = "Directory the values are written to, each `/` of a key name starting a subdirectory"
+        )]
+        dir: PathBuf,
+        #[arg(short, long, default_value_t = 4, help = "Keys fetched at the same time")]
+        jobs: usize,
+        #[arg(long, help = "Overwrite the files that already exist")]
+        force: bool,
+        #[arg(long, help = "Stop at the first failure instead of fetching the remaining keys")]
+        fail_fast: bool,
+    },
+    #[command(about = "Remove a key-value pair, or unlink an alias")]
+    Rm {
+        key: String,
+        #[arg(long, help = "If the key has aliases, hand it over to its first alias")]
+        promote_alias: bool,
+    },
+    #[command(about = "Make another name resolve to an existing key")]
+    Alias { key: String, alias: String },
+    #[command(about = "Rename a key without uploading its data again")]
+    Mv { old_key: String, new_key: String },
+    #[command(about = "Bring a removed key back from the trash")]
+    Restore { key: String },
+    #[command(about = "Rebuild the lost index entries of derived keys from their pads")]
+    Recover {
+        #[arg(long, help = "Key names to look for, one per line")]
+        keys_from: PathBuf,
     },
-    #[command(about = "Remove a key-value pair")]
-    Rm { key: String },
     #[command(about = "List stored keys")]
     Ls {
+        #[arg(
+            conflicts_with = "remote",
+            help = "Only list the keys starting with this prefix, like backups/"
+        )]
+        prefix: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["trash", "remote"],
+            help = "Collapse the keys holding this delimiter past the prefix into one entry per level, like backups/ for /"
+        )]
+        delimiter: Option<String>,
+        #[arg(short, long, help = "Show the aliases of each key")]
+        long: bool,
         #[arg(long, help = "Show fetch history")]
         history: bool,
+        #[arg(short, long, help = "Only list the keys of this collection")]
+        collection: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "collection",
+            help = "List the removed keys that can still be restored"
+        )]
+        trash: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["collection", "trash"],
+            help = "List the removed keys that can still be restored along with the live ones"
+        )]
+        include_trash: bool,
+        #[arg(
+            value_enum,
+            long,
+            default_value_t = LsSort::Name,
+            help = "Order of the keys, ties are listed by name so the output can be diffed"
+        )]
+        sort: LsSort,
+        #[arg(
+            long,
+            conflicts_with_all = ["collection", "trash", "include_trash"],
+            help = "List the keys of this public catalogue instead of the store"
+        )]
+        index_file: Option<PathBuf>,
+        #[arg(
+            long,
+            conflicts_with_all = ["collection", "trash", "include_trash", "index_file"],
+            help = "List the keys of the remote index without syncing, marking which ones the local index has"
+        )]
+        remote: bool,
     },
+    #[command(about = "Show the listing details of a single key")]
+    Stat { key: String },
     #[command(about = "Show storage statistics")]
-    Stats,
+    Stats {
+        #[arg(
+            long,
+            help = "Show the network calls made since the daemon started instead, it must run with MUTANT_IO_ACCOUNTING=1"
+        )]
+        io: bool,
+    },
+    #[command(
+        about = "Measure upload and download throughput by storing, fetching and removing a random payload"
+    )]
+    Bench {
+        #[arg(short, long, default_value = "64M", help = "Payload size, e.g. 512K, 64M or 1G")]
+        size: String,
+        #[arg(value_enum, short, long, default_value_t = StorageModeCli::Heaviest)]
+        mode: StorageModeCli,
+        #[arg(short, long, help = "Keep the benchmark key instead of removing it")]
+        keep: bool,
+    },
+    #[command(
+        about = "Store, fetch, update and remove a throwaway value, reporting each stage with hints (exit code 1 if any fails)"
+    )]
+    Selftest {
+        #[arg(
+            long,
+            help = "Fail unless the daemon runs on the local devnet (mutant-daemon --local)"
+        )]
+        local: bool,
+    },
+    #[command(
+        about = "Check whether a scratchpad exists on the network (exit code 0 if it does, 1 otherwise)"
+    )]
+    PadExists {
+        #[arg(help = "Hex encoded scratchpad address")]
+        address: String,
+    },
+    #[command(
+        about = "Fetch every chunk of a key and check it against its checksum (exit code 1 if any is damaged)"
+    )]
+    Verify { key: String },
+    #[command(about = "Manage named groups of keys")]
+    Collection {
+        #[command(subcommand)]
+        command: CollectionCommands,
+    },
+    #[command(about = "Store a directory tree as keys and compare it with what was stored")]
+    Mirror {
+        #[command(subcommand)]
+        command: MirrorCommands,
+    },
+    #[command(about = "Read the audit log of mutating operations")]
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
     #[command(about = "Manage background tasks")]
     Tasks {
         #[command(subcommand)]
         command: TasksCommands,
     },
+    #[command(about = "Manage the local index caches")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
     #[command(about = "Manage the daemon")]
     Daemon {
         #[command(subcommand)]
@@ -56,10 +269,29 @@ pub enum Commands {
     },
     #[command(about = "Synchronize local index cache with remote storage")]
     Sync {
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "only")]
         push_force: bool,
         #[arg(short, long)]
         background: bool,
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            help = "Only sync the keys matching this pattern, `*` matching any characters. Can be repeated"
+        )]
+        only: Vec<String>,
+        #[arg(
+            long,
+            requires = "only",
+            conflicts_with = "pull",
+            help = "Only merge the selected keys into the remote index"
+        )]
+        push: bool,
+        #[arg(
+            long,
+            requires = "only",
+            help = "Only merge the selected keys into the local index"
+        )]
+        pull: bool,
     },
     #[command(
         about = "Perform a get check on scratchpads that should have been created but failed at some point. Removes the pads that are not found."
@@ -73,7 +305,14 @@ pub enum Commands {
     #[command(about = "Import scratchpad private key from a file")]
     Import { file_path: String },
     #[command(about = "Export all scratchpad private key to a file")]
-    Export { destination_path: String },
+    Export {
+        destination_path: String,
+        #[arg(
+            long,
+            help = "Export the public keys, without any secret key, as a catalogue for --index-file"
+        )]
+        public_catalogue: bool,
+    },
     #[command(
         about = "Perform a health check on scratchpads that should have been created but cannot be retrieved. Recycles the pads that are not found."
     )]
@@ -86,6 +325,31 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// How the command uses the local index cache, `None` when it leaves it alone.
+    pub fn lock_mode(&self) -> Option<LockMode> {
+        match self {
+            Commands::Put { dry_run: true, .. }
+            | Commands::Get { .. }
+            | Commands::GetMany { .. }
+            | Commands::Ls { .. }
+            | Commands::Stat { .. }
+            | Commands::Stats { .. }
+            | Commands::PadExists { .. }
+            | Commands::Verify { .. }
+            | Commands::Export { .. }
+            | Commands::Mirror {
+                command: MirrorCommands::Check { .. },
+            }
+            | Commands::Cache {
+                command: CacheCommands::Clean { dry_run: true },
+            } => Some(LockMode::Shared),
+            Commands::Audit { .. } | Commands::Tasks { .. } | Commands::Daemon { .. } => None,
+            _ => Some(LockMode::Exclusive),
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum StorageModeCli {
     /// 0.5 MB per scratchpad
@@ -100,6 +364,27 @@ pub enum StorageModeCli {
     Heaviest,
 }
 
+/// How the progress of long operations is shown, see `--progress`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum ProgressMode {
+    /// Bars on a terminal, plain lines when stderr is not one
+    Auto,
+    /// Bars even when stderr is not a terminal
+    Always,
+    /// A line when the progress moved by a tenth or every few seconds, for logs
+    Plain,
+    /// No progress at all
+    Never,
+}
+
+/// Order of the keys listed by `ls`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum LsSort {
+    Name,
+    /// Most recently fetched first, needs `MUTANT_TRACK_ACCESS` on the daemon
+    LastAccess,
+}
+
 impl From<StorageModeCli> for StorageMode {
     fn from(mode: StorageModeCli) -> Self {
         match mode {
@@ -120,6 +405,72 @@ pub enum TasksCommands {
     Get { task_id: String },
     #[command(about = "Stop a background task")]
     Stop { task_id: String },
+    #[command(about = "List the tasks the daemon finished lately")]
+    History {
+        #[arg(short, long, help = "Show at most this many tasks")]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(clap::Subcommand)]
+pub enum CollectionCommands {
+    #[command(about = "Add keys to a collection, creating it if needed")]
+    Add {
+        name: String,
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    #[command(about = "Remove a collection")]
+    Rm {
+        name: String,
+        #[arg(long, help = "Also remove the keys of the collection")]
+        delete_keys: bool,
+    },
+}
+
+#[derive(clap::Subcommand)]
+pub enum MirrorCommands {
+    #[command(
+        about = "Store each file of a directory as a private key under a prefix, skipping the unchanged ones"
+    )]
+    Push {
+        dir: PathBuf,
+        #[arg(help = "Keys are named <prefix>/<path relative to the directory>")]
+        prefix: String,
+    },
+    #[command(
+        about = "Compare a directory with the keys mirrored from it, from the hashes in the index (exit code 1 if they differ)"
+    )]
+    Check { dir: PathBuf, prefix: String },
+}
+
+#[derive(clap::Subcommand)]
+pub enum AuditCommands {
+    #[command(about = "Show the latest audit records")]
+    Tail {
+        #[arg(short = 'n', long, default_value_t = 20, help = "Number of records to show")]
+        lines: usize,
+        #[arg(long, help = "Audit log to read instead of MUTANT_AUDIT_LOG")]
+        file: Option<PathBuf>,
+    },
+    #[command(about = "Show the audit records of a key")]
+    Search {
+        #[arg(short, long)]
+        key: String,
+        #[arg(long, help = "Audit log to read instead of MUTANT_AUDIT_LOG")]
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::Subcommand)]
+pub enum CacheCommands {
+    #[command(
+        about = "Remove the index caches of other networks and app ids past MUTANT_CACHE_MAX_BYTES or MUTANT_CACHE_MAX_AGE_DAYS"
+    )]
+    Clean {
+        #[arg(long, help = "Only list what would be removed")]
+        dry_run: bool,
+    },
 }
 
 #[derive(clap::Subcommand)]
@@ -0,0 +1,19 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::connect_to_daemon;
+use anyhow::Result;
+use colored::Colorize;
+
+pub async fn handle_alias(key: String, alias: String, progress: &ProgressManager) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    let response = client.alias(&key, &alias).await?;
+
+    progress.println(format!(
+        "{} '{}' now resolves to '{}'.",
+        "•".bright_green(),
+        response.alias,
+        response.key
+    ));
+
+    Ok(())
+}
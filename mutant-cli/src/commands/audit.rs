@@ -0,0 +1,129 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::cli::AuditCommands;
+use crate::report::{render_json, OutputFormat};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use humansize::{format_size, BINARY};
+use log::warn;
+use mutant_protocol::AuditRecord;
+use std::path::{Path, PathBuf};
+
+pub async fn handle_audit(
+    command: AuditCommands,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let records = match command {
+        AuditCommands::Tail { lines, file } => {
+            let mut records = read_records(&audit_log_path(file)?)?;
+            records.drain(..records.len().saturating_sub(lines));
+            records
+        }
+        AuditCommands::Search { key, file } => read_records(&audit_log_path(file)?)?
+            .into_iter()
+            .filter(|record| record.key.as_deref() == Some(key.as_str()))
+            .collect(),
+    };
+
+    if output.json {
+        progress.println(render_json(&records)?);
+    } else if records.is_empty() {
+        progress.println("No audit records.");
+    } else {
+        records
+            .iter()
+            .for_each(|record| print_record(record, progress));
+    }
+
+    Ok(())
+}
+
+/// The log written by the daemon, unless `file` points elsewhere.
+fn audit_log_path(file: Option<PathBuf>) -> Result<PathBuf> {
+    file.or_else(|| std::env::var_os("MUTANT_AUDIT_LOG").map(PathBuf::from))
+        .ok_or_else(|| anyhow!("No audit log configured, set MUTANT_AUDIT_LOG or pass --file"))
+}
+
+/// Reads every record of the log, skipping the lines that cannot be parsed.
+fn read_records(path: &Path) -> Result<Vec<AuditRecord>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read audit log {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!("Skipping malformed audit record: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+fn print_record(record: &AuditRecord, progress: &ProgressManager) {
+    let timestamp = DateTime::from_timestamp_millis(record.timestamp_ms as i64)
+        .map(|t| {
+            t.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let operation = format!("{:?}", record.operation).to_lowercase();
+    let outcome = match &record.error {
+        None => "ok".bright_green(),
+        Some(error) => error.bright_red(),
+    };
+
+    let mut details = Vec::new();
+    if let Some(size) = record.size_bytes {
+        details.push(format_size(size as u64, BINARY));
+    }
+    if let Some(pads) = record.pad_count {
+        details.push(format!("{} pads", pads));
+    }
+    details.push(format!("{}ms", record.duration_ms));
+
+    progress.println(format!(
+        "{} {} {} {} ({}) {}",
+        "•".bright_green(),
+        timestamp,
+        format!("{:<7}", operation).bright_blue(),
+        record.key.as_deref().unwrap_or("-"),
+        details.join(", "),
+        outcome
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mutant_protocol::AuditOperation;
+
+    #[test]
+    fn test_read_records_skips_malformed_lines() {
+        let record = AuditRecord {
+            timestamp_ms: 1_700_000_000_000,
+            owner: "owner".to_string(),
+            operation: AuditOperation::Store,
+            key: Some("foo".to_string()),
+            size_bytes: Some(42),
+            pad_count: Some(1),
+            error: None,
+            duration_ms: 12,
+        };
+        let path = std::env::temp_dir().join(format!("mutant-audit-{}.log", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            format!("{}\nnot json\n\n", serde_json::to_string(&record).unwrap()),
+        )
+        .unwrap();
+
+        let records = read_records(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.unwrap(), vec![record]);
+    }
+}
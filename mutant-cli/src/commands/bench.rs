@@ -0,0 +1,282 @@
+use crate::connect_to_daemon;
+use crate::report::{bench_report, OutputFormat};
+use anyhow::{anyhow, bail, Result};
+use mutant_client::{error::ClientError, ProgressReceiver};
+use mutant_protocol::{GetEvent, PutEvent, StorageMode, TaskProgress, TaskResult};
+use serde::Serialize;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// Outcome of a `mutant bench` run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub size_bytes: usize,
+    pub total_chunks: usize,
+    pub new_pads: usize,
+    pub reused_pads: usize,
+    pub upload_secs: f64,
+    pub upload_bytes_per_sec: f64,
+    pub upload_chunk_latency_ms: Percentiles,
+    pub download_secs: f64,
+    pub download_bytes_per_sec: f64,
+    pub download_chunk_latency_ms: Percentiles,
+    pub verified: bool,
+    pub kept_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Percentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+impl Percentiles {
+    /// Computes the percentiles of the intervals between consecutive chunk events,
+    /// starting from `start`. With several chunks in flight this is the time between
+    /// two chunks completing, which is what bounds the throughput.
+    fn from_events(start: Instant, events: &[Instant]) -> Self {
+        let mut previous = start;
+        let mut intervals: Vec<u64> = events
+            .iter()
+            .map(|event| {
+                let interval = event.saturating_duration_since(previous).as_millis() as u64;
+                previous = *event;
+                interval
+            })
+            .collect();
+        intervals.sort_unstable();
+
+        Self {
+            p50: percentile(&intervals, 50),
+            p90: percentile(&intervals, 90),
+            p99: percentile(&intervals, 99),
+        }
+    }
+}
+
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Parses sizes such as `512`, `64K`, `64M` or `1G` (powers of 1024).
+pub fn parse_size(size: &str) -> Result<usize> {
+    let size = size.trim();
+    let (digits, multiplier) = match size.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&size[..size.len() - 1], 1024),
+        Some('M') => (&size[..size.len() - 1], 1024 * 1024),
+        Some('G') => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+
+    let value: usize = digits
+        .parse()
+        .map_err(|_| anyhow!("Invalid size '{}', expected e.g. 512K, 64M or 1G", size))?;
+    if value == 0 {
+        bail!("Benchmark size must be greater than zero");
+    }
+
+    Ok(value * multiplier)
+}
+
+/// Incompressible payload, so the numbers are not skewed by content.
+pub(crate) fn random_payload(size: usize) -> Vec<u8> {
+    let mut state = (uuid::Uuid::new_v4().as_u128() as u64) | 1;
+    let mut payload = Vec::with_capacity(size + 8);
+    while payload.len() < size {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        payload.extend_from_slice(&state.to_le_bytes());
+    }
+    payload.truncate(size);
+    payload
+}
+
+/// Records when each chunk event matching `is_chunk_event` comes through.
+fn track_chunk_events(
+    mut progress_rx: ProgressReceiver,
+    is_chunk_event: fn(&TaskProgress) -> bool,
+    starting: Arc<Mutex<Option<PutEvent>>>,
+) -> (Arc<Mutex<Vec<Instant>>>, JoinHandle<()>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_clone = events.clone();
+
+    let handle = tokio::spawn(async move {
+        while let Some(Ok(progress)) = progress_rx.recv().await {
+            if let TaskProgress::Put(event @ PutEvent::Starting { .. }) = &progress {
+                *starting.lock().unwrap() = Some(event.clone());
+            }
+            if is_chunk_event(&progress) {
+                events_clone.lock().unwrap().push(Instant::now());
+            }
+        }
+    });
+
+    (events, handle)
+}
+
+/// Runs a started task to its end and returns when each of its chunk events came
+/// through. Taking the task generically lets callers start it with any request.
+async fn run_tracked(
+    (start_task, progress_rx): (
+        impl Future<Output = Result<TaskResult, ClientError>>,
+        ProgressReceiver,
+    ),
+    is_chunk_event: fn(&TaskProgress) -> bool,
+    starting: Arc<Mutex<Option<PutEvent>>>,
+    operation: &str,
+) -> Result<Vec<Instant>> {
+    let (events, tracker) = track_chunk_events(progress_rx, is_chunk_event, starting);
+    let result = start_task.await;
+    tracker.abort();
+    task_error(result?, operation)?;
+
+    let events = events.lock().unwrap().clone();
+    Ok(events)
+}
+
+fn task_error(result: TaskResult, operation: &str) -> Result<()> {
+    match result {
+        TaskResult::Result(_) => Ok(()),
+        TaskResult::Error(error) => bail!("Benchmark {} failed: {}", operation, error),
+        TaskResult::Pending => bail!("Benchmark {} did not complete", operation),
+    }
+}
+
+pub async fn handle_bench(
+    size: String,
+    mode: StorageMode,
+    keep: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    let size_bytes = parse_size(&size)?;
+    let run_id = uuid::Uuid::new_v4().simple().to_string();
+    let key = format!("mutant-bench-{}", run_id);
+    let temp_dir = std::env::temp_dir();
+    let source_path: PathBuf = temp_dir.join(format!("{}.src", key));
+    let destination_path: PathBuf = temp_dir.join(format!("{}.dst", key));
+
+    let payload = random_payload(size_bytes);
+    std::fs::write(&source_path, &payload)?;
+
+    let result = run_bench(&key, &payload, &source_path, &destination_path, mode, keep).await;
+
+    std::fs::remove_file(&source_path).unwrap_or_default();
+    std::fs::remove_file(&destination_path).unwrap_or_default();
+
+    let result = result?;
+    output.print(&result, &bench_report(&result))?;
+
+    Ok(())
+}
+
+async fn run_bench(
+    key: &str,
+    payload: &[u8],
+    source_path: &Path,
+    destination_path: &Path,
+    mode: StorageMode,
+    keep: bool,
+) -> Result<BenchResult> {
+    let mut client = connect_to_daemon().await?;
+    let size_bytes = payload.len();
+
+    // Upload
+    let starting = Arc::new(Mutex::new(None));
+    let upload_start = Instant::now();
+    let source_path = source_path.to_string_lossy();
+    let pad_confirmed: fn(&TaskProgress) -> bool =
+        |p| matches!(p, TaskProgress::Put(PutEvent::PadsConfirmed { .. }));
+    let started = client.put(key, &source_path, mode, false, false).await?;
+    let upload_events = run_tracked(started, pad_confirmed, starting.clone(), "upload").await?;
+    let upload_elapsed = upload_start.elapsed();
+
+    // Download
+    let download_start = Instant::now();
+    let started = client
+        .get(key, &destination_path.to_string_lossy(), false, None)
+        .await?;
+    let download_events = run_tracked(
+        started,
+        |p| matches!(p, TaskProgress::Get(GetEvent::PadFetched { .. })),
+        Arc::new(Mutex::new(None)),
+        "download",
+    )
+    .await?;
+    let download_elapsed = download_start.elapsed();
+
+    let verified = std::fs::read(destination_path)
+        .map(|fetched| fetched == payload)
+        .unwrap_or(false);
+
+    if !keep {
+        client.rm(key).await?;
+    }
+
+    let (total_chunks, new_pads) = match *starting.lock().unwrap() {
+        Some(PutEvent::Starting {
+            total_chunks,
+            chunks_to_reserve,
+            ..
+        }) => (total_chunks, chunks_to_reserve),
+        _ => (0, 0),
+    };
+
+    Ok(BenchResult {
+        size_bytes,
+        total_chunks,
+        new_pads,
+        reused_pads: total_chunks.saturating_sub(new_pads),
+        upload_secs: upload_elapsed.as_secs_f64(),
+        upload_bytes_per_sec: throughput(size_bytes, upload_elapsed),
+        upload_chunk_latency_ms: Percentiles::from_events(upload_start, &upload_events),
+        download_secs: download_elapsed.as_secs_f64(),
+        download_bytes_per_sec: throughput(size_bytes, download_elapsed),
+        download_chunk_latency_ms: Percentiles::from_events(download_start, &download_events),
+        verified,
+        kept_key: keep.then(|| key.to_string()),
+    })
+}
+
+fn throughput(size_bytes: usize, elapsed: Duration) -> f64 {
+    size_bytes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("64K").unwrap(), 64 * 1024);
+        assert_eq!(parse_size("64m").unwrap(), 64 * 1024 * 1024);
+        assert_eq!(parse_size("1G").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let start = Instant::now();
+        let events: Vec<Instant> = (1..=10)
+            .map(|i| start + Duration::from_millis(i * 10))
+            .collect();
+
+        let percentiles = Percentiles::from_events(start, &events);
+
+        assert_eq!(percentiles.p50, 10);
+        assert_eq!(percentiles.p99, 10);
+        assert_eq!(percentile(&[], 50), 0);
+        assert_eq!(percentile(&[1, 2, 3, 4], 50), 2);
+        assert_eq!(percentile(&[1, 2, 3, 4], 90), 4);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::{cli::CacheCommands, connect_to_daemon};
+use anyhow::Result;
+use colored::Colorize;
+use humansize::{format_size, BINARY};
+
+pub async fn handle_cache(command: CacheCommands, progress: &ProgressManager) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    match command {
+        CacheCommands::Clean { dry_run } => {
+            let report = client.clean_cache(dry_run).await?;
+            let action = if report.dry_run {
+                "would remove".yellow()
+            } else {
+                "removed".green()
+            };
+
+            for (path, size) in &report.removed {
+                progress.println(format!(
+                    "{} {} ({})",
+                    action,
+                    path,
+                    format_size(*size, BINARY)
+                ));
+            }
+            for name in &report.kept_pending {
+                progress.println(format!(
+                    "{} {} (changes not pushed yet, run sync with this network or app id)",
+                    "kept".dimmed(),
+                    name
+                ));
+            }
+            progress.println(format!(
+                "{} {} files, {} {}, {} left.",
+                "•".bright_green(),
+                report.removed.len(),
+                format_size(report.reclaimed_bytes, BINARY),
+                if report.dry_run {
+                    "to reclaim"
+                } else {
+                    "reclaimed"
+                },
+                format_size(report.remaining_bytes, BINARY)
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::{cli::CollectionCommands, connect_to_daemon};
+use anyhow::Result;
+use colored::Colorize;
+
+pub async fn handle_collection(
+    command: CollectionCommands,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    match command {
+        CollectionCommands::Add { name, keys } => {
+            let added = keys.len();
+            let collection = client.collection_add(&name, keys).await?;
+
+            progress.println(format!(
+                "{} Added {} keys to collection '{}' ({} keys).",
+                "•".bright_green(),
+                added,
+                collection.name,
+                collection.keys.len()
+            ));
+        }
+        CollectionCommands::Rm { name, delete_keys } => {
+            let collection = client.collection_rm(&name, delete_keys).await?;
+
+            if delete_keys {
+                progress.println(format!(
+                    "{} Collection '{}' and its {} keys removed.",
+                    "•".bright_green(),
+                    collection.name,
+                    collection.keys.len()
+                ));
+            } else {
+                progress.println(format!(
+                    "{} Collection '{}' removed, its {} keys are kept.",
+                    "•".bright_green(),
+                    collection.name,
+                    collection.keys.len()
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -2,28 +2,52 @@ use crate::cli::DaemonCommands;
 use anyhow::Result;
 use tokio::process::Command;
 
-pub async fn handle_daemon(command: DaemonCommands) -> Result<()> {
+pub async fn handle_daemon(command: DaemonCommands, concurrency: Option<u64>) -> Result<()> {
     match command {
-        DaemonCommands::Start => start_daemon().await,
+        DaemonCommands::Start => start_daemon(concurrency).await,
         DaemonCommands::Stop => stop_daemon().await,
-        DaemonCommands::Restart => restart_daemon().await,
+        DaemonCommands::Restart => restart_daemon(concurrency).await,
         DaemonCommands::Status => status_daemon().await,
         // DaemonCommands::Logs => logs_daemon().await,
         _ => Err(anyhow::anyhow!("Command not implemented")),
     }
 }
 
-pub async fn start_daemon() -> Result<()> {
-    match std::fs::read_to_string("/tmp/mutant-daemon.lock") {
-        Ok(_pid) => {
-            return Ok(());
+/// Whether a daemon holds the lock file, i.e. whether `start_daemon` has nothing to do.
+pub fn is_running() -> bool {
+    std::fs::read_to_string("/tmp/mutant-daemon.lock").is_ok()
+}
+
+/// Why the daemon last refused to start, such as a devnet wallet on a public network.
+/// Left by the daemon, which runs in the background and has no terminal of its own.
+pub fn startup_error() -> Option<String> {
+    std::fs::read_to_string("/tmp/mutant-daemon.error")
+        .ok()
+        .filter(|error| !error.trim().is_empty())
+}
+
+/// Starts the daemon if none is running, `concurrency` capping the pads it reads or
+/// writes at once. A running daemon keeps the cap it was started with.
+pub async fn start_daemon(concurrency: Option<u64>) -> Result<()> {
+    if is_running() {
+        if concurrency.is_some() {
+            eprintln!(
+                "Daemon already running, --concurrency only applies once it is restarted (mutant daemon restart)"
+            );
         }
-        Err(_e) => {}
-    };
+        return Ok(());
+    }
 
     println!("Starting daemon...");
 
-    let _ = Command::new("bash").arg("-c").arg("mutant-daemon --ignore-ctrl-c &").spawn()?;
+    let mut command_line = "mutant-daemon --ignore-ctrl-c".to_string();
+    if let Some(concurrency) = concurrency {
+        command_line.push_str(&format!(" --concurrency {}", concurrency));
+    }
+    let _ = Command::new("bash")
+        .arg("-c")
+        .arg(format!("{} &", command_line))
+        .spawn()?;
 
     tokio::time::sleep(std::time::Duration::from_millis(300)).await;
 
@@ -57,9 +81,9 @@ async fn stop_daemon() -> Result<()> {
     Ok(())
 }
 
-async fn restart_daemon() -> Result<()> {
+async fn restart_daemon(concurrency: Option<u64>) -> Result<()> {
     stop_daemon().await?;
-    start_daemon().await?;
+    start_daemon(concurrency).await?;
     Ok(())
 }
 
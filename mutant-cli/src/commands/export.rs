@@ -1,14 +1,29 @@
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
 use anyhow::Result;
 use colored::Colorize;
 
-pub async fn handle_export(destination_path: String) -> Result<()> {
+pub async fn handle_export(
+    destination_path: String,
+    public_catalogue: bool,
+    progress: &ProgressManager,
+) -> Result<()> {
     let mut client = connect_to_daemon().await?;
+    if public_catalogue {
+        let result = client.export_public_catalogue(&destination_path).await?;
+        progress.println(format!(
+            "{} Exported {} public keys to catalogue '{}'.",
+            "•".bright_green(),
+            result.nb_keys_exported,
+            destination_path
+        ));
+        return Ok(());
+    }
     client.export(&destination_path).await?;
-    println!(
+    progress.println(format!(
         "{} Exported file '{}'.",
         "•".bright_green(),
         destination_path
-    );
+    ));
     Ok(())
 }
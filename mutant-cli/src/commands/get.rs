@@ -1,28 +1,68 @@
 use crate::callbacks;
-use crate::callbacks::progress::ProgressWrapper;
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
 use crate::history::append_history_entry;
 use crate::history::FetchHistoryEntry;
-use crate::utils::{ensure_progress_cleared, format_elapsed_time};
-use anyhow::Result;
+use crate::report::io_summary;
+use crate::utils::{absolute_path, format_elapsed_time};
+use anyhow::{bail, Result};
 use chrono::Utc;
 use colored::Colorize;
 use mutant_protocol::TaskResult;
 use mutant_protocol::TaskResultType;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+/// Checks that the value can be written to `destination`, creating its missing parent
+/// directories with `parents`.
+fn prepare_destination(destination: &Path, parents: bool, force: bool) -> Result<()> {
+    if destination.is_dir() {
+        bail!("{} is a directory", destination.display());
+    }
+    if destination.exists() && !force {
+        bail!(
+            "{} exists, use --force to overwrite it",
+            destination.display()
+        );
+    }
+    match destination.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            if !parents {
+                bail!(
+                    "{} does not exist, use --parents to create it",
+                    parent.display()
+                );
+            }
+            std::fs::create_dir_all(parent)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_get(
     key: String,
-    destination_path: String,
+    destination: PathBuf,
     public: bool,
+    index_file: Option<PathBuf>,
+    parents: bool,
+    force: bool,
     background: bool,
-    quiet: bool,
+    progress: &ProgressManager,
 ) -> Result<()> {
+    let index_file = index_file.as_deref().map(absolute_path).transpose()?;
+    prepare_destination(&destination, parents, force)?;
+    // The daemon writes the file, relative paths are resolved here
+    let destination_path = absolute_path(&destination)?;
+
     if background {
         let _ = tokio::spawn(async move {
             let mut client = connect_to_daemon().await.unwrap();
-            let (start_task, _progress_rx) =
-                client.get(&key, &destination_path, public).await.unwrap();
+            let (start_task, _progress_rx) = client
+                .get(&key, &destination_path, public, index_file.as_deref())
+                .await
+                .unwrap();
             start_task.await.unwrap();
         });
 
@@ -36,17 +76,12 @@ pub async fn handle_get(
     // Start timing the operation
     let start_time = Instant::now();
 
-    let (start_task, progress_rx) = client.get(&key, &destination_path, public).await?;
+    let (start_task, progress_rx) = client
+        .get(&key, &destination_path, public, index_file.as_deref())
+        .await?;
 
-    // Create the progress bar wrapper
-    // Keep it in scope until the end of the function to ensure progress bars are properly cleaned up
-    let progress = if !quiet {
-        let progress = ProgressWrapper::new();
-        callbacks::get::create_get_progress(progress_rx, progress.multi_progress());
-        Some(progress)
-    } else {
-        None
-    };
+    progress.set_label(&key);
+    callbacks::get::create_get_progress(progress_rx, progress);
 
     match start_task.await {
         Ok(result) => match result {
@@ -54,29 +89,33 @@ pub async fn handle_get(
                 if error.contains("Key not found") ||
                    error.contains("No pads found for key") ||
                    error.contains("upload is not finished") {
-                    eprintln!("{} Key '{}' not found.", "Error:".bright_red(), key);
-                } else {
-                    eprintln!("{} {}", "Error:".bright_red(), error);
+                    bail!("Key '{}' not found.", key);
                 }
+                bail!(error);
             }
             TaskResult::Result(result) => match result {
                 TaskResultType::Get(result) => {
                     // Calculate and format elapsed time
                     let time_str = format_elapsed_time(start_time.elapsed());
 
-                    // Ensure progress bars are cleared before displaying final message
-                    if let Some(p) = &progress {
-                        ensure_progress_cleared(p.multi_progress());
-                    }
-
-                    println!(
+                    progress.clear();
+                    progress.println(format!(
                         "{} Get task completed in {}. Result saved to {} on daemon.",
                         "•".bright_green(),
                         time_str,
                         destination_path
-                    );
+                    ));
 
-                    if public {
+                    if let Some(io) = &result.io {
+                        progress.println(format!(
+                            "{} Network I/O: {}",
+                            "•".bright_blue(),
+                            io_summary(io, result.size as u64)
+                        ));
+                    }
+
+                    // The history lists addresses, catalogue names are not.
+                    if public && index_file.is_none() {
                         let history_entry = FetchHistoryEntry {
                             address: key,
                             size: result.size,
@@ -85,18 +124,38 @@ pub async fn handle_get(
                         append_history_entry(history_entry);
                     }
                 }
-                _ => {
-                    eprintln!("{} {}", "Error:".bright_red(), "Unknown task result");
-                }
+                _ => bail!("Unknown task result"),
             },
             TaskResult::Pending => {
-                println!("{} Get task pending.", "•".bright_yellow());
+                progress.println(format!("{} Get task pending.", "•".bright_yellow()));
             }
         },
-        Err(e) => {
-            eprintln!("{} Task failed: {}", "Error:".bright_red(), e);
-        }
+        Err(e) => bail!("Task failed: {}", e),
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_destination() {
+        let dir = std::env::temp_dir().join(format!("mutant-get-{}", uuid::Uuid::new_v4()));
+        let nested = dir.join("a").join("b.bin");
+
+        let err = prepare_destination(&nested, false, false).unwrap_err();
+        assert!(err.to_string().contains("--parents"));
+        prepare_destination(&nested, true, false).unwrap();
+        assert!(dir.join("a").is_dir());
+
+        std::fs::write(&nested, b"value").unwrap();
+        let err = prepare_destination(&nested, false, false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        prepare_destination(&nested, false, true).unwrap();
+        assert!(prepare_destination(&dir, false, true).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,360 @@
+use crate::callbacks::progress::{ProgressManager, StyledProgressBar};
+use crate::connect_to_daemon;
+use crate::utils::{absolute_path, format_elapsed_time};
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use mutant_client::MutantClient;
+use mutant_protocol::{
+    key_matches, GetEvent, KeyDetails, TaskProgress, TaskResult, TaskResultType,
+};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// A key to fetch and the file its value is written to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Download {
+    key: String,
+    path: PathBuf,
+    size: u64,
+}
+
+/// A key that could not be fetched and why.
+type Failure = (String, String);
+
+const SKIPPED: &str = "Skipped after an earlier failure";
+
+/// Where `key` is written under `dir`, each `/` of its name starting a subdirectory.
+/// `None` for the names that would not land in a file under `dir`.
+fn key_path(dir: &Path, key: &str) -> Option<PathBuf> {
+    let mut path = dir.to_path_buf();
+    for part in key.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains('\\') {
+            return None;
+        }
+        path.push(part);
+    }
+    Some(path)
+}
+
+/// The key names of `content`, one per line, trimmed, skipping blank lines.
+fn key_names(content: &str) -> BTreeSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Sorts `names` into the downloads to run and the keys failing before any download:
+/// unknown keys, names that are not a path under `dir` and, without `force`, files
+/// that already exist.
+fn plan_downloads(
+    names: BTreeSet<String>,
+    listed: &[KeyDetails],
+    dir: &Path,
+    force: bool,
+) -> (Vec<Download>, Vec<Failure>) {
+    let sizes: HashMap<&str, u64> = listed
+        .iter()
+        .map(|detail| (detail.key.as_str(), detail.total_size as u64))
+        .collect();
+    let mut downloads = Vec::new();
+    let mut failures = Vec::new();
+
+    for key in names {
+        let Some(&size) = sizes.get(key.as_str()) else {
+            failures.push((key, "Key not found".to_string()));
+            continue;
+        };
+        let Some(path) = key_path(dir, &key) else {
+            failures.push((key, format!("Cannot be written under {}", dir.display())));
+            continue;
+        };
+        if path.is_dir() {
+            failures.push((key, format!("{} is a directory", path.display())));
+        } else if path.exists() && !force {
+            failures.push((
+                key,
+                format!("{} exists, use --force to overwrite it", path.display()),
+            ));
+        } else {
+            downloads.push(Download { key, path, size });
+        }
+    }
+
+    (downloads, failures)
+}
+
+/// Fetches `download`, moving `bar` along as its pads come in.
+async fn fetch(
+    client: &mut MutantClient,
+    download: &Download,
+    bar: &StyledProgressBar,
+) -> Result<()> {
+    if let Some(parent) = download.path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let destination = download.path.to_string_lossy();
+    let (start_task, mut progress_rx) =
+        client.get(&download.key, &destination, false, None).await?;
+    tokio::pin!(start_task);
+
+    let mut total_chunks = 0;
+    let mut counted = 0;
+    let result = loop {
+        tokio::select! {
+            result = &mut start_task => break result?,
+            Some(event) = progress_rx.recv() => match event {
+                Ok(TaskProgress::Get(GetEvent::Starting { total_chunks: chunks })) => {
+                    total_chunks = chunks as u64;
+                }
+                Ok(TaskProgress::Get(GetEvent::PadFetched { completed_count, .. }))
+                    if total_chunks > 0 =>
+                {
+                    let fetched = download.size * completed_count as u64 / total_chunks;
+                    if fetched > counted {
+                        bar.inc(fetched - counted);
+                        counted = fetched;
+                    }
+                }
+                _ => {}
+            },
+        }
+    };
+
+    match result {
+        TaskResult::Result(TaskResultType::Get(_)) => {
+            bar.inc(download.size.saturating_sub(counted));
+            Ok(())
+        }
+        TaskResult::Error(error) => Err(anyhow!(error)),
+        _ => Err(anyhow!("Unexpected task result")),
+    }
+}
+
+/// Fetches the downloads of `queue` one after the other until it is empty or `stop`
+/// is set.
+async fn run_worker(
+    mut client: MutantClient,
+    queue: Arc<Mutex<VecDeque<Download>>>,
+    stop: Arc<AtomicBool>,
+    fail_fast: bool,
+    bar: Arc<StyledProgressBar>,
+    done: Arc<AtomicUsize>,
+    total: usize,
+) -> Vec<Failure> {
+    let mut failures = Vec::new();
+
+    while !stop.load(Ordering::SeqCst) {
+        let Some(download) = queue.lock().unwrap().pop_front() else {
+            break;
+        };
+        if let Err(e) = fetch(&mut client, &download, &bar).await {
+            failures.push((download.key, e.to_string()));
+            if fail_fast {
+                stop.store(true, Ordering::SeqCst);
+            }
+        }
+        let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+        bar.set_message(format!("{}/{} keys", done, total));
+    }
+
+    failures
+}
+
+/// `keys` is a pattern matched against the stored keys, or `-` to read their names
+/// from stdin. Exits with code 1 when any key could not be fetched.
+pub async fn handle_get_many(
+    keys: String,
+    dir: PathBuf,
+    jobs: usize,
+    force: bool,
+    fail_fast: bool,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let dir = PathBuf::from(absolute_path(&dir)?);
+    let mut client = connect_to_daemon().await?;
+    let listed = client.list_keys().await?;
+
+    let names: BTreeSet<String> = if keys == "-" {
+        key_names(&std::io::read_to_string(std::io::stdin())?)
+    } else {
+        listed
+            .iter()
+            .filter(|detail| key_matches(&keys, &detail.key))
+            .map(|detail| detail.key.clone())
+            .collect()
+    };
+    if names.is_empty() {
+        println!("No keys match '{}'.", keys);
+        return Ok(());
+    }
+
+    let (downloads, mut failures) = plan_downloads(names, &listed, &dir, force);
+    let total = downloads.len() + failures.len();
+    let start_time = Instant::now();
+
+    if fail_fast && !failures.is_empty() {
+        failures.extend(
+            downloads
+                .into_iter()
+                .map(|download| (download.key, SKIPPED.to_string())),
+        );
+    } else if !downloads.is_empty() {
+        let bar = Arc::new(StyledProgressBar::new_for_bytes(progress));
+        bar.set_length(downloads.iter().map(|download| download.size).sum());
+        bar.set_message(format!("0/{} keys", downloads.len()));
+
+        // Each worker holds its own connection, a single one runs one task at a time.
+        let workers = jobs.clamp(1, downloads.len());
+        let mut clients = vec![client];
+        while clients.len() < workers {
+            clients.push(connect_to_daemon().await?);
+        }
+
+        let queue = Arc::new(Mutex::new(VecDeque::from(downloads)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let done = Arc::new(AtomicUsize::new(0));
+        let total_downloads = queue.lock().unwrap().len();
+        let handles: Vec<_> = clients
+            .into_iter()
+            .map(|client| {
+                tokio::spawn(run_worker(
+                    client,
+                    queue.clone(),
+                    stop.clone(),
+                    fail_fast,
+                    bar.clone(),
+                    done.clone(),
+                    total_downloads,
+                ))
+            })
+            .collect();
+        for handle in handles {
+            failures.extend(handle.await?);
+        }
+        bar.finish_and_clear();
+
+        for download in queue.lock().unwrap().drain(..) {
+            failures.push((download.key, SKIPPED.to_string()));
+        }
+    }
+
+    let fetched = total - failures.len();
+    progress.println(format!(
+        "{} {} of {} keys fetched to {} in {}.",
+        if failures.is_empty() {
+            "•".bright_green()
+        } else {
+            "•".bright_yellow()
+        },
+        fetched,
+        total,
+        dir.display(),
+        format_elapsed_time(start_time.elapsed())
+    ));
+
+    if !failures.is_empty() {
+        failures.sort();
+        for (key, reason) in &failures {
+            progress.eprintln(format!("{} {}: {}", "Failed:".bright_red(), key, reason));
+        }
+        bail!("{} of {} keys could not be fetched", failures.len(), total);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(key: &str, total_size: usize) -> KeyDetails {
+        KeyDetails {
+            key: key.to_string(),
+            total_size,
+            pad_count: 1,
+            confirmed_pads: 1,
+            confirmed_bytes: total_size as u64,
+            wasted_bytes: 0,
+            is_public: false,
+            public_address: None,
+            aliases: Vec::new(),
+            trashed_at_ms: None,
+            is_finished: true,
+            completion_percentage: 100,
+            checksum: None,
+            modified_at_ms: None,
+            last_fetched_ms: None,
+            fetch_count: None,
+        }
+    }
+
+    #[test]
+    fn test_key_path() {
+        let dir = Path::new("/restore");
+        assert_eq!(
+            key_path(dir, "photos/2024/beach.jpg"),
+            Some(PathBuf::from("/restore/photos/2024/beach.jpg"))
+        );
+        assert_eq!(
+            key_path(dir, "notes"),
+            Some(PathBuf::from("/restore/notes"))
+        );
+        assert_eq!(key_path(dir, "../etc/passwd"), None);
+        assert_eq!(key_path(dir, "/absolute"), None);
+        assert_eq!(key_path(dir, "trailing/"), None);
+        assert_eq!(key_path(dir, "a/./b"), None);
+    }
+
+    #[test]
+    fn test_key_names_skip_blank_lines() {
+        let names = key_names("photos/a.jpg\n\n  notes  \r\nnotes\n");
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec!["notes", "photos/a.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_plan_downloads() {
+        let dir = std::env::temp_dir().join(format!("mutant-get-many-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("folder")).unwrap();
+        std::fs::write(dir.join("existing"), b"old").unwrap();
+        let listed = vec![
+            detail("photos/a.jpg", 10),
+            detail("existing", 20),
+            detail("folder", 30),
+            detail("../escape", 40),
+        ];
+        let names: BTreeSet<String> = ["photos/a.jpg", "existing", "folder", "../escape", "gone"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let (downloads, failures) = plan_downloads(names.clone(), &listed, &dir, false);
+        assert_eq!(
+            downloads,
+            vec![Download {
+                key: "photos/a.jpg".to_string(),
+                path: dir.join("photos/a.jpg"),
+                size: 10,
+            }]
+        );
+        let failed: Vec<&str> = failures.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(failed, vec!["../escape", "existing", "folder", "gone"]);
+        assert!(failures[1].1.contains("--force"));
+
+        // Existing files are overwritten with --force, directories never are.
+        let (downloads, failures) = plan_downloads(names, &listed, &dir, true);
+        let keys: Vec<&str> = downloads.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, vec!["existing", "photos/a.jpg"]);
+        assert_eq!(failures.len(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
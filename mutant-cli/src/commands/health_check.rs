@@ -1,6 +1,7 @@
 use crate::callbacks;
-use crate::callbacks::progress::ProgressWrapper;
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
+use crate::report::{health_report, OutputFormat};
 use anyhow::Result;
 use colored::Colorize;
 use mutant_protocol::TaskResult;
@@ -10,7 +11,8 @@ pub async fn handle_health_check(
     key_name: String,
     background: bool,
     recycle: bool,
-    quiet: bool,
+    output: OutputFormat,
+    progress: &ProgressManager,
 ) -> Result<()> {
     if background {
         let _ = tokio::spawn(async move {
@@ -27,41 +29,38 @@ pub async fn handle_health_check(
     let mut client = connect_to_daemon().await?;
     let (start_task, progress_rx) = client.health_check(&key_name, recycle).await?;
 
-    // Create the progress bar wrapper
-    // Keep it in scope until the end of the function to ensure progress bars are properly cleaned up
-    let _progress = if !quiet {
-        let progress = ProgressWrapper::new();
-        callbacks::health_check::create_health_check_progress(progress_rx, progress.multi_progress());
-        Some(progress)
-    } else {
-        None
-    };
+    callbacks::health_check::create_health_check_progress(progress_rx, progress);
 
     match start_task.await {
         Ok(result) => match result {
             TaskResult::Error(error) => {
                 if error.contains("not found") || error.contains("No pads found for key") {
-                    eprintln!("{} Key '{}' not found.", "Error:".bright_red(), key_name);
+                    progress.eprintln(format!(
+                        "{} Key '{}' not found.",
+                        "Error:".bright_red(),
+                        key_name
+                    ));
                 } else {
-                    eprintln!("{} {}", "Error:".bright_red(), error);
+                    progress.eprintln(format!("{} {}", "Error:".bright_red(), error));
                 }
             }
             TaskResult::Result(result) => match result {
                 TaskResultType::HealthCheck(result) => {
-                    println!("Health check complete.");
-                    println!("  {} keys reset", result.nb_keys_reset);
-                    println!("  {} keys recycled", result.nb_keys_recycled);
+                    progress.suspend(|| output.print(&result, &health_report(&result)))?;
                 }
                 _ => {
-                    eprintln!("{} {}", "Error:".bright_red(), "Unknown task result");
+                    progress.eprintln(format!("{} Unknown task result", "Error:".bright_red()));
                 }
             },
             TaskResult::Pending => {
-                println!("{} Health check task pending.", "•".bright_yellow());
+                progress.println(format!(
+                    "{} Health check task pending.",
+                    "•".bright_yellow()
+                ));
             }
         },
         Err(e) => {
-            eprintln!("{} Task failed: {}", "Error:".bright_red(), e);
+            progress.eprintln(format!("{} Task failed: {}", "Error:".bright_red(), e));
         }
     }
 
@@ -1,10 +1,15 @@
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
 use anyhow::Result;
 use colored::Colorize;
 
-pub async fn handle_import(file_path: String) -> Result<()> {
+pub async fn handle_import(file_path: String, progress: &ProgressManager) -> Result<()> {
     let mut client = connect_to_daemon().await?;
     client.import(&file_path).await?;
-    println!("{} Imported file '{}'.", "•".bright_green(), file_path);
+    progress.println(format!(
+        "{} Imported file '{}'.",
+        "•".bright_green(),
+        file_path
+    ));
     Ok(())
 }
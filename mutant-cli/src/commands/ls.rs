@@ -1,73 +1,722 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::cli::LsSort;
+use crate::report::{render_json, OutputFormat};
+use crate::utils::absolute_path;
 use crate::{connect_to_daemon, history::load_history};
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
 use colored::Colorize;
 use humansize::{format_size, BINARY};
-use log::info;
+use log::{info, warn};
+use mutant_client::MutantClient;
+use mutant_protocol::KeyDetails;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_ls(
+    prefix: Option<String>,
+    delimiter: Option<String>,
+    show_aliases: bool,
+    show_history: bool,
+    collection: Option<String>,
+    trash: bool,
+    include_trash: bool,
+    sort: LsSort,
+    index_file: Option<PathBuf>,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
+    if output.json && delimiter.is_some() {
+        bail!("--delimiter cannot be used with --json, the keys are listed one by one");
+    }
+    if output.json && show_history {
+        bail!("--history cannot be used with --json");
+    }
 
-pub async fn handle_ls(show_history: bool) -> Result<()> {
     let mut client = connect_to_daemon().await?;
-    let details = client.list_keys().await?;
+    if trash {
+        return list_trash(&mut client, output, progress).await;
+    }
 
-    if details.is_empty() {
-        println!("No keys stored.");
-    } else {
-        println!(
-            " {: <20} {:>5} {:>10} {: <12} {}",
-            "Key", "Pads", "Size", "Status", "Address/Info"
-        );
-        println!("{}", "-".repeat(70));
+    let mut details = match (&collection, &index_file) {
+        (Some(name), _) => client.list_collection_keys(name).await?,
+        (None, Some(index_file)) => {
+            client
+                .list_index_file_keys(&absolute_path(index_file)?)
+                .await?
+        }
+        (None, None) => {
+            client
+                .list_keys_with_prefix(prefix.as_deref().unwrap_or_default())
+                .await?
+        }
+    };
+    if include_trash {
+        details.extend(client.list_trash().await?);
+    }
+    // The store is filtered by the daemon, the other listings are filtered here.
+    let prefix = prefix.unwrap_or_default();
+    details.retain(|detail| detail.key.starts_with(&prefix));
+    sort_details(&mut details, sort);
 
-        for detail in details {
-            let completion_str = if detail.pad_count == 0 {
-                "0% (0/0)".to_string()
-            } else if detail.confirmed_pads == detail.pad_count {
-                "Ready".bright_green().to_string()
-            } else {
-                format!(
-                    "{}% ({}/{})",
-                    detail.confirmed_pads * 100 / detail.pad_count,
-                    detail.confirmed_pads,
-                    detail.pad_count
-                )
-                .bright_yellow()
-                .to_string()
+    // The listing is printed at once, without the bars in the way
+    progress.suspend(|| -> Result<()> {
+        if output.json {
+            let keys: Vec<ListedKey> = details.iter().map(ListedKey::from).collect();
+            println!("{}", render_json(&keys)?);
+            return Ok(());
+        }
+
+        if details.is_empty() {
+            match (collection, index_file) {
+                _ if !prefix.is_empty() => println!("No keys starting with '{}'.", prefix),
+                (Some(name), _) => println!("No keys in collection '{}'.", name),
+                (None, Some(index_file)) => {
+                    println!("No keys in catalogue '{}'.", index_file.display())
+                }
+                (None, None) => println!("No keys stored."),
+            }
+        } else {
+            print_key_header();
+            let delimiter = delimiter.filter(|delimiter| !delimiter.is_empty());
+            let listed = match &delimiter {
+                Some(delimiter) => collapse_levels(details, &prefix, delimiter),
+                None => details.into_iter().map(Listed::Key).collect(),
             };
+            for entry in listed {
+                match entry {
+                    Listed::Key(detail) => print_key(detail, show_aliases),
+                    Listed::Level {
+                        name,
+                        keys,
+                        total_size,
+                    } => print!("{}", format_level(&name, keys, total_size)),
+                }
+            }
+        }
 
-            let size_str = format_size(detail.total_size, BINARY);
+        // Only show fetch history if requested
+        if show_history {
+            info!("Loading fetch history from file...");
+            let mut history = load_history();
+            if !history.is_empty() {
+                println!("\n--- Fetch History ---");
+                history.sort_by(|a, b| {
+                    b.fetched_at
+                        .cmp(&a.fetched_at)
+                        .then_with(|| a.address.cmp(&b.address))
+                });
 
-            let address_info = if detail.is_public {
-                format!("Public: {}", detail.public_address.unwrap_or_default())
-            } else {
-                "Private".to_string()
-            };
+                for entry in history {
+                    let size_str = format_size(entry.size, BINARY);
+                    let date_str = entry.fetched_at.format(DATE_FORMAT).to_string();
+
+                    println!(" {: <32} {:>10} {}", entry.address, size_str, date_str);
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// A key as `ls` and `stat` print it with `--json`. Fields are only ever added to it so
+/// scripts can rely on them, dates are RFC 3339 in UTC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ListedKey {
+    pub key: String,
+    pub size: usize,
+    pub pad_count: usize,
+    pub confirmed_pads: usize,
+    pub is_public: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_address: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// `None` for the keys stored without an integrity proof.
+    pub modified: Option<String>,
+    pub is_finished: bool,
+    pub completion_percentage: u8,
+    /// Bytes of the value held by the confirmed pads.
+    #[serde(default)]
+    pub confirmed_bytes: u64,
+    /// Space of the confirmed pads holding none of the value.
+    #[serde(default)]
+    pub wasted_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// When the key was moved to the trash, only for the removed keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed: Option<String>,
+}
+
+impl From<&KeyDetails> for ListedKey {
+    fn from(detail: &KeyDetails) -> Self {
+        Self {
+            key: detail.key.clone(),
+            size: detail.total_size,
+            pad_count: detail.pad_count,
+            confirmed_pads: detail.confirmed_pads,
+            is_public: detail.is_public,
+            public_address: detail.public_address.clone(),
+            aliases: detail.aliases.clone(),
+            modified: detail.modified_at_ms.and_then(format_rfc3339),
+            is_finished: detail.is_finished,
+            completion_percentage: detail.completion_percentage,
+            confirmed_bytes: detail.confirmed_bytes,
+            wasted_bytes: detail.wasted_bytes,
+            checksum: detail.checksum.clone(),
+            trashed: detail.trashed_at_ms.and_then(format_rfc3339),
+        }
+    }
+}
+
+fn format_rfc3339(ms: u64) -> Option<String> {
+    DateTime::<Utc>::from_timestamp_millis(ms as i64)
+        .map(|date| date.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+/// A row of `ls --delimiter`.
+#[derive(Debug, PartialEq)]
+enum Listed {
+    Key(KeyDetails),
+    /// The keys sharing a level right below the prefix, such as `backups/`.
+    Level {
+        name: String,
+        keys: usize,
+        total_size: usize,
+    },
+}
+
+/// Collapses the keys holding `delimiter` past `prefix` into one level each, named after
+/// them up to the delimiter: `backups/2024/db.tar` is counted in `backups/` without
+/// prefix and in `backups/2024/` with `backups/`. Levels come first by name, the other
+/// keys keep their order.
+fn collapse_levels(details: Vec<KeyDetails>, prefix: &str, delimiter: &str) -> Vec<Listed> {
+    let mut levels: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut keys = Vec::new();
+
+    for detail in details {
+        let level_end = detail
+            .key
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.find(delimiter))
+            .map(|end| prefix.len() + end + delimiter.len());
+        match level_end {
+            Some(end) => {
+                let (count, size) = levels.entry(detail.key[..end].to_string()).or_default();
+                *count += 1;
+                *size += detail.total_size;
+            }
+            None => keys.push(Listed::Key(detail)),
+        }
+    }
+
+    levels
+        .into_iter()
+        .map(|(name, (keys, total_size))| Listed::Level {
+            name,
+            keys,
+            total_size,
+        })
+        .chain(keys)
+        .collect()
+}
+
+/// The line of a level of `ls --delimiter`, in the columns of [`print_key`].
+fn format_level(name: &str, keys: usize, total_size: usize) -> String {
+    let keys_str = if keys == 1 {
+        "1 key".to_string()
+    } else {
+        format!("{} keys", keys)
+    };
+    format!(
+        " {} {:>5} {:>10} {}\n",
+        format!("{: <20}", name).bright_blue(),
+        "",
+        format_size(total_size, BINARY),
+        keys_str.dimmed()
+    )
+}
+
+/// Where a key listed by `ls --remote` is, the remote index being compared with the
+/// local one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum KeyPresence {
+    Both,
+    RemoteOnly,
+    LocalOnly,
+}
+
+impl KeyPresence {
+    /// The marker column, padded before being colored like the other columns.
+    fn marker(self) -> String {
+        match self {
+            KeyPresence::Both => format!("{: <12}", "both").dimmed().to_string(),
+            KeyPresence::RemoteOnly => format!("{: <12}", "remote-only").bright_cyan().to_string(),
+            KeyPresence::LocalOnly => format!("{: <12}", "local-only").bright_yellow().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RemoteKey {
+    /// Left out when the local index could not be listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence: Option<KeyPresence>,
+    #[serde(flatten)]
+    detail: KeyDetails,
+}
+
+/// Lists the remote index as it was pushed, without merging it into the local one.
+pub async fn handle_ls_remote(
+    show_aliases: bool,
+    sort: LsSort,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+    let remote = client.list_remote_keys().await?;
+    let local = match client.list_keys().await {
+        Ok(local) => Some(local),
+        Err(e) => {
+            warn!("Could not list the local index to compare with: {}", e);
+            None
+        }
+    };
+    let keys = compare_with_local(remote, local, sort);
+
+    progress.suspend(|| -> Result<()> {
+        if output.json {
+            println!("{}", render_json(&keys)?);
+            return Ok(());
+        }
+
+        println!("{}", "Remote index, as last pushed (not synced)".bold());
+        if keys.is_empty() {
+            println!("No keys in the remote index.");
+            return Ok(());
+        }
+        print!(" {: <12}", "Where");
+        print_key_header();
+        for key in keys {
+            let marker = key.presence.map(KeyPresence::marker).unwrap_or_default();
+            print!(" {: <12}", marker);
+            print_key(key.detail, show_aliases);
+        }
+
+        Ok(())
+    })
+}
+
+/// The keys of the remote index followed by the keys only the local index has, if it
+/// could be listed, each one marked with where it is.
+fn compare_with_local(
+    remote: Vec<KeyDetails>,
+    local: Option<Vec<KeyDetails>>,
+    sort: LsSort,
+) -> Vec<RemoteKey> {
+    let remote_keys: HashSet<String> = remote.iter().map(|d| d.key.clone()).collect();
+    let local_keys: Option<HashSet<String>> = local
+        .as_ref()
+        .map(|local| local.iter().map(|d| d.key.clone()).collect());
+
+    let mut details = remote;
+    details.extend(
+        local
+            .into_iter()
+            .flatten()
+            .filter(|detail| !remote_keys.contains(&detail.key)),
+    );
+    sort_details(&mut details, sort);
+
+    details
+        .into_iter()
+        .map(|detail| {
+            let presence = local_keys.as_ref().map(|local_keys| {
+                match (
+                    remote_keys.contains(&detail.key),
+                    local_keys.contains(&detail.key),
+                ) {
+                    (true, true) => KeyPresence::Both,
+                    (true, false) => KeyPresence::RemoteOnly,
+                    (false, _) => KeyPresence::LocalOnly,
+                }
+            });
+            RemoteKey { presence, detail }
+        })
+        .collect()
+}
+
+/// Prints the column titles of [`print_key`], within [`ProgressManager::suspend`].
+pub(crate) fn print_key_header() {
+    println!(
+        " {: <20} {:>5} {:>10} {: <12} {}",
+        "Key", "Pads", "Size", "Status", "Address/Info"
+    );
+    println!("{}", "-".repeat(70));
+}
+
+/// Prints the row of a key in the listing, followed by its aliases with `show_aliases`.
+/// Like [`print_key_header`], it is called within [`ProgressManager::suspend`].
+pub(crate) fn print_key(detail: KeyDetails, show_aliases: bool) {
+    print!("{}", format_key(detail, show_aliases));
+}
+
+/// The lines [`print_key`] prints. Columns are padded before being colored so they line
+/// up the same whether or not the output goes to a terminal.
+fn format_key(detail: KeyDetails, show_aliases: bool) -> String {
+    let completion_str = if detail.trashed_at_ms.is_some() {
+        format!("{: <12}", "Trashed").dimmed().to_string()
+    } else if detail.pad_count == 0 {
+        format!("{: <12}", "0% (0/0)")
+    } else if detail.is_finished {
+        format!("{: <12}", "Ready").bright_green().to_string()
+    } else {
+        format!(
+            "{: <12}",
+            format!(
+                "{}% ({}/{})",
+                detail.completion_percentage, detail.confirmed_pads, detail.pad_count
+            )
+        )
+        .bright_yellow()
+        .to_string()
+    };
+
+    let size_str = format_size(detail.total_size, BINARY);
+
+    let address_info = if detail.is_public {
+        format!("Public: {}", detail.public_address.unwrap_or_default())
+    } else {
+        "Private".to_string()
+    };
+
+    let mut lines = format!(
+        " {: <20} {:>5} {:>10} {} {}\n",
+        detail.key, detail.pad_count, size_str, completion_str, address_info
+    );
+
+    if show_aliases {
+        for alias in &detail.aliases {
+            lines.push_str(&format!("   {} {}\n", "↳".bright_blue(), alias));
+        }
+        if let (Some(count), Some(last)) = (detail.fetch_count, detail.last_fetched_ms) {
+            lines.push_str(&format!(
+                "   {}\n",
+                format!("fetched {} times, last {}", count, format_ms(last)).dimmed()
+            ));
+        }
+    }
+
+    lines
+}
+
+/// Keys are listed by name, keys never fetched come last when sorting by access. Ties
+/// are broken by name so the same index always lists the same way.
+fn sort_details(details: &mut [KeyDetails], sort: LsSort) {
+    match sort {
+        LsSort::Name => details.sort_by(|a, b| a.key.cmp(&b.key)),
+        LsSort::LastAccess => details.sort_by(|a, b| {
+            b.last_fetched_ms
+                .cmp(&a.last_fetched_ms)
+                .then_with(|| a.key.cmp(&b.key))
+        }),
+    }
+}
+
+/// Dates are always printed in UTC with this format, whatever the locale of the machine.
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+
+fn format_ms(ms: u64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(ms as i64)
+        .map(|date| date.format(DATE_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+async fn list_trash(
+    client: &mut MutantClient,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let details = client.list_trash().await?;
+
+    progress.suspend(|| -> Result<()> {
+        if output.json {
+            let keys: Vec<ListedKey> = details.iter().map(ListedKey::from).collect();
+            println!("{}", render_json(&keys)?);
+            return Ok(());
+        }
+
+        if details.is_empty() {
+            println!("The trash is empty.");
+            return Ok(());
+        }
+
+        println!(" {: <20} {:>5} {:>10} Removed", "Key", "Pads", "Size");
+        println!("{}", "-".repeat(70));
+
+        for detail in details {
+            let removed_str = detail.trashed_at_ms.map(format_ms).unwrap_or_default();
 
             println!(
-                " {: <20} {:>5} {:>10} {: <21} {}",
+                " {: <20} {:>5} {:>10} {}",
                 detail.key,
                 detail.pad_count,
-                size_str,
-                completion_str.to_string(),
-                address_info
+                format_size(detail.total_size, BINARY),
+                removed_str
             );
         }
-    }
 
-    // Only show fetch history if requested
-    if show_history {
-        info!("Loading fetch history from file...");
-        let mut history = load_history();
-        if !history.is_empty() {
-            println!("\n--- Fetch History ---");
-            history.sort_by(|a, b| b.fetched_at.cmp(&a.fetched_at));
+        println!(
+            "\nRestore a key with {}.",
+            "mutant restore <key>".bright_blue()
+        );
 
-            for entry in history {
-                let size_str = format_size(entry.size, BINARY);
-                let date_str = entry.fetched_at.format("%b %d %H:%M").to_string();
+        Ok(())
+    })
+}
 
-                println!(" {: <32} {:>10} {}", entry.address, size_str, date_str);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(key: &str, last_fetched_ms: Option<u64>) -> KeyDetails {
+        KeyDetails {
+            key: key.to_string(),
+            total_size: 0,
+            pad_count: 0,
+            confirmed_pads: 0,
+            confirmed_bytes: 0,
+            wasted_bytes: 0,
+            is_public: false,
+            public_address: None,
+            aliases: Vec::new(),
+            trashed_at_ms: None,
+            is_finished: false,
+            completion_percentage: 0,
+            checksum: None,
+            modified_at_ms: None,
+            last_fetched_ms,
+            fetch_count: last_fetched_ms.map(|_| 1),
         }
     }
 
-    Ok(())
+    #[test]
+    fn test_remote_listing_marks_where_each_key_is() {
+        let remote = vec![detail("shared", None), detail("pushed", None)];
+        let local = vec![detail("local", None), detail("shared", Some(1))];
+
+        let keys = compare_with_local(remote.clone(), Some(local), LsSort::Name);
+        let listed: Vec<_> = keys
+            .iter()
+            .map(|key| (key.detail.key.as_str(), key.presence))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("local", Some(KeyPresence::LocalOnly)),
+                ("pushed", Some(KeyPresence::RemoteOnly)),
+                ("shared", Some(KeyPresence::Both)),
+            ]
+        );
+        // A key on both sides is listed as the remote index has it.
+        assert_eq!(keys[2].detail.last_fetched_ms, None);
+
+        let json = serde_json::to_value(&keys[1]).unwrap();
+        assert_eq!(json["presence"], "remote-only");
+        assert_eq!(json["key"], "pushed");
+
+        // Without the local index, the keys are not marked.
+        let keys = compare_with_local(remote, None, LsSort::Name);
+        assert_eq!(keys.len(), 2);
+        assert!(keys.iter().all(|key| key.presence.is_none()));
+        assert!(serde_json::to_value(&keys[0])
+            .unwrap()
+            .get("presence")
+            .is_none());
+    }
+
+    #[test]
+    fn test_json_listing_snapshot() {
+        let details = [
+            KeyDetails {
+                total_size: 3_000_000,
+                pad_count: 2,
+                confirmed_pads: 2,
+                confirmed_bytes: 3_000_000,
+                wasted_bytes: 5_388_608,
+                is_finished: true,
+                completion_percentage: 100,
+                modified_at_ms: Some(1_700_000_000_000),
+                ..detail("beta", None)
+            },
+            KeyDetails {
+                total_size: 10,
+                pad_count: 1,
+                trashed_at_ms: Some(1_700_000_000_000),
+                ..detail("gone", None)
+            },
+        ];
+        let keys: Vec<ListedKey> = details.iter().map(ListedKey::from).collect();
+
+        let expected = r#"[
+  {
+    "key": "beta",
+    "size": 3000000,
+    "pad_count": 2,
+    "confirmed_pads": 2,
+    "is_public": false,
+    "modified": "2023-11-14T22:13:20.000Z",
+    "is_finished": true,
+    "completion_percentage": 100,
+    "confirmed_bytes": 3000000,
+    "wasted_bytes": 5388608
+  },
+  {
+    "key": "gone",
+    "size": 10,
+    "pad_count": 1,
+    "confirmed_pads": 0,
+    "is_public": false,
+    "modified": null,
+    "is_finished": false,
+    "completion_percentage": 0,
+    "confirmed_bytes": 0,
+    "wasted_bytes": 0,
+    "trashed": "2023-11-14T22:13:20.000Z"
+  }
+]"#;
+        let json = render_json(&keys).unwrap();
+        assert_eq!(json, expected);
+
+        let parsed: Vec<ListedKey> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, keys);
+    }
+
+    #[test]
+    fn test_delimiter_collapses_deeper_levels() {
+        let sized = |key: &str, total_size: usize| KeyDetails {
+            total_size,
+            ..detail(key, None)
+        };
+        let index = || {
+            vec![
+                sized("backups/2024/05/db.tar", 10),
+                sized("backups/2024/06/db.tar", 20),
+                sized("backups/notes", 1),
+                sized("photos/cat.jpg", 5),
+                sized("readme", 2),
+            ]
+        };
+        let level = |name: &str, keys, total_size| Listed::Level {
+            name: name.to_string(),
+            keys,
+            total_size,
+        };
+
+        assert_eq!(
+            collapse_levels(index(), "", "/"),
+            vec![
+                level("backups/", 3, 31),
+                level("photos/", 1, 5),
+                Listed::Key(sized("readme", 2)),
+            ]
+        );
+        assert_eq!(
+            collapse_levels(index()[..3].to_vec(), "backups/", "/"),
+            vec![
+                level("backups/2024/", 2, 30),
+                Listed::Key(sized("backups/notes", 1)),
+            ]
+        );
+        assert!(format_level("photos/", 1, 5).contains("1 key"));
+    }
+
+    #[test]
+    fn test_sort_by_last_access_puts_unfetched_keys_last() {
+        let mut details = vec![
+            detail("never", None),
+            detail("old", Some(1)),
+            detail("also-never", None),
+            detail("recent", Some(2)),
+        ];
+
+        sort_details(&mut details, LsSort::LastAccess);
+        let keys: Vec<_> = details.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, vec!["recent", "old", "also-never", "never"]);
+
+        sort_details(&mut details, LsSort::Name);
+        let keys: Vec<_> = details.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, vec!["also-never", "never", "old", "recent"]);
+    }
+
+    #[test]
+    fn test_listing_the_same_index_twice_prints_the_same_output() {
+        let render = |mut details: Vec<KeyDetails>| {
+            sort_details(&mut details, LsSort::Name);
+            details
+                .into_iter()
+                .map(|detail| format_key(detail, true))
+                .collect::<String>()
+        };
+        let index = vec![
+            KeyDetails {
+                aliases: vec!["b-alias".to_string(), "z-alias".to_string()],
+                is_finished: true,
+                pad_count: 3,
+                ..detail("beta", Some(1_700_000_000_000))
+            },
+            detail("alpha", None),
+            KeyDetails {
+                pad_count: 4,
+                confirmed_pads: 1,
+                completion_percentage: 25,
+                ..detail("gamma", Some(1_600_000_000_000))
+            },
+        ];
+        let mut shuffled = index.clone();
+        shuffled.reverse();
+
+        let first = render(index);
+        assert_eq!(first, render(shuffled));
+        assert!(first.find(" alpha").unwrap() < first.find(" beta").unwrap());
+        assert!(first.contains("last 2023-11-14 22:13 UTC"));
+    }
+
+    #[test]
+    fn test_status_column_has_a_fixed_width() {
+        let ready = format_key(
+            KeyDetails {
+                is_finished: true,
+                pad_count: 1,
+                ..detail("a", None)
+            },
+            false,
+        );
+        let pending = format_key(
+            KeyDetails {
+                pad_count: 10,
+                confirmed_pads: 6,
+                completion_percentage: 60,
+                ..detail("b", None)
+            },
+            false,
+        );
+        let trashed = format_key(
+            KeyDetails {
+                trashed_at_ms: Some(1_700_000_000_000),
+                pad_count: 2,
+                ..detail("c", None)
+            },
+            false,
+        );
+
+        assert_eq!(ready.find("Private"), pending.find("Private"));
+        assert_eq!(ready.find("Private"), trashed.find("Private"));
+        assert!(trashed.contains("Trashed"));
+    }
 }
@@ -0,0 +1,80 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::utils::absolute_path;
+use crate::{cli::MirrorCommands, connect_to_daemon};
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+/// `check` returns an error, and so exits with code 1, when the directory differs from what
+/// was mirrored.
+pub async fn handle_mirror(command: MirrorCommands, progress: &ProgressManager) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    match command {
+        MirrorCommands::Push { dir, prefix } => {
+            let manifest = client.mirror_push(&absolute_path(&dir)?, &prefix).await?;
+            let stored = manifest.files.iter().filter(|file| file.stored).count();
+
+            for file in manifest.files.iter().filter(|file| file.stored) {
+                progress.println(format!(
+                    "{} {} -> {}",
+                    "stored".green(),
+                    file.path,
+                    file.key
+                ));
+            }
+            for link in &manifest.symlinks {
+                progress.println(format!("{} {} (symbolic link)", "skipped".yellow(), link));
+            }
+            for empty_dir in &manifest.empty_dirs {
+                progress.println(format!(
+                    "{} {}/ (empty directory)",
+                    "skipped".yellow(),
+                    empty_dir
+                ));
+            }
+            progress.println(format!(
+                "{} {} files stored, {} unchanged under '{}'.",
+                "•".bright_green(),
+                stored,
+                manifest.files.len() - stored,
+                manifest.prefix
+            ));
+        }
+        MirrorCommands::Check { dir, prefix } => {
+            let check = client.mirror_check(&absolute_path(&dir)?, &prefix).await?;
+
+            for path in &check.modified {
+                progress.println(format!("{} {}", "modified".yellow(), path));
+            }
+            for path in &check.only_local {
+                progress.println(format!("{} {}", "not stored".red(), path));
+            }
+            for path in &check.only_stored {
+                progress.println(format!("{} {}", "deleted".red(), path));
+            }
+            for link in &check.symlinks {
+                progress.println(format!("{} {} (symbolic link)", "skipped".dimmed(), link));
+            }
+
+            if !check.is_clean() {
+                progress.println(format!(
+                    "{} {} files unchanged, {} modified, {} not stored, {} deleted.",
+                    "•".bright_yellow(),
+                    check.unchanged.len(),
+                    check.modified.len(),
+                    check.only_local.len(),
+                    check.only_stored.len()
+                ));
+                bail!("{} differs from the keys under '{}'", dir.display(), prefix);
+            }
+            progress.println(format!(
+                "{} {} files match the keys under '{}'.",
+                "•".bright_green(),
+                check.unchanged.len(),
+                prefix
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -1,12 +1,27 @@
+pub mod alias;
+pub mod audit;
+pub mod bench;
+pub mod cache;
+pub mod collection;
 pub mod daemon;
 pub mod export;
 pub mod get;
+pub mod get_many;
 pub mod health_check;
 pub mod import;
 pub mod ls;
+pub mod mirror;
+pub mod mv;
+pub mod pad_exists;
 pub mod purge;
 pub mod put;
+pub mod put_many;
+pub mod recover;
+pub mod restore;
 pub mod rm;
+pub mod selftest;
+pub mod stat;
 pub mod stats;
 pub mod sync;
 pub mod tasks;
+pub mod verify;
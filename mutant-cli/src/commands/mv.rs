@@ -0,0 +1,19 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::connect_to_daemon;
+use anyhow::Result;
+use colored::Colorize;
+
+pub async fn handle_mv(old_key: String, new_key: String, progress: &ProgressManager) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    let response = client.rename(&old_key, &new_key).await?;
+
+    progress.println(format!(
+        "{} '{}' renamed to '{}'.",
+        "•".bright_green(),
+        response.old_key,
+        response.new_key
+    ));
+
+    Ok(())
+}
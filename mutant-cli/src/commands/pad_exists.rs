@@ -0,0 +1,17 @@
+use crate::connect_to_daemon;
+use crate::report::{pad_exists_report, OutputFormat};
+use anyhow::{bail, Result};
+
+/// Exits with code 0 when the pad exists and 1 when it does not.
+pub async fn handle_pad_exists(address: String, output: OutputFormat) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+    let response = client.pad_exists(&address).await?;
+
+    output.print(&response, &pad_exists_report(&response))?;
+
+    if !response.exists {
+        bail!("Pad {} does not exist", address);
+    }
+
+    Ok(())
+}
@@ -1,12 +1,18 @@
 use crate::callbacks;
-use crate::callbacks::progress::ProgressWrapper;
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
+use crate::report::{purge_report, OutputFormat};
 use anyhow::Result;
 use colored::Colorize;
 use mutant_protocol::TaskResult;
 use mutant_protocol::TaskResultType;
 
-pub async fn handle_purge(background: bool, aggressive: bool, quiet: bool) -> Result<()> {
+pub async fn handle_purge(
+    background: bool,
+    aggressive: bool,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
     if background {
         let _ = tokio::spawn(async move {
             let mut client = connect_to_daemon().await.unwrap();
@@ -22,36 +28,30 @@ pub async fn handle_purge(background: bool, aggressive: bool, quiet: bool) -> Re
     let mut client = connect_to_daemon().await?;
     let (start_task, progress_rx) = client.purge(aggressive).await?;
 
-    // Create the progress bar wrapper
-    // Keep it in scope until the end of the function to ensure progress bars are properly cleaned up
-    let _progress = if !quiet {
-        let progress = ProgressWrapper::new();
-        callbacks::purge::create_purge_progress(progress_rx, progress.multi_progress());
-        Some(progress)
-    } else {
-        None
-    };
+    callbacks::purge::create_purge_progress(progress_rx, progress);
 
     match start_task.await {
         Ok(result) => match result {
             TaskResult::Error(error) => {
-                eprintln!("{} {}", "Error:".bright_red(), error);
+                progress.eprintln(format!("{} {}", "Error:".bright_red(), error));
             }
             TaskResult::Result(result) => match result {
                 TaskResultType::Purge(result) => {
-                    println!("{} Purge task completed.", "•".bright_green());
-                    println!("  {} pads purged", result.nb_pads_purged);
+                    if !output.json {
+                        progress.println(format!("{} Purge task completed.", "•".bright_green()));
+                    }
+                    progress.suspend(|| output.print(&result, &purge_report(&result)))?;
                 }
                 _ => {
-                    eprintln!("{} {}", "Error:".bright_red(), "Unknown task result");
+                    progress.eprintln(format!("{} Unknown task result", "Error:".bright_red()));
                 }
             },
             TaskResult::Pending => {
-                println!("{} Sync task pending.", "•".bright_yellow());
+                progress.println(format!("{} Sync task pending.", "•".bright_yellow()));
             }
         },
         Err(e) => {
-            eprintln!("{} Task failed: {}", "Error:".bright_red(), e);
+            progress.eprintln(format!("{} Task failed: {}", "Error:".bright_red(), e));
         }
     }
 
@@ -1,13 +1,35 @@
 use crate::callbacks;
-use crate::callbacks::progress::ProgressWrapper;
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
-use crate::utils::{ensure_progress_cleared, format_elapsed_time};
-use anyhow::Result;
+use crate::report::{estimate_report, io_summary, OutputFormat};
+use crate::utils::{absolute_path, format_elapsed_time};
+use anyhow::{Context, Result};
 use colored::Colorize;
 use mutant_protocol::{StorageMode, TaskResult};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+/// Prints what storing the file would take, without writing anything.
+pub async fn handle_put_dry_run(
+    key: String,
+    file: String,
+    public: bool,
+    mode: StorageMode,
+    output: OutputFormat,
+) -> Result<()> {
+    let path = absolute_path(Path::new(&file))?;
+    let data_len = std::fs::metadata(&path)
+        .with_context(|| format!("Could not read {}", path))?
+        .len();
+
+    let mut client = connect_to_daemon().await?;
+    let estimate = client
+        .estimate_store(&key, data_len as usize, mode, public)
+        .await?;
+
+    output.print(&estimate, &estimate_report(&estimate))
+}
+
 pub async fn handle_put(
     key: String,
     file: String,
@@ -15,7 +37,7 @@ pub async fn handle_put(
     mode: StorageMode,
     no_verify: bool,
     background: bool,
-    quiet: bool,
+    progress: &ProgressManager,
 ) -> Result<()> {
     // Convert to absolute path to ensure daemon can find the file
     let path_buf = PathBuf::from(&file);
@@ -25,6 +47,9 @@ pub async fn handle_put(
         std::env::current_dir()?.join(path_buf)
     };
     let source_path = absolute_path.to_string_lossy().to_string();
+    let bytes_total = std::fs::metadata(&absolute_path)
+        .with_context(|| format!("Could not read {}", absolute_path.display()))?
+        .len();
 
     if background {
         let _ = tokio::spawn(async move {
@@ -50,52 +75,65 @@ pub async fn handle_put(
         .put(&key, &source_path, mode, public, no_verify)
         .await?;
 
-    // Create the progress bar wrapper
-    // Keep it in scope until the end of the function to ensure progress bars are properly cleaned up
-    let progress = if !quiet {
-        let progress = ProgressWrapper::new();
-        callbacks::put::create_put_progress(progress_rx, progress.multi_progress().clone());
-        Some(progress)
-    } else {
-        None
-    };
+    progress.set_label(&key);
+    callbacks::put::create_put_progress(progress_rx, Some(bytes_total), progress);
 
     match start_task.await {
         Ok(result) => match result {
             TaskResult::Error(error) => {
-                eprintln!("{} {}", "Error:".bright_red(), error);
+                progress.eprintln(format!("{} {}", "Error:".bright_red(), error));
             }
             TaskResult::Result(result) => {
                 // Calculate and format elapsed time
                 let time_str = format_elapsed_time(start_time.elapsed());
 
-                // Ensure progress bars are cleared before displaying final message
-                if let Some(p) = &progress {
-                    ensure_progress_cleared(p.multi_progress());
-                }
+                progress.clear();
+                progress.println(format!(
+                    "{} Upload complete! (took {})",
+                    "•".bright_green(),
+                    time_str
+                ));
 
-                println!("{} Upload complete! (took {})", "•".bright_green(), time_str);
+                if let mutant_protocol::TaskResultType::Put(put_result) = result {
+                    if let (Some(unchanged), Some(rewritten)) =
+                        (put_result.chunks_unchanged, put_result.chunks_rewritten)
+                    {
+                        progress.println(format!(
+                            "{} Updated in place: {} chunks rewritten, {} unchanged",
+                            "•".bright_blue(),
+                            rewritten,
+                            unchanged
+                        ));
+                    }
+
+                    if let Some(io) = &put_result.io {
+                        progress.println(format!(
+                            "{} Network I/O: {}",
+                            "•".bright_blue(),
+                            io_summary(io, bytes_total)
+                        ));
+                    }
 
-                // If this is a public key, display the index address
-                if public {
-                    if let mutant_protocol::TaskResultType::Put(put_result) = result {
+                    // If this is a public key, display the index address
+                    if public {
                         if let Some(public_address) = put_result.public_address {
-                            println!("{} Public index address: {}", "•".bright_blue(), public_address);
+                            progress.println(format!(
+                                "{} Public index address: {}",
+                                "•".bright_blue(),
+                                public_address
+                            ));
                         }
                     }
                 }
             }
             TaskResult::Pending => {
-                println!("{} Upload pending.", "•".bright_yellow());
+                progress.println(format!("{} Upload pending.", "•".bright_yellow()));
             }
         },
         Err(e) => {
-            eprintln!("{} Task failed: {}", "Error:".bright_red(), e);
+            progress.eprintln(format!("{} Task failed: {}", "Error:".bright_red(), e));
         }
     }
 
-    // // Keep the MultiProgress instance alive until the task is complete
-    // drop(multi_progress);
-
     Ok(())
 }
@@ -0,0 +1,99 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::connect_to_daemon;
+use crate::utils::{absolute_path, format_elapsed_time};
+use anyhow::{anyhow, bail, Result};
+use colored::Colorize;
+use mutant_protocol::StorageMode;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// The `(key, file)` pairs of a manifest, one `key<TAB>file` per line, skipping blank
+/// lines. Relative files are taken from the current directory, the daemon reading them
+/// from wherever it runs.
+fn parse_manifest(content: &str) -> Result<Vec<(String, String)>> {
+    let mut items = Vec::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (key, file) = line
+            .split_once('\t')
+            .filter(|(key, file)| !key.is_empty() && !file.is_empty())
+            .ok_or_else(|| anyhow!("Line {} is not `key<TAB>file`: {}", number + 1, line))?;
+        items.push((key.to_string(), absolute_path(Path::new(file))?));
+    }
+
+    Ok(items)
+}
+
+/// Stores every key of `manifest` in one batch, the index being saved once at the end.
+/// Fails, and so exits with code 1, when any key could not be stored.
+pub async fn handle_put_many(
+    manifest: PathBuf,
+    mode: StorageMode,
+    fail_fast: bool,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let content = std::fs::read_to_string(&manifest)
+        .map_err(|e| anyhow!("Failed to read {}: {}", manifest.display(), e))?;
+    let items = parse_manifest(&content)?;
+    if items.is_empty() {
+        progress.println(format!("No keys to store in {}.", manifest.display()));
+        return Ok(());
+    }
+
+    let total = items.len();
+    let start_time = Instant::now();
+    let mut client = connect_to_daemon().await?;
+    let outcome = client.store_many(items, mode, fail_fast).await?;
+    let failures = outcome.failures();
+
+    progress.println(format!(
+        "{} {} of {} keys stored in {}.",
+        if failures.is_empty() {
+            "•".bright_green()
+        } else {
+            "•".bright_yellow()
+        },
+        total - failures.len(),
+        total,
+        format_elapsed_time(start_time.elapsed())
+    ));
+
+    if !failures.is_empty() {
+        for (key, reason) in &failures {
+            progress.eprintln(format!("{} {}: {}", "Failed:".bright_red(), key, reason));
+        }
+        bail!("{} of {} keys could not be stored", failures.len(), total);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let items =
+            parse_manifest("notes\t/data/notes.txt\n\nphotos/a.jpg\t/data/a b.jpg\r\n").unwrap();
+        assert_eq!(
+            items,
+            vec![
+                ("notes".to_string(), "/data/notes.txt".to_string()),
+                ("photos/a.jpg".to_string(), "/data/a b.jpg".to_string()),
+            ]
+        );
+
+        let relative = parse_manifest("key\tfile.txt").unwrap();
+        assert!(Path::new(&relative[0].1).is_absolute());
+
+        let error = parse_manifest("ok\t/a\nno tab here\n").unwrap_err();
+        assert!(error.to_string().contains("Line 2"), "{}", error);
+        assert!(parse_manifest("\t/a").is_err());
+        assert!(parse_manifest("key\t").is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::callbacks::progress::{ProgressManager, StyledProgressBar};
+use crate::connect_to_daemon;
+use crate::report::{recover_report, OutputFormat};
+use anyhow::{Context, Result};
+use mutant_protocol::RecoverResponse;
+use std::path::Path;
+
+/// Reads the key names of `keys_from`, one per line, trimmed, skipping blank lines.
+fn read_key_hints(keys_from: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(keys_from)
+        .with_context(|| format!("Could not read key names from {}", keys_from.display()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+pub async fn handle_recover(
+    keys_from: &Path,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let key_hints = read_key_hints(keys_from)?;
+    let mut client = connect_to_daemon().await?;
+
+    let pb = StyledProgressBar::new_for_steps(progress);
+    pb.set_message("Scanning pads...".to_string());
+    pb.set_length(key_hints.len() as u64);
+
+    // One key per request, so that the bar moves as keys get scanned.
+    let mut summary = RecoverResponse::default();
+    for key in key_hints {
+        let response = client.recover(vec![key]).await?;
+        summary.recovered.extend(response.recovered);
+        summary.missing.extend(response.missing);
+        summary.skipped.extend(response.skipped);
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    progress.suspend(|| output.print(&summary, &recover_report(&summary)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_key_hints_skips_blank_lines() {
+        let path = std::env::temp_dir().join(format!("mutant-keys-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "photos/a.jpg\n\n  notes.txt  \n").unwrap();
+
+        assert_eq!(
+            read_key_hints(&path).unwrap(),
+            vec!["photos/a.jpg", "notes.txt"]
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
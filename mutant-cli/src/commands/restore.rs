@@ -0,0 +1,23 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::connect_to_daemon;
+use anyhow::Result;
+use colored::Colorize;
+
+pub async fn handle_restore(key: String, progress: &ProgressManager) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    match client.restore(&key).await {
+        Ok(response) => {
+            progress.println(format!(
+                "{} Key '{}' restored from the trash.",
+                "•".bright_green(),
+                response.user_key
+            ));
+        }
+        Err(e) => {
+            progress.eprintln(format!("{} {}", "Error:".bright_red(), e));
+        }
+    }
+
+    Ok(())
+}
@@ -1,19 +1,45 @@
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
 use anyhow::Result;
 use colored::Colorize;
 
-pub async fn handle_rm(key: String) -> Result<()> {
+pub async fn handle_rm(key: String, promote_alias: bool, progress: &ProgressManager) -> Result<()> {
     let mut client = connect_to_daemon().await?;
 
-    match client.rm(&key).await {
+    let result = if promote_alias {
+        client.rm_promoting_alias(&key).await
+    } else {
+        client.rm(&key).await
+    };
+
+    match result {
+        Ok(report) if report.existed => {
+            progress.println(format!("{} Key '{}' removed.", "•".bright_green(), key));
+        }
+        Ok(report) if report.retried => {
+            // The interrupted attempt may have removed it, or it was already gone.
+            progress.println(format!(
+                "{} Key '{}' is gone, the connection dropped while removing it.",
+                "•".bright_green(),
+                key
+            ));
+        }
         Ok(_) => {
-            println!("{} Key '{}' removed.", "•".bright_green(), key);
+            progress.println(format!(
+                "{} Key '{}' not found, nothing removed.",
+                "•".yellow(),
+                key
+            ));
         }
         Err(e) => {
             if e.to_string().contains("not found") {
-                eprintln!("{} Key '{}' not found.", "Error:".bright_red(), key);
+                progress.eprintln(format!(
+                    "{} Key '{}' not found.",
+                    "Error:".bright_red(),
+                    key
+                ));
             } else {
-                eprintln!("{} {}", "Error:".bright_red(), e);
+                progress.eprintln(format!("{} {}", "Error:".bright_red(), e));
             }
             // Don't propagate the error to allow the CLI to exit cleanly
         }
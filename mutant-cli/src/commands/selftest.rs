@@ -0,0 +1,566 @@
+use crate::commands::bench::random_payload;
+use crate::connect_to_daemon;
+use crate::report::{selftest_report, OutputFormat};
+use anyhow::{anyhow, bail, Result};
+use mutant_client::MutantClient;
+use mutant_protocol::{InitOutcome, PutEvent, StorageMode, TaskProgress, TaskResult};
+use serde::Serialize;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest a stage may take before it is failed, so that a stuck daemon does not hang
+/// the run.
+const STAGE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Smallest pads, so that the run costs as little as a store can.
+const MODE: StorageMode = StorageMode::Lightest;
+
+/// The stages of `mutant selftest`, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stage {
+    Connect,
+    Wallet,
+    Network,
+    Index,
+    Reserve,
+    Store,
+    Fetch,
+    Update,
+    Remove,
+    Purge,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Connect => "Connect",
+            Stage::Wallet => "Wallet",
+            Stage::Network => "Network",
+            Stage::Index => "Index",
+            Stage::Reserve => "Reserve",
+            Stage::Store => "Store",
+            Stage::Fetch => "Fetch",
+            Stage::Update => "Update",
+            Stage::Remove => "Remove",
+            Stage::Purge => "Purge",
+        }
+    }
+
+    /// What to try when the stage fails.
+    fn hint(self) -> &'static str {
+        match self {
+            Stage::Connect => {
+                "Start the daemon with `mutant daemon start`, or run `mutant-daemon` in a terminal to see why it exits."
+            }
+            Stage::Wallet => {
+                "Check the private key the daemon was set up with and send tokens and gas to its wallet."
+            }
+            Stage::Network => {
+                "Check your connection. With --local, restart the daemon on the local devnet: `mutant daemon stop`, then `mutant-daemon --local`."
+            }
+            Stage::Index => {
+                "The local index cache could not be read: `mutant cache clean` lets the daemon rebuild it from the network."
+            }
+            Stage::Reserve => "No new pad could be created: the wallet may lack gas.",
+            Stage::Store => {
+                "Writing the pads failed: run `mutant put` again with RUST_LOG=debug to see which write fails, interrupted stores resume."
+            }
+            Stage::Fetch => {
+                "The value read back failed or differs from what was stored: `mutant verify <key>` shows the damaged chunks."
+            }
+            Stage::Update => {
+                "Rewriting an existing key failed: run `mutant put` on it again with RUST_LOG=debug."
+            }
+            Stage::Remove => "The key could not be removed: `mutant rm <key>` it by hand.",
+            Stage::Purge => {
+                "The pads pending verification could not be checked, `mutant purge` can be run again later."
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StageOutcome {
+    Passed,
+    Failed,
+    /// An earlier stage failed, or there was nothing for the stage to do.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageResult {
+    pub stage: Stage,
+    pub outcome: StageOutcome,
+    pub elapsed_ms: u64,
+    /// What went wrong, or why the stage was skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+}
+
+/// What the report of a support request needs to know about the setup.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelftestEnvironment {
+    pub cli_version: &'static str,
+    /// Version of the mutant-lib the daemon runs, `None` when it could not be asked.
+    pub lib_version: Option<String>,
+    pub network: Option<String>,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub local: bool,
+}
+
+/// Outcome of a `mutant selftest` run.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelftestResult {
+    pub environment: SelftestEnvironment,
+    pub stages: Vec<StageResult>,
+}
+
+impl SelftestResult {
+    pub fn passed(&self) -> bool {
+        self.stages
+            .iter()
+            .all(|stage| stage.outcome != StageOutcome::Failed)
+    }
+}
+
+/// The stages run so far. Once one failed, the next ones are skipped.
+struct Run {
+    stages: Vec<StageResult>,
+    failed: bool,
+}
+
+impl Run {
+    /// Runs `stage` unless an earlier one failed, `None` when it did not pass.
+    async fn stage<T>(&mut self, stage: Stage, run: impl Future<Output = Result<T>>) -> Option<T> {
+        if self.failed {
+            self.skip(stage, "an earlier stage failed");
+            return None;
+        }
+
+        let start = Instant::now();
+        let result = tokio::time::timeout(STAGE_TIMEOUT, run)
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("timed out after {}s", STAGE_TIMEOUT.as_secs())));
+        match result {
+            Ok(value) => {
+                self.record(stage, start.elapsed(), Ok(()));
+                Some(value)
+            }
+            Err(e) => {
+                self.record(stage, start.elapsed(), Err(e));
+                None
+            }
+        }
+    }
+
+    fn record(&mut self, stage: Stage, elapsed: Duration, result: Result<()>) {
+        let (outcome, detail, hint) = match result {
+            Ok(()) => (StageOutcome::Passed, None, None),
+            Err(e) => {
+                self.failed = true;
+                (
+                    StageOutcome::Failed,
+                    Some(e.to_string()),
+                    Some(stage.hint()),
+                )
+            }
+        };
+        self.stages.push(StageResult {
+            stage,
+            outcome,
+            elapsed_ms: elapsed.as_millis() as u64,
+            detail,
+            hint,
+        });
+    }
+
+    fn skip(&mut self, stage: Stage, reason: &str) {
+        self.stages.push(StageResult {
+            stage,
+            outcome: StageOutcome::Skipped,
+            elapsed_ms: 0,
+            detail: Some(reason.to_string()),
+            hint: None,
+        });
+    }
+}
+
+/// When the pads of a store were reserved, from its progress events.
+#[derive(Default)]
+struct Reservation {
+    chunks_to_reserve: Option<usize>,
+    first_reserved: Option<Instant>,
+}
+
+/// Exits with code 0 when every stage passed and 1 otherwise.
+pub async fn handle_selftest(local: bool, output: OutputFormat) -> Result<()> {
+    let key = format!("mutant-selftest-{}", uuid::Uuid::new_v4().simple());
+    let temp_dir = std::env::temp_dir();
+    let paths = SelftestPaths {
+        value: temp_dir.join(format!("{}.src", key)),
+        update: temp_dir.join(format!("{}.upd", key)),
+        fetched: temp_dir.join(format!("{}.dst", key)),
+    };
+
+    let result = run_selftest(&key, &paths, local).await;
+    for path in [&paths.value, &paths.update, &paths.fetched] {
+        std::fs::remove_file(path).unwrap_or_default();
+    }
+
+    output.print(&result, &selftest_report(&result))?;
+
+    if !result.passed() {
+        bail!("The self test failed");
+    }
+
+    Ok(())
+}
+
+struct SelftestPaths {
+    value: PathBuf,
+    update: PathBuf,
+    fetched: PathBuf,
+}
+
+/// Goes through the public client API only, as any application of the daemon would.
+async fn run_selftest(key: &str, paths: &SelftestPaths, local: bool) -> SelftestResult {
+    let mut run = Run {
+        stages: Vec::new(),
+        failed: false,
+    };
+    let mut environment = SelftestEnvironment {
+        cli_version: env!("CARGO_PKG_VERSION"),
+        lib_version: None,
+        network: None,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        local,
+    };
+
+    // The connection only fails on the first request, made by this stage.
+    let connected = run
+        .stage(Stage::Connect, async {
+            let mut client = connect_to_daemon().await?;
+            let outcome = client.init_report().await?;
+            Ok((client, outcome))
+        })
+        .await;
+    let Some((mut client, outcome)) = connected else {
+        return finish(run, environment, &[Stage::Wallet, Stage::Network]);
+    };
+    environment.lib_version = outcome.lib_version.clone();
+    environment.network = outcome.network.clone();
+
+    run.stage(Stage::Wallet, async { check_wallet(&outcome) })
+        .await;
+    run.stage(Stage::Network, async { check_network(&outcome, local) })
+        .await;
+    run.stage(Stage::Index, async {
+        client.get_stats().await?;
+        client.list_keys_with_prefix(key).await?;
+        Ok(())
+    })
+    .await;
+
+    let chunk_size = MODE.scratchpad_size();
+    let value = random_payload(chunk_size * 2 + chunk_size / 2);
+    let update = random_payload(chunk_size / 4);
+    let reservation = Arc::new(Mutex::new(Reservation::default()));
+    let store_start = Instant::now();
+    run.stage(Stage::Store, async {
+        std::fs::write(&paths.value, &value)?;
+        std::fs::write(&paths.update, &update)?;
+        store(&mut client, key, &paths.value, reservation.clone()).await
+    })
+    .await;
+    record_reservation(&mut run, &reservation.lock().unwrap(), store_start);
+
+    run.stage(Stage::Fetch, async {
+        fetch_and_compare(&mut client, key, &paths.fetched, &value).await?;
+        let verified = client.verify(key).await?.result;
+        if let Some(failure) = verified.failures.first() {
+            bail!(
+                "chunk {} of {} is damaged: {}",
+                failure.chunk_index,
+                verified.total_chunks,
+                failure.reason
+            );
+        }
+        Ok(())
+    })
+    .await;
+    run.stage(Stage::Update, async {
+        store(&mut client, key, &paths.update, Arc::default()).await?;
+        fetch_and_compare(&mut client, key, &paths.fetched, &update).await
+    })
+    .await;
+
+    // The key is removed even after a failure, the run leaves nothing behind.
+    let store_ran = run
+        .stages
+        .iter()
+        .any(|stage| stage.stage == Stage::Store && stage.outcome != StageOutcome::Skipped);
+    if run.failed && store_ran {
+        if let Err(e) = remove(&mut client, key).await {
+            log::warn!("Could not remove the selftest key '{}': {}", key, e);
+        }
+    }
+    run.stage(Stage::Remove, remove(&mut client, key)).await;
+    run.stage(Stage::Purge, async {
+        let (task, _progress) = client.purge(false).await?;
+        task_result(task.await?)
+    })
+    .await;
+
+    finish(run, environment, &[])
+}
+
+/// Skips the stages that depended on what could not be done.
+fn finish(mut run: Run, environment: SelftestEnvironment, skipped: &[Stage]) -> SelftestResult {
+    for stage in skipped {
+        run.skip(*stage, "an earlier stage failed");
+    }
+    SelftestResult {
+        environment,
+        stages: run.stages,
+    }
+}
+
+fn check_wallet(outcome: &InitOutcome) -> Result<()> {
+    match outcome.wallet_funded {
+        Some(true) => Ok(()),
+        Some(false) => bail!("the wallet has no tokens or no gas"),
+        None => bail!("the wallet balance could not be read"),
+    }
+}
+
+fn check_network(outcome: &InitOutcome, local: bool) -> Result<()> {
+    if !outcome.remote_reachable {
+        bail!("the network could not be reached");
+    }
+    if local && outcome.network.as_deref() != Some("Devnet") {
+        bail!(
+            "the daemon runs on {}, not on the local devnet",
+            outcome.network.as_deref().unwrap_or("an unknown network")
+        );
+    }
+    Ok(())
+}
+
+/// The reservation happens within the store, it is timed from its progress events and
+/// reported before it.
+fn record_reservation(run: &mut Run, reservation: &Reservation, store_start: Instant) {
+    let Some(store) = run.stages.pop() else {
+        return;
+    };
+
+    match (reservation.chunks_to_reserve, reservation.first_reserved) {
+        _ if store.outcome == StageOutcome::Skipped => {
+            run.skip(Stage::Reserve, "an earlier stage failed")
+        }
+        (_, Some(at)) => run.record(
+            Stage::Reserve,
+            at.saturating_duration_since(store_start),
+            Ok(()),
+        ),
+        (Some(0), None) => run.skip(Stage::Reserve, "the store reused free pads"),
+        (Some(_), None) if store.outcome == StageOutcome::Failed => run.record(
+            Stage::Reserve,
+            store_start.elapsed(),
+            Err(anyhow!("the store failed before reserving a pad")),
+        ),
+        _ => run.skip(Stage::Reserve, "no pad reservation was reported"),
+    }
+    run.stages.push(store);
+}
+
+async fn store(
+    client: &mut MutantClient,
+    key: &str,
+    path: &Path,
+    reservation: Arc<Mutex<Reservation>>,
+) -> Result<()> {
+    let (task, mut progress_rx) = client
+        .put(key, &path.to_string_lossy(), MODE, false, false)
+        .await?;
+    let tracker = tokio::spawn(async move {
+        while let Some(Ok(progress)) = progress_rx.recv().await {
+            let mut reservation = reservation.lock().unwrap();
+            match progress {
+                TaskProgress::Put(PutEvent::Starting {
+                    chunks_to_reserve, ..
+                }) => reservation.chunks_to_reserve = Some(chunks_to_reserve),
+                TaskProgress::Put(PutEvent::PadReserved) => {
+                    reservation.first_reserved.get_or_insert_with(Instant::now);
+                }
+                _ => {}
+            }
+        }
+    });
+
+    let result = task.await;
+    tracker.abort();
+    task_result(result?)
+}
+
+async fn fetch_and_compare(
+    client: &mut MutantClient,
+    key: &str,
+    destination: &Path,
+    expected: &[u8],
+) -> Result<()> {
+    let (task, _progress) = client
+        .get(key, &destination.to_string_lossy(), false, None)
+        .await?;
+    task_result(task.await?)?;
+
+    let fetched = std::fs::read(destination)?;
+    if fetched != expected {
+        bail!(
+            "fetched {} bytes that differ from the {} bytes stored",
+            fetched.len(),
+            expected.len()
+        );
+    }
+    Ok(())
+}
+
+async fn remove(client: &mut MutantClient, key: &str) -> Result<()> {
+    let report = client.rm(key).await?;
+    if !report.existed && !report.retried {
+        bail!("the key was not found");
+    }
+    Ok(())
+}
+
+fn task_result(result: TaskResult) -> Result<()> {
+    match result {
+        TaskResult::Result(_) => Ok(()),
+        TaskResult::Error(error) => bail!("{}", error),
+        TaskResult::Pending => bail!("the task did not complete"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(network: &str) -> InitOutcome {
+        InitOutcome {
+            new_store: false,
+            wallet_funded: Some(true),
+            remote_reachable: true,
+            incomplete_keys: Vec::new(),
+            network: Some(network.to_string()),
+            lib_version: Some("0.6.2".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_local_run_needs_a_devnet_daemon() {
+        assert!(check_network(&outcome("Devnet"), true).is_ok());
+        assert!(check_network(&outcome("Mainnet"), false).is_ok());
+        let error = check_network(&outcome("Mainnet"), true).unwrap_err();
+        assert!(error.to_string().contains("not on the local devnet"));
+
+        let offline = InitOutcome {
+            remote_reachable: false,
+            ..outcome("Devnet")
+        };
+        assert!(check_network(&offline, true).is_err());
+        let unfunded = InitOutcome {
+            wallet_funded: Some(false),
+            ..outcome("Devnet")
+        };
+        assert!(check_wallet(&unfunded).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stages_after_a_failure_are_skipped() {
+        let mut run = Run {
+            stages: Vec::new(),
+            failed: false,
+        };
+        assert_eq!(run.stage(Stage::Connect, async { Ok(1) }).await, Some(1));
+        assert_eq!(
+            run.stage(Stage::Wallet, async { Err::<(), _>(anyhow!("no gas")) })
+                .await,
+            None
+        );
+        let mut reached = false;
+        run.stage(Stage::Network, async {
+            reached = true;
+            Ok(())
+        })
+        .await;
+        assert!(!reached);
+
+        let result = finish(run, environment(), &[Stage::Index]);
+        let outcomes: Vec<_> = result
+            .stages
+            .iter()
+            .map(|stage| (stage.stage, stage.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (Stage::Connect, StageOutcome::Passed),
+                (Stage::Wallet, StageOutcome::Failed),
+                (Stage::Network, StageOutcome::Skipped),
+                (Stage::Index, StageOutcome::Skipped),
+            ]
+        );
+        assert_eq!(result.stages[1].hint, Some(Stage::Wallet.hint()));
+        assert!(!result.passed());
+    }
+
+    #[test]
+    fn test_reservation_is_reported_before_the_store() {
+        let mut run = Run {
+            stages: Vec::new(),
+            failed: false,
+        };
+        let start = Instant::now();
+        run.record(Stage::Store, Duration::from_millis(30), Ok(()));
+        let reservation = Reservation {
+            chunks_to_reserve: Some(3),
+            first_reserved: Some(start + Duration::from_millis(10)),
+        };
+
+        record_reservation(&mut run, &reservation, start);
+        let stages: Vec<_> = run.stages.iter().map(|stage| stage.stage).collect();
+        assert_eq!(stages, vec![Stage::Reserve, Stage::Store]);
+        assert_eq!(run.stages[0].elapsed_ms, 10);
+        assert_eq!(run.stages[0].outcome, StageOutcome::Passed);
+
+        let mut run = Run {
+            stages: Vec::new(),
+            failed: false,
+        };
+        run.record(Stage::Store, Duration::ZERO, Ok(()));
+        let reused = Reservation {
+            chunks_to_reserve: Some(0),
+            first_reserved: None,
+        };
+        record_reservation(&mut run, &reused, start);
+        assert_eq!(run.stages[0].outcome, StageOutcome::Skipped);
+    }
+
+    fn environment() -> SelftestEnvironment {
+        SelftestEnvironment {
+            cli_version: "0.6.2",
+            lib_version: None,
+            network: None,
+            os: "linux",
+            arch: "x86_64",
+            local: false,
+        }
+    }
+}
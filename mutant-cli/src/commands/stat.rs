@@ -0,0 +1,28 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::commands::ls::{print_key, print_key_header, ListedKey};
+use crate::connect_to_daemon;
+use crate::report::{render_json, OutputFormat};
+use anyhow::{bail, Result};
+
+/// Prints the `ls -l` row of a single key, or its entry of `ls --json`. Fails, and so exits
+/// with code 1, when there is no such key.
+pub async fn handle_stat(
+    key: String,
+    output: OutputFormat,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+
+    match client.stat(&key).await? {
+        Some(detail) if output.json => {
+            progress.println(render_json(&ListedKey::from(&detail))?);
+        }
+        Some(detail) => progress.suspend(|| {
+            print_key_header();
+            print_key(detail, true);
+        }),
+        None => bail!("Key '{}' not found.", key),
+    }
+
+    Ok(())
+}
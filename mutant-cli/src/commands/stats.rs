@@ -1,17 +1,19 @@
 use crate::connect_to_daemon;
-use anyhow::Result;
+use crate::report::{io_report, stats_report, OutputFormat};
+use anyhow::{anyhow, Result};
 
-pub async fn handle_stats() -> Result<()> {
+pub async fn handle_stats(io: bool, output: OutputFormat) -> Result<()> {
     let mut client = connect_to_daemon().await?;
     let stats = client.get_stats().await?;
 
-    println!("Storage Statistics:");
-    println!("-------------------");
-    println!("Total Keys: {}", stats.total_keys);
-    println!("Total Pads Managed:    {}", stats.total_pads);
-    println!("  Occupied (Private):  {}", stats.occupied_pads);
-    println!("  Free Pads:           {}", stats.free_pads);
-    println!("  Pending Verify Pads: {}", stats.pending_verify_pads);
+    if io {
+        let io = stats.io.ok_or_else(|| {
+            anyhow!("The daemon does not count its network calls, restart it with MUTANT_IO_ACCOUNTING=1")
+        })?;
+        output.print(&io, &io_report(&io))?;
+    } else {
+        output.print(&stats, &stats_report(&stats))?;
+    }
 
     Ok(())
 }
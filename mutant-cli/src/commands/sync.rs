@@ -1,16 +1,49 @@
 use crate::callbacks;
-use crate::callbacks::progress::ProgressWrapper;
+use crate::callbacks::progress::ProgressManager;
 use crate::connect_to_daemon;
 use anyhow::Result;
 use colored::Colorize;
+use mutant_client::error::ClientError;
+use mutant_client::{MutantClient, ProgressReceiver};
+use mutant_protocol::SyncDirection;
 use mutant_protocol::TaskResult;
 use mutant_protocol::TaskResultType;
+use std::future::Future;
+use std::pin::Pin;
+
+type SyncTask<'a> = Pin<Box<dyn Future<Output = Result<TaskResult, ClientError>> + Send + 'a>>;
+
+/// Starts a full sync, or a selective one when `only` holds patterns.
+async fn start_sync(
+    client: &mut MutantClient,
+    push_force: bool,
+    only: Vec<String>,
+    direction: SyncDirection,
+) -> Result<(SyncTask<'_>, ProgressReceiver), ClientError> {
+    if only.is_empty() {
+        let (start_task, progress_rx) = client.sync(push_force).await?;
+        Ok((Box::pin(start_task), progress_rx))
+    } else {
+        let (start_task, progress_rx) = client.sync_keys(only, direction).await?;
+        Ok((Box::pin(start_task), progress_rx))
+    }
+}
+
+pub async fn handle_sync(
+    background: bool,
+    push_force: bool,
+    only: Vec<String>,
+    direction: SyncDirection,
+    progress: &ProgressManager,
+) -> Result<()> {
+    let pushes = only.is_empty() || direction != SyncDirection::Pull;
 
-pub async fn handle_sync(background: bool, push_force: bool, quiet: bool) -> Result<()> {
     if background {
         let _ = tokio::spawn(async move {
             let mut client = connect_to_daemon().await.unwrap();
-            let (start_task, _progress_rx) = client.sync(push_force).await.unwrap();
+            let (start_task, _progress_rx) = start_sync(&mut client, push_force, only, direction)
+                .await
+                .unwrap();
             start_task.await.unwrap();
         });
 
@@ -20,42 +53,54 @@ pub async fn handle_sync(background: bool, push_force: bool, quiet: bool) -> Res
     }
 
     let mut client = connect_to_daemon().await?;
-    let (start_task, progress_rx) = client.sync(push_force).await?;
-
-    // Create the progress bar wrapper
-    // Keep it in scope until the end of the function to ensure progress bars are properly cleaned up
-    let _progress = if !quiet {
-        let progress = ProgressWrapper::new();
-        callbacks::sync::create_sync_progress(progress_rx, progress.multi_progress());
-        Some(progress)
-    } else {
-        None
-    };
+    let (start_task, progress_rx) = start_sync(&mut client, push_force, only, direction).await?;
+
+    callbacks::sync::create_sync_progress(progress_rx, progress);
 
     match start_task.await {
         Ok(result) => match result {
             TaskResult::Error(error) => {
-                eprintln!("{} {}", "Error:".bright_red(), error);
+                progress.eprintln(format!("{} {}", "Error:".bright_red(), error));
             }
             TaskResult::Result(result) => match result {
                 TaskResultType::Sync(result) => {
-                    println!("Synchronization complete.");
-                    println!("  {} keys added", result.nb_keys_added);
-                    println!("  {} keys updated", result.nb_keys_updated);
-                    println!("  {} free pads added", result.nb_free_pads_added);
-                    println!("  {} pending pads added", result.nb_pending_pads_added);
-                    println!("{} Sync task completed.", "•".bright_green());
+                    progress.suspend(|| {
+                        println!("Synchronization complete.");
+                        println!("  {} keys added", result.nb_keys_added);
+                        println!("  {} keys updated", result.nb_keys_updated);
+                        println!("  {} free pads added", result.nb_free_pads_added);
+                        println!("  {} pending pads added", result.nb_pending_pads_added);
+                        if result.nb_keys_trashed > 0 {
+                            println!("  {} keys moved to the trash", result.nb_keys_trashed);
+                        }
+                        if result.nb_keys_restored > 0 {
+                            println!("  {} keys restored from the trash", result.nb_keys_restored);
+                        }
+                        if !pushes {
+                            println!("{} Remote index left untouched.", "•".bright_blue());
+                        } else if result.index_verified {
+                            println!(
+                                "{} Remote index pushed and verified.",
+                                "•".bright_green()
+                            );
+                        } else {
+                            println!(
+                                "{} Remote index pushed, not read back (MUTANT_VERIFY_INDEX_PUSH is off).",
+                                "•".bright_yellow()
+                            );
+                        }
+                    });
                 }
                 _ => {
-                    eprintln!("{} {}", "Error:".bright_red(), "Unknown task result");
+                    progress.eprintln(format!("{} Unknown task result", "Error:".bright_red()));
                 }
             },
             TaskResult::Pending => {
-                println!("{} Sync task pending.", "•".bright_yellow());
+                progress.println(format!("{} Sync task pending.", "•".bright_yellow()));
             }
         },
         Err(e) => {
-            eprintln!("{} Task failed: {}", "Error:".bright_red(), e);
+            progress.eprintln(format!("{} Task failed: {}", "Error:".bright_red(), e));
         }
     }
 
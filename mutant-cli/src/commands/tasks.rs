@@ -1,9 +1,11 @@
+use crate::callbacks::progress::ProgressManager;
 use crate::{cli::TasksCommands, connect_to_daemon};
 use anyhow::Result;
 use colored::Colorize;
-use mutant_protocol::{TaskProgress, TaskResult};
+use mutant_protocol::{TaskProgress, TaskRecord, TaskResult, TaskStatus};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub async fn handle_tasks(command: TasksCommands) -> Result<()> {
+pub async fn handle_tasks(command: TasksCommands, progress: &ProgressManager) -> Result<()> {
     let mut client = connect_to_daemon().await?;
 
     match command {
@@ -11,61 +13,61 @@ pub async fn handle_tasks(command: TasksCommands) -> Result<()> {
             let tasks = client.list_tasks().await?;
 
             for task in tasks {
-                println!(
+                progress.println(format!(
                     "{} {} - {} ({})",
                     "•".bright_green(),
                     task.task_id,
                     format!("{:?}", task.task_type).bright_blue(),
                     format!("{:?}", task.status).bright_yellow()
-                );
+                ));
             }
         }
         TasksCommands::Get { task_id } => {
             let task_id = uuid::Uuid::parse_str(&task_id)?;
             let task = client.query_task(task_id).await?;
 
-            println!("Task: {:#?}", task);
+            progress.println(format!("Task: {:#?}", task));
 
-            println!(
+            progress.println(format!(
                 "{} {} - {} ({})",
                 "•".bright_green(),
                 task.id,
                 format!("{:?}", task.task_type).bright_blue(),
                 format!("{:?}", task.status).bright_yellow()
-            );
+            ));
 
-            if let Some(progress) = task.progress {
-                match progress {
+            if let Some(task_progress) = task.progress {
+                match task_progress {
                     TaskProgress::Put(event) => {
-                        println!("  Progress: {:?}", event);
+                        progress.println(format!("  Progress: {:?}", event));
                     }
                     TaskProgress::Get(event) => {
-                        println!("  Progress: {:?}", event);
+                        progress.println(format!("  Progress: {:?}", event));
                     }
                     TaskProgress::Sync(event) => {
-                        println!("  Progress: {:?}", event);
+                        progress.println(format!("  Progress: {:?}", event));
                     }
                     TaskProgress::Purge(event) => {
-                        println!("  Progress: {:?}", event);
+                        progress.println(format!("  Progress: {:?}", event));
                     }
                     TaskProgress::HealthCheck(event) => {
-                        println!("  Progress: {:?}", event);
+                        progress.println(format!("  Progress: {:?}", event));
                     }
                 }
             }
 
             match task.result {
                 TaskResult::Pending => {
-                    println!("  {}: {}", "Result".bright_yellow(), "Pending");
+                    progress.println(format!("  {}: {}", "Result".bright_yellow(), "Pending"));
                 }
                 TaskResult::Error(error) => {
-                    println!("  {}: {}", "Error".bright_red(), error);
+                    progress.println(format!("  {}: {}", "Error".bright_red(), error));
                 }
                 TaskResult::Result(_result) => {
-                    println!(
+                    progress.println(format!(
                         "  {}: Completed (result stored on daemon)",
                         "Result".bright_green()
-                    );
+                    ));
                 }
             }
         }
@@ -73,7 +75,82 @@ pub async fn handle_tasks(command: TasksCommands) -> Result<()> {
             let task_id = uuid::Uuid::parse_str(&task_id)?;
             client.stop_task(task_id).await?;
         }
+        TasksCommands::History { limit } => {
+            let records = client.task_history(limit).await?;
+            if records.is_empty() {
+                progress.println("No finished tasks.");
+            }
+
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default();
+            for record in records {
+                print_record(&record, now_ms, progress);
+            }
+        }
     }
 
     Ok(())
 }
+
+fn print_record(record: &TaskRecord, now_ms: u64, progress: &ProgressManager) {
+    let status = format!("{:?}", record.status);
+    let status = match record.status {
+        TaskStatus::Completed => status.bright_green(),
+        TaskStatus::Failed => status.bright_red(),
+        _ => status.bright_yellow(),
+    };
+    let took = format_duration(record.finished_at_ms.saturating_sub(record.started_at_ms));
+
+    progress.println(format!(
+        "{} {} - {} {} ({}, {}, took {})",
+        "•".bright_green(),
+        record.task_id,
+        format!("{:?}", record.task_type).bright_blue(),
+        record.key.as_deref().unwrap_or("-"),
+        status,
+        relative_time(record.finished_at_ms, now_ms),
+        took
+    ));
+    if let Some(error) = &record.error {
+        progress.println(format!("  {}: {}", "Error".bright_red(), error));
+    }
+}
+
+/// `finished_at_ms` as seen from `now_ms`, e.g. "5m ago".
+fn relative_time(finished_at_ms: u64, now_ms: u64) -> String {
+    let elapsed_ms = now_ms.saturating_sub(finished_at_ms);
+    if elapsed_ms < 1000 {
+        return "just now".to_string();
+    }
+    format!("{} ago", format_duration(elapsed_ms))
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    match secs {
+        0 => format!("{}ms", ms),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_time() {
+        let now = 10 * 86_400_000;
+        assert_eq!(relative_time(now, now), "just now");
+        assert_eq!(relative_time(now + 5_000, now), "just now");
+        assert_eq!(relative_time(now - 42_000, now), "42s ago");
+        assert_eq!(relative_time(now - 5 * 60_000 - 30_000, now), "5m ago");
+        assert_eq!(relative_time(now - 3 * 3_600_000, now), "3h ago");
+        assert_eq!(relative_time(now - 2 * 86_400_000, now), "2d ago");
+        assert_eq!(format_duration(250), "250ms");
+    }
+}
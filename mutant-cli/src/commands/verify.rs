@@ -0,0 +1,21 @@
+use crate::connect_to_daemon;
+use crate::report::{verify_report, OutputFormat};
+use anyhow::{bail, Result};
+
+/// Exits with code 0 when every chunk of the key is intact and 1 otherwise.
+pub async fn handle_verify(key: String, output: OutputFormat) -> Result<()> {
+    let mut client = connect_to_daemon().await?;
+    let response = client.verify(&key).await?;
+
+    output.print(&response.result, &verify_report(&response.result))?;
+
+    if !response.result.failures.is_empty() {
+        bail!(
+            "{} of the chunks of '{}' failed the verification",
+            response.result.failures.len(),
+            key
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::callbacks::progress::ProgressManager;
+use anyhow::{anyhow, bail, Context, Result};
+use directories::BaseDirs;
+use indicatif::ProgressBar;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const LOCK_FILE_NAME: &str = "cache.lock";
+
+/// How a command uses the local index cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    /// Only reads the cache, any number of these can run together.
+    Shared,
+    /// Changes the cache, runs alone.
+    Exclusive,
+}
+
+/// Advisory lock on the local index cache, released when dropped.
+pub struct CacheLock {
+    _file: File,
+}
+
+/// Gets the path of the lock file, next to the index cache in the mutant data directory.
+fn get_lock_file_path() -> Result<PathBuf> {
+    let base_dirs = BaseDirs::new().ok_or_else(|| anyhow!("Could not find the data directory"))?;
+    let dir = base_dirs.data_dir().join("mutant");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(LOCK_FILE_NAME))
+}
+
+impl CacheLock {
+    /// Takes the lock in `mode`. When another mutant process holds it, waits for it
+    /// behind a spinner, or fails right away if `no_wait` is set.
+    pub async fn acquire(
+        mode: LockMode,
+        no_wait: bool,
+        progress: &ProgressManager,
+    ) -> Result<Self> {
+        let path = get_lock_file_path()?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Could not open the cache lock {}", path.display()))?;
+
+        let attempt = match mode {
+            LockMode::Shared => file.try_lock_shared(),
+            LockMode::Exclusive => file.try_lock(),
+        };
+        match attempt {
+            Ok(()) => return Ok(Self { _file: file }),
+            Err(TryLockError::WouldBlock) if no_wait => {
+                bail!("Another mutant process is using the local cache, run again without --no-wait to wait for it")
+            }
+            Err(TryLockError::WouldBlock) => {}
+            Err(TryLockError::Error(e)) => {
+                return Err(e).context("Could not lock the local cache");
+            }
+        }
+
+        let spinner = progress.add(ProgressBar::new_spinner());
+        spinner.set_message("waiting for other mutant process");
+        spinner.enable_steady_tick(Duration::from_millis(100));
+
+        let file = tokio::task::spawn_blocking(move || {
+            match mode {
+                LockMode::Shared => file.lock_shared(),
+                LockMode::Exclusive => file.lock(),
+            }
+            .map(|_| file)
+        })
+        .await?
+        .context("Could not lock the local cache")?;
+        spinner.finish_and_clear();
+
+        Ok(Self { _file: file })
+    }
+}
@@ -5,6 +5,9 @@ mod callbacks;
 mod cli;
 mod commands;
 mod history;
+mod lock;
+mod onboarding;
+mod report;
 mod utils;
 
 pub use app::connect_to_daemon;
@@ -0,0 +1,167 @@
+use crate::callbacks::progress::ProgressManager;
+use crate::connect_to_daemon;
+use colored::Colorize;
+use directories::ProjectDirs;
+use log::{debug, warn};
+use mutant_protocol::InitOutcome;
+use std::fs;
+use std::path::PathBuf;
+
+const ONBOARDED_FILE_NAME: &str = "onboarded";
+
+/// Gets the path of the file marking that the onboarding hints were shown.
+fn get_onboarded_file_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "MutAnt", "MutAnt")
+        .map(|proj_dirs| proj_dirs.config_dir().join(ONBOARDED_FILE_NAME))
+}
+
+/// What a new user should know about their store before using it.
+fn onboarding_hints(outcome: &InitOutcome) -> Vec<String> {
+    let mut hints = Vec::new();
+
+    if !outcome.remote_reachable {
+        hints.push(
+            "The network could not be reached: check your connection, commands will fail until it is back."
+                .to_string(),
+        );
+    }
+    if outcome.wallet_funded == Some(false) {
+        hints.push(
+            "Your wallet has no tokens or no gas: stores will fail, fetches of public keys work."
+                .to_string(),
+        );
+    }
+    if outcome.new_store && outcome.remote_reachable {
+        hints.push(
+            "This is a new store: put a first value with `mutant put <key> <file>`.".to_string(),
+        );
+    }
+
+    hints
+}
+
+/// One line about the keys the daemon found incomplete at init, if any.
+fn incomplete_notice(outcome: &InitOutcome) -> Option<String> {
+    match outcome.incomplete_keys.len() {
+        0 => None,
+        count => Some(format!(
+            "{} keys have an interrupted upload and cannot be fetched: run `mutant put <key> <file>` again to resume one.",
+            count
+        )),
+    }
+}
+
+/// Prints the onboarding hints the first time the CLI reaches a daemon. Failures are
+/// only logged, the hints are then shown on a later run.
+pub async fn print_hints_once(progress: &ProgressManager) {
+    let Some(path) = get_onboarded_file_path() else {
+        return;
+    };
+    if path.exists() {
+        return;
+    }
+
+    let outcome = match connect_to_daemon().await {
+        Ok(mut client) => client.init_report().await,
+        Err(e) => {
+            debug!("Skipping onboarding hints, daemon unreachable: {}", e);
+            return;
+        }
+    };
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            debug!("Skipping onboarding hints: {}", e);
+            return;
+        }
+    };
+
+    for hint in onboarding_hints(&outcome) {
+        progress.eprintln(format!("{} {}", "Hint:".bright_cyan(), hint));
+    }
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Err(e) = fs::write(&path, b"") {
+        warn!("Failed to record onboarding in {:?}: {}", path, e);
+    }
+}
+
+/// Prints a notice when the daemon found incomplete keys at init. Meant for a daemon
+/// just started, failures are only logged.
+pub async fn print_incomplete_notice(progress: &ProgressManager) {
+    let outcome = match connect_to_daemon().await {
+        Ok(mut client) => client.init_report().await,
+        Err(e) => {
+            debug!("Skipping incomplete keys notice, daemon unreachable: {}", e);
+            return;
+        }
+    };
+    match outcome {
+        Ok(outcome) => {
+            if let Some(notice) = incomplete_notice(&outcome) {
+                progress.eprintln(format!("{} {}", "Notice:".yellow(), notice));
+            }
+        }
+        Err(e) => debug!("Skipping incomplete keys notice: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_onboarding_hints() {
+        let healthy = InitOutcome {
+            new_store: false,
+            wallet_funded: Some(true),
+            remote_reachable: true,
+            incomplete_keys: Vec::new(),
+            network: None,
+            lib_version: None,
+        };
+        assert!(onboarding_hints(&healthy).is_empty());
+        assert_eq!(incomplete_notice(&healthy), None);
+
+        let empty_wallet = InitOutcome {
+            new_store: true,
+            wallet_funded: Some(false),
+            remote_reachable: true,
+            incomplete_keys: Vec::new(),
+            network: None,
+            lib_version: None,
+        };
+        let hints = onboarding_hints(&empty_wallet);
+        assert_eq!(hints.len(), 2);
+        assert!(hints[0].contains("no tokens"));
+
+        let offline = InitOutcome {
+            new_store: true,
+            wallet_funded: None,
+            remote_reachable: false,
+            incomplete_keys: Vec::new(),
+            network: None,
+            lib_version: None,
+        };
+        let hints = onboarding_hints(&offline);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("could not be reached"));
+    }
+
+    #[test]
+    fn test_incomplete_notice() {
+        let outcome = InitOutcome {
+            new_store: false,
+            wallet_funded: Some(true),
+            remote_reachable: true,
+            incomplete_keys: vec!["a".to_string(), "b".to_string()],
+            network: None,
+            lib_version: None,
+        };
+        let notice = incomplete_notice(&outcome).unwrap();
+        assert!(notice.starts_with("2 keys have an interrupted upload"));
+        assert!(notice.contains("mutant put"));
+    }
+}
@@ -0,0 +1,816 @@
+use crate::commands::bench::{BenchResult, Percentiles};
+use crate::commands::selftest::{SelftestResult, StageOutcome};
+use anyhow::Result;
+use humansize::{format_size, BINARY};
+use colored::Colorize;
+use mutant_protocol::{
+    HealthCheckResult, IoStats, PadExistsResponse, PurgeResult, RecoverResponse, StatsResponse,
+    StoreEstimate, VerifyResult,
+};
+use serde::Serialize;
+use std::io::IsTerminal;
+
+/// How important a report line is, used to color the value column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportRow {
+    pub label: String,
+    pub value: String,
+    pub severity: Severity,
+}
+
+/// A diagnostic report rendered as a two columns table.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    pub rows: Vec<ReportRow>,
+}
+
+impl Report {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn row(mut self, label: &str, value: impl ToString, severity: Severity) -> Self {
+        self.rows.push(ReportRow {
+            label: label.to_string(),
+            value: value.to_string(),
+            severity,
+        });
+        self
+    }
+}
+
+/// Output settings shared by every diagnostic command.
+#[derive(Debug, Clone, Copy)]
+pub struct OutputFormat {
+    pub json: bool,
+    pub color: bool,
+}
+
+impl OutputFormat {
+    /// Colors are disabled with `--no-color` or when stdout is not a terminal.
+    pub fn new(json: bool, no_color: bool) -> Self {
+        Self {
+            json,
+            color: !no_color && std::io::stdout().is_terminal(),
+        }
+    }
+
+    /// Prints `value` as JSON or `report` as a table depending on the selected format.
+    pub fn print<T: Serialize>(&self, value: &T, report: &Report) -> Result<()> {
+        if self.json {
+            println!("{}", render_json(value)?);
+        } else {
+            print!("{}", render_table(report, self.color));
+        }
+        Ok(())
+    }
+}
+
+pub fn render_json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+pub fn render_table(report: &Report, color: bool) -> String {
+    let label_width = report
+        .rows
+        .iter()
+        .map(|row| row.label.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    out.push_str(&format!("{}\n", report.title));
+    out.push_str(&format!("{}\n", "-".repeat(report.title.len())));
+
+    for row in &report.rows {
+        let value = if color {
+            match row.severity {
+                Severity::Info => row.value.normal().to_string(),
+                Severity::Warning => row.value.bright_yellow().to_string(),
+                Severity::Error => row.value.bright_red().to_string(),
+            }
+        } else {
+            row.value.clone()
+        };
+        out.push_str(&format!(
+            "{:<width$}  {}\n",
+            format!("{}:", row.label),
+            value,
+            width = label_width + 1
+        ));
+    }
+
+    out
+}
+
+pub fn stats_report(stats: &StatsResponse) -> Report {
+    let pending_severity = if stats.pending_verify_pads > 0 {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+
+    let mut report = Report::new("Storage Statistics")
+        .row("Total Keys", stats.total_keys, Severity::Info)
+        .row("Total Pads Managed", stats.total_pads, Severity::Info)
+        .row("Occupied (Private)", stats.occupied_pads, Severity::Info)
+        .row("Free Pads", stats.free_pads, Severity::Info)
+        .row("Pending Verify Pads", stats.pending_verify_pads, pending_severity)
+        .row(
+            "Index Memory (est.)",
+            format_size(stats.index_memory_bytes, BINARY),
+            Severity::Info,
+        )
+        .row(
+            "Index Serialized",
+            format_size(stats.index_serialized_bytes, BINARY),
+            Severity::Info,
+        );
+
+    if stats.confirmed_bytes > 0 {
+        let pad_space = stats.confirmed_bytes.saturating_add(stats.wasted_bytes);
+        report = report
+            .row(
+                "Confirmed Data",
+                format_size(stats.confirmed_bytes, BINARY),
+                Severity::Info,
+            )
+            .row(
+                "Wasted Space",
+                format!(
+                    "{} ({}%)",
+                    format_size(stats.wasted_bytes, BINARY),
+                    stats.wasted_bytes as u128 * 100 / pad_space as u128
+                ),
+                Severity::Info,
+            );
+    }
+
+    if stats.trashed_keys > 0 {
+        report = report
+            .row("Trashed Keys", stats.trashed_keys, Severity::Info)
+            .row("Trash Pads", stats.trash_pads, Severity::Info)
+            .row(
+                "Trash Size",
+                format_size(stats.trash_bytes, BINARY),
+                Severity::Info,
+            );
+    }
+
+    if let Some(behind) = stats.remote_operations_behind {
+        report = report.row(
+            "Remote Index",
+            format!("{} operations behind", behind),
+            Severity::Warning,
+        );
+    }
+
+    if let Some(quota) = &stats.quota {
+        if let Some(max) = quota.max_pads {
+            report = report.row(
+                "Pad Quota",
+                quota_usage(quota.used_pads, max, |n| n.to_string()),
+                quota_severity(quota.used_pads, max),
+            );
+        }
+        if let Some(max) = quota.max_bytes {
+            report = report.row(
+                "Byte Quota",
+                quota_usage(quota.used_bytes, max, |n| format_size(n, BINARY)),
+                quota_severity(quota.used_bytes, max),
+            );
+        }
+    }
+
+    if stats.bundle_pads == 0 {
+        return report;
+    }
+
+    let occupancy = stats.bundle_live_bytes * 100 / stats.bundle_capacity_bytes.max(1);
+    report
+        .row("Bundle Pads", stats.bundle_pads, Severity::Info)
+        .row("Bundle Occupancy", format!("{}%", occupancy), Severity::Info)
+}
+
+/// The network calls counted by a daemon running with `MUTANT_IO_ACCOUNTING`.
+pub fn io_report(io: &IoStats) -> Report {
+    let failed_severity = if io.failed_writes > 0 {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+
+    let fell_back_severity = if io.creates_fell_back_to_update > 0 {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+
+    Report::new("Network I/O")
+        .row("Pads Created", io.pads_created, Severity::Info)
+        .row("Pads Updated", io.pads_updated, Severity::Info)
+        .row("Failed Writes", io.failed_writes, failed_severity)
+        .row(
+            "Existing Pads Updated",
+            io.creates_fell_back_to_update,
+            fell_back_severity,
+        )
+        .row("Pads Read", io.pads_read, Severity::Info)
+        .row("Existence Checks", io.existence_checks, Severity::Info)
+        .row("Bytes Written", format_size(io.bytes_written, BINARY), Severity::Info)
+        .row("Bytes Read", format_size(io.bytes_read, BINARY), Severity::Info)
+}
+
+/// One line summing up the network calls of an operation on `logical_bytes` of data.
+pub fn io_summary(io: &IoStats, logical_bytes: u64) -> String {
+    let mut summary = format!(
+        "{} writes ({} created, {} updated, {} failed), {} reads, {} checks, {} written",
+        io.writes(),
+        io.pads_created,
+        io.pads_updated,
+        io.failed_writes,
+        io.pads_read,
+        io.existence_checks,
+        format_size(io.bytes_written, BINARY)
+    );
+    if let Some(amplification) = io.write_amplification(logical_bytes) {
+        summary.push_str(&format!(" ({:.2}x the data)", amplification));
+    }
+    summary
+}
+
+fn quota_usage(used: u64, max: u64, format: impl Fn(u64) -> String) -> String {
+    format!(
+        "{} / {} ({}%)",
+        format(used),
+        format(max),
+        used * 100 / max.max(1)
+    )
+}
+
+/// Warns from 90% of the quota on.
+fn quota_severity(used: u64, max: u64) -> Severity {
+    if used >= max {
+        Severity::Error
+    } else if used * 10 >= max * 9 {
+        Severity::Warning
+    } else {
+        Severity::Info
+    }
+}
+
+pub fn bench_report(result: &BenchResult) -> Report {
+    let verified_severity = if result.verified {
+        Severity::Info
+    } else {
+        Severity::Error
+    };
+    let latency = |p: &Percentiles| format!("p50 {}ms / p90 {}ms / p99 {}ms", p.p50, p.p90, p.p99);
+
+    let report = Report::new("Benchmark Report")
+        .row("Payload", format_size(result.size_bytes as u64, BINARY), Severity::Info)
+        .row("Chunks", result.total_chunks, Severity::Info)
+        .row("New Pads", result.new_pads, Severity::Info)
+        .row("Reused Pads", result.reused_pads, Severity::Info)
+        .row(
+            "Upload",
+            format!(
+                "{}/s in {:.2}s",
+                format_size(result.upload_bytes_per_sec as u64, BINARY),
+                result.upload_secs
+            ),
+            Severity::Info,
+        )
+        .row("Upload Chunk Latency", latency(&result.upload_chunk_latency_ms), Severity::Info)
+        .row(
+            "Download",
+            format!(
+                "{}/s in {:.2}s",
+                format_size(result.download_bytes_per_sec as u64, BINARY),
+                result.download_secs
+            ),
+            Severity::Info,
+        )
+        .row("Download Chunk Latency", latency(&result.download_chunk_latency_ms), Severity::Info)
+        .row("Content Verified", result.verified, verified_severity);
+
+    match &result.kept_key {
+        Some(key) => report.row("Kept Key", key, Severity::Info),
+        None => report,
+    }
+}
+
+pub fn pad_exists_report(response: &PadExistsResponse) -> Report {
+    let report = Report::new("Pad Report").row("Address", &response.address, Severity::Info);
+
+    match response.counter {
+        Some(counter) if response.exists => report
+            .row("Exists", true, Severity::Info)
+            .row("Counter", counter, Severity::Info),
+        _ => report.row("Exists", false, Severity::Warning),
+    }
+}
+
+pub fn verify_report(result: &VerifyResult) -> Report {
+    let intact = result.total_chunks - result.failures.len();
+    let intact_severity = if result.failures.is_empty() {
+        Severity::Info
+    } else {
+        Severity::Error
+    };
+
+    let mut report = Report::new("Verify Report")
+        .row("Key", &result.key, Severity::Info)
+        .row(
+            "Intact Chunks",
+            format!("{}/{}", intact, result.total_chunks),
+            intact_severity,
+        );
+
+    for failure in &result.failures {
+        report = report.row(
+            &format!("Chunk {}", failure.chunk_index),
+            format!("{} ({})", failure.reason, failure.address),
+            Severity::Error,
+        );
+    }
+
+    report
+}
+
+/// What `put --dry-run` would do. Only the new pads are paid for, the cost being
+/// averaged from the pads created so far.
+pub fn estimate_report(estimate: &StoreEstimate) -> Report {
+    let cost = match estimate.estimated_cost {
+        Some(cost) => format!("~{} atto tokens", cost),
+        None => "unknown, no pad was created yet".to_string(),
+    };
+
+    Report::new("Store Estimate (dry run)")
+        .row("Chunks", estimate.total_chunks, Severity::Info)
+        .row("Pads of the Key", estimate.reused_key_pads, Severity::Info)
+        .row("Free Pads Reused", estimate.reused_free_pads, Severity::Info)
+        .row("New Pads", estimate.new_pads_required, Severity::Info)
+        .row("Estimated Cost", cost, Severity::Info)
+}
+
+/// The environment of the run, then one row per stage with its timing, and the hint of
+/// a failed stage on the row after it.
+pub fn selftest_report(result: &SelftestResult) -> Report {
+    let environment = &result.environment;
+    let unknown = || "unknown".to_string();
+    let mut report = Report::new("Selftest Report")
+        .row("CLI Version", environment.cli_version, Severity::Info)
+        .row(
+            "Daemon Version",
+            environment.lib_version.clone().unwrap_or_else(unknown),
+            Severity::Info,
+        )
+        .row(
+            "Network",
+            environment.network.clone().unwrap_or_else(unknown),
+            Severity::Info,
+        )
+        .row(
+            "Platform",
+            format!("{}/{}", environment.os, environment.arch),
+            Severity::Info,
+        );
+
+    for stage in &result.stages {
+        let (value, severity) = match stage.outcome {
+            StageOutcome::Passed => (format!("pass ({} ms)", stage.elapsed_ms), Severity::Info),
+            StageOutcome::Failed => (
+                format!(
+                    "FAIL ({} ms): {}",
+                    stage.elapsed_ms,
+                    stage.detail.as_deref().unwrap_or_default()
+                ),
+                Severity::Error,
+            ),
+            StageOutcome::Skipped => (
+                format!("skipped, {}", stage.detail.as_deref().unwrap_or_default()),
+                Severity::Warning,
+            ),
+        };
+        report = report.row(stage.stage.name(), value, severity);
+        if let Some(hint) = stage.hint {
+            report = report.row("Hint", hint, Severity::Warning);
+        }
+    }
+
+    let (verdict, severity) = if result.passed() {
+        ("PASS", Severity::Info)
+    } else {
+        ("FAIL", Severity::Error)
+    };
+    report.row("Result", verdict, severity)
+}
+
+pub fn purge_report(result: &PurgeResult) -> Report {
+    Report::new("Purge Report").row("Pads Purged", result.nb_pads_purged, Severity::Info)
+}
+
+pub fn recover_report(response: &RecoverResponse) -> Report {
+    let missing_severity = if response.missing.is_empty() {
+        Severity::Info
+    } else {
+        Severity::Warning
+    };
+    let chunks: usize = response.recovered.iter().map(|(_, chunks)| chunks).sum();
+
+    let mut report = Report::new("Recovery Report")
+        .row("Recovered Keys", response.recovered.len(), Severity::Info)
+        .row("Recovered Pads", chunks, Severity::Info)
+        .row("Missing Keys", response.missing.len(), missing_severity)
+        .row("Already Indexed", response.skipped.len(), Severity::Info);
+
+    if !response.missing.is_empty() {
+        report = report.row("Missing", response.missing.join(", "), Severity::Warning);
+    }
+
+    report
+}
+
+pub fn health_report(result: &HealthCheckResult) -> Report {
+    let reset_severity = if result.nb_keys_reset > 0 {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+    let recycled_severity = if result.nb_keys_recycled > 0 {
+        Severity::Warning
+    } else {
+        Severity::Info
+    };
+
+    Report::new("Health Check Report")
+        .row("Keys Reset", result.nb_keys_reset, reset_severity)
+        .row("Keys Recycled", result.nb_keys_recycled, recycled_severity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::selftest::{SelftestEnvironment, Stage, StageResult};
+    use mutant_protocol::{ChunkFailure, QuotaUsage};
+
+    #[test]
+    fn test_stats_table_snapshot() {
+        let stats = StatsResponse {
+            total_keys: 3,
+            total_pads: 12,
+            occupied_pads: 8,
+            free_pads: 3,
+            pending_verify_pads: 1,
+            bundle_pads: 0,
+            bundle_live_bytes: 0,
+            bundle_capacity_bytes: 0,
+            index_memory_bytes: 2048,
+            index_serialized_bytes: 512,
+            trash_pads: 0,
+            trashed_keys: 0,
+            trash_bytes: 0,
+            confirmed_bytes: 0,
+            wasted_bytes: 0,
+            remote_operations_behind: None,
+            quota: None,
+            io: None,
+        };
+
+        let expected = "\
+Storage Statistics
+------------------
+Total Keys:           3
+Total Pads Managed:   12
+Occupied (Private):   8
+Free Pads:            3
+Pending Verify Pads:  1
+Index Memory (est.):  2 KiB
+Index Serialized:     512 B
+";
+        assert_eq!(render_table(&stats_report(&stats), false), expected);
+    }
+
+    #[test]
+    fn test_stats_table_counts_trash_apart() {
+        let stats = StatsResponse {
+            total_keys: 1,
+            total_pads: 6,
+            occupied_pads: 2,
+            free_pads: 1,
+            pending_verify_pads: 0,
+            bundle_pads: 0,
+            bundle_live_bytes: 0,
+            bundle_capacity_bytes: 0,
+            index_memory_bytes: 0,
+            index_serialized_bytes: 0,
+            trash_pads: 3,
+            trashed_keys: 2,
+            trash_bytes: 3 * 4 * 1024 * 1024,
+            confirmed_bytes: 0,
+            wasted_bytes: 0,
+            remote_operations_behind: None,
+            quota: None,
+            io: None,
+        };
+
+        let table = render_table(&stats_report(&stats), false);
+        assert!(table.contains("Occupied (Private):   2\n"));
+        assert!(table.contains("Trashed Keys:         2\n"));
+        assert!(table.contains("Trash Pads:           3\n"));
+        assert!(table.contains("Trash Size:           12 MiB\n"));
+    }
+
+    #[test]
+    fn test_stats_table_shows_wasted_space() {
+        let stats = StatsResponse {
+            total_keys: 1,
+            total_pads: 2,
+            occupied_pads: 2,
+            free_pads: 0,
+            pending_verify_pads: 0,
+            bundle_pads: 0,
+            bundle_live_bytes: 0,
+            bundle_capacity_bytes: 0,
+            index_memory_bytes: 0,
+            index_serialized_bytes: 0,
+            trash_pads: 0,
+            trashed_keys: 0,
+            trash_bytes: 0,
+            confirmed_bytes: 5 * 1024 * 1024,
+            wasted_bytes: 3 * 1024 * 1024,
+            remote_operations_behind: None,
+            quota: None,
+            io: None,
+        };
+
+        let table = render_table(&stats_report(&stats), false);
+        assert!(table.contains("Confirmed Data:       5 MiB\n"));
+        assert!(table.contains("Wasted Space:         3 MiB (37%)\n"));
+    }
+
+    #[test]
+    fn test_stats_table_warns_when_the_remote_index_is_behind() {
+        let stats = StatsResponse {
+            total_keys: 1,
+            total_pads: 2,
+            occupied_pads: 2,
+            free_pads: 0,
+            pending_verify_pads: 0,
+            bundle_pads: 0,
+            bundle_live_bytes: 0,
+            bundle_capacity_bytes: 0,
+            index_memory_bytes: 0,
+            index_serialized_bytes: 0,
+            trash_pads: 0,
+            trashed_keys: 0,
+            trash_bytes: 0,
+            confirmed_bytes: 0,
+            wasted_bytes: 0,
+            remote_operations_behind: Some(3),
+            quota: None,
+            io: None,
+        };
+
+        let report = stats_report(&stats);
+        let row = report.rows.last().unwrap();
+        assert_eq!(row.label, "Remote Index");
+        assert_eq!(row.value, "3 operations behind");
+        assert_eq!(row.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_stats_table_shows_quota_usage() {
+        let stats = StatsResponse {
+            total_keys: 1,
+            total_pads: 10,
+            occupied_pads: 9,
+            free_pads: 1,
+            pending_verify_pads: 0,
+            bundle_pads: 0,
+            bundle_live_bytes: 0,
+            bundle_capacity_bytes: 0,
+            index_memory_bytes: 0,
+            index_serialized_bytes: 0,
+            trash_pads: 0,
+            trashed_keys: 0,
+            trash_bytes: 0,
+            confirmed_bytes: 0,
+            wasted_bytes: 0,
+            remote_operations_behind: None,
+            quota: Some(QuotaUsage {
+                used_pads: 9,
+                max_pads: Some(10),
+                used_bytes: 1024,
+                max_bytes: Some(4096),
+            }),
+            io: None,
+        };
+
+        let report = stats_report(&stats);
+        let table = render_table(&report, false);
+        assert!(table.contains("Pad Quota:            9 / 10 (90%)\n"));
+        assert!(table.contains("Byte Quota:           1 KiB / 4 KiB (25%)\n"));
+        assert_eq!(report.rows[7].label, "Pad Quota");
+        assert_eq!(report.rows[7].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_io_report_and_summary() {
+        let io = IoStats {
+            pads_created: 4,
+            pads_updated: 1,
+            failed_writes: 2,
+            pads_read: 5,
+            existence_checks: 0,
+            creates_fell_back_to_update: 1,
+            bytes_written: 5 * 1024,
+            bytes_read: 4 * 1024,
+        };
+
+        let report = io_report(&io);
+        let table = render_table(&report, false);
+        assert!(table.contains("Pads Created:           4\n"));
+        assert!(table.contains("Bytes Written:          5 KiB\n"));
+        assert_eq!(report.rows[2].severity, Severity::Warning);
+        assert_eq!(report.rows[3].label, "Existing Pads Updated");
+        assert_eq!(report.rows[3].severity, Severity::Warning);
+
+        assert_eq!(
+            io_summary(&io, 4 * 1024),
+            "7 writes (4 created, 1 updated, 2 failed), 5 reads, 0 checks, 5 KiB written (1.25x the data)"
+        );
+        assert!(!io_summary(&io, 0).contains("the data"));
+    }
+
+    #[test]
+    fn test_recover_report_lists_missing_keys() {
+        let response = RecoverResponse {
+            recovered: vec![("a".to_string(), 2), ("b".to_string(), 1)],
+            missing: vec!["c".to_string(), "d".to_string()],
+            skipped: vec![],
+        };
+
+        let table = render_table(&recover_report(&response), false);
+        assert!(table.contains("Recovered Keys:   2\n"));
+        assert!(table.contains("Recovered Pads:   3\n"));
+        assert!(table.contains("Missing:          c, d\n"));
+    }
+
+    #[test]
+    fn test_verify_report_lists_damaged_chunks() {
+        let result = VerifyResult {
+            key: "k".to_string(),
+            total_chunks: 3,
+            failures: vec![ChunkFailure {
+                chunk_index: 1,
+                address: "ab".to_string(),
+                reason: "checksum mismatch".to_string(),
+            }],
+        };
+
+        let table = render_table(&verify_report(&result), false);
+        assert!(table.contains("Intact Chunks:  2/3\n"));
+        assert!(table.contains("Chunk 1:        checksum mismatch (ab)\n"));
+    }
+
+    #[test]
+    fn test_estimate_report() {
+        let estimate = StoreEstimate {
+            total_chunks: 5,
+            reused_key_pads: 2,
+            reused_free_pads: 1,
+            new_pads_required: 2,
+            estimated_cost: Some(400),
+        };
+
+        let expected = "\
+Store Estimate (dry run)
+------------------------
+Chunks:            5
+Pads of the Key:   2
+Free Pads Reused:  1
+New Pads:          2
+Estimated Cost:    ~400 atto tokens
+";
+        assert_eq!(render_table(&estimate_report(&estimate), false), expected);
+
+        let unpriced = StoreEstimate {
+            estimated_cost: None,
+            ..estimate
+        };
+        assert!(render_table(&estimate_report(&unpriced), false).contains("unknown"));
+    }
+
+    #[test]
+    fn test_selftest_report_gives_the_hint_of_a_failed_stage() {
+        let stage = |stage, outcome, detail: Option<&str>, hint| StageResult {
+            stage,
+            outcome,
+            elapsed_ms: 12,
+            detail: detail.map(str::to_string),
+            hint,
+        };
+        let result = SelftestResult {
+            environment: SelftestEnvironment {
+                cli_version: "0.6.2",
+                lib_version: Some("0.6.2".to_string()),
+                network: Some("Devnet".to_string()),
+                os: "linux",
+                arch: "x86_64",
+                local: true,
+            },
+            stages: vec![
+                stage(Stage::Connect, StageOutcome::Passed, None, None),
+                stage(
+                    Stage::Wallet,
+                    StageOutcome::Failed,
+                    Some("no gas"),
+                    Some("Fund it."),
+                ),
+                stage(
+                    Stage::Network,
+                    StageOutcome::Skipped,
+                    Some("an earlier stage failed"),
+                    None,
+                ),
+            ],
+        };
+
+        let table = render_table(&selftest_report(&result), false);
+        assert!(table.contains("Network:         Devnet\n"));
+        assert!(table.contains("Connect:         pass (12 ms)\n"));
+        assert!(
+            table.contains("Wallet:          FAIL (12 ms): no gas\nHint:            Fund it.\n")
+        );
+        assert!(table.contains("Network:         skipped, an earlier stage failed\n"));
+        assert!(table.ends_with("Result:          FAIL\n"));
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["stages"][1]["outcome"], "failed");
+        assert!(json["stages"][0].get("hint").is_none());
+    }
+
+    #[test]
+    fn test_stats_json_round_trip() {
+        let stats = StatsResponse {
+            total_keys: 3,
+            total_pads: 12,
+            occupied_pads: 8,
+            free_pads: 3,
+            pending_verify_pads: 1,
+            bundle_pads: 0,
+            bundle_live_bytes: 0,
+            bundle_capacity_bytes: 0,
+            index_memory_bytes: 2048,
+            index_serialized_bytes: 512,
+            trash_pads: 1,
+            trashed_keys: 1,
+            trash_bytes: 4096,
+            confirmed_bytes: 0,
+            wasted_bytes: 0,
+            remote_operations_behind: Some(2),
+            quota: Some(QuotaUsage {
+                used_pads: 9,
+                max_pads: Some(20),
+                used_bytes: 4096,
+                max_bytes: None,
+            }),
+            io: None,
+        };
+
+        let json = render_json(&stats).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["total_keys"], 3);
+        assert_eq!(value["pending_verify_pads"], 1);
+        assert_eq!(value["remote_operations_behind"], 2);
+        assert_eq!(value["quota"]["max_pads"], 20);
+
+        let parsed: StatsResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, stats);
+    }
+
+    #[test]
+    fn test_health_report_json() {
+        let result = HealthCheckResult {
+            nb_keys_reset: 1,
+            nb_keys_recycled: 2,
+        };
+
+        let json = render_json(&result).unwrap();
+        let parsed: HealthCheckResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, result);
+    }
+}
@@ -1,5 +1,5 @@
+use std::path::Path;
 use std::time::Duration;
-use indicatif::MultiProgress;
 use pretty_duration::{PrettyDurationOptions, PrettyDurationOutputFormat};
 
 /// Format a duration into a human-readable string using the humantime crate
@@ -19,11 +19,12 @@ pub fn format_elapsed_time(elapsed: Duration) -> String {
     format!("{}", elapsed)
 }
 
-/// Ensure all progress bars in a MultiProgress are properly cleared
-///
-/// This function should be called before displaying final messages to ensure
-/// that all progress bars are cleared from the terminal.
-pub fn ensure_progress_cleared(multi_progress: &MultiProgress) {
-    // Force a refresh of the progress bars
-    multi_progress.clear().unwrap_or_default();
+/// Resolves `path` against the current directory, the daemon does not share it.
+pub fn absolute_path(path: &Path) -> std::io::Result<String> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    Ok(path.to_string_lossy().to_string())
 }
@@ -0,0 +1,59 @@
+#![cfg(target_os = "linux")]
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Holds the cache lock of a fresh data directory, as another mutant process would.
+fn lock_cache(shared: bool) -> (PathBuf, File) {
+    let data_dir = std::env::temp_dir().join(format!("mutant-lock-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(data_dir.join("mutant")).unwrap();
+    let file = File::create(data_dir.join("mutant").join("cache.lock")).unwrap();
+    if shared {
+        file.lock_shared().unwrap();
+    } else {
+        file.lock().unwrap();
+    }
+    (data_dir, file)
+}
+
+fn mutant(data_dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_mutant"));
+    command.env("XDG_DATA_HOME", data_dir).args(args);
+    command
+}
+
+#[test]
+fn test_no_wait_fails_while_another_process_uses_the_cache() {
+    for shared in [false, true] {
+        let (data_dir, _lock) = lock_cache(shared);
+
+        let output = mutant(&data_dir, &["--no-wait", "rm", "some_key"])
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .contains("Another mutant process is using the local cache"));
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
+
+#[test]
+fn test_mutating_command_waits_for_the_lock() {
+    let (data_dir, _lock) = lock_cache(false);
+
+    let mut child = mutant(&data_dir, &["--quiet", "rm", "some_key"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(500));
+
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+    child.wait().unwrap();
+    fs::remove_dir_all(&data_dir).unwrap();
+}
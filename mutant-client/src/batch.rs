@@ -0,0 +1,44 @@
+use mutant_protocol::{BatchOperation, BatchResultResponse};
+
+/// Outcome of a `store_many` or `remove_many` request, one entry per key in request
+/// order. Failed keys can be sent again on their own, storing a key with unchanged
+/// data only reuses its pads and removing a key that is already gone succeeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOutcome {
+    pub operation: BatchOperation,
+    pub per_item: Vec<(String, Result<(), String>)>,
+}
+
+impl BatchOutcome {
+    pub fn all_ok(&self) -> bool {
+        self.per_item.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// The failed keys, with the reason they failed.
+    pub fn failures(&self) -> Vec<(&str, &str)> {
+        self.per_item
+            .iter()
+            .filter_map(|(key, result)| match result {
+                Ok(()) => None,
+                Err(e) => Some((key.as_str(), e.as_str())),
+            })
+            .collect()
+    }
+
+    pub fn succeeded(&self) -> Vec<&str> {
+        self.per_item
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+}
+
+impl From<BatchResultResponse> for BatchOutcome {
+    fn from(response: BatchResultResponse) -> Self {
+        Self {
+            operation: response.operation,
+            per_item: response.per_item,
+        }
+    }
+}
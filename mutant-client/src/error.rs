@@ -23,7 +23,7 @@ pub enum ClientError {
     NonTextMessageReceived(String),
 
     #[error("Received unexpected response from server: {0:?}")]
-    UnexpectedResponse(Response),
+    UnexpectedResponse(Box<Response>),
 
     #[error("Server returned an error: {0}")]
     ServerError(String),
@@ -34,6 +34,9 @@ pub enum ClientError {
     #[error("Client is not connected or connection closed")]
     NotConnected,
 
+    #[error("Connection to the daemon lost before its response")]
+    ConnectionLost,
+
     #[error("Failed to interact with JavaScript API: {0}")]
     JsError(String),
 
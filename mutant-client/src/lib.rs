@@ -11,17 +11,25 @@ use url::Url;
 use wasm_bindgen_futures::spawn_local;
 
 use mutant_protocol::{
-    ExportResult, HealthCheckResult, ImportResult, KeyDetails, PurgeResult, Request, StatsResponse,
-    StorageMode, SyncResult, Task, TaskId, TaskListEntry, TaskProgress, TaskResult, TaskStatus,
-    TaskStoppedResponse, TaskType,
+    AliasResponse, CacheCleanReport, CollectionAction, CollectionResponse, ExportResult,
+    HealthCheckResult, ImportResult, InitOutcome, KeyDetails, KeyDetailsField, ListKeysRequest,
+    ListKeysResponse, Notification, PadExistsResponse, PurgeResult, RecoverResponse,
+    RenameResponse, Request, ResetMode, ResetPrepareResponse, ResetResponse, RestoreResponse,
+    RmSuccessResponse, StatsResponse, StorageMode, StoreEstimate, SyncDirection, SyncResult, Task,
+    TaskId, TaskListEntry, TaskProgress, TaskRecord, TaskResult, TaskStatus, TaskStoppedResponse,
+    TaskType, TreeCheck, TreeManifest, VerifyResponse,
 };
 
+mod batch;
 pub mod error;
 mod macros;
 mod request;
 mod response;
+mod rm;
 
+pub use crate::batch::BatchOutcome;
 use crate::error::ClientError;
+pub use crate::rm::RmReport;
 
 // Shared state for tasks managed by the client (using Arc<Mutex> for thread safety)
 type ClientTaskMap = Arc<Mutex<HashMap<TaskId, Task>>>;
@@ -41,6 +49,25 @@ pub enum PendingRequestKey {
     Export,
     HealthCheck,
     StopTask,
+    PadExists,
+    Verify,
+    EstimateStore,
+    Collection,
+    Alias,
+    Rename,
+    Restore,
+    StoreMany,
+    RemoveMany,
+    Recover,
+    StatKey,
+    TaskHistory,
+    InitReport,
+    MirrorPush,
+    MirrorCheck,
+    CleanCache,
+    ResetPrepare,
+    ResetCommit,
+    SubscribeNotifications,
 }
 
 // Enum to hold the different sender types for the pending requests map
@@ -52,8 +79,8 @@ pub enum PendingSender {
     ),
     ListTasks(oneshot::Sender<Result<Vec<TaskListEntry>, ClientError>>),
     QueryTask(oneshot::Sender<Result<Task, ClientError>>),
-    Rm(oneshot::Sender<Result<(), ClientError>>),
-    ListKeys(oneshot::Sender<Result<Vec<KeyDetails>, ClientError>>),
+    Rm(oneshot::Sender<Result<RmSuccessResponse, ClientError>>),
+    ListKeys(oneshot::Sender<Result<ListKeysResponse, ClientError>>),
     Stats(oneshot::Sender<Result<StatsResponse, ClientError>>),
     Sync(oneshot::Sender<Result<SyncResult, ClientError>>),
     Purge(oneshot::Sender<Result<PurgeResult, ClientError>>),
@@ -61,6 +88,25 @@ pub enum PendingSender {
     Export(oneshot::Sender<Result<ExportResult, ClientError>>),
     HealthCheck(oneshot::Sender<Result<HealthCheckResult, ClientError>>),
     StopTask(oneshot::Sender<Result<TaskStoppedResponse, ClientError>>),
+    PadExists(oneshot::Sender<Result<PadExistsResponse, ClientError>>),
+    Verify(oneshot::Sender<Result<VerifyResponse, ClientError>>),
+    EstimateStore(oneshot::Sender<Result<StoreEstimate, ClientError>>),
+    Collection(oneshot::Sender<Result<CollectionResponse, ClientError>>),
+    Alias(oneshot::Sender<Result<AliasResponse, ClientError>>),
+    Rename(oneshot::Sender<Result<RenameResponse, ClientError>>),
+    Restore(oneshot::Sender<Result<RestoreResponse, ClientError>>),
+    StoreMany(oneshot::Sender<Result<BatchOutcome, ClientError>>),
+    RemoveMany(oneshot::Sender<Result<BatchOutcome, ClientError>>),
+    Recover(oneshot::Sender<Result<RecoverResponse, ClientError>>),
+    StatKey(oneshot::Sender<Result<Option<KeyDetails>, ClientError>>),
+    TaskHistory(oneshot::Sender<Result<Vec<TaskRecord>, ClientError>>),
+    InitReport(oneshot::Sender<Result<InitOutcome, ClientError>>),
+    MirrorPush(oneshot::Sender<Result<TreeManifest, ClientError>>),
+    MirrorCheck(oneshot::Sender<Result<TreeCheck, ClientError>>),
+    CleanCache(oneshot::Sender<Result<CacheCleanReport, ClientError>>),
+    ResetPrepare(oneshot::Sender<Result<ResetPrepareResponse, ClientError>>),
+    ResetCommit(oneshot::Sender<Result<ResetResponse, ClientError>>),
+    SubscribeNotifications(oneshot::Sender<Result<(), ClientError>>),
 }
 
 // The new map type for pending requests
@@ -72,6 +118,11 @@ pub type ProgressReceiver = mpsc::UnboundedReceiver<Result<TaskProgress, ClientE
 type CompletionSender = oneshot::Sender<Result<TaskResult, ClientError>>;
 type ProgressSender = mpsc::UnboundedSender<Result<TaskProgress, ClientError>>;
 
+pub type NotificationReceiver = mpsc::UnboundedReceiver<Notification>;
+
+// Where the notifications go once the client subscribed to them
+type NotificationSlot = Arc<Mutex<Option<mpsc::UnboundedSender<Notification>>>>;
+
 type TaskChannels = (CompletionSender, ProgressSender);
 type TaskChannelsMap = Arc<Mutex<HashMap<TaskId, TaskChannels>>>;
 
@@ -89,7 +140,10 @@ pub struct MutantClient {
     tasks: ClientTaskMap,
     task_channels: TaskChannelsMap,
     pending_requests: PendingRequestMap,
+    notifications: NotificationSlot,
     state: Arc<Mutex<ConnectionState>>,
+    /// Where [`Self::connect`] connected to, for [`Self::reconnect`].
+    addr: Option<String>,
 }
 
 impl MutantClient {
@@ -101,7 +155,9 @@ impl MutantClient {
             tasks: Arc::new(Mutex::new(HashMap::new())),
             task_channels: Arc::new(Mutex::new(HashMap::new())),
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
+            notifications: Arc::new(Mutex::new(None)),
             state: Arc::new(Mutex::new(ConnectionState::Disconnected)),
+            addr: None,
         }
     }
 
@@ -122,6 +178,7 @@ impl MutantClient {
 
         self.sender = Some(sender);
         self.receiver = Some(receiver);
+        self.addr = Some(addr.to_string());
 
         *self.state.lock().unwrap() = ConnectionState::Connected;
 
@@ -148,6 +205,16 @@ impl MutantClient {
         Ok(())
     }
 
+    /// Connects again to the address given to [`Self::connect`], once the daemon dropped
+    /// the connection. Requests that were waiting for a response failed with
+    /// [`ClientError::ConnectionLost`] and are not sent again.
+    pub async fn reconnect(&mut self) -> Result<(), ClientError> {
+        let addr = self.addr.clone().ok_or(ClientError::NotConnected)?;
+        self.sender = None;
+        self.receiver = None;
+        self.connect(&addr).await
+    }
+
     // --- Public API Methods ---
     // A simple request/response map or channels might be needed.
 
@@ -178,11 +245,15 @@ impl MutantClient {
         )
     }
 
+    /// Fetches `user_key` into `destination_path`. With `index_file`, a path on the daemon
+    /// to a catalogue written by [`Self::export_public_catalogue`], the key is resolved by
+    /// its name in the catalogue.
     pub async fn get(
         &mut self,
         user_key: &str,
         destination_path: &str,
         public: bool,
+        index_file: Option<&str>,
     ) -> Result<
         (
             impl Future<Output = Result<TaskResult, ClientError>> + '_,
@@ -197,6 +268,7 @@ impl MutantClient {
                 user_key: user_key.to_string(),
                 destination_path: destination_path.to_string(),
                 public,
+                index_file: index_file.map(str::to_string),
             }
         )
     }
@@ -211,7 +283,38 @@ impl MutantClient {
         ),
         ClientError,
     > {
-        long_request!(self, Sync, SyncRequest { push_force })
+        long_request!(
+            self,
+            Sync,
+            SyncRequest {
+                push_force,
+                only: Vec::new(),
+                direction: SyncDirection::default(),
+            }
+        )
+    }
+
+    /// Syncs only the keys matching `patterns`, see `MutAnt::sync_keys`.
+    pub async fn sync_keys(
+        &mut self,
+        patterns: Vec<String>,
+        direction: SyncDirection,
+    ) -> Result<
+        (
+            impl Future<Output = Result<TaskResult, ClientError>> + '_,
+            ProgressReceiver,
+        ),
+        ClientError,
+    > {
+        long_request!(
+            self,
+            Sync,
+            SyncRequest {
+                push_force: false,
+                only: patterns,
+                direction,
+            }
+        )
     }
 
     pub async fn purge(
@@ -250,23 +353,380 @@ impl MutantClient {
 
     /// Retrieves a list of all stored keys from the daemon.
     pub async fn list_keys(&mut self) -> Result<Vec<KeyDetails>, ClientError> {
-        direct_request!(self, ListKeys, ListKeysRequest)
+        self.list_keys_pages(ListKeysRequest::default()).await
+    }
+
+    /// Retrieves the stored keys starting with `prefix`.
+    pub async fn list_keys_with_prefix(
+        &mut self,
+        prefix: &str,
+    ) -> Result<Vec<KeyDetails>, ClientError> {
+        self.list_keys_pages(ListKeysRequest {
+            prefix: Some(prefix.to_string()),
+            ..Default::default()
+        })
+        .await
     }
 
-    pub async fn rm(&mut self, user_key: &str) -> Result<(), ClientError> {
+    /// Retrieves all stored keys with only the optional details in `fields`.
+    pub async fn list_keys_with_fields(
+        &mut self,
+        fields: Vec<KeyDetailsField>,
+    ) -> Result<Vec<KeyDetails>, ClientError> {
+        self.list_keys_pages(ListKeysRequest {
+            fields: Some(fields),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Lists the removed keys that can still be restored.
+    pub async fn list_trash(&mut self) -> Result<Vec<KeyDetails>, ClientError> {
+        self.list_keys_pages(ListKeysRequest {
+            trash: true,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Lists the keys of a public catalogue, `index_file` being a path on the daemon.
+    pub async fn list_index_file_keys(
+        &mut self,
+        index_file: &str,
+    ) -> Result<Vec<KeyDetails>, ClientError> {
+        self.list_keys_pages(ListKeysRequest {
+            index_file: Some(index_file.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Lists the keys of the remote index, leaving the local index of the daemon as is.
+    pub async fn list_remote_keys(&mut self) -> Result<Vec<KeyDetails>, ClientError> {
+        self.list_keys_pages(ListKeysRequest {
+            remote: true,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Lists the keys that belong to `collection`.
+    pub async fn list_collection_keys(
+        &mut self,
+        collection: &str,
+    ) -> Result<Vec<KeyDetails>, ClientError> {
+        self.list_keys_pages(ListKeysRequest {
+            collection: Some(collection.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Requests the pages of a listing one after the other, the daemon caps the
+    /// number of keys in a response.
+    async fn list_keys_pages(
+        &mut self,
+        request: ListKeysRequest,
+    ) -> Result<Vec<KeyDetails>, ClientError> {
+        let mut keys = Vec::new();
+        let mut offset = request.offset;
+
+        loop {
+            let page: ListKeysResponse = direct_request!(
+                self,
+                ListKeys,
+                ListKeysRequest {
+                    offset,
+                    ..request.clone()
+                }
+            )?;
+            keys.extend(page.keys);
+
+            match page.next_offset {
+                Some(next_offset) => offset = next_offset,
+                None => return Ok(keys),
+            }
+        }
+    }
+
+    /// Adds existing keys to a collection, creating it if needed.
+    pub async fn collection_add(
+        &mut self,
+        name: &str,
+        keys: Vec<String>,
+    ) -> Result<CollectionResponse, ClientError> {
+        direct_request!(
+            self,
+            Collection,
+            CollectionRequest {
+                name: name.to_string(),
+                action: CollectionAction::Add { keys },
+            }
+        )
+    }
+
+    /// Deletes a collection, and its keys with `delete_keys`.
+    pub async fn collection_rm(
+        &mut self,
+        name: &str,
+        delete_keys: bool,
+    ) -> Result<CollectionResponse, ClientError> {
+        direct_request!(
+            self,
+            Collection,
+            CollectionRequest {
+                name: name.to_string(),
+                action: CollectionAction::Remove { delete_keys },
+            }
+        )
+    }
+
+    /// Removes a key, see [`RmReport`] for what happens when the connection drops.
+    pub async fn rm(&mut self, user_key: &str) -> Result<RmReport, ClientError> {
+        self.rm_retrying(user_key, false).await
+    }
+
+    /// Removes a key that still has aliases by handing its data over to its first alias.
+    pub async fn rm_promoting_alias(&mut self, user_key: &str) -> Result<RmReport, ClientError> {
+        self.rm_retrying(user_key, true).await
+    }
+
+    /// Sends the remove again once reconnected when the connection dropped before its
+    /// response, the daemon answering a remove of a key already gone with
+    /// `existed: false` instead of an error.
+    async fn rm_retrying(
+        &mut self,
+        user_key: &str,
+        promote_alias: bool,
+    ) -> Result<RmReport, ClientError> {
+        match self.rm_once(user_key, promote_alias).await {
+            Err(ClientError::ConnectionLost) => {
+                warn!(
+                    "Connection lost before the removal of '{}' was answered, sending it again",
+                    user_key
+                );
+                self.reconnect().await?;
+                let response = self.rm_once(user_key, promote_alias).await?;
+                Ok(RmReport::new(response, true))
+            }
+            result => result.map(|response| RmReport::new(response, false)),
+        }
+    }
+
+    async fn rm_once(
+        &mut self,
+        user_key: &str,
+        promote_alias: bool,
+    ) -> Result<RmSuccessResponse, ClientError> {
         direct_request!(
             self,
             Rm,
             RmRequest {
                 user_key: user_key.to_string(),
+                promote_alias,
+            }
+        )
+    }
+
+    pub async fn alias(&mut self, key: &str, alias: &str) -> Result<AliasResponse, ClientError> {
+        direct_request!(
+            self,
+            Alias,
+            AliasRequest {
+                key: key.to_string(),
+                alias: alias.to_string(),
+            }
+        )
+    }
+
+    /// Renames a key, or an alias, without uploading anything.
+    pub async fn rename(
+        &mut self,
+        old_key: &str,
+        new_key: &str,
+    ) -> Result<RenameResponse, ClientError> {
+        direct_request!(
+            self,
+            Rename,
+            RenameRequest {
+                old_key: old_key.to_string(),
+                new_key: new_key.to_string(),
+            }
+        )
+    }
+
+    /// Brings a key back from the trash.
+    pub async fn restore(&mut self, user_key: &str) -> Result<RestoreResponse, ClientError> {
+        direct_request!(
+            self,
+            Restore,
+            RestoreRequest {
+                user_key: user_key.to_string(),
+            }
+        )
+    }
+
+    /// Rebuilds the index entries of `key_hints` from the pads at their derived addresses,
+    /// for when both the local cache and the remote index are lost. Only the keys stored
+    /// after being given to `MutAnt::precompute_key_addresses` are found.
+    pub async fn recover(
+        &mut self,
+        key_hints: Vec<String>,
+    ) -> Result<RecoverResponse, ClientError> {
+        direct_request!(self, Recover, RecoverRequest { key_hints })
+    }
+
+    /// Whether the store of the daemon is new, its wallet funded and the network
+    /// reachable, see `InitOutcome`. Takes a few network lookups.
+    pub async fn init_report(&mut self) -> Result<InitOutcome, ClientError> {
+        direct_request!(self, InitReport, InitReportRequest)
+    }
+
+    /// Stores each file under `dir`, an absolute path on the daemon side, as a private key
+    /// under `prefix`. Files whose key already holds the same content are skipped.
+    pub async fn mirror_push(
+        &mut self,
+        dir: &str,
+        prefix: &str,
+    ) -> Result<TreeManifest, ClientError> {
+        direct_request!(
+            self,
+            MirrorPush,
+            MirrorPushRequest {
+                dir: dir.to_string(),
+                prefix: prefix.to_string(),
+            }
+        )
+    }
+
+    /// Compares the files under `dir` with the keys mirrored from it under `prefix`,
+    /// from the hashes in the index of the daemon.
+    pub async fn mirror_check(
+        &mut self,
+        dir: &str,
+        prefix: &str,
+    ) -> Result<TreeCheck, ClientError> {
+        direct_request!(
+            self,
+            MirrorCheck,
+            MirrorCheckRequest {
+                dir: dir.to_string(),
+                prefix: prefix.to_string(),
             }
         )
     }
 
+    /// Removes the local index caches the daemon does not use past the default limits,
+    /// or only lists them on a `dry_run`.
+    pub async fn clean_cache(&mut self, dry_run: bool) -> Result<CacheCleanReport, ClientError> {
+        direct_request!(self, CleanCache, CleanCacheRequest { dry_run })
+    }
+
+    /// Drops every key of the daemon, see [`ResetMode`] for what becomes of their pads.
+    /// Nothing is sent unless `confirm` is set.
+    ///
+    /// The reset is prepared then committed with the one-time token the daemon answers
+    /// the preparation with, which also tells what is about to be dropped.
+    pub async fn reset(
+        &mut self,
+        mode: ResetMode,
+        confirm: bool,
+    ) -> Result<ResetResponse, ClientError> {
+        if !confirm {
+            return Err(ClientError::InternalError(
+                "Refusing to reset without confirmation".to_string(),
+            ));
+        }
+
+        let prepared = self.reset_prepare().await?;
+        info!(
+            "Resetting {} keys and {} pads ({:?})",
+            prepared.summary.key_count, prepared.summary.pad_count, mode
+        );
+        self.reset_commit(&prepared.token, mode).await
+    }
+
+    /// First step of [`Self::reset`], changing nothing.
+    pub async fn reset_prepare(&mut self) -> Result<ResetPrepareResponse, ClientError> {
+        direct_request!(self, ResetPrepare, ResetPrepareRequest {})
+    }
+
+    /// Second step of [`Self::reset`], with the token of [`Self::reset_prepare`].
+    pub async fn reset_commit(
+        &mut self,
+        token: &str,
+        mode: ResetMode,
+    ) -> Result<ResetResponse, ClientError> {
+        direct_request!(
+            self,
+            ResetCommit,
+            ResetCommitRequest {
+                token: token.to_string(),
+                mode,
+            }
+        )
+    }
+
+    /// Details of a single key, `None` if there is no such key. Answered from the index
+    /// of the daemon, without listing the other keys or fetching the value.
+    pub async fn stat(&mut self, key: &str) -> Result<Option<KeyDetails>, ClientError> {
+        direct_request!(
+            self,
+            StatKey,
+            StatKeyRequest {
+                key: key.to_string(),
+            }
+        )
+    }
+
+    /// Stores each `(key, file path)` pair on its own, the files being read by the
+    /// daemon. A failed key leaves the others in place, with `fail_fast` the keys
+    /// following it are skipped.
+    pub async fn store_many(
+        &mut self,
+        items: Vec<(String, String)>,
+        mode: StorageMode,
+        fail_fast: bool,
+    ) -> Result<BatchOutcome, ClientError> {
+        let items = items
+            .into_iter()
+            .map(|(user_key, source_path)| mutant_protocol::StoreManyItem {
+                user_key,
+                source_path,
+            })
+            .collect();
+
+        direct_request!(
+            self,
+            StoreMany,
+            StoreManyRequest {
+                items,
+                mode,
+                fail_fast
+            }
+        )
+    }
+
+    /// Removes each key on its own, a failed removal leaves the others in place.
+    pub async fn remove_many(
+        &mut self,
+        user_keys: Vec<String>,
+    ) -> Result<BatchOutcome, ClientError> {
+        direct_request!(self, RemoveMany, RemoveManyRequest { user_keys })
+    }
+
     pub async fn list_tasks(&mut self) -> Result<Vec<TaskListEntry>, ClientError> {
         direct_request!(self, ListTasks, ListTasksRequest)
     }
 
+    /// The tasks the daemon finished lately, most recent first, at most `limit` of them.
+    pub async fn task_history(
+        &mut self,
+        limit: Option<usize>,
+    ) -> Result<Vec<TaskRecord>, ClientError> {
+        direct_request!(self, TaskHistory, TaskHistoryRequest { limit })
+    }
+
     pub async fn query_task(&mut self, task_id: TaskId) -> Result<Task, ClientError> {
         direct_request!(self, QueryTask, QueryTaskRequest { task_id })
     }
@@ -275,6 +735,50 @@ impl MutantClient {
         direct_request!(self, Stats, StatsRequest {})
     }
 
+    /// Checks whether a scratchpad exists on the network and returns its counter.
+    pub async fn pad_exists(&mut self, address: &str) -> Result<PadExistsResponse, ClientError> {
+        direct_request!(
+            self,
+            PadExists,
+            PadExistsRequest {
+                address: address.to_string()
+            }
+        )
+    }
+
+    /// Fetches every chunk of a key and reports the ones that are missing or do not
+    /// match their checksum, without downloading the data to the client.
+    pub async fn verify(&mut self, user_key: &str) -> Result<VerifyResponse, ClientError> {
+        direct_request!(
+            self,
+            Verify,
+            VerifyRequest {
+                user_key: user_key.to_string(),
+            }
+        )
+    }
+
+    /// Tells how many pads storing `data_len` bytes under `user_key` would reuse and
+    /// create, and their rough cost, without writing anything.
+    pub async fn estimate_store(
+        &mut self,
+        user_key: &str,
+        data_len: usize,
+        mode: StorageMode,
+        public: bool,
+    ) -> Result<StoreEstimate, ClientError> {
+        direct_request!(
+            self,
+            EstimateStore,
+            EstimateStoreRequest {
+                user_key: user_key.to_string(),
+                data_len,
+                mode,
+                public,
+            }
+        )
+    }
+
     pub async fn import(&mut self, file_path: &str) -> Result<ImportResult, ClientError> {
         direct_request!(
             self,
@@ -290,7 +794,24 @@ impl MutantClient {
             self,
             Export,
             ExportRequest {
-                destination_path: destination_path.to_string()
+                destination_path: destination_path.to_string(),
+                public_catalogue: false,
+            }
+        )
+    }
+
+    /// Writes the public keys of the store, without their secret keys, to
+    /// `destination_path` on the daemon, for readers without a wallet.
+    pub async fn export_public_catalogue(
+        &mut self,
+        destination_path: &str,
+    ) -> Result<ExportResult, ClientError> {
+        direct_request!(
+            self,
+            Export,
+            ExportRequest {
+                destination_path: destination_path.to_string(),
+                public_catalogue: true,
             }
         )
     }
@@ -300,6 +821,33 @@ impl MutantClient {
         direct_request!(self, StopTask, StopTaskRequest { task_id })
     }
 
+    /// Subscribes to the [`Notification`]s the daemon pushes when the keys change, through
+    /// this client or any other. They keep coming on the returned receiver whatever
+    /// requests this client makes meanwhile. Subscribing again replaces the receiver.
+    pub async fn notifications(&mut self) -> Result<NotificationReceiver, ClientError> {
+        let (notification_tx, notification_rx) = mpsc::unbounded_channel();
+        let subscribed = self
+            .notifications
+            .lock()
+            .unwrap()
+            .replace(notification_tx)
+            .is_some();
+
+        if !subscribed {
+            let result: Result<(), ClientError> = direct_request!(
+                self,
+                SubscribeNotifications,
+                SubscribeNotificationsRequest {}
+            );
+            if let Err(e) = result {
+                *self.notifications.lock().unwrap() = None;
+                return Err(e);
+            }
+        }
+
+        Ok(notification_rx)
+    }
+
     // --- Accessor methods for internal state ---
 
     pub fn get_task_status(&self, task_id: TaskId) -> Option<TaskStatus> {
@@ -327,7 +875,9 @@ impl Clone for MutantClient {
             tasks: self.tasks.clone(),
             task_channels: self.task_channels.clone(),
             pending_requests: self.pending_requests.clone(), // Clone the new map
+            notifications: self.notifications.clone(),
             state: self.state.clone(),
+            addr: self.addr.clone(),
         }
     }
 }
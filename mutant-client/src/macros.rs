@@ -23,12 +23,11 @@ macro_rules! direct_request {
                     debug!("{} request sent, waiting for response...", stringify!($key));
                     match receiver.await {
                         Ok(result) => result,
+                        // The pending requests are dropped when the connection closes
                         Err(_) => {
                             $self.pending_requests.lock().unwrap().remove(&key);
                             error!("{} response channel canceled", stringify!($key));
-                            Err(ClientError::InternalError(
-                                "{} response channel canceled".to_string(),
-                            ))
+                            Err(ClientError::ConnectionLost)
                         }
                     }
                 }
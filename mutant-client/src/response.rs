@@ -1,13 +1,13 @@
 use log::{debug, error, trace, warn};
 use mutant_protocol::{
-    ErrorResponse, ExportResponse, ImportResponse, ListKeysResponse, Response, RmSuccessResponse,
-    Task, TaskCreatedResponse, TaskListResponse, TaskProgress, TaskResult, TaskResultResponse,
-    TaskStatus, TaskStoppedResponse, TaskType, TaskUpdateResponse,
+    BatchOperation, ErrorCode, ErrorResponse, ExportResponse, ImportResponse, Response, Task,
+    TaskCreatedResponse, TaskHistoryResponse, TaskListResponse, TaskProgress, TaskResult,
+    TaskResultResponse, TaskStatus, TaskStoppedResponse, TaskType, TaskUpdateResponse,
 };
 
 use crate::{
-    error::ClientError, ClientTaskMap, PendingRequestKey, PendingRequestMap, PendingSender,
-    TaskChannelsMap,
+    error::ClientError, ClientTaskMap, ConnectionState, NotificationSlot, PendingRequestKey,
+    PendingRequestMap, PendingSender, TaskChannelsMap,
 };
 
 use super::MutantClient;
@@ -21,6 +21,7 @@ impl MutantClient {
         tasks: &ClientTaskMap,
         task_channels: &TaskChannelsMap,
         pending_requests: &PendingRequestMap,
+        notifications: &NotificationSlot,
     ) {
         match response {
             Response::TaskCreated(TaskCreatedResponse { task_id }) => {
@@ -41,6 +42,7 @@ impl MutantClient {
                             progress: None,
                             result: TaskResult::Pending,
                             key: None,
+                            snapshot: None,
                         },
                     );
 
@@ -62,6 +64,7 @@ impl MutantClient {
                 task_id,
                 status,
                 progress,
+                repeat,
             }) => {
                 let mut tasks_guard = tasks.lock().unwrap();
                 let task_exists = tasks_guard.contains_key(&task_id);
@@ -73,8 +76,12 @@ impl MutantClient {
                     if let Some(progress_update) = progress {
                         if let Some((_, progress_tx)) = task_channels.lock().unwrap().get(&task_id)
                         {
-                            if progress_tx.send(Ok(progress_update)).is_err() {
-                                warn!("Failed to send progress update for task {}", task_id);
+                            // Merged updates are replayed, progress events count occurrences.
+                            for _ in 0..repeat {
+                                if progress_tx.send(Ok(progress_update.clone())).is_err() {
+                                    warn!("Failed to send progress update for task {}", task_id);
+                                    break;
+                                }
                             }
                         }
                     }
@@ -96,6 +103,7 @@ impl MutantClient {
                             progress,
                             result: TaskResult::Pending,
                             key: None,
+                            snapshot: None,
                         },
                     );
                 }
@@ -160,6 +168,7 @@ impl MutantClient {
                             result,
                             progress: None,
                             key: None,
+                            snapshot: None,
                         },
                     );
                 }
@@ -192,9 +201,23 @@ impl MutantClient {
                     warn!("Received TaskList but no ListTasks request was pending");
                 }
             }
+            Response::TaskHistory(TaskHistoryResponse { records }) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::TaskHistory);
+                if let Some(PendingSender::TaskHistory(sender)) = pending_sender {
+                    if sender.send(Ok(records)).is_err() {
+                        warn!("Failed to send TaskHistory response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received TaskHistory but no TaskHistory request was pending");
+                }
+            }
             Response::Error(ErrorResponse {
                 error,
                 original_request: _,
+                code,
             }) => {
                 error!(
                     "Server error received: {}. Check server logs for details.",
@@ -243,30 +266,132 @@ impl MutantClient {
                 {
                     error!("Error occurred during export request: {}", error);
                     let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::PadExists(sender)) =
+                    requests.remove(&PendingRequestKey::PadExists)
+                {
+                    error!("Error occurred during pad exists request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::Verify(sender)) =
+                    requests.remove(&PendingRequestKey::Verify)
+                {
+                    error!("Error occurred during verify request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::EstimateStore(sender)) =
+                    requests.remove(&PendingRequestKey::EstimateStore)
+                {
+                    error!("Error occurred during estimate store request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::Collection(sender)) =
+                    requests.remove(&PendingRequestKey::Collection)
+                {
+                    error!("Error occurred during collection request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::Alias(sender)) =
+                    requests.remove(&PendingRequestKey::Alias)
+                {
+                    error!("Error occurred during alias request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::Rename(sender)) =
+                    requests.remove(&PendingRequestKey::Rename)
+                {
+                    error!("Error occurred during rename request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::Restore(sender)) =
+                    requests.remove(&PendingRequestKey::Restore)
+                {
+                    error!("Error occurred during restore request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::StoreMany(sender)) =
+                    requests.remove(&PendingRequestKey::StoreMany)
+                {
+                    error!("Error occurred during store many request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::RemoveMany(sender)) =
+                    requests.remove(&PendingRequestKey::RemoveMany)
+                {
+                    error!("Error occurred during remove many request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::Recover(sender)) =
+                    requests.remove(&PendingRequestKey::Recover)
+                {
+                    error!("Error occurred during recover request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::InitReport(sender)) =
+                    requests.remove(&PendingRequestKey::InitReport)
+                {
+                    error!("Error occurred during init report request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::MirrorPush(sender)) =
+                    requests.remove(&PendingRequestKey::MirrorPush)
+                {
+                    error!("Error occurred during mirror push request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::MirrorCheck(sender)) =
+                    requests.remove(&PendingRequestKey::MirrorCheck)
+                {
+                    error!("Error occurred during mirror check request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::CleanCache(sender)) =
+                    requests.remove(&PendingRequestKey::CleanCache)
+                {
+                    error!("Error occurred during clean cache request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::ResetPrepare(sender)) =
+                    requests.remove(&PendingRequestKey::ResetPrepare)
+                {
+                    error!("Error occurred during reset prepare request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::ResetCommit(sender)) =
+                    requests.remove(&PendingRequestKey::ResetCommit)
+                {
+                    error!("Error occurred during reset commit request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::SubscribeNotifications(sender)) =
+                    requests.remove(&PendingRequestKey::SubscribeNotifications)
+                {
+                    error!(
+                        "Error occurred during notifications subscription: {}",
+                        error
+                    );
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::TaskHistory(sender)) =
+                    requests.remove(&PendingRequestKey::TaskHistory)
+                {
+                    error!("Error occurred during task history request: {}", error);
+                    let _ = sender.send(Err(ClientError::ServerError(error)));
+                } else if let Some(PendingSender::StatKey(sender)) =
+                    requests.remove(&PendingRequestKey::StatKey)
+                {
+                    if code == Some(ErrorCode::KeyNotFound) {
+                        let _ = sender.send(Ok(None));
+                    } else {
+                        error!("Error occurred during stat request: {}", error);
+                        let _ = sender.send(Err(ClientError::ServerError(error)));
+                    }
                 } else {
                     warn!("Received server error, but no matching pending request found.");
                 }
             }
-            Response::RmSuccess(RmSuccessResponse { user_key: _ }) => {
+            Response::RmSuccess(rm_response) => {
                 let pending_sender = pending_requests
                     .lock()
                     .unwrap()
                     .remove(&PendingRequestKey::Rm);
                 if let Some(PendingSender::Rm(sender)) = pending_sender {
-                    if sender.send(Ok(())).is_err() {
+                    if sender.send(Ok(rm_response)).is_err() {
                         warn!("Failed to send RM success response (receiver dropped)");
                     }
                 } else {
                     warn!("Received RM success response but no Rm request was pending");
                 }
             }
-            Response::ListKeys(ListKeysResponse { keys }) => {
+            Response::ListKeys(list_keys_response) => {
                 let pending_sender = pending_requests
                     .lock()
                     .unwrap()
                     .remove(&PendingRequestKey::ListKeys);
                 if let Some(PendingSender::ListKeys(sender)) = pending_sender {
-                    if sender.send(Ok(keys)).is_err() {
+                    if sender.send(Ok(list_keys_response)).is_err() {
                         warn!("Failed to send ListKeys response (receiver dropped)");
                     }
                 } else {
@@ -312,6 +437,246 @@ impl MutantClient {
                     warn!("Received Export response but no Export request was pending");
                 }
             }
+            Response::PadExists(pad_exists_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::PadExists);
+                if let Some(PendingSender::PadExists(sender)) = pending_sender {
+                    if sender.send(Ok(pad_exists_response)).is_err() {
+                        warn!("Failed to send PadExists response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received PadExists response but no PadExists request was pending");
+                }
+            }
+            Response::Verify(verify_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::Verify);
+                if let Some(PendingSender::Verify(sender)) = pending_sender {
+                    if sender.send(Ok(verify_response)).is_err() {
+                        warn!("Failed to send Verify response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Verify response but no Verify request was pending");
+                }
+            }
+            Response::EstimateStore(estimate_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::EstimateStore);
+                if let Some(PendingSender::EstimateStore(sender)) = pending_sender {
+                    if sender.send(Ok(estimate_response.estimate)).is_err() {
+                        warn!("Failed to send EstimateStore response (receiver dropped)");
+                    }
+                } else {
+                    warn!(
+                        "Received EstimateStore response but no EstimateStore request was pending"
+                    );
+                }
+            }
+            Response::Collection(collection_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::Collection);
+                if let Some(PendingSender::Collection(sender)) = pending_sender {
+                    if sender.send(Ok(collection_response)).is_err() {
+                        warn!("Failed to send Collection response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Collection response but no Collection request was pending");
+                }
+            }
+            Response::Alias(alias_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::Alias);
+                if let Some(PendingSender::Alias(sender)) = pending_sender {
+                    if sender.send(Ok(alias_response)).is_err() {
+                        warn!("Failed to send Alias response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Alias response but no Alias request was pending");
+                }
+            }
+            Response::Rename(rename_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::Rename);
+                if let Some(PendingSender::Rename(sender)) = pending_sender {
+                    if sender.send(Ok(rename_response)).is_err() {
+                        warn!("Failed to send Rename response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Rename response but no Rename request was pending");
+                }
+            }
+            Response::Restore(restore_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::Restore);
+                if let Some(PendingSender::Restore(sender)) = pending_sender {
+                    if sender.send(Ok(restore_response)).is_err() {
+                        warn!("Failed to send Restore response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Restore response but no Restore request was pending");
+                }
+            }
+            Response::Recover(recover_response) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::Recover);
+                if let Some(PendingSender::Recover(sender)) = pending_sender {
+                    if sender.send(Ok(recover_response)).is_err() {
+                        warn!("Failed to send Recover response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Recover response but no Recover request was pending");
+                }
+            }
+            Response::InitReport(outcome) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::InitReport);
+                if let Some(PendingSender::InitReport(sender)) = pending_sender {
+                    if sender.send(Ok(outcome)).is_err() {
+                        warn!("Failed to send InitReport response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received InitReport response but no InitReport request was pending");
+                }
+            }
+            Response::MirrorPush(manifest) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::MirrorPush);
+                if let Some(PendingSender::MirrorPush(sender)) = pending_sender {
+                    if sender.send(Ok(manifest)).is_err() {
+                        warn!("Failed to send MirrorPush response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received MirrorPush response but no MirrorPush request was pending");
+                }
+            }
+            Response::MirrorCheck(check) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::MirrorCheck);
+                if let Some(PendingSender::MirrorCheck(sender)) = pending_sender {
+                    if sender.send(Ok(check)).is_err() {
+                        warn!("Failed to send MirrorCheck response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received MirrorCheck response but no MirrorCheck request was pending");
+                }
+            }
+            Response::CacheClean(report) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::CleanCache);
+                if let Some(PendingSender::CleanCache(sender)) = pending_sender {
+                    if sender.send(Ok(report)).is_err() {
+                        warn!("Failed to send CacheClean response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received CacheClean response but no CleanCache request was pending");
+                }
+            }
+            Response::ResetPrepare(prepared) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::ResetPrepare);
+                if let Some(PendingSender::ResetPrepare(sender)) = pending_sender {
+                    if sender.send(Ok(prepared)).is_err() {
+                        warn!("Failed to send ResetPrepare response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received ResetPrepare response but no ResetPrepare request was pending");
+                }
+            }
+            Response::Reset(reset) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::ResetCommit);
+                if let Some(PendingSender::ResetCommit(sender)) = pending_sender {
+                    if sender.send(Ok(reset)).is_err() {
+                        warn!("Failed to send Reset response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received Reset response but no ResetCommit request was pending");
+                }
+            }
+            Response::SubscribeNotifications(_) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::SubscribeNotifications);
+                if let Some(PendingSender::SubscribeNotifications(sender)) = pending_sender {
+                    if sender.send(Ok(())).is_err() {
+                        warn!("Failed to send SubscribeNotifications response (receiver dropped)");
+                    }
+                } else {
+                    warn!(
+                        "Received SubscribeNotifications response but no subscription was pending"
+                    );
+                }
+            }
+            Response::Notification(notification) => {
+                let mut notifications = notifications.lock().unwrap();
+                match notifications.as_ref() {
+                    Some(notification_tx) => {
+                        if notification_tx.send(notification).is_err() {
+                            debug!("Notification receiver dropped, no longer forwarding");
+                            *notifications = None;
+                        }
+                    }
+                    None => debug!("Received a notification without being subscribed"),
+                }
+            }
+            Response::StatKey(details) => {
+                let pending_sender = pending_requests
+                    .lock()
+                    .unwrap()
+                    .remove(&PendingRequestKey::StatKey);
+                if let Some(PendingSender::StatKey(sender)) = pending_sender {
+                    if sender.send(Ok(Some(details))).is_err() {
+                        warn!("Failed to send StatKey response (receiver dropped)");
+                    }
+                } else {
+                    warn!("Received StatKey response but no StatKey request was pending");
+                }
+            }
+            Response::BatchResult(batch_result) => {
+                let key = match batch_result.operation {
+                    BatchOperation::Store => PendingRequestKey::StoreMany,
+                    BatchOperation::Remove => PendingRequestKey::RemoveMany,
+                };
+                let pending_sender = pending_requests.lock().unwrap().remove(&key);
+                match pending_sender {
+                    Some(PendingSender::StoreMany(sender))
+                    | Some(PendingSender::RemoveMany(sender)) => {
+                        if sender.send(Ok(batch_result.into())).is_err() {
+                            warn!("Failed to send BatchResult response (receiver dropped)");
+                        }
+                    }
+                    _ => warn!("Received BatchResult response but no batch request was pending"),
+                }
+            }
             Response::TaskStopped(res) => handle_task_stopped(res, pending_requests.clone()),
         }
     }
@@ -330,6 +695,7 @@ impl MutantClient {
                                             &self.tasks,
                                             &self.task_channels,
                                             &self.pending_requests,
+                                            &self.notifications,
                                         );
                                         return Some(Ok(response));
                                     }
@@ -344,10 +710,12 @@ impl MutantClient {
                         }
                         ewebsock::WsEvent::Error(e) => {
                             error!("WebSocket error: {}", e);
+                            self.connection_lost();
                             return Some(Err(ClientError::WebSocketError(e.to_string())));
                         }
                         ewebsock::WsEvent::Closed => {
                             debug!("WebSocket connection closed");
+                            self.connection_lost();
                             return None;
                         }
                         ewebsock::WsEvent::Opened => {
@@ -365,6 +733,13 @@ impl MutantClient {
             None
         }
     }
+
+    /// Drops the requests waiting for a response, which then fail with
+    /// [`ClientError::ConnectionLost`] instead of waiting forever.
+    fn connection_lost(&self) {
+        *self.state.lock().unwrap() = ConnectionState::Disconnected;
+        self.pending_requests.lock().unwrap().clear();
+    }
 }
 
 // Correctly define the function to accept Arc<Mutex<...>>
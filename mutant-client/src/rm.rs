@@ -0,0 +1,23 @@
+use mutant_protocol::RmSuccessResponse;
+
+/// Outcome of a `rm` request. A remove whose response was lost with the connection is
+/// sent again once reconnected, removing a key that is already gone succeeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RmReport {
+    pub user_key: String,
+    /// Whether the key was there when the request that was answered came in.
+    pub existed: bool,
+    /// The connection dropped before the first request was answered. The key may then
+    /// have been removed by that request even though `existed` is false.
+    pub retried: bool,
+}
+
+impl RmReport {
+    pub(crate) fn new(response: RmSuccessResponse, retried: bool) -> Self {
+        Self {
+            user_key: response.user_key,
+            existed: response.existed,
+            retried,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use mutant_client::{MutantClient, RmReport};
+use mutant_protocol::{Request, Response, RmSuccessResponse};
+use warp::Filter;
+use warp::ws::{Message, Ws};
+
+/// Stands in for the daemon: removes the keys of the `Rm` requests it gets, but drops the
+/// first connection right after removing the key, before answering.
+fn flaky_daemon(keys: Arc<Mutex<HashSet<String>>>) -> String {
+    let connections = Arc::new(AtomicUsize::new(0));
+    let route = warp::path("ws").and(warp::ws()).map(move |ws: Ws| {
+        let keys = keys.clone();
+        let first = connections.fetch_add(1, Ordering::SeqCst) == 0;
+        ws.on_upgrade(move |socket| async move {
+            let (mut tx, mut rx) = socket.split();
+            while let Some(Ok(message)) = rx.next().await {
+                let Ok(text) = message.to_str() else {
+                    continue;
+                };
+                let Ok(Request::Rm(req)) = serde_json::from_str(text) else {
+                    continue;
+                };
+
+                let existed = keys.lock().unwrap().remove(&req.user_key);
+                if first {
+                    return;
+                }
+                let response = Response::RmSuccess(RmSuccessResponse {
+                    user_key: req.user_key,
+                    existed,
+                });
+                let json = serde_json::to_string(&response).unwrap();
+                if tx.send(Message::text(json)).await.is_err() {
+                    return;
+                }
+            }
+        })
+    });
+
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    format!("ws://{}/ws", addr)
+}
+
+#[tokio::test]
+async fn test_rm_is_sent_again_when_the_connection_drops() {
+    let keys = Arc::new(Mutex::new(HashSet::from(["doomed".to_string()])));
+    let mut client = MutantClient::new();
+    client.connect(&flaky_daemon(keys.clone())).await.unwrap();
+
+    let report = tokio::time::timeout(Duration::from_secs(10), client.rm("doomed"))
+        .await
+        .expect("the lost response is not waited for forever")
+        .unwrap();
+
+    // The first attempt removed the key, the second one found it gone.
+    assert_eq!(
+        report,
+        RmReport {
+            user_key: "doomed".to_string(),
+            existed: false,
+            retried: true,
+        }
+    );
+    assert!(keys.lock().unwrap().is_empty());
+
+    // The client stays connected to the daemon it reconnected to.
+    keys.lock().unwrap().insert("other".to_string());
+    let report = client.rm("other").await.unwrap();
+    assert!(report.existed);
+    assert!(!report.retried);
+}
@@ -1,7 +1,10 @@
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 use xdg::BaseDirectories;
 
-use mutant_lib::{config::NetworkChoice, MutAnt};
+use mutant_lib::{
+    config::{MutAntConfig, NetworkChoice},
+    MutAnt,
+};
 use tokio::sync::{RwLock, OnceCell};
 use warp::Filter;
 
@@ -16,40 +19,76 @@ use tokio::signal;
 // Thread-safe singleton to track public-only mode
 pub static PUBLIC_ONLY_MODE: OnceCell<bool> = OnceCell::const_new();
 
+/// Why the last start of the daemon was refused, for the CLI that started it in the
+/// background and only finds it gone.
+pub const STARTUP_ERROR_FILE: &str = "/tmp/mutant-daemon.error";
+
 /// Helper function to initialize MutAnt based on network choice and private key
-async fn init_mutant(network_choice: NetworkChoice, private_key: Option<String>) -> Result<(MutAnt, bool), Error> {
+async fn init_mutant(
+    network_choice: NetworkChoice,
+    private_key: Option<String>,
+    config: MutAntConfig,
+) -> Result<(MutAnt, bool), Error> {
     let mut is_public_only = private_key.is_none();
 
-    let mutant = match (network_choice, private_key) {
+    let builder = match (network_choice, private_key) {
         // Full access with private key
         (NetworkChoice::Devnet, _) => {
             log::info!("Running in local mode");
             is_public_only = false;
-            MutAnt::init_local().await
+            MutAnt::public_builder()
+        }
+        (_, Some(key)) if config.allow_dev_key_on_mainnet => {
+            log::warn!(
+                "Running in {:?} mode, the devnet key is accepted",
+                network_choice
+            );
+            MutAnt::builder(&key)
         }
         (NetworkChoice::Alphanet, Some(key)) => {
             log::info!("Running in alphanet mode");
-            MutAnt::init_alphanet(&key).await
+            MutAnt::builder(&key)
         }
         (NetworkChoice::Mainnet, Some(key)) => {
             log::info!("Running in mainnet mode");
-            MutAnt::init(&key).await
+            MutAnt::builder(&key)
         }
 
         // Public-only mode (no private key)
         (NetworkChoice::Alphanet, None) => {
             log::info!("Running in alphanet public-only mode");
-            MutAnt::init_public_alphanet().await
+            MutAnt::public_builder()
         }
         (NetworkChoice::Mainnet, None) => {
             log::info!("Running in mainnet public-only mode");
-            MutAnt::init_public().await
+            MutAnt::public_builder()
         }
-    }.map_err(Error::MutAnt)?;
+    };
+    let mutant = builder
+        .network(network_choice)
+        .config(config)
+        .build()
+        .await
+        .map_err(explain_init_error)?;
 
     Ok((mutant, is_public_only))
 }
 
+/// Explains why the key of the local devnet is refused on a public network.
+fn explain_init_error(e: mutant_lib::error::Error) -> Error {
+    match e {
+        mutant_lib::error::Error::NetworkMismatch {
+            wallet_network: NetworkChoice::Devnet,
+            ..
+        } => Error::NetworkMismatch(
+            e,
+            "this is the publicly known key of the local devnet, anyone can read what is \
+             stored with it and spend from it. Did you mean --local?",
+        ),
+        e => Error::MutAnt(e),
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize)]
 struct NetworkConfig {
     public_key: String,
@@ -136,6 +175,8 @@ pub struct AppOptions {
     pub local: bool,
     pub alphanet: bool,
     pub ignore_ctrl_c: bool,
+    /// Settings of the MutAnt instance served.
+    pub config: MutAntConfig,
 }
 
 pub async fn run(options: AppOptions) -> Result<(), Error> {
@@ -153,6 +194,7 @@ pub async fn run(options: AppOptions) -> Result<(), Error> {
     // Note: The lockfile crate typically manages this implicitly through the file lock.
     // Writing the PID might be redundant or could be handled differently.
     std::fs::write("/tmp/mutant-daemon.lock", std::process::id().to_string())?;
+    let _ = std::fs::remove_file(STARTUP_ERROR_FILE);
 
     log::info!("Starting Mutant Daemon...");
 
@@ -201,7 +243,7 @@ pub async fn run(options: AppOptions) -> Result<(), Error> {
     };
 
     // Initialize MutAnt with the appropriate mode
-    let (mutant, is_public_only) = init_mutant(network_choice, private_key).await?;
+    let (mutant, is_public_only) = init_mutant(network_choice, private_key, options.config).await?;
     let mutant = Arc::new(mutant);
 
     // Set the public-only mode flag
@@ -210,6 +252,18 @@ pub async fn run(options: AppOptions) -> Result<(), Error> {
     log::info!("MutAnt initialized successfully{}",
         if is_public_only { " in public-only mode" } else { "" });
 
+    // A push that failed last time is retried right away, the remote index is behind until then.
+    if !is_public_only {
+        let mutant = mutant.clone();
+        tokio::spawn(async move {
+            match mutant.retry_pending_push().await {
+                Ok(Some(_)) => log::info!("Pushed the pending changes of the remote index"),
+                Ok(None) => {}
+                Err(e) => log::warn!("The remote index is still behind, pushing it failed: {}", e),
+            }
+        });
+    }
+
     // Initialize Task Management
     let tasks: TaskMap = Arc::new(RwLock::new(HashMap::new()));
     log::info!("Task manager initialized.");
@@ -218,6 +272,10 @@ pub async fn run(options: AppOptions) -> Result<(), Error> {
     let active_keys: handlers::ActiveKeysMap = Arc::new(RwLock::new(HashMap::new()));
     log::info!("Active keys manager initialized.");
 
+    // Keep handles around to clean up once the server has shut down
+    let mutant_for_shutdown = mutant.clone();
+    let tasks_for_shutdown = tasks.clone();
+
     // Define WebSocket route using the actual handler
     let ws_route = warp::path("ws")
         .and(warp::ws())
@@ -270,7 +328,40 @@ pub async fn run(options: AppOptions) -> Result<(), Error> {
     tokio::task::spawn(server).await.map_err(Error::JoinError)?;
     log::info!("Server task finished.");
 
+    for (task_id, entry) in tasks_for_shutdown.write().await.drain() {
+        if let Some(abort_handle) = entry.abort_handle {
+            log::info!("Aborting task {} on shutdown", task_id);
+            abort_handle.abort();
+        }
+    }
+
+    // The local cache is saved before the push, a push that fails is retried on the next start.
+    match (*mutant_for_shutdown).clone().close().await {
+        Ok(()) => log::info!("MutAnt closed."),
+        Err(e) => log::warn!("Failed to close MutAnt: {}", e),
+    }
+
     // The lock file (_lock_file) is released automatically when the process exits
     // because the file descriptor associated with the lock is closed.
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dev_key_on_mainnet_is_explained() {
+        let error = explain_init_error(mutant_lib::error::Error::NetworkMismatch {
+            wallet_network: NetworkChoice::Devnet,
+            configured: NetworkChoice::Mainnet,
+        });
+        let message = error.to_string();
+        assert!(matches!(error, Error::NetworkMismatch(..)));
+        assert!(message.contains("publicly known key"));
+        assert!(message.contains("--i-know-what-im-doing"));
+
+        let error = explain_init_error(mutant_lib::error::Error::OperationCancelled);
+        assert!(matches!(error, Error::MutAnt(_)));
+    }
+}
@@ -20,6 +20,9 @@ pub enum Error {
     #[error("IO error (formatted): {0}")]
     IoError(String),
 
+    #[error("{0} ({1} Pass --i-know-what-im-doing to use this wallet anyway)")]
+    NetworkMismatch(mutant_lib::error::Error, &'static str),
+
     #[error("Library error (formatted): {0}")]
     LibError(mutant_lib::error::Error),
 
@@ -1,5 +1,4 @@
 use futures_util::sink::SinkExt;
-use tokio::sync::mpsc;
 use warp::ws::{Message, WebSocket};
 
 use crate::error::Error as DaemonError;
@@ -18,5 +17,4 @@ pub(crate) async fn send_response(
     Ok(())
 }
 
-/// Type alias for the update channel sender
-pub(crate) type UpdateSender = mpsc::UnboundedSender<Response>;
+pub(crate) use super::update_channel::UpdateSender;
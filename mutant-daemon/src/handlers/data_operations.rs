@@ -1,18 +1,25 @@
+use std::collections::HashSet;
+use std::future::Future;
 use std::sync::Arc;
+use futures_util::StreamExt;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 use crate::error::Error as DaemonError;
-use super::{TaskEntry, TaskMap, ActiveKeysMap, try_register_key, release_key, is_public_only_mode, PUBLIC_ONLY_ERROR_MSG};
-use mutant_lib::storage::ScratchpadAddress;
+use super::{TaskEntry, TaskMap, insert_task, ActiveKeysMap, try_register_key, release_key, register_free_keys, cancel_store, is_public_only_mode, PUBLIC_ONLY_ERROR_MSG};
+use mutant_lib::storage::{measure_io, FetchStream, IoStats, ScratchpadAddress};
 use mutant_lib::MutAnt;
 use mutant_protocol::{
-    ErrorResponse, GetCallback, GetEvent, GetRequest, GetResult, PutCallback, PutEvent, PutRequest,
-    PutResult, Response, RmRequest, RmSuccessResponse, Task, TaskCreatedResponse, TaskProgress,
-    TaskResult, TaskResultResponse, TaskResultType, TaskStatus, TaskType, TaskUpdateResponse,
+    BatchOperation, BatchResultResponse, ErrorResponse, GetCallback, GetEvent, GetRequest,
+    GetResult, ProgressSnapshot, PutCallback, PutEvent, PutRequest, PutResult, RemoveManyRequest,
+    Response, RmRequest, RmSuccessResponse, StoreManyRequest, Task, TaskCreatedResponse,
+    TaskProgress, TaskResult, TaskResultResponse, TaskResultType, TaskStatus, TaskType,
+    TaskUpdateResponse,
 };
 
 use super::common::UpdateSender;
+use super::notifications::notify_keys_changed;
 
 pub(crate) async fn handle_put(
     req: PutRequest,
@@ -28,6 +35,7 @@ pub(crate) async fn handle_put(
             .send(Response::Error(ErrorResponse {
                 error: PUBLIC_ONLY_ERROR_MSG.to_string(),
                 original_request: Some(original_request_str.to_string()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
@@ -61,6 +69,13 @@ pub(crate) async fn handle_put(
         ))
     })?;
     let data_arc = Arc::new(data_bytes_vec); // Wrap in Arc
+    let key_existed = match mutant.contains_key(&user_key).await {
+        Ok(key_existed) => key_existed,
+        Err(e) => {
+            release_key(&active_keys, &user_key).await;
+            return Err(e.into());
+        }
+    };
 
     let task = Task {
         id: task_id,
@@ -69,6 +84,7 @@ pub(crate) async fn handle_put(
         progress: None,
         result: TaskResult::Pending,
         key: Some(user_key.clone()),
+        snapshot: Some(ProgressSnapshot::new(data_arc.len() as u64)),
     };
     // We will insert the TaskEntry after spawning the task and getting the handle
 
@@ -107,10 +123,20 @@ pub(crate) async fn handle_put(
         let update_tx_clone = update_tx.clone();
         let task_id_clone = task_id;
         let tasks_clone = tasks.clone();
+        // Chunks kept and rewritten, reported by the update of an existing key.
+        let update_plan: Arc<std::sync::Mutex<Option<(usize, usize)>>> = Default::default();
+        let update_plan_clone = update_plan.clone();
         let callback: PutCallback = Arc::new(move |event: PutEvent| {
             let tx = update_tx_clone.clone();
             let task_id = task_id_clone;
             let tasks = tasks_clone.clone();
+            if let PutEvent::UpdatePlanned {
+                chunks_unchanged,
+                chunks_rewritten,
+            } = event
+            {
+                *update_plan_clone.lock().unwrap() = Some((chunks_unchanged, chunks_rewritten));
+            }
             Box::pin(async move {
                 let progress = TaskProgress::Put(event);
                 // Update task progress in map
@@ -118,12 +144,18 @@ pub(crate) async fn handle_put(
                 if let Some(entry) = tasks_guard.get_mut(&task_id) {
                     // Only update if the task is still considered InProgress
                     if entry.task.status == TaskStatus::InProgress {
+                        if let (Some(snapshot), TaskProgress::Put(event)) =
+                            (entry.task.snapshot.as_mut(), &progress)
+                        {
+                            snapshot.apply(event);
+                        }
                         entry.task.progress = Some(progress.clone());
                         // Send update via channel
                         let _ = tx.send(Response::TaskUpdate(TaskUpdateResponse {
                             task_id,
                             status: TaskStatus::InProgress,
                             progress: Some(progress),
+                            repeat: 1,
                         }));
                     } else {
                         log::warn!("Received PUT progress update for task not InProgress (status: {:?}). Ignoring. task_id={}", entry.task.status, task_id);
@@ -136,16 +168,19 @@ pub(crate) async fn handle_put(
         });
 
         // Call put with the callback
-        let result = mutant
-            .put(
-                &user_key,
-                data_to_put, // Pass the Arc<Vec<u8>>
-                req.mode,
-                req.public,
-                req.no_verify,
-                Some(callback), // Pass callback here
-            )
-            .await;
+        let (result, io) = with_io(mutant.config().io_accounting, async {
+            mutant
+                .put(
+                    &user_key,
+                    data_to_put, // Pass the Arc<Vec<u8>>
+                    req.mode,
+                    req.public,
+                    req.no_verify,
+                    Some(callback), // Pass callback here
+                )
+                .await
+        })
+        .await;
 
         let final_response = {
             let mut tasks_guard = tasks.write().await;
@@ -170,10 +205,19 @@ pub(crate) async fn handle_put(
                             };
 
                             entry.task.status = TaskStatus::Completed;
+                            let update_plan = *update_plan.lock().unwrap();
                             entry.task.result = TaskResult::Result(TaskResultType::Put(PutResult {
                                 public_address,
+                                chunks_unchanged: update_plan.map(|(unchanged, _)| unchanged),
+                                chunks_rewritten: update_plan.map(|(_, rewritten)| rewritten),
+                                io,
                             }));
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
+                            if key_existed {
+                                notify_keys_changed([], [], [user_key.clone()]);
+                            } else {
+                                notify_keys_changed([user_key.clone()], [], []);
+                            }
                             log::info!("PUT task completed successfully: task_id={}, user_key={}, source_path={}", task_id, user_key, source_path);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -184,7 +228,7 @@ pub(crate) async fn handle_put(
                         Err(e) => {
                             entry.task.status = TaskStatus::Failed;
                             entry.task.result = TaskResult::Error(e.to_string());
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::error!("PUT task failed: task_id={}, user_key={}, source_path={}, error={}", task_id, user_key, source_path, e);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -195,7 +239,7 @@ pub(crate) async fn handle_put(
                     }
                 } else {
                     log::info!("PUT task was stopped before completion: task_id={}", task_id);
-                    entry.abort_handle = None; // Ensure handle is cleared if stopped
+                    entry.finish();
                     None // No final result to send if stopped
                 }
             } else {
@@ -215,19 +259,79 @@ pub(crate) async fn handle_put(
 
     // Get the abort handle and create the TaskEntry
     let abort_handle = task_handle.abort_handle();
-    let task_entry = TaskEntry {
-        task, // The task struct created earlier
-        abort_handle: Some(abort_handle),
-    };
+    let task_entry = TaskEntry::new(task, abort_handle);
 
     // Insert the TaskEntry into the map *after* spawning
     {
-        tasks.write().await.insert(task_id, task_entry);
+        insert_task(&tasks, task_id, task_entry).await;
     }
 
     Ok(())
 }
 
+/// Runs `operation`, measuring the network calls it makes when `io_accounting` is on,
+/// for its result to report them.
+async fn with_io<F: Future>(io_accounting: bool, operation: F) -> (F::Output, Option<IoStats>) {
+    if io_accounting {
+        let (output, io) = measure_io(operation).await;
+        (output, Some(io))
+    } else {
+        (operation.await, None)
+    }
+}
+
+/// Pads fetched at once when writing a value to a file, which bounds the chunks held in
+/// memory however large the value is.
+const GET_STREAM_WINDOW: usize = 32;
+
+fn write_error(destination_path: &str, e: std::io::Error) -> DaemonError {
+    DaemonError::IoError(format!(
+        "Failed to write to destination file {}: {}",
+        destination_path, e
+    ))
+}
+
+/// Writes the chunks of `stream` to `destination_path` as they come, returning the size
+/// written. They go to `<destination_path>.partial` first, renamed once the value is
+/// complete and removed when a chunk fails, so the destination never holds part of it.
+async fn write_stream(stream: FetchStream, destination_path: &str) -> Result<usize, DaemonError> {
+    let partial_path = format!("{}.partial", destination_path);
+    match write_chunks(stream, &partial_path).await {
+        Ok(size) => {
+            fs::rename(&partial_path, destination_path)
+                .await
+                .map_err(|e| write_error(destination_path, e))?;
+            Ok(size)
+        }
+        Err(e) => {
+            if let Err(remove_error) = fs::remove_file(&partial_path).await {
+                log::warn!(
+                    "Failed to remove partial file {}: {}",
+                    partial_path,
+                    remove_error
+                );
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn write_chunks(mut stream: FetchStream, path: &str) -> Result<usize, DaemonError> {
+    let mut file = fs::File::create(path)
+        .await
+        .map_err(|e| write_error(path, e))?;
+    let mut size = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(DaemonError::LibError)?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| write_error(path, e))?;
+        size += chunk.len();
+    }
+    file.flush().await.map_err(|e| write_error(path, e))?;
+    Ok(size)
+}
+
 pub(crate) async fn handle_get(
     req: GetRequest,
     update_tx: UpdateSender,
@@ -240,6 +344,14 @@ pub(crate) async fn handle_get(
     let user_key = req.user_key.clone();
     let destination_path = req.destination_path.clone(); // Keep path for logging and writing
 
+    // Names of a catalogue resolve through a read-only instance loaded for this request only.
+    let mutant = match &req.index_file {
+        Some(index_file) => Arc::new(
+            MutAnt::init_from_index_file_on(index_file, mutant.network_choice()).await?,
+        ),
+        None => mutant,
+    };
+
     // Try to register the key for this task
     try_register_key(
         &active_keys,
@@ -257,6 +369,7 @@ pub(crate) async fn handle_get(
         progress: None,
         result: TaskResult::Pending,
         key: Some(user_key.clone()),
+        snapshot: None,
     };
 
     update_tx
@@ -309,6 +422,7 @@ pub(crate) async fn handle_get(
                             task_id,
                             status: TaskStatus::InProgress,
                             progress: Some(progress),
+                            repeat: 1,
                         }));
                     } else {
                         log::warn!("Received GET progress update for task not InProgress (status: {:?}). Ignoring. task_id={}", entry.task.status, task_id);
@@ -320,47 +434,46 @@ pub(crate) async fn handle_get(
             })
         });
 
-        // Check if the key exists first for private keys
-        let get_result = if req.public {
-            // TODO: Fix public key handling if necessary, ScratchpadAddress requires valid hex
-            match ScratchpadAddress::from_hex(&user_key) {
-                Ok(address) => mutant.get_public(&address, Some(callback)).await,
-                Err(hex_err) => {
-                    // Wrap the underlying lib error in DaemonError::LibError
-                    let lib_err = mutant_lib::error::Error::Internal(format!(
-                        "Invalid public key hex format for '{}': {}",
-                        user_key, hex_err
-                    ));
-                    Err(lib_err)
+        // Public values are fetched whole, the others are written as their chunks arrive
+        let (write_result, io) = with_io(mutant.config().io_accounting, async {
+            if req.public && req.index_file.is_none() {
+                // TODO: Fix public key handling if necessary, ScratchpadAddress requires valid hex
+                let get_result = match ScratchpadAddress::from_hex(&user_key) {
+                    Ok(address) => mutant.get_public(&address, Some(callback)).await,
+                    Err(hex_err) => {
+                        // Wrap the underlying lib error in DaemonError::LibError
+                        let lib_err = mutant_lib::error::Error::Internal(format!(
+                            "Invalid public key hex format for '{}': {}",
+                            user_key, hex_err
+                        ));
+                        Err(lib_err)
+                    }
+                };
+                match get_result {
+                    Ok(data_bytes) => fs::write(&destination_path, &data_bytes)
+                        .await
+                        .map_err(|e| write_error(&destination_path, e))
+                        .map(|_| data_bytes.len()),
+                    Err(e) => Err(DaemonError::LibError(e)), // Propagate the lib error
                 }
-            }
-        } else {
-            // Check if the key exists first for better error messages
-            if !mutant.contains_key(&user_key).await {
-                Err(mutant_lib::error::Error::Internal(format!(
-                    "Key '{}' not found",
-                    user_key
-                )))
             } else {
-                mutant.get(&user_key, Some(callback)).await // Pass callback
+                // Check if the key exists first for better error messages
+                match mutant.contains_key(&user_key).await {
+                    Ok(false) => Err(DaemonError::LibError(mutant_lib::error::Error::Internal(
+                        format!("Key '{}' not found", user_key),
+                    ))),
+                    Ok(true) => match mutant
+                        .fetch_stream(&user_key, GET_STREAM_WINDOW, Some(callback))
+                        .await
+                    {
+                        Ok(stream) => write_stream(stream, &destination_path).await,
+                        Err(e) => Err(DaemonError::LibError(e)),
+                    },
+                    Err(e) => Err(DaemonError::LibError(e)),
+                }
             }
-        };
-
-        let write_result = match get_result {
-            Ok(data_bytes) => {
-                // Write the received bytes to the destination path
-                fs::write(&destination_path, &data_bytes)
-                    .await
-                    .map_err(|e| {
-                        DaemonError::IoError(format!(
-                            "Failed to write to destination file {}: {}",
-                            destination_path, e
-                        ))
-                    })
-                    .map(|_| data_bytes) // Pass data_bytes through on success for tracing length maybe
-            }
-            Err(e) => Err(DaemonError::LibError(e)), // Propagate the lib error
-        };
+        })
+        .await;
 
         let final_response = {
             let mut tasks_guard = tasks.write().await;
@@ -368,14 +481,12 @@ pub(crate) async fn handle_get(
                 // Only update if the task hasn't been stopped externally
                 if entry.task.status != TaskStatus::Stopped {
                     match write_result {
-                        Ok(data_bytes) => {
+                        Ok(size) => {
                             entry.task.status = TaskStatus::Completed;
                             entry.task.result =
-                                TaskResult::Result(TaskResultType::Get(GetResult {
-                                    size: data_bytes.len(),
-                                }));
-                            entry.abort_handle = None; // Task finished, remove handle
-                            log::info!("GET task completed successfully: task_id={}, user_key={}, destination_path={}, bytes_written={}", task_id, user_key, destination_path, data_bytes.len());
+                                TaskResult::Result(TaskResultType::Get(GetResult { size, io }));
+                            entry.finish();
+                            log::info!("GET task completed successfully: task_id={}, user_key={}, destination_path={}, bytes_written={}", task_id, user_key, destination_path, size);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
                                 status: TaskStatus::Completed,
@@ -386,7 +497,7 @@ pub(crate) async fn handle_get(
                             let error_msg = e.to_string();
                             entry.task.status = TaskStatus::Failed;
                             entry.task.result = TaskResult::Error(error_msg.clone());
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::error!("GET task failed: task_id={}, user_key={}, destination_path={}, error={}", task_id, user_key, destination_path, error_msg);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -397,7 +508,7 @@ pub(crate) async fn handle_get(
                     }
                 } else {
                     log::info!("GET task was stopped before completion: task_id={}", task_id);
-                    entry.abort_handle = None; // Ensure handle is cleared if stopped
+                    entry.finish();
                     None // No final result to send if stopped
                 }
             } else {
@@ -418,14 +529,11 @@ pub(crate) async fn handle_get(
 
     // Get the abort handle and create the TaskEntry
     let abort_handle = task_handle.abort_handle();
-    let task_entry = TaskEntry {
-        task, // The task struct created earlier
-        abort_handle: Some(abort_handle),
-    };
+    let task_entry = TaskEntry::new(task, abort_handle);
 
     // Insert the TaskEntry into the map *after* spawning
     {
-        tasks.write().await.insert(task_id, task_entry);
+        insert_task(&tasks, task_id, task_entry).await;
     }
 
     Ok(())
@@ -435,6 +543,7 @@ pub(crate) async fn handle_rm(
     req: RmRequest,
     update_tx: UpdateSender,
     mutant: Arc<MutAnt>,
+    tasks: TaskMap,
     active_keys: ActiveKeysMap,
     original_request_str: &str,
 ) -> Result<(), DaemonError> {
@@ -444,6 +553,7 @@ pub(crate) async fn handle_rm(
             .send(Response::Error(ErrorResponse {
                 error: PUBLIC_ONLY_ERROR_MSG.to_string(),
                 original_request: Some(original_request_str.to_string()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
@@ -453,19 +563,23 @@ pub(crate) async fn handle_rm(
     log::info!("Starting RM task: user_key={}", user_key);
 
     // Check if the key exists first
-    let key_exists = mutant.contains_key(&user_key).await;
+    let key_exists = mutant.contains_key(&user_key).await?;
 
     if !key_exists {
         log::info!("RM task for non-existent key: user_key={}", user_key);
-        // Return an error for non-existent keys
+        // Removing a key that is already gone succeeds, a client can send it again
+        // when the connection dropped before the response
         return update_tx
-            .send(Response::Error(ErrorResponse {
-                error: format!("Key '{}' not found", user_key),
-                original_request: Some(original_request_str.to_string()),
+            .send(Response::RmSuccess(RmSuccessResponse {
+                user_key,
+                existed: false,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
 
+    // Removing a key that is still being stored interrupts the store
+    cancel_store(&active_keys, &tasks, &user_key).await;
+
     // Try to register the key for this task
     try_register_key(
         &active_keys,
@@ -476,7 +590,7 @@ pub(crate) async fn handle_rm(
         original_request_str,
     ).await?;
 
-    let result = mutant.rm(&user_key).await;
+    let result = mutant.remove(&user_key, req.promote_alias).await;
 
     // Release the key after the operation completes
     release_key(&active_keys, &user_key).await;
@@ -484,13 +598,18 @@ pub(crate) async fn handle_rm(
     let response = match result {
         Ok(_) => {
             log::info!("RM task completed successfully: user_key={}", user_key);
-            Response::RmSuccess(RmSuccessResponse { user_key })
+            notify_keys_changed([], [user_key.clone()], []);
+            Response::RmSuccess(RmSuccessResponse {
+                user_key,
+                existed: true,
+            })
         }
         Err(e) => {
             log::error!("RM task failed: user_key={}, error={}", user_key, e);
             Response::Error(ErrorResponse {
                 error: e.to_string(),
                 original_request: Some(original_request_str.to_string()),
+                code: None,
             })
         }
     };
@@ -501,3 +620,196 @@ pub(crate) async fn handle_rm(
 
     Ok(())
 }
+
+type BatchItems = Vec<(String, Result<(), String>)>;
+
+/// Starts the outcome of each key of a batch, failing right away the keys used by
+/// another operation and the ones repeated in the batch.
+fn claim_batch_items(keys: &[String], registered: &[String]) -> BatchItems {
+    let mut claimed = HashSet::new();
+
+    keys.iter()
+        .map(|key| {
+            let result = if claimed.contains(key) {
+                Err(format!("Key '{}' appears more than once in the batch", key))
+            } else if registered.contains(key) {
+                claimed.insert(key);
+                Ok(())
+            } else {
+                Err(format!(
+                    "Key '{}' is already being used by another operation",
+                    key
+                ))
+            };
+            (key.clone(), result)
+        })
+        .collect()
+}
+
+/// Fills in the outcome of the items handed to the library, which reports them in the
+/// same order.
+fn merge_batch_report(
+    per_item: &mut BatchItems,
+    report: Vec<(String, Result<(), mutant_lib::error::Error>)>,
+) {
+    let processed = per_item.iter_mut().filter(|(_, result)| result.is_ok());
+    for ((_, item_result), (_, result)) in processed.zip(report) {
+        *item_result = result.map_err(|e| e.to_string());
+    }
+}
+
+pub(crate) async fn handle_store_many(
+    req: StoreManyRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+    active_keys: ActiveKeysMap,
+    original_request_str: &str,
+) -> Result<(), DaemonError> {
+    // Check if we're in public-only mode
+    if is_public_only_mode() {
+        return update_tx
+            .send(Response::Error(ErrorResponse {
+                error: PUBLIC_ONLY_ERROR_MSG.to_string(),
+                original_request: Some(original_request_str.to_string()),
+                code: None,
+            }))
+            .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
+    }
+
+    let task_id = Uuid::new_v4();
+    let keys: Vec<String> = req.items.iter().map(|item| item.user_key.clone()).collect();
+    log::info!("Starting batch store of {} keys", keys.len());
+
+    let registered = register_free_keys(&active_keys, &keys, task_id, TaskType::Put).await;
+    let mut per_item = claim_batch_items(&keys, &registered);
+
+    let mut stores = Vec::new();
+    for (item, (_, result)) in req.items.into_iter().zip(per_item.iter_mut()) {
+        if result.is_err() {
+            continue;
+        }
+        match fs::read(&item.source_path).await {
+            Ok(data) => stores.push((item.user_key, data)),
+            Err(e) => {
+                *result = Err(format!(
+                    "Failed to read source file {}: {}",
+                    item.source_path, e
+                ))
+            }
+        }
+    }
+
+    let mut existing = HashSet::new();
+    for (key, _) in &stores {
+        // A failed index load fails the batch below, on every key.
+        if let Ok(true) = mutant.contains_key(key).await {
+            existing.insert(key.clone());
+        }
+    }
+    let report = mutant
+        .store_many(stores, req.mode, req.fail_fast, None)
+        .await;
+    let (updated, added): (Vec<String>, Vec<String>) = report
+        .iter()
+        .filter(|(_, result)| result.is_ok())
+        .map(|(key, _)| key.clone())
+        .partition(|key| existing.contains(key));
+    notify_keys_changed(added, [], updated);
+
+    // Release the keys after the operation completes
+    for key in &registered {
+        release_key(&active_keys, key).await;
+    }
+
+    merge_batch_report(&mut per_item, report);
+    let failed = per_item
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .count();
+    log::info!(
+        "Batch store completed: {} of {} keys failed",
+        failed,
+        per_item.len()
+    );
+
+    update_tx
+        .send(Response::BatchResult(BatchResultResponse {
+            operation: BatchOperation::Store,
+            per_item,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_remove_many(
+    req: RemoveManyRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+    active_keys: ActiveKeysMap,
+    original_request_str: &str,
+) -> Result<(), DaemonError> {
+    // Check if we're in public-only mode
+    if is_public_only_mode() {
+        return update_tx
+            .send(Response::Error(ErrorResponse {
+                error: PUBLIC_ONLY_ERROR_MSG.to_string(),
+                original_request: Some(original_request_str.to_string()),
+                code: None,
+            }))
+            .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
+    }
+
+    let task_id = Uuid::new_v4();
+    log::info!("Starting batch removal of {} keys", req.user_keys.len());
+
+    let registered = register_free_keys(&active_keys, &req.user_keys, task_id, TaskType::Rm).await;
+    let mut per_item = claim_batch_items(&req.user_keys, &registered);
+
+    let removes = per_item
+        .iter()
+        .filter(|(_, result)| result.is_ok())
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+    let mut existing = HashSet::new();
+    for key in &removes {
+        // A failed index load fails the batch below, on every key.
+        if let Ok(true) = mutant.contains_key(key).await {
+            existing.insert(key.clone());
+        }
+    }
+    let report = mutant.remove_many(removes).await;
+    notify_keys_changed(
+        [],
+        report
+            .iter()
+            .filter(|(key, result)| result.is_ok() && existing.contains(key))
+            .map(|(key, _)| key.clone()),
+        [],
+    );
+
+    // Release the keys after the operation completes
+    for key in &registered {
+        release_key(&active_keys, key).await;
+    }
+
+    merge_batch_report(&mut per_item, report);
+    let failed = per_item
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .count();
+    log::info!(
+        "Batch removal completed: {} of {} keys failed",
+        failed,
+        per_item.len()
+    );
+
+    update_tx
+        .send(Response::BatchResult(BatchResultResponse {
+            operation: BatchOperation::Remove,
+            per_item,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
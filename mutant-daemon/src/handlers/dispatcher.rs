@@ -7,11 +7,23 @@ use mutant_lib::MutAnt;
 use mutant_protocol::Request;
 
 use super::common::UpdateSender;
-use super::data_operations::{handle_get, handle_put, handle_rm};
-use super::import_export::{handle_export, handle_import};
-use super::metadata::{handle_list_keys, handle_stats};
-use super::system_operations::{handle_health_check, handle_purge, handle_sync};
-use super::task_management::{handle_list_tasks, handle_query_task, handle_stop_task};
+use super::data_operations::{
+    handle_get, handle_put, handle_remove_many, handle_rm, handle_store_many,
+};
+use super::import_export::{handle_export, handle_import, handle_mirror_check, handle_mirror_push};
+use super::metadata::{
+    handle_alias, handle_collection, handle_estimate_store, handle_init_report, handle_list_keys,
+    handle_pad_exists, handle_recover, handle_rename, handle_restore, handle_stat_key,
+    handle_stats, handle_verify,
+};
+use super::notifications::handle_subscribe_notifications;
+use super::reset::{handle_reset_commit, handle_reset_prepare};
+use super::system_operations::{
+    handle_clean_cache, handle_health_check, handle_purge, handle_sync,
+};
+use super::task_management::{
+    handle_list_tasks, handle_query_task, handle_stop_task, handle_task_history,
+};
 
 pub(crate) async fn handle_request(
     request: Request,
@@ -28,7 +40,10 @@ pub(crate) async fn handle_request(
             handle_query_task(query_req, update_tx, tasks, original_request_str).await?
         }
         Request::ListTasks(list_req) => handle_list_tasks(list_req, update_tx, tasks).await?,
-        Request::Rm(rm_req) => handle_rm(rm_req, update_tx, mutant, active_keys, original_request_str).await?,
+        Request::TaskHistory(history_req) => {
+            handle_task_history(history_req, update_tx, tasks).await?
+        }
+        Request::Rm(rm_req) => handle_rm(rm_req, update_tx, mutant, tasks, active_keys, original_request_str).await?,
         Request::ListKeys(list_keys_req) => {
             handle_list_keys(list_keys_req, update_tx, mutant).await?
         }
@@ -43,6 +58,60 @@ pub(crate) async fn handle_request(
         Request::StopTask(stop_task_req) => {
             handle_stop_task(stop_task_req, update_tx, tasks).await?
         }
+        Request::PadExists(pad_exists_req) => {
+            handle_pad_exists(pad_exists_req, update_tx, mutant).await?
+        }
+        Request::Verify(verify_req) => handle_verify(verify_req, update_tx, mutant).await?,
+        Request::EstimateStore(estimate_req) => {
+            handle_estimate_store(estimate_req, update_tx, mutant).await?
+        }
+        Request::Collection(collection_req) => {
+            handle_collection(collection_req, update_tx, mutant, active_keys).await?
+        }
+        Request::Alias(alias_req) => handle_alias(alias_req, update_tx, mutant).await?,
+        Request::Rename(rename_req) => handle_rename(rename_req, update_tx, mutant).await?,
+        Request::Restore(restore_req) => handle_restore(restore_req, update_tx, mutant).await?,
+        Request::Recover(recover_req) => handle_recover(recover_req, update_tx, mutant).await?,
+        Request::StatKey(stat_req) => handle_stat_key(stat_req, update_tx, mutant).await?,
+        Request::InitReport(report_req) => {
+            handle_init_report(report_req, update_tx, mutant).await?
+        }
+        Request::MirrorPush(mirror_req) => {
+            handle_mirror_push(mirror_req, update_tx, mutant).await?
+        }
+        Request::MirrorCheck(mirror_req) => {
+            handle_mirror_check(mirror_req, update_tx, mutant).await?
+        }
+        Request::CleanCache(clean_req) => handle_clean_cache(clean_req, update_tx, mutant).await?,
+        Request::ResetPrepare(reset_req) => {
+            handle_reset_prepare(reset_req, update_tx, mutant).await?
+        }
+        Request::ResetCommit(reset_req) => {
+            handle_reset_commit(reset_req, update_tx, mutant, active_keys).await?
+        }
+        Request::SubscribeNotifications(subscribe_req) => {
+            handle_subscribe_notifications(subscribe_req, update_tx).await?
+        }
+        Request::StoreMany(store_many_req) => {
+            handle_store_many(
+                store_many_req,
+                update_tx,
+                mutant,
+                active_keys,
+                original_request_str,
+            )
+            .await?
+        }
+        Request::RemoveMany(remove_many_req) => {
+            handle_remove_many(
+                remove_many_req,
+                update_tx,
+                mutant,
+                active_keys,
+                original_request_str,
+            )
+            .await?
+        }
     }
     Ok(())
 }
@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::sync::Arc;
 use tokio::fs;
 
@@ -6,8 +7,8 @@ use super::{is_public_only_mode, PUBLIC_ONLY_ERROR_MSG};
 use mutant_lib::storage::PadInfo;
 use mutant_lib::MutAnt;
 use mutant_protocol::{
-    ErrorResponse, ExportRequest, ExportResponse, ExportResult, ImportRequest, ImportResponse, ImportResult,
-    Response,
+    ErrorResponse, ExportRequest, ExportResponse, ExportResult, ImportRequest, ImportResponse,
+    ImportResult, MirrorCheckRequest, MirrorPushRequest, Response,
 };
 
 use super::common::UpdateSender;
@@ -25,6 +26,7 @@ pub(crate) async fn handle_import(
             .send(Response::Error(ErrorResponse {
                 error: PUBLIC_ONLY_ERROR_MSG.to_string(),
                 original_request: Some(serde_json::to_string(&req).unwrap_or_default()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
@@ -67,12 +69,22 @@ pub(crate) async fn handle_export(
             .send(Response::Error(ErrorResponse {
                 error: PUBLIC_ONLY_ERROR_MSG.to_string(),
                 original_request: Some(serde_json::to_string(&req).unwrap_or_default()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
 
     let destination_path = req.destination_path.clone();
 
+    if req.public_catalogue {
+        let nb_keys_exported = mutant.export_public_catalogue(&destination_path).await?;
+        return update_tx
+            .send(Response::Export(ExportResponse {
+                result: ExportResult { nb_keys_exported },
+            }))
+            .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
+    }
+
     let pads_hex = mutant.export_raw_pads_private_key().await?;
 
     let pads_hex = serde_json::to_vec(&pads_hex)
@@ -94,3 +106,53 @@ pub(crate) async fn handle_export(
 
     Ok(())
 }
+
+pub(crate) async fn handle_mirror_push(
+    req: MirrorPushRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!(
+        "Handling MirrorPush request for {} under '{}'",
+        req.dir,
+        req.prefix
+    );
+
+    if is_public_only_mode() {
+        return update_tx
+            .send(Response::Error(ErrorResponse {
+                error: PUBLIC_ONLY_ERROR_MSG.to_string(),
+                original_request: Some(serde_json::to_string(&req).unwrap_or_default()),
+                code: None,
+            }))
+            .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
+    }
+
+    let manifest = mutant.store_tree(Path::new(&req.dir), &req.prefix).await?;
+
+    update_tx
+        .send(Response::MirrorPush(manifest))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_mirror_check(
+    req: MirrorCheckRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!(
+        "Handling MirrorCheck request for {} under '{}'",
+        req.dir,
+        req.prefix
+    );
+
+    let check = mutant.verify_tree(Path::new(&req.dir), &req.prefix).await?;
+
+    update_tx
+        .send(Response::MirrorCheck(check))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
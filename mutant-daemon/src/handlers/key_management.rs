@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use mutant_protocol::{ErrorResponse, Response, TaskType};
+use mutant_protocol::{ErrorResponse, Response, TaskResult, TaskStatus, TaskType};
 use crate::error::Error as DaemonError;
 use super::common::UpdateSender;
+use super::TaskMap;
 
 /// Tracks which keys are currently being operated on and by which task
 #[derive(Debug, Clone)]
@@ -42,6 +43,7 @@ pub async fn try_register_key(
             .send(Response::Error(ErrorResponse {
                 error: error_msg,
                 original_request: Some(original_request_str.to_string()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
 
@@ -74,3 +76,71 @@ pub async fn release_key(active_keys: &ActiveKeysMap, key: &str) {
         log::warn!("Attempted to release key '{}' that wasn't registered", key);
     }
 }
+
+/// Stops the store still running on a key about to be removed, so that it does not
+/// keep writing to the pads the removal releases. The key is released for the removal.
+/// Other operations are left running, the removal then fails as the key is in use.
+pub async fn cancel_store(active_keys: &ActiveKeysMap, tasks: &TaskMap, key: &str) {
+    let mut keys_guard = active_keys.write().await;
+
+    let Some(entry) = keys_guard.get(key) else {
+        return;
+    };
+    if entry.task_type != TaskType::Put {
+        return;
+    }
+    let task_id = entry.task_id;
+
+    let mut tasks_guard = tasks.write().await;
+    // The task is only known once its store started, leave it to its key until then.
+    let Some(task_entry) = tasks_guard.get_mut(&task_id) else {
+        return;
+    };
+    let Some(handle) = task_entry.abort_handle.take() else {
+        return;
+    };
+
+    handle.abort();
+    task_entry.task.status = TaskStatus::Stopped;
+    task_entry.task.result = TaskResult::Error(format!("Task stopped, key '{}' was removed", key));
+    task_entry.finish();
+    keys_guard.remove(key);
+
+    log::info!("Stopped store task {} to remove key '{}'", task_id, key);
+}
+
+/// Registers the free keys of a batch for a specific task
+/// Returns the keys that were registered, the ones already in use are left to their operation
+pub async fn register_free_keys(
+    active_keys: &ActiveKeysMap,
+    keys: &[String],
+    task_id: uuid::Uuid,
+    task_type: TaskType,
+) -> Vec<String> {
+    let mut keys_guard = active_keys.write().await;
+    let mut registered = Vec::new();
+
+    for key in keys {
+        if keys_guard.contains_key(key) {
+            continue;
+        }
+        keys_guard.insert(
+            key.clone(),
+            ActiveKeyEntry {
+                task_id,
+                task_type: task_type.clone(),
+            },
+        );
+        registered.push(key.clone());
+    }
+
+    log::debug!(
+        "Registered {} of {} keys for task ID: {}, type: {:?}",
+        registered.len(),
+        keys.len(),
+        task_id,
+        task_type
+    );
+
+    registered
+}
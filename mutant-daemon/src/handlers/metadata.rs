@@ -1,80 +1,97 @@
 use std::sync::Arc;
 
 use crate::error::Error as DaemonError;
-use mutant_lib::storage::{IndexEntry, PadStatus};
+use mutant_lib::storage::{key_details, ScratchpadAddress};
 use mutant_lib::MutAnt;
 use mutant_protocol::{
-    ErrorResponse, KeyDetails, ListKeysRequest, ListKeysResponse, Response, StatsRequest,
-    StatsResponse,
+    AliasRequest, AliasResponse, CollectionAction, CollectionRequest, CollectionResponse,
+    ErrorCode, ErrorResponse, EstimateStoreRequest, EstimateStoreResponse, InitReportRequest,
+    KeyDetails, ListKeysRequest, ListKeysResponse, PadExistsRequest, PadExistsResponse, QuotaUsage,
+    RecoverRequest, RecoverResponse, RenameRequest, RenameResponse, Response, RestoreRequest,
+    RestoreResponse, StatKeyRequest, StatsRequest, StatsResponse, VerifyRequest, VerifyResponse,
 };
 
 use super::common::UpdateSender;
+use super::notifications::notify_keys_changed;
+use super::{is_public_only_mode, ActiveKeysMap, PUBLIC_ONLY_ERROR_MSG};
+
+/// Most keys sent in one `ListKeys` response, keeps the frames small on large indexes.
+const LIST_KEYS_PAGE_SIZE: usize = 1000;
 
 pub(crate) async fn handle_list_keys(
-    _req: ListKeysRequest,
+    req: ListKeysRequest,
     update_tx: UpdateSender,
     mutant: Arc<MutAnt>,
 ) -> Result<(), DaemonError> {
     log::debug!("Handling ListKeys request");
 
-    // Use mutant.list() to get detailed IndexEntry data
-    let index_result = mutant.list().await;
+    // A catalogue is listed through a read-only instance loaded for this request only.
+    let mutant = match &req.index_file {
+        Some(index_file) => Arc::new(
+            MutAnt::init_from_index_file_on(index_file, mutant.network_choice()).await?,
+        ),
+        None => mutant,
+    };
+
+    if req.remote {
+        let details = with_prefix(mutant.fetch_remote_key_details().await?, &req);
+        log::info!("Found {} keys in the remote index", details.len());
+
+        update_tx
+            .send(Response::ListKeys(list_keys_page(details, &req)))
+            .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+        return Ok(());
+    }
+
+    if req.trash {
+        let details = mutant
+            .list_trash()
+            .await?
+            .into_iter()
+            .map(|(key, trashed)| KeyDetails {
+                trashed_at_ms: Some(trashed.deleted_at.timestamp_millis() as u64),
+                ..key_details(
+                    key,
+                    trashed.entry,
+                    Vec::new(),
+                    trashed.integrity_proof,
+                    None,
+                )
+            })
+            .collect();
+        let details = with_prefix(details, &req);
+
+        update_tx
+            .send(Response::ListKeys(list_keys_page(details, &req)))
+            .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+        return Ok(());
+    }
 
-    let response = match index_result {
-        Ok(index_map) => {
-            log::info!("Found {} keys", index_map.len());
-            let details: Vec<KeyDetails> = index_map
+    // Read from the summary of the index, listing does not load its pad lists.
+    let details = mutant
+        .list_key_details_with_prefix(req.prefix.as_deref().unwrap_or_default())
+        .await;
+    let details = match &req.collection {
+        Some(name) => mutant.list_collection(name).await.map(|members| {
+            details
                 .into_iter()
-                .map(|(key, entry)| match entry {
-                    IndexEntry::PrivateKey(pads) => {
-                        let total_size = pads.iter().map(|p| p.size).sum::<usize>();
-                        let pad_count = pads.len();
-                        let confirmed_pads = pads
-                            .iter()
-                            .filter(|p| p.status == PadStatus::Confirmed)
-                            .count();
-                        KeyDetails {
-                            key,
-                            total_size,
-                            pad_count,
-                            confirmed_pads,
-                            is_public: false,
-                            public_address: None,
-                        }
-                    }
-                    IndexEntry::PublicUpload(index_pad, pads) => {
-                        let data_size = pads.iter().map(|p| p.size).sum::<usize>();
-                        let total_size = data_size + index_pad.size;
-                        let pad_count = pads.len() + 1; // +1 for index pad
-                        let confirmed_data_pads = pads
-                            .iter()
-                            .filter(|p| p.status == PadStatus::Confirmed)
-                            .count();
-                        let index_pad_confirmed = if index_pad.status == PadStatus::Confirmed {
-                            1
-                        } else {
-                            0
-                        };
-                        let confirmed_pads = confirmed_data_pads + index_pad_confirmed;
-                        KeyDetails {
-                            key,
-                            total_size,
-                            pad_count,
-                            confirmed_pads,
-                            is_public: true,
-                            public_address: Some(index_pad.address.to_hex()),
-                        }
-                    }
-                })
-                .collect();
-
-            Response::ListKeys(ListKeysResponse { keys: details })
+                .filter(|details| members.contains(&details.key))
+                .collect()
+        }),
+        None => Ok(details),
+    };
+
+    let response = match details {
+        Ok(details) => {
+            log::info!("Found {} keys", details.len());
+            Response::ListKeys(list_keys_page(details, &req))
         }
         Err(e) => {
             log::error!("Failed to list keys from mutant-lib: {}", e);
             Response::Error(ErrorResponse {
                 error: format!("Failed to retrieve key list: {}", e),
                 original_request: None, // Cannot easily get original string here yet
+                code: None,
             })
         }
     };
@@ -86,6 +103,76 @@ pub(crate) async fn handle_list_keys(
     Ok(())
 }
 
+/// The keys of `details` starting with the prefix of `req`, for the listings that are
+/// not read from the index.
+fn with_prefix(mut details: Vec<KeyDetails>, req: &ListKeysRequest) -> Vec<KeyDetails> {
+    if let Some(prefix) = &req.prefix {
+        details.retain(|details| details.key.starts_with(prefix.as_str()));
+    }
+    details
+}
+
+/// Cuts the page asked by `req` out of `details`, with the requested fields only.
+fn list_keys_page(details: Vec<KeyDetails>, req: &ListKeysRequest) -> ListKeysResponse {
+    let limit = req
+        .limit
+        .unwrap_or(LIST_KEYS_PAGE_SIZE)
+        .clamp(1, LIST_KEYS_PAGE_SIZE);
+    let end = req.offset.saturating_add(limit);
+    let next_offset = (end < details.len()).then_some(end);
+
+    let keys = details
+        .into_iter()
+        .skip(req.offset)
+        .take(limit)
+        .map(|mut key_details| {
+            if let Some(fields) = &req.fields {
+                key_details.retain_fields(fields);
+            }
+            key_details
+        })
+        .collect();
+
+    ListKeysResponse { keys, next_offset }
+}
+
+pub(crate) async fn handle_init_report(
+    _req: InitReportRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    let outcome = mutant.init_report().await?;
+
+    update_tx
+        .send(Response::InitReport(outcome))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_stat_key(
+    req: StatKeyRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling StatKey request for key '{}'", req.key);
+
+    let response = match mutant.key_details(&req.key).await? {
+        Some(details) => Response::StatKey(details),
+        None => Response::Error(ErrorResponse {
+            error: format!("Key '{}' not found", req.key),
+            original_request: None,
+            code: Some(ErrorCode::KeyNotFound),
+        }),
+    };
+
+    update_tx
+        .send(response)
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
 pub(crate) async fn handle_stats(
     _req: StatsRequest,
     update_tx: UpdateSender,
@@ -95,7 +182,9 @@ pub(crate) async fn handle_stats(
 
     // Call the method which returns StorageStats directly
     let stats = mutant.get_storage_stats().await;
+    let quota = &mutant.config().quota;
     log::info!("Retrieved storage stats successfully: {:?}", stats);
+    let footprint = mutant.index_memory_estimate().await?;
 
     // Adapt the fields from mutant_lib::StorageStats to mutant_protocol::StatsResponse
     let response = Response::Stats(StatsResponse {
@@ -105,6 +194,24 @@ pub(crate) async fn handle_stats(
         occupied_pads: stats.occupied_pads,
         free_pads: stats.free_pads,
         pending_verify_pads: stats.pending_verification_pads,
+        bundle_pads: stats.bundle_pads,
+        bundle_live_bytes: stats.bundle_live_bytes,
+        bundle_capacity_bytes: stats.bundle_capacity_bytes,
+        index_memory_bytes: footprint.bytes_estimate as u64,
+        index_serialized_bytes: footprint.serialized_bytes as u64,
+        trash_pads: stats.trash_pads,
+        trashed_keys: stats.trashed_keys,
+        trash_bytes: stats.trash_bytes,
+        confirmed_bytes: stats.confirmed_bytes,
+        wasted_bytes: stats.wasted_bytes,
+        remote_operations_behind: mutant.remote_operations_behind(),
+        quota: quota.is_set().then(|| QuotaUsage {
+            used_pads: stats.used_pads(),
+            max_pads: quota.max_total_pads.map(|max| max as u64),
+            used_bytes: stats.stored_bytes,
+            max_bytes: quota.max_total_bytes,
+        }),
+        io: mutant.io_stats(),
     });
 
     update_tx
@@ -113,3 +220,220 @@ pub(crate) async fn handle_stats(
 
     Ok(())
 }
+
+pub(crate) async fn handle_pad_exists(
+    req: PadExistsRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling PadExists request for {}", req.address);
+
+    let address = ScratchpadAddress::from_hex(&req.address)
+        .map_err(|e| DaemonError::Internal(format!("Invalid scratchpad address: {}", e)))?;
+
+    let exists = mutant.pad_exists(&address).await?;
+    let counter = if exists {
+        Some(mutant.pad_counter(&address).await?)
+    } else {
+        None
+    };
+
+    let response = Response::PadExists(PadExistsResponse {
+        address: req.address,
+        exists,
+        counter,
+    });
+
+    update_tx
+        .send(response)
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_verify(
+    req: VerifyRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling Verify request for {}", req.user_key);
+
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    let result = mutant.verify_chunks(&req.user_key).await?;
+
+    update_tx
+        .send(Response::Verify(VerifyResponse { result }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_estimate_store(
+    req: EstimateStoreRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling EstimateStore request for {}", req.user_key);
+
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    let estimate = mutant
+        .estimate_store(&req.user_key, req.data_len, req.mode, req.public)
+        .await?;
+
+    update_tx
+        .send(Response::EstimateStore(EstimateStoreResponse { estimate }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_collection(
+    req: CollectionRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+    active_keys: ActiveKeysMap,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling Collection request for {}", req.name);
+
+    if let CollectionAction::Remove { delete_keys: true } = req.action {
+        if is_public_only_mode() {
+            return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+        }
+
+        let members = mutant.list_collection(&req.name).await?;
+        let active = active_keys.read().await;
+        if let Some(key) = members.iter().find(|key| active.contains_key(*key)) {
+            return Err(DaemonError::Internal(format!(
+                "Key '{}' is being used by another operation",
+                key
+            )));
+        }
+    }
+
+    let keys = match req.action {
+        CollectionAction::Add { keys } => {
+            mutant.add_to_collection(&req.name, &keys).await?;
+            mutant.list_collection(&req.name).await?
+        }
+        CollectionAction::Remove { delete_keys } => {
+            mutant.remove_collection(&req.name, delete_keys).await?
+        }
+    };
+
+    let response = Response::Collection(CollectionResponse {
+        name: req.name,
+        keys,
+    });
+
+    update_tx
+        .send(response)
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_alias(
+    req: AliasRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling Alias request {} -> {}", req.alias, req.key);
+
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    mutant.alias(&req.key, &req.alias).await?;
+
+    update_tx
+        .send(Response::Alias(AliasResponse {
+            key: req.key,
+            alias: req.alias,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_rename(
+    req: RenameRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling Rename request {} -> {}", req.old_key, req.new_key);
+
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    // Renaming an alias leaves the listed keys as they are.
+    let is_key = mutant
+        .stat(&req.old_key)
+        .await?
+        .is_some_and(|stat| stat.key == req.old_key);
+    mutant.rename(&req.old_key, &req.new_key).await?;
+    if is_key {
+        notify_keys_changed([req.new_key.clone()], [req.old_key.clone()], []);
+    }
+
+    update_tx
+        .send(Response::Rename(RenameResponse {
+            old_key: req.old_key,
+            new_key: req.new_key,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_restore(
+    req: RestoreRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling Restore request for key: {}", req.user_key);
+
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    mutant.restore(&req.user_key).await?;
+
+    update_tx
+        .send(Response::Restore(RestoreResponse {
+            user_key: req.user_key,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_recover(
+    req: RecoverRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    log::debug!("Handling Recover request for {} keys", req.key_hints.len());
+
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    let recovery = mutant.rebuild_index_by_scan(req.key_hints).await?;
+
+    update_tx
+        .send(Response::Recover(RecoverResponse {
+            recovered: recovery.recovered,
+            missing: recovery.missing,
+            skipped: recovery.skipped,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
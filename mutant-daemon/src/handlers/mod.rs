@@ -4,15 +4,18 @@ mod websocket;
 mod dispatcher;
 mod data_operations;
 mod task_management;
+mod update_channel;
 mod system_operations;
 mod metadata;
 mod import_export;
 mod key_management;
+mod reset;
+mod notifications;
 
 // Public exports
 pub use websocket::handle_ws;
-pub use task_management::{TaskEntry, TaskMap};
-pub use key_management::{ActiveKeysMap, try_register_key, release_key};
+pub use task_management::{TaskEntry, TaskMap, insert_task};
+pub use key_management::{ActiveKeysMap, try_register_key, release_key, register_free_keys, cancel_store};
 
 /// Check if the daemon is running in public-only mode
 pub fn is_public_only_mode() -> bool {
@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+use tokio::sync::broadcast;
+
+use crate::error::Error as DaemonError;
+use mutant_protocol::{
+    Notification, Response, SubscribeNotificationsRequest, SubscribeNotificationsResponse,
+};
+
+use super::common::UpdateSender;
+
+/// Notifications a subscriber can lag behind before the oldest ones are dropped for it.
+const NOTIFICATION_BACKLOG: usize = 256;
+
+/// Notifications of every connection of the daemon.
+static NOTIFICATIONS: OnceLock<broadcast::Sender<Notification>> = OnceLock::new();
+
+fn notifications() -> &'static broadcast::Sender<Notification> {
+    NOTIFICATIONS.get_or_init(|| broadcast::channel(NOTIFICATION_BACKLOG).0)
+}
+
+/// Tells the subscribed clients which keys a completed operation changed. Nothing is
+/// sent when it changed none.
+pub(crate) fn notify_keys_changed(
+    added: impl IntoIterator<Item = String>,
+    removed: impl IntoIterator<Item = String>,
+    updated: impl IntoIterator<Item = String>,
+) {
+    let sorted = |keys: BTreeSet<String>| keys.into_iter().collect::<Vec<_>>();
+    let added: BTreeSet<String> = added.into_iter().collect();
+    let removed: BTreeSet<String> = removed.into_iter().collect();
+    let updated: BTreeSet<String> = updated
+        .into_iter()
+        .filter(|key| !added.contains(key) && !removed.contains(key))
+        .collect();
+    if added.is_empty() && removed.is_empty() && updated.is_empty() {
+        return;
+    }
+
+    // Fails only when no client is subscribed.
+    let _ = notifications().send(Notification::KeysChanged {
+        added: sorted(added),
+        removed: sorted(removed),
+        updated: sorted(updated),
+    });
+}
+
+/// Notifies the changes between two listings of the keys, taken before and after an
+/// operation that may change any of them.
+pub(crate) fn notify_listing_changed<V: PartialEq>(
+    before: &BTreeMap<String, V>,
+    after: &BTreeMap<String, V>,
+) {
+    notify_keys_changed(
+        after
+            .keys()
+            .filter(|key| !before.contains_key(*key))
+            .cloned(),
+        before
+            .keys()
+            .filter(|key| !after.contains_key(*key))
+            .cloned(),
+        after
+            .iter()
+            .filter(|(key, value)| before.get(*key).is_some_and(|old| old != *value))
+            .map(|(key, _)| key.clone()),
+    );
+}
+
+/// Forwards the notifications to the client until it disconnects. They go through the
+/// same channel as the responses, so they interleave with the requests of the client.
+pub(crate) async fn handle_subscribe_notifications(
+    _req: SubscribeNotificationsRequest,
+    update_tx: UpdateSender,
+) -> Result<(), DaemonError> {
+    let receiver = notifications().subscribe();
+
+    update_tx
+        .send(Response::SubscribeNotifications(
+            SubscribeNotificationsResponse {},
+        ))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    tokio::spawn(forward_notifications(receiver, update_tx));
+    Ok(())
+}
+
+async fn forward_notifications(
+    mut receiver: broadcast::Receiver<Notification>,
+    update_tx: UpdateSender,
+) {
+    loop {
+        match receiver.recv().await {
+            Ok(notification) => {
+                if update_tx
+                    .send(Response::Notification(notification))
+                    .is_err()
+                {
+                    log::debug!("Notification subscriber disconnected");
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!("Notification subscriber missed {} notifications", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::update_channel::update_channel;
+
+    async fn next_notification(
+        update_rx: &mut crate::handlers::update_channel::UpdateReceiver,
+    ) -> Notification {
+        match update_rx.recv().await {
+            Some(Response::Notification(notification)) => notification,
+            other => panic!("expected a notification, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_subscriber_is_notified() {
+        let (first_tx, mut first_rx) = update_channel();
+        let (second_tx, mut second_rx) = update_channel();
+        for (update_tx, update_rx) in [(first_tx, &mut first_rx), (second_tx, &mut second_rx)] {
+            handle_subscribe_notifications(SubscribeNotificationsRequest {}, update_tx)
+                .await
+                .unwrap();
+            assert!(matches!(
+                update_rx.recv().await,
+                Some(Response::SubscribeNotifications(_))
+            ));
+        }
+
+        // A client storing a key notifies the other one as well as itself.
+        let key = format!("notified-{}", uuid::Uuid::new_v4());
+        notify_keys_changed([key.clone()], [], [key.clone()]);
+        let expected = Notification::KeysChanged {
+            added: vec![key.clone()],
+            removed: Vec::new(),
+            updated: Vec::new(),
+        };
+        // Other tests may notify in between, the notification is looked for by key.
+        for update_rx in [&mut first_rx, &mut second_rx] {
+            loop {
+                if next_notification(update_rx).await == expected {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_listing_changes() {
+        let before = BTreeMap::from([
+            ("kept".to_string(), 1),
+            ("changed".to_string(), 1),
+            ("dropped".to_string(), 1),
+        ]);
+        let after = BTreeMap::from([
+            ("kept".to_string(), 1),
+            ("changed".to_string(), 2),
+            ("new".to_string(), 1),
+        ]);
+
+        let mut receiver = notifications().subscribe();
+        notify_listing_changed(&before, &after);
+        notify_listing_changed(&after, &after);
+
+        let mut received = Vec::new();
+        while let Ok(notification) = receiver.try_recv() {
+            received.push(notification);
+        }
+        assert!(received.contains(&Notification::KeysChanged {
+            added: vec!["new".to_string()],
+            removed: vec!["dropped".to_string()],
+            updated: vec!["changed".to_string()],
+        }));
+    }
+}
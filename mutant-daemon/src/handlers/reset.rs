@@ -0,0 +1,154 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{is_public_only_mode, ActiveKeysMap, PUBLIC_ONLY_ERROR_MSG};
+use crate::error::Error as DaemonError;
+use mutant_lib::MutAnt;
+use mutant_protocol::{
+    ResetCommitRequest, ResetPrepareRequest, ResetPrepareResponse, ResetResponse, Response,
+};
+
+use super::common::UpdateSender;
+
+/// How long the token of a prepared reset can commit it.
+const RESET_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// Token of the latest prepared reset, shared by every connection of the daemon.
+static PENDING_RESET: ResetToken = ResetToken::new();
+
+/// The one-time token a reset has to be committed with. Preparing a reset again
+/// replaces the token, and any commit attempt uses it up, matching or not.
+struct ResetToken(Mutex<Option<(String, Instant)>>);
+
+impl ResetToken {
+    const fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    fn issue(&self, now: Instant) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        *self.0.lock().unwrap() = Some((token.clone(), now));
+        token
+    }
+
+    fn redeem(&self, token: &str, now: Instant) -> Result<(), DaemonError> {
+        match self.0.lock().unwrap().take() {
+            Some((issued, issued_at)) if issued == token => {
+                if now.duration_since(issued_at) > RESET_TOKEN_TTL {
+                    return Err(DaemonError::Internal(
+                        "Reset token expired, prepare the reset again".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(DaemonError::Internal(
+                "Invalid reset token, prepare the reset again".to_string(),
+            )),
+        }
+    }
+}
+
+pub(crate) async fn handle_reset_prepare(
+    _req: ResetPrepareRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    let summary = mutant.reset_summary().await?;
+    let token = PENDING_RESET.issue(Instant::now());
+    log::warn!(
+        "Reset prepared, committing it would drop {} keys and {} pads",
+        summary.key_count,
+        summary.pad_count
+    );
+
+    update_tx
+        .send(Response::ResetPrepare(ResetPrepareResponse {
+            token,
+            summary,
+            expires_in_secs: RESET_TOKEN_TTL.as_secs(),
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+pub(crate) async fn handle_reset_commit(
+    req: ResetCommitRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+    active_keys: ActiveKeysMap,
+) -> Result<(), DaemonError> {
+    if is_public_only_mode() {
+        return Err(DaemonError::Internal(PUBLIC_ONLY_ERROR_MSG.to_string()));
+    }
+
+    if let Err(e) = PENDING_RESET.redeem(&req.token, Instant::now()) {
+        log::warn!("Rejected reset ({:?}): {}", req.mode, e);
+        return Err(e);
+    }
+
+    let running = active_keys.read().await.len();
+    if running > 0 {
+        return Err(DaemonError::Internal(format!(
+            "Cannot reset while {} keys are being operated on, prepare the reset again once they are done",
+            running
+        )));
+    }
+
+    // The library records the reset in the audit log, when one is configured.
+    let summary = mutant.reset(req.mode).await?;
+    log::warn!(
+        "Reset the index ({:?}): {} keys and {} pads dropped",
+        req.mode,
+        summary.key_count,
+        summary.pad_count
+    );
+
+    update_tx
+        .send(Response::Reset(ResetResponse {
+            mode: req.mode,
+            summary,
+        }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_token_is_good_for_one_attempt() {
+        let pending = ResetToken::new();
+        let now = Instant::now();
+
+        let token = pending.issue(now);
+        assert!(pending.redeem(&token, now).is_ok());
+        assert!(pending.redeem(&token, now).is_err());
+
+        // A wrong token uses up the right one too.
+        let token = pending.issue(now);
+        assert!(pending.redeem("not-the-token", now).is_err());
+        assert!(pending.redeem(&token, now).is_err());
+
+        // Preparing again replaces the token.
+        let stale = pending.issue(now);
+        let token = pending.issue(now);
+        assert_ne!(stale, token);
+        assert!(pending.redeem(&stale, now).is_err());
+    }
+
+    #[test]
+    fn test_reset_token_expires() {
+        let pending = ResetToken::new();
+        let issued_at = Instant::now();
+
+        let token = pending.issue(issued_at);
+        assert!(pending.redeem(&token, issued_at + RESET_TOKEN_TTL).is_ok());
+
+        let token = pending.issue(issued_at);
+        let late = issued_at + RESET_TOKEN_TTL + Duration::from_secs(1);
+        assert!(pending.redeem(&token, late).is_err());
+    }
+}
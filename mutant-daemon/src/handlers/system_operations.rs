@@ -2,16 +2,17 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::error::Error as DaemonError;
-use super::{TaskMap, TaskEntry, is_public_only_mode, PUBLIC_ONLY_ERROR_MSG};
+use super::{TaskMap, TaskEntry, insert_task, is_public_only_mode, PUBLIC_ONLY_ERROR_MSG};
 use mutant_lib::MutAnt;
 use mutant_protocol::{
-    ErrorResponse, HealthCheckCallback, HealthCheckEvent, HealthCheckRequest, PurgeCallback, PurgeEvent,
-    PurgeRequest, Response, SyncCallback, SyncEvent, SyncRequest, Task, TaskCreatedResponse,
-    TaskProgress, TaskResult, TaskResultResponse, TaskResultType, TaskStatus, TaskType,
-    TaskUpdateResponse,
+    CleanCacheRequest, ErrorResponse, HealthCheckCallback, HealthCheckEvent, HealthCheckRequest,
+    PurgeCallback, PurgeEvent, PurgeRequest, Response, SyncCallback, SyncEvent, SyncRequest, Task,
+    TaskCreatedResponse, TaskProgress, TaskResult, TaskResultResponse, TaskResultType, TaskStatus,
+    TaskType, TaskUpdateResponse,
 };
 
 use super::common::UpdateSender;
+use super::notifications::notify_listing_changed;
 
 pub(crate) async fn handle_sync(
     req: SyncRequest,
@@ -24,7 +25,8 @@ pub(crate) async fn handle_sync(
         return update_tx
             .send(Response::Error(ErrorResponse {
                 error: PUBLIC_ONLY_ERROR_MSG.to_string(),
-                original_request: Some(serde_json::to_string(&SyncRequest { push_force: req.push_force }).unwrap_or_default()),
+                original_request: Some(serde_json::to_string(&req).unwrap_or_default()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
@@ -38,6 +40,7 @@ pub(crate) async fn handle_sync(
         progress: None,
         result: TaskResult::Pending,
         key: None, // Sync doesn't operate on a specific key
+        snapshot: None,
     };
 
     update_tx
@@ -86,6 +89,7 @@ pub(crate) async fn handle_sync(
                             task_id,
                             status: TaskStatus::InProgress,
                             progress: Some(progress),
+                            repeat: 1,
                         }));
                     } else {
                         log::warn!("Received SYNC progress update for task not InProgress (status: {:?}). Ignoring. task_id={}", entry.task.status, task_id);
@@ -97,8 +101,21 @@ pub(crate) async fn handle_sync(
             })
         });
 
-        // Call sync with the callback
-        let sync_result = mutant.sync(req.push_force, Some(callback)).await; // Pass callback
+        let keys_before = mutant.list().await.unwrap_or_default();
+
+        // Call sync with the callback, restricted to the selected keys if any
+        let sync_result = if req.only.is_empty() {
+            mutant.sync(req.push_force, Some(callback)).await
+        } else {
+            mutant
+                .sync_keys(&req.only, req.direction, Some(callback))
+                .await
+        };
+        if sync_result.is_ok() {
+            if let Ok(keys_after) = mutant.list().await {
+                notify_listing_changed(&keys_before, &keys_after);
+            }
+        }
 
         let final_response = {
             let mut tasks_guard = tasks.write().await;
@@ -110,7 +127,7 @@ pub(crate) async fn handle_sync(
                             entry.task.status = TaskStatus::Completed;
                             entry.task.result =
                                 TaskResult::Result(TaskResultType::Sync(sync_result_data));
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::info!("SYNC task completed successfully: task_id={}", task_id);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -122,7 +139,7 @@ pub(crate) async fn handle_sync(
                             let error_msg = e.to_string();
                             entry.task.status = TaskStatus::Failed;
                             entry.task.result = TaskResult::Error(error_msg.clone());
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::error!("SYNC task failed: task_id={}, error={}", task_id, error_msg);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -133,7 +150,7 @@ pub(crate) async fn handle_sync(
                     }
                 } else {
                     log::info!("SYNC task was stopped before completion: task_id={}", task_id);
-                    entry.abort_handle = None; // Ensure handle is cleared if stopped
+                    entry.finish();
                     None // No final result to send if stopped
                 }
             } else {
@@ -150,14 +167,11 @@ pub(crate) async fn handle_sync(
 
     // Get the abort handle and create the TaskEntry
     let abort_handle = task_handle.abort_handle();
-    let task_entry = TaskEntry {
-        task, // The task struct created earlier
-        abort_handle: Some(abort_handle),
-    };
+    let task_entry = TaskEntry::new(task, abort_handle);
 
     // Insert the TaskEntry into the map *after* spawning
     {
-        tasks.write().await.insert(task_id, task_entry);
+        insert_task(&tasks, task_id, task_entry).await;
     }
 
     Ok(())
@@ -175,6 +189,7 @@ pub(crate) async fn handle_purge(
             .send(Response::Error(ErrorResponse {
                 error: PUBLIC_ONLY_ERROR_MSG.to_string(),
                 original_request: Some(serde_json::to_string(&PurgeRequest { aggressive: req.aggressive }).unwrap_or_default()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
@@ -189,6 +204,7 @@ pub(crate) async fn handle_purge(
         progress: None,
         result: TaskResult::Pending,
         key: None, // Purge doesn't operate on a specific key
+        snapshot: None,
     };
 
     update_tx
@@ -237,6 +253,7 @@ pub(crate) async fn handle_purge(
                             task_id,
                             status: TaskStatus::InProgress,
                             progress: Some(progress),
+                            repeat: 1,
                         }));
                     } else {
                         log::warn!("Received PURGE progress update for task not InProgress (status: {:?}). Ignoring. task_id={}", entry.task.status, task_id);
@@ -261,7 +278,7 @@ pub(crate) async fn handle_purge(
                             entry.task.status = TaskStatus::Completed;
                             entry.task.result =
                                 TaskResult::Result(TaskResultType::Purge(purge_result_data));
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::info!("PURGE task completed successfully: task_id={}", task_id);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -273,7 +290,7 @@ pub(crate) async fn handle_purge(
                             let error_msg = e.to_string();
                             entry.task.status = TaskStatus::Failed;
                             entry.task.result = TaskResult::Error(error_msg.clone());
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::error!("PURGE task failed: task_id={}, error={}", task_id, error_msg);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -284,7 +301,7 @@ pub(crate) async fn handle_purge(
                     }
                 } else {
                     log::info!("PURGE task was stopped before completion: task_id={}", task_id);
-                    entry.abort_handle = None; // Ensure handle is cleared if stopped
+                    entry.finish();
                     None // No final result to send if stopped
                 }
             } else {
@@ -301,14 +318,11 @@ pub(crate) async fn handle_purge(
 
     // Get the abort handle and create the TaskEntry
     let abort_handle = task_handle.abort_handle();
-    let task_entry = TaskEntry {
-        task, // The task struct created earlier
-        abort_handle: Some(abort_handle),
-    };
+    let task_entry = TaskEntry::new(task, abort_handle);
 
     // Insert the TaskEntry into the map *after* spawning
     {
-        tasks.write().await.insert(task_id, task_entry);
+        insert_task(&tasks, task_id, task_entry).await;
     }
 
     Ok(())
@@ -329,6 +343,7 @@ pub(crate) async fn handle_health_check(
                     key_name: req.key_name.clone(),
                     recycle: req.recycle
                 }).unwrap_or_default()),
+                code: None,
             }))
             .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)));
     }
@@ -342,6 +357,7 @@ pub(crate) async fn handle_health_check(
         progress: None,
         result: TaskResult::Pending,
         key: Some(req.key_name.clone()), // Health check operates on a specific key
+        snapshot: None,
     };
 
     update_tx
@@ -390,6 +406,7 @@ pub(crate) async fn handle_health_check(
                             task_id,
                             status: TaskStatus::InProgress,
                             progress: Some(progress),
+                            repeat: 1,
                         }));
                     } else {
                         log::warn!("Received HEALTH_CHECK progress update for task not InProgress (status: {:?}). Ignoring. task_id={}", entry.task.status, task_id);
@@ -402,16 +419,18 @@ pub(crate) async fn handle_health_check(
         });
 
         // Check if the key exists first for better error messages
-        let health_check_result = if !mutant.contains_key(&req.key_name).await {
+        let health_check_result = match mutant.contains_key(&req.key_name).await {
             // If the key doesn't exist, return an error
-            Err(mutant_lib::error::Error::Internal(format!(
+            Ok(false) => Err(mutant_lib::error::Error::Internal(format!(
                 "Key '{}' not found",
                 req.key_name
-            )))
-        } else {
-            mutant
-                .health_check(&req.key_name, req.recycle, Some(callback))
-                .await // Pass callback
+            ))),
+            Ok(true) => {
+                mutant
+                    .health_check(&req.key_name, req.recycle, Some(callback))
+                    .await // Pass callback
+            }
+            Err(e) => Err(e),
         };
 
         let final_response = {
@@ -425,7 +444,7 @@ pub(crate) async fn handle_health_check(
                             entry.task.result = TaskResult::Result(TaskResultType::HealthCheck(
                                 health_check_result_data,
                             ));
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::info!("HEALTH CHECK task completed successfully: task_id={}", task_id);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -437,7 +456,7 @@ pub(crate) async fn handle_health_check(
                             let error_msg = e.to_string();
                             entry.task.status = TaskStatus::Failed;
                             entry.task.result = TaskResult::Error(error_msg.clone());
-                            entry.abort_handle = None; // Task finished, remove handle
+                            entry.finish();
                             log::error!("HEALTH CHECK task failed: task_id={}, error={}", task_id, error_msg);
                             Some(Response::TaskResult(TaskResultResponse {
                                 task_id,
@@ -448,7 +467,7 @@ pub(crate) async fn handle_health_check(
                     }
                 } else {
                     log::info!("HEALTH CHECK task was stopped before completion: task_id={}", task_id);
-                    entry.abort_handle = None; // Ensure handle is cleared if stopped
+                    entry.finish();
                     None // No final result to send if stopped
                 }
             } else {
@@ -465,15 +484,26 @@ pub(crate) async fn handle_health_check(
 
     // Get the abort handle and create the TaskEntry
     let abort_handle = task_handle.abort_handle();
-    let task_entry = TaskEntry {
-        task, // The task struct created earlier
-        abort_handle: Some(abort_handle),
-    };
+    let task_entry = TaskEntry::new(task, abort_handle);
 
     // Insert the TaskEntry into the map *after* spawning
     {
-        tasks.write().await.insert(task_id, task_entry);
+        insert_task(&tasks, task_id, task_entry).await;
     }
 
     Ok(())
 }
+
+pub(crate) async fn handle_clean_cache(
+    req: CleanCacheRequest,
+    update_tx: UpdateSender,
+    mutant: Arc<MutAnt>,
+) -> Result<(), DaemonError> {
+    let report = mutant.clean_cache(&mutant.config().cache_policy, req.dry_run)?;
+
+    update_tx
+        .send(Response::CacheClean(report))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    Ok(())
+}
@@ -1,23 +1,93 @@
 use crate::error::Error as DaemonError;
 use mutant_protocol::{
-    ErrorResponse, ListTasksRequest, QueryTaskRequest, Response, StopTaskRequest, Task, TaskListEntry, TaskListResponse, TaskResultResponse, TaskStatus, TaskStoppedResponse, TaskUpdateResponse
+    ErrorResponse, ListTasksRequest, QueryTaskRequest, Response, StopTaskRequest, Task,
+    TaskHistoryRequest, TaskHistoryResponse, TaskListEntry, TaskListResponse, TaskRecord,
+    TaskResult, TaskResultResponse, TaskStatus, TaskStoppedResponse, TaskUpdateResponse,
 };
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tokio::task::AbortHandle;
 use mutant_protocol::TaskId;
 
 use super::common::UpdateSender;
 
+/// Finished tasks kept for `TaskHistory`, the oldest ones are dropped past it.
+const TASK_HISTORY_SIZE: usize = 100;
+
 #[derive(Debug)]
 pub struct TaskEntry {
     pub task: Task,
     pub abort_handle: Option<AbortHandle>,
+    /// In milliseconds since the epoch.
+    pub started_at_ms: u64,
+    /// In milliseconds since the epoch, `None` while the task runs.
+    pub finished_at_ms: Option<u64>,
+}
+
+impl TaskEntry {
+    pub fn new(task: Task, abort_handle: AbortHandle) -> Self {
+        Self {
+            task,
+            abort_handle: Some(abort_handle),
+            started_at_ms: now_ms(),
+            finished_at_ms: None,
+        }
+    }
+
+    /// Clears the abort handle and records when the task ended, the first time only.
+    pub fn finish(&mut self) {
+        self.abort_handle = None;
+        self.finished_at_ms.get_or_insert_with(now_ms);
+    }
+
+    fn record(&self) -> Option<TaskRecord> {
+        let finished_at_ms = self.finished_at_ms?;
+        let error = match &self.task.result {
+            TaskResult::Error(e) => Some(e.clone()),
+            _ => None,
+        };
+
+        Some(TaskRecord {
+            task_id: self.task.id,
+            task_type: self.task.task_type.clone(),
+            key: self.task.key.clone(),
+            status: self.task.status.clone(),
+            started_at_ms: self.started_at_ms,
+            finished_at_ms,
+            error,
+        })
+    }
 }
 
 pub type TaskMap = Arc<RwLock<HashMap<TaskId, TaskEntry>>>;
 
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Adds a started task, dropping the oldest finished ones beyond [`TASK_HISTORY_SIZE`].
+pub async fn insert_task(tasks: &TaskMap, task_id: TaskId, entry: TaskEntry) {
+    let mut tasks_guard = tasks.write().await;
+    tasks_guard.insert(task_id, entry);
+
+    let mut finished: Vec<(u64, TaskId)> = tasks_guard
+        .iter()
+        .filter_map(|(id, entry)| entry.finished_at_ms.map(|at| (at, *id)))
+        .collect();
+    if finished.len() > TASK_HISTORY_SIZE {
+        finished.sort();
+        let excess = finished.len() - TASK_HISTORY_SIZE;
+        for (_, id) in finished.into_iter().take(excess) {
+            tasks_guard.remove(&id);
+        }
+    }
+}
+
 pub(crate) async fn handle_query_task(
     req: QueryTaskRequest,
     update_tx: UpdateSender,
@@ -41,6 +111,7 @@ pub(crate) async fn handle_query_task(
                 task_id: entry.task.id,
                 status: entry.task.status.clone(),
                 progress: entry.task.progress.clone(),
+                repeat: 1,
             }),
         }
     } else {
@@ -48,6 +119,7 @@ pub(crate) async fn handle_query_task(
         Response::Error(ErrorResponse {
             error: format!("Task not found: {}", task_id),
             original_request: Some(original_request_str.to_string()),
+            code: None,
         })
     };
 
@@ -98,7 +170,7 @@ pub(crate) async fn handle_stop_task(
                 // Abort the task if the handle exists
                 if let Some(handle) = &entry.abort_handle {
                     handle.abort();
-                    entry.abort_handle = None; // Remove handle after aborting
+                    entry.finish();
                     entry.task.status = TaskStatus::Stopped;
                     entry.task.result =
                         mutant_protocol::TaskResult::Error("Task stopped by user request".to_string());
@@ -122,6 +194,7 @@ pub(crate) async fn handle_stop_task(
                         entry.task.result = mutant_protocol::TaskResult::Error(
                             "Task stopped by user request (handle missing)".to_string(),
                         );
+                        entry.finish();
                     }
                 }
             }
@@ -136,6 +209,7 @@ pub(crate) async fn handle_stop_task(
         let error_response = Response::Error(ErrorResponse {
             error: format!("Task not found: {}", task_id),
             original_request: None, // Don't have original request string here easily
+            code: None,
         });
         if update_tx.send(error_response).is_err() {
             log::warn!("Failed to send task not found error to client (channel closed): task_id={}", task_id);
@@ -144,3 +218,23 @@ pub(crate) async fn handle_stop_task(
 
     Ok(())
 }
+
+pub(crate) async fn handle_task_history(
+    req: TaskHistoryRequest,
+    update_tx: UpdateSender,
+    tasks: TaskMap,
+) -> Result<(), DaemonError> {
+    let tasks_guard = tasks.read().await;
+    let mut records: Vec<TaskRecord> = tasks_guard.values().filter_map(TaskEntry::record).collect();
+    records.sort_by(|a, b| b.finished_at_ms.cmp(&a.finished_at_ms));
+    if let Some(limit) = req.limit {
+        records.truncate(limit);
+    }
+
+    update_tx
+        .send(Response::TaskHistory(TaskHistoryResponse { records }))
+        .map_err(|e| DaemonError::Internal(format!("Update channel send error: {}", e)))?;
+
+    log::debug!("Listed task history");
+    Ok(())
+}
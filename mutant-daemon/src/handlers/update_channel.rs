@@ -0,0 +1,400 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use mutant_protocol::{
+    GetEvent, PutEvent, Response, TaskId, TaskProgress, TaskStatus, TaskUpdateResponse,
+};
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::Notify;
+
+/// Responses waiting for a client before progress updates start being merged.
+const UPDATE_BUFFER_SIZE: usize = 64;
+
+/// Creates the channel carrying the responses of one websocket client.
+///
+/// Sending never waits: once the client lags [`UPDATE_BUFFER_SIZE`] responses behind,
+/// the progress updates of a task are merged together until the client catches up,
+/// so a slow client neither stalls the operations nor grows the daemon memory.
+/// Every other response, results and errors included, is always delivered.
+pub(crate) fn update_channel() -> (UpdateSender, UpdateReceiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {
+            senders: 1,
+            ..Default::default()
+        }),
+        notify: Notify::new(),
+    });
+
+    (
+        UpdateSender {
+            shared: shared.clone(),
+        },
+        UpdateReceiver {
+            shared,
+            ready: VecDeque::new(),
+        },
+    )
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    notify: Notify,
+}
+
+#[derive(Default)]
+struct Queue {
+    items: VecDeque<Item>,
+    /// Progress of the tasks waiting in `items` as merged updates.
+    backlogs: HashMap<TaskId, ProgressBacklog>,
+    senders: usize,
+    receiver_closed: bool,
+}
+
+enum Item {
+    Response(Response),
+    /// The merged progress of a task, taken from `backlogs` when its turn comes.
+    Progress(TaskId),
+}
+
+/// The progress updates of a task the client has not received yet.
+struct ProgressBacklog {
+    /// The latest one wins.
+    status: TaskStatus,
+    /// Each distinct event with how many times it happened, in the order first seen.
+    /// Chunk completions of one kind are merged into the one with the highest count.
+    events: Vec<(TaskProgress, usize)>,
+}
+
+impl ProgressBacklog {
+    fn new(status: TaskStatus) -> Self {
+        Self {
+            status,
+            events: Vec::new(),
+        }
+    }
+
+    fn merge(&mut self, status: TaskStatus, progress: TaskProgress, repeat: usize) {
+        self.status = status;
+        let merged = self
+            .events
+            .iter_mut()
+            .find(|(event, _)| *event == progress || completed_counts(event, &progress).is_some());
+        match merged {
+            Some((event, count)) => {
+                if let Some((old, new)) = completed_counts(event, &progress) {
+                    if new > old {
+                        *event = progress;
+                    }
+                }
+                *count += repeat;
+            }
+            None => self.events.push((progress, repeat)),
+        }
+    }
+
+    fn into_responses(self, task_id: TaskId) -> impl Iterator<Item = Response> {
+        let status = self.status;
+        self.events.into_iter().map(move |(progress, repeat)| {
+            Response::TaskUpdate(TaskUpdateResponse {
+                task_id,
+                status: status.clone(),
+                progress: Some(progress),
+                repeat,
+            })
+        })
+    }
+}
+
+/// The completed counts of two chunk completions of the same kind.
+fn completed_counts(old: &TaskProgress, new: &TaskProgress) -> Option<(usize, usize)> {
+    match (old, new) {
+        (
+            TaskProgress::Put(PutEvent::PadsWritten {
+                completed_count: old,
+                ..
+            }),
+            TaskProgress::Put(PutEvent::PadsWritten {
+                completed_count: new,
+                ..
+            }),
+        )
+        | (
+            TaskProgress::Put(PutEvent::PadsConfirmed {
+                completed_count: old,
+                ..
+            }),
+            TaskProgress::Put(PutEvent::PadsConfirmed {
+                completed_count: new,
+                ..
+            }),
+        )
+        | (
+            TaskProgress::Get(GetEvent::PadFetched {
+                completed_count: old,
+                ..
+            }),
+            TaskProgress::Get(GetEvent::PadFetched {
+                completed_count: new,
+                ..
+            }),
+        ) => Some((*old, *new)),
+        _ => None,
+    }
+}
+
+pub(crate) struct UpdateSender {
+    shared: Arc<Shared>,
+}
+
+impl UpdateSender {
+    /// Queues `response` for the client, failing only once the client is gone.
+    pub fn send(&self, response: Response) -> Result<(), SendError<Response>> {
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.receiver_closed {
+            return Err(SendError(response));
+        }
+
+        match response {
+            Response::TaskUpdate(TaskUpdateResponse {
+                task_id,
+                status,
+                progress: Some(progress),
+                repeat,
+            }) if queue.backlogs.contains_key(&task_id)
+                || queue.items.len() >= UPDATE_BUFFER_SIZE =>
+            {
+                if !queue.backlogs.contains_key(&task_id) {
+                    queue.items.push_back(Item::Progress(task_id));
+                }
+                queue
+                    .backlogs
+                    .entry(task_id)
+                    .or_insert_with(|| ProgressBacklog::new(status.clone()))
+                    .merge(status, progress, repeat);
+            }
+            response => queue.items.push_back(Item::Response(response)),
+        }
+        drop(queue);
+
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+}
+
+impl Clone for UpdateSender {
+    fn clone(&self) -> Self {
+        self.shared.queue.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl Drop for UpdateSender {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().senders -= 1;
+        // Wakes the receiver in case this was the last sender.
+        self.shared.notify.notify_one();
+    }
+}
+
+pub(crate) struct UpdateReceiver {
+    shared: Arc<Shared>,
+    /// Updates of a merged backlog not handed out yet.
+    ready: VecDeque<Response>,
+}
+
+impl UpdateReceiver {
+    /// The next response to send, `None` once every sender is dropped and all was received.
+    pub async fn recv(&mut self) -> Option<Response> {
+        loop {
+            if let Some(response) = self.ready.pop_front() {
+                return Some(response);
+            }
+
+            {
+                let mut queue = self.shared.queue.lock().unwrap();
+                match queue.items.pop_front() {
+                    Some(Item::Response(response)) => return Some(response),
+                    Some(Item::Progress(task_id)) => {
+                        if let Some(backlog) = queue.backlogs.remove(&task_id) {
+                            self.ready.extend(backlog.into_responses(task_id));
+                        }
+                        continue;
+                    }
+                    None if queue.senders == 0 => return None,
+                    None => {}
+                }
+            }
+
+            self.shared.notify.notified().await;
+        }
+    }
+}
+
+impl Drop for UpdateReceiver {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        queue.receiver_closed = true;
+        queue.items.clear();
+        queue.backlogs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mutant_protocol::{PutResult, TaskResult, TaskResultResponse, TaskResultType};
+    use std::time::Duration;
+
+    fn put_update(task_id: TaskId, event: PutEvent) -> Response {
+        Response::TaskUpdate(TaskUpdateResponse {
+            task_id,
+            status: TaskStatus::InProgress,
+            progress: Some(TaskProgress::Put(event)),
+            repeat: 1,
+        })
+    }
+
+    fn written_event(chunk_index: usize) -> PutEvent {
+        PutEvent::PadsWritten {
+            chunk_index,
+            completed_count: chunk_index + 1,
+        }
+    }
+
+    #[test]
+    fn test_merged_chunk_completions_keep_the_highest_count() {
+        let mut backlog = ProgressBacklog::new(TaskStatus::InProgress);
+        for completed_count in [2, 4, 3, 1] {
+            let event = PutEvent::PadsWritten {
+                chunk_index: completed_count * 10,
+                completed_count,
+            };
+            backlog.merge(TaskStatus::InProgress, TaskProgress::Put(event), 1);
+        }
+
+        assert_eq!(
+            backlog.events,
+            vec![(
+                TaskProgress::Put(PutEvent::PadsWritten {
+                    chunk_index: 40,
+                    completed_count: 4,
+                }),
+                4
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_does_not_hold_back_the_store() {
+        const PADS: usize = 10_000;
+
+        let (update_tx, mut update_rx) = update_channel();
+        let task_id = TaskId::new_v4();
+
+        let subscriber = tokio::spawn(async move {
+            let mut written = 0;
+            let mut confirmed = 0;
+            let mut confirmed_position = 0;
+            let mut received = 0;
+            let mut last = None;
+            while let Some(response) = update_rx.recv().await {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                received += 1;
+                if let Response::TaskUpdate(update) = &response {
+                    match update.progress {
+                        Some(TaskProgress::Put(PutEvent::PadsWritten { .. })) => {
+                            written += update.repeat
+                        }
+                        Some(TaskProgress::Put(PutEvent::PadsConfirmed {
+                            completed_count,
+                            ..
+                        })) => {
+                            assert!(completed_count > confirmed_position);
+                            confirmed_position = completed_count;
+                            confirmed += update.repeat
+                        }
+                        _ => {}
+                    }
+                }
+                last = Some(response);
+            }
+            (written, confirmed, confirmed_position, received, last)
+        });
+
+        let started = std::time::Instant::now();
+        update_tx
+            .send(put_update(
+                task_id,
+                PutEvent::Starting {
+                    total_chunks: PADS,
+                    initial_written_count: 0,
+                    initial_confirmed_count: 0,
+                    chunks_to_reserve: PADS,
+                },
+            ))
+            .unwrap();
+        for chunk_index in 0..PADS {
+            update_tx
+                .send(put_update(task_id, written_event(chunk_index)))
+                .unwrap();
+            update_tx
+                .send(put_update(
+                    task_id,
+                    PutEvent::PadsConfirmed {
+                        chunk_index,
+                        completed_count: chunk_index + 1,
+                    },
+                ))
+                .unwrap();
+            let queue = update_tx.shared.queue.lock().unwrap();
+            assert!(queue.items.len() <= UPDATE_BUFFER_SIZE + 1);
+        }
+        update_tx
+            .send(put_update(task_id, PutEvent::Complete))
+            .unwrap();
+        let result = Response::TaskResult(TaskResultResponse {
+            task_id,
+            status: TaskStatus::Completed,
+            result: TaskResult::Result(TaskResultType::Put(PutResult {
+                public_address: None,
+                chunks_unchanged: None,
+                chunks_rewritten: None,
+                io: None,
+            })),
+        });
+        update_tx.send(result.clone()).unwrap();
+        // A subscriber keeping up with every update would have needed 20 seconds.
+        assert!(started.elapsed() < Duration::from_secs(2));
+        drop(update_tx);
+
+        let (written, confirmed, confirmed_position, received, last) = subscriber.await.unwrap();
+        assert_eq!(written, PADS);
+        assert_eq!(confirmed, PADS);
+        assert_eq!(confirmed_position, PADS);
+        assert!(received < 2 * PADS);
+        assert_eq!(last, Some(result));
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_not_merged_while_the_subscriber_keeps_up() {
+        let (update_tx, mut update_rx) = update_channel();
+        let task_id = TaskId::new_v4();
+
+        for chunk_index in 0..3 {
+            update_tx
+                .send(put_update(task_id, written_event(chunk_index)))
+                .unwrap();
+            assert_eq!(
+                update_rx.recv().await,
+                Some(put_update(task_id, written_event(chunk_index)))
+            );
+        }
+
+        drop(update_rx);
+        assert!(update_tx
+            .send(put_update(task_id, written_event(3)))
+            .is_err());
+    }
+}
@@ -4,18 +4,18 @@ use futures_util::{
     sink::SinkExt,
     stream::StreamExt,
 };
-use tokio::sync::mpsc;
 use warp::ws::WebSocket;
 use mutant_lib::MutAnt;
 use mutant_protocol::{ErrorResponse, Request, Response};
 
 use super::common::send_response;
 use super::dispatcher::handle_request;
+use super::update_channel::update_channel;
 use super::{TaskMap, ActiveKeysMap};
 
 pub async fn handle_ws(ws: WebSocket, mutant: Arc<MutAnt>, tasks: TaskMap, active_keys: ActiveKeysMap) {
     let (mut ws_sender, mut ws_receiver) = ws.split();
-    let (update_tx, mut update_rx) = mpsc::unbounded_channel::<Response>();
+    let (update_tx, mut update_rx) = update_channel();
 
     log::info!("WebSocket client connected");
 
@@ -66,6 +66,7 @@ pub async fn handle_ws(ws: WebSocket, mutant: Arc<MutAnt>, tasks: TaskMap, activ
                         let _ = update_tx.send(Response::Error(ErrorResponse {
                             error: e.to_string(),
                             original_request: Some(original_request),
+                            code: None,
                         }));
                     }
                 }
@@ -74,6 +75,7 @@ pub async fn handle_ws(ws: WebSocket, mutant: Arc<MutAnt>, tasks: TaskMap, activ
                     let _ = update_tx.send(Response::Error(ErrorResponse {
                         error: format!("Invalid JSON request: {}", e),
                         original_request: Some(original_request),
+                        code: None,
                     }));
                 }
             }
@@ -82,6 +84,7 @@ pub async fn handle_ws(ws: WebSocket, mutant: Arc<MutAnt>, tasks: TaskMap, activ
             let _ = update_tx.send(Response::Error(ErrorResponse {
                 error: "Binary messages are not supported".to_string(),
                 original_request: None,
+                code: None,
             }));
         } else if msg.is_ping() {
             log::trace!("Received Ping");
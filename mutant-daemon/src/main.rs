@@ -7,6 +7,7 @@ mod wallet;
 
 use error::Error;
 use log::LevelFilter;
+use mutant_lib::config::MutAntConfig;
 
 #[derive(Parser)]
 struct Args {
@@ -16,6 +17,12 @@ struct Args {
     alphanet: bool,
     #[arg(long)]
     ignore_ctrl_c: bool,
+    /// Accept the key of the local devnet on a public network, where anyone can spend from it
+    #[arg(long = "i-know-what-im-doing")]
+    allow_dev_key: bool,
+    /// Pads read or written at once, across all operations
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    concurrency: Option<u64>,
 }
 
 #[tokio::main]
@@ -29,13 +36,30 @@ async fn main() -> Result<(), Error> {
     // Parse command line arguments
     let args = Args::parse();
 
+    let mut config = MutAntConfig::try_from_env()?;
+    if let Some(concurrency) = args.concurrency {
+        config.max_concurrent_pad_ops = concurrency as usize;
+    }
+    config.allow_dev_key_on_mainnet |= args.allow_dev_key;
+
     // Convert to app options
     let options = app::AppOptions {
         local: args.local,
         alphanet: args.alphanet,
         ignore_ctrl_c: args.ignore_ctrl_c,
+        config,
     };
 
     // Run the application
-    app::run(options).await
+    match app::run(options).await {
+        // Printed in full, the debug output would bury why the wallet was refused.
+        Err(e @ Error::NetworkMismatch(..)) => {
+            eprintln!("Error: {}", e);
+            if let Err(write_error) = std::fs::write(app::STARTUP_ERROR_FILE, e.to_string()) {
+                log::warn!("Failed to leave the refusal for the CLI: {}", write_error);
+            }
+            std::process::exit(1);
+        }
+        result => result,
+    }
 }
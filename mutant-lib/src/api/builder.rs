@@ -0,0 +1,150 @@
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    clock::Clock,
+    config::MutAntConfig,
+    error::Error,
+    events::InitCallback,
+    network::NetworkChoice,
+    ops::{ChunkTransform, ReadStrategy},
+};
+
+use super::{ensure_key_matches_network, MutAnt};
+
+/// Sets a [`MutAnt`] up in one go, for the embedders that need more than what one of
+/// the `init` functions takes. Anything left unset gets the default of
+/// [`MutAnt::init`], that is [`MutAntConfig::from_env`] for the settings.
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+///
+/// use mutant_lib::config::{Clock, NetworkChoice};
+/// use mutant_lib::storage::ZstdTransform;
+/// use mutant_lib::MutAnt;
+///
+/// # async fn run(key_hex: &str) -> Result<(), mutant_lib::error::Error> {
+/// let ant = MutAnt::builder(key_hex)
+///     .network(NetworkChoice::Alphanet)
+///     .clock(Clock::new(chrono::Utc::now))
+///     .audit_log(None)
+///     .transform(Arc::new(ZstdTransform::default()))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct MutAntBuilder {
+    private_key_hex: String,
+    network_choice: NetworkChoice,
+    accept_dev_key: bool,
+    init_callback: Option<InitCallback>,
+    config: Option<MutAntConfig>,
+    read_strategy: Option<ReadStrategy>,
+    transform_pipeline: Vec<Arc<dyn ChunkTransform>>,
+    registered_transforms: Vec<Arc<dyn ChunkTransform>>,
+}
+
+impl MutAntBuilder {
+    pub(super) fn new(private_key_hex: &str) -> Self {
+        Self {
+            private_key_hex: private_key_hex.to_string(),
+            network_choice: NetworkChoice::Mainnet,
+            accept_dev_key: false,
+            init_callback: None,
+            config: None,
+            read_strategy: None,
+            transform_pipeline: Vec::new(),
+            registered_transforms: Vec::new(),
+        }
+    }
+
+    /// The network to connect to, the mainnet by default.
+    pub fn network(mut self, network_choice: NetworkChoice) -> Self {
+        self.network_choice = network_choice;
+        self
+    }
+
+    /// Accepts the key of the local devnet on a public network, where anyone can spend
+    /// from it, see [`MutAnt::init_with_dev_key`]. Applies whatever the config given,
+    /// which can allow it too with [`MutAntConfig::allow_dev_key_on_mainnet`].
+    pub fn accept_dev_key(mut self) -> Self {
+        self.accept_dev_key = true;
+        self
+    }
+
+    /// Reports the progress of the initialization, see [`MutAnt::init_with_progress`].
+    pub fn init_callback(mut self, init_callback: InitCallback) -> Self {
+        self.init_callback = Some(init_callback);
+        self
+    }
+
+    /// The settings of the instance, replacing the ones given before.
+    pub fn config(mut self, config: MutAntConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// See [`MutAntConfig::clock`].
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.config_mut().clock = clock;
+        self
+    }
+
+    /// See [`MutAntConfig::audit_log`], `None` disables the audit log.
+    pub fn audit_log(mut self, audit_log: Option<PathBuf>) -> Self {
+        self.config_mut().audit_log = audit_log;
+        self
+    }
+
+    /// See [`MutAnt::configure_read_strategy`].
+    pub fn read_strategy(mut self, read_strategy: ReadStrategy) -> Self {
+        self.read_strategy = Some(read_strategy);
+        self
+    }
+
+    /// Appends `transform` to the pipeline new values go through, after the ones added
+    /// before, see [`MutAnt::configure_transforms`].
+    pub fn transform(mut self, transform: Arc<dyn ChunkTransform>) -> Self {
+        self.transform_pipeline.push(transform);
+        self
+    }
+
+    /// See [`MutAnt::register_transform`].
+    pub fn register_transform(mut self, transform: Arc<dyn ChunkTransform>) -> Self {
+        self.registered_transforms.push(transform);
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut MutAntConfig {
+        self.config.get_or_insert_with(MutAntConfig::from_env)
+    }
+
+    pub async fn build(self) -> Result<MutAnt, Error> {
+        let mut config = match self.config {
+            Some(config) => config,
+            None => MutAntConfig::try_from_env()?,
+        };
+        config.allow_dev_key_on_mainnet |= self.accept_dev_key;
+        ensure_key_matches_network(&self.private_key_hex, self.network_choice, &config)?;
+
+        let mutant = MutAnt::init_all_with_progress(
+            &self.private_key_hex,
+            self.network_choice,
+            self.init_callback,
+            Arc::new(config),
+        )
+        .await?;
+
+        if let Some(read_strategy) = self.read_strategy {
+            mutant.configure_read_strategy(read_strategy).await;
+        }
+        for transform in self.registered_transforms {
+            mutant.register_transform(transform).await;
+        }
+        if !self.transform_pipeline.is_empty() {
+            mutant.configure_transforms(self.transform_pipeline).await;
+        }
+
+        Ok(mutant)
+    }
+}
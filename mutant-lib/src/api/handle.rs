@@ -0,0 +1,87 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use mutant_protocol::{ProgressSnapshot, PutCallback};
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::error::Error;
+use crate::network::io::inherit_io;
+
+/// A store running in the background, see [`super::MutAnt::put_with_handle`].
+///
+/// Awaiting the handle returns the result of the store, [`Self::progress`] tells where
+/// it stands in the meantime without having to fold its events.
+pub struct OperationHandle<T> {
+    task: JoinHandle<Result<T, Error>>,
+    snapshot: Arc<Mutex<ProgressSnapshot>>,
+}
+
+impl<T: Send + 'static> OperationHandle<T> {
+    /// Runs `operation` with a callback keeping the snapshot of the handle up to date,
+    /// every event being forwarded to `put_callback` afterwards.
+    pub(super) fn spawn<F, Fut>(
+        bytes_total: u64,
+        put_callback: Option<PutCallback>,
+        operation: F,
+    ) -> Self
+    where
+        F: FnOnce(Option<PutCallback>) -> Fut,
+        Fut: Future<Output = Result<T, Error>> + Send + 'static,
+    {
+        let snapshot = Arc::new(Mutex::new(ProgressSnapshot::new(bytes_total)));
+        let tracked = snapshot.clone();
+        let tracking_callback: PutCallback = Arc::new(move |event| {
+            tracked.lock().unwrap().apply(&event);
+            let put_callback = put_callback.clone();
+            Box::pin(async move {
+                match put_callback {
+                    Some(callback) => callback(event).await,
+                    None => Ok(true),
+                }
+            })
+        });
+
+        Self {
+            task: tokio::spawn(inherit_io(operation(Some(tracking_callback)))),
+            snapshot,
+        }
+    }
+
+    /// Where the store stands, as of its last event.
+    pub fn progress(&self) -> ProgressSnapshot {
+        *self.snapshot.lock().unwrap()
+    }
+
+    /// Stops the store, awaiting the handle then returns [`Error::OperationCancelled`].
+    /// The pads written so far are kept, storing the key again resumes from them.
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Stops the store like [`Self::cancel`] without the handle.
+    pub(super) fn abort_handle(&self) -> AbortHandle {
+        self.task.abort_handle()
+    }
+}
+
+impl<T> Future for OperationHandle<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.task)
+            .poll(cx)
+            .map(|joined| match joined {
+                Ok(result) => result,
+                Err(e) if e.is_cancelled() => Err(Error::OperationCancelled),
+                Err(e) => Err(Error::Internal(format!("Operation task failed: {}", e))),
+            })
+    }
+}
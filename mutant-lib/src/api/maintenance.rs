@@ -0,0 +1,80 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::error::Error;
+
+/// An operation rewriting the pad pools of the index as a whole. Two of them at once
+/// could both take the same free pad, or drop one, so they run one at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceOp {
+    Purge,
+    Sync,
+    Reset,
+}
+
+impl fmt::Display for MaintenanceOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MaintenanceOp::Purge => "purge",
+            MaintenanceOp::Sync => "sync",
+            MaintenanceOp::Reset => "reset",
+        })
+    }
+}
+
+/// Lets a single [`MaintenanceOp`] run at a time. Stores and fetches do not take it.
+#[derive(Debug, Default)]
+pub(crate) struct MaintenanceLock {
+    current: Mutex<Option<MaintenanceOp>>,
+}
+
+impl MaintenanceLock {
+    /// Starts `op`, failing with `MaintenanceInProgress` while another one runs. The
+    /// lock is released when the returned guard is dropped.
+    pub(crate) fn begin(self: &Arc<Self>, op: MaintenanceOp) -> Result<MaintenanceGuard, Error> {
+        let mut current = self.current.lock().unwrap();
+        if let Some(current) = *current {
+            return Err(Error::MaintenanceInProgress { current });
+        }
+        *current = Some(op);
+        Ok(MaintenanceGuard { lock: self.clone() })
+    }
+}
+
+pub(crate) struct MaintenanceGuard {
+    lock: Arc<MaintenanceLock>,
+}
+
+impl Drop for MaintenanceGuard {
+    fn drop(&mut self) {
+        *self.lock.current.lock().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_maintenance_operation_at_a_time() {
+        let lock = Arc::new(MaintenanceLock::default());
+
+        let purge = lock.begin(MaintenanceOp::Purge).unwrap();
+        assert!(matches!(
+            lock.begin(MaintenanceOp::Sync),
+            Err(Error::MaintenanceInProgress {
+                current: MaintenanceOp::Purge
+            })
+        ));
+        assert!(lock.begin(MaintenanceOp::Purge).is_err());
+
+        drop(purge);
+        let _sync = lock.begin(MaintenanceOp::Sync).unwrap();
+        assert!(matches!(
+            lock.begin(MaintenanceOp::Reset),
+            Err(Error::MaintenanceInProgress {
+                current: MaintenanceOp::Sync
+            })
+        ));
+    }
+}
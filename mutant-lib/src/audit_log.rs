@@ -0,0 +1,64 @@
+use mutant_protocol::AuditRecord;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// Appends `record` to the audit log at `path`.
+///
+/// The file is exclusively locked for the write, so that records of concurrent
+/// processes sharing the same log never interleave.
+pub(crate) fn append(path: &Path, record: &AuditRecord) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock()?;
+    let written = file.write_all(&line).and_then(|_| file.flush());
+    file.unlock()?;
+
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mutant_protocol::AuditOperation;
+
+    #[test]
+    fn test_concurrent_appends_keep_one_record_per_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let record = AuditRecord {
+                        timestamp_ms: i,
+                        owner: "owner".to_string(),
+                        operation: AuditOperation::Store,
+                        key: Some(format!("key-{}", i)),
+                        size_bytes: Some(1024),
+                        pad_count: Some(1),
+                        error: None,
+                        duration_ms: 1,
+                    };
+                    append(&path, &record).unwrap();
+                })
+            })
+            .collect();
+        writers.into_iter().for_each(|w| w.join().unwrap());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut keys: Vec<String> = content
+            .lines()
+            .map(|line| serde_json::from_str::<AuditRecord>(line).unwrap())
+            .filter_map(|record| record.key)
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            (0..8).map(|i| format!("key-{}", i)).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Longest time recorded fetches stay in memory only.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often a key has been fetched with this wallet on this machine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyAccess {
+    /// When the key was last fetched, in milliseconds since the epoch.
+    pub last_fetched_ms: u64,
+    pub fetch_count: u64,
+}
+
+#[derive(Debug, Default)]
+struct AccessState {
+    keys: HashMap<String, KeyAccess>,
+    dirty: bool,
+    last_flush: Option<Instant>,
+}
+
+/// Per-key fetch statistics, kept in a file next to the local index cache.
+///
+/// They are never written to the index: the remote index is not synced on every
+/// read, and other devices of the wallet do not learn what was read here.
+#[derive(Debug, Default)]
+pub struct AccessTracker {
+    /// `None` when tracking is disabled.
+    path: Option<PathBuf>,
+    state: Mutex<AccessState>,
+}
+
+impl AccessTracker {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Tracks fetches into the file at `path`, starting from what it already holds.
+    pub fn load(path: PathBuf) -> Self {
+        let keys = match fs::read(&path) {
+            Ok(bytes) => serde_cbor::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable access statistics {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path: Some(path),
+            state: Mutex::new(AccessState {
+                keys,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Counts a fetch of `key_name`. The file is only written once enough time passed
+    /// since it last was, see [`Self::flush`].
+    pub fn record_fetch(&self, key_name: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let mut state = self.state.lock().unwrap();
+        let access = state.keys.entry(key_name.to_string()).or_default();
+        access.last_fetched_ms = now_ms;
+        access.fetch_count += 1;
+        state.dirty = true;
+
+        let last_flush = *state.last_flush.get_or_insert_with(Instant::now);
+        if last_flush.elapsed() >= FLUSH_INTERVAL {
+            if let Err(e) = self.write(&mut state) {
+                warn!("Failed to save access statistics: {}", e);
+            }
+        }
+    }
+
+    pub fn get(&self, key_name: &str) -> Option<KeyAccess> {
+        self.state.lock().unwrap().keys.get(key_name).copied()
+    }
+
+    pub fn list(&self) -> HashMap<String, KeyAccess> {
+        self.state.lock().unwrap().keys.clone()
+    }
+
+    /// Drops the statistics of a removed key.
+    pub fn forget(&self, key_name: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.keys.remove(key_name).is_some() {
+            state.dirty = true;
+        }
+    }
+
+    /// Moves the statistics of a renamed key to its new name.
+    pub fn rename(&self, old_key: &str, new_key: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(access) = state.keys.remove(old_key) {
+            state.keys.insert(new_key.to_string(), access);
+            state.dirty = true;
+        }
+    }
+
+    /// Writes the fetches recorded since the last write.
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.write(&mut self.state.lock().unwrap())
+    }
+
+    fn write(&self, state: &mut AccessState) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !state.dirty {
+            return Ok(());
+        }
+
+        let bytes = serde_cbor::to_vec(&state.keys)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        fs::write(path, bytes)?;
+
+        state.dirty = false;
+        state.last_flush = Some(Instant::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_tracker_records_nothing() {
+        let tracker = AccessTracker::disabled();
+        tracker.record_fetch("key");
+        assert_eq!(tracker.get("key"), None);
+        tracker.flush().unwrap();
+    }
+
+    #[test]
+    fn test_fetches_are_written_on_flush_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.access.cbor");
+
+        let tracker = AccessTracker::load(path.clone());
+        tracker.record_fetch("key");
+        tracker.record_fetch("key");
+        tracker.record_fetch("other");
+        assert!(!path.exists());
+
+        let access = tracker.get("key").unwrap();
+        assert_eq!(access.fetch_count, 2);
+        assert!(access.last_fetched_ms > 0);
+
+        tracker.flush().unwrap();
+        let reloaded = AccessTracker::load(path.clone());
+        assert_eq!(reloaded.list(), tracker.list());
+
+        reloaded.forget("other");
+        reloaded.flush().unwrap();
+        assert_eq!(AccessTracker::load(path).get("other"), None);
+    }
+}
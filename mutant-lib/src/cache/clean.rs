@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use log::warn;
+use mutant_protocol::CacheCleanReport;
+
+use super::PendingPush;
+
+/// Prefix of the index caches and of the files kept next to them.
+const CACHE_PREFIX: &str = "master_index_";
+
+/// Files kept next to an index cache, named after it.
+const SIDECARS: [&str; 3] = [".access", ".pending", ".reads"];
+
+/// When the local index caches of other networks and application ids are removed.
+///
+/// Each network and application id has its own cache in the data directory and
+/// nothing removes the ones no longer used. They only hold a copy of the remote index,
+/// a later `sync` brings a removed one back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Removes the least recently used caches until they all fit in this size.
+    pub max_total_bytes: u64,
+    /// Removes the caches unused for longer than this.
+    pub max_age: Duration,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 256 * 1024 * 1024,
+            max_age: Duration::from_secs(90 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// An index cache and the files kept next to it.
+#[derive(Debug, Default)]
+struct CacheGroup {
+    files: Vec<(PathBuf, u64)>,
+    bytes: u64,
+    last_used: Option<SystemTime>,
+}
+
+/// The name of the index cache `file_name` belongs to, `None` for the files that are
+/// not part of a cache.
+fn cache_name(file_name: &str) -> Option<&str> {
+    if !file_name.starts_with(CACHE_PREFIX) {
+        return None;
+    }
+    let name = file_name.strip_suffix(".cbor")?;
+    Some(
+        SIDECARS
+            .iter()
+            .find_map(|sidecar| name.strip_suffix(sidecar))
+            .unwrap_or(name),
+    )
+}
+
+fn cache_groups(dir: &Path) -> io::Result<BTreeMap<String, CacheGroup>> {
+    let mut groups: BTreeMap<String, CacheGroup> = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(name) = cache_name(&file_name).filter(|_| metadata.is_file()) else {
+            continue;
+        };
+
+        let group = groups.entry(name.to_string()).or_default();
+        group.files.push((entry.path(), metadata.len()));
+        group.bytes += metadata.len();
+        group.last_used = group.last_used.max(metadata.modified().ok());
+    }
+    Ok(groups)
+}
+
+/// Removes the index caches of `dir` past the limits of `policy`, least recently used
+/// first. The cache at `active` and the caches with changes not pushed yet are kept.
+pub fn clean(
+    dir: &Path,
+    active: &Path,
+    policy: &CachePolicy,
+    dry_run: bool,
+) -> io::Result<CacheCleanReport> {
+    let active_name = active
+        .file_name()
+        .and_then(|name| cache_name(&name.to_string_lossy()).map(str::to_string));
+    let groups = cache_groups(dir)?;
+    let mut remaining: u64 = groups.values().map(|group| group.bytes).sum();
+    let mut report = CacheCleanReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    let mut candidates: Vec<(String, CacheGroup)> = groups
+        .into_iter()
+        .filter(|(name, _)| Some(name) != active_name.as_ref())
+        .collect();
+    candidates.sort_by_key(|(_, group)| group.last_used);
+
+    let now = SystemTime::now();
+    for (name, group) in candidates {
+        let expired = group
+            .last_used
+            .and_then(|last_used| now.duration_since(last_used).ok())
+            .is_some_and(|age| age > policy.max_age);
+        if !expired && remaining <= policy.max_total_bytes {
+            continue;
+        }
+
+        let pending = dir.join(format!("{}.pending.cbor", name));
+        if pending.exists() && PendingPush::load(pending).has_pending_operations() {
+            report.kept_pending.push(name);
+            continue;
+        }
+
+        for (path, size) in group.files {
+            if !dry_run {
+                if let Err(e) = fs::remove_file(&path) {
+                    warn!("Failed to remove cache file {:?}: {}", path, e);
+                    continue;
+                }
+            }
+            remaining -= size;
+            report.reclaimed_bytes += size;
+            report.removed.push((path.display().to_string(), size));
+        }
+    }
+
+    report.remaining_bytes = remaining;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    fn write(dir: &Path, name: &str, size: usize, age_days: u64) {
+        fs::write(dir.join(name), vec![0; size]).unwrap();
+        set_age(dir, name, age_days);
+    }
+
+    fn set_age(dir: &Path, name: &str, age_days: u64) {
+        let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+        File::options()
+            .write(true)
+            .open(dir.join(name))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    fn removed_names(report: &CacheCleanReport) -> Vec<String> {
+        let mut names: Vec<String> = report
+            .removed
+            .iter()
+            .map(|(path, _)| {
+                Path::new(path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_cache_name() {
+        assert_eq!(
+            cache_name("master_index_mainnet.cbor"),
+            Some("master_index_mainnet")
+        );
+        assert_eq!(
+            cache_name("master_index_mainnet.access.cbor"),
+            Some("master_index_mainnet")
+        );
+        assert_eq!(
+            cache_name("master_index_devnet.app-1a2b.pending.cbor"),
+            Some("master_index_devnet.app-1a2b")
+        );
+        assert_eq!(cache_name("cache.lock"), None);
+    }
+
+    #[test]
+    fn test_clean_keeps_the_active_and_pending_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "master_index_mainnet.cbor", 100, 400);
+        write(dir.path(), "master_index_mainnet.access.cbor", 10, 400);
+        write(dir.path(), "master_index_devnet.cbor", 100, 200);
+        write(dir.path(), "master_index_devnet.access.cbor", 10, 200);
+        write(dir.path(), "master_index_alphanet.cbor", 100, 200);
+        write(dir.path(), "master_index_mainnet.app-1a2b.cbor", 100, 1);
+        write(dir.path(), "cache.lock", 0, 400);
+        let pending = PendingPush::load(dir.path().join("master_index_alphanet.pending.cbor"));
+        pending.record_operation();
+        set_age(dir.path(), "master_index_alphanet.pending.cbor", 200);
+
+        let policy = CachePolicy {
+            max_total_bytes: 1024,
+            max_age: Duration::from_secs(90 * 24 * 60 * 60),
+        };
+        let active = dir.path().join("master_index_mainnet.cbor");
+        let report = clean(dir.path(), &active, &policy, true).unwrap();
+        assert_eq!(
+            removed_names(&report),
+            vec![
+                "master_index_devnet.access.cbor",
+                "master_index_devnet.cbor"
+            ]
+        );
+        assert_eq!(report.reclaimed_bytes, 110);
+        assert_eq!(report.kept_pending, vec!["master_index_alphanet"]);
+        // Nothing is removed on a dry run.
+        assert!(dir.path().join("master_index_devnet.cbor").exists());
+
+        let report = clean(dir.path(), &active, &policy, false).unwrap();
+        assert_eq!(report.reclaimed_bytes, 110);
+        assert!(!dir.path().join("master_index_devnet.cbor").exists());
+        assert!(dir.path().join("master_index_mainnet.cbor").exists());
+        assert!(dir.path().join("master_index_alphanet.cbor").exists());
+        assert!(dir.path().join("cache.lock").exists());
+
+        // Over the size limit, the least recently used caches go first.
+        let policy = CachePolicy {
+            max_total_bytes: 350,
+            ..policy
+        };
+        write(dir.path(), "master_index_devnet.cbor", 100, 2);
+        let report = clean(dir.path(), &active, &policy, false).unwrap();
+        assert_eq!(removed_names(&report), vec!["master_index_devnet.cbor"]);
+        assert!(report.remaining_bytes <= policy.max_total_bytes);
+        assert!(dir
+            .path()
+            .join("master_index_mainnet.app-1a2b.cbor")
+            .exists());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod access;
+pub mod clean;
+pub mod pending_push;
+pub mod predictive;
+
+pub use access::{AccessTracker, KeyAccess};
+pub use clean::CachePolicy;
+pub use pending_push::PendingPush;
+pub use predictive::{AccessPattern, PredictiveReadCache};
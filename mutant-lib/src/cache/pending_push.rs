@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PendingState {
+    /// Mutating operations since the remote index was last pushed.
+    operations: u64,
+    /// Why the last push failed, `None` if none failed since the last successful one.
+    failed_push: Option<String>,
+}
+
+/// The index changes the remote index does not hold yet, kept in a file next to the
+/// local index cache.
+///
+/// Changes are always saved to the local cache first, so a push that fails loses
+/// nothing: they wait for the next push. A failed push is recorded here so that the
+/// next start of the daemon retries it and `stats` tells how far behind the remote
+/// index is.
+#[derive(Debug, Default)]
+pub struct PendingPush {
+    /// `None` for an instance that never pushes.
+    path: Option<PathBuf>,
+    state: Mutex<PendingState>,
+}
+
+impl PendingPush {
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Tracks the pending changes in the file at `path`, starting from what it holds.
+    pub fn load(path: PathBuf) -> Self {
+        let state = match fs::read(&path) {
+            Ok(bytes) => serde_cbor::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable pending push record {:?}: {}", path, e);
+                PendingState::default()
+            }),
+            Err(_) => PendingState::default(),
+        };
+
+        Self {
+            path: Some(path),
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Counts a mutating operation saved to the local cache only.
+    pub fn record_operation(&self) {
+        self.update(|state| state.operations += 1);
+    }
+
+    /// Records that pushing the index failed with `error`.
+    pub fn record_failed_push(&self, error: &str) {
+        self.update(|state| state.failed_push = Some(error.to_string()));
+    }
+
+    /// Records that the remote index holds every change of the local one.
+    pub fn record_push(&self) {
+        self.update(|state| *state = PendingState::default());
+    }
+
+    /// How many operations the remote index is behind, `None` unless the last push
+    /// failed with some of them pending.
+    pub fn operations_behind(&self) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        (state.failed_push.is_some() && state.operations > 0).then_some(state.operations)
+    }
+
+    /// Whether operations were saved to the local cache since the last push.
+    pub fn has_pending_operations(&self) -> bool {
+        self.state.lock().unwrap().operations > 0
+    }
+
+    /// Why the last push failed, if it did.
+    pub fn failed_push(&self) -> Option<String> {
+        self.state.lock().unwrap().failed_push.clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut PendingState)) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        f(&mut state);
+
+        let written = serde_cbor::to_vec(&*state)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            .and_then(|bytes| fs::write(path, bytes));
+        if let Err(e) = written {
+            warn!("Failed to save the pending push record {:?}: {}", path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_push_survives_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.pending.cbor");
+        let pending = PendingPush::load(path.clone());
+        pending.record_operation();
+        pending.record_operation();
+        // Nothing failed yet, the changes simply were not pushed.
+        assert_eq!(pending.operations_behind(), None);
+
+        pending.record_failed_push("network unreachable");
+        let reloaded = PendingPush::load(path.clone());
+        assert_eq!(reloaded.operations_behind(), Some(2));
+        assert_eq!(
+            reloaded.failed_push().as_deref(),
+            Some("network unreachable")
+        );
+
+        reloaded.record_push();
+        assert_eq!(PendingPush::load(path).operations_behind(), None);
+
+        let disabled = PendingPush::disabled();
+        disabled.record_operation();
+        disabled.record_failed_push("never written");
+        assert_eq!(disabled.operations_behind(), None);
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::ops::ReadStrategy;
+
+/// Number of recorded reads needed before the history is trusted.
+const MIN_READS_FOR_PREDICTION: u64 = 3;
+
+/// Longest time recorded reads stay in memory only.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How a key has been read so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessPattern {
+    /// Number of readers opened on the key.
+    pub reads: u64,
+    /// Number of those readers that were consumed up to the end.
+    pub full_reads: u64,
+}
+
+impl AccessPattern {
+    /// Keys mostly read to the end are worth fetching eagerly, keys mostly read
+    /// partially are better fetched lazily.
+    pub fn preferred_strategy(&self) -> ReadStrategy {
+        if self.reads < MIN_READS_FOR_PREDICTION || self.full_reads * 2 >= self.reads {
+            ReadStrategy::Eager
+        } else {
+            ReadStrategy::Lazy
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PatternState {
+    patterns: HashMap<String, AccessPattern>,
+    dirty: bool,
+    last_flush: Option<Instant>,
+}
+
+/// Tracks per-key access patterns to pick between eager and lazy reads.
+///
+/// The history is kept in a file next to the local index cache, like the
+/// [`super::AccessTracker`] statistics, and never written to the index so reads don't
+/// cause index churn.
+#[derive(Debug, Default)]
+pub struct PredictiveReadCache {
+    /// `None` for a history that only lives as long as the instance.
+    path: Option<PathBuf>,
+    state: Mutex<PatternState>,
+}
+
+impl PredictiveReadCache {
+    /// A history kept in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the history in the file at `path`, starting from what it already holds.
+    pub fn load(path: PathBuf) -> Self {
+        let patterns = match fs::read(&path) {
+            Ok(bytes) => serde_cbor::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable read history {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            path: Some(path),
+            state: Mutex::new(PatternState {
+                patterns,
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn strategy_for(&self, key_name: &str) -> ReadStrategy {
+        self.access_pattern(key_name).preferred_strategy()
+    }
+
+    pub fn access_pattern(&self, key_name: &str) -> AccessPattern {
+        self.state
+            .lock()
+            .unwrap()
+            .patterns
+            .get(key_name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Counts a read of `key_name`. The file is only written once enough time passed
+    /// since it last was, see [`Self::flush`].
+    pub fn record_read(&self, key_name: &str, read_to_end: bool) {
+        let mut state = self.state.lock().unwrap();
+        let pattern = state.patterns.entry(key_name.to_string()).or_default();
+        pattern.reads += 1;
+        if read_to_end {
+            pattern.full_reads += 1;
+        }
+        state.dirty = true;
+
+        let last_flush = *state.last_flush.get_or_insert_with(Instant::now);
+        if last_flush.elapsed() >= FLUSH_INTERVAL {
+            if let Err(e) = self.write(&mut state) {
+                warn!("Failed to save the read history: {}", e);
+            }
+        }
+    }
+
+    /// Drops the history of a removed key.
+    pub fn forget(&self, key_name: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.patterns.remove(key_name).is_some() {
+            state.dirty = true;
+        }
+    }
+
+    /// Moves the history of a renamed key to its new name.
+    pub fn rename(&self, old_key: &str, new_key: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(pattern) = state.patterns.remove(old_key) {
+            state.patterns.insert(new_key.to_string(), pattern);
+            state.dirty = true;
+        }
+    }
+
+    /// Writes the reads recorded since the last write.
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.write(&mut self.state.lock().unwrap())
+    }
+
+    fn write(&self, state: &mut PatternState) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !state.dirty {
+            return Ok(());
+        }
+
+        let bytes = serde_cbor::to_vec(&state.patterns)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        fs::write(path, bytes)?;
+
+        state.dirty = false;
+        state.last_flush = Some(Instant::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_to_eager_without_history() {
+        let cache = PredictiveReadCache::new();
+        assert_eq!(cache.strategy_for("key"), ReadStrategy::Eager);
+
+        cache.record_read("key", false);
+        cache.record_read("key", false);
+        assert_eq!(cache.strategy_for("key"), ReadStrategy::Eager);
+    }
+
+    #[test]
+    fn test_switches_to_lazy_on_partial_reads() {
+        let cache = PredictiveReadCache::new();
+        for _ in 0..3 {
+            cache.record_read("key", false);
+        }
+        cache.record_read("key", true);
+
+        assert_eq!(
+            cache.access_pattern("key"),
+            AccessPattern {
+                reads: 4,
+                full_reads: 1
+            }
+        );
+        assert_eq!(cache.strategy_for("key"), ReadStrategy::Lazy);
+        assert_eq!(cache.strategy_for("other"), ReadStrategy::Eager);
+    }
+
+    #[test]
+    fn test_history_outlives_the_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.reads.cbor");
+
+        let cache = PredictiveReadCache::load(path.clone());
+        for _ in 0..3 {
+            cache.record_read("key", false);
+        }
+        cache.record_read("gone", true);
+        assert!(!path.exists());
+        cache.forget("gone");
+        cache.flush().unwrap();
+
+        let reloaded = PredictiveReadCache::load(path);
+        assert_eq!(reloaded.strategy_for("key"), ReadStrategy::Lazy);
+        assert_eq!(reloaded.access_pattern("gone"), AccessPattern::default());
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::Arc;
+
+/// Where the timestamps of the index and of the audit log come from: modification
+/// times, removals and restorations from the trash.
+///
+/// The system clock by default. A device without a battery-backed clock, which reads
+/// 1970 until it gets the time from somewhere, can inject its own source with
+/// [`crate::config::MutAntConfig::clock`].
+#[derive(Clone, Default)]
+pub struct Clock(Option<Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>>);
+
+impl Clock {
+    pub fn new(now: impl Fn() -> DateTime<Utc> + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(now)))
+    }
+
+    /// A clock stopped at `at`.
+    pub fn fixed(at: DateTime<Utc>) -> Self {
+        Self::new(move || at)
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match &self.0 {
+            Some(now) => now(),
+            None => Utc::now(),
+        }
+    }
+
+    /// [`Self::now`] in milliseconds since the epoch, `0` before it.
+    pub fn now_ms(&self) -> u64 {
+        self.now().timestamp_millis().max(0) as u64
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Clock(injected)"),
+            None => f.write_str("Clock(system)"),
+        }
+    }
+}
+
+// Two indexes holding the same data are equal whatever clock they read.
+impl PartialEq for Clock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Clock {}
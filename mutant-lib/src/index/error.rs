@@ -2,7 +2,7 @@ use thiserror::Error;
 
 use crate::network::NetworkChoice;
 
-#[derive(Error, Debug, PartialEq, Clone)]
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
 pub enum IndexError {
     #[error("Key already exists: {0}")]
     KeyAlreadyExists(String),
@@ -22,6 +22,67 @@ pub enum IndexError {
     #[error("Deserialization error: {0}")]
     DeserializationError(String),
 
+    #[error("Index corrupted for key {key}: missing chunk indices {missing_indices:?}")]
+    IndexCorrupted {
+        key: String,
+        missing_indices: Vec<usize>,
+    },
+
+    #[error("Collection not found: {0}")]
+    CollectionNotFound(String),
+
+    #[error("Collection already exists: {0}")]
+    CollectionAlreadyExists(String),
+
+    #[error("Key {key} still has aliases: {aliases:?}")]
+    KeyHasAliases { key: String, aliases: Vec<String> },
+
+    #[error("No integrity proof for key: {0}")]
+    IntegrityProofNotFound(String),
+
+    #[error("Index would grow to {size} bytes, above the configured maximum of {max} bytes")]
+    IndexTooLarge { size: usize, max: usize },
+
+    #[error("Key not found in the trash: {0}")]
+    NotInTrash(String),
+
+    #[error("Cannot restore key {key}: {reason}")]
+    RestoreConflict { key: String, reason: String },
+
+    #[error("Remote index read back with checksum {found:x} instead of the pushed {expected:x}")]
+    PushVerificationFailed { expected: usize, found: usize },
+
+    #[error("Remote index of {size} bytes (checksum {checksum:x}) cannot be read, it is left as it is until a forced sync pushes the local one: {reason}")]
+    CorruptRemoteIndex {
+        size: usize,
+        checksum: usize,
+        reason: String,
+    },
+
+    #[error("Local index cache {path} of {size} bytes (checksum {checksum:x}) cannot be read, it is not saved over until the index is reset: {reason}")]
+    CorruptIndexCache {
+        path: String,
+        size: usize,
+        checksum: usize,
+        reason: String,
+    },
+
+    #[error("Invalid public catalogue: {0}")]
+    InvalidCatalogue(String),
+
+    #[error("Key {0} is in the namespace reserved for MutAnt")]
+    ReservedKey(String),
+
+    #[error("Pad of {owner} holds {size} bytes with {padding} bytes of padding, the index is corrupted")]
+    InvalidPadSize {
+        owner: String,
+        size: usize,
+        padding: usize,
+    },
+
+    #[error("Derived pad {address} of key {key} is held by another key")]
+    DerivedPadInUse { key: String, address: String },
+
     #[error("Network mismatch: {x:?} != {y:?}")]
     NetworkMismatch { x: NetworkChoice, y: NetworkChoice },
 }
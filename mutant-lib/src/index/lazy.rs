@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+use crate::error::Error;
+use crate::index::master_index::MasterIndex;
+
+/// The index of a [`crate::MutAnt`], opened from its summary by
+/// [`MasterIndex::open_summary`]. Locking it loads the rest of it first, so only what
+/// goes through [`Self::read_summary`] runs before the pad lists are read.
+#[derive(Clone)]
+pub(crate) struct LazyIndex {
+    index: Arc<RwLock<MasterIndex>>,
+    /// Set once the rest of the index is loaded, spares the check to the later locks.
+    loaded: Arc<AtomicBool>,
+}
+
+impl LazyIndex {
+    pub(crate) fn new(index: MasterIndex) -> Self {
+        Self {
+            loaded: Arc::new(AtomicBool::new(!index.details_pending())),
+            index: Arc::new(RwLock::new(index)),
+        }
+    }
+
+    /// The lock itself, for the operations given the index once
+    /// [`Self::ensure_details_loaded`] succeeded.
+    pub(crate) fn shared(&self) -> Arc<RwLock<MasterIndex>> {
+        self.index.clone()
+    }
+
+    /// Loads the rest of the index when it was opened from its summary.
+    pub(crate) async fn ensure_details_loaded(&self) -> Result<(), Error> {
+        if self.loaded.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        self.index.write().await.load_details()?;
+        self.loaded.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Reads the index once its pad lists are loaded. Fails with the load error rather
+    /// than handing out an index holding nothing but its summary.
+    pub(crate) async fn read(&self) -> Result<RwLockReadGuard<'_, MasterIndex>, Error> {
+        self.ensure_details_loaded().await?;
+        Ok(self.index.read().await)
+    }
+
+    /// Writes the index once its pad lists are loaded, see [`Self::read`].
+    pub(crate) async fn write(&self) -> Result<RwLockWriteGuard<'_, MasterIndex>, Error> {
+        self.ensure_details_loaded().await?;
+        Ok(self.index.write().await)
+    }
+
+    /// Reads the index as it is, for the `summary_*` methods of [`MasterIndex`].
+    pub(crate) async fn read_summary(&self) -> RwLockReadGuard<'_, MasterIndex> {
+        self.index.read().await
+    }
+
+    /// Reads the index as it is, without waiting for the lock.
+    pub(crate) fn try_read(&self) -> Result<RwLockReadGuard<'_, MasterIndex>, TryLockError> {
+        self.index.try_read()
+    }
+}
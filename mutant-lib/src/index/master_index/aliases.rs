@@ -0,0 +1,148 @@
+use crate::error::Error;
+use crate::index::error::IndexError;
+use log::info;
+
+use super::MasterIndex;
+
+impl MasterIndex {
+    /// Makes `alias_name` resolve to the key `existing_key` resolves to, without
+    /// duplicating any pad.
+    pub fn add_alias(&mut self, existing_key: &str, alias_name: &str) -> Result<(), Error> {
+        let canonical = self.resolve_alias(existing_key).to_string();
+        if !self.contains_key(&canonical) {
+            return Err(IndexError::KeyNotFound(existing_key.to_string()).into());
+        }
+        if self.contains_key(alias_name) || self.is_alias(alias_name) {
+            return Err(IndexError::KeyAlreadyExists(alias_name.to_string()).into());
+        }
+
+        info!("Aliasing {} to {}", alias_name, canonical);
+        self.aliases.insert(alias_name.to_string(), canonical);
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Returns the key `name` is an alias of, or `name` itself.
+    pub fn resolve_alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    pub fn is_alias(&self, name: &str) -> bool {
+        self.aliases.contains_key(name)
+    }
+
+    pub fn aliases_of(&self, key_name: &str) -> Vec<String> {
+        self.aliases
+            .iter()
+            .filter(|(_, canonical)| canonical.as_str() == key_name)
+            .map(|(alias, _)| alias.clone())
+            .collect()
+    }
+
+    /// Unlinks an alias, leaving the key it resolves to untouched.
+    pub fn remove_alias(&mut self, alias_name: &str) -> Result<(), Error> {
+        if self.aliases.remove(alias_name).is_none() {
+            return Err(IndexError::KeyNotFound(alias_name.to_string()).into());
+        }
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Fails if some aliases still resolve to `key_name`.
+    pub(crate) fn ensure_no_aliases(&self, key_name: &str) -> Result<(), Error> {
+        let aliases = self.aliases_of(key_name);
+        if aliases.is_empty() {
+            return Ok(());
+        }
+        Err(IndexError::KeyHasAliases {
+            key: key_name.to_string(),
+            aliases,
+        }
+        .into())
+    }
+
+    /// Hands the data of `key_name` over to its first alias, which becomes the key the
+    /// other aliases resolve to. Returns the promoted alias, `None` if there is none.
+    pub fn promote_alias(&mut self, key_name: &str) -> Result<Option<String>, Error> {
+        let Some(promoted) = self.aliases_of(key_name).into_iter().next() else {
+            return Ok(None);
+        };
+        self.aliases.remove(&promoted);
+
+        if let Some(entry) = self.index.remove(key_name) {
+            self.index.insert(promoted.clone(), entry);
+        } else if let Some(slot) = self.bundled_keys.remove(key_name) {
+            self.bundled_keys.insert(promoted.clone(), slot);
+        } else {
+            return Err(IndexError::KeyNotFound(key_name.to_string()).into());
+        }
+
+        if let Some(padding) = self.final_chunk_padding.remove(key_name) {
+            self.final_chunk_padding.insert(promoted.clone(), padding);
+        }
+        if let Some(header_len) = self.envelopes.remove(key_name) {
+            self.envelopes.insert(promoted.clone(), header_len);
+        }
+        if let Some(transforms) = self.transforms.remove(key_name) {
+            self.transforms.insert(promoted.clone(), transforms);
+        }
+        if let Some(record) = self.file_records.remove(key_name) {
+            self.file_records.insert(promoted.clone(), record);
+        }
+        if let Some(permutation) = self.chunk_permutations.remove(key_name) {
+            self.chunk_permutations.insert(promoted.clone(), permutation);
+        }
+        if let Some(modified_at) = self.modified_at.remove(key_name) {
+            self.modified_at.insert(promoted.clone(), modified_at);
+        }
+        for keys in self.collections.values_mut() {
+            if keys.remove(key_name) {
+                keys.insert(promoted.clone());
+            }
+        }
+        // A proof names the key it was made for.
+        self.integrity_proofs.remove(key_name);
+        for canonical in self.aliases.values_mut() {
+            if canonical == key_name {
+                *canonical = promoted.clone();
+            }
+        }
+
+        info!("Promoted alias {} in place of {}", promoted, key_name);
+        self.save(self.network_choice)?;
+
+        Ok(Some(promoted))
+    }
+
+    /// Adds the aliases of `other` and drops the ones that no longer resolve to a key,
+    /// or that a key now shadows. Returns the number of aliases added or dropped.
+    pub fn merge_aliases(&mut self, other: &MasterIndex) -> usize {
+        let before = self.aliases.clone();
+
+        for (alias, canonical) in &other.aliases {
+            self.aliases
+                .entry(alias.clone())
+                .or_insert_with(|| canonical.clone());
+        }
+
+        let dangling: Vec<String> = self
+            .aliases
+            .iter()
+            .filter(|(alias, canonical)| self.contains_key(alias) || !self.contains_key(canonical))
+            .map(|(alias, _)| alias.clone())
+            .collect();
+        for alias in dangling {
+            self.aliases.remove(&alias);
+        }
+
+        before
+            .keys()
+            .filter(|alias| !self.aliases.contains_key(*alias))
+            .count()
+            + self
+                .aliases
+                .keys()
+                .filter(|alias| !before.contains_key(*alias))
+                .count()
+    }
+}
@@ -0,0 +1,233 @@
+// Only the stats read bundles when the `bundles` feature is off.
+#![cfg_attr(not(feature = "bundles"), allow(dead_code))]
+
+use crate::error::Error;
+use crate::index::error::IndexError;
+use crate::index::pad_info::PadInfo;
+use crate::storage::ScratchpadAddress;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::MasterIndex;
+
+const BUNDLE_COUNT_SIZE: usize = 4;
+const BUNDLE_SLOT_HEADER_SIZE: usize = 8;
+
+/// Location of a small value packed into a shared bundle pad.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BundleSlot {
+    pub bundle_address: ScratchpadAddress,
+    /// Offset of the value from the start of the bundle pad content.
+    pub offset: usize,
+    pub len: usize,
+    pub checksum: usize,
+}
+
+/// A pad holding several small values behind an offset table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Bundle {
+    pub pad: PadInfo,
+    /// Maximum size of the bundle pad content, offset table included.
+    pub capacity: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BundleOccupancy {
+    /// Bytes taken by values that are still referenced.
+    pub live_bytes: usize,
+    /// Bytes written on the pad, including removed values not compacted yet.
+    pub used_bytes: usize,
+    pub capacity: usize,
+}
+
+impl BundleOccupancy {
+    pub fn ratio(&self) -> f64 {
+        if self.used_bytes == 0 {
+            return 0.0;
+        }
+        self.live_bytes as f64 / self.used_bytes as f64
+    }
+}
+
+/// Size of a bundle pad content holding values of the given lengths.
+pub fn bundle_size(value_lens: impl Iterator<Item = usize>) -> usize {
+    value_lens.fold(BUNDLE_COUNT_SIZE, |acc, len| {
+        acc.saturating_add(BUNDLE_SLOT_HEADER_SIZE)
+            .saturating_add(len)
+    })
+}
+
+/// Lays out `values` behind an offset table and returns the pad content along with
+/// the `(offset, len)` of each value, in the same order.
+pub fn encode_bundle(values: &[&[u8]]) -> (Vec<u8>, Vec<(usize, usize)>) {
+    let mut data = Vec::with_capacity(bundle_size(values.iter().map(|v| v.len())));
+    let mut slots = Vec::with_capacity(values.len());
+    let mut offset = BUNDLE_COUNT_SIZE + BUNDLE_SLOT_HEADER_SIZE * values.len();
+
+    data.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        data.extend_from_slice(&(offset as u32).to_le_bytes());
+        data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        slots.push((offset, value.len()));
+        offset += value.len();
+    }
+    for value in values {
+        data.extend_from_slice(value);
+    }
+
+    (data, slots)
+}
+
+/// Reads back the values of a bundle pad content from its offset table.
+pub fn decode_bundle(data: &[u8]) -> Result<Vec<(usize, usize)>, Error> {
+    let corrupted = || Error::Internal("Corrupted bundle pad offset table".to_string());
+
+    let count = data
+        .get(..BUNDLE_COUNT_SIZE)
+        .ok_or_else(corrupted)?
+        .try_into()
+        .map(u32::from_le_bytes)
+        .map_err(|_| corrupted())? as usize;
+
+    let mut slots = Vec::with_capacity(count);
+    for i in 0..count {
+        let start = BUNDLE_COUNT_SIZE + i * BUNDLE_SLOT_HEADER_SIZE;
+        let header = data
+            .get(start..start + BUNDLE_SLOT_HEADER_SIZE)
+            .ok_or_else(corrupted)?;
+        let offset = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+        if offset + len > data.len() {
+            return Err(corrupted());
+        }
+        slots.push((offset, len));
+    }
+
+    Ok(slots)
+}
+
+impl MasterIndex {
+    pub fn is_bundled(&self, key_name: &str) -> bool {
+        self.bundled_keys.contains_key(key_name)
+    }
+
+    /// The slot of a bundled key along with the pad holding it.
+    pub fn get_bundled(&self, key_name: &str) -> Option<(BundleSlot, PadInfo)> {
+        let slot = self.bundled_keys.get(key_name)?;
+        let bundle = self.find_bundle(&slot.bundle_address)?;
+        Some((slot.clone(), bundle.pad.clone()))
+    }
+
+    pub fn get_bundle(&self, address: &ScratchpadAddress) -> Option<Bundle> {
+        self.find_bundle(address).cloned()
+    }
+
+    fn find_bundle(&self, address: &ScratchpadAddress) -> Option<&Bundle> {
+        self.bundles.iter().find(|b| b.pad.address == *address)
+    }
+
+    /// Keys stored in the given bundle, ordered by offset.
+    pub fn bundle_keys(&self, address: &ScratchpadAddress) -> Vec<(String, BundleSlot)> {
+        let mut keys: Vec<_> = self
+            .bundled_keys
+            .iter()
+            .filter(|(_, slot)| slot.bundle_address == *address)
+            .map(|(key, slot)| (key.clone(), slot.clone()))
+            .collect();
+        keys.sort_by_key(|(_, slot)| slot.offset);
+        keys
+    }
+
+    pub fn bundle_occupancy(&self, address: &ScratchpadAddress) -> BundleOccupancy {
+        let Some(bundle) = self.find_bundle(address) else {
+            return BundleOccupancy::default();
+        };
+        let keys = self.bundle_keys(address);
+
+        BundleOccupancy {
+            live_bytes: bundle_size(keys.iter().map(|(_, slot)| slot.len)),
+            used_bytes: bundle.pad.size,
+            capacity: bundle.capacity,
+        }
+    }
+
+    /// Finds a bundle with room for one more value of `len` bytes, once compacted.
+    /// `except_key` is not counted, as it is about to be replaced.
+    pub fn find_bundle_with_room(
+        &self,
+        len: usize,
+        capacity: usize,
+        except_key: Option<&str>,
+    ) -> Option<Bundle> {
+        self.bundles
+            .iter()
+            .filter(|bundle| bundle.capacity == capacity)
+            .find(|bundle| {
+                let live = self
+                    .bundle_keys(&bundle.pad.address)
+                    .into_iter()
+                    .filter(|(key, _)| Some(key.as_str()) != except_key)
+                    .map(|(_, slot)| slot.len);
+                bundle_size(live.chain(std::iter::once(len))) <= bundle.capacity
+            })
+            .cloned()
+    }
+
+    /// Records a freshly written bundle pad and the new location of every value it holds.
+    pub fn commit_bundle(
+        &mut self,
+        bundle: Bundle,
+        slots: Vec<(String, BundleSlot)>,
+    ) -> Result<(), Error> {
+        let address = bundle.pad.address;
+
+        match self.bundles.iter_mut().find(|b| b.pad.address == address) {
+            Some(existing) => *existing = bundle,
+            None => self.bundles.push(bundle),
+        }
+
+        self.bundled_keys
+            .retain(|_, slot| slot.bundle_address != address);
+        for (key, slot) in slots {
+            self.bundled_keys.insert(key, slot);
+        }
+
+        debug!("Committed bundle {}", address);
+
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Forgets a bundled key. Its bytes stay on the bundle pad until the next compaction.
+    pub fn remove_bundled_key(&mut self, key_name: &str) -> Result<BundleSlot, Error> {
+        let slot = self
+            .bundled_keys
+            .remove(key_name)
+            .ok_or_else(|| IndexError::KeyNotFound(key_name.to_string()))?;
+        self.envelopes.remove(key_name);
+        self.transforms.remove(key_name);
+        self.modified_at.remove(key_name);
+
+        self.save(self.network_choice)?;
+
+        Ok(slot)
+    }
+
+    /// Drops a bundle that no longer holds any value and gives its pad back.
+    pub fn release_bundle(&mut self, address: &ScratchpadAddress) -> Result<(), Error> {
+        if let Some(position) = self.bundles.iter().position(|b| b.pad.address == *address) {
+            let mut bundle = self.bundles.remove(position);
+            bundle.pad.checksum = 0;
+            bundle.pad.size = 0;
+            self.bundled_keys
+                .retain(|_, slot| slot.bundle_address != *address);
+            self.free_pads(vec![bundle.pad])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn bundles(&self) -> &[Bundle] {
+        &self.bundles
+    }
+}
@@ -0,0 +1,97 @@
+use crate::config::NetworkChoice;
+use crate::error::Error;
+use crate::index::error::IndexError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{IndexEntry, MasterIndex};
+
+/// The public keys of an index, without any secret key, so it can be handed out as a
+/// read-only catalogue. Load it with `MutAnt::init_from_index_file`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PublicCatalogue {
+    pub network_choice: NetworkChoice,
+    pub keys: BTreeMap<String, IndexEntry>,
+    /// Length of the envelope header of the keys stored with one.
+    #[serde(default)]
+    pub envelopes: BTreeMap<String, usize>,
+}
+
+impl PublicCatalogue {
+    /// Reads a catalogue written by [`Self::save`].
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read(path)
+            .map_err(|_| IndexError::IndexFileNotFound(path.display().to_string()))?;
+        serde_json::from_slice(&data)
+            .map_err(|e| IndexError::DeserializationError(e.to_string()).into())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let data = serde_json::to_vec_pretty(self)
+            .map_err(|e| IndexError::SerializationError(e.to_string()))?;
+        std::fs::write(path, data)
+            .map_err(|e| Error::Internal(format!("Failed to write {}: {}", path.display(), e)))
+    }
+}
+
+impl MasterIndex {
+    /// The public keys of the index with the secret keys of their pads left out.
+    pub fn export_public_catalogue(&self) -> PublicCatalogue {
+        let keys: BTreeMap<String, IndexEntry> = self
+            .index
+            .iter()
+            .filter_map(|(key, entry)| match entry {
+                IndexEntry::PublicUpload(index_pad, pads) => {
+                    let mut index_pad = index_pad.clone();
+                    index_pad.sk_bytes.clear();
+                    let pads = pads
+                        .iter()
+                        .cloned()
+                        .map(|mut pad| {
+                            pad.sk_bytes.clear();
+                            pad
+                        })
+                        .collect();
+                    Some((key.clone(), IndexEntry::PublicUpload(index_pad, pads)))
+                }
+                IndexEntry::PrivateKey(_) => None,
+            })
+            .collect();
+        let envelopes = self
+            .envelopes
+            .iter()
+            .filter(|(key, _)| keys.contains_key(*key))
+            .map(|(key, size)| (key.clone(), *size))
+            .collect();
+
+        PublicCatalogue {
+            network_choice: self.network_choice,
+            keys,
+            envelopes,
+        }
+    }
+
+    /// An index holding the keys of `catalogue` that is never written to disk, every
+    /// save fails with `ReadOnlyMode`.
+    pub fn from_public_catalogue(catalogue: PublicCatalogue) -> Result<Self, Error> {
+        if let Some(key) = catalogue
+            .keys
+            .iter()
+            .find_map(|(key, entry)| matches!(entry, IndexEntry::PrivateKey(_)).then_some(key))
+        {
+            return Err(IndexError::InvalidCatalogue(format!("key {} is private", key)).into());
+        }
+
+        let mut index = MasterIndex::new_empty(catalogue.network_choice);
+        index.index = catalogue.keys;
+        index.envelopes = catalogue.envelopes;
+        index.read_only = true;
+        Ok(index)
+    }
+
+    /// Whether the index was loaded from a public catalogue.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
@@ -0,0 +1,72 @@
+use crate::error::Error;
+use crate::index::error::IndexError;
+use log::info;
+
+use super::MasterIndex;
+
+impl MasterIndex {
+    pub fn create_collection(&mut self, name: &str) -> Result<(), Error> {
+        if self.collections.contains_key(name) {
+            return Err(IndexError::CollectionAlreadyExists(name.to_string()).into());
+        }
+
+        self.collections.insert(name.to_string(), Default::default());
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Adds existing keys to a collection, creating the collection if needed.
+    pub fn add_to_collection(&mut self, name: &str, keys: &[String]) -> Result<(), Error> {
+        if let Some(missing) = keys.iter().find(|key| !self.contains_key(key)) {
+            return Err(IndexError::KeyNotFound(missing.clone()).into());
+        }
+
+        self.collections
+            .entry(name.to_string())
+            .or_default()
+            .extend(keys.iter().cloned());
+
+        info!("Added {} keys to collection {}", keys.len(), name);
+
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    pub fn list_collection(&self, name: &str) -> Result<Vec<String>, Error> {
+        self.collections
+            .get(name)
+            .map(|keys| keys.iter().cloned().collect())
+            .ok_or_else(|| IndexError::CollectionNotFound(name.to_string()).into())
+    }
+
+    pub fn list_collections(&self) -> Vec<String> {
+        self.collections.keys().cloned().collect()
+    }
+
+    /// Deletes a collection, leaving its keys untouched, and returns its members.
+    pub fn remove_collection(&mut self, name: &str) -> Result<Vec<String>, Error> {
+        let keys = self
+            .collections
+            .remove(name)
+            .ok_or_else(|| IndexError::CollectionNotFound(name.to_string()))?;
+
+        self.save(self.network_choice)?;
+
+        Ok(keys.into_iter().collect())
+    }
+
+    /// Adds the collections and memberships of `other` to this index, keeping the
+    /// members of both sides. Returns the number of memberships added.
+    pub fn merge_collections(&mut self, other: &MasterIndex) -> usize {
+        let mut added = 0;
+        for (name, keys) in &other.collections {
+            let local = self.collections.entry(name.clone()).or_default();
+            for key in keys {
+                if local.insert(key.clone()) {
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
@@ -1,49 +1,222 @@
-use crate::config::NetworkChoice;
+use crate::config::{MutAntConfig, NetworkChoice};
 use crate::error::Error;
 use crate::index::error::IndexError;
+use crate::index::PadInfo;
+use crate::rng::InstanceRng;
+use mutant_protocol::MAX_SCRATCHPAD_SIZE;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-use super::{MasterIndex, get_index_file_path};
+use super::summary::skip_summary;
+use super::{IndexEntry, MasterIndex, get_index_file_path};
+
+/// Sequence numbers of the snapshots of one local index cache.
+#[derive(Default)]
+pub(super) struct SnapshotSequence {
+    next: AtomicU64,
+    /// The last snapshot written to the file.
+    written: Mutex<u64>,
+}
+
+impl SnapshotSequence {
+    pub(super) fn next(&self) -> u64 {
+        self.next.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Snapshot sequence of each local index cache, so that a background checkpoint
+    /// finishing late never overwrites a more recent save of the same file, while the
+    /// saves of other caches are left alone.
+    static ref SNAPSHOTS: Mutex<HashMap<PathBuf, Arc<SnapshotSequence>>> = Default::default();
+}
+
+pub(super) fn snapshot_sequence(path: &Path) -> Arc<SnapshotSequence> {
+    SNAPSHOTS
+        .lock()
+        .unwrap()
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone()
+}
+
+/// What [`MasterIndex::save`] did with the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOutcome {
+    /// Written to the local cache.
+    Written,
+    /// Held back by a batch, written once the batch ends.
+    Deferred,
+    /// Only the summary of the index is loaded, the cache on disk is left as it is.
+    DetailsPending,
+    /// A more recent save of the same cache was already written.
+    Superseded,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Saves of the index started on the thread, see [`MasterIndex::saves_started`].
+    static SAVES_STARTED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Unsaved pad status transitions, never persisted.
+#[derive(Clone, Debug, Default)]
+pub(super) struct CheckpointState {
+    pending_transitions: usize,
+    last_save: Option<Instant>,
+    /// Batches holding back the saves, see [`MasterIndex::defer_saves`].
+    deferred: usize,
+}
+
+// Two indexes holding the same data are equal whatever their saving history.
+impl PartialEq for CheckpointState {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CheckpointState {}
+
+/// The error of a local cache at `path` that could not be loaded, with the size and
+/// checksum of what is on disk to tell it apart from a later cache.
+pub(super) fn corrupt_cache_error(path: &Path, reason: String) -> IndexError {
+    let data = std::fs::read(path).unwrap_or_default();
+    IndexError::CorruptIndexCache {
+        path: path.display().to_string(),
+        size: data.len(),
+        checksum: PadInfo::checksum(&data),
+        reason,
+    }
+}
 
 impl MasterIndex {
-    fn new_empty(network_choice: NetworkChoice) -> Self {
+    pub(super) fn new_empty(network_choice: NetworkChoice) -> Self {
         MasterIndex {
             index: Default::default(),
             free_pads: Vec::new(),
             pending_verification_pads: Vec::new(),
             network_choice,
+            bundles: Vec::new(),
+            bundled_keys: Default::default(),
+            spend: Default::default(),
+            restoring_keys: Default::default(),
+            final_chunk_padding: Default::default(),
+            envelopes: Default::default(),
+            transforms: Default::default(),
+            collections: Default::default(),
+            aliases: Default::default(),
+            trash: Default::default(),
+            restored_at: Default::default(),
+            modified_at: Default::default(),
+            integrity_proofs: Default::default(),
+            file_records: Default::default(),
+            chunk_permutations: Default::default(),
+            derived_keys: Default::default(),
+            pending_details: None,
+            checkpoint: Default::default(),
+            read_only: false,
+            corrupt_cache: None,
+            config: Arc::default(),
+            rng: InstanceRng::default(),
         }
     }
 
     pub fn new(network_choice: NetworkChoice) -> Self {
-        match MasterIndex::load(network_choice) {
-            Ok(index) => {
-                log::info!("Loaded master index from file for {:?}.", network_choice);
-                index
-            }
+        Self::open(network_choice, Arc::default()).0
+    }
+
+    /// Loads the local index cache of the application id of `config`, or creates an empty
+    /// index if there is none. The flag tells whether the index was created.
+    pub fn open(network_choice: NetworkChoice, config: Arc<MutAntConfig>) -> (Self, bool) {
+        Self::open_with(network_choice, config, MasterIndex::load)
+    }
+
+    /// [`Self::open`] reading the local index cache with `load`.
+    pub(super) fn open_with(
+        network_choice: NetworkChoice,
+        config: Arc<MutAntConfig>,
+        load: impl FnOnce(&Path, NetworkChoice) -> Result<Self, Error>,
+    ) -> (Self, bool) {
+        let (mut index, created) = Self::open_cache(network_choice, config.app_id.as_deref(), load);
+        index.config = config;
+        (index, created)
+    }
+
+    fn open_cache(
+        network_choice: NetworkChoice,
+        app_id: Option<&str>,
+        load: impl FnOnce(&Path, NetworkChoice) -> Result<Self, Error>,
+    ) -> (Self, bool) {
+        let path = match get_index_file_path(network_choice, app_id) {
+            Ok(path) => path,
             Err(e) => {
                 log::warn!(
-                    "Failed to load master index for {:?}, creating a new one. Error: {}",
+                    "No master index cache path for {:?}, creating a new index. Error: {}",
                     network_choice,
                     e
                 );
-                MasterIndex::new_empty(network_choice)
+                return (MasterIndex::new_empty(network_choice), true);
+            }
+        };
+
+        match load(&path, network_choice) {
+            Ok(index) => {
+                log::info!("Loaded master index from file for {:?}.", network_choice);
+                (index, false)
+            }
+            Err(Error::Index(IndexError::IndexFileNotFound(_))) => {
+                log::info!(
+                    "No master index for {:?} yet, creating a new one.",
+                    network_choice
+                );
+                (MasterIndex::new_empty(network_choice), true)
+            }
+            // An unreadable cache is not an empty one, saving over it would lose every key.
+            Err(e) => {
+                let corrupt = corrupt_cache_error(&path, e.to_string());
+                log::error!("{}", corrupt);
+                let mut index = MasterIndex::new_empty(network_choice);
+                index.corrupt_cache = Some(corrupt);
+                (index, false)
             }
         }
     }
 
-    fn load(network_choice: NetworkChoice) -> Result<Self, Error> {
-        let path = get_index_file_path(network_choice)?;
+    /// Decodes the remote index as a sync pushed it. Bytes that do not decode to a sound
+    /// index are a [`IndexError::CorruptRemoteIndex`], never an empty index that the next
+    /// push would write over the real one.
+    pub(crate) fn decode_remote(data: &[u8]) -> Result<Self, IndexError> {
+        let corrupt = |reason: String| IndexError::CorruptRemoteIndex {
+            size: data.len(),
+            checksum: PadInfo::checksum(data),
+            reason,
+        };
+        let index: MasterIndex =
+            serde_cbor::from_slice(data).map_err(|e| corrupt(e.to_string()))?;
+        index
+            .check_pad_sizes()
+            .map_err(|e| corrupt(e.to_string()))?;
+        Ok(index)
+    }
+
+    /// Loads the whole index from `path`, written with or without its summary.
+    pub(super) fn load(path: &Path, network_choice: NetworkChoice) -> Result<Self, Error> {
         if !path.exists() {
             return Err(Error::Index(IndexError::IndexFileNotFound(
                 path.display().to_string(),
             )));
         }
-        let file = File::open(&path).map_err(|_e| {
+        let file = File::open(path).map_err(|_e| {
             Error::Index(IndexError::IndexFileNotFound(path.display().to_string()))
         })?;
-        let reader = BufReader::new(file);
+        let mut reader = BufReader::new(file);
+        skip_summary(&mut reader)
+            .map_err(|e| Error::Index(IndexError::DeserializationError(e.to_string())))?;
         let index: MasterIndex = serde_cbor::from_reader(reader)
             .map_err(|e| Error::Index(IndexError::DeserializationError(e.to_string())))?;
 
@@ -53,19 +226,227 @@ impl MasterIndex {
                 y: index.network_choice,
             }));
         }
+        index.check_pad_sizes()?;
 
         Ok(index)
     }
 
-    pub fn save(&self, network_choice: NetworkChoice) -> Result<(), Error> {
-        let path = get_index_file_path(network_choice)?;
-        let file = File::create(&path).map_err(|_e| {
+    /// Fails on a pad holding more than a scratchpad can, or more padding than bytes,
+    /// which only a corrupted index has and which would throw the stats off.
+    pub(super) fn check_pad_sizes(&self) -> Result<(), IndexError> {
+        fn entry_pads(entry: &IndexEntry) -> Vec<&PadInfo> {
+            match entry {
+                IndexEntry::PrivateKey(pads) => pads.iter().collect(),
+                IndexEntry::PublicUpload(index_pad, pads) => {
+                    std::iter::once(index_pad).chain(pads).collect()
+                }
+            }
+        }
+
+        let owned = self
+            .index
+            .iter()
+            .map(|(key, entry)| (key.as_str(), entry_pads(entry)))
+            .chain(
+                self.trash
+                    .iter()
+                    .map(|(key, trashed)| (key.as_str(), entry_pads(&trashed.entry))),
+            )
+            .chain([
+                ("the free pads", self.free_pads.iter().collect()),
+                (
+                    "the pads to verify",
+                    self.pending_verification_pads.iter().collect(),
+                ),
+                (
+                    "a bundle",
+                    self.bundles.iter().map(|bundle| &bundle.pad).collect(),
+                ),
+            ]);
+
+        for (owner, pads) in owned {
+            if let Some(pad) = pads
+                .into_iter()
+                .find(|pad| pad.size > MAX_SCRATCHPAD_SIZE || pad.padding > pad.size)
+            {
+                return Err(IndexError::InvalidPadSize {
+                    owner: owner.to_string(),
+                    size: pad.size,
+                    padding: pad.padding,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the index to the local cache of `network_choice`. Returns whether it was
+    /// written, or why it was not.
+    pub fn save(&self, network_choice: NetworkChoice) -> Result<SaveOutcome, Error> {
+        if let Some(corrupt) = &self.corrupt_cache {
+            return Err(Error::Index(corrupt.clone()));
+        }
+        if self.checkpoint.deferred > 0 {
+            log::debug!("Master index save deferred until the end of the batch");
+            return Ok(SaveOutcome::Deferred);
+        }
+        // Any change loads the rest of the index first, one still on disk is unchanged.
+        if self.details_pending() {
+            log::debug!("Master index unchanged since its summary was loaded");
+            return Ok(SaveOutcome::DetailsPending);
+        }
+        #[cfg(test)]
+        SAVES_STARTED.with(|saves| saves.set(saves.get() + 1));
+        let path = get_index_file_path(network_choice, self.config.app_id.as_deref())?;
+        let sequence = snapshot_sequence(&path);
+        let snapshot = sequence.next();
+        self.write_snapshot(&path, &sequence, snapshot)
+    }
+
+    pub(super) fn write_snapshot(
+        &self,
+        path: &Path,
+        sequence: &SnapshotSequence,
+        snapshot: u64,
+    ) -> Result<SaveOutcome, Error> {
+        if self.read_only {
+            return Err(Error::ReadOnlyMode("saving the index".to_string()));
+        }
+
+        let mut written = sequence.written.lock().unwrap();
+        if *written > snapshot {
+            log::debug!("Skipping stale master index snapshot {}", snapshot);
+            return Ok(SaveOutcome::Superseded);
+        }
+
+        let file = File::create(path).map_err(|_e| {
             Error::Index(IndexError::IndexFileNotFound(path.display().to_string()))
         })?;
-        let writer = BufWriter::new(file);
-        serde_cbor::to_writer(writer, self)
-            .map_err(|e| Error::Index(IndexError::SerializationError(e.to_string())))?;
+        self.write_with_summary(BufWriter::new(file))?;
+        *written = snapshot;
         log::info!("Saved master index to {}", path.display());
+        Ok(SaveOutcome::Written)
+    }
+
+    /// Persists the index to the local cache file of its own network, see [`Self::save`].
+    pub fn flush(&self) -> Result<SaveOutcome, Error> {
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Reads its settings from `config` from now on, see [`MutAntConfig`].
+    pub(crate) fn set_config(&mut self, config: Arc<MutAntConfig>) {
+        self.config = config;
+    }
+
+    /// Draws the pads it generates and the chunk permutations from `rng` from now on.
+    pub(crate) fn set_rng(&mut self, rng: InstanceRng) {
+        self.rng = rng;
+    }
+
+    pub(crate) fn config(&self) -> &MutAntConfig {
+        &self.config
+    }
+
+    /// Holds back every save until the matching [`Self::resume_saves`], for the changes
+    /// of a batch to be written once. Calls nest.
+    pub(crate) fn defer_saves(&mut self) {
+        self.checkpoint.deferred += 1;
+    }
+
+    /// Ends a [`Self::defer_saves`], saving the index when the outermost one ends.
+    pub(crate) fn resume_saves(&mut self) -> Result<(), Error> {
+        self.checkpoint.deferred = self.checkpoint.deferred.saturating_sub(1);
+        if self.checkpoint.deferred > 0 {
+            return Ok(());
+        }
+        self.checkpoint = CheckpointState {
+            pending_transitions: 0,
+            last_save: Some(Instant::now()),
+            deferred: 0,
+        };
+        self.flush()?;
+        Ok(())
+    }
+
+    /// Records a pad status transition and saves the index once enough of them
+    /// accumulated or the oldest of them waited long enough.
+    ///
+    /// Inside a tokio runtime the serialization runs on a blocking task over a
+    /// snapshot of the index, so the write pipeline is never held up by disk I/O.
+    /// Callers must [`Self::save_transitions`] once their operation is over.
+    pub(crate) fn checkpoint(&mut self) -> Result<(), Error> {
+        // Counted from the first unsaved transition, the first one of an operation
+        // started long after the previous save does not trigger a save on its own.
+        if self.checkpoint.pending_transitions == 0 {
+            self.checkpoint.last_save = Some(Instant::now());
+        }
+        let last_save = *self.checkpoint.last_save.get_or_insert_with(Instant::now);
+        self.checkpoint.pending_transitions += 1;
+
+        if self.checkpoint.pending_transitions < self.config.checkpoint_every
+            && last_save.elapsed() < self.config.checkpoint_interval
+        {
+            return Ok(());
+        }
+
+        if self.checkpoint.deferred > 0 {
+            return Ok(());
+        }
+
+        self.checkpoint = CheckpointState {
+            pending_transitions: 0,
+            last_save: Some(Instant::now()),
+            deferred: 0,
+        };
+
+        #[cfg(test)]
+        SAVES_STARTED.with(|saves| saves.set(saves.get() + 1));
+        let path = get_index_file_path(self.network_choice, self.config.app_id.as_deref())?;
+        let sequence = snapshot_sequence(&path);
+        let snapshot = sequence.next();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let index = self.clone();
+                handle.spawn_blocking(move || {
+                    if let Err(e) = index.write_snapshot(&path, &sequence, snapshot) {
+                        log::warn!("Failed to checkpoint master index: {}", e);
+                    }
+                });
+                Ok(())
+            }
+            Err(_) => {
+                self.write_snapshot(&path, &sequence, snapshot)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Saves the pad status transitions not checkpointed yet, at the end of the
+    /// operation that made them. Nothing is written when there are none.
+    pub(crate) fn save_transitions(&mut self) -> Result<(), Error> {
+        if self.checkpoint.pending_transitions == 0 {
+            return Ok(());
+        }
+        if self.checkpoint.deferred > 0 {
+            self.save(self.network_choice)?;
+            return Ok(());
+        }
+        self.checkpoint.pending_transitions = 0;
+        self.checkpoint.last_save = Some(Instant::now());
+        self.flush()?;
         Ok(())
     }
+
+    #[cfg(test)]
+    pub(super) fn pending_transitions(&self) -> usize {
+        self.checkpoint.pending_transitions
+    }
+
+    /// Saves of any index started on the calling thread so far, for the tests to count
+    /// the saves of an operation.
+    #[cfg(test)]
+    pub(crate) fn saves_started() -> usize {
+        SAVES_STARTED.with(|saves| saves.get())
+    }
 }
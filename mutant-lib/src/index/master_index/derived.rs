@@ -0,0 +1,126 @@
+use crate::error::Error;
+use crate::index::error::IndexError;
+use crate::index::pad_info::PadInfo;
+use crate::index::PadStatus;
+use crate::ops::utils::derive_pad_secret_key;
+use autonomi::ScratchpadAddress;
+use blsttc::SecretKey;
+use log::info;
+use mutant_protocol::StorageMode;
+use std::ops::Range;
+
+use super::{IndexEntry, MasterIndex};
+
+impl MasterIndex {
+    /// Records that the pads of `key_names` are derived from the master key, the key name
+    /// and the chunk index from their next store on, see `MutAnt::precompute_key_addresses`.
+    pub fn register_derived_keys(&mut self, key_names: &[&str]) -> Result<(), Error> {
+        self.derived_keys
+            .extend(key_names.iter().map(|key_name| key_name.to_string()));
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Whether the pads of `key_name` are derived, see [`Self::register_derived_keys`].
+    pub fn is_derived_key(&self, key_name: &str) -> bool {
+        self.derived_keys.contains(key_name)
+    }
+
+    /// Creates the private key `key_name` on its derived pads, like [`Self::create_key`]
+    /// does on free or new pads.
+    pub fn create_derived_key(
+        &mut self,
+        key_name: &str,
+        data_bytes: &[u8],
+        mode: StorageMode,
+        master_secret: &SecretKey,
+    ) -> Result<(Vec<PadInfo>, Vec<Range<usize>>), Error> {
+        if self.index.contains_key(key_name) {
+            return Err(IndexError::KeyAlreadyExists(key_name.to_string()).into());
+        }
+
+        let chunk_ranges = self.chunk_data(data_bytes, mode);
+        let pads = self.derived_pads(key_name, data_bytes, &chunk_ranges, master_secret)?;
+
+        self.index
+            .insert(key_name.to_string(), IndexEntry::PrivateKey(pads.clone()));
+        self.stamp_final_chunk_padding(key_name);
+        self.mark_modified(key_name);
+        self.save(self.network_choice)?;
+
+        Ok((pads, chunk_ranges))
+    }
+
+    /// The derived pads of the `chunk_ranges` of `data_bytes` stored under `key_name`.
+    ///
+    /// A pad of the chunk that `key_name` holds and whose content did not change is reused
+    /// as is, one whose content changed is marked to be written again in place. The other
+    /// derived pads are taken out of the pad pool or created. A pad held by another key,
+    /// or by a key in the trash, fails the store with `DerivedPadInUse`.
+    pub(crate) fn derived_pads(
+        &mut self,
+        key_name: &str,
+        data_bytes: &[u8],
+        chunk_ranges: &[Range<usize>],
+        master_secret: &SecretKey,
+    ) -> Result<Vec<PadInfo>, Error> {
+        let held = self.get_pads(key_name);
+        let pad_keys = (0..chunk_ranges.len())
+            .map(|chunk_index| derive_pad_secret_key(master_secret, key_name, chunk_index))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        for pad_key in &pad_keys {
+            let address = ScratchpadAddress::new(pad_key.public_key());
+            let held_here = held.iter().any(|p| p.address == address);
+            if !held_here && !self.is_pooled(&address) && self.pad_exists(&address) {
+                return Err(IndexError::DerivedPadInUse {
+                    key: key_name.to_string(),
+                    address: address.to_hex(),
+                }
+                .into());
+            }
+        }
+
+        let mut pads = Vec::with_capacity(chunk_ranges.len());
+        for (chunk_index, (range, pad_key)) in chunk_ranges.iter().zip(&pad_keys).enumerate() {
+            let chunk = &data_bytes[range.clone()];
+            let mut pad = PadInfo::with_secret_key(pad_key, chunk, chunk_index);
+
+            if let Some(previous) = held.iter().find(|p| p.address == pad.address) {
+                if previous.checksum == pad.checksum && previous.size == pad.size {
+                    pads.push(previous.clone());
+                    continue;
+                }
+                if previous.status != PadStatus::Generated {
+                    pad = previous.clone().update_data(chunk, chunk_index);
+                    pad.status = PadStatus::Free;
+                }
+            } else if let Some(free) = self.take_free_pad(&pad.address) {
+                pad = free.update_data(chunk, chunk_index);
+                pad.status = PadStatus::Free;
+            } else {
+                // A pad awaiting verification has no known counter on the network, it is
+                // created like a new pad and updated if it exists.
+                self.pending_verification_pads
+                    .retain(|p| p.address != pad.address);
+            }
+            pads.push(pad);
+        }
+
+        info!("Derived {} pads for key {}", pads.len(), key_name);
+        Ok(pads)
+    }
+
+    fn is_pooled(&self, address: &ScratchpadAddress) -> bool {
+        self.free_pads
+            .iter()
+            .chain(&self.pending_verification_pads)
+            .any(|p| p.address == *address)
+    }
+
+    /// Takes the pad at `address` out of the free pads.
+    fn take_free_pad(&mut self, address: &ScratchpadAddress) -> Option<PadInfo> {
+        let position = self.free_pads.iter().position(|p| p.address == *address)?;
+        Some(self.free_pads.remove(position))
+    }
+}
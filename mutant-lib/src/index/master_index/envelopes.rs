@@ -0,0 +1,42 @@
+use crate::error::Error;
+
+use super::{IndexEntry, MasterIndex};
+
+impl MasterIndex {
+    /// Records that the value of `key_name` starts with an envelope header of
+    /// `header_len` bytes, `0` meaning it has none.
+    pub fn set_envelope_size(&mut self, key_name: &str, header_len: usize) -> Result<(), Error> {
+        let changed = if header_len > 0 {
+            self.envelopes.insert(key_name.to_string(), header_len) != Some(header_len)
+        } else {
+            self.envelopes.remove(key_name).is_some()
+        };
+        if !changed {
+            return Ok(());
+        }
+
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Length of the envelope header of `key_name`, `None` if it was stored without one.
+    pub fn envelope_size(&self, key_name: &str) -> Option<usize> {
+        self.envelopes.get(key_name).copied()
+    }
+
+    /// Size of the value of `key_name` as it was given to `put`, without the filler
+    /// of the last chunk, the envelope header nor what its transforms changed.
+    pub fn data_size(&self, key_name: &str) -> Option<usize> {
+        let stored: usize = match self.index.get(key_name) {
+            Some(_) if self.transforms.contains_key(key_name) => {
+                self.transforms.get(key_name)?.data_size
+            }
+            Some(IndexEntry::PrivateKey(pads)) | Some(IndexEntry::PublicUpload(_, pads)) => {
+                pads.iter().map(|p| p.size - p.padding).sum()
+            }
+            None => self.bundled_keys.get(key_name)?.len,
+        };
+
+        Some(stored.saturating_sub(self.envelope_size(key_name).unwrap_or(0)))
+    }
+}
@@ -0,0 +1,39 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::MasterIndex;
+
+/// What a key stored from a file looked like on disk, see `MutAnt::store_tree`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FileRecord {
+    pub size: u64,
+    /// Last modification time of the file, in milliseconds since the epoch.
+    pub modified_at_ms: u64,
+    /// SHA-256 of the content of the file.
+    pub sha256: [u8; 32],
+}
+
+impl MasterIndex {
+    pub fn set_file_record(&mut self, key_name: &str, record: FileRecord) -> Result<(), Error> {
+        if self.file_records.get(key_name) == Some(&record) {
+            return Ok(());
+        }
+        self.file_records.insert(key_name.to_string(), record);
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    pub fn get_file_record(&self, key_name: &str) -> Option<&FileRecord> {
+        self.file_records.get(key_name)
+    }
+
+    /// The file records of the keys starting with `prefix`.
+    pub fn file_records_under(&self, prefix: &str) -> BTreeMap<String, FileRecord> {
+        self.file_records
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, record)| (key.clone(), record.clone()))
+            .collect()
+    }
+}
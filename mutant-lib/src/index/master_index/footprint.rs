@@ -0,0 +1,141 @@
+use std::io::Write;
+use std::mem::size_of;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::index::error::IndexError;
+use crate::index::PadInfo;
+
+use super::{BundleSlot, IndexEntry, MasterIndex};
+
+/// Size of the master index, in memory and once serialized.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexFootprint {
+    pub keys: usize,
+    /// Pads of the keys plus the free and pending verification pads.
+    pub pads: usize,
+    /// Approximate heap and inline memory held by the index, dominated by the pads.
+    pub bytes_estimate: usize,
+    /// Size of the index as written to the local cache and the remote index.
+    pub serialized_bytes: usize,
+}
+
+/// Counts the bytes written to it, for dry serializations.
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn serialized_len<T: Serialize>(value: &T) -> Result<usize, Error> {
+    let mut counter = ByteCounter::default();
+    serde_cbor::to_writer(&mut counter, value)
+        .map_err(|e| Error::Index(IndexError::SerializationError(e.to_string())))?;
+    Ok(counter.0)
+}
+
+fn pad_bytes(pad: &PadInfo) -> usize {
+    size_of::<PadInfo>() + pad.sk_bytes.capacity()
+}
+
+impl MasterIndex {
+    /// Measures the index, serializing it without writing it anywhere.
+    pub fn memory_footprint(&self) -> Result<IndexFootprint, Error> {
+        Ok(self.footprint_with(serialized_len(self)?))
+    }
+
+    /// [`Self::memory_footprint`] of an index already known to serialize to
+    /// `serialized_bytes`.
+    pub(super) fn footprint_with(&self, serialized_bytes: usize) -> IndexFootprint {
+        let mut pads = self.free_pads.len() + self.pending_verification_pads.len();
+        let mut bytes_estimate = size_of::<MasterIndex>()
+            + self
+                .free_pads
+                .iter()
+                .chain(&self.pending_verification_pads)
+                .map(pad_bytes)
+                .sum::<usize>();
+
+        let trashed = self
+            .trash
+            .iter()
+            .map(|(key, trashed)| (key, &trashed.entry));
+        for (key, entry) in self.index.iter().chain(trashed) {
+            let entry_pads: Vec<&PadInfo> = match entry {
+                IndexEntry::PrivateKey(pads) => pads.iter().collect(),
+                IndexEntry::PublicUpload(index_pad, pads) => {
+                    std::iter::once(index_pad).chain(pads).collect()
+                }
+            };
+            pads += entry_pads.len();
+            bytes_estimate += key.capacity()
+                + size_of::<IndexEntry>()
+                + entry_pads.into_iter().map(pad_bytes).sum::<usize>();
+        }
+
+        pads += self.bundles.len();
+        bytes_estimate += self
+            .bundles
+            .iter()
+            .map(|bundle| pad_bytes(&bundle.pad))
+            .sum::<usize>()
+            + self
+                .bundled_keys
+                .keys()
+                .map(|key| key.capacity() + size_of::<BundleSlot>())
+                .sum::<usize>();
+
+        IndexFootprint {
+            keys: self.index.len() + self.bundled_keys.len(),
+            pads,
+            bytes_estimate,
+            serialized_bytes,
+        }
+    }
+
+    /// Fails if storing `chunks` pads under `key_name` would grow the serialized index
+    /// past the `max_index_serialized_bytes` of its config.
+    pub(crate) fn ensure_room_for(&self, key_name: &str, chunks: usize) -> Result<(), Error> {
+        match self.config.max_index_serialized_bytes {
+            Some(max) => self.check_room_for(key_name, chunks, max),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_room_for(
+        &self,
+        key_name: &str,
+        chunks: usize,
+        max: usize,
+    ) -> Result<(), Error> {
+        let is_new = !self.contains_key(key_name);
+        // Reused free pads move to the key without growing the index.
+        let new_pads = chunks
+            .saturating_sub(self.get_pads(key_name).len())
+            .saturating_sub(self.free_pads.len());
+        if new_pads == 0 && !is_new {
+            return Ok(());
+        }
+
+        let mut growth = new_pads * serialized_len(&PadInfo::new(&[], 0))?;
+        if is_new {
+            growth += serialized_len(&(key_name, IndexEntry::PrivateKey(Vec::new())))?;
+        }
+
+        let size = serialized_len(self)? + growth;
+        if size > max {
+            return Err(IndexError::IndexTooLarge { size, max }.into());
+        }
+
+        Ok(())
+    }
+}
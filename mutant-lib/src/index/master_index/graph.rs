@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{IndexEntry, MasterIndex};
+
+/// The kind of a key in the graph.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// A private key, only readable with the owner key.
+    Regular,
+    /// A public upload, readable by anyone through its index pad.
+    Public,
+}
+
+/// The kind of relationship between two keys.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeType {
+    /// Both keys reference the same scratchpad, which should never happen in a healthy index.
+    SharedPad,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyNode {
+    pub key: String,
+    pub key_type: KeyType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyEdge {
+    pub from: String,
+    pub to: String,
+    pub edge_type: EdgeType,
+}
+
+/// Relationships between the keys of the index, see `MutAnt::list_key_graph`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyGraph {
+    pub nodes: Vec<KeyNode>,
+    pub edges: Vec<KeyEdge>,
+}
+
+impl KeyGraph {
+    /// Renders the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph mutant {\n");
+
+        for node in &self.nodes {
+            let shape = match node.key_type {
+                KeyType::Regular => "box",
+                KeyType::Public => "ellipse",
+            };
+            out.push_str(&format!(
+                "    \"{}\" [shape={}];\n",
+                escape_dot(&node.key),
+                shape
+            ));
+        }
+
+        for edge in &self.edges {
+            let label = match edge.edge_type {
+                EdgeType::SharedPad => "shared_pad",
+            };
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                label
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl MasterIndex {
+    pub fn key_graph(&self) -> KeyGraph {
+        let mut graph = KeyGraph::default();
+        let mut pad_owners: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for (key, entry) in self.index.iter() {
+            let (key_type, pads) = match entry {
+                IndexEntry::PrivateKey(pads) => (KeyType::Regular, pads.iter().collect::<Vec<_>>()),
+                IndexEntry::PublicUpload(index_pad, pads) => (
+                    KeyType::Public,
+                    std::iter::once(index_pad).chain(pads.iter()).collect(),
+                ),
+            };
+
+            graph.nodes.push(KeyNode {
+                key: key.clone(),
+                key_type,
+            });
+
+            for pad in pads {
+                let owners = pad_owners.entry(pad.address.to_hex()).or_default();
+                if !owners.contains(key) {
+                    owners.push(key.clone());
+                }
+            }
+        }
+
+        for owners in pad_owners.values() {
+            for (i, from) in owners.iter().enumerate() {
+                for to in owners.iter().skip(i + 1) {
+                    let edge = KeyEdge {
+                        from: from.clone(),
+                        to: to.clone(),
+                        edge_type: EdgeType::SharedPad,
+                    };
+                    if !graph.edges.contains(&edge) {
+                        graph.edges.push(edge);
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+}
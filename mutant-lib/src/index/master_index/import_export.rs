@@ -7,7 +7,8 @@ use super::{IndexEntry, MasterIndex};
 impl MasterIndex {
     pub fn export_raw_pads_private_key(&self) -> Result<Vec<PadInfo>, Error> {
         let mut pads_hex = Vec::new();
-        for (_key, entry) in self.index.iter() {
+        let trashed = self.trash.values().map(|trashed| &trashed.entry);
+        for entry in self.index.values().chain(trashed) {
             match entry {
                 IndexEntry::PrivateKey(pads) => {
                     for pad in pads {
@@ -0,0 +1,73 @@
+use crate::error::Error;
+use log::info;
+
+use super::MasterIndex;
+
+/// What to do at init with the keys of the index whose store was interrupted, which
+/// cannot be fetched until they are stored again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IncompletePolicy {
+    /// Keep them as they are, storing one again resumes it (default).
+    #[default]
+    Ignore,
+    /// Remove them like `rm` does: the pads never written are freed, the written ones
+    /// await verification by a purge before being reused.
+    QueuePadsForVerification,
+    /// Move them to the trash, their pads stay reserved until the trash is reaped and
+    /// `restore` brings one back to resume it.
+    DropEntries,
+}
+
+impl IncompletePolicy {
+    pub fn from_env_value(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "ignore" => Some(IncompletePolicy::Ignore),
+            "verify" => Some(IncompletePolicy::QueuePadsForVerification),
+            "drop" => Some(IncompletePolicy::DropEntries),
+            _ => None,
+        }
+    }
+}
+
+impl MasterIndex {
+    /// The keys with pads not confirmed yet, whose store was interrupted or is running.
+    pub fn incomplete_keys(&self) -> Vec<String> {
+        if self.details_pending() {
+            return self
+                .summary_keys()
+                .into_values()
+                .filter(|details| details.confirmed_pads < details.pad_count)
+                .map(|details| details.key)
+                .collect();
+        }
+
+        self.index
+            .keys()
+            .filter(|key_name| !self.is_finished(key_name))
+            .cloned()
+            .collect()
+    }
+
+    /// Applies `policy` to the incomplete keys, returning them.
+    pub fn apply_incomplete_policy(
+        &mut self,
+        policy: IncompletePolicy,
+    ) -> Result<Vec<String>, Error> {
+        let keys = self.incomplete_keys();
+        if keys.is_empty() || policy == IncompletePolicy::Ignore {
+            return Ok(keys);
+        }
+        self.load_details()?;
+
+        for key_name in keys.iter() {
+            match policy {
+                IncompletePolicy::Ignore => {}
+                IncompletePolicy::QueuePadsForVerification => self.remove_key(key_name)?,
+                IncompletePolicy::DropEntries => self.trash_key(key_name)?,
+            }
+        }
+        info!("Applied {:?} to {} incomplete keys", policy, keys.len());
+
+        Ok(keys)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::error::Error;
+use autonomi::ScratchpadAddress;
+use blsttc::{PublicKey, SecretKey, Signature};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use super::MasterIndex;
+
+/// Signed statement that a key held a given value, in given pads, at a given time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IntegrityProof {
+    pub key: String,
+    /// SHA-256 of the stored value.
+    pub data_hash: [u8; 32],
+    /// Data pads of the key, in chunk order.
+    pub pad_addresses: Vec<ScratchpadAddress>,
+    /// BLS signature of `data_hash`, `pad_addresses` and `timestamp` by the owner key.
+    pub signature: Vec<u8>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl IntegrityProof {
+    pub fn new(
+        key: &str,
+        data: &[u8],
+        pad_addresses: Vec<ScratchpadAddress>,
+        owner: &SecretKey,
+    ) -> Self {
+        Self::new_at(key, data, pad_addresses, owner, Utc::now())
+    }
+
+    /// Like [`Self::new`], dated `timestamp` rather than now.
+    pub fn new_at(
+        key: &str,
+        data: &[u8],
+        pad_addresses: Vec<ScratchpadAddress>,
+        owner: &SecretKey,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        let mut proof = Self {
+            key: key.to_string(),
+            data_hash: Sha256::digest(data).into(),
+            pad_addresses,
+            signature: Vec::new(),
+            timestamp,
+        };
+        proof.signature = owner.sign(proof.signed_bytes()).to_bytes().to_vec();
+        proof
+    }
+
+    /// Checks that `data` and `pad_addresses` are the ones of the proof and that the
+    /// proof was signed by `owner`.
+    pub fn verify(
+        &self,
+        data: &[u8],
+        pad_addresses: &[ScratchpadAddress],
+        owner: &PublicKey,
+    ) -> bool {
+        if <[u8; 32]>::from(Sha256::digest(data)) != self.data_hash
+            || pad_addresses != self.pad_addresses.as_slice()
+        {
+            return false;
+        }
+
+        let signature = match <[u8; 96]>::try_from(self.signature.as_slice())
+            .ok()
+            .and_then(|bytes| Signature::from_bytes(bytes).ok())
+        {
+            Some(signature) => signature,
+            None => return false,
+        };
+
+        owner.verify(&signature, self.signed_bytes())
+    }
+
+    /// The proof of the same value and pads under `key`, signed anew by `owner` and
+    /// dated `timestamp`.
+    pub fn renamed(&self, key: &str, owner: &SecretKey, timestamp: DateTime<Utc>) -> Self {
+        let mut proof = Self {
+            key: key.to_string(),
+            signature: Vec::new(),
+            timestamp,
+            ..self.clone()
+        };
+        proof.signature = owner.sign(proof.signed_bytes()).to_bytes().to_vec();
+        proof
+    }
+
+    pub fn data_hash_hex(&self) -> String {
+        hex::encode(self.data_hash)
+    }
+
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.data_hash.to_vec();
+        for address in &self.pad_addresses {
+            bytes.extend_from_slice(&address.owner().to_bytes());
+        }
+        bytes.extend_from_slice(&self.timestamp.timestamp_micros().to_be_bytes());
+        bytes
+    }
+}
+
+impl MasterIndex {
+    /// Attaches `proof` to its key. A later update of the key makes it fail to verify
+    /// until a new proof is stored.
+    pub fn set_integrity_proof(&mut self, proof: IntegrityProof) -> Result<(), Error> {
+        self.integrity_proofs.insert(proof.key.clone(), proof);
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    pub fn get_integrity_proof(&self, key_name: &str) -> Option<&IntegrityProof> {
+        self.integrity_proofs.get(key_name)
+    }
+
+    pub fn list_integrity_proofs(&self) -> BTreeMap<String, IntegrityProof> {
+        self.integrity_proofs.clone()
+    }
+}
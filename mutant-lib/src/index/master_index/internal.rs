@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::index::error::IndexError;
+
+use super::key_management::redact_secret_keys;
+use super::{IndexEntry, MasterIndex};
+
+/// Prefix of the keys MutAnt keeps for itself in the index. They are left out of
+/// [`MasterIndex::list`] and cannot be stored or removed by users.
+pub const INTERNAL_KEY_PREFIX: &str = "__mutant__/";
+
+pub fn is_internal_key(key_name: &str) -> bool {
+    key_name.starts_with(INTERNAL_KEY_PREFIX)
+}
+
+/// The name under which an internal subsystem keeps `name` in the index.
+pub fn internal_key(name: &str) -> String {
+    format!("{}{}", INTERNAL_KEY_PREFIX, name)
+}
+
+/// Fails with `ReservedKey` for a key name given by a user in the internal namespace.
+pub fn ensure_user_key(key_name: &str) -> Result<(), Error> {
+    if is_internal_key(key_name) {
+        return Err(IndexError::ReservedKey(key_name.to_string()).into());
+    }
+    Ok(())
+}
+
+impl MasterIndex {
+    /// The internal keys, with the secret keys of their pads zeroed like [`Self::list`].
+    pub fn list_internal_keys(&self) -> BTreeMap<String, IndexEntry> {
+        self.index
+            .iter()
+            .filter(|(key_name, _)| is_internal_key(key_name))
+            .map(|(key_name, entry)| {
+                let mut entry = entry.clone();
+                redact_secret_keys(&mut entry);
+                (key_name.clone(), entry)
+            })
+            .collect()
+    }
+
+    /// Checks that every internal key is a private value whose chunks can be read back,
+    /// returning what is wrong with each of the others.
+    pub fn check_internal_keys(&self) -> Vec<(String, String)> {
+        let mut problems = Vec::new();
+        for (key_name, entry) in self.index.iter() {
+            if !is_internal_key(key_name) {
+                continue;
+            }
+            if matches!(entry, IndexEntry::PublicUpload(_, _)) {
+                problems.push((key_name.clone(), "stored as a public key".to_string()));
+            } else if let Err(e) = self.validate_chunk_indices(key_name) {
+                problems.push((key_name.clone(), e.to_string()));
+            } else if !self.is_finished(key_name) {
+                problems.push((key_name.clone(), "store not finished".to_string()));
+            }
+        }
+        problems
+    }
+}
@@ -1,11 +1,140 @@
+use crate::cache::KeyAccess;
 use crate::error::Error;
 use crate::index::error::IndexError;
-use crate::index::PadStatus;
+use crate::index::{PadInfo, PadStatus};
+use autonomi::ScratchpadAddress;
+use blsttc::SecretKey;
+use chrono::{DateTime, Utc};
 use log::{debug, info};
-use mutant_protocol::StorageMode;
+use mutant_protocol::{KeyDetails, StorageMode};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::ops::Range;
 
-use super::{IndexEntry, MasterIndex};
+use super::internal::is_internal_key;
+use super::selection::entry_addresses;
+use super::status::confirmed_usage;
+use super::{IndexEntry, IntegrityProof, MasterIndex};
+
+/// What the index knows about a single key, see [`MasterIndex::stat`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyStat {
+    /// The key the looked up name resolves to.
+    pub key: String,
+    /// The entry of the key, with the secret keys of its pads zeroed.
+    pub entry: IndexEntry,
+    pub aliases: Vec<String>,
+    pub integrity_proof: Option<IntegrityProof>,
+    /// When the key was last given a value or renamed, if the index recorded it.
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl KeyStat {
+    /// The listing details of the key, see [`key_details`].
+    pub fn details(self, access: Option<KeyAccess>) -> KeyDetails {
+        entry_details(
+            self.key,
+            &self.entry,
+            self.aliases,
+            self.integrity_proof,
+            self.modified_at,
+            access,
+        )
+    }
+}
+
+/// The listing details of a key of the index or of the trash, `access` being what
+/// the access tracker knows of it.
+pub fn key_details(
+    key: String,
+    entry: IndexEntry,
+    aliases: Vec<String>,
+    proof: Option<IntegrityProof>,
+    access: Option<KeyAccess>,
+) -> KeyDetails {
+    entry_details(key, &entry, aliases, proof, None, access)
+}
+
+/// [`key_details`] of an entry left in place, modified at the latest of `modified_at`
+/// and the time its integrity proof was signed.
+pub(super) fn entry_details(
+    key: String,
+    entry: &IndexEntry,
+    aliases: Vec<String>,
+    proof: Option<IntegrityProof>,
+    modified_at: Option<DateTime<Utc>>,
+    access: Option<KeyAccess>,
+) -> KeyDetails {
+    let (total_size, pad_count, confirmed_pads, public_address) = match entry {
+        IndexEntry::PrivateKey(pads) => {
+            let total_size = data_size(pads);
+            let pad_count = pads.len();
+            let confirmed_pads = pads
+                .iter()
+                .filter(|p| p.status == PadStatus::Confirmed)
+                .count();
+            (total_size, pad_count, confirmed_pads, None)
+        }
+        IndexEntry::PublicUpload(index_pad, pads) => {
+            let total_size = data_size(pads).saturating_add(index_pad.size);
+            let pad_count = pads.len() + 1; // +1 for index pad
+            let confirmed_data_pads = pads
+                .iter()
+                .filter(|p| p.status == PadStatus::Confirmed)
+                .count();
+            let index_pad_confirmed = if index_pad.status == PadStatus::Confirmed {
+                1
+            } else {
+                0
+            };
+            let confirmed_pads = confirmed_data_pads + index_pad_confirmed;
+            (
+                total_size,
+                pad_count,
+                confirmed_pads,
+                Some(index_pad.address.to_hex()),
+            )
+        }
+    };
+
+    let (confirmed_bytes, wasted_bytes) = confirmed_usage(entry);
+
+    KeyDetails {
+        key,
+        total_size,
+        pad_count,
+        confirmed_pads,
+        confirmed_bytes,
+        wasted_bytes,
+        is_public: public_address.is_some(),
+        public_address,
+        aliases,
+        trashed_at_ms: None,
+        is_finished: pad_count > 0 && confirmed_pads == pad_count,
+        completion_percentage: completion_percentage(confirmed_pads, pad_count),
+        checksum: proof.as_ref().map(IntegrityProof::data_hash_hex),
+        modified_at_ms: proof
+            .map(|proof| proof.timestamp)
+            .max(modified_at)
+            .map(|at| at.timestamp_millis() as u64),
+        last_fetched_ms: access.map(|access| access.last_fetched_ms),
+        fetch_count: access.map(|access| access.fetch_count),
+    }
+}
+
+/// The bytes of value held by `pads`, their padding left out.
+fn data_size(pads: &[PadInfo]) -> usize {
+    pads.iter()
+        .map(|p| p.size.saturating_sub(p.padding))
+        .fold(0, usize::saturating_add)
+}
+
+/// The whole percentage of `total` that `done` is, exact for any pad count.
+pub(super) fn completion_percentage(done: usize, total: usize) -> u8 {
+    if total == 0 {
+        return 0;
+    }
+    (done.min(total) as u128 * 100 / total as u128) as u8
+}
 
 impl MasterIndex {
     pub fn create_key(
@@ -36,6 +165,8 @@ impl MasterIndex {
             self.index
                 .insert(key_name.to_string(), IndexEntry::PrivateKey(pads.clone()));
         }
+        self.stamp_final_chunk_padding(key_name);
+        self.mark_modified(key_name);
 
         self.save(self.network_choice)?;
 
@@ -43,74 +174,225 @@ impl MasterIndex {
     }
 
     pub fn remove_key(&mut self, key_name: &str) -> Result<(), Error> {
-        // for each pad that has a status different than Generated, we update their status to Free
+        if let Some(entry) = self.index.remove(key_name) {
+            self.release_pads(key_name, entry);
+        }
+
+        self.restoring_keys.remove(key_name);
+        self.final_chunk_padding.remove(key_name);
+        self.envelopes.remove(key_name);
+        self.transforms.remove(key_name);
+        self.integrity_proofs.remove(key_name);
+        self.file_records.remove(key_name);
+        self.chunk_permutations.remove(key_name);
+        self.derived_keys.remove(key_name);
+        self.restored_at.remove(key_name);
+        self.modified_at.remove(key_name);
+        self.collections.values_mut().for_each(|keys| {
+            keys.remove(key_name);
+        });
+
+        self.save(self.network_choice)?;
+
+        info!("Removed key {}", key_name);
+
+        Ok(())
+    }
+
+    /// Hands the pads of a removed entry back to the pad pool.
+    ///
+    /// Pads never written, generated or reused free ones, are freed. Written and
+    /// confirmed pads are freed too when the key was complete. For an incomplete key,
+    /// whose store was interrupted, they await verification instead, as their counter
+    /// on the network is not known.
+    pub(super) fn release_pads(&mut self, key_name: &str, entry: IndexEntry) {
+        self.release_pads_except(key_name, entry, &HashSet::new());
+    }
+
+    /// Like [`Self::release_pads`], but leaves out the pads whose address is in `kept`,
+    /// which another entry still holds.
+    fn release_pads_except(
+        &mut self,
+        key_name: &str,
+        entry: IndexEntry,
+        kept: &HashSet<ScratchpadAddress>,
+    ) {
+        let (index_pad, pads) = match entry {
+            IndexEntry::PrivateKey(pads) => (None, pads),
+            IndexEntry::PublicUpload(index_pad, pads) => (Some(index_pad), pads),
+        };
+        let pads: Vec<_> = index_pad.into_iter().chain(pads).collect();
+        let complete = pads.iter().all(|p| p.status == PadStatus::Confirmed);
+
         let mut pads_to_free = Vec::new();
         let mut pads_to_verify = Vec::new();
-
-        if let Some(entry) = self.index.get_mut(key_name) {
-            if let IndexEntry::PrivateKey(pads) = entry {
-                pads.iter_mut().for_each(|p| {
-                    if p.status != PadStatus::Generated {
-                        p.status = PadStatus::Free;
-                        pads_to_free.push(p.clone());
-                    } else {
-                        pads_to_verify.push(p.clone());
-                    }
-                    p.checksum = 0;
-                    p.size = 0;
-                });
-            } else if let IndexEntry::PublicUpload(index, pads) = entry {
-                if index.status != PadStatus::Generated {
-                    index.status = PadStatus::Free;
-                    pads_to_free.push(index.clone());
-                } else {
-                    pads_to_verify.push(index.clone());
+        for mut pad in pads.into_iter().filter(|p| !kept.contains(&p.address)) {
+            match pad.status {
+                PadStatus::Written | PadStatus::Confirmed if !complete => pads_to_verify.push(pad),
+                _ => {
+                    pad.status = PadStatus::Free;
+                    pads_to_free.push(pad);
                 }
-                pads.iter_mut().for_each(|p| {
-                    if p.status != PadStatus::Generated {
-                        p.status = PadStatus::Free;
-                        pads_to_free.push(p.clone());
-                    } else {
-                        pads_to_verify.push(p.clone());
-                    }
-                    p.checksum = 0;
-                    p.size = 0;
-                });
             }
         }
 
-        debug!(
-            "Removing key {} with {} pads to free and {} pads to verify",
-            key_name,
-            pads_to_free.len(),
-            pads_to_verify.len()
-        );
+        if complete {
+            debug!(
+                "Removing key {} with {} pads to free",
+                key_name,
+                pads_to_free.len()
+            );
+        } else {
+            info!(
+                "Removing incomplete key {}: {} pads never written freed, {} written pads to verify",
+                key_name,
+                pads_to_free.len(),
+                pads_to_verify.len()
+            );
+        }
 
         self.free_pads.extend(pads_to_free);
         self.pending_verification_pads.extend(pads_to_verify);
+    }
 
-        self.index.remove(key_name);
+    /// Moves the entry of `old_key` to `new_key`, with everything the index records
+    /// about it. No pad is touched, only the index changes. An alias is renamed
+    /// alone, the aliases of a renamed key follow it. The integrity proof of the key
+    /// is signed again by `owner` with the time of the rename.
+    pub fn rename_key(
+        &mut self,
+        old_key: &str,
+        new_key: &str,
+        owner: &SecretKey,
+    ) -> Result<(), Error> {
+        if !self.contains_key(old_key) && !self.is_alias(old_key) {
+            return Err(IndexError::KeyNotFound(old_key.to_string()).into());
+        }
+        if self.contains_key(new_key) || self.is_alias(new_key) {
+            return Err(IndexError::KeyAlreadyExists(new_key.to_string()).into());
+        }
 
-        self.save(self.network_choice)?;
+        if let Some(canonical) = self.aliases.remove(old_key) {
+            self.aliases.insert(new_key.to_string(), canonical);
+            info!("Renamed alias {} to {}", old_key, new_key);
+            self.save(self.network_choice)?;
+            return Ok(());
+        }
 
-        info!("Removed key {}", key_name);
+        if let Some(entry) = self.index.remove(old_key) {
+            self.index.insert(new_key.to_string(), entry);
+        } else if let Some(slot) = self.bundled_keys.remove(old_key) {
+            self.bundled_keys.insert(new_key.to_string(), slot);
+        }
+        if let Some(expected_chunks) = self.restoring_keys.remove(old_key) {
+            self.restoring_keys
+                .insert(new_key.to_string(), expected_chunks);
+        }
+        if let Some(padding) = self.final_chunk_padding.remove(old_key) {
+            self.final_chunk_padding
+                .insert(new_key.to_string(), padding);
+        }
+        if let Some(header_len) = self.envelopes.remove(old_key) {
+            self.envelopes.insert(new_key.to_string(), header_len);
+        }
+        if let Some(transforms) = self.transforms.remove(old_key) {
+            self.transforms.insert(new_key.to_string(), transforms);
+        }
+        if let Some(record) = self.file_records.remove(old_key) {
+            self.file_records.insert(new_key.to_string(), record);
+        }
+        if let Some(permutation) = self.chunk_permutations.remove(old_key) {
+            self.chunk_permutations
+                .insert(new_key.to_string(), permutation);
+        }
+        // Its next store derives the pads from the new name.
+        if self.derived_keys.remove(old_key) {
+            self.derived_keys.insert(new_key.to_string());
+        }
+        if let Some(restored_at) = self.restored_at.remove(old_key) {
+            self.restored_at.insert(new_key.to_string(), restored_at);
+        }
+        self.modified_at.remove(old_key);
+        self.mark_modified(new_key);
+        if let Some(proof) = self.integrity_proofs.remove(old_key) {
+            let proof = proof.renamed(new_key, owner, self.config.clock.now());
+            self.integrity_proofs.insert(new_key.to_string(), proof);
+        }
+        for keys in self.collections.values_mut() {
+            if keys.remove(old_key) {
+                keys.insert(new_key.to_string());
+            }
+        }
+        for canonical in self.aliases.values_mut() {
+            if canonical == old_key {
+                *canonical = new_key.to_string();
+            }
+        }
 
+        info!("Renamed key {} to {}", old_key, new_key);
+        self.save(self.network_choice)?;
         Ok(())
     }
 
     pub fn contains_key(&self, key_name: &str) -> bool {
-        self.index.contains_key(key_name)
+        self.index.contains_key(key_name) || self.bundled_keys.contains_key(key_name)
     }
 
+    /// The data pads of `key_name`, numbered and ordered by chunk when the key was
+    /// stored with its chunks shuffled.
     pub fn get_pads(&self, key_name: &str) -> Vec<super::PadInfo> {
-        if let Some(entry) = self.index.get(key_name) {
+        let pads = if let Some(entry) = self.index.get(key_name) {
             match entry {
                 IndexEntry::PrivateKey(pads) => pads.clone(),
                 IndexEntry::PublicUpload(_index, pads) => pads.clone(),
             }
         } else {
             Vec::new()
+        };
+        self.to_chunk_order(key_name, pads)
+    }
+
+    /// Checks that the chunk indices of a key are exactly `0..n`, so the fetched chunks
+    /// can be reassembled in order.
+    pub fn validate_chunk_indices(&self, key_name: &str) -> Result<(), Error> {
+        let missing_indices = missing_chunk_indices(&self.get_pads(key_name));
+        if missing_indices.is_empty() {
+            return Ok(());
         }
+
+        Err(IndexError::IndexCorrupted {
+            key: key_name.to_string(),
+            missing_indices,
+        }
+        .into())
+    }
+
+    /// Shifts the chunk indices of a key back to `0..n` when they are contiguous but
+    /// do not start at 0. Returns whether the key got renumbered.
+    pub fn renumber_chunk_indices(&mut self, key_name: &str) -> Result<bool, Error> {
+        let pads = match self.index.get_mut(key_name) {
+            Some(IndexEntry::PrivateKey(pads)) => pads,
+            Some(IndexEntry::PublicUpload(_index, pads)) => pads,
+            None => return Err(IndexError::KeyNotFound(key_name.to_string()).into()),
+        };
+
+        let indices: BTreeSet<usize> = pads.iter().map(|p| p.chunk_index).collect();
+        let (Some(&first), Some(&last)) = (indices.first(), indices.last()) else {
+            return Ok(false);
+        };
+        if first == 0 || indices.len() != pads.len() || last - first + 1 != pads.len() {
+            return Ok(false);
+        }
+
+        pads.iter_mut().for_each(|p| p.chunk_index -= first);
+        info!(
+            "Renumbered chunk indices of key {} to start at 0 (was {})",
+            key_name, first
+        );
+
+        self.save(self.network_choice)?;
+
+        Ok(true)
     }
 
     pub fn get_entry(&self, key_name: &str) -> Option<&IndexEntry> {
@@ -119,6 +401,7 @@ impl MasterIndex {
 
     pub fn add_entry(&mut self, key_name: &str, entry: IndexEntry) -> Result<(), Error> {
         self.index.insert(key_name.to_string(), entry);
+        self.mark_modified(key_name);
         self.save(self.network_choice)?;
         Ok(())
     }
@@ -164,17 +447,181 @@ impl MasterIndex {
         })
     }
 
+    /// The keys of the user, see [`Self::list_internal_keys`] for the others.
     pub fn list(&self) -> std::collections::BTreeMap<String, IndexEntry> {
-        let mut keys = self.index.clone();
+        let mut keys: std::collections::BTreeMap<String, IndexEntry> = self
+            .index
+            .iter()
+            .filter(|(key_name, _)| !is_internal_key(key_name))
+            .map(|(key_name, entry)| (key_name.clone(), entry.clone()))
+            .collect();
         // put all the secret keys in the entries to 0
-        keys.iter_mut().for_each(|(_, entry)| {
-            if let IndexEntry::PrivateKey(pads) = entry {
-                pads.iter_mut().for_each(|p| p.sk_bytes = vec![0; 32]);
-            } else if let IndexEntry::PublicUpload(index, pads) = entry {
-                pads.iter_mut().for_each(|p| p.sk_bytes = vec![0; 32]);
-                index.sk_bytes = vec![0; 32];
-            }
-        });
+        keys.iter_mut()
+            .for_each(|(_, entry)| redact_secret_keys(entry));
         keys
     }
+
+    /// Looks `name` up, following aliases, without copying the rest of the index.
+    pub fn stat(&self, name: &str) -> Option<KeyStat> {
+        let key_name = self.resolve_alias(name);
+        let mut entry = self.index.get(key_name)?.clone();
+        redact_secret_keys(&mut entry);
+
+        Some(KeyStat {
+            key: key_name.to_string(),
+            entry,
+            aliases: self.aliases_of(key_name),
+            integrity_proof: self.get_integrity_proof(key_name).cloned(),
+            modified_at: self.modified_at.get(key_name).copied(),
+        })
+    }
+
+    /// Removes `key_name` the way a user removal does: an alias is unlinked, a key that
+    /// aliases still resolve to fails with `KeyHasAliases`, a bundled key leaves its slot
+    /// and, with `trash`, a key goes to the trash instead of freeing its pads. Removing a
+    /// key that is already gone succeeds.
+    ///
+    /// Returns the bundle the key was packed in, for its dead slot to be compacted.
+    pub fn remove_or_unlink(
+        &mut self,
+        key_name: &str,
+        trash: bool,
+    ) -> Result<Option<ScratchpadAddress>, Error> {
+        if self.is_alias(key_name) {
+            self.remove_alias(key_name)?;
+            return Ok(None);
+        }
+        self.ensure_no_aliases(key_name)?;
+
+        if self.is_bundled(key_name) {
+            return Ok(Some(self.remove_bundled_key(key_name)?.bundle_address));
+        }
+        if !trash {
+            self.remove_key(key_name)?;
+        } else if self.contains_key(key_name) {
+            self.trash_key(key_name)?;
+        }
+        Ok(None)
+    }
+
+    /// Gives `key_name` the value stored under `staged_key`, which disappears. The pads of
+    /// the value `key_name` had go back to the pad pool. Its aliases and collections stay,
+    /// its integrity proof and file record, which belonged to that value, are dropped.
+    ///
+    /// Returns the bundle the previous value was packed in, for its dead slot to be
+    /// compacted.
+    pub fn commit_staged_value(
+        &mut self,
+        staged_key: &str,
+        key_name: &str,
+    ) -> Result<Option<ScratchpadAddress>, Error> {
+        if !self.contains_key(staged_key) {
+            return Err(IndexError::KeyNotFound(staged_key.to_string()).into());
+        }
+
+        let previous_bundle = self
+            .bundled_keys
+            .remove(key_name)
+            .map(|slot| slot.bundle_address);
+        if let Some(previous) = self.index.remove(key_name) {
+            self.release_pads(key_name, previous);
+        }
+        self.restoring_keys.remove(key_name);
+        self.integrity_proofs.remove(key_name);
+        self.file_records.remove(key_name);
+
+        if let Some(entry) = self.index.remove(staged_key) {
+            self.index.insert(key_name.to_string(), entry);
+        } else if let Some(slot) = self.bundled_keys.remove(staged_key) {
+            self.bundled_keys.insert(key_name.to_string(), slot);
+        }
+        move_entry(&mut self.final_chunk_padding, staged_key, key_name);
+        move_entry(&mut self.envelopes, staged_key, key_name);
+        move_entry(&mut self.transforms, staged_key, key_name);
+        move_entry(&mut self.chunk_permutations, staged_key, key_name);
+        self.modified_at.remove(staged_key);
+        self.mark_modified(key_name);
+
+        info!("Committed staged value {} to {}", staged_key, key_name);
+        self.save(self.network_choice)?;
+
+        Ok(previous_bundle)
+    }
+
+    /// Gives `key_name` the pads an update staged under `staged_key`, which disappears.
+    /// The pads of the previous value that the staged one does not reuse go back to the
+    /// pad pool. Everything else the index records about the key stays.
+    pub fn commit_staged_pads(&mut self, staged_key: &str, key_name: &str) -> Result<(), Error> {
+        let entry = self
+            .index
+            .remove(staged_key)
+            .ok_or_else(|| IndexError::KeyNotFound(staged_key.to_string()))?;
+
+        let kept: HashSet<_> = entry_addresses(&entry).into_iter().collect();
+        if let Some(previous) = self.index.insert(key_name.to_string(), entry) {
+            self.release_pads_except(key_name, previous, &kept);
+        }
+        move_entry(&mut self.chunk_permutations, staged_key, key_name);
+        self.modified_at.remove(staged_key);
+        self.stamp_final_chunk_padding(key_name);
+        self.mark_modified(key_name);
+
+        info!(
+            "Committed the pads staged under {} to {}",
+            staged_key, key_name
+        );
+        self.save(self.network_choice)?;
+
+        Ok(())
+    }
+
+    /// Drops the pads an update of `key_name` staged under `staged_key`. The ones
+    /// `key_name` still holds stay with it, the others go back to the pad pool.
+    pub fn discard_staged_pads(&mut self, staged_key: &str, key_name: &str) -> Result<(), Error> {
+        if let Some(staged) = self.index.remove(staged_key) {
+            let kept: HashSet<_> = self
+                .index
+                .get(key_name)
+                .map(|entry| entry_addresses(entry).into_iter().collect())
+                .unwrap_or_default();
+            self.release_pads_except(staged_key, staged, &kept);
+        }
+        self.chunk_permutations.remove(staged_key);
+        self.modified_at.remove(staged_key);
+
+        debug!("Discarded the pads staged under {}", staged_key);
+        self.save(self.network_choice)?;
+
+        Ok(())
+    }
+
+    /// Records that `key_name` changed as of now, see [`KeyStat::modified_at`].
+    pub(super) fn mark_modified(&mut self, key_name: &str) {
+        self.modified_at
+            .insert(key_name.to_string(), self.config.clock.now());
+    }
+}
+
+/// Moves what `map` records under `from` to `to`, dropping what `to` had.
+fn move_entry<V>(map: &mut BTreeMap<String, V>, from: &str, to: &str) {
+    match map.remove(from) {
+        Some(value) => map.insert(to.to_string(), value),
+        None => map.remove(to),
+    };
+}
+
+/// The chunk indices in `0..pads.len()` that no pad holds.
+pub fn missing_chunk_indices(pads: &[super::PadInfo]) -> Vec<usize> {
+    let present: HashSet<usize> = pads.iter().map(|p| p.chunk_index).collect();
+    (0..pads.len()).filter(|i| !present.contains(i)).collect()
+}
+
+pub(super) fn redact_secret_keys(entry: &mut IndexEntry) {
+    match entry {
+        IndexEntry::PrivateKey(pads) => pads.iter_mut().for_each(|p| p.sk_bytes = vec![0; 32]),
+        IndexEntry::PublicUpload(index, pads) => {
+            pads.iter_mut().for_each(|p| p.sk_bytes = vec![0; 32]);
+            index.sk_bytes = vec![0; 32];
+        }
+    }
 }
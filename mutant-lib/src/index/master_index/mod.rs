@@ -1,15 +1,41 @@
-use crate::config::NetworkChoice;
+use crate::config::{MutAntConfig, NetworkChoice};
+use crate::index::error::IndexError;
 use crate::index::pad_info::PadInfo;
+use crate::rng::InstanceRng;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 
 // Re-export modules
+mod aliases;
+mod bundles;
+mod catalogue;
+mod collections;
 mod core;
+mod derived;
+mod envelopes;
+mod files;
+mod footprint;
+mod graph;
+mod incomplete;
 mod key_management;
 mod pad_management;
+mod permutation;
+mod prefix;
 mod status;
 mod public_keys;
+mod quota;
+mod reset;
+mod restore;
+mod selection;
+mod spend;
+mod summary;
+mod transforms;
+mod trash;
 mod import_export;
+mod integrity;
+mod internal;
 mod utils;
 
 #[cfg(test)]
@@ -17,6 +43,22 @@ mod tests;
 
 // Re-export utility functions
 pub use utils::get_index_file_path;
+pub use bundles::{decode_bundle, encode_bundle, Bundle, BundleOccupancy, BundleSlot};
+pub use catalogue::PublicCatalogue;
+pub use self::core::SaveOutcome;
+pub use files::FileRecord;
+pub use footprint::IndexFootprint;
+pub use graph::{EdgeType, KeyEdge, KeyGraph, KeyNode, KeyType};
+pub use incomplete::IncompletePolicy;
+pub use integrity::IntegrityProof;
+pub use internal::{ensure_user_key, internal_key, is_internal_key, INTERNAL_KEY_PREFIX};
+pub use key_management::{key_details, missing_chunk_indices, KeyStat};
+pub use quota::{Quota, QuotaResource};
+pub use restore::ScanRecovery;
+pub use spend::SpendReport;
+pub use summary::IndexSummary;
+pub use transforms::{KeyTransforms, TransformSpec};
+pub use trash::TrashedKey;
 
 /// Represents an entry in the master index, which can be either private key data or public upload data.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -40,13 +82,137 @@ pub struct MasterIndex {
     pending_verification_pads: Vec<PadInfo>,
 
     network_choice: NetworkChoice,
+
+    /// Pads shared by several small values, see the `bundles` feature.
+    /// Always (de)serialized so that an index written with the feature stays loadable without it.
+    #[serde(default)]
+    bundles: Vec<Bundle>,
+
+    /// Mapping from bundled key names to their slot in a bundle pad.
+    #[serde(default)]
+    bundled_keys: BTreeMap<String, BundleSlot>,
+
+    /// Running totals of what the pad writes cost.
+    #[serde(default)]
+    spend: SpendReport,
+
+    /// Keys being rebuilt pad by pad, with the number of chunks they are expected to have.
+    #[serde(default)]
+    restoring_keys: BTreeMap<String, usize>,
+
+    /// Filler bytes appended to the last chunk of keys stored with `MUTANT_PAD_FINAL_CHUNK`.
+    #[serde(default)]
+    final_chunk_padding: BTreeMap<String, usize>,
+
+    /// Named groups of keys, a key can belong to several of them.
+    #[serde(default)]
+    collections: BTreeMap<String, BTreeSet<String>>,
+
+    /// Alternative names of keys, alias to key.
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+
+    /// Removed keys kept until the trash retention expires, see `MUTANT_TRASH_RETENTION_SECS`.
+    #[serde(default)]
+    trash: BTreeMap<String, TrashedKey>,
+
+    /// When the keys restored from the trash were restored, so a sync can tell whether
+    /// a removal on another machine came before or after.
+    #[serde(default)]
+    restored_at: BTreeMap<String, DateTime<Utc>>,
+
+    /// When the keys were last renamed or given a value in their own pads.
+    #[serde(default)]
+    modified_at: BTreeMap<String, DateTime<Utc>>,
+
+    /// Integrity proofs of the keys stored with one.
+    #[serde(default)]
+    integrity_proofs: BTreeMap<String, IntegrityProof>,
+
+    /// Length of the envelope header in front of the keys stored with one.
+    #[serde(default)]
+    envelopes: BTreeMap<String, usize>,
+
+    /// Transforms the values of the keys stored through a transform pipeline went through.
+    #[serde(default)]
+    transforms: BTreeMap<String, KeyTransforms>,
+
+    /// Size, modification time and hash of the files the keys mirrored from a directory
+    /// were stored from.
+    #[serde(default)]
+    file_records: BTreeMap<String, FileRecord>,
+
+    /// Slot each chunk of the keys stored with `MUTANT_SHUFFLE_PADS` is recorded under,
+    /// indexed by chunk.
+    #[serde(default)]
+    chunk_permutations: BTreeMap<String, Vec<usize>>,
+
+    /// Keys whose pads are derived from the master key, their name and their chunk index,
+    /// see `MutAnt::precompute_key_addresses`.
+    #[serde(default)]
+    derived_keys: BTreeSet<String>,
+
+    /// The summary the index was opened from while the rest of it is still on disk,
+    /// see [`MasterIndex::open_summary`].
+    #[serde(skip)]
+    pending_details: Option<summary::PendingDetails>,
+
+    /// Pad status transitions not yet saved to disk.
+    #[serde(skip)]
+    checkpoint: self::core::CheckpointState,
+
+    /// Loaded from a public catalogue, never saved.
+    #[serde(skip)]
+    read_only: bool,
+
+    /// The local cache this index was opened from could not be read. It is never saved
+    /// over until [`MasterIndex::reset`] starts the index afresh.
+    #[serde(skip)]
+    corrupt_cache: Option<IndexError>,
+
+    /// Settings of the instance the index belongs to, the timestamps of the proofs and of
+    /// the trash are taken from its clock.
+    #[serde(skip)]
+    config: Arc<MutAntConfig>,
+
+    /// Random source of the instance the index belongs to, the pads it generates and
+    /// the chunk permutations are drawn from it.
+    #[serde(skip)]
+    rng: InstanceRng,
 }
 
-#[derive(Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
 pub struct StorageStats {
     pub nb_keys: u64,
     pub total_pads: u64,
     pub occupied_pads: u64,
     pub free_pads: u64,
     pub pending_verification_pads: u64,
+    pub bundle_pads: u64,
+    pub bundled_keys: u64,
+    pub bundle_live_bytes: u64,
+    pub bundle_capacity_bytes: u64,
+    /// Pads of the keys in the trash, neither occupied nor free.
+    pub trash_pads: u64,
+    pub trashed_keys: u64,
+    /// Bytes held by the pads of the keys in the trash, part of `stored_bytes`.
+    pub trash_bytes: u64,
+    /// Bytes held by the pads of the keys, the bundles and the trash.
+    pub stored_bytes: u64,
+    /// Bytes of the values of the keys held by their confirmed pads, the filler of
+    /// `MUTANT_PAD_FINAL_CHUNK` left out. Defaulted for the summaries written before it.
+    #[serde(default)]
+    pub confirmed_bytes: u64,
+    /// Space of the confirmed pads of the keys that holds none of their values.
+    #[serde(default)]
+    pub wasted_bytes: u64,
+}
+
+impl StorageStats {
+    /// Pads holding data, of the keys, the bundles and the trash.
+    pub fn used_pads(&self) -> u64 {
+        self.occupied_pads
+            .saturating_add(self.bundle_pads)
+            .saturating_add(self.trash_pads)
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{error::Error, index::PadStatus};
 use crate::index::error::IndexError;
 use crate::index::pad_info::PadInfo;
+use crate::ops::chunking::ChunkRanges;
 use crate::storage::ScratchpadAddress;
 use mutant_protocol::StorageMode;
 use std::ops::Range;
@@ -9,17 +10,7 @@ use super::{IndexEntry, MasterIndex};
 
 impl MasterIndex {
     pub fn chunk_data(&self, data_bytes: &[u8], mode: StorageMode) -> Vec<Range<usize>> {
-        let pad_size = mode.scratchpad_size();
-        let mut ranges = Vec::new();
-        let mut current_pos = 0;
-
-        while current_pos < data_bytes.len() {
-            let end = std::cmp::min(current_pos + pad_size, data_bytes.len());
-            ranges.push(current_pos..end);
-            current_pos = end;
-        }
-
-        ranges
+        ChunkRanges::new(data_bytes.len(), mode.scratchpad_size()).collect()
     }
 
     pub fn acquire_pads(
@@ -60,6 +51,27 @@ impl MasterIndex {
         Ok(generated_pads)
     }
 
+    /// Takes a pad for chunk `chunk_index` of a value streamed into `key_name`, failing
+    /// like a whole store would when the value, `content_len` bytes so far, goes over the
+    /// quota or makes the index too large.
+    pub(crate) fn acquire_streamed_pad(
+        &mut self,
+        key_name: &str,
+        chunk: &[u8],
+        chunk_index: usize,
+        content_len: usize,
+    ) -> Result<PadInfo, Error> {
+        self.ensure_room_for(key_name, chunk_index + 1)?;
+        self.ensure_quota_for(key_name, content_len, chunk_index + 1)?;
+
+        let range = 0..chunk.len();
+        let mut pad = self
+            .generate_pads(chunk, std::iter::once(&range))?
+            .remove(0);
+        pad.chunk_index = chunk_index;
+        Ok(pad)
+    }
+
     /// Internal helper function to acquire a specified number of pads,
     /// prioritizing generation of new pads before using free ones.
     pub(crate) fn _acquire_pads_internal(&mut self, num_pads_needed: usize) -> Result<Vec<PadInfo>, Error> {
@@ -83,7 +95,7 @@ impl MasterIndex {
         let mut generated_new_pads = Vec::with_capacity(pads_to_generate);
         for _ in 0..pads_to_generate {
             // Create a dummy PadInfo; size and checksum will be set later
-            generated_new_pads.push(PadInfo::new(&[], 0));
+            generated_new_pads.push(PadInfo::with_secret_key(&self.rng.secret_key(), &[], 0));
         }
 
         // Combine taken and generated pads (Free first, then New)
@@ -117,7 +129,8 @@ impl MasterIndex {
             return Err(Error::Index(IndexError::KeyNotFound(key_name.to_string())));
         }
 
-        // Update the key with the new pads
+        // Update the key with the new pads, under their slot if its chunks are shuffled
+        let pads = self.slot_ordered(key_name, pads);
         if let Some(index_pad) = index_pad {
             // For public keys, update with the index pad
             self.index.insert(
@@ -128,6 +141,8 @@ impl MasterIndex {
             // For private keys, just update with the new pads
             self.index.insert(key_name.to_string(), IndexEntry::PrivateKey(pads));
         }
+        self.stamp_final_chunk_padding(key_name);
+        self.mark_modified(key_name);
 
         // Save the updated index
         self.save(self.network_choice)?;
@@ -140,10 +155,18 @@ impl MasterIndex {
         key_name: &str,
         pad_address: &ScratchpadAddress,
     ) -> Result<PadInfo, Error> {
+        // The pad of each chunk of a derived key is fixed by its name, see `derived_keys`
+        if self.is_derived_key(key_name) {
+            return Err(Error::Internal(format!(
+                "Cannot move a chunk of key {} off its derived pad {}",
+                key_name, pad_address
+            )));
+        }
+
         let mut new_pad = if self.free_pads.is_empty() {
             // If no free pads, generate a new one temporarily.
             // The actual data/checksum doesn't matter here as it will be overwritten.
-            PadInfo::new(&[0u8; 1], 0)
+            PadInfo::with_secret_key(&self.rng.secret_key(), &[0u8; 1], 0)
         } else {
             // Use a pad from the free list
             self.free_pads.pop().unwrap()
@@ -231,22 +254,65 @@ impl MasterIndex {
             .chain(self.pending_verification_pads.iter())
             .any(|p| p.address == *pad_address);
 
-        let index_exists = self.index.iter().any(|(_, entry)| match entry {
-            super::IndexEntry::PrivateKey(pads) => pads.iter().any(|p| p.address == *pad_address),
-            super::IndexEntry::PublicUpload(index_pad, pads) => {
+        let entry_has_pad = |entry: &IndexEntry| match entry {
+            IndexEntry::PrivateKey(pads) => pads.iter().any(|p| p.address == *pad_address),
+            IndexEntry::PublicUpload(index_pad, pads) => {
                 index_pad.address == *pad_address || pads.iter().any(|p| p.address == *pad_address)
             }
-        });
+        };
+        let index_exists = self.index.values().any(entry_has_pad);
+        let trash_exists = self
+            .trash
+            .values()
+            .any(|trashed| entry_has_pad(&trashed.entry));
 
-        address_exists || index_exists
+        address_exists || index_exists || trash_exists
     }
 
     /// Helper function to update a pad's properties based on another pad
     /// This is used when recycling pads or updating pad information
+    /// Records how many filler bytes the last chunk of `key_name` carries, see
+    /// `MUTANT_PAD_FINAL_CHUNK`, and saves the index.
+    pub(crate) fn set_final_chunk_padding(
+        &mut self,
+        key_name: &str,
+        padding: usize,
+    ) -> Result<(), Error> {
+        if padding > 0 {
+            self.final_chunk_padding.insert(key_name.to_string(), padding);
+        } else {
+            self.final_chunk_padding.remove(key_name);
+        }
+        self.stamp_final_chunk_padding(key_name);
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Copies the recorded padding of `key_name` onto its last data pad, the others
+    /// carrying none.
+    pub(super) fn stamp_final_chunk_padding(&mut self, key_name: &str) {
+        let padding = self.final_chunk_padding.get(key_name).copied().unwrap_or(0);
+        let pads = match self.index.get_mut(key_name) {
+            Some(IndexEntry::PrivateKey(pads)) => pads,
+            Some(IndexEntry::PublicUpload(_, pads)) => pads,
+            None => return,
+        };
+
+        let last_chunk = pads.iter().map(|p| p.chunk_index).max();
+        pads.iter_mut().for_each(|p| {
+            p.padding = if Some(p.chunk_index) == last_chunk {
+                padding
+            } else {
+                0
+            };
+        });
+    }
+
     pub(crate) fn update_pad_properties(target_pad: &mut PadInfo, source_pad: &PadInfo) {
         target_pad.checksum = source_pad.checksum;
         target_pad.size = source_pad.size;
         target_pad.chunk_index = source_pad.chunk_index;
+        target_pad.padding = source_pad.padding;
     }
 
     pub(crate) fn free_pads(&mut self, pads: Vec<PadInfo>) -> Result<(), Error> {
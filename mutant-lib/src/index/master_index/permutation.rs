@@ -0,0 +1,135 @@
+use crate::error::Error;
+use crate::index::pad_info::PadInfo;
+use crate::rng::InstanceRng;
+use log::warn;
+use rand::seq::SliceRandom;
+use std::ops::Range;
+
+use super::{IndexEntry, MasterIndex};
+
+/// A random permutation of `0..len` drawn from `rng`.
+fn random_permutation(len: usize, rng: &InstanceRng) -> Vec<usize> {
+    let mut permutation: Vec<usize> = (0..len).collect();
+    rng.with_rng(|rng| permutation.shuffle(rng));
+    permutation
+}
+
+/// Sets the chunk index of each pad to `map[chunk_index]` and orders them by it.
+fn remap(mut pads: Vec<PadInfo>, map: &[usize]) -> Vec<PadInfo> {
+    for pad in &mut pads {
+        match map.get(pad.chunk_index) {
+            Some(&mapped) => pad.chunk_index = mapped,
+            None => warn!(
+                "Chunk index {} of pad {} is out of the permutation",
+                pad.chunk_index, pad.address
+            ),
+        }
+    }
+    pads.sort_by_key(|pad| pad.chunk_index);
+    pads
+}
+
+fn inverse(permutation: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; permutation.len()];
+    for (chunk, &slot) in permutation.iter().enumerate() {
+        inverse[slot] = chunk;
+    }
+    inverse
+}
+
+impl MasterIndex {
+    /// Records the pads of `key_name` under a random slot each instead of their chunk
+    /// index, see `MUTANT_SHUFFLE_PADS`. [`Self::get_pads`] still returns them in chunk
+    /// order and the writes of [`Self::to_slot_order`] pads land on the right chunk.
+    pub fn shuffle_chunks(&mut self, key_name: &str) -> Result<(), Error> {
+        let pads = self.get_pads(key_name);
+        if pads.len() < 2 {
+            return Ok(());
+        }
+        self.chunk_permutations.insert(
+            key_name.to_string(),
+            random_permutation(pads.len(), &self.rng),
+        );
+        let pads = self.slot_ordered(key_name, pads);
+        match self.index.get_mut(key_name) {
+            Some(IndexEntry::PrivateKey(stored)) => *stored = pads,
+            Some(IndexEntry::PublicUpload(_, stored)) => *stored = pads,
+            None => {}
+        }
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// The slot each chunk of `key_name` is recorded under, `None` for the keys stored
+    /// in order.
+    pub fn chunk_permutation(&self, key_name: &str) -> Option<&[usize]> {
+        self.chunk_permutations.get(key_name).map(Vec::as_slice)
+    }
+
+    /// `pads` of `key_name` numbered by slot instead of by chunk.
+    pub(crate) fn to_slot_order(&self, key_name: &str, pads: Vec<PadInfo>) -> Vec<PadInfo> {
+        match self.chunk_permutations.get(key_name) {
+            Some(permutation) => remap(pads, permutation),
+            None => pads,
+        }
+    }
+
+    /// `chunk_ranges` of `key_name` ordered by slot, so the range of a pad numbered by
+    /// [`Self::to_slot_order`] is at its chunk index.
+    pub(crate) fn ranges_in_slot_order(
+        &self,
+        key_name: &str,
+        chunk_ranges: Vec<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        match self.chunk_permutations.get(key_name) {
+            Some(permutation) if permutation.len() == chunk_ranges.len() => inverse(permutation)
+                .into_iter()
+                .map(|chunk| chunk_ranges[chunk].clone())
+                .collect(),
+            _ => chunk_ranges,
+        }
+    }
+
+    /// `pads` of `key_name`, as recorded, numbered and ordered by chunk.
+    pub(super) fn to_chunk_order(&self, key_name: &str, pads: Vec<PadInfo>) -> Vec<PadInfo> {
+        match self.chunk_permutations.get(key_name) {
+            Some(permutation) => remap(pads, &inverse(permutation)),
+            None => pads,
+        }
+    }
+
+    /// All the `pads` of `key_name`, numbered by chunk, in the slot order they are
+    /// recorded in. A key whose number of chunks changed gets a new permutation: the
+    /// slots only exist in the index, so nothing has to be written again.
+    pub(super) fn slot_ordered(&mut self, key_name: &str, pads: Vec<PadInfo>) -> Vec<PadInfo> {
+        match self.chunk_permutations.get(key_name) {
+            Some(permutation) if permutation.len() != pads.len() => {
+                self.chunk_permutations.insert(
+                    key_name.to_string(),
+                    random_permutation(pads.len(), &self.rng),
+                );
+            }
+            Some(_) => {}
+            None => return pads,
+        }
+        self.to_slot_order(key_name, pads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remap_round_trip() {
+        let permutation = random_permutation(16, &InstanceRng::default());
+        let pads: Vec<PadInfo> = (0..16).map(|i| PadInfo::new(&[i as u8], i)).collect();
+
+        let in_slots = remap(pads.clone(), &permutation);
+        assert!(in_slots
+            .iter()
+            .enumerate()
+            .all(|(i, pad)| pad.chunk_index == i));
+        assert_eq!(remap(in_slots, &inverse(&permutation)), pads);
+    }
+}
@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use super::MasterIndex;
+
+/// The entries of `map` whose key starts with `prefix`, scanned from the first key not
+/// before it since the keys are sorted. Every entry for an empty prefix.
+pub(super) fn with_prefix<'a, V>(
+    map: &'a BTreeMap<String, V>,
+    prefix: &'a str,
+) -> impl Iterator<Item = (&'a String, &'a V)> {
+    map.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(move |(key, _)| key.starts_with(prefix))
+}
+
+impl MasterIndex {
+    /// The keys starting with `prefix`, internal ones included, in order. A key holding
+    /// `prefix` anywhere but at its start does not match. Read from the summary while
+    /// the pad lists are on disk.
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        if self.details_pending() {
+            return self.summary_keys_with_prefix(prefix).into_keys().collect();
+        }
+
+        with_prefix(&self.index, prefix)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
@@ -17,8 +17,11 @@ impl MasterIndex {
         None
     }
 
+    /// Fills the index pad of a public key with the pad list of its chunks, returning
+    /// the pad and its content. The index is not saved here, the write of the index pad
+    /// that always follows saves it.
     pub fn populate_index_pad(&mut self, key_name: &str) -> Result<(PadInfo, Vec<u8>), Error> {
-        let res = match self.index.get_mut(key_name) {
+        match self.index.get_mut(key_name) {
             Some(IndexEntry::PublicUpload(index_pad, pads)) => {
                 let index_data = serde_cbor::to_vec(&pads).unwrap();
 
@@ -28,10 +31,6 @@ impl MasterIndex {
                 Ok((index_pad.clone(), index_data))
             }
             _ => Err(IndexError::KeyNotFound(key_name.to_string()).into()),
-        };
-
-        self.save(self.network_choice)?;
-
-        res
+        }
     }
 }
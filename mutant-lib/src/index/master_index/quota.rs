@@ -0,0 +1,103 @@
+use std::fmt;
+
+use crate::error::Error;
+
+use super::MasterIndex;
+
+/// Caps what can be stored with a wallet. Stores that would go over a limit fail
+/// before any pad is acquired.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Quota {
+    /// Pads holding data, of the keys, the bundles and the trash
+    /// (`MUTANT_MAX_TOTAL_PADS`).
+    pub max_total_pads: Option<usize>,
+    /// Bytes held by those pads (`MUTANT_MAX_TOTAL_BYTES`).
+    pub max_total_bytes: Option<u64>,
+    /// Free pads are already paid for, taking them does not count against
+    /// `max_total_pads` (`MUTANT_QUOTA_EXEMPT_FREE_PADS`).
+    pub exempt_free_pads: bool,
+}
+
+impl Quota {
+    pub fn is_set(&self) -> bool {
+        self.max_total_pads.is_some() || self.max_total_bytes.is_some()
+    }
+}
+
+/// What a [`Quota`] limit counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaResource {
+    Pads,
+    Bytes,
+}
+
+impl fmt::Display for QuotaResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaResource::Pads => write!(f, "pads"),
+            QuotaResource::Bytes => write!(f, "bytes"),
+        }
+    }
+}
+
+impl MasterIndex {
+    /// Fails if storing `content_len` bytes in `chunks` pads under `key_name` would go
+    /// over the quota of its config, see [`Quota`].
+    pub(crate) fn ensure_quota_for(
+        &self,
+        key_name: &str,
+        content_len: usize,
+        chunks: usize,
+    ) -> Result<(), Error> {
+        let quota = &self.config.quota;
+        if !quota.is_set() {
+            return Ok(());
+        }
+        self.check_quota_for(key_name, content_len, chunks, quota)
+    }
+
+    pub(crate) fn check_quota_for(
+        &self,
+        key_name: &str,
+        content_len: usize,
+        chunks: usize,
+        quota: &Quota,
+    ) -> Result<(), Error> {
+        let stats = self.get_storage_stats();
+        let (used_pads, used_bytes) = (stats.used_pads(), stats.stored_bytes);
+
+        if let Some(limit) = quota.max_total_pads {
+            // A key being rewritten keeps its pads.
+            let mut requested = chunks.saturating_sub(self.get_pads(key_name).len());
+            if quota.exempt_free_pads {
+                requested = requested.saturating_sub(self.free_pads.len());
+            }
+            if requested > 0 && used_pads + requested as u64 > limit as u64 {
+                return Err(Error::QuotaExceeded {
+                    resource: QuotaResource::Pads,
+                    limit: limit as u64,
+                    current: used_pads,
+                    requested: requested as u64,
+                });
+            }
+        }
+
+        if let Some(limit) = quota.max_total_bytes {
+            let previous_len: usize = match self.bundled_keys.get(key_name) {
+                Some(slot) => slot.len,
+                None => self.get_pads(key_name).iter().map(|p| p.size).sum(),
+            };
+            let requested = content_len.saturating_sub(previous_len) as u64;
+            if requested > 0 && used_bytes + requested > limit {
+                return Err(Error::QuotaExceeded {
+                    resource: QuotaResource::Bytes,
+                    limit,
+                    current: used_bytes,
+                    requested,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
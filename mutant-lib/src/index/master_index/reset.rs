@@ -0,0 +1,75 @@
+use crate::error::Error;
+use log::info;
+use mutant_protocol::{ResetMode, ResetSummary};
+
+use super::{IndexEntry, MasterIndex};
+
+impl MasterIndex {
+    /// What [`Self::reset`] drops: every key, the ones in the trash and the bundled ones
+    /// included, and the pads holding them.
+    pub fn reset_summary(&self) -> ResetSummary {
+        let entry_pads = |entry: &IndexEntry| match entry {
+            IndexEntry::PrivateKey(pads) => pads.len(),
+            IndexEntry::PublicUpload(_, pads) => pads.len() + 1,
+        };
+
+        ResetSummary {
+            key_count: self.index.len() + self.trash.len() + self.bundled_keys.len(),
+            pad_count: self.index.values().map(entry_pads).sum::<usize>()
+                + self
+                    .trash
+                    .values()
+                    .map(|trashed| entry_pads(&trashed.entry))
+                    .sum::<usize>()
+                + self.bundles.len(),
+        }
+    }
+
+    /// Drops every key of the index, leaving it as if nothing had been stored.
+    ///
+    /// With [`ResetMode::Harvest`] the pads of the keys go back to the pad pool like a
+    /// removal hands them back, with [`ResetMode::Abandon`] they are forgotten. The pads
+    /// already free or awaiting verification are kept either way.
+    pub fn reset(&mut self, mode: ResetMode) -> Result<ResetSummary, Error> {
+        let summary = self.reset_summary();
+
+        let entries: Vec<(String, IndexEntry)> = std::mem::take(&mut self.index)
+            .into_iter()
+            .chain(
+                std::mem::take(&mut self.trash)
+                    .into_iter()
+                    .map(|(key, trashed)| (key, trashed.entry)),
+            )
+            .collect();
+        let bundles = std::mem::take(&mut self.bundles);
+
+        *self = MasterIndex {
+            free_pads: std::mem::take(&mut self.free_pads),
+            pending_verification_pads: std::mem::take(&mut self.pending_verification_pads),
+            spend: std::mem::take(&mut self.spend),
+            checkpoint: std::mem::take(&mut self.checkpoint),
+            read_only: self.read_only,
+            config: self.config.clone(),
+            rng: self.rng.clone(),
+            ..MasterIndex::new_empty(self.network_choice)
+        };
+
+        if mode == ResetMode::Harvest {
+            for (key, entry) in entries {
+                self.release_pads(&key, entry);
+            }
+            for bundle in bundles {
+                self.release_pads("bundle", IndexEntry::PrivateKey(vec![bundle.pad]));
+            }
+        }
+
+        self.save(self.network_choice)?;
+
+        info!(
+            "Reset the index ({:?}): {} keys and {} pads dropped",
+            mode, summary.key_count, summary.pad_count
+        );
+
+        Ok(summary)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::Error;
+use crate::index::error::IndexError;
+use crate::index::pad_info::PadInfo;
+use crate::index::PadStatus;
+use log::{debug, info};
+
+use super::{IndexEntry, MasterIndex};
+
+/// Outcome of a rebuild of the index from the pads found at derived addresses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanRecovery {
+    /// Keys put back in the index, with their number of chunks.
+    pub recovered: Vec<(String, usize)>,
+    /// Keys with no pad at their first derived address.
+    pub missing: Vec<String>,
+    /// Keys left alone because the index already has them.
+    pub skipped: Vec<String>,
+}
+
+impl MasterIndex {
+    /// Registers one pad of a key being restored from individually known pad keys.
+    ///
+    /// The pad is recorded as `Written`, so the key cannot be read until
+    /// `mark_as_complete` confirms that all of its `total_chunks` pads are known.
+    /// Registering the same chunk again replaces it.
+    pub fn restore_pad(
+        &mut self,
+        key_name: &str,
+        pad: PadInfo,
+        total_chunks: usize,
+    ) -> Result<(), Error> {
+        let conflict = |reason: String| -> Error {
+            IndexError::RestoreConflict {
+                key: key_name.to_string(),
+                reason,
+            }
+            .into()
+        };
+
+        if pad.chunk_index >= total_chunks {
+            return Err(conflict(format!(
+                "chunk index {} is out of range for {} chunks",
+                pad.chunk_index, total_chunks
+            )));
+        }
+
+        match self.restoring_keys.get(key_name) {
+            Some(expected) if *expected != total_chunks => {
+                return Err(conflict(format!(
+                    "restore was started with {} chunks, not {}",
+                    expected, total_chunks
+                )));
+            }
+            Some(_) => {}
+            None if self.contains_key(key_name) => {
+                return Err(IndexError::KeyAlreadyExists(key_name.to_string()).into());
+            }
+            None => {}
+        }
+
+        let pads = match self
+            .index
+            .entry(key_name.to_string())
+            .or_insert_with(|| IndexEntry::PrivateKey(Vec::new()))
+        {
+            IndexEntry::PrivateKey(pads) => pads,
+            IndexEntry::PublicUpload(_, _) => {
+                return Err(conflict("key is a public upload".to_string()));
+            }
+        };
+
+        if let Some(other) = pads
+            .iter()
+            .find(|p| p.address == pad.address && p.chunk_index != pad.chunk_index)
+        {
+            return Err(conflict(format!(
+                "pad {} is already registered as chunk {}",
+                pad.address, other.chunk_index
+            )));
+        }
+
+        debug!(
+            "Restoring chunk {}/{} of key {} from pad {}",
+            pad.chunk_index, total_chunks, key_name, pad.address
+        );
+
+        let mut pad = pad;
+        pad.status = PadStatus::Written;
+        pads.retain(|p| p.chunk_index != pad.chunk_index);
+        pads.push(pad);
+        pads.sort_by_key(|p| p.chunk_index);
+
+        self.restoring_keys
+            .insert(key_name.to_string(), total_chunks);
+
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Finalizes a key restored with `restore_pad` once all its chunks are registered.
+    pub fn mark_as_complete(&mut self, key_name: &str) -> Result<(), Error> {
+        let total_chunks = *self
+            .restoring_keys
+            .get(key_name)
+            .ok_or_else(|| IndexError::KeyNotFound(key_name.to_string()))?;
+
+        let pads = match self.index.get_mut(key_name) {
+            Some(IndexEntry::PrivateKey(pads)) => pads,
+            _ => return Err(IndexError::KeyNotFound(key_name.to_string()).into()),
+        };
+
+        let missing_indices: Vec<usize> = (0..total_chunks)
+            .filter(|i| !pads.iter().any(|p| p.chunk_index == *i))
+            .collect();
+        if !missing_indices.is_empty() {
+            return Err(IndexError::IndexCorrupted {
+                key: key_name.to_string(),
+                missing_indices,
+            }
+            .into());
+        }
+
+        pads.iter_mut()
+            .for_each(|p| p.status = PadStatus::Confirmed);
+        self.restoring_keys.remove(key_name);
+
+        info!("Restored key {} with {} pads", key_name, total_chunks);
+
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Whether a key is being restored and still waits for `mark_as_complete`.
+    pub fn is_restoring(&self, key_name: &str) -> bool {
+        self.restoring_keys.contains_key(key_name)
+    }
+}
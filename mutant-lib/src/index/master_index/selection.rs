@@ -0,0 +1,136 @@
+use autonomi::ScratchpadAddress;
+use log::warn;
+use mutant_protocol::key_matches;
+use std::collections::{BTreeSet, HashSet};
+
+use crate::error::Error;
+
+use super::{IndexEntry, MasterIndex};
+
+/// The counter a newer version of an entry has a higher value of.
+pub(super) fn entry_counter(entry: &IndexEntry) -> u64 {
+    match entry {
+        IndexEntry::PrivateKey(pads) => pads.first().map_or(0, |pad| pad.last_known_counter),
+        IndexEntry::PublicUpload(index_pad, _) => index_pad.last_known_counter,
+    }
+}
+
+pub(super) fn entry_addresses(entry: &IndexEntry) -> Vec<ScratchpadAddress> {
+    match entry {
+        IndexEntry::PrivateKey(pads) => pads.iter().map(|pad| pad.address).collect(),
+        IndexEntry::PublicUpload(index_pad, pads) => std::iter::once(index_pad)
+            .chain(pads)
+            .map(|pad| pad.address)
+            .collect(),
+    }
+}
+
+impl MasterIndex {
+    /// Names of the keys matching any of `patterns`.
+    pub fn select_keys(&self, patterns: &[String]) -> BTreeSet<String> {
+        self.index
+            .keys()
+            .filter(|key| patterns.iter().any(|pattern| key_matches(pattern, key)))
+            .cloned()
+            .collect()
+    }
+
+    /// Copies the entries of `keys` from `source` that are missing here or have a higher
+    /// counter there, along with the envelope, transforms, padding, integrity proof and
+    /// chunk order bookkeeping needed to read them back. The pads of the copied entries
+    /// are taken out of the free and pending lists so they are never handed out to
+    /// another key; the other keys and pads are left untouched and nothing is saved.
+    ///
+    /// Returns the number of keys added and updated.
+    pub fn merge_selected(
+        &mut self,
+        source: &MasterIndex,
+        keys: &BTreeSet<String>,
+    ) -> (usize, usize) {
+        let mut added = 0;
+        let mut updated = 0;
+        let mut taken: HashSet<ScratchpadAddress> = HashSet::new();
+
+        for key in keys {
+            let Some(entry) = source.index.get(key) else {
+                continue;
+            };
+            match self.index.get(key) {
+                None => added += 1,
+                Some(existing)
+                    if std::mem::discriminant(existing) != std::mem::discriminant(entry) =>
+                {
+                    warn!("Key {} is public on one side only, not merging it", key);
+                    continue;
+                }
+                Some(existing) if entry_counter(entry) > entry_counter(existing) => updated += 1,
+                Some(_) => continue,
+            }
+
+            taken.extend(entry_addresses(entry));
+            self.index.insert(key.clone(), entry.clone());
+
+            self.copy_key_records(source, key);
+        }
+
+        self.free_pads.retain(|pad| !taken.contains(&pad.address));
+        self.pending_verification_pads
+            .retain(|pad| !taken.contains(&pad.address));
+
+        (added, updated)
+    }
+
+    /// Merges every key of `remote` into this index, as a full sync does. The keys
+    /// missing here are added and the ones with a higher counter there replace the local
+    /// ones, along with the records needed to read them back. The keys removed here
+    /// since the last push are left out, their pads are held by the trash.
+    ///
+    /// Returns the number of keys added and updated.
+    pub fn merge_entries(&mut self, remote: &MasterIndex) -> Result<(usize, usize), Error> {
+        let mut added = 0;
+        let mut updated = 0;
+
+        for (key, remote_entry) in remote.list() {
+            if self.is_trashed(&key) && !self.contains_key(&key) {
+                continue;
+            }
+            if self.get_entry(&key).is_none() {
+                self.copy_key_records(remote, &key);
+                self.add_entry(&key, remote_entry)?;
+                added += 1;
+            } else if self.update_entry(&key, remote_entry)? {
+                self.copy_key_records(remote, &key);
+                updated += 1;
+            }
+        }
+
+        Ok((added, updated))
+    }
+
+    /// Replaces what this index records about how to read `key` back with what `source`
+    /// records, the entry itself aside.
+    fn copy_key_records(&mut self, source: &MasterIndex, key: &str) {
+        match source.envelopes.get(key) {
+            Some(header_len) => self.envelopes.insert(key.to_string(), *header_len),
+            None => self.envelopes.remove(key),
+        };
+        match source.transforms.get(key) {
+            Some(transforms) => self.transforms.insert(key.to_string(), transforms.clone()),
+            None => self.transforms.remove(key),
+        };
+        match source.final_chunk_padding.get(key) {
+            Some(padding) => self.final_chunk_padding.insert(key.to_string(), *padding),
+            None => self.final_chunk_padding.remove(key),
+        };
+        match source.integrity_proofs.get(key) {
+            Some(proof) => self.integrity_proofs.insert(key.to_string(), proof.clone()),
+            None => self.integrity_proofs.remove(key),
+        };
+        match source.chunk_permutations.get(key) {
+            Some(permutation) => self
+                .chunk_permutations
+                .insert(key.to_string(), permutation.clone()),
+            None => self.chunk_permutations.remove(key),
+        };
+    }
+}
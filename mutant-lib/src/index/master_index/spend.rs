@@ -0,0 +1,81 @@
+use mutant_protocol::{StorageMode, StoreEstimate};
+use serde::{Deserialize, Serialize};
+
+use super::MasterIndex;
+use crate::ops::chunking::ChunkRanges;
+
+/// What has been paid for the writes made through this index.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpendReport {
+    /// Sum of the costs of every pad write, in atto tokens.
+    pub total_tokens_spent: u128,
+    pub pads_created: u64,
+    pub pads_updated: u64,
+    /// Part of `total_tokens_spent` reported for updates of existing pads.
+    pub estimated_tokens_for_updates: u128,
+}
+
+impl SpendReport {
+    /// Average cost of creating a pad, in atto tokens.
+    pub fn average_cost_per_pad(&self) -> f64 {
+        if self.pads_created == 0 {
+            return 0.0;
+        }
+        self.total_tokens_spent.saturating_sub(self.estimated_tokens_for_updates) as f64
+            / self.pads_created as f64
+    }
+}
+
+impl MasterIndex {
+    /// Accounts for a successful pad write. Persisted with the next save of the index.
+    pub(crate) fn record_spend(&mut self, created: bool, cost: u128) {
+        self.spend.total_tokens_spent = self.spend.total_tokens_spent.saturating_add(cost);
+        if created {
+            self.spend.pads_created += 1;
+        } else {
+            self.spend.pads_updated += 1;
+            self.spend.estimated_tokens_for_updates =
+                self.spend.estimated_tokens_for_updates.saturating_add(cost);
+        }
+    }
+
+    pub fn spend_report(&self) -> SpendReport {
+        self.spend.clone()
+    }
+
+    /// What storing `data_len` bytes under `key_name` would take, as [`Self::acquire_pads`]
+    /// would hand the pads out: the pads of the key first, as it is rewritten in place,
+    /// then the free pads, then new ones. Only the new pads are counted in the cost.
+    pub fn estimate_store(
+        &self,
+        key_name: &str,
+        data_len: usize,
+        mode: &StorageMode,
+        public: bool,
+    ) -> StoreEstimate {
+        let total_chunks =
+            ChunkRanges::new(data_len, mode.scratchpad_size()).count() + public as usize;
+
+        // The index pad of a public key is kept along with its data pads.
+        let key_pads = self.get_pads(key_name).len() + self.is_public(key_name) as usize;
+        let reused_key_pads = key_pads.min(total_chunks);
+        let reused_free_pads = (total_chunks - reused_key_pads).min(self.free_pads.len());
+        let new_pads_required = total_chunks - reused_key_pads - reused_free_pads;
+
+        let estimated_cost = (self.spend.pads_created > 0).then(|| {
+            let created_cost = self
+                .spend
+                .total_tokens_spent
+                .saturating_sub(self.spend.estimated_tokens_for_updates);
+            created_cost / self.spend.pads_created as u128 * new_pads_required as u128
+        });
+
+        StoreEstimate {
+            total_chunks,
+            reused_key_pads,
+            reused_free_pads,
+            new_pads_required,
+            estimated_cost,
+        }
+    }
+}
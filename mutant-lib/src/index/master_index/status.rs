@@ -2,9 +2,10 @@ use crate::error::Error;
 use crate::index::error::IndexError;
 use crate::index::pad_info::PadInfo;
 use crate::index::PadStatus;
+use crate::ops::chunking::ChunkIter;
 use crate::storage::ScratchpadAddress;
 use log::debug;
-use mutant_protocol::StorageMode;
+use mutant_protocol::{StorageMode, MAX_SCRATCHPAD_SIZE};
 
 use super::{IndexEntry, MasterIndex};
 
@@ -51,7 +52,7 @@ impl MasterIndex {
             Err(IndexError::KeyNotFound(key_name.to_string()).into())
         };
 
-        self.save(self.network_choice)?;
+        self.checkpoint()?;
 
         res
     }
@@ -73,34 +74,22 @@ impl MasterIndex {
     }
 
     pub fn verify_checksum(&self, key_name: &str, data_bytes: &[u8], mode: StorageMode) -> bool {
-        let new_checksums = data_bytes
-            .chunks(mode.scratchpad_size())
-            .map(|chunk| PadInfo::checksum(chunk))
+        let new_checksums = ChunkIter::new(data_bytes, mode.scratchpad_size())
+            .map(PadInfo::checksum)
             .collect::<Vec<_>>();
 
-        if let Some(entry) = self.index.get(key_name) {
-            match entry {
-                IndexEntry::PrivateKey(pads) => {
-                    if pads.len() != new_checksums.len() {
-                        return false;
-                    }
-                    pads.iter()
-                        .zip(new_checksums.iter())
-                        .all(|(p, c)| p.checksum == *c)
-                }
-                IndexEntry::PublicUpload(_index_pad, pads) => {
-                    if pads.len() != new_checksums.len() {
-                        return false;
-                    }
+        if !self.index.contains_key(key_name) {
+            return false;
+        }
 
-                    pads.iter()
-                        .zip(new_checksums.iter())
-                        .all(|(p, c)| p.checksum == *c)
-                }
-            }
-        } else {
-            false
+        // In chunk order, whether or not the chunks of the key are shuffled
+        let pads = self.get_pads(key_name);
+        if pads.len() != new_checksums.len() {
+            return false;
         }
+        pads.iter()
+            .zip(new_checksums.iter())
+            .all(|(p, c)| p.checksum == *c)
     }
 
     pub fn get_pending_pads(&self) -> Vec<PadInfo> {
@@ -130,6 +119,8 @@ impl MasterIndex {
         Ok(())
     }
 
+    /// The pad and byte counts of the index. Byte totals saturate rather than wrap, a
+    /// corrupted pad size cannot make them overflow.
     pub fn get_storage_stats(&self) -> super::StorageStats {
         let mut stats = super::StorageStats::default();
 
@@ -146,8 +137,82 @@ impl MasterIndex {
         stats.free_pads = self.free_pads.len() as u64;
         stats.pending_verification_pads = self.pending_verification_pads.len() as u64;
 
-        stats.total_pads = stats.occupied_pads + stats.free_pads + stats.pending_verification_pads;
+        stats.bundle_pads = self.bundles.len() as u64;
+        stats.bundled_keys = self.bundled_keys.len() as u64;
+        for bundle in self.bundles.iter() {
+            let occupancy = self.bundle_occupancy(&bundle.pad.address);
+            stats.bundle_live_bytes = stats
+                .bundle_live_bytes
+                .saturating_add(occupancy.live_bytes as u64);
+            stats.bundle_capacity_bytes = stats
+                .bundle_capacity_bytes
+                .saturating_add(occupancy.capacity as u64);
+        }
+
+        stats.trashed_keys = self.trash.len() as u64;
+        stats.trash_pads = self
+            .trash
+            .values()
+            .map(|trashed| match &trashed.entry {
+                IndexEntry::PrivateKey(pads) => pads.len() as u64,
+                IndexEntry::PublicUpload(_index, pads) => pads.len() as u64 + 1,
+            })
+            .sum();
+
+        let entry_bytes = |entry: &IndexEntry| match entry {
+            IndexEntry::PrivateKey(pads) => saturating_sum(pads.iter().map(|p| p.size as u64)),
+            IndexEntry::PublicUpload(index_pad, pads) => saturating_sum(
+                std::iter::once(index_pad)
+                    .chain(pads)
+                    .map(|p| p.size as u64),
+            ),
+        };
+        let key_bytes = saturating_sum(self.index.values().map(entry_bytes));
+        stats.trash_bytes = saturating_sum(
+            self.trash
+                .values()
+                .map(|trashed| entry_bytes(&trashed.entry)),
+        );
+        stats.stored_bytes = key_bytes
+            .saturating_add(stats.trash_bytes)
+            .saturating_add(stats.bundle_live_bytes);
+        for entry in self.index.values() {
+            let (confirmed, wasted) = confirmed_usage(entry);
+            stats.confirmed_bytes = stats.confirmed_bytes.saturating_add(confirmed);
+            stats.wasted_bytes = stats.wasted_bytes.saturating_add(wasted);
+        }
+
+        stats.nb_keys += stats.bundled_keys;
+        stats.total_pads = stats.used_pads() + stats.free_pads + stats.pending_verification_pads;
 
         stats
     }
 }
+
+/// The bytes of value the confirmed pads of `entry` hold, and the space of those pads
+/// left unused. Each pad takes a whole scratchpad whatever the size of its chunk, so
+/// the final partial chunk and its filler count as waste, the pads not confirmed yet
+/// count as neither.
+pub(super) fn confirmed_usage(entry: &IndexEntry) -> (u64, u64) {
+    let (index_pad, pads) = match entry {
+        IndexEntry::PrivateKey(pads) => (None, pads),
+        IndexEntry::PublicUpload(index_pad, pads) => (Some(index_pad), pads),
+    };
+
+    index_pad
+        .into_iter()
+        .chain(pads)
+        .filter(|pad| pad.status == PadStatus::Confirmed)
+        .fold((0u64, 0u64), |(confirmed, wasted), pad| {
+            let held = pad.size.saturating_sub(pad.padding);
+            (
+                confirmed.saturating_add(held as u64),
+                wasted.saturating_add(MAX_SCRATCHPAD_SIZE.saturating_sub(held) as u64),
+            )
+        })
+}
+
+/// Sums byte counts, stopping at `u64::MAX` instead of wrapping.
+pub(super) fn saturating_sum(values: impl Iterator<Item = u64>) -> u64 {
+    values.fold(0, u64::saturating_add)
+}
@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::info;
+use mutant_protocol::KeyDetails;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{MutAntConfig, NetworkChoice};
+use crate::error::Error;
+use crate::index::error::IndexError;
+
+use super::core::corrupt_cache_error;
+use super::key_management::entry_details;
+use super::prefix::with_prefix;
+use super::{IndexFootprint, MasterIndex, StorageStats};
+
+/// Starts a local index cache written as its summary followed by the whole index. A
+/// cache without it is the whole index alone, as written before summaries existed.
+const SUMMARY_MAGIC: &[u8; 8] = b"MUTSUMM1";
+
+/// What `ls` and `stats` need of the index, written in front of it in the local cache
+/// so that they can answer before the pad lists are read, see
+/// [`MasterIndex::open_summary`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IndexSummary {
+    pub network_choice: NetworkChoice,
+    /// Listing details of every key, internal ones included, without access statistics.
+    pub keys: BTreeMap<String, KeyDetails>,
+    pub stats: StorageStats,
+    pub footprint: IndexFootprint,
+}
+
+/// An index opened from its summary, and the cache holding the rest of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct PendingDetails {
+    path: PathBuf,
+    summary: IndexSummary,
+}
+
+/// Length of the summary at the start of `reader`, which is left past the header.
+/// `None` for a cache without summary, `reader` then being rewound to its start.
+fn summary_len<R: Read + Seek>(reader: &mut R) -> io::Result<Option<u64>> {
+    let mut header = [0u8; 16];
+    if reader.read_exact(&mut header).is_ok() && header[..8] == SUMMARY_MAGIC[..] {
+        let len: [u8; 8] = header[8..].try_into().expect("8 bytes");
+        return Ok(Some(u64::from_le_bytes(len)));
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(None)
+}
+
+/// Leaves `reader` at the start of the whole index, past the summary if there is one.
+pub(super) fn skip_summary<R: Read + Seek>(reader: &mut R) -> io::Result<()> {
+    if let Some(len) = summary_len(reader)? {
+        let len = i64::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "summary too large"))?;
+        reader.seek(SeekFrom::Current(len))?;
+    }
+    Ok(())
+}
+
+impl MasterIndex {
+    /// Like [`Self::open`], reading only the summary of the local cache: the pad lists
+    /// stay on disk until [`Self::load_details`]. A cache written before summaries
+    /// existed is loaded whole.
+    pub fn open_summary(network_choice: NetworkChoice, config: Arc<MutAntConfig>) -> (Self, bool) {
+        Self::open_with(network_choice, config, MasterIndex::load_summary)
+    }
+
+    pub(super) fn load_summary(path: &Path, network_choice: NetworkChoice) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|_e| {
+            Error::Index(IndexError::IndexFileNotFound(path.display().to_string()))
+        })?;
+        let mut reader = BufReader::new(file);
+        let len = summary_len(&mut reader)
+            .map_err(|e| Error::Index(IndexError::DeserializationError(e.to_string())))?;
+        let Some(len) = len else {
+            return Self::load(path, network_choice);
+        };
+
+        let summary: IndexSummary = serde_cbor::from_reader(reader.take(len))
+            .map_err(|e| Error::Index(IndexError::DeserializationError(e.to_string())))?;
+        if summary.network_choice != network_choice {
+            return Err(Error::Index(IndexError::NetworkMismatch {
+                x: network_choice,
+                y: summary.network_choice,
+            }));
+        }
+
+        let mut index = Self::new_empty(network_choice);
+        index.pending_details = Some(PendingDetails {
+            path: path.to_path_buf(),
+            summary,
+        });
+        Ok(index)
+    }
+
+    /// Whether the index was opened from its summary and the rest of it is still on
+    /// disk. Only [`Self::summary_keys`], [`Self::summary_stats`] and
+    /// [`Self::summary_footprint`] answer until [`Self::load_details`].
+    pub fn details_pending(&self) -> bool {
+        self.pending_details.is_some()
+    }
+
+    /// Reads the rest of an index opened by [`Self::open_summary`], nothing when it is
+    /// loaded already. When the rest cannot be read, the summary keeps answering and the
+    /// cache is never saved over, see [`IndexError::CorruptIndexCache`].
+    pub fn load_details(&mut self) -> Result<(), Error> {
+        let Some(pending) = &self.pending_details else {
+            return Ok(());
+        };
+        if let Some(corrupt) = &self.corrupt_cache {
+            return Err(Error::Index(corrupt.clone()));
+        }
+
+        let mut loaded = match Self::load(&pending.path, self.network_choice) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                let corrupt = corrupt_cache_error(&pending.path, e.to_string());
+                self.corrupt_cache = Some(corrupt.clone());
+                return Err(Error::Index(corrupt));
+            }
+        };
+        loaded.read_only = self.read_only;
+        loaded.config = self.config.clone();
+        loaded.rng = self.rng.clone();
+        *self = loaded;
+        info!("Loaded the pad lists of the master index");
+        Ok(())
+    }
+
+    /// The listing details of every key, see [`IndexSummary::keys`].
+    pub fn summary_keys(&self) -> BTreeMap<String, KeyDetails> {
+        self.summary_keys_with_prefix("")
+    }
+
+    /// [`Self::summary_keys`] of the keys starting with `prefix`, see
+    /// [`Self::keys_with_prefix`].
+    pub fn summary_keys_with_prefix(&self, prefix: &str) -> BTreeMap<String, KeyDetails> {
+        match &self.pending_details {
+            Some(pending) => with_prefix(&pending.summary.keys, prefix)
+                .map(|(key, details)| (key.clone(), details.clone()))
+                .collect(),
+            None => self.key_summaries(prefix),
+        }
+    }
+
+    /// [`Self::get_storage_stats`], from the summary while the rest is on disk.
+    pub fn summary_stats(&self) -> StorageStats {
+        match &self.pending_details {
+            Some(pending) => pending.summary.stats.clone(),
+            None => self.get_storage_stats(),
+        }
+    }
+
+    /// [`Self::memory_footprint`], from the summary while the rest is on disk.
+    pub fn summary_footprint(&self) -> Result<IndexFootprint, Error> {
+        match &self.pending_details {
+            Some(pending) => Ok(pending.summary.footprint.clone()),
+            None => self.memory_footprint(),
+        }
+    }
+
+    fn key_summaries(&self, prefix: &str) -> BTreeMap<String, KeyDetails> {
+        let mut aliases: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (alias, key) in &self.aliases {
+            aliases.entry(key).or_default().push(alias.clone());
+        }
+
+        with_prefix(&self.index, prefix)
+            .map(|(key, entry)| {
+                let details = entry_details(
+                    key.clone(),
+                    entry,
+                    aliases.remove(key.as_str()).unwrap_or_default(),
+                    self.integrity_proofs.get(key).cloned(),
+                    self.modified_at.get(key).copied(),
+                    None,
+                );
+                (key.clone(), details)
+            })
+            .collect()
+    }
+
+    /// Writes the summary of the index followed by the whole index.
+    pub(super) fn write_with_summary(&self, mut writer: impl Write) -> Result<(), Error> {
+        let index = serde_cbor::to_vec(self)
+            .map_err(|e| Error::Index(IndexError::SerializationError(e.to_string())))?;
+        let summary = serde_cbor::to_vec(&IndexSummary {
+            network_choice: self.network_choice,
+            keys: self.key_summaries(""),
+            stats: self.get_storage_stats(),
+            footprint: self.footprint_with(index.len()),
+        })
+        .map_err(|e| Error::Index(IndexError::SerializationError(e.to_string())))?;
+
+        [
+            &SUMMARY_MAGIC[..],
+            &(summary.len() as u64).to_le_bytes(),
+            &summary,
+            &index,
+        ]
+        .into_iter()
+        .try_for_each(|part| writer.write_all(part))
+        .and_then(|()| writer.flush())
+        .map_err(|e| Error::Index(IndexError::SerializationError(e.to_string())))
+    }
+}
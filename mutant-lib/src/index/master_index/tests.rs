@@ -1,8 +1,15 @@
 use super::*;
+use crate::clock::Clock;
 use crate::config::NetworkChoice;
+use crate::error::Error;
+use crate::index::error::IndexError;
 use crate::index::PadStatus;
-use mutant_protocol::{StorageMode, MEDIUM_SCRATCHPAD_SIZE};
+use mutant_protocol::{
+    ResetMode, ResetSummary, StorageMode, MAX_SCRATCHPAD_SIZE, MEDIUM_SCRATCHPAD_SIZE,
+};
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::Duration;
 
 const DEFAULT_SCRATCHPAD_SIZE: usize = MEDIUM_SCRATCHPAD_SIZE;
 
@@ -118,20 +125,87 @@ fn test_remove_key_moves_pads() {
         .update_pad_status(key_conf, &pad_conf_addr, PadStatus::Confirmed, None)
         .unwrap(); // Mark as non-generated
 
-    // Remove the key with the 'Generated' pad
+    // Remove the key with the 'Generated' pad, it was never written
     index.remove_key(key_gen).unwrap();
     assert!(!index.contains_key(key_gen));
-    assert_eq!(index.pending_verification_pads.len(), 1);
-    assert_eq!(index.pending_verification_pads[0].address, pad_gen_addr);
-    assert_eq!(index.free_pads.len(), 0);
+    assert_eq!(index.free_pads.len(), 1);
+    assert_eq!(index.free_pads[0].address, pad_gen_addr);
+    assert_eq!(index.free_pads[0].status, PadStatus::Free);
 
     // Remove the key with the 'Confirmed' pad
     index.remove_key(key_conf).unwrap();
     assert!(!index.contains_key(key_conf));
-    assert_eq!(index.pending_verification_pads.len(), 1); // Should still have the one from key_gen
-    assert_eq!(index.free_pads.len(), 1);
-    assert_eq!(index.free_pads[0].address, pad_conf_addr);
-    assert_eq!(index.free_pads[0].status, PadStatus::Free);
+    assert!(index.pending_verification_pads.is_empty());
+    assert_eq!(index.free_pads.len(), 2);
+    assert_eq!(index.free_pads[1].address, pad_conf_addr);
+    assert_eq!(index.free_pads[1].status, PadStatus::Free);
+}
+
+#[test]
+fn test_removed_pads_are_reused() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 10];
+    let (pads, _) = index
+        .create_key("key", &data, StorageMode::Medium, false)
+        .unwrap();
+    for pad in pads.iter() {
+        index
+            .update_pad_status("key", &pad.address, PadStatus::Confirmed, None)
+            .unwrap();
+    }
+    let free_before = index.get_storage_stats().free_pads;
+
+    index.remove_key("key").unwrap();
+    assert_eq!(index.get_storage_stats().free_pads, free_before + 10);
+
+    // Storing again takes the freed pads, with their secret keys, and creates none
+    let (reused, _) = index
+        .create_key("other", &data, StorageMode::Medium, false)
+        .unwrap();
+    assert_eq!(reused.len(), 10);
+    assert!(reused.iter().all(|p| p.status == PadStatus::Free));
+    let removed: HashSet<_> = pads
+        .iter()
+        .map(|p| (p.address, p.sk_bytes.clone()))
+        .collect();
+    let taken: HashSet<_> = reused
+        .iter()
+        .map(|p| (p.address, p.sk_bytes.clone()))
+        .collect();
+    assert_eq!(taken, removed);
+    assert_eq!(index.get_storage_stats().free_pads, free_before);
+}
+
+#[test]
+fn test_remove_key_of_interrupted_store() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 4];
+    let (pads, _) = index
+        .create_key("key", &data, StorageMode::Medium, false)
+        .unwrap();
+    assert_eq!(pads.len(), 4);
+
+    // The store was cancelled halfway: one pad confirmed, one written, two never written.
+    index
+        .update_pad_status("key", &pads[0].address, PadStatus::Confirmed, None)
+        .unwrap();
+    index
+        .update_pad_status("key", &pads[1].address, PadStatus::Written, None)
+        .unwrap();
+
+    index.remove_key("key").unwrap();
+    assert!(!index.contains_key("key"));
+
+    let to_verify: Vec<_> = index
+        .pending_verification_pads
+        .iter()
+        .map(|p| p.address)
+        .collect();
+    assert_eq!(to_verify, vec![pads[0].address, pads[1].address]);
+
+    let freed: Vec<_> = index.free_pads.iter().map(|p| p.address).collect();
+    assert_eq!(freed, vec![pads[2].address, pads[3].address]);
+    assert!(index.free_pads.iter().all(|p| p.status == PadStatus::Free));
 }
 
 #[test]
@@ -160,6 +234,343 @@ fn test_is_finished() {
     assert!(!index.is_finished("non_existent_key")); // Test non-existent key
 }
 
+/// An index with a complete key and a key whose store was interrupted after writing
+/// one of its two pads.
+fn index_with_interrupted_store() -> (PathBuf, MasterIndex, Vec<PadInfo>) {
+    let (td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+    let (done, _) = index
+        .create_key("done", &data, StorageMode::Medium, false)
+        .unwrap();
+    for pad in done.iter() {
+        index
+            .update_pad_status("done", &pad.address, PadStatus::Confirmed, None)
+            .unwrap();
+    }
+    let (pads, _) = index
+        .create_key("interrupted", &data, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .update_pad_status("interrupted", &pads[0].address, PadStatus::Written, None)
+        .unwrap();
+    (td, index, pads)
+}
+
+#[test]
+fn test_incomplete_policy_ignore() {
+    let (_td, mut index, _pads) = index_with_interrupted_store();
+    assert_eq!(index.incomplete_keys(), vec!["interrupted"]);
+
+    let keys = index
+        .apply_incomplete_policy(IncompletePolicy::Ignore)
+        .unwrap();
+    assert_eq!(keys, vec!["interrupted"]);
+    assert!(index.contains_key("interrupted"));
+    assert!(index.pending_verification_pads.is_empty());
+}
+
+#[test]
+fn test_incomplete_policy_queue_pads_for_verification() {
+    let (_td, mut index, pads) = index_with_interrupted_store();
+
+    let keys = index
+        .apply_incomplete_policy(IncompletePolicy::QueuePadsForVerification)
+        .unwrap();
+    assert_eq!(keys, vec!["interrupted"]);
+    assert!(!index.contains_key("interrupted"));
+    assert!(index.contains_key("done"));
+    let to_verify: Vec<_> = index
+        .pending_verification_pads
+        .iter()
+        .map(|p| p.address)
+        .collect();
+    assert_eq!(to_verify, vec![pads[0].address]);
+    let freed: Vec<_> = index.free_pads.iter().map(|p| p.address).collect();
+    assert_eq!(freed, vec![pads[1].address]);
+    assert!(index.incomplete_keys().is_empty());
+}
+
+#[test]
+fn test_incomplete_policy_drop_entries() {
+    let (_td, mut index, _pads) = index_with_interrupted_store();
+
+    let keys = index
+        .apply_incomplete_policy(IncompletePolicy::DropEntries)
+        .unwrap();
+    assert_eq!(keys, vec!["interrupted"]);
+    assert!(!index.contains_key("interrupted"));
+    assert!(index.trash.contains_key("interrupted"));
+    assert!(index.free_pads.is_empty());
+    assert!(index.pending_verification_pads.is_empty());
+    assert!(index.incomplete_keys().is_empty());
+}
+
+#[test]
+fn test_index_opened_from_its_summary() {
+    let (_td, mut index, _pads) = index_with_interrupted_store();
+    index.add_alias("done", "also_done").unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    index
+        .write_with_summary(std::fs::File::create(file.path()).unwrap())
+        .unwrap();
+
+    let mut opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+    assert!(opened.details_pending());
+    assert!(opened.get_pads("done").is_empty());
+    assert_eq!(opened.summary_keys(), index.summary_keys());
+    assert_eq!(opened.summary_keys()["done"].aliases, vec!["also_done"]);
+    assert_eq!(opened.summary_keys()["interrupted"].confirmed_pads, 0);
+    assert_eq!(opened.summary_stats(), index.get_storage_stats());
+    assert_eq!(
+        opened.summary_footprint().unwrap(),
+        index.memory_footprint().unwrap()
+    );
+    assert_eq!(opened.incomplete_keys(), vec!["interrupted"]);
+    // Nothing changed, the cache is left alone.
+    assert_eq!(
+        opened.save(NetworkChoice::Devnet).unwrap(),
+        SaveOutcome::DetailsPending
+    );
+
+    opened.load_details().unwrap();
+    assert!(!opened.details_pending());
+    assert_eq!(opened, index);
+    assert_eq!(
+        MasterIndex::load(file.path(), NetworkChoice::Devnet).unwrap(),
+        index
+    );
+    assert!(matches!(
+        MasterIndex::load_summary(file.path(), NetworkChoice::Mainnet),
+        Err(Error::Index(IndexError::NetworkMismatch { .. }))
+    ));
+}
+
+#[test]
+fn test_index_without_summary_is_loaded_whole() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    serde_cbor::to_writer(std::fs::File::create(file.path()).unwrap(), &index).unwrap();
+
+    let opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+    assert!(!opened.details_pending());
+    assert_eq!(opened, index);
+    assert_eq!(opened.summary_keys(), index.summary_keys());
+}
+
+#[test]
+fn test_corrupt_remote_index_is_never_an_empty_one() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let data = serde_cbor::to_vec(&index).unwrap();
+    assert_eq!(MasterIndex::decode_remote(&data).unwrap(), index);
+
+    let truncated = &data[..data.len() / 2];
+    match MasterIndex::decode_remote(truncated) {
+        Err(IndexError::CorruptRemoteIndex { size, checksum, .. }) => {
+            assert_eq!(size, truncated.len());
+            assert_eq!(checksum, PadInfo::checksum(truncated));
+        }
+        other => panic!("Expected a corrupt remote index, got {:?}", other),
+    }
+
+    let mut flipped = data.clone();
+    flipped[0] ^= 0x80;
+    assert!(matches!(
+        MasterIndex::decode_remote(&flipped),
+        Err(IndexError::CorruptRemoteIndex { .. })
+    ));
+}
+
+#[test]
+fn test_unreadable_cache_is_never_saved_over() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    serde_cbor::to_writer(std::fs::File::create(file.path()).unwrap(), &index).unwrap();
+    let data = std::fs::read(file.path()).unwrap();
+
+    std::fs::write(file.path(), &data[..data.len() / 2]).unwrap();
+    assert!(MasterIndex::load(file.path(), NetworkChoice::Devnet).is_err());
+    let mut flipped = data.clone();
+    flipped[0] ^= 0x80;
+    std::fs::write(file.path(), &flipped).unwrap();
+    assert!(MasterIndex::load(file.path(), NetworkChoice::Devnet).is_err());
+
+    let (mut opened, created) =
+        MasterIndex::open_with(NetworkChoice::Devnet, Arc::default(), |_, network| {
+            MasterIndex::load(file.path(), network)
+        });
+    assert!(!created);
+    assert!(opened.list().is_empty());
+    assert!(matches!(
+        opened.save(NetworkChoice::Devnet),
+        Err(Error::Index(IndexError::CorruptIndexCache { .. }))
+    ));
+
+    opened.reset(ResetMode::Abandon).unwrap();
+    opened.save(NetworkChoice::Devnet).unwrap();
+}
+
+#[test]
+fn test_unreadable_details_keep_the_summary() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    index
+        .write_with_summary(std::fs::File::create(file.path()).unwrap())
+        .unwrap();
+    let mut opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+
+    let data = std::fs::read(file.path()).unwrap();
+    std::fs::write(file.path(), &data[..data.len() - 16]).unwrap();
+    let size = data.len() - 16;
+    match opened.load_details() {
+        Err(Error::Index(IndexError::CorruptIndexCache { size: read, .. })) => {
+            assert_eq!(read, size)
+        }
+        other => panic!("Expected a corrupt index cache, got {:?}", other),
+    }
+    assert!(opened.details_pending());
+    assert_eq!(opened.summary_keys(), index.summary_keys());
+    assert!(opened.load_details().is_err());
+    assert!(opened.save(NetworkChoice::Devnet).is_err());
+}
+
+#[test]
+fn test_incomplete_policy_loads_the_pad_lists() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    index
+        .write_with_summary(std::fs::File::create(file.path()).unwrap())
+        .unwrap();
+
+    let mut opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+    let keys = opened
+        .apply_incomplete_policy(IncompletePolicy::DropEntries)
+        .unwrap();
+    assert_eq!(keys, vec!["interrupted"]);
+    assert!(!opened.details_pending());
+    assert!(opened.contains_key("done"));
+    assert!(opened.trash.contains_key("interrupted"));
+}
+
+#[tokio::test]
+async fn test_lazy_index_loads_the_pad_lists_when_locked() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    index
+        .write_with_summary(std::fs::File::create(file.path()).unwrap())
+        .unwrap();
+    let opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+    let lazy = crate::index::LazyIndex::new(opened);
+
+    assert_eq!(lazy.read_summary().await.summary_stats().nb_keys, 2);
+    assert!(lazy.read_summary().await.details_pending());
+
+    assert_eq!(lazy.read().await.unwrap().get_pads("done").len(), 2);
+    assert!(!lazy.shared().read().await.details_pending());
+}
+
+#[tokio::test]
+async fn test_lazy_index_locks_fail_with_the_load_error() {
+    let (_td, index, _pads) = index_with_interrupted_store();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    index
+        .write_with_summary(std::fs::File::create(file.path()).unwrap())
+        .unwrap();
+    let opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+    let data = std::fs::read(file.path()).unwrap();
+    std::fs::write(file.path(), &data[..data.len() - 16]).unwrap();
+    let lazy = crate::index::LazyIndex::new(opened);
+
+    assert!(matches!(
+        lazy.read().await,
+        Err(Error::Index(IndexError::CorruptIndexCache { .. }))
+    ));
+    assert!(lazy.write().await.is_err());
+    assert_eq!(lazy.read_summary().await.summary_stats().nb_keys, 2);
+}
+
+#[test]
+fn test_incomplete_policy_from_env_value() {
+    assert_eq!(
+        IncompletePolicy::from_env_value("Verify"),
+        Some(IncompletePolicy::QueuePadsForVerification)
+    );
+    assert_eq!(
+        IncompletePolicy::from_env_value("drop"),
+        Some(IncompletePolicy::DropEntries)
+    );
+    assert_eq!(IncompletePolicy::from_env_value("resume"), None);
+}
+
+#[test]
+fn test_internal_keys_are_kept_apart() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE];
+    let marker = internal_key("marker");
+    let (pads, _) = index
+        .create_key(&marker, &data, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("user_key", &data, StorageMode::Medium, false)
+        .unwrap();
+
+    assert_eq!(marker, "__mutant__/marker");
+    assert!(is_internal_key(&marker));
+    assert_eq!(
+        index.list().into_keys().collect::<Vec<_>>(),
+        vec!["user_key"]
+    );
+    let internal = index.list_internal_keys();
+    assert_eq!(internal.keys().collect::<Vec<_>>(), vec![&marker]);
+    match &internal[&marker] {
+        IndexEntry::PrivateKey(pads) => assert!(pads.iter().all(|p| p.sk_bytes == vec![0; 32])),
+        entry => panic!("Unexpected entry {:?}", entry),
+    }
+
+    assert!(matches!(
+        ensure_user_key(&marker),
+        Err(Error::Index(IndexError::ReservedKey(_)))
+    ));
+    assert!(ensure_user_key("user_key").is_ok());
+
+    // Not confirmed yet, then complete
+    assert_eq!(index.check_internal_keys().len(), 1);
+    index
+        .update_pad_status(&marker, &pads[0].address, PadStatus::Confirmed, None)
+        .unwrap();
+    assert!(index.check_internal_keys().is_empty());
+
+    let public_marker = internal_key("public");
+    index
+        .create_key(&public_marker, &data, StorageMode::Medium, true)
+        .unwrap();
+    let problems = index.check_internal_keys();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].0, public_marker);
+    assert!(problems[0].1.contains("public"));
+}
+
+#[test]
+fn test_key_details_from_stat() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+    let (pads, _) = index
+        .create_key("key", &data, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .update_pad_status("key", &pads[0].address, PadStatus::Confirmed, None)
+        .unwrap();
+
+    let details = index.stat("key").unwrap().details(None);
+    assert_eq!(details.key, "key");
+    assert_eq!(details.total_size, data.len());
+    assert_eq!(details.pad_count, 2);
+    assert_eq!(details.confirmed_pads, 1);
+    assert!(!details.is_finished);
+    assert_eq!(details.completion_percentage, 50);
+    assert_eq!(details.last_fetched_ms, None);
+    assert!(index.stat("missing").is_none());
+}
+
 #[test]
 fn test_verify_checksum_private() {
     let (_td, mut index) = setup_test_environment();
@@ -203,6 +614,56 @@ fn test_list() {
     assert_eq!(keys.keys().collect::<Vec<_>>(), vec!["key1", "key2"]);
 }
 
+#[test]
+fn test_keys_with_prefix() {
+    let (_td, mut index) = setup_test_environment();
+    for key in [
+        "backups/2024/05/db.tar",
+        "backups/notes",
+        "backupsX",
+        "old/backups/db.tar",
+        "photos/cat.jpg",
+    ] {
+        index
+            .create_key(key, &[1], StorageMode::Medium, false)
+            .unwrap();
+    }
+
+    assert_eq!(
+        index.keys_with_prefix("backups/"),
+        vec!["backups/2024/05/db.tar", "backups/notes"]
+    );
+    assert_eq!(
+        index.keys_with_prefix("backups/2024/"),
+        vec!["backups/2024/05/db.tar"]
+    );
+    assert!(index.keys_with_prefix("zzz").is_empty());
+    assert_eq!(index.keys_with_prefix("").len(), 5);
+    assert_eq!(
+        index
+            .summary_keys_with_prefix("backups")
+            .into_keys()
+            .collect::<Vec<_>>(),
+        vec!["backups/2024/05/db.tar", "backups/notes", "backupsX"]
+    );
+
+    // Opened from its summary, the keys are found the same way.
+    let file = tempfile::NamedTempFile::new().unwrap();
+    index
+        .write_with_summary(std::fs::File::create(file.path()).unwrap())
+        .unwrap();
+    let opened = MasterIndex::load_summary(file.path(), NetworkChoice::Devnet).unwrap();
+    assert!(opened.details_pending());
+    assert_eq!(
+        opened.keys_with_prefix("backups/"),
+        index.keys_with_prefix("backups/")
+    );
+    assert_eq!(
+        opened.summary_keys_with_prefix("photos/"),
+        index.summary_keys_with_prefix("photos/")
+    );
+}
+
 #[test]
 fn test_acquire_pads_reuse_free() {
     let (_td, mut index) = setup_test_environment();
@@ -317,3 +778,1571 @@ fn test_load_non_existent_file() {
     let (_td, index) = setup_test_environment(); // Sets up XDG env but doesn't create the file
     assert!(index.index.is_empty()); // Verify it's empty
 }
+
+#[test]
+fn test_commit_staged_value_replaces_the_previous_value() {
+    let (_td, mut index) = setup_test_environment();
+    let (old_pads, _) = index
+        .create_key("key", &[1u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    index
+        .update_pad_status("key", &old_pads[0].address, PadStatus::Confirmed, None)
+        .unwrap();
+    index.add_alias("key", "alias").unwrap();
+    index
+        .add_to_collection("photos", &["key".to_string()])
+        .unwrap();
+    let (new_pads, _) = index
+        .create_key("staged", &[2u8; 10], StorageMode::Medium, false)
+        .unwrap();
+
+    assert_eq!(index.commit_staged_value("staged", "key").unwrap(), None);
+
+    assert!(!index.contains_key("staged"));
+    assert_eq!(index.get_pads("key")[0].address, new_pads[0].address);
+    assert_eq!(index.resolve_alias("alias"), "key");
+    assert_eq!(index.list_collection("photos").unwrap(), vec!["key"]);
+    assert_eq!(index.free_pads.len(), 1);
+    assert_eq!(index.free_pads[0].address, old_pads[0].address);
+
+    assert!(index.commit_staged_value("staged", "key").is_err());
+}
+
+#[test]
+fn test_commit_staged_pads_frees_only_the_pads_left_behind() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![1u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+    let (old_pads, _) = index
+        .create_key("key", &data, StorageMode::Medium, false)
+        .unwrap();
+    let (fresh, _) = index
+        .create_key("staged", &[2u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    let mut staged_pads = vec![old_pads[0].clone(), fresh[0].clone()];
+    staged_pads[1].chunk_index = 1;
+    index
+        .add_entry("staged", IndexEntry::PrivateKey(staged_pads.clone()))
+        .unwrap();
+
+    index.commit_staged_pads("staged", "key").unwrap();
+
+    assert!(!index.contains_key("staged"));
+    assert_eq!(index.get_pads("key"), staged_pads);
+    assert_eq!(index.free_pads.len(), 1);
+    assert_eq!(index.free_pads[0].address, old_pads[1].address);
+
+    assert!(index.commit_staged_pads("staged", "key").is_err());
+}
+
+#[test]
+fn test_discard_staged_pads_keeps_the_pads_of_the_key() {
+    let (_td, mut index) = setup_test_environment();
+    let (old_pads, _) = index
+        .create_key("key", &[1u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    let (fresh, _) = index
+        .create_key("staged", &[2u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    let mut staged_pads = vec![old_pads[0].clone(), fresh[0].clone()];
+    staged_pads[1].chunk_index = 1;
+    index
+        .add_entry("staged", IndexEntry::PrivateKey(staged_pads))
+        .unwrap();
+
+    index.discard_staged_pads("staged", "key").unwrap();
+
+    assert!(!index.contains_key("staged"));
+    assert_eq!(index.get_pads("key"), old_pads);
+    assert_eq!(index.free_pads.len(), 1);
+    assert_eq!(index.free_pads[0].address, fresh[0].address);
+}
+
+#[test]
+fn test_derived_key_takes_its_derived_pads() {
+    let (_td, mut index) = setup_test_environment();
+    let master = blsttc::SecretKey::random();
+    let data = vec![3u8; DEFAULT_SCRATCHPAD_SIZE + 10];
+    index.register_derived_keys(&["derived"]).unwrap();
+    assert!(index.is_derived_key("derived"));
+
+    let (pads, _) = index
+        .create_derived_key("derived", &data, StorageMode::Medium, &master)
+        .unwrap();
+    let expected: Vec<_> = (0..2)
+        .map(|i| {
+            let sk = crate::ops::utils::derive_pad_secret_key(&master, "derived", i).unwrap();
+            autonomi::ScratchpadAddress::new(sk.public_key())
+        })
+        .collect();
+    assert_eq!(pads.iter().map(|p| p.address).collect::<Vec<_>>(), expected);
+
+    // Stored again after a removal, the key takes its pads back from the pool.
+    index.remove_key("derived").unwrap();
+    assert!(!index.is_derived_key("derived"));
+    assert_eq!(index.free_pads.len(), 2);
+    index.register_derived_keys(&["derived"]).unwrap();
+    let (again, _) = index
+        .create_derived_key("derived", &data, StorageMode::Medium, &master)
+        .unwrap();
+    assert_eq!(
+        again.iter().map(|p| p.address).collect::<Vec<_>>(),
+        expected
+    );
+    assert!(index.free_pads.is_empty());
+}
+
+#[test]
+fn test_renamed_derived_key_stays_derived() {
+    let (_td, mut index) = setup_test_environment();
+    let master = blsttc::SecretKey::random();
+    let data = vec![3u8; 10];
+    index.register_derived_keys(&["derived"]).unwrap();
+    index
+        .create_derived_key("derived", &data, StorageMode::Medium, &master)
+        .unwrap();
+
+    index.rename_key("derived", "renamed", &master).unwrap();
+    assert!(!index.is_derived_key("derived"));
+    assert!(index.is_derived_key("renamed"));
+
+    // Its next store derives the pads from the new name.
+    let pads = index
+        .derived_pads("renamed", &data, &[0..data.len()], &master)
+        .unwrap();
+    let sk = crate::ops::utils::derive_pad_secret_key(&master, "renamed", 0).unwrap();
+    assert_eq!(
+        pads[0].address,
+        autonomi::ScratchpadAddress::new(sk.public_key())
+    );
+}
+
+#[test]
+fn test_derived_pad_held_by_another_key_fails_the_store() {
+    let (_td, mut index) = setup_test_environment();
+    let master = blsttc::SecretKey::random();
+    index.register_derived_keys(&["derived"]).unwrap();
+    let (pads, _) = index
+        .create_derived_key("derived", &[1u8; 10], StorageMode::Medium, &master)
+        .unwrap();
+    index.rename_key("derived", "renamed", &master).unwrap();
+
+    let result = index.create_derived_key("derived", &[2u8; 10], StorageMode::Medium, &master);
+    assert!(matches!(
+        result,
+        Err(Error::Index(IndexError::DerivedPadInUse { .. }))
+    ));
+    assert_eq!(index.get_pads("renamed"), pads);
+    assert!(!index.contains_key("derived"));
+}
+
+#[test]
+fn test_remove_or_unlink_follows_the_removal_rules() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; 16];
+    index
+        .create_key("key", &data, StorageMode::Medium, false)
+        .unwrap();
+    index.add_alias("key", "alias").unwrap();
+
+    assert!(matches!(
+        index.remove_or_unlink("key", false),
+        Err(Error::Index(IndexError::KeyHasAliases { .. }))
+    ));
+    assert!(index.contains_key("key"));
+
+    assert_eq!(index.remove_or_unlink("alias", false).unwrap(), None);
+    assert!(!index.is_alias("alias"));
+    assert!(index.contains_key("key"));
+
+    index.remove_or_unlink("key", true).unwrap();
+    assert!(!index.contains_key("key"));
+    assert!(index.is_trashed("key"));
+
+    index
+        .create_key("other", &data, StorageMode::Medium, false)
+        .unwrap();
+    index.remove_or_unlink("other", false).unwrap();
+    assert!(!index.contains_key("other"));
+    assert!(!index.is_trashed("other"));
+}
+
+#[test]
+fn test_key_graph() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .create_key("private", &[1u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("public", &[2u8; 10], StorageMode::Medium, true)
+        .unwrap();
+
+    let graph = index.key_graph();
+
+    assert_eq!(
+        graph.nodes,
+        vec![
+            KeyNode {
+                key: "private".to_string(),
+                key_type: KeyType::Regular,
+            },
+            KeyNode {
+                key: "public".to_string(),
+                key_type: KeyType::Public,
+            },
+        ]
+    );
+    assert!(graph.edges.is_empty());
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph mutant {"));
+    assert!(dot.contains("\"private\" [shape=box];"));
+    assert!(dot.contains("\"public\" [shape=ellipse];"));
+}
+
+#[test]
+fn test_bundle_encoding_roundtrip() {
+    let values = vec![b"first".as_slice(), b"".as_slice(), b"third value".as_slice()];
+    let (data, offsets) = encode_bundle(&values);
+
+    assert_eq!(decode_bundle(&data).unwrap(), offsets);
+    for (value, (offset, len)) in values.iter().zip(offsets) {
+        assert_eq!(&data[offset..offset + len], *value);
+    }
+
+    assert!(decode_bundle(&data[..data.len() - 1]).is_err());
+}
+
+#[test]
+fn test_bundle_bookkeeping() {
+    let (_td, mut index) = setup_test_environment();
+    let capacity = DEFAULT_SCRATCHPAD_SIZE;
+    let (data, offsets) = encode_bundle(&[b"aaaa".as_slice(), b"bbbb".as_slice()]);
+    let pad = PadInfo::new(&data, 0);
+    let address = pad.address;
+
+    let slots = ["a", "b"]
+        .iter()
+        .zip(offsets)
+        .map(|(key, (offset, len))| {
+            (
+                key.to_string(),
+                BundleSlot {
+                    bundle_address: address,
+                    offset,
+                    len,
+                    checksum: PadInfo::checksum(&data[offset..offset + len]),
+                },
+            )
+        })
+        .collect();
+    index.commit_bundle(Bundle { pad, capacity }, slots).unwrap();
+
+    assert!(index.contains_key("a"));
+    assert!(index.is_bundled("b"));
+    assert_eq!(index.get_storage_stats().bundle_pads, 1);
+    assert!(index.find_bundle_with_room(10, capacity, None).is_some());
+    assert!(index.find_bundle_with_room(capacity, capacity, None).is_none());
+
+    index.remove_bundled_key("a").unwrap();
+    assert!(!index.contains_key("a"));
+    assert!(index.bundle_occupancy(&address).ratio() < 1.0);
+
+    index.remove_bundled_key("b").unwrap();
+    index.release_bundle(&address).unwrap();
+    assert!(index.bundles().is_empty());
+    assert_eq!(index.pending_verification_pads.len(), 1);
+}
+
+fn pads_with_chunk_indices(indices: &[usize]) -> Vec<PadInfo> {
+    indices
+        .iter()
+        .map(|&i| {
+            let mut pad = PadInfo::new(&[i as u8; 4], i);
+            pad.status = PadStatus::Confirmed;
+            pad
+        })
+        .collect()
+}
+
+fn missing_indices_of(index: &MasterIndex, key_name: &str) -> Vec<usize> {
+    match index.validate_chunk_indices(key_name) {
+        Err(crate::error::Error::Index(crate::index::error::IndexError::IndexCorrupted {
+            key,
+            missing_indices,
+        })) => {
+            assert_eq!(key, key_name);
+            missing_indices
+        }
+        other => panic!("Expected IndexCorrupted, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_validate_chunk_indices_sparse() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .add_entry("sparse", IndexEntry::PrivateKey(pads_with_chunk_indices(&[0, 3, 1])))
+        .unwrap();
+    index
+        .add_entry("duplicated", IndexEntry::PrivateKey(pads_with_chunk_indices(&[1, 0, 1])))
+        .unwrap();
+    index
+        .add_entry("unordered", IndexEntry::PrivateKey(pads_with_chunk_indices(&[2, 0, 1])))
+        .unwrap();
+
+    assert_eq!(missing_indices_of(&index, "sparse"), vec![2]);
+    assert_eq!(missing_indices_of(&index, "duplicated"), vec![2]);
+    assert!(index.validate_chunk_indices("unordered").is_ok());
+}
+
+#[test]
+fn test_renumber_offset_chunk_indices() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .add_entry("offset", IndexEntry::PrivateKey(pads_with_chunk_indices(&[3, 1, 2])))
+        .unwrap();
+    index
+        .add_entry("sparse", IndexEntry::PrivateKey(pads_with_chunk_indices(&[1, 3])))
+        .unwrap();
+
+    assert!(index.validate_chunk_indices("offset").is_err());
+    assert!(index.renumber_chunk_indices("offset").unwrap());
+    assert!(index.validate_chunk_indices("offset").is_ok());
+    let mut indices: Vec<_> = index.get_pads("offset").iter().map(|p| p.chunk_index).collect();
+    indices.sort();
+    assert_eq!(indices, vec![0, 1, 2]);
+
+    assert!(!index.renumber_chunk_indices("sparse").unwrap());
+    assert!(index.validate_chunk_indices("sparse").is_err());
+}
+
+#[test]
+fn test_spend_report() {
+    let (_td, mut index) = setup_test_environment();
+    assert_eq!(index.spend_report().average_cost_per_pad(), 0.0);
+
+    index.record_spend(true, 100);
+    index.record_spend(true, 300);
+    index.record_spend(false, 10);
+
+    let report = index.spend_report();
+    assert_eq!(report.total_tokens_spent, 410);
+    assert_eq!(report.pads_created, 2);
+    assert_eq!(report.pads_updated, 1);
+    assert_eq!(report.estimated_tokens_for_updates, 10);
+    assert_eq!(report.average_cost_per_pad(), 200.0);
+}
+
+#[test]
+fn test_estimate_store_counts_reused_pads_as_free() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 3];
+    let (pads, _) = index
+        .create_key("removed", &data, StorageMode::Medium, false)
+        .unwrap();
+    for pad in pads.iter() {
+        index
+            .update_pad_status("removed", &pad.address, PadStatus::Confirmed, None)
+            .unwrap();
+    }
+    index.remove_key("removed").unwrap();
+    index
+        .create_key(
+            "kept",
+            &data[..DEFAULT_SCRATCHPAD_SIZE * 2],
+            StorageMode::Medium,
+            false,
+        )
+        .unwrap();
+    assert_eq!(index.get_storage_stats().free_pads, 1);
+
+    // Nothing was created yet to price the new pads with.
+    let estimate = index.estimate_store(
+        "new",
+        DEFAULT_SCRATCHPAD_SIZE * 4 + 1,
+        &StorageMode::Medium,
+        false,
+    );
+    assert_eq!(
+        (
+            estimate.total_chunks,
+            estimate.reused_key_pads,
+            estimate.reused_free_pads
+        ),
+        (5, 0, 1)
+    );
+    assert_eq!(estimate.new_pads_required, 4);
+    assert_eq!(estimate.estimated_cost, None);
+
+    index.record_spend(true, 100);
+    index.record_spend(true, 300);
+    index.record_spend(false, 10);
+
+    // Storing the key again rewrites its own pads, only the last one is created.
+    let estimate = index.estimate_store(
+        "kept",
+        DEFAULT_SCRATCHPAD_SIZE * 4,
+        &StorageMode::Medium,
+        false,
+    );
+    assert_eq!(
+        (
+            estimate.reused_key_pads,
+            estimate.reused_free_pads,
+            estimate.new_pads_required
+        ),
+        (2, 1, 1)
+    );
+    assert_eq!(estimate.estimated_cost, Some(200));
+
+    let estimate = index.estimate_store("kept", 10, &StorageMode::Medium, false);
+    assert_eq!((estimate.total_chunks, estimate.new_pads_required), (1, 0));
+    assert_eq!(estimate.estimated_cost, Some(0));
+
+    // A public key needs its index pad on top of the data pads.
+    let estimate = index.estimate_store("new", 10, &StorageMode::Medium, true);
+    assert_eq!(estimate.total_chunks, 2);
+    assert_eq!(estimate.new_pads_required, 1);
+}
+
+#[test]
+fn test_pad_status_transitions_are_checkpointed() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+    let key_name = "checkpointed_key";
+    let (pads, _) = index
+        .create_key(key_name, &data, StorageMode::Medium, false)
+        .unwrap();
+
+    index
+        .update_pad_status(key_name, &pads[0].address, PadStatus::Written, None)
+        .unwrap();
+    index
+        .update_pad_status(key_name, &pads[1].address, PadStatus::Written, None)
+        .unwrap();
+    assert_eq!(index.pending_transitions(), 2);
+
+    // The checkpoint state is not part of the index data.
+    let mut restored = index.clone();
+    restored.checkpoint = Default::default();
+    assert_eq!(restored.pending_transitions(), 0);
+    assert_eq!(restored, index);
+}
+
+#[test]
+fn test_deferred_saves_are_reported() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .create_key("key", &[1u8; 16], StorageMode::Medium, false)
+        .unwrap();
+
+    index.defer_saves();
+    assert_eq!(index.flush().unwrap(), SaveOutcome::Deferred);
+    index.resume_saves().unwrap();
+    assert_eq!(index.flush().unwrap(), SaveOutcome::Written);
+}
+
+#[test]
+fn test_snapshots_are_sequenced_per_cache() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .create_key("key", &[1u8; 16], StorageMode::Medium, false)
+        .unwrap();
+    let app_id = "snapshot_sequence_test";
+    let mut other = index.clone();
+    other.set_config(Arc::new(MutAntConfig {
+        app_id: Some(app_id.to_string()),
+        ..Default::default()
+    }));
+    let path = get_index_file_path(NetworkChoice::Devnet, Some(app_id)).unwrap();
+    let sequence = super::core::snapshot_sequence(&path);
+
+    // Saves of another cache do not make a checkpoint of this one stale.
+    let snapshot = sequence.next();
+    assert_eq!(index.flush().unwrap(), SaveOutcome::Written);
+    assert_eq!(
+        other.write_snapshot(&path, &sequence, snapshot).unwrap(),
+        SaveOutcome::Written
+    );
+
+    // A later save of the same cache does.
+    let stale = sequence.next();
+    assert_eq!(other.flush().unwrap(), SaveOutcome::Written);
+    assert_eq!(
+        other.write_snapshot(&path, &sequence, stale).unwrap(),
+        SaveOutcome::Superseded
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_seeded_runs_generate_the_same_pads() {
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 3];
+    let run = |seed| {
+        let mut index = MasterIndex::new_empty(NetworkChoice::Devnet);
+        index.set_rng(crate::rng::InstanceRng::new(Some(seed)));
+        let (pads, _) = index
+            .create_key("seeded_key", &data, StorageMode::Medium, false)
+            .unwrap();
+        pads.into_iter().map(|pad| pad.address).collect::<Vec<_>>()
+    };
+
+    let first = run(7);
+    assert_eq!(first.len(), 3);
+    assert_eq!(run(7), first);
+    assert_ne!(run(8), first);
+}
+
+#[test]
+fn test_restore_key_pad_by_pad() {
+    let (_td, mut index) = setup_test_environment();
+    let key_name = "restored_key";
+    let pads = pads_with_chunk_indices(&[0, 1]);
+
+    index.restore_pad(key_name, pads[1].clone(), 2).unwrap();
+    assert!(index.is_restoring(key_name));
+    assert!(!index.is_finished(key_name));
+
+    // Chunk 0 is still unknown.
+    assert!(matches!(
+        index.mark_as_complete(key_name),
+        Err(crate::error::Error::Index(crate::index::error::IndexError::IndexCorrupted {
+            ref missing_indices,
+            ..
+        })) if *missing_indices == vec![0]
+    ));
+    assert!(index.restore_pad(key_name, pads[0].clone(), 3).is_err());
+
+    index.restore_pad(key_name, pads[0].clone(), 2).unwrap();
+    index.mark_as_complete(key_name).unwrap();
+
+    assert!(!index.is_restoring(key_name));
+    assert!(index.is_finished(key_name));
+    assert_eq!(index.get_pads(key_name).len(), 2);
+    assert!(index.restore_pad(key_name, pads[0].clone(), 2).is_err());
+}
+
+#[test]
+fn test_collections() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; 16];
+    for key in ["a", "b", "c"] {
+        index
+            .create_key(key, &data, StorageMode::Medium, false)
+            .unwrap();
+    }
+
+    index
+        .add_to_collection("photos", &["a".to_string(), "b".to_string()])
+        .unwrap();
+    assert!(index
+        .add_to_collection("photos", &["missing".to_string()])
+        .is_err());
+    assert!(index.create_collection("photos").is_err());
+    assert_eq!(index.list_collection("photos").unwrap(), vec!["a", "b"]);
+
+    // Removing a key drops it from its collections.
+    index.remove_key("a").unwrap();
+    assert_eq!(index.list_collection("photos").unwrap(), vec!["b"]);
+
+    let mut other = index.clone();
+    other
+        .add_to_collection("photos", &["c".to_string()])
+        .unwrap();
+    other.create_collection("docs").unwrap();
+    assert_eq!(index.merge_collections(&other), 1);
+    assert_eq!(index.list_collections(), vec!["docs", "photos"]);
+    assert_eq!(index.list_collection("photos").unwrap(), vec!["b", "c"]);
+
+    assert_eq!(index.remove_collection("photos").unwrap(), vec!["b", "c"]);
+    assert!(index.list_collection("photos").is_err());
+    assert!(index.contains_key("b"));
+}
+
+#[test]
+fn test_integrity_proof() {
+    let (_td, mut index) = setup_test_environment();
+    let owner = blsttc::SecretKey::random();
+    let data = b"signed value".to_vec();
+    let pads = pads_with_chunk_indices(&[0, 1]);
+    let addresses: Vec<_> = pads.iter().map(|p| p.address).collect();
+
+    let proof = IntegrityProof::new("proven_key", &data, addresses.clone(), &owner);
+    assert!(proof.verify(&data, &addresses, &owner.public_key()));
+    assert!(!proof.verify(b"tampered value", &addresses, &owner.public_key()));
+    assert!(!proof.verify(&data, &addresses[..1], &owner.public_key()));
+    assert!(!proof.verify(&data, &addresses, &blsttc::SecretKey::random().public_key()));
+
+    // The proof still verifies once persisted with the index.
+    let bytes = serde_cbor::to_vec(&proof).unwrap();
+    let decoded: IntegrityProof = serde_cbor::from_slice(&bytes).unwrap();
+    assert!(decoded.verify(&data, &addresses, &owner.public_key()));
+
+    index
+        .create_key("proven_key", &data, StorageMode::Medium, false)
+        .unwrap();
+    index.set_integrity_proof(proof.clone()).unwrap();
+    assert_eq!(index.get_integrity_proof("proven_key"), Some(&proof));
+
+    index.remove_key("proven_key").unwrap();
+    assert!(index.get_integrity_proof("proven_key").is_none());
+}
+
+#[test]
+fn test_full_sync_carries_the_integrity_proofs_over() {
+    let (_td, mut remote) = setup_test_environment();
+    let owner = blsttc::SecretKey::random();
+    let data = b"signed value".to_vec();
+    let (pads, _) = remote
+        .create_key("proven_key", &data, StorageMode::Medium, false)
+        .unwrap();
+    let addresses: Vec<_> = pads.iter().map(|p| p.address).collect();
+    let proof = IntegrityProof::new("proven_key", &data, addresses.clone(), &owner);
+    remote.set_integrity_proof(proof.clone()).unwrap();
+
+    let mut fresh = MasterIndex::new_empty(NetworkChoice::Devnet);
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (1, 0));
+    assert_eq!(fresh.get_integrity_proof("proven_key"), Some(&proof));
+
+    // A newer value stored without a proof leaves no stale one behind.
+    remote.integrity_proofs.remove("proven_key");
+    if let Some(IndexEntry::PrivateKey(pads)) = remote.index.get_mut("proven_key") {
+        pads[0].last_known_counter += 1;
+    }
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (0, 1));
+    assert!(fresh.get_integrity_proof("proven_key").is_none());
+}
+
+#[test]
+fn test_memory_footprint_tracks_growth() {
+    let (_td, mut index) = setup_test_environment();
+    let empty = index.memory_footprint().unwrap();
+    assert_eq!((empty.keys, empty.pads), (0, 0));
+
+    let mut previous = empty;
+    for i in 0..3 {
+        let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+        index
+            .create_key(&format!("key_{}", i), &data, StorageMode::Medium, false)
+            .unwrap();
+
+        let footprint = index.memory_footprint().unwrap();
+        assert_eq!(footprint.keys, i + 1);
+        assert_eq!(footprint.pads, 2 * (i + 1));
+        assert!(footprint.bytes_estimate > previous.bytes_estimate);
+        assert!(footprint.serialized_bytes > previous.serialized_bytes);
+        previous = footprint;
+    }
+
+    assert_eq!(
+        previous.serialized_bytes,
+        serde_cbor::to_vec(&index).unwrap().len()
+    );
+
+    // A new key needs room for its entry and pads, rewriting a key in place does not.
+    let max = previous.serialized_bytes + 10;
+    assert!(matches!(
+        index.check_room_for("new_key", 1, max),
+        Err(crate::error::Error::Index(
+            crate::index::error::IndexError::IndexTooLarge { .. }
+        ))
+    ));
+    assert!(index.check_room_for("key_0", 2, max).is_ok());
+    assert!(index
+        .check_room_for("new_key", 1, previous.serialized_bytes * 2)
+        .is_ok());
+}
+
+#[test]
+fn test_aliases() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; 16];
+    index
+        .create_key("v2/config", &data, StorageMode::Medium, false)
+        .unwrap();
+
+    index.add_alias("v2/config", "old/path/config").unwrap();
+    // Aliasing an alias resolves to the key itself.
+    index.add_alias("old/path/config", "config").unwrap();
+    assert!(index.add_alias("missing", "other").is_err());
+    assert!(index.add_alias("v2/config", "config").is_err());
+    assert_eq!(index.resolve_alias("old/path/config"), "v2/config");
+    assert_eq!(index.resolve_alias("config"), "v2/config");
+    assert_eq!(
+        index.aliases_of("v2/config"),
+        vec!["config", "old/path/config"]
+    );
+
+    assert!(index.ensure_no_aliases("v2/config").is_err());
+    index.remove_alias("config").unwrap();
+    assert_eq!(index.resolve_alias("config"), "config");
+
+    // The data is handed over to the remaining alias, nothing is freed.
+    let pads = index.get_pads("v2/config");
+    assert_eq!(
+        index.promote_alias("v2/config").unwrap().as_deref(),
+        Some("old/path/config")
+    );
+    assert!(!index.contains_key("v2/config"));
+    assert_eq!(index.get_pads("old/path/config"), pads);
+    assert!(index.aliases_of("old/path/config").is_empty());
+
+    // A merge only keeps aliases of existing keys that no key shadows.
+    let mut other = index.clone();
+    other.add_alias("old/path/config", "legacy").unwrap();
+    other
+        .aliases
+        .insert("dangling".to_string(), "gone".to_string());
+    other
+        .aliases
+        .insert("old/path/config".to_string(), "legacy".to_string());
+    assert_eq!(index.merge_aliases(&other), 1);
+    assert_eq!(index.resolve_alias("legacy"), "old/path/config");
+    assert!(!index.is_alias("dangling"));
+    assert!(!index.is_alias("old/path/config"));
+}
+
+#[test]
+fn test_rename_key() {
+    let (_td, mut index) = setup_test_environment();
+    let owner = blsttc::SecretKey::random();
+    let data = vec![0u8; 16];
+    index
+        .create_key("draft", &data, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("other", &data, StorageMode::Medium, false)
+        .unwrap();
+    index.add_alias("draft", "latest").unwrap();
+    index
+        .add_to_collection("docs", &["draft".to_string()])
+        .unwrap();
+    let addresses: Vec<_> = index.get_pads("draft").iter().map(|p| p.address).collect();
+    let proof = IntegrityProof::new("draft", &data, addresses.clone(), &owner);
+    index.set_integrity_proof(proof.clone()).unwrap();
+    let entry = index.get_entry("draft").cloned();
+    let pad_count = index.free_pads.len() + index.pending_verification_pads.len();
+
+    assert!(matches!(
+        index.rename_key("draft", "other", &owner),
+        Err(Error::Index(IndexError::KeyAlreadyExists(_)))
+    ));
+    assert!(matches!(
+        index.rename_key("draft", "latest", &owner),
+        Err(Error::Index(IndexError::KeyAlreadyExists(_)))
+    ));
+    assert!(matches!(
+        index.rename_key("missing", "final", &owner),
+        Err(Error::Index(IndexError::KeyNotFound(_)))
+    ));
+
+    let at = chrono::Utc::now() + chrono::Duration::seconds(60);
+    index.set_config(with_clock(Clock::fixed(at)));
+    index.rename_key("draft", "final", &owner).unwrap();
+    assert!(!index.contains_key("draft"));
+    assert!(!index.modified_at.contains_key("draft"));
+    let stat = index.stat("final").unwrap();
+    assert_eq!(stat.modified_at, Some(at));
+    assert_eq!(
+        stat.details(None).modified_at_ms,
+        Some(at.timestamp_millis() as u64)
+    );
+    assert_eq!(index.get_entry("final").cloned(), entry);
+    assert_eq!(
+        index.free_pads.len() + index.pending_verification_pads.len(),
+        pad_count
+    );
+    assert_eq!(index.resolve_alias("latest"), "final");
+    assert!(index.collections["docs"].contains("final"));
+
+    // The proof follows the key, signed again as of the rename.
+    let renamed = index.get_integrity_proof("final").unwrap();
+    assert!(index.get_integrity_proof("draft").is_none());
+    assert_eq!(renamed.key, "final");
+    assert!(renamed.timestamp >= proof.timestamp);
+    assert!(renamed.verify(&data, &addresses, &owner.public_key()));
+
+    // An alias is renamed alone.
+    index.rename_key("latest", "current", &owner).unwrap();
+    assert!(!index.is_alias("latest"));
+    assert_eq!(index.resolve_alias("current"), "final");
+    assert!(index.contains_key("final"));
+}
+
+#[test]
+fn test_stat() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; 16];
+    index
+        .create_key("key", &data, StorageMode::Medium, false)
+        .unwrap();
+    index.add_alias("key", "alias").unwrap();
+
+    let stat = index.stat("alias").unwrap();
+    assert_eq!(stat.key, "key");
+    assert_eq!(stat.aliases, vec!["alias"]);
+    assert!(stat.integrity_proof.is_none());
+    let IndexEntry::PrivateKey(pads) = &stat.entry else {
+        panic!("expected a private key");
+    };
+    assert_eq!(pads.len(), index.get_pads("key").len());
+    assert!(pads.iter().all(|p| p.sk_bytes == vec![0; 32]));
+
+    assert!(index.stat("missing").is_none());
+}
+
+#[test]
+fn test_trash() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; 16];
+    index
+        .create_key("doomed", &data, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .add_to_collection("photos", &["doomed".to_string()])
+        .unwrap();
+    let pads = index.get_pads("doomed");
+
+    index.trash_key("doomed").unwrap();
+    assert!(!index.contains_key("doomed"));
+    assert!(index.is_trashed("doomed"));
+    assert!(index.list_collection("photos").unwrap().is_empty());
+    // The pads stay held by the trash, neither occupied nor free.
+    assert!(index.pad_exists(&pads[0].address));
+    assert!(index.free_pads.is_empty() && index.pending_verification_pads.is_empty());
+    let stats = index.get_storage_stats();
+    assert_eq!((stats.occupied_pads, stats.trash_pads), (0, 1));
+    assert_eq!(stats.total_pads, 1);
+    assert_eq!(stats.trash_bytes, 16);
+
+    index.restore_trashed_key("doomed").unwrap();
+    assert_eq!(index.get_pads("doomed"), pads);
+    assert_eq!(index.list_collection("photos").unwrap(), vec!["doomed"]);
+    assert!(index.restore_trashed_key("doomed").is_err());
+
+    index.trash_key("doomed").unwrap();
+    assert!(index
+        .reap_expired_trash(Duration::from_secs(3600))
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        index.reap_expired_trash(Duration::ZERO).unwrap(),
+        vec!["doomed"]
+    );
+    assert!(!index.is_trashed("doomed"));
+    assert_eq!(index.free_pads.len(), 1);
+}
+
+fn with_clock(clock: Clock) -> Arc<MutAntConfig> {
+    Arc::new(MutAntConfig {
+        clock,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_injected_clock_dates_the_trash() {
+    let (_td, mut index) = setup_test_environment();
+    let owner = blsttc::SecretKey::random();
+    let at = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    index.set_config(with_clock(Clock::fixed(at)));
+    let data = vec![0u8; 16];
+    index
+        .create_key("dated", &data, StorageMode::Medium, false)
+        .unwrap();
+    let addresses: Vec<_> = index.get_pads("dated").iter().map(|p| p.address).collect();
+    let proof = IntegrityProof::new("dated", &data, addresses, &owner);
+    index.set_integrity_proof(proof).unwrap();
+
+    index.rename_key("dated", "renamed", &owner).unwrap();
+    assert_eq!(index.get_integrity_proof("renamed").unwrap().timestamp, at);
+
+    index.trash_key("renamed").unwrap();
+    assert_eq!(index.list_trash()["renamed"].deleted_at, at);
+    index.restore_trashed_key("renamed").unwrap();
+    assert_eq!(index.restored_at["renamed"], at);
+
+    // The trash only expires as the clock moves on.
+    index.trash_key("renamed").unwrap();
+    assert!(index
+        .reap_expired_trash(Duration::from_secs(60))
+        .unwrap()
+        .is_empty());
+    index.set_config(with_clock(Clock::fixed(at + chrono::Duration::seconds(60))));
+    assert_eq!(
+        index.reap_expired_trash(Duration::from_secs(60)).unwrap(),
+        vec!["renamed"]
+    );
+}
+
+#[test]
+fn test_reset() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+    index
+        .create_key("kept", &data, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("doomed", &data[..16], StorageMode::Medium, false)
+        .unwrap();
+    index.trash_key("doomed").unwrap();
+    index.add_alias("kept", "nick").unwrap();
+
+    let summary = index.reset_summary();
+    assert_eq!((summary.key_count, summary.pad_count), (2, 3));
+
+    let snapshot = index.clone();
+    assert_eq!(index.reset(ResetMode::Harvest).unwrap(), summary);
+    assert!(index.list().is_empty());
+    assert!(index.list_trash().is_empty());
+    assert!(!index.is_alias("nick"));
+    assert_eq!(index.free_pads.len(), 3);
+    assert_eq!(index.reset_summary(), ResetSummary::default());
+
+    // Abandoned pads are gone for good, only the already free ones are left.
+    let mut index = snapshot;
+    index.free_pads.push(PadInfo::new(&[], 0));
+    assert_eq!(index.reset(ResetMode::Abandon).unwrap(), summary);
+    assert!(index.list().is_empty());
+    assert_eq!(index.free_pads.len(), 1);
+}
+
+#[test]
+fn test_trash_follows_sync() {
+    let (_td, mut a) = setup_test_environment();
+    a.create_key("shared", &[1u8; 16], StorageMode::Medium, false)
+        .unwrap();
+    a.create_key("edited", &[2u8; 16], StorageMode::Medium, false)
+        .unwrap();
+    let mut b = a.clone();
+
+    // Removed on A, the key goes to the trash of B instead of coming back to A.
+    a.trash_key("shared").unwrap();
+    assert_eq!(b.merge_trash(&a).unwrap(), (1, 0));
+    assert!(b.is_trashed("shared") && !b.contains_key("shared"));
+    assert_eq!(b.merge_trash(&a).unwrap(), (0, 0));
+
+    // Restored on B after the removal, the key comes back on A.
+    std::thread::sleep(Duration::from_millis(5));
+    b.restore_trashed_key("shared").unwrap();
+    assert_eq!(a.merge_trash(&b).unwrap(), (0, 1));
+    assert!(a.contains_key("shared") && !a.is_trashed("shared"));
+    assert_eq!(b.merge_trash(&a).unwrap(), (0, 0));
+    assert_eq!(a.merge_trash(&b).unwrap(), (0, 0));
+
+    // Written on B after it was removed on A, the key stays live on B.
+    a.trash_key("edited").unwrap();
+    if let Some(IndexEntry::PrivateKey(pads)) = b.index.get_mut("edited") {
+        pads[0].last_known_counter += 1;
+    }
+    assert_eq!(b.merge_trash(&a).unwrap(), (0, 0));
+    assert!(b.contains_key("edited"));
+
+    // An index that never held the key keeps its pads reserved in the trash.
+    let mut fresh = MasterIndex::new_empty(NetworkChoice::Devnet);
+    assert_eq!(fresh.merge_trash(&a).unwrap(), (0, 0));
+    assert!(fresh.is_trashed("edited"));
+    assert_eq!(fresh.get_storage_stats().trash_pads, 1);
+}
+
+#[test]
+fn test_envelope_size_bookkeeping() {
+    let (_td, mut index) = setup_test_environment();
+    let header = crate::ops::EnvelopeHeader::new("text/plain", "1.0.0");
+    let enveloped = header.wrap(b"hello").unwrap();
+    let plain = b"MTEV but not enveloped";
+    index
+        .create_key("wrapped", &enveloped, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("plain", plain, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .set_envelope_size("wrapped", header.encoded_len())
+        .unwrap();
+
+    assert_eq!(index.envelope_size("wrapped"), Some(header.encoded_len()));
+    assert_eq!(index.data_size("wrapped"), Some(5));
+    assert_eq!(index.envelope_size("plain"), None);
+    assert_eq!(index.data_size("plain"), Some(plain.len()));
+    assert_eq!(index.data_size("missing"), None);
+
+    index.trash_key("wrapped").unwrap();
+    assert_eq!(index.envelope_size("wrapped"), None);
+    index.restore_trashed_key("wrapped").unwrap();
+    assert_eq!(index.data_size("wrapped"), Some(5));
+
+    // Storing the key again without an envelope forgets it.
+    index.set_envelope_size("wrapped", 0).unwrap();
+    assert_eq!(index.data_size("wrapped"), Some(enveloped.len()));
+
+    index
+        .set_envelope_size("wrapped", header.encoded_len())
+        .unwrap();
+    index.remove_key("wrapped").unwrap();
+    assert_eq!(index.envelope_size("wrapped"), None);
+}
+
+#[test]
+fn test_full_sync_carries_the_envelopes_over() {
+    let (_td, mut remote) = setup_test_environment();
+    let header = crate::ops::EnvelopeHeader::new("text/plain", "1.0.0");
+    let enveloped = header.wrap(b"hello").unwrap();
+    remote
+        .create_key("wrapped", &enveloped, StorageMode::Medium, false)
+        .unwrap();
+    remote
+        .set_envelope_size("wrapped", header.encoded_len())
+        .unwrap();
+
+    let mut fresh = MasterIndex::new_empty(NetworkChoice::Devnet);
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (1, 0));
+    assert_eq!(fresh.envelope_size("wrapped"), Some(header.encoded_len()));
+    assert_eq!(fresh.data_size("wrapped"), Some(5));
+}
+
+#[test]
+fn test_transforms_bookkeeping() {
+    let (_td, mut index) = setup_test_environment();
+    let mut transforms = crate::ops::Transforms::default();
+    transforms.set_pipeline(vec![std::sync::Arc::new(
+        crate::ops::PadToSizeTransform::new(64),
+    )]);
+    let (encoded, recorded) = transforms.encode(b"hello").unwrap();
+    index
+        .create_key("padded", &encoded, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .set_transforms("padded", Some(recorded.clone()))
+        .unwrap();
+
+    assert_eq!(index.transforms("padded"), Some(&recorded));
+    assert_eq!(index.data_size("padded"), Some(5));
+
+    index.trash_key("padded").unwrap();
+    assert_eq!(index.transforms("padded"), None);
+    index.restore_trashed_key("padded").unwrap();
+    assert_eq!(index.transforms("padded"), Some(&recorded));
+
+    let owner = blsttc::SecretKey::random();
+    index.rename_key("padded", "renamed", &owner).unwrap();
+    assert_eq!(index.transforms("padded"), None);
+    assert_eq!(index.data_size("renamed"), Some(5));
+
+    // Storing the key again without transforms forgets them.
+    index.set_transforms("renamed", None).unwrap();
+    assert_eq!(index.data_size("renamed"), Some(encoded.len()));
+
+    index.set_transforms("renamed", Some(recorded)).unwrap();
+    index.remove_key("renamed").unwrap();
+    assert_eq!(index.transforms("renamed"), None);
+}
+
+#[test]
+fn test_full_sync_carries_the_transforms_over() {
+    let (_td, mut remote) = setup_test_environment();
+    let mut transforms = crate::ops::Transforms::default();
+    transforms.set_pipeline(vec![std::sync::Arc::new(
+        crate::ops::PadToSizeTransform::new(64),
+    )]);
+    let (encoded, recorded) = transforms.encode(b"hello").unwrap();
+    remote
+        .create_key("padded", &encoded, StorageMode::Medium, false)
+        .unwrap();
+    remote
+        .set_transforms("padded", Some(recorded.clone()))
+        .unwrap();
+
+    let mut fresh = MasterIndex::new_empty(NetworkChoice::Devnet);
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (1, 0));
+    let synced = fresh.transforms("padded").unwrap();
+    assert_eq!(transforms.decode(encoded, synced).unwrap(), b"hello");
+    assert_eq!(fresh.data_size("padded"), Some(5));
+
+    // A newer value stored without transforms drops the ones recorded here.
+    remote.set_transforms("padded", None).unwrap();
+    if let Some(IndexEntry::PrivateKey(pads)) = remote.index.get_mut("padded") {
+        pads[0].last_known_counter += 1;
+    }
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (0, 1));
+    assert_eq!(fresh.transforms("padded"), None);
+}
+
+#[test]
+fn test_full_sync_carries_the_final_chunk_padding_over() {
+    let (_td, mut remote) = setup_test_environment();
+    let data = vec![4u8; DEFAULT_SCRATCHPAD_SIZE];
+    remote
+        .create_key("padded", &data, StorageMode::Medium, false)
+        .unwrap();
+    remote.set_final_chunk_padding("padded", 100).unwrap();
+
+    let mut fresh = MasterIndex::new_empty(NetworkChoice::Devnet);
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (1, 0));
+    // Stamped again on the pads, as an update does, the padding is kept.
+    fresh.stamp_final_chunk_padding("padded");
+    assert_eq!(fresh.get_pads("padded")[0].padding, 100);
+    assert_eq!(fresh.data_size("padded"), Some(DEFAULT_SCRATCHPAD_SIZE - 100));
+}
+
+#[test]
+fn test_quota() {
+    let (_td, mut index) = setup_test_environment();
+    let data = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2];
+    index
+        .create_key("existing", &data, StorageMode::Medium, false)
+        .unwrap();
+    let stats = index.get_storage_stats();
+    assert_eq!(
+        (stats.used_pads(), stats.stored_bytes),
+        (2, data.len() as u64)
+    );
+
+    let quota = Quota {
+        max_total_pads: Some(3),
+        ..Default::default()
+    };
+    assert!(index
+        .check_quota_for("new_key", DEFAULT_SCRATCHPAD_SIZE, 1, &quota)
+        .is_ok());
+    assert!(matches!(
+        index.check_quota_for("new_key", DEFAULT_SCRATCHPAD_SIZE * 2, 2, &quota),
+        Err(crate::error::Error::QuotaExceeded {
+            resource: QuotaResource::Pads,
+            limit: 3,
+            current: 2,
+            requested: 2,
+        })
+    ));
+    // Rewriting a key reuses its own pads.
+    assert!(index
+        .check_quota_for("existing", DEFAULT_SCRATCHPAD_SIZE * 3, 3, &quota)
+        .is_ok());
+
+    // Free pads are already paid for when exempt.
+    index.remove_key("existing").unwrap();
+    let quota = Quota {
+        max_total_pads: Some(1),
+        exempt_free_pads: true,
+        ..Default::default()
+    };
+    assert!(index
+        .check_quota_for("new_key", DEFAULT_SCRATCHPAD_SIZE * 3, 3, &quota)
+        .is_ok());
+
+    let quota = Quota {
+        max_total_bytes: Some(10),
+        ..Default::default()
+    };
+    assert!(index.check_quota_for("new_key", 10, 1, &quota).is_ok());
+    assert!(matches!(
+        index.check_quota_for("new_key", 11, 1, &quota),
+        Err(crate::error::Error::QuotaExceeded {
+            resource: QuotaResource::Bytes,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_acquire_streamed_pad_checks_the_quota() {
+    let (_td, mut index) = setup_test_environment();
+    index.set_config(Arc::new(MutAntConfig {
+        quota: Quota {
+            max_total_pads: Some(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    }));
+    let chunk = vec![7u8; 16];
+
+    let first = index
+        .acquire_streamed_pad("streamed", &chunk, 0, chunk.len())
+        .unwrap();
+    assert_eq!((first.chunk_index, first.size), (0, chunk.len()));
+    assert_eq!(first.checksum, PadInfo::checksum(&chunk));
+    let second = index
+        .acquire_streamed_pad("streamed", &chunk, 1, chunk.len() * 2)
+        .unwrap();
+    assert_eq!(second.chunk_index, 1);
+    assert_ne!(second.address, first.address);
+
+    assert!(matches!(
+        index.acquire_streamed_pad("streamed", &chunk, 2, chunk.len() * 3),
+        Err(crate::error::Error::QuotaExceeded {
+            resource: QuotaResource::Pads,
+            requested: 3,
+            ..
+        })
+    ));
+    assert!(!index.contains_key("streamed"));
+}
+
+#[test]
+fn test_public_catalogue() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .create_key("private", &[1u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("public", &[2u8; 10], StorageMode::Medium, true)
+        .unwrap();
+    index.set_envelope_size("public", 4).unwrap();
+
+    let catalogue = index.export_public_catalogue();
+    assert_eq!(catalogue.keys.len(), 1);
+    assert_eq!(catalogue.envelopes.get("public"), Some(&4));
+    let Some(IndexEntry::PublicUpload(index_pad, pads)) = catalogue.keys.get("public") else {
+        panic!("public key missing from the catalogue");
+    };
+    assert!(index_pad.sk_bytes.is_empty());
+    assert!(pads.iter().all(|pad| pad.sk_bytes.is_empty()));
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    catalogue.save(file.path()).unwrap();
+    let loaded = PublicCatalogue::load(file.path()).unwrap();
+    assert_eq!(loaded, catalogue);
+
+    let mut read_only = MasterIndex::from_public_catalogue(loaded).unwrap();
+    assert!(read_only.is_read_only());
+    assert!(read_only.contains_key("public"));
+    assert!(read_only.is_public("public"));
+    assert!(!read_only.contains_key("private"));
+    assert_eq!(read_only.get_pads("public").len(), pads.len());
+    assert_eq!(read_only.envelope_size("public"), Some(4));
+
+    assert!(matches!(
+        read_only.flush(),
+        Err(crate::error::Error::ReadOnlyMode(_))
+    ));
+    assert!(read_only
+        .create_key("new", &[3u8; 10], StorageMode::Medium, true)
+        .is_err());
+}
+
+#[test]
+fn test_public_catalogue_rejects_private_keys() {
+    let (_td, mut index) = setup_test_environment();
+    index
+        .create_key("private", &[1u8; 10], StorageMode::Medium, false)
+        .unwrap();
+
+    let mut catalogue = index.export_public_catalogue();
+    assert!(catalogue.keys.is_empty());
+    catalogue.keys = index.list();
+    assert!(matches!(
+        MasterIndex::from_public_catalogue(catalogue),
+        Err(crate::error::Error::Index(
+            crate::index::error::IndexError::InvalidCatalogue(_)
+        ))
+    ));
+
+    let missing = std::env::temp_dir().join("mutant-missing-catalogue.json");
+    assert!(matches!(
+        PublicCatalogue::load(&missing),
+        Err(crate::error::Error::Index(
+            crate::index::error::IndexError::IndexFileNotFound(_)
+        ))
+    ));
+}
+
+#[test]
+fn test_merge_selected_keys() {
+    let (_td, mut local) = setup_test_environment();
+    local
+        .create_key("project-a/one", &[1u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    local
+        .create_key("other", &[2u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    local.set_envelope_size("project-a/one", 4).unwrap();
+
+    let mut remote = MasterIndex::new_empty(NetworkChoice::Devnet);
+    // The remote still holds the pads of the key as free, and a pad of its own.
+    let own_pads = pads_with_chunk_indices(&[9]);
+    remote.free_pads = local.get_pads("project-a/one");
+    remote.free_pads.extend(own_pads.clone());
+
+    let selected = local.select_keys(&["project-a/*".to_string()]);
+    assert_eq!(selected.len(), 1);
+    assert_eq!(remote.merge_selected(&local, &selected), (1, 0));
+
+    assert!(remote.contains_key("project-a/one"));
+    assert!(!remote.contains_key("other"));
+    assert_eq!(remote.envelope_size("project-a/one"), Some(4));
+    assert_eq!(remote.free_pads, own_pads);
+
+    // Merging again changes nothing, the remote entry is as recent as the local one.
+    assert_eq!(remote.merge_selected(&local, &selected), (0, 0));
+}
+
+/// What the put pipeline stores at each address: the pads and chunk ranges are numbered
+/// by slot, and each pad gets the range at its chunk index.
+fn write_pads(
+    index: &MasterIndex,
+    key_name: &str,
+    pads: Vec<PadInfo>,
+    chunk_ranges: &[std::ops::Range<usize>],
+    data: &[u8],
+) -> std::collections::HashMap<autonomi::ScratchpadAddress, Vec<u8>> {
+    let pads = index.to_slot_order(key_name, pads);
+    let chunk_ranges = index.ranges_in_slot_order(key_name, chunk_ranges.to_vec());
+    pads.iter()
+        .map(|pad| {
+            let chunk = &data[chunk_ranges[pad.chunk_index].clone()];
+            (pad.address, chunk.to_vec())
+        })
+        .collect()
+}
+
+/// The value a get reassembles from the pads sorted by chunk index.
+fn read_pads(
+    index: &MasterIndex,
+    key_name: &str,
+    written: &std::collections::HashMap<autonomi::ScratchpadAddress, Vec<u8>>,
+) -> Vec<u8> {
+    let mut pads = index.get_pads(key_name);
+    pads.sort_by_key(|pad| pad.chunk_index);
+    pads.iter()
+        .flat_map(|pad| written[&pad.address].clone())
+        .collect()
+}
+
+#[test]
+fn test_shuffled_chunks_round_trip() {
+    let (_td, mut index) = setup_test_environment();
+    let mode = StorageMode::Lightest;
+    let chunk_size = mode.scratchpad_size();
+    let data: Vec<u8> = (0..8u8)
+        .flat_map(|chunk| vec![chunk; chunk_size])
+        .chain([8u8; 10])
+        .collect();
+    let (pads, chunk_ranges) = index
+        .create_key("shuffled", &data, mode.clone(), false)
+        .unwrap();
+    index.shuffle_chunks("shuffled").unwrap();
+
+    // Each pad is recorded under its slot, and read back in chunk order.
+    let permutation = index.chunk_permutation("shuffled").unwrap().to_vec();
+    let Some(IndexEntry::PrivateKey(recorded)) = index.index.get("shuffled") else {
+        panic!("Key not found or not a PrivateKey entry");
+    };
+    for (chunk, pad) in pads.iter().enumerate() {
+        assert_eq!(recorded[permutation[chunk]].address, pad.address);
+        assert_eq!(recorded[permutation[chunk]].chunk_index, permutation[chunk]);
+    }
+    assert_eq!(index.get_pads("shuffled"), pads);
+    assert!(index.verify_checksum("shuffled", &data, mode.clone()));
+
+    let written = write_pads(&index, "shuffled", pads.clone(), &chunk_ranges, &data);
+    assert_eq!(read_pads(&index, "shuffled", &written), data);
+
+    // Dropping chunks draws a new permutation of the remaining ones.
+    let shorter = &data[..5 * chunk_size];
+    index
+        .update_key_with_pads("shuffled", pads[..5].to_vec(), None)
+        .unwrap();
+    assert_eq!(index.chunk_permutation("shuffled").unwrap().len(), 5);
+    assert_eq!(index.get_pads("shuffled"), pads[..5].to_vec());
+
+    let written = write_pads(
+        &index,
+        "shuffled",
+        pads[..5].to_vec(),
+        &chunk_ranges[..5],
+        shorter,
+    );
+    assert_eq!(read_pads(&index, "shuffled", &written), shorter);
+
+    // The permutation follows the key to the trash and back.
+    index.trash_key("shuffled").unwrap();
+    assert!(index.chunk_permutation("shuffled").is_none());
+    index.restore_trashed_key("shuffled").unwrap();
+    assert_eq!(index.get_pads("shuffled"), pads[..5].to_vec());
+}
+
+#[test]
+fn test_shuffled_chunks_follow_a_full_sync() {
+    let (_td, mut remote) = setup_test_environment();
+    let mode = StorageMode::Lightest;
+    let chunk_size = mode.scratchpad_size();
+    let data: Vec<u8> = (0..8u8).flat_map(|chunk| vec![chunk; chunk_size]).collect();
+    let (pads, chunk_ranges) = remote.create_key("shuffled", &data, mode, false).unwrap();
+    remote.shuffle_chunks("shuffled").unwrap();
+    let written = write_pads(&remote, "shuffled", pads, &chunk_ranges, &data);
+
+    let mut fresh = MasterIndex::new_empty(NetworkChoice::Devnet);
+    assert_eq!(fresh.merge_entries(&remote).unwrap(), (1, 0));
+    assert_eq!(
+        fresh.chunk_permutation("shuffled"),
+        remote.chunk_permutation("shuffled")
+    );
+    assert_eq!(read_pads(&fresh, "shuffled", &written), data);
+}
+
+#[test]
+fn test_check_pad_sizes_boundaries() {
+    use mutant_protocol::MAX_SCRATCHPAD_SIZE;
+
+    let (_td, mut index) = setup_test_environment();
+    index
+        .create_key("key", &[0u8; 10], StorageMode::Medium, false)
+        .unwrap();
+    let set_pad = |index: &mut MasterIndex, size: usize, padding: usize| {
+        let Some(IndexEntry::PrivateKey(pads)) = index.index.get_mut("key") else {
+            panic!("key is missing");
+        };
+        pads[0].size = size;
+        pads[0].padding = padding;
+    };
+    assert_eq!(index.check_pad_sizes(), Ok(()));
+
+    set_pad(&mut index, MAX_SCRATCHPAD_SIZE, MAX_SCRATCHPAD_SIZE);
+    assert_eq!(index.check_pad_sizes(), Ok(()));
+
+    set_pad(&mut index, MAX_SCRATCHPAD_SIZE + 1, 0);
+    assert!(matches!(
+        index.check_pad_sizes(),
+        Err(IndexError::InvalidPadSize { owner, .. }) if owner == "key"
+    ));
+
+    set_pad(&mut index, 10, 11);
+    assert!(matches!(
+        index.check_pad_sizes(),
+        Err(IndexError::InvalidPadSize {
+            size: 10,
+            padding: 11,
+            ..
+        })
+    ));
+
+    // A corrupted free pad is caught too.
+    set_pad(&mut index, 10, 0);
+    let mut free_pad = index.get_pads("key")[0].clone();
+    free_pad.size = usize::MAX;
+    index.free_pads.push(free_pad);
+    assert!(matches!(
+        index.check_pad_sizes(),
+        Err(IndexError::InvalidPadSize { owner, .. }) if owner == "the free pads"
+    ));
+}
+
+#[test]
+fn test_completion_percentage_of_huge_keys() {
+    use super::key_management::completion_percentage;
+
+    assert_eq!(completion_percentage(0, 0), 0);
+    assert_eq!(completion_percentage(1, 3), 33);
+    // Past 2^24 pads an f32 ratio rounds these to 100.
+    assert_eq!(completion_percentage(16_777_216, 16_777_217), 99);
+    assert_eq!(completion_percentage(16_777_217, 16_777_217), 100);
+    assert_eq!(completion_percentage(usize::MAX - 1, usize::MAX), 99);
+    assert_eq!(completion_percentage(usize::MAX, usize::MAX), 100);
+    assert_eq!(completion_percentage(5, 4), 100);
+}
+
+mod prop {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn arb_pad() -> impl Strategy<Value = PadInfo> {
+        (any::<usize>(), any::<usize>(), any::<bool>()).prop_map(|(size, padding, confirmed)| {
+            let mut pad = PadInfo::new(&[], 0);
+            pad.size = size;
+            pad.padding = padding;
+            if confirmed {
+                pad.status = PadStatus::Confirmed;
+            }
+            pad
+        })
+    }
+
+    fn arb_entry() -> impl Strategy<Value = IndexEntry> {
+        prop_oneof![
+            proptest::collection::vec(arb_pad(), 0..6).prop_map(IndexEntry::PrivateKey),
+            (arb_pad(), proptest::collection::vec(arb_pad(), 0..6))
+                .prop_map(|(index_pad, pads)| IndexEntry::PublicUpload(index_pad, pads)),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_stats_of_any_index_do_not_overflow(
+            entries in proptest::collection::vec(arb_entry(), 0..6),
+            trashed in proptest::collection::vec(arb_entry(), 0..3),
+            free_pads in proptest::collection::vec(arb_pad(), 0..4),
+        ) {
+            let mut index = MasterIndex::new_empty(NetworkChoice::Devnet);
+            for (i, entry) in entries.iter().enumerate() {
+                index.index.insert(format!("key{}", i), entry.clone());
+            }
+            for (i, entry) in trashed.into_iter().enumerate() {
+                index.trash.insert(
+                    format!("trashed{}", i),
+                    TrashedKey {
+                        entry,
+                        deleted_at: chrono::Utc::now(),
+                        final_chunk_padding: 0,
+                        envelope_size: 0,
+                        transforms: None,
+                        collections: Vec::new(),
+                        integrity_proof: None,
+                        chunk_permutation: None,
+                    },
+                );
+            }
+            index.free_pads = free_pads;
+
+            let stats = index.get_storage_stats();
+            prop_assert_eq!(
+                stats.total_pads,
+                stats.used_pads() + stats.free_pads + stats.pending_verification_pads
+            );
+            prop_assert!(stats.stored_bytes >= stats.trash_bytes);
+
+            for (i, entry) in entries.into_iter().enumerate() {
+                let details = key_details(format!("key{}", i), entry, Vec::new(), None, None);
+                prop_assert!(details.completion_percentage <= 100);
+                prop_assert!(details.confirmed_pads <= details.pad_count);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_storage_stats_count_wasted_space() {
+    let (_td, mut index) = setup_test_environment();
+    let complete = vec![0u8; DEFAULT_SCRATCHPAD_SIZE + 100];
+    let incomplete = vec![0u8; DEFAULT_SCRATCHPAD_SIZE * 2 + 10];
+    index
+        .create_key("complete", &complete, StorageMode::Medium, false)
+        .unwrap();
+    index
+        .create_key("incomplete", &incomplete, StorageMode::Medium, false)
+        .unwrap();
+    for pad in index.get_pads("complete") {
+        index
+            .update_pad_status("complete", &pad.address, PadStatus::Confirmed, None)
+            .unwrap();
+    }
+    let first = index.get_pads("incomplete")[0].address;
+    index
+        .update_pad_status("incomplete", &first, PadStatus::Confirmed, None)
+        .unwrap();
+
+    // Every confirmed pad takes a whole scratchpad, the final partial chunk included.
+    let pad = MAX_SCRATCHPAD_SIZE as u64;
+    let confirmed = (DEFAULT_SCRATCHPAD_SIZE * 2 + 100) as u64;
+    let stats = index.get_storage_stats();
+    assert_eq!(stats.confirmed_bytes, confirmed);
+    assert_eq!(stats.wasted_bytes, pad * 3 - confirmed);
+
+    let details = index.stat("complete").unwrap().details(None);
+    assert_eq!(details.confirmed_bytes, complete.len() as u64);
+    assert_eq!(details.wasted_bytes, pad * 2 - complete.len() as u64);
+    let details = index.stat("incomplete").unwrap().details(None);
+    assert_eq!(details.confirmed_bytes, DEFAULT_SCRATCHPAD_SIZE as u64);
+    assert_eq!(details.wasted_bytes, pad - DEFAULT_SCRATCHPAD_SIZE as u64);
+}
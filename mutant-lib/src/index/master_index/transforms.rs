@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+
+use super::MasterIndex;
+
+/// One transform a value went through, as named by `ChunkTransform::id`, with the
+/// settings it was applied with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransformSpec {
+    pub id: String,
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+/// The transforms the value of a key went through before being chunked, in the order
+/// they were applied.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyTransforms {
+    pub steps: Vec<TransformSpec>,
+    /// Size of the value before the transforms, envelope header included.
+    pub data_size: usize,
+}
+
+impl MasterIndex {
+    /// Records the transforms the value of `key_name` went through, `None` meaning it
+    /// is stored as it was given.
+    pub fn set_transforms(
+        &mut self,
+        key_name: &str,
+        transforms: Option<KeyTransforms>,
+    ) -> Result<(), Error> {
+        let changed = match transforms {
+            Some(transforms) => {
+                self.transforms
+                    .insert(key_name.to_string(), transforms.clone())
+                    != Some(transforms)
+            }
+            None => self.transforms.remove(key_name).is_some(),
+        };
+        if !changed {
+            return Ok(());
+        }
+
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// The transforms of `key_name`, `None` if it was stored without any.
+    pub fn transforms(&self, key_name: &str) -> Option<&KeyTransforms> {
+        self.transforms.get(key_name)
+    }
+}
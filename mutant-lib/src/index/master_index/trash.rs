@@ -0,0 +1,243 @@
+use crate::error::Error;
+use crate::index::error::IndexError;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use super::selection::{entry_addresses, entry_counter};
+use super::{IndexEntry, IntegrityProof, KeyTransforms, MasterIndex};
+
+/// A removed key whose pads stay reserved until it is restored or reaped.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrashedKey {
+    pub entry: IndexEntry,
+    pub deleted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub final_chunk_padding: usize,
+    /// Length of the envelope header of the value, `0` if it has none.
+    #[serde(default)]
+    pub envelope_size: usize,
+    /// Transforms the value went through, if any.
+    #[serde(default)]
+    pub transforms: Option<KeyTransforms>,
+    /// Collections the key belonged to.
+    #[serde(default)]
+    pub collections: Vec<String>,
+    #[serde(default)]
+    pub integrity_proof: Option<IntegrityProof>,
+    /// Slot of each chunk, for a key stored with its chunks shuffled.
+    #[serde(default)]
+    pub chunk_permutation: Option<Vec<usize>>,
+}
+
+impl MasterIndex {
+    /// Moves `key_name` to the trash. Its pads are neither freed nor reused until the
+    /// key is reaped, a previous key of the same name in the trash is reaped right away.
+    pub fn trash_key(&mut self, key_name: &str) -> Result<(), Error> {
+        let Some(entry) = self.index.remove(key_name) else {
+            return Err(IndexError::KeyNotFound(key_name.to_string()).into());
+        };
+        if let Some(previous) = self.trash.remove(key_name) {
+            self.release_pads(key_name, previous.entry);
+        }
+
+        let collections = self
+            .collections
+            .iter_mut()
+            .filter_map(|(name, keys)| keys.remove(key_name).then(|| name.clone()))
+            .collect();
+        let trashed = TrashedKey {
+            entry,
+            deleted_at: self.config.clock.now(),
+            final_chunk_padding: self.final_chunk_padding.remove(key_name).unwrap_or(0),
+            envelope_size: self.envelopes.remove(key_name).unwrap_or(0),
+            transforms: self.transforms.remove(key_name),
+            collections,
+            integrity_proof: self.integrity_proofs.remove(key_name),
+            chunk_permutation: self.chunk_permutations.remove(key_name),
+        };
+        self.restoring_keys.remove(key_name);
+        self.file_records.remove(key_name);
+        self.restored_at.remove(key_name);
+        self.modified_at.remove(key_name);
+        self.trash.insert(key_name.to_string(), trashed);
+
+        info!("Moved key {} to the trash", key_name);
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    /// Puts a key of the trash back in place, with its collections and integrity proof.
+    pub fn restore_trashed_key(&mut self, key_name: &str) -> Result<(), Error> {
+        if self.contains_key(key_name) || self.is_alias(key_name) {
+            return Err(IndexError::KeyAlreadyExists(key_name.to_string()).into());
+        }
+        let Some(trashed) = self.trash.remove(key_name) else {
+            return Err(IndexError::NotInTrash(key_name.to_string()).into());
+        };
+
+        self.index.insert(key_name.to_string(), trashed.entry);
+        if trashed.final_chunk_padding > 0 {
+            self.final_chunk_padding
+                .insert(key_name.to_string(), trashed.final_chunk_padding);
+        }
+        if trashed.envelope_size > 0 {
+            self.envelopes
+                .insert(key_name.to_string(), trashed.envelope_size);
+        }
+        if let Some(transforms) = trashed.transforms {
+            self.transforms.insert(key_name.to_string(), transforms);
+        }
+        for name in trashed.collections {
+            // Collections removed in the meantime stay removed.
+            if let Some(keys) = self.collections.get_mut(&name) {
+                keys.insert(key_name.to_string());
+            }
+        }
+        if let Some(proof) = trashed.integrity_proof {
+            self.integrity_proofs.insert(key_name.to_string(), proof);
+        }
+        if let Some(permutation) = trashed.chunk_permutation {
+            self.chunk_permutations
+                .insert(key_name.to_string(), permutation);
+        }
+        self.restored_at
+            .insert(key_name.to_string(), self.config.clock.now());
+
+        info!("Restored key {} from the trash", key_name);
+        self.save(self.network_choice)?;
+        Ok(())
+    }
+
+    pub fn is_trashed(&self, key_name: &str) -> bool {
+        self.trash.contains_key(key_name)
+    }
+
+    pub fn list_trash(&self) -> BTreeMap<String, TrashedKey> {
+        self.trash.clone()
+    }
+
+    /// Frees the pads of the keys that have been in the trash for `retention` or more.
+    /// Returns the reaped keys.
+    pub fn reap_expired_trash(&mut self, retention: Duration) -> Result<Vec<String>, Error> {
+        let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+        let now = self.config.clock.now();
+        let expired: Vec<String> = self
+            .trash
+            .iter()
+            .filter(|(_, trashed)| now.signed_duration_since(trashed.deleted_at) >= retention)
+            .map(|(key, _)| key.clone())
+            .collect();
+        if expired.is_empty() {
+            return Ok(expired);
+        }
+
+        for key in &expired {
+            if let Some(trashed) = self.trash.remove(key) {
+                self.release_pads(key, trashed.entry);
+            }
+        }
+
+        info!("Reaped {} keys from the trash", expired.len());
+        self.save(self.network_choice)?;
+
+        Ok(expired)
+    }
+
+    /// Merges the trash of `remote` into this index, so that a key removed on one
+    /// machine is not brought back by another one and a key restored on one machine
+    /// comes back on the others. The latest of a removal and a restoration wins:
+    ///
+    /// - a key in the trash of `remote` and live here goes to the trash, unless it was
+    ///   restored here after the removal or holds other pads or a newer write than the
+    ///   removed entry;
+    /// - a key in the trash here, live on `remote` and restored there after the removal
+    ///   is restored, the merge of the keys then brings its newer writes if any;
+    /// - a key in both trashes keeps the latest removal time, a key in the trash of
+    ///   `remote` only is added to this trash so its pads stay reserved, unless a pad of
+    ///   it is held by another key here.
+    ///
+    /// Returns the number of live keys moved to the trash and of keys restored.
+    pub fn merge_trash(&mut self, remote: &MasterIndex) -> Result<(usize, usize), Error> {
+        let mut trashed = 0;
+        let mut restored = 0;
+        let mut changed = false;
+
+        for (key_name, removed) in &remote.trash {
+            if let Some(local) = self.trash.get_mut(key_name) {
+                if removed.deleted_at > local.deleted_at {
+                    local.deleted_at = removed.deleted_at;
+                    changed = true;
+                }
+                continue;
+            }
+
+            match self.index.get(key_name) {
+                Some(entry) => {
+                    let restored_later = self
+                        .restored_at
+                        .get(key_name)
+                        .is_some_and(|at| *at > removed.deleted_at);
+                    let written_later = entry_addresses(entry) != entry_addresses(&removed.entry)
+                        || entry_counter(entry) > entry_counter(&removed.entry);
+                    if restored_later || written_later {
+                        continue;
+                    }
+                    self.trash_key(key_name)?;
+                    if let Some(local) = self.trash.get_mut(key_name) {
+                        local.deleted_at = removed.deleted_at;
+                    }
+                    trashed += 1;
+                    changed = true;
+                }
+                None => {
+                    let held: HashSet<_> = self
+                        .index
+                        .values()
+                        .chain(self.trash.values().map(|trashed| &trashed.entry))
+                        .flat_map(entry_addresses)
+                        .collect();
+                    let addresses: HashSet<_> =
+                        entry_addresses(&removed.entry).into_iter().collect();
+                    if !addresses.is_disjoint(&held) {
+                        continue;
+                    }
+                    self.free_pads
+                        .retain(|pad| !addresses.contains(&pad.address));
+                    self.pending_verification_pads
+                        .retain(|pad| !addresses.contains(&pad.address));
+                    self.trash.insert(key_name.clone(), removed.clone());
+                    changed = true;
+                }
+            }
+        }
+
+        let restored_remotely: Vec<(String, DateTime<Utc>)> = self
+            .trash
+            .iter()
+            .filter(|(key_name, _)| {
+                !remote.trash.contains_key(*key_name) && remote.index.contains_key(*key_name)
+            })
+            .filter_map(|(key_name, local)| {
+                let at = *remote.restored_at.get(key_name)?;
+                (at > local.deleted_at).then(|| (key_name.clone(), at))
+            })
+            .collect();
+        for (key_name, at) in restored_remotely {
+            if self.contains_key(&key_name) {
+                continue;
+            }
+            self.restore_trashed_key(&key_name)?;
+            self.restored_at.insert(key_name, at);
+            restored += 1;
+            changed = true;
+        }
+
+        if changed {
+            self.save(self.network_choice)?;
+        }
+        Ok((trashed, restored))
+    }
+}
@@ -1,6 +1,7 @@
 use crate::config::NetworkChoice;
 use crate::error::Error;
 use crate::index::error::IndexError;
+use crate::ops::utils::app_id_tag;
 use std::fs;
 use std::path::PathBuf;
 use xdg::BaseDirectories;
@@ -15,13 +16,20 @@ pub fn get_mutant_data_dir() -> Result<PathBuf, Error> {
     Ok(data_dir)
 }
 
-// Helper function to get the full path for the index file
-pub fn get_index_file_path(network_choice: NetworkChoice) -> Result<PathBuf, Error> {
+// Helper function to get the full path for the index file, one per application id
+pub fn get_index_file_path(
+    network_choice: NetworkChoice,
+    app_id: Option<&str>,
+) -> Result<PathBuf, Error> {
     let data_dir = get_mutant_data_dir()?;
     let filename = match network_choice {
         NetworkChoice::Mainnet => "master_index_mainnet.cbor",
         NetworkChoice::Devnet => "master_index_devnet.cbor",
         NetworkChoice::Alphanet => "master_index_alphanet.cbor",
     };
-    Ok(data_dir.join(filename))
+    let path = data_dir.join(filename);
+    Ok(match app_id {
+        Some(app_id) => path.with_extension(format!("app-{}.cbor", app_id_tag(app_id))),
+        None => path,
+    })
 }
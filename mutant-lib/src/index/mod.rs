@@ -1,5 +1,7 @@
 pub mod error;
+pub(crate) mod lazy;
 pub mod master_index;
 pub mod pad_info;
 
+pub(crate) use lazy::LazyIndex;
 pub(crate) use pad_info::{PadInfo, PadStatus};
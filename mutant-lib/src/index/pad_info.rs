@@ -45,6 +45,10 @@ pub struct PadInfo {
 
     /// The checksum of unencrypted data stored on the pad.
     pub checksum: usize,
+
+    /// Number of filler bytes appended to the chunk to hide the real size of the value.
+    #[serde(default)]
+    pub padding: usize,
 }
 
 impl PadInfo {
@@ -59,7 +63,12 @@ impl PadInfo {
 
 impl PadInfo {
     pub fn new(data: &[u8], chunk_index: usize) -> Self {
-        let secret_key = SecretKey::random();
+        let secret_key = crate::rng::random_secret_key(&mut rand::thread_rng());
+        Self::with_secret_key(&secret_key, data, chunk_index)
+    }
+
+    /// A new pad holding `data` at the address of `secret_key`.
+    pub fn with_secret_key(secret_key: &SecretKey, data: &[u8], chunk_index: usize) -> Self {
         let sk_bytes = secret_key.to_bytes().to_vec();
         let address = ScratchpadAddress::new(secret_key.public_key());
         Self {
@@ -70,6 +79,7 @@ impl PadInfo {
             last_known_counter: 0,
             chunk_index,
             checksum: Self::checksum(data),
+            padding: 0,
         }
     }
 
@@ -78,6 +88,7 @@ impl PadInfo {
         self.checksum = Self::checksum(data);
         self.last_known_counter += 1;
         self.chunk_index = chunk_index;
+        self.padding = 0;
         self
     }
 
@@ -85,6 +96,12 @@ impl PadInfo {
         self.status = status;
     }
 
+    /// Drops the filler bytes from the content fetched from this pad.
+    pub fn strip_padding(&self, mut data: Vec<u8>) -> Vec<u8> {
+        data.truncate(data.len().saturating_sub(self.padding));
+        data
+    }
+
     pub fn checksum(data: &[u8]) -> usize {
         Crc::<u32>::new(&CRC_32_ISCSI).checksum(data) as usize
     }
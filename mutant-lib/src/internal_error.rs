@@ -1,4 +1,9 @@
-use crate::{index::error::IndexError, network::NetworkError};
+use crate::{
+    api::MaintenanceOp,
+    index::{error::IndexError, master_index::QuotaResource},
+    network::{NetworkChoice, NetworkError},
+    ops::DataError,
+};
 use deadpool::managed::PoolError;
 use never::Never;
 use thiserror::Error;
@@ -29,9 +34,10 @@ pub enum Error {
     // #[error("Pad Lifecycle Layer Error: {0}")]
     // PadLifecycle(#[from] PadLifecycleError),
 
-    // /// Errors related to data processing or handling (e.g., serialization, deserialization issues).
-    // #[error("Data Operation Layer Error: {0}")]
-    // Data(#[from] DataError),
+    /// Errors undoing the transforms a value was stored through.
+    #[error("Data Operation Layer Error: {0}")]
+    Data(#[from] DataError),
+
     /// Errors occurring within user-provided callback functions.
     #[error("Callback Error: {0}")]
     Callback(String),
@@ -66,6 +72,59 @@ pub enum Error {
     /// Indicates a timeout occurred during an operation.
     #[error("Operation timed out: {0}")]
     Timeout(String),
+
+    /// The wallet key is meant for another network than the configured one.
+    #[error(
+        "Wallet key is meant for {wallet_network:?} but the configured network is {configured:?}"
+    )]
+    NetworkMismatch {
+        wallet_network: NetworkChoice,
+        configured: NetworkChoice,
+    },
+
+    /// A written pad was read back, but never with the data written to it before the
+    /// confirmation gave up.
+    #[error("Verification failed: chunk {chunk_index} read back from pad {address} differs from what was written")]
+    VerificationFailed { chunk_index: usize, address: String },
+
+    /// A fetched chunk is not what was stored: other data than its checksum and size in
+    /// the index, under the counter written or a later one.
+    #[error("Checksum mismatch: chunk {chunk_index} of key {key} read from pad {address} differs from what was stored")]
+    ChecksumMismatch {
+        key: String,
+        chunk_index: usize,
+        address: String,
+    },
+
+    /// A value expected to start with an envelope header does not.
+    #[error("Invalid envelope: {0}")]
+    InvalidEnvelope(String),
+
+    /// Pads of a value are gone from the network, found by the existence check run
+    /// before fetching, see `MutAntConfig::preflight_existence_check`.
+    #[error("{count} pads are missing from the network, including {examples:?}")]
+    PadsMissing {
+        count: usize,
+        /// Addresses of the first missing pads, in hex.
+        examples: Vec<String>,
+    },
+
+    /// A store would go over the configured quota, see `MutAntConfig::quota`.
+    #[error("Quota exceeded: {requested} more {resource} on top of {current} would go over the limit of {limit}")]
+    QuotaExceeded {
+        resource: QuotaResource,
+        limit: u64,
+        current: u64,
+        requested: u64,
+    },
+
+    /// A mutating call on an instance created with `MutAnt::init_from_index_file`.
+    #[error("Read-only mode: {0} is not available on an instance loaded from an index file")]
+    ReadOnlyMode(String),
+
+    /// A purge, sync or reset was started while another one was running.
+    #[error("Maintenance in progress: a {current} is already running, try again once it is done")]
+    MaintenanceInProgress { current: MaintenanceOp },
 }
 
 // Implementation to convert deadpool PoolError into our internal Error::PoolError
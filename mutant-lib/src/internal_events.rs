@@ -1,7 +1,8 @@
 use mutant_protocol::{
     GetCallback as ProtocolGetCallback, GetEvent as ProtocolGetEvent,
     HealthCheckCallback as ProtocolHealthCheckCallback,
-    HealthCheckEvent as ProtocolHealthCheckEvent, PurgeCallback as ProtocolPurgeCallback,
+    HealthCheckEvent as ProtocolHealthCheckEvent, InitCallback as ProtocolInitCallback,
+    InitProgressEvent as ProtocolInitProgressEvent, PurgeCallback as ProtocolPurgeCallback,
     PurgeEvent as ProtocolPurgeEvent, PutCallback as ProtocolPutCallback,
     PutEvent as ProtocolPutEvent, SyncCallback as ProtocolSyncCallback,
     SyncEvent as ProtocolSyncEvent,
@@ -78,3 +79,16 @@ pub(crate) async fn invoke_health_check_callback(
         Ok(true)
     }
 }
+
+pub(crate) async fn invoke_init_callback(
+    callback: &Option<ProtocolInitCallback>,
+    event: ProtocolInitProgressEvent,
+) -> Result<Option<bool>, Error> {
+    if let Some(cb) = callback {
+        cb(event)
+            .await
+            .map_err(|e| Error::CallbackError(e.to_string()))
+    } else {
+        Ok(None)
+    }
+}
@@ -95,6 +95,18 @@
 //! Other operations requiring a private key (like `put`, `get`, `remove`, etc.)
 //! will fail.
 //!
+//! ### Isolating Applications
+//!
+//! Applications sharing a wallet also share its master index, and so its keys. Setting
+//! `MutAntConfig::app_id` (`MUTANT_APP_ID` in the environment) gives each application id
+//! its own master index, derived from the wallet and the id, and its own local index
+//! cache. Leaving it unset keeps the index used so far.
+//!
+//! Changing the id points at another store, empty the first time: the keys stored
+//! under the previous id are not listed anymore. `MutAnt::init_with_progress` reports
+//! `InitProgressEvent::CreatedNewIndex` when it starts from an empty index, so that a
+//! mistyped id does not go unnoticed.
+//!
 //! ### Using the Daemon and Client
 //!
 //! For most applications, it's recommended to use the daemon architecture:
@@ -149,6 +161,16 @@ mod index;
 mod network;
 /// Handles data structures and serialization/deserialization logic, including worker pools.
 mod ops;
+/// Local caches used to tune read behaviour and track key accesses.
+mod cache;
+/// Append-only log of the mutating operations.
+mod audit_log;
+/// Randomness of the pad keys and pad orders, seedable with the `test-utils` feature.
+mod rng;
+/// Source of the timestamps, injectable for devices without a reliable clock.
+mod clock;
+/// Settings of an instance, given to its builder.
+mod settings;
 
 /// Defines custom error types used throughout the `mutant-lib`.
 mod internal_error;
@@ -156,30 +178,52 @@ mod internal_error;
 mod internal_events;
 
 // Re-export main API entry point
-pub use crate::api::MutAnt;
+pub use crate::api::{MutAnt, MutAntBuilder, OperationHandle};
 
 pub mod config {
+    pub use crate::cache::CachePolicy;
+    pub use crate::clock::Clock;
+    pub use crate::index::master_index::{IncompletePolicy, Quota};
+    pub use crate::network::retry::RetryPolicy;
     pub use crate::network::NetworkChoice;
+    pub use crate::ops::utils::{derive_pad_secret_key, KeyDerivationInfo};
+    pub use crate::ops::{ExistingPadPolicy, ReadStrategy};
+    pub use crate::settings::MutAntConfig;
 }
 
 pub mod storage {
-    pub use super::network::{GetResult, PutResult};
-    pub use crate::index::master_index::IndexEntry;
+    pub use super::network::io::measure_io;
+    pub use super::network::{GetResult, PadNetworkHint, PutResult};
+    pub use crate::cache::KeyAccess;
+    pub use crate::index::master_index::{
+        key_details, Bundle, BundleOccupancy, BundleSlot, EdgeType, FileRecord, IndexEntry,
+        IndexFootprint, IntegrityProof, KeyEdge, KeyGraph, KeyNode, KeyStat, KeyTransforms,
+        KeyType, PublicCatalogue, ScanRecovery, SpendReport, TransformSpec, TrashedKey,
+        INTERNAL_KEY_PREFIX,
+    };
     pub use crate::index::pad_info::{PadInfo, PadStatus};
+    pub use crate::ops::{
+        ChunkTransform, EnvelopeHeader, FetchStream, MutAntReader, PadToSizeTransform,
+        StorePipeline, Transforms, XChaCha20Poly1305Transform, ZstdTransform,
+    };
     pub use autonomi::ScratchpadAddress;
-    pub use mutant_protocol::StorageMode;
+    pub use mutant_protocol::{CacheCleanReport, IoStats, StorageMode, SyncDirection};
 }
 
 pub mod error {
+    pub use crate::api::MaintenanceOp;
+    pub use crate::index::master_index::QuotaResource;
     pub use crate::internal_error::Error;
+    pub use crate::ops::DataError;
     pub use crate::ops::worker::PoolError;
 }
 
 pub mod events {
+    pub use crate::network::NetworkEvent;
     pub use mutant_protocol::{
-        GetCallback, GetEvent, HealthCheckCallback, HealthCheckEvent, InitCallback,
-        InitProgressEvent, PurgeCallback, PurgeEvent, PutCallback, PutEvent,
-        SyncCallback, SyncEvent, TaskProgress, TaskResult, TaskStatus, TaskType,
+        AuditOperation, AuditRecord, GetCallback, GetEvent, HealthCheckCallback, HealthCheckEvent,
+        InitCallback, InitProgressEvent, ProgressSnapshot, PurgeCallback, PurgeEvent, PutCallback,
+        PutEvent, SyncCallback, SyncEvent, TaskProgress, TaskResult, TaskStatus, TaskType,
     };
 }
 
@@ -35,4 +35,37 @@ pub enum NetworkError {
 
     #[error("Network operation timed out: {0}")]
     Timeout(String),
+
+    #[error("Network request failed: {0}")]
+    RequestFailed(String),
+
+    #[error("Scratchpad read behind its last write: {0}")]
+    StaleRecord(String),
+
+    #[error("Scratchpad already exists on the network: {0}")]
+    PadAlreadyExists(String),
+
+    #[error("Cannot determine whether write landed: {0}")]
+    AmbiguousWrite(String),
+
+    #[error("Not supported by this network: {0}")]
+    Unsupported(String),
+}
+
+impl NetworkError {
+    /// Whether the operation that failed with this error may succeed if tried again.
+    ///
+    /// Timeouts, dropped connections, requests the network failed to answer and replicas
+    /// not updated yet are transient, while a missing record, an existing pad, a failed payment or a bad
+    /// configuration will fail the same way every time.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            NetworkError::Timeout(_)
+            | NetworkError::ClientAccessError(_)
+            | NetworkError::RequestFailed(_)
+            | NetworkError::StaleRecord(_) => true,
+            NetworkError::GetError(e) => !matches!(e, GetRecordError::RecordNotFound),
+            _ => false,
+        }
+    }
 }
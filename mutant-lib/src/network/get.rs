@@ -83,7 +83,7 @@ pub(super) async fn get(
                         AntNetworkError::GetRecordError(get_error) => {
                             Err(NetworkError::GetError(get_error))
                         }
-                        _ => Err(NetworkError::InternalError(format!(
+                        _ => Err(NetworkError::RequestFailed(format!(
                             "Failed to get scratchpad {}: {}",
                             address, e
                         ))),
@@ -1,17 +1,23 @@
 #![cfg(test)]
 
+use crate::config::MutAntConfig;
 use crate::index::{PadInfo, PadStatus};
 use crate::network::client::Config;
-use crate::network::{Network, NetworkChoice};
-use crate::ops::{DATA_ENCODING_PRIVATE_DATA, DATA_ENCODING_PUBLIC_DATA};
+use crate::network::{Network, NetworkChoice, NetworkError, NetworkEvent};
+use crate::ops::{ExistingPadPolicy, DATA_ENCODING_PRIVATE_DATA, DATA_ENCODING_PUBLIC_DATA};
 use autonomi::{AttoTokens, ScratchpadAddress, SecretKey};
 use rand::RngCore;
+use std::sync::Arc;
 
 use super::DEV_TESTNET_PRIVATE_KEY_HEX;
 
 async fn setup_adapter() -> Network {
-    Network::new(DEV_TESTNET_PRIVATE_KEY_HEX, NetworkChoice::Devnet)
-        .expect("Test adapter setup failed")
+    Network::new(
+        DEV_TESTNET_PRIVATE_KEY_HEX,
+        NetworkChoice::Devnet,
+        Default::default(),
+    )
+    .expect("Test adapter setup failed")
 }
 
 fn generate_random_data(size: usize) -> Vec<u8> {
@@ -33,6 +39,7 @@ fn create_initial_pad_info(data_size: usize) -> (PadInfo, ScratchpadAddress) {
         checksum: 0,
         chunk_index: 0,
         sk_bytes,
+        padding: 0,
     };
     (pad_info, address)
 }
@@ -308,3 +315,152 @@ async fn test_put_private_update() {
     );
     assert_eq!(final_data.counter, 1, "Final private counter mismatch");
 }
+
+fn setup_adapter_with_policy(on_existing_pad: ExistingPadPolicy) -> Network {
+    let config = MutAntConfig {
+        on_existing_pad,
+        io_accounting: true,
+        ..Default::default()
+    };
+    Network::new(
+        DEV_TESTNET_PRIVATE_KEY_HEX,
+        NetworkChoice::Devnet,
+        Arc::new(config),
+    )
+    .expect("Test adapter setup failed")
+}
+
+#[tokio::test]
+async fn test_put_existing_generated_pad_updates_it() {
+    let adapter = setup_adapter_with_policy(ExistingPadPolicy::Update);
+    let mut events = adapter.subscribe();
+    let (mut pad_info, address) = create_initial_pad_info(512);
+    let client = adapter
+        .get_client(Config::Put)
+        .await
+        .expect("Failed to get PUT client");
+
+    let created = adapter
+        .put(
+            &client,
+            &pad_info,
+            &generate_random_data(512),
+            DATA_ENCODING_PRIVATE_DATA,
+            false,
+        )
+        .await
+        .expect("Initial put failed");
+    assert!(!created.already_existed);
+    assert!(events.try_recv().is_err(), "A real create is no event");
+
+    // The pad is still believed new, as after an index lost before its status was saved.
+    let data = generate_random_data(512);
+    pad_info.last_known_counter += 1;
+    let updated = adapter
+        .put(&client, &pad_info, &data, DATA_ENCODING_PRIVATE_DATA, false)
+        .await
+        .expect("Put over the existing pad failed");
+
+    assert!(updated.already_existed);
+    assert_eq!(updated.cost, AttoTokens::zero());
+    assert_eq!(
+        events.try_recv().unwrap(),
+        NetworkEvent::CreateFellBackToUpdate { address }
+    );
+    let io = adapter.io_stats().unwrap();
+    assert_eq!((io.pads_created, io.pads_updated), (1, 1));
+    assert_eq!(io.creates_fell_back_to_update, 1);
+    // Each write looked the pad up first.
+    assert_eq!(io.existence_checks, 2);
+
+    // A pad known to be written is updated without a lookup.
+    pad_info.status = PadStatus::Written;
+    pad_info.last_known_counter += 1;
+    let data = generate_random_data(512);
+    let updated = adapter
+        .put(&client, &pad_info, &data, DATA_ENCODING_PRIVATE_DATA, false)
+        .await
+        .expect("Update of the written pad failed");
+    assert!(updated.already_existed);
+    let io = adapter.io_stats().unwrap();
+    assert_eq!((io.pads_updated, io.existence_checks), (2, 2));
+
+    let client = adapter
+        .get_client(Config::Get)
+        .await
+        .expect("Failed to get GET client");
+    let stored = adapter
+        .get(&client, &address, Some(&pad_info.secret_key()))
+        .await
+        .expect("Get after update failed");
+    assert_eq!(stored.data, data);
+}
+
+#[tokio::test]
+async fn test_put_existing_generated_pad_fails() {
+    let adapter = setup_adapter_with_policy(ExistingPadPolicy::Fail);
+    let mut events = adapter.subscribe();
+    let (mut pad_info, address) = create_initial_pad_info(512);
+    let client = adapter
+        .get_client(Config::Put)
+        .await
+        .expect("Failed to get PUT client");
+
+    let data = generate_random_data(512);
+    adapter
+        .put(&client, &pad_info, &data, DATA_ENCODING_PRIVATE_DATA, false)
+        .await
+        .expect("Initial put failed");
+
+    pad_info.last_known_counter += 1;
+    let result = adapter
+        .put(
+            &client,
+            &pad_info,
+            &generate_random_data(512),
+            DATA_ENCODING_PRIVATE_DATA,
+            false,
+        )
+        .await;
+    assert!(
+        matches!(result, Err(NetworkError::PadAlreadyExists(ref hex)) if *hex == address.to_hex()),
+        "Expected PadAlreadyExists, got {:?}",
+        result
+    );
+    assert!(events.try_recv().is_err());
+    let io = adapter.io_stats().unwrap();
+    assert_eq!(
+        (io.pads_created, io.pads_updated, io.failed_writes),
+        (1, 0, 1)
+    );
+    assert_eq!(io.creates_fell_back_to_update, 0);
+
+    // Refused before writing: the pad still holds the first value.
+    let client = adapter
+        .get_client(Config::Get)
+        .await
+        .expect("Failed to get GET client");
+    let stored = adapter
+        .get(&client, &address, Some(&pad_info.secret_key()))
+        .await
+        .expect("Get after refused put failed");
+    assert_eq!(stored.data, data);
+    assert_eq!(stored.counter, 0);
+
+    // A pad known to exist is updated whatever the policy.
+    pad_info.status = PadStatus::Written;
+    let client = adapter
+        .get_client(Config::Put)
+        .await
+        .expect("Failed to get PUT client");
+    adapter
+        .put(
+            &client,
+            &pad_info,
+            &generate_random_data(512),
+            DATA_ENCODING_PRIVATE_DATA,
+            false,
+        )
+        .await
+        .expect("Update of a written pad failed");
+}
@@ -0,0 +1,165 @@
+use mutant_protocol::IoStats;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+tokio::task_local! {
+    /// Counters of the operation measured by [`measure_io`] the current task belongs to.
+    static OPERATION_IO: Arc<IoCounters>;
+}
+
+/// One scratchpad call made on the network. `CreateFellBackToUpdate` marks the write
+/// recorded just before as meant to create a pad that turned out to exist.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum IoCall {
+    Created { bytes: u64 },
+    Updated { bytes: u64 },
+    FailedWrite,
+    Read { bytes: u64 },
+    FailedRead,
+    ExistenceCheck,
+    CreateFellBackToUpdate,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct IoCounters {
+    /// Counts the calls recorded here, see the `io_accounting` of `MutAntConfig`. Measured
+    /// operations count theirs either way.
+    enabled: bool,
+    pads_created: AtomicU64,
+    pads_updated: AtomicU64,
+    failed_writes: AtomicU64,
+    pads_read: AtomicU64,
+    existence_checks: AtomicU64,
+    creates_fell_back_to_update: AtomicU64,
+    bytes_written: AtomicU64,
+    bytes_read: AtomicU64,
+}
+
+impl IoCounters {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    fn add(&self, call: IoCall) {
+        let (counter, bytes) = match call {
+            IoCall::Created { bytes } => (&self.pads_created, Some((&self.bytes_written, bytes))),
+            IoCall::Updated { bytes } => (&self.pads_updated, Some((&self.bytes_written, bytes))),
+            IoCall::FailedWrite => (&self.failed_writes, None),
+            IoCall::Read { bytes } => (&self.pads_read, Some((&self.bytes_read, bytes))),
+            IoCall::FailedRead => (&self.pads_read, None),
+            IoCall::ExistenceCheck => (&self.existence_checks, None),
+            IoCall::CreateFellBackToUpdate => (&self.creates_fell_back_to_update, None),
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Some((total, bytes)) = bytes {
+            total.fetch_add(bytes, Ordering::Relaxed);
+        }
+    }
+
+    /// Accounts for `call` here when enabled, and in the operation being measured, if any.
+    pub(crate) fn record(&self, call: IoCall) {
+        if self.enabled {
+            self.add(call);
+        }
+        let _ = OPERATION_IO.try_with(|operation| operation.add(call));
+    }
+
+    pub(crate) fn snapshot(&self) -> IoStats {
+        IoStats {
+            pads_created: self.pads_created.load(Ordering::Relaxed),
+            pads_updated: self.pads_updated.load(Ordering::Relaxed),
+            failed_writes: self.failed_writes.load(Ordering::Relaxed),
+            pads_read: self.pads_read.load(Ordering::Relaxed),
+            existence_checks: self.existence_checks.load(Ordering::Relaxed),
+            creates_fell_back_to_update: self.creates_fell_back_to_update.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Runs `operation`, counting the scratchpad calls it makes, including those of the
+/// tasks it spawns. A measure nested in another one counts its calls for itself only.
+pub async fn measure_io<F: Future>(operation: F) -> (F::Output, IoStats) {
+    let counters = Arc::new(IoCounters::default());
+    let output = OPERATION_IO.scope(counters.clone(), operation).await;
+    (output, counters.snapshot())
+}
+
+/// Makes `future` count toward the operation being measured by the calling task, for it
+/// to be spawned without escaping the measure.
+pub(crate) fn inherit_io<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let operation = OPERATION_IO.try_with(Arc::clone).ok();
+    async move {
+        match operation {
+            Some(operation) => OPERATION_IO.scope(operation, future).await,
+            None => future.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_measure_counts_the_calls_of_spawned_tasks() {
+        let totals = Arc::new(IoCounters::default());
+
+        let ((), io) = measure_io({
+            let totals = totals.clone();
+            async move {
+                totals.record(IoCall::Created { bytes: 10 });
+                let spawned = totals.clone();
+                tokio::spawn(inherit_io(async move {
+                    spawned.record(IoCall::Updated { bytes: 5 });
+                    spawned.record(IoCall::CreateFellBackToUpdate);
+                    spawned.record(IoCall::Read { bytes: 7 });
+                    spawned.record(IoCall::FailedWrite);
+                }))
+                .await
+                .unwrap();
+                // Spawned without inheriting, it is not part of the operation.
+                let escaped = totals.clone();
+                tokio::spawn(async move { escaped.record(IoCall::ExistenceCheck) })
+                    .await
+                    .unwrap();
+            }
+        })
+        .await;
+
+        assert_eq!(
+            io,
+            IoStats {
+                pads_created: 1,
+                pads_updated: 1,
+                failed_writes: 1,
+                pads_read: 1,
+                existence_checks: 0,
+                creates_fell_back_to_update: 1,
+                bytes_written: 15,
+                bytes_read: 7,
+            }
+        );
+        assert_eq!(io.writes(), 3);
+        assert_eq!(io.write_amplification(10), Some(1.5));
+    }
+
+    #[tokio::test]
+    async fn test_nested_measures_count_apart() {
+        let totals = IoCounters::default();
+        let (inner, outer) = measure_io(async {
+            totals.record(IoCall::Read { bytes: 1 });
+            let ((), inner) = measure_io(async { totals.record(IoCall::Read { bytes: 2 }) }).await;
+            inner
+        })
+        .await;
+
+        assert_eq!((outer.pads_read, outer.bytes_read), (1, 1));
+        assert_eq!((inner.pads_read, inner.bytes_read), (1, 2));
+    }
+}
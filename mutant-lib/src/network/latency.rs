@@ -0,0 +1,74 @@
+use autonomi::ScratchpadAddress;
+use std::collections::HashMap;
+
+/// Pads whose fetch duration is remembered. Past this, the half fetched the longest
+/// ago is forgotten.
+const MAX_FETCH_LATENCIES: usize = 100_000;
+
+/// Duration of the last successful fetch of the pads fetched most recently.
+#[derive(Debug)]
+pub(crate) struct FetchLatencies {
+    capacity: usize,
+    /// Duration in milliseconds and rank of the last fetch of each pad.
+    latencies: HashMap<ScratchpadAddress, (u64, u64)>,
+    fetches: u64,
+}
+
+impl FetchLatencies {
+    pub(crate) fn new() -> Self {
+        Self::with_capacity(MAX_FETCH_LATENCIES)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            latencies: HashMap::new(),
+            fetches: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, address: ScratchpadAddress, millis: u64) {
+        self.fetches += 1;
+        self.latencies.insert(address, (millis, self.fetches));
+        if self.latencies.len() > self.capacity {
+            let kept_from = self.fetches - (self.capacity / 2) as u64;
+            self.latencies.retain(|_, (_, rank)| *rank > kept_from);
+        }
+    }
+
+    pub(crate) fn get(&self, address: &ScratchpadAddress) -> Option<u64> {
+        self.latencies.get(address).map(|(millis, _)| *millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blsttc::SecretKey;
+
+    fn address() -> ScratchpadAddress {
+        ScratchpadAddress::new(SecretKey::random().public_key())
+    }
+
+    #[test]
+    fn test_the_oldest_fetches_are_forgotten_past_the_capacity() {
+        let mut latencies = FetchLatencies::with_capacity(4);
+        let addresses: Vec<_> = (0..5).map(|_| address()).collect();
+        for (millis, address) in addresses.iter().enumerate() {
+            latencies.record(*address, millis as u64);
+        }
+
+        assert_eq!(latencies.latencies.len(), 2);
+        assert_eq!(latencies.get(&addresses[0]), None);
+        assert_eq!(latencies.get(&addresses[3]), Some(3));
+        assert_eq!(latencies.get(&addresses[4]), Some(4));
+
+        // Fetched again, a pad counts as recent.
+        latencies.record(addresses[0], 0);
+        latencies.record(addresses[1], 1);
+        latencies.record(addresses[3], 7);
+        latencies.record(addresses[2], 2);
+        assert_eq!(latencies.get(&addresses[3]), Some(7));
+        assert_eq!(latencies.get(&addresses[4]), None);
+    }
+}
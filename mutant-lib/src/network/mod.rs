@@ -1,22 +1,40 @@
 pub mod client;
 pub mod error;
 pub mod get;
+pub mod io;
+mod latency;
 pub mod put;
+pub mod retry;
 pub mod wallet;
 
 use blsttc::SecretKey;
 use client::Config;
 pub use error::NetworkError;
 
+use self::io::{IoCall, IoCounters};
+use self::latency::FetchLatencies;
 use self::wallet::create_wallet;
-use crate::index::PadInfo;
+use crate::config::MutAntConfig;
+use crate::index::{PadInfo, PadStatus};
+use crate::ops::worker::with_pad_op_permit;
+use crate::ops::ExistingPadPolicy;
+use crate::rng::InstanceRng;
 
 // Make this public so other test modules can use it
 pub const DEV_TESTNET_PRIVATE_KEY_HEX: &str =
     "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
 
+use ant_networking::GetRecordError;
 use autonomi::{AttoTokens, Client, ScratchpadAddress, Wallet};
 use log::debug;
+use mutant_protocol::IoStats;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{broadcast, Semaphore};
+
+/// Events queued on the channel of a network for the subscribers that lag behind.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum NetworkChoice {
@@ -49,6 +67,34 @@ pub struct PutResult {
     pub cost: AttoTokens,
     /// The address of the scratchpad that was put.
     pub address: ScratchpadAddress,
+    /// The network already held the scratchpad, which got updated without being paid for.
+    pub already_existed: bool,
+}
+
+impl PutResult {
+    /// The cost in atto tokens, saturating at `u128::MAX`.
+    pub fn cost_atto(&self) -> u128 {
+        u128::try_from(self.cost.as_atto()).unwrap_or(u128::MAX)
+    }
+}
+
+/// Something that happened on the network worth telling the application about, see
+/// `MutAnt::subscribe_network_events`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkEvent {
+    /// A freshly generated pad was already stored on the network and got updated instead
+    /// of created, following [`ExistingPadPolicy::Update`].
+    CreateFellBackToUpdate { address: ScratchpadAddress },
+}
+
+/// What this node measured when reading a pad, used to order reads. It is local only:
+/// the pinned Autonomi client does not expose its closest-peers lookup, so the peers
+/// holding the pad are not known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PadNetworkHint {
+    pub address: ScratchpadAddress,
+    /// Round trip of the last successful fetch of the pad, if it was fetched before.
+    pub estimated_rtt_ms: Option<u64>,
 }
 
 lazy_static::lazy_static! {
@@ -71,6 +117,17 @@ pub struct Network {
     wallet: Wallet,
     network_choice: NetworkChoice,
     secret_key: SecretKey,
+    /// Duration of the last successful fetch of the pads fetched recently, in milliseconds.
+    fetch_latencies: Mutex<FetchLatencies>,
+    /// Scratchpad calls made through this network, counted with `io_accounting`.
+    io: IoCounters,
+    /// Pad operations in flight, up to `max_concurrent_pad_ops`.
+    pad_ops: Arc<Semaphore>,
+    /// Settings of the instance, read by the operations run over this network.
+    config: Arc<MutAntConfig>,
+    /// Random source of the instance, shared with its index.
+    rng: InstanceRng,
+    events: broadcast::Sender<NetworkEvent>,
 }
 
 impl Network {
@@ -78,6 +135,7 @@ impl Network {
     pub(crate) fn new(
         private_key_hex: &str,
         network_choice: NetworkChoice,
+        config: Arc<MutAntConfig>,
     ) -> Result<Self, NetworkError> {
         debug!(
             "Creating AutonomiNetworkAdapter configuration for network: {:?}",
@@ -90,9 +148,38 @@ impl Network {
             wallet,
             network_choice,
             secret_key,
+            fetch_latencies: Mutex::new(FetchLatencies::new()),
+            io: IoCounters::new(config.io_accounting),
+            pad_ops: Arc::new(Semaphore::new(config.max_concurrent_pad_ops)),
+            rng: InstanceRng::from_config(&config),
+            config,
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
         })
     }
 
+    pub(crate) fn config(&self) -> &Arc<MutAntConfig> {
+        &self.config
+    }
+
+    pub(crate) fn rng(&self) -> &InstanceRng {
+        &self.rng
+    }
+
+    /// Limit of the pad operations in flight, shared by every operation of the instance.
+    pub(crate) fn pad_ops(&self) -> Arc<Semaphore> {
+        self.pad_ops.clone()
+    }
+
+    /// Receives the events of this network from now on.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<NetworkEvent> {
+        self.events.subscribe()
+    }
+
+    /// Runs `op` once fewer than `max_concurrent_pad_ops` pad operations are in flight.
+    pub(crate) async fn with_pad_op_permit<F: Future>(&self, op: F) -> F::Output {
+        with_pad_op_permit(&self.pad_ops, op).await
+    }
+
     /// Retrieves an Autonomi network client.
     /// This method creates a new client for each call.
     pub(crate) async fn get_client(
@@ -111,9 +198,40 @@ impl Network {
         address: &ScratchpadAddress,
         owner_sk: Option<&SecretKey>,
     ) -> Result<GetResult, NetworkError> {
-        get::get(client.deref(), address, owner_sk).await
+        let start = Instant::now();
+        let result = self.record_read(get::get(client.deref(), address, owner_sk).await)?;
+        self.fetch_latencies
+            .lock()
+            .unwrap()
+            .record(*address, start.elapsed().as_millis() as u64);
+        Ok(result)
     }
 
+    /// Returns the round trip of the last local fetch of each of `addresses`.
+    pub(crate) fn pad_network_hints(
+        &self,
+        addresses: &[ScratchpadAddress],
+    ) -> Vec<PadNetworkHint> {
+        let latencies = self.fetch_latencies.lock().unwrap();
+        addresses
+            .iter()
+            .map(|address| PadNetworkHint {
+                address: *address,
+                estimated_rtt_ms: latencies.get(address),
+            })
+            .collect()
+    }
+
+    /// Orders `pads` so that the ones fetched the fastest before come first,
+    /// followed by the never fetched ones in their original order.
+    pub(crate) fn sort_by_estimated_rtt(&self, pads: &mut [PadInfo]) {
+        let latencies = self.fetch_latencies.lock().unwrap();
+        pads.sort_by_key(|pad| latencies.get(&pad.address).unwrap_or(u64::MAX));
+    }
+
+    /// Writes `data` to the pad. A `Generated` pad is expected to be new: finding it on
+    /// the network fails the put under [`ExistingPadPolicy::Fail`], and is reported with
+    /// [`NetworkEvent::CreateFellBackToUpdate`] under [`ExistingPadPolicy::Update`].
     pub(crate) async fn put<C: std::ops::Deref<Target = Client>>(
         &self,
         client: C,
@@ -122,15 +240,141 @@ impl Network {
         data_encoding: u64,
         is_public: bool,
     ) -> Result<PutResult, NetworkError> {
-        put::put(
+        let creating = pad_info.status == PadStatus::Generated;
+        if creating {
+            // A new pad is looked up before it is written.
+            self.io.record(IoCall::ExistenceCheck);
+        }
+        let result = put::put(
             client.deref(),
             self.wallet.clone(),
             pad_info,
             data,
             data_encoding,
             is_public,
+            creating,
+            creating && self.config.on_existing_pad == ExistingPadPolicy::Fail,
         )
-        .await
+        .await;
+        let bytes = data.len() as u64;
+        self.io.record(match &result {
+            Ok(put) if put.already_existed => IoCall::Updated { bytes },
+            Ok(_) => IoCall::Created { bytes },
+            Err(_) => IoCall::FailedWrite,
+        });
+        if let Ok(put) = &result {
+            if creating && put.already_existed {
+                self.io.record(IoCall::CreateFellBackToUpdate);
+                // Nobody listening is fine, the event is also counted.
+                let _ = self.events.send(NetworkEvent::CreateFellBackToUpdate {
+                    address: put.address,
+                });
+            }
+        }
+        result
+    }
+
+    fn record_read(
+        &self,
+        result: Result<GetResult, NetworkError>,
+    ) -> Result<GetResult, NetworkError> {
+        self.io.record(match &result {
+            Ok(get) => IoCall::Read {
+                bytes: get.data.len() as u64,
+            },
+            Err(_) => IoCall::FailedRead,
+        });
+        result
+    }
+
+    /// Scratchpad calls made since this network was created, `None` unless
+    /// `io_accounting` is on.
+    pub(crate) fn io_stats(&self) -> Option<IoStats> {
+        self.config.io_accounting.then(|| self.io.snapshot())
+    }
+
+    /// Checks whether `data` is already stored in the pad at the expected counter,
+    /// i.e. whether a previous write whose outcome is unknown actually landed.
+    ///
+    /// Returns `AmbiguousWrite` when the pad is in a state that no single write of
+    /// `data` could explain, or when it cannot be read.
+    pub(crate) async fn write_landed<C: std::ops::Deref<Target = Client>>(
+        &self,
+        client: C,
+        pad_info: &PadInfo,
+        data: &[u8],
+        is_public: bool,
+    ) -> Result<bool, NetworkError> {
+        let owned_key;
+        let secret_key_ref = if is_public {
+            None
+        } else {
+            owned_key = pad_info.secret_key();
+            Some(&owned_key)
+        };
+
+        match self.record_read(get::get(client.deref(), &pad_info.address, secret_key_ref).await) {
+            Ok(result) => {
+                let expected_counter = pad_info.last_known_counter;
+                let same_content = PadInfo::checksum(&result.data) == PadInfo::checksum(data);
+
+                if result.counter < expected_counter {
+                    Ok(false)
+                } else if result.counter == expected_counter && same_content {
+                    Ok(true)
+                } else {
+                    Err(NetworkError::AmbiguousWrite(format!(
+                        "pad {} is at counter {} (expected {}), content matches: {}",
+                        pad_info.address, result.counter, expected_counter, same_content
+                    )))
+                }
+            }
+            Err(NetworkError::GetError(GetRecordError::RecordNotFound)) => Ok(false),
+            Err(e) => Err(NetworkError::AmbiguousWrite(format!(
+                "failed to read back pad {}: {}",
+                pad_info.address, e
+            ))),
+        }
+    }
+
+    /// Whether the wallet holds both the tokens and the gas that stores are paid with.
+    pub(crate) async fn is_wallet_funded(&self) -> Result<bool, NetworkError> {
+        let tokens = self.wallet.balance_of_tokens().await.map_err(|e| {
+            NetworkError::WalletError(format!("Failed to read token balance: {}", e))
+        })?;
+        let gas =
+            self.wallet.balance_of_gas_tokens().await.map_err(|e| {
+                NetworkError::WalletError(format!("Failed to read gas balance: {}", e))
+            })?;
+
+        Ok(!tokens.is_zero() && !gas.is_zero())
+    }
+
+    /// Checks whether a scratchpad is stored at `address`, without decrypting it.
+    pub(crate) async fn check_existence<C: std::ops::Deref<Target = Client>>(
+        &self,
+        client: C,
+        address: &ScratchpadAddress,
+    ) -> Result<bool, NetworkError> {
+        self.io.record(IoCall::ExistenceCheck);
+        match get::get(client.deref(), address, None).await {
+            Ok(_) => Ok(true),
+            Err(NetworkError::GetError(GetRecordError::RecordNotFound)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Counts the network copies of the scratchpad at `address`. The pinned Autonomi
+    /// client does not report the holders of a record, so this is always `Unsupported`.
+    pub(crate) async fn pad_replication<C: std::ops::Deref<Target = Client>>(
+        &self,
+        _client: C,
+        address: &ScratchpadAddress,
+    ) -> Result<usize, NetworkError> {
+        Err(NetworkError::Unsupported(format!(
+            "the Autonomi client cannot count the copies of pad {}",
+            address
+        )))
     }
 
     pub fn secret_key(&self) -> &SecretKey {
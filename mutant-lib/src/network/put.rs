@@ -1,7 +1,10 @@
 use crate::index::PadInfo;
 use crate::network::error::NetworkError;
+use crate::network::get;
 use crate::network::PutResult;
+use ant_networking::GetRecordError;
 use autonomi::client::payment::PaymentOption;
+use autonomi::scratchpad::ScratchpadError;
 use autonomi::Client;
 use autonomi::{Bytes, Scratchpad, ScratchpadAddress, SecretKey, Wallet};
 use log::{debug, error, info, trace};
@@ -15,6 +18,11 @@ const PUT_TIMEOUT_SECS: u64 = 60 * 60; // 1 hour
 /// and calls the client's `scratchpad_put` method with the appropriate payment option.
 /// It does not handle retries.
 ///
+/// When `creating`, the scratchpad is looked up first, to tell whether the network
+/// already holds it. With `refuse_existing`, a scratchpad found there fails the put
+/// before anything is written or paid. Other pads were written before and are updated
+/// with a single put.
+///
 /// # Arguments
 ///
 /// * `client` - A reference to the `AutonomiNetworkAdapter`.
@@ -23,13 +31,16 @@ const PUT_TIMEOUT_SECS: u64 = 60 * 60; // 1 hour
 /// * `data` - The raw data bytes to be included in the scratchpad.
 /// * `data_encoding` - The encoding type for the data (e.g., content type).
 /// * `is_public` - Flag indicating if the scratchpad should be public (no encryption).
+/// * `creating` - The pad is believed new, it is looked up before being written.
+/// * `refuse_existing` - Fail instead of updating a scratchpad already on the network.
 ///
 /// # Errors
 ///
 /// Returns `NetworkError` if:
 /// - The client cannot be initialized.
 /// - The `SecretKey` cannot be reconstructed from `pad_info`.
-/// - The `scratchpad_put` operation fails.
+/// - The scratchpad already exists and `refuse_existing` is set (`PadAlreadyExists`).
+/// - The lookup or the `scratchpad_put` operation fails.
 pub(super) async fn put(
     client: &Client,
     payment_wallet: Wallet,
@@ -37,6 +48,8 @@ pub(super) async fn put(
     data: &[u8],
     data_encoding: u64,
     is_public: bool,
+    creating: bool,
+    refuse_existing: bool,
 ) -> Result<PutResult, NetworkError> {
     debug!(
         "Starting put for pad {} with data length {}",
@@ -67,30 +80,31 @@ pub(super) async fn put(
     let addr = *scratchpad.address();
     trace!("network::put called for address: {}", addr);
 
-    let payment = PaymentOption::Wallet(payment_wallet);
-
-    let put_future = client.scratchpad_put(scratchpad.clone(), payment);
+    let put_future = pay_and_put(
+        client,
+        &payment_wallet,
+        scratchpad,
+        creating,
+        refuse_existing,
+    );
 
     match timeout(Duration::from_secs(PUT_TIMEOUT_SECS), put_future).await {
-        Ok(Ok((cost, received_addr))) => {
-            if addr != received_addr {
-                error!(
-                    "Mismatch between expected addr {} and received addr {} during put",
-                    addr, received_addr
-                );
-            }
-            info!("Put successful for scratchpad {} with cost {}", addr, cost);
-            Ok(PutResult {
-                cost,
-                address: addr,
-            })
+        Ok(Ok(result)) => {
+            info!(
+                "Put successful for scratchpad {} with cost {}{}",
+                addr,
+                result.cost,
+                if result.already_existed {
+                    " (already existed)"
+                } else {
+                    ""
+                }
+            );
+            Ok(result)
         }
         Ok(Err(e)) => {
             error!("Failed to put scratchpad {}: {}", addr, e);
-            Err(NetworkError::InternalError(format!(
-                "Failed to put scratchpad {}: {}",
-                addr, e
-            )))
+            Err(e)
         }
         Err(_) => {
             error!("Timeout putting scratchpad {}", addr);
@@ -102,6 +116,54 @@ pub(super) async fn put(
     }
 }
 
+/// Looks a scratchpad being created up, then has the client pay for it and write it.
+/// A scratchpad found on the network is only written if `refuse_existing` is not set,
+/// the client does not pay again for a record the network already stores.
+async fn pay_and_put(
+    client: &Client,
+    payment_wallet: &Wallet,
+    scratchpad: Scratchpad,
+    creating: bool,
+    refuse_existing: bool,
+) -> Result<PutResult, NetworkError> {
+    let addr = *scratchpad.address();
+
+    let already_existed = if creating {
+        match get::get(client, &addr, None).await {
+            Ok(_) => true,
+            Err(NetworkError::GetError(GetRecordError::RecordNotFound)) => false,
+            Err(e) => return Err(e),
+        }
+    } else {
+        true
+    };
+    if already_existed && refuse_existing {
+        return Err(NetworkError::PadAlreadyExists(addr.to_hex()));
+    }
+
+    let (cost, received_addr) = client
+        .scratchpad_put(scratchpad, PaymentOption::Wallet(payment_wallet.clone()))
+        .await
+        .map_err(|e| match e {
+            ScratchpadError::Network(_) => {
+                NetworkError::RequestFailed(format!("Failed to put scratchpad {}: {}", addr, e))
+            }
+            _ => NetworkError::InternalError(format!("Failed to put scratchpad {}: {}", addr, e)),
+        })?;
+    if addr != received_addr {
+        error!(
+            "Mismatch between expected addr {} and received addr {} during put",
+            addr, received_addr
+        );
+    }
+
+    Ok(PutResult {
+        cost,
+        address: addr,
+        already_existed,
+    })
+}
+
 /// Creates a new public (unencrypted) Scratchpad instance with a valid signature.
 fn create_public_scratchpad(
     owner_sk: &SecretKey,
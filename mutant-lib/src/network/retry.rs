@@ -0,0 +1,206 @@
+use super::NetworkError;
+use log::debug;
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+/// How often, and how far apart, a failed network operation is tried again.
+///
+/// The delay before retry `n` is `base_delay * 2^(n - 1)`, capped at `max_delay`.
+/// With `jitter` on, each delay is drawn uniformly from its upper half so that workers
+/// failing together do not all come back at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, the first one included.
+    pub max_attempts: usize,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: crate::ops::PAD_RECYCLING_RETRIES,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Upper bound of the delay before the given retry (`attempt` is 1 for the first retry).
+    pub fn max_delay_before(&self, attempt: usize) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(u32::MAX as usize) as u32;
+        let factor = 2u32.checked_pow(exponent).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(Duration::MAX)
+            .min(self.max_delay)
+    }
+
+    /// Delay to wait before the given retry, jitter included.
+    pub fn delay_before(&self, attempt: usize) -> Duration {
+        let delay = self.max_delay_before(attempt);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+        let millis = delay.as_millis().min(u64::MAX as u128) as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(millis / 2..=millis))
+    }
+
+    /// Runs `op` until it succeeds, fails with an error that is not
+    /// [transient](NetworkError::is_transient), or `max_attempts` is reached.
+    ///
+    /// `op` gets the attempt number, starting at 1. `on_retry` is awaited with the number
+    /// of the attempt about to be made before each retry.
+    pub async fn retry<T, Op, OpFut, OnRetry, RetryFut>(
+        &self,
+        what: &str,
+        mut op: Op,
+        mut on_retry: OnRetry,
+    ) -> Result<T, NetworkError>
+    where
+        Op: FnMut(usize) -> OpFut,
+        OpFut: Future<Output = Result<T, NetworkError>>,
+        OnRetry: FnMut(usize) -> RetryFut,
+        RetryFut: Future<Output = ()>,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match op(attempt).await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < max_attempts && e.is_transient() => {
+                    let delay = self.delay_before(attempt);
+                    attempt += 1;
+                    debug!(
+                        "{} failed: {}. Retrying in {:?} (attempt {}/{})",
+                        what, e, delay, attempt, max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    on_retry(attempt).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ant_networking::GetRecordError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn policy(max_attempts: usize) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: false,
+        }
+    }
+
+    /// Fails with `error` on the first `failures` calls, then succeeds.
+    async fn flaky(
+        calls: &AtomicUsize,
+        failures: usize,
+        error: NetworkError,
+    ) -> Result<usize, NetworkError> {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call <= failures {
+            Err(error)
+        } else {
+            Ok(call)
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            jitter: false,
+        };
+        let delays: Vec<_> = (1..=6)
+            .map(|n| policy.delay_before(n).as_millis())
+            .collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.delay_before(usize::MAX), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_the_upper_half() {
+        let policy = RetryPolicy {
+            jitter: true,
+            ..RetryPolicy::default()
+        };
+        for attempt in 1..=8 {
+            let max = policy.max_delay_before(attempt);
+            let delay = policy.delay_before(attempt);
+            assert!(
+                delay <= max && delay >= max / 2,
+                "{:?} out of {:?}",
+                delay,
+                max
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried_until_success() {
+        let calls = AtomicUsize::new(0);
+        let retries = AtomicUsize::new(0);
+        let result = policy(5)
+            .retry(
+                "put",
+                |_| flaky(&calls, 3, NetworkError::Timeout("put".to_string())),
+                |attempt| {
+                    assert_eq!(attempt, retries.fetch_add(1, Ordering::SeqCst) + 2);
+                    async {}
+                },
+            )
+            .await;
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(retries.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = AtomicUsize::new(0);
+        let result = policy(3)
+            .retry(
+                "put",
+                |_| flaky(&calls, 10, NetworkError::Timeout("put".to_string())),
+                |_| async {},
+            )
+            .await;
+        assert!(matches!(result, Err(NetworkError::Timeout(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let calls = AtomicUsize::new(0);
+        let result = policy(5)
+            .retry(
+                "put",
+                |_| flaky(&calls, 1, NetworkError::PadAlreadyExists("pad".to_string())),
+                |_| async {},
+            )
+            .await;
+        assert!(matches!(result, Err(NetworkError::PadAlreadyExists(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_transient_errors_are_told_by_their_variant() {
+        assert!(NetworkError::RequestFailed("quorum".to_string()).is_transient());
+        assert!(NetworkError::StaleRecord("pad".to_string()).is_transient());
+        assert!(!NetworkError::InternalError("connection timed out".to_string()).is_transient());
+        assert!(!NetworkError::GetError(GetRecordError::RecordNotFound).is_transient());
+    }
+}
@@ -1,10 +1,17 @@
 use crate::network::error::NetworkError;
-use crate::network::NetworkChoice;
+use crate::network::{NetworkChoice, DEV_TESTNET_PRIVATE_KEY_HEX};
 use autonomi::{Network, SecretKey, Wallet};
 use hex;
 use log::info;
 use sha2::{Digest, Sha256};
 
+/// Whether `private_key_hex` is the well-known key of the local devnet, which anyone can
+/// spend from.
+pub(crate) fn is_dev_key(private_key_hex: &str) -> bool {
+    let normalize = |key: &str| key.trim().trim_start_matches("0x").to_lowercase();
+    normalize(private_key_hex) == normalize(DEV_TESTNET_PRIVATE_KEY_HEX)
+}
+
 pub(crate) fn create_wallet(
     private_key_hex: &str,
     network_choice: NetworkChoice,
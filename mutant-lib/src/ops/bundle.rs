@@ -0,0 +1,341 @@
+use crate::error::Error;
+use crate::events::{GetCallback, GetEvent, PutCallback};
+use crate::index::master_index::{
+    decode_bundle, encode_bundle, Bundle, BundleSlot, MasterIndex,
+};
+use crate::index::{PadInfo, PadStatus};
+use crate::internal_events::{invoke_get_callback, invoke_put_callback};
+use crate::network::client::Config;
+use crate::network::{Network, NetworkError};
+use autonomi::{Client, ScratchpadAddress};
+use log::{debug, info};
+use mutant_protocol::{PutEvent, StorageMode};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard, RwLock};
+use tokio::time::Instant;
+
+use super::MAX_CONFIRMATION_DURATION;
+
+pub const DATA_ENCODING_BUNDLE: u64 = 4;
+
+/// Below this ratio of live bytes over written bytes, a bundle gets compacted on removal.
+const BUNDLE_COMPACTION_THRESHOLD: f64 = 0.5;
+
+lazy_static::lazy_static! {
+    /// Bundle pads are rewritten as a whole, so concurrent writes to them must not interleave.
+    static ref BUNDLE_WRITE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub(super) fn should_bundle(
+    index: &MasterIndex,
+    key_name: &str,
+    content_len: usize,
+    public: bool,
+) -> bool {
+    !public
+        && content_len <= index.config().bundle_threshold
+        && (!index.contains_key(key_name) || index.is_bundled(key_name))
+}
+
+pub(super) async fn put(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    key_name: &str,
+    content: &[u8],
+    mode: StorageMode,
+    put_callback: Option<PutCallback>,
+) -> Result<ScratchpadAddress, Error> {
+    let _guard = BUNDLE_WRITE_LOCK.lock().await;
+    index
+        .read()
+        .await
+        .ensure_quota_for(key_name, content.len(), 0)?;
+
+    invoke_put_callback(
+        &put_callback,
+        PutEvent::Starting {
+            total_chunks: 1,
+            initial_written_count: 0,
+            initial_confirmed_count: 0,
+            chunks_to_reserve: 1,
+        },
+    )
+    .await?;
+
+    let client = network
+        .get_client(Config::Put)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    let capacity = mode.scratchpad_size();
+    let previous_bundle = index
+        .read()
+        .await
+        .get_bundled(key_name)
+        .map(|(slot, _)| slot.bundle_address);
+    let target = index
+        .read()
+        .await
+        .find_bundle_with_room(content.len(), capacity, Some(key_name));
+
+    let (pad, mut values) = match target {
+        Some(bundle) => {
+            let values = fetch_live_values(&index, &network, &client, &bundle).await?;
+            (bundle.pad, values)
+        }
+        None => {
+            let pad = index.write().await._acquire_pads_internal(1)?.remove(0);
+            (pad, Vec::new())
+        }
+    };
+
+    values.retain(|(key, _)| key != key_name);
+    values.push((key_name.to_string(), content.to_vec()));
+
+    let address = write_bundle(&index, &network, &client, pad, capacity, values).await?;
+
+    // The key moved to another bundle, its old slot is now dead weight.
+    if let Some(previous) = previous_bundle.filter(|previous| *previous != address) {
+        compact_if_needed(&index, &network, &client, &previous).await?;
+    }
+
+    info!("Stored {} in bundle {}", key_name, address);
+
+    invoke_put_callback(
+        &put_callback,
+        PutEvent::PadsWritten {
+            chunk_index: 0,
+            completed_count: 1,
+        },
+    )
+    .await?;
+    invoke_put_callback(
+        &put_callback,
+        PutEvent::PadsConfirmed {
+            chunk_index: 0,
+            completed_count: 1,
+        },
+    )
+    .await?;
+    invoke_put_callback(&put_callback, PutEvent::Complete).await?;
+
+    Ok(address)
+}
+
+pub(super) async fn get(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    key_name: &str,
+    get_callback: Option<GetCallback>,
+) -> Result<Vec<u8>, Error> {
+    let (slot, pad) = index
+        .read()
+        .await
+        .get_bundled(key_name)
+        .ok_or_else(|| Error::Internal(format!("Key {} is not bundled", key_name)))?;
+
+    invoke_get_callback(&get_callback, GetEvent::Starting { total_chunks: 1 }).await?;
+
+    let client = network
+        .get_client(Config::Get)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+    let result = network
+        .get(&client, &pad.address, Some(&pad.secret_key()))
+        .await?;
+
+    let value = slot_value(&result.data, &slot)?.to_vec();
+
+    invoke_get_callback(
+        &get_callback,
+        GetEvent::PadFetched {
+            chunk_index: 0,
+            completed_count: 1,
+        },
+    )
+    .await?;
+    invoke_get_callback(&get_callback, GetEvent::Complete).await?;
+
+    Ok(value)
+}
+
+pub(super) async fn remove(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    key_name: &str,
+) -> Result<(), Error> {
+    let _guard = BUNDLE_WRITE_LOCK.lock().await;
+
+    let slot = index.write().await.remove_bundled_key(key_name)?;
+
+    let client = network
+        .get_client(Config::Put)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    compact_if_needed(&index, &network, &client, &slot.bundle_address).await
+}
+
+/// Holds off every bundle write, see [`super::Data::lock_bundle_writes`].
+pub(super) async fn lock_writes() -> MutexGuard<'static, ()> {
+    BUNDLE_WRITE_LOCK.lock().await
+}
+
+/// Compacts a bundle that keys were removed from while the writes were held off.
+pub(super) async fn compact(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    address: &ScratchpadAddress,
+) -> Result<(), Error> {
+    let _guard = BUNDLE_WRITE_LOCK.lock().await;
+
+    let client = network
+        .get_client(Config::Put)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    compact_if_needed(&index, &network, &client, address).await
+}
+
+/// Releases an empty bundle, or rewrites it without its dead slots once mostly unused.
+async fn compact_if_needed(
+    index: &Arc<RwLock<MasterIndex>>,
+    network: &Arc<Network>,
+    client: &Client,
+    address: &ScratchpadAddress,
+) -> Result<(), Error> {
+    let (bundle, live_keys, occupancy) = {
+        let index = index.read().await;
+        match index.get_bundle(address) {
+            Some(bundle) => (
+                bundle,
+                index.bundle_keys(address).len(),
+                index.bundle_occupancy(address),
+            ),
+            None => return Ok(()),
+        }
+    };
+
+    if live_keys == 0 {
+        debug!("Releasing empty bundle {}", address);
+        return index.write().await.release_bundle(address);
+    }
+
+    if occupancy.ratio() < BUNDLE_COMPACTION_THRESHOLD {
+        debug!(
+            "Compacting bundle {} ({} live bytes out of {})",
+            address, occupancy.live_bytes, occupancy.used_bytes
+        );
+        let values = fetch_live_values(index, network, client, &bundle).await?;
+        write_bundle(index, network, client, bundle.pad, bundle.capacity, values).await?;
+    }
+
+    Ok(())
+}
+
+async fn fetch_live_values(
+    index: &Arc<RwLock<MasterIndex>>,
+    network: &Arc<Network>,
+    client: &Client,
+    bundle: &Bundle,
+) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    let keys = index.read().await.bundle_keys(&bundle.pad.address);
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let result = network
+        .get(client, &bundle.pad.address, Some(&bundle.pad.secret_key()))
+        .await?;
+    decode_bundle(&result.data)?;
+
+    keys.into_iter()
+        .map(|(key, slot)| Ok((key, slot_value(&result.data, &slot)?.to_vec())))
+        .collect()
+}
+
+fn slot_value<'a>(data: &'a [u8], slot: &BundleSlot) -> Result<&'a [u8], Error> {
+    let value = data
+        .get(slot.offset..slot.offset + slot.len)
+        .ok_or_else(|| {
+            Error::Internal(format!(
+                "Bundle {} is shorter than expected",
+                slot.bundle_address
+            ))
+        })?;
+
+    if PadInfo::checksum(value) != slot.checksum {
+        return Err(Error::Internal(format!(
+            "Checksum mismatch for value at offset {} of bundle {}",
+            slot.offset, slot.bundle_address
+        )));
+    }
+
+    Ok(value)
+}
+
+/// Writes `values` to `pad` as a compacted bundle and records their new slots.
+async fn write_bundle(
+    index: &Arc<RwLock<MasterIndex>>,
+    network: &Arc<Network>,
+    client: &Client,
+    pad: PadInfo,
+    capacity: usize,
+    values: Vec<(String, Vec<u8>)>,
+) -> Result<ScratchpadAddress, Error> {
+    let is_new = index.read().await.get_bundle(&pad.address).is_none();
+    let value_refs: Vec<&[u8]> = values.iter().map(|(_, value)| value.as_slice()).collect();
+    let (data, offsets) = encode_bundle(&value_refs);
+    let mut pad = pad.update_data(&data, 0);
+
+    match network
+        .put(client, &pad, &data, DATA_ENCODING_BUNDLE, false)
+        .await
+    {
+        Ok(result) => index.write().await.record_spend(
+            pad.status == PadStatus::Generated && !result.already_existed,
+            result.cost_atto(),
+        ),
+        Err(e) => {
+            if is_new {
+                index.write().await.free_pads(vec![pad])?;
+            }
+            return Err(Error::Network(e));
+        }
+    }
+    pad.status = PadStatus::Written;
+
+    let confirmation_start = Instant::now();
+    while confirmation_start.elapsed() < MAX_CONFIRMATION_DURATION {
+        if let Ok(true) = network.write_landed(client, &pad, &data, false).await {
+            pad.status = PadStatus::Confirmed;
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    let address = pad.address;
+    let slots = values
+        .iter()
+        .zip(offsets)
+        .map(|((key, value), (offset, len))| {
+            (
+                key.clone(),
+                BundleSlot {
+                    bundle_address: address,
+                    offset,
+                    len,
+                    checksum: PadInfo::checksum(value),
+                },
+            )
+        })
+        .collect();
+
+    index
+        .write()
+        .await
+        .commit_bundle(Bundle { pad, capacity }, slots)?;
+
+    Ok(address)
+}
@@ -0,0 +1,83 @@
+use crate::error::Error;
+use log::debug;
+use std::collections::HashSet;
+use std::future::Future;
+use tokio::sync::Mutex;
+
+/// Tracks the chunks an operation completed so each one is reported exactly once.
+///
+/// Workers finish chunks out of order and a chunk can complete twice, e.g. when a pad
+/// is recycled after its write was already reported. Reports are serialized, so the
+/// completed count they carry only ever goes up.
+#[derive(Default)]
+pub(crate) struct ChunkProgress {
+    completed: Mutex<HashSet<usize>>,
+}
+
+impl ChunkProgress {
+    /// Starts from the chunks an earlier attempt already completed.
+    pub fn with_completed(chunk_indices: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            completed: Mutex::new(chunk_indices.into_iter().collect()),
+        }
+    }
+
+    /// Records `chunk_index` as completed and calls `emit` with the number of completed
+    /// chunks, unless the chunk was already reported.
+    ///
+    /// Returns whether the operation should go on, `false` once `emit` asked to stop.
+    pub async fn report<F, Fut>(&self, chunk_index: usize, emit: F) -> Result<bool, Error>
+    where
+        F: FnOnce(usize) -> Fut,
+        Fut: Future<Output = Result<bool, Error>>,
+    {
+        let mut completed = self.completed.lock().await;
+        if !completed.insert(chunk_index) {
+            debug!("Chunk {} already reported, skipping", chunk_index);
+            return Ok(true);
+        }
+        emit(completed.len()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_shuffled_chunks_are_reported_once_in_count_order() {
+        let progress = Arc::new(ChunkProgress::with_completed([0, 1]));
+
+        // Chunks 0 and 1 were done before, 3 and 7 complete a second time after a retry.
+        let mut chunks: Vec<usize> = (0..20).chain([3, 7]).collect();
+        chunks.shuffle(&mut rand::thread_rng());
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for chunk_index in chunks {
+            let progress = progress.clone();
+            let reports = reports.clone();
+            handles.push(tokio::spawn(async move {
+                progress
+                    .report(chunk_index, |completed_count| async move {
+                        reports.lock().unwrap().push((chunk_index, completed_count));
+                        Ok(true)
+                    })
+                    .await
+            }));
+        }
+        for handle in handles {
+            assert!(handle.await.unwrap().unwrap());
+        }
+
+        let reports = reports.lock().unwrap();
+        let counts: Vec<usize> = reports.iter().map(|(_, count)| *count).collect();
+        assert_eq!(counts, (3..=20).collect::<Vec<_>>());
+
+        let mut indices: Vec<usize> = reports.iter().map(|(index, _)| *index).collect();
+        indices.sort();
+        assert_eq!(indices, (2..20).collect::<Vec<_>>());
+    }
+}
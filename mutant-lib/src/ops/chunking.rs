@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use bytes::Bytes;
+
+use crate::error::Error;
+
+/// The byte ranges splitting `len` bytes of content into chunks of `chunk_size` bytes,
+/// the last one possibly shorter. Empty content has no chunk.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkRanges {
+    len: usize,
+    chunk_size: usize,
+    start: usize,
+}
+
+impl ChunkRanges {
+    pub fn new(len: usize, chunk_size: usize) -> Self {
+        Self {
+            len,
+            chunk_size: chunk_size.max(1),
+            start: 0,
+        }
+    }
+}
+
+impl Iterator for ChunkRanges {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.len {
+            return None;
+        }
+        let end = std::cmp::min(self.start + self.chunk_size, self.len);
+        let range = self.start..end;
+        self.start = end;
+        Some(range)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.len - self.start).div_ceil(self.chunk_size);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ChunkRanges {}
+
+/// The chunks of `data`, as slices of it, see [`ChunkRanges`].
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkIter<'a> {
+    data: &'a [u8],
+    ranges: ChunkRanges,
+}
+
+impl<'a> ChunkIter<'a> {
+    pub fn new(data: &'a [u8], chunk_size: usize) -> Self {
+        Self {
+            data,
+            ranges: ChunkRanges::new(data.len(), chunk_size),
+        }
+    }
+}
+
+impl<'a> Iterator for ChunkIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.ranges.next().map(|range| &self.data[range])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ranges.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ChunkIter<'_> {}
+
+/// Puts the chunks of a value back together, whatever the order they are fetched in.
+///
+/// Chunks are taken out in order, either one by one as soon as the next one in line has
+/// been pushed with [`Reassembler::next_ready`], or all at once with
+/// [`Reassembler::finish`].
+#[derive(Debug, Default)]
+pub(crate) struct Reassembler {
+    pending: BTreeMap<usize, Bytes>,
+    next: usize,
+    taken: usize,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the chunk at `index`, replacing the one already pushed there. A chunk that
+    /// was already taken out is ignored.
+    pub fn push(&mut self, index: usize, chunk: impl Into<Bytes>) {
+        if index >= self.next {
+            self.pending.insert(index, chunk.into());
+        }
+    }
+
+    /// The chunks pushed and not taken out yet.
+    pub fn held(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The index of the next chunk in line.
+    pub fn next_index(&self) -> usize {
+        self.next
+    }
+
+    /// Takes out the next chunk in line, if it was pushed already.
+    pub fn next_ready(&mut self) -> Option<Bytes> {
+        let chunk = self.pending.remove(&self.next)?;
+        self.next += 1;
+        self.taken += chunk.len();
+        Some(chunk)
+    }
+
+    /// Concatenates the chunks not taken out yet, failing if one is missing in between
+    /// or if the value, including the chunks already taken out, is not `expected_size`
+    /// bytes long.
+    pub fn finish(mut self, expected_size: usize) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::with_capacity(expected_size.saturating_sub(self.taken));
+        while let Some(chunk) = self.next_ready() {
+            data.extend_from_slice(&chunk);
+        }
+
+        if !self.pending.is_empty() {
+            return Err(Error::Internal(format!(
+                "Chunk {} is missing, {} chunks after it were fetched",
+                self.next,
+                self.pending.len()
+            )));
+        }
+        if self.taken != expected_size {
+            return Err(Error::Internal(format!(
+                "Reassembled {} bytes, expected {}",
+                self.taken, expected_size
+            )));
+        }
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn content_and_chunk_size() -> impl Strategy<Value = (Vec<u8>, usize)> {
+        (proptest::collection::vec(any::<u8>(), 0..4096), 1usize..512)
+    }
+
+    #[test]
+    fn test_empty_content_has_no_chunk() {
+        assert_eq!(ChunkIter::new(&[], 10).count(), 0);
+        assert!(Reassembler::new().finish(0).unwrap().is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_chunks_cover_the_content((content, chunk_size) in content_and_chunk_size()) {
+            let chunks: Vec<&[u8]> = ChunkIter::new(&content, chunk_size).collect();
+
+            prop_assert_eq!(chunks.len(), content.len().div_ceil(chunk_size));
+            prop_assert_eq!(ChunkIter::new(&content, chunk_size).len(), chunks.len());
+            if let Some((last, full)) = chunks.split_last() {
+                prop_assert!(full.iter().all(|c| c.len() == chunk_size));
+                prop_assert!(!last.is_empty() && last.len() <= chunk_size);
+            }
+            prop_assert_eq!(chunks.concat(), content);
+        }
+
+        #[test]
+        fn prop_round_trip_in_any_order(
+            (content, chunk_size) in content_and_chunk_size(),
+            seed in any::<u64>(),
+        ) {
+            let mut chunks: Vec<_> = ChunkIter::new(&content, chunk_size).enumerate().collect();
+            shuffle(&mut chunks, seed);
+
+            let mut reassembler = Reassembler::new();
+            for (index, chunk) in chunks {
+                reassembler.push(index, Bytes::copy_from_slice(chunk));
+            }
+            prop_assert_eq!(reassembler.finish(content.len()).unwrap(), content);
+        }
+
+        #[test]
+        fn prop_streamed_round_trip(
+            (content, chunk_size) in content_and_chunk_size(),
+            seed in any::<u64>(),
+        ) {
+            let mut chunks: Vec<_> = ChunkIter::new(&content, chunk_size).enumerate().collect();
+            shuffle(&mut chunks, seed);
+
+            let mut reassembler = Reassembler::new();
+            let mut streamed = Vec::new();
+            for (index, chunk) in chunks {
+                reassembler.push(index, Bytes::copy_from_slice(chunk));
+                while let Some(ready) = reassembler.next_ready() {
+                    streamed.extend_from_slice(&ready);
+                }
+            }
+            prop_assert_eq!(reassembler.held(), 0);
+            prop_assert!(reassembler.finish(content.len()).unwrap().is_empty());
+            prop_assert_eq!(streamed, content);
+        }
+
+        #[test]
+        fn prop_missing_chunk_fails(
+            (content, chunk_size) in content_and_chunk_size(),
+            missing in any::<prop::sample::Index>(),
+        ) {
+            let total = content.len().div_ceil(chunk_size);
+            prop_assume!(total > 0);
+            let missing = missing.index(total);
+
+            let mut reassembler = Reassembler::new();
+            for (index, chunk) in ChunkIter::new(&content, chunk_size).enumerate() {
+                if index != missing {
+                    reassembler.push(index, Bytes::copy_from_slice(chunk));
+                }
+            }
+            prop_assert!(matches!(
+                reassembler.finish(content.len()),
+                Err(Error::Internal(_))
+            ));
+        }
+
+        #[test]
+        fn prop_size_mismatch_fails(
+            (content, chunk_size) in content_and_chunk_size(),
+            delta in 1usize..64,
+            longer in any::<bool>(),
+        ) {
+            let expected = if longer {
+                content.len() + delta
+            } else {
+                prop_assume!(content.len() >= delta);
+                content.len() - delta
+            };
+
+            let mut reassembler = Reassembler::new();
+            for (index, chunk) in ChunkIter::new(&content, chunk_size).enumerate() {
+                reassembler.push(index, Bytes::copy_from_slice(chunk));
+            }
+            prop_assert!(matches!(reassembler.finish(expected), Err(Error::Internal(_))));
+        }
+    }
+
+    /// Deterministic Fisher-Yates from `seed`, so a failing case shrinks and replays.
+    fn shuffle<T>(items: &mut [T], mut seed: u64) {
+        for i in (1..items.len()).rev() {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            items.swap(i, (seed >> 33) as usize % (i + 1));
+        }
+    }
+}
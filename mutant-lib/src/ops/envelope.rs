@@ -0,0 +1,207 @@
+//! Optional header stored in front of a value, describing what the value is.
+//!
+//! The binary format is stable: a value enveloped by any version of MutAnt can be
+//! read back by any later one, and by other programs fetching the raw bytes of a
+//! public key. All integers are big-endian.
+//!
+//! | Offset     | Size | Field                                   |
+//! |------------|------|-----------------------------------------|
+//! | 0          | 4    | magic, `b"MTEV"`                        |
+//! | 4          | 1    | envelope version, `1`                   |
+//! | 5          | 1    | flags, see [`EnvelopeHeader::COMPRESSED`] |
+//! | 6          | 2    | length `F` of the format                |
+//! | 8          | F    | format, UTF-8                           |
+//! | 8 + F      | 2    | length `A` of the application version   |
+//! | 10 + F     | A    | application version, UTF-8              |
+//! | 10 + F + A | ..   | the value                               |
+//!
+//! Unknown flag bits are kept as they are, readers must ignore the ones they do not
+//! know about. A new envelope version is only introduced for changes older readers
+//! cannot skip over.
+
+use crate::error::Error;
+
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"MTEV";
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Describes the value following it, see the module documentation for the layout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvelopeHeader {
+    /// What the value is, e.g. a MIME type or an application specific name.
+    pub format: String,
+    /// Version of the application that wrote the value.
+    pub app_version: String,
+    pub flags: u8,
+}
+
+impl EnvelopeHeader {
+    /// The value is compressed.
+    pub const COMPRESSED: u8 = 1 << 0;
+    /// The value is encrypted by the application, on top of the pad encryption.
+    pub const ENCRYPTED: u8 = 1 << 1;
+
+    pub fn new(format: impl Into<String>, app_version: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+            app_version: app_version.into(),
+            flags: 0,
+        }
+    }
+
+    pub fn with_flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.flags & Self::COMPRESSED != 0
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & Self::ENCRYPTED != 0
+    }
+
+    /// Number of bytes the header takes in front of the value.
+    pub fn encoded_len(&self) -> usize {
+        10 + self.format.len() + self.app_version.len()
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        let format_len = field_len("format", &self.format)?;
+        let app_version_len = field_len("app version", &self.app_version)?;
+
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.extend_from_slice(&ENVELOPE_MAGIC);
+        bytes.push(ENVELOPE_VERSION);
+        bytes.push(self.flags);
+        bytes.extend_from_slice(&format_len.to_be_bytes());
+        bytes.extend_from_slice(self.format.as_bytes());
+        bytes.extend_from_slice(&app_version_len.to_be_bytes());
+        bytes.extend_from_slice(self.app_version.as_bytes());
+        Ok(bytes)
+    }
+
+    /// Prepends the header to `data`.
+    pub fn wrap(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut bytes = self.encode()?;
+        bytes.extend_from_slice(data);
+        Ok(bytes)
+    }
+
+    /// Reads the header at the start of `bytes`, returning it with its encoded length.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, usize), Error> {
+        if bytes.len() < 6 || bytes[..4] != ENVELOPE_MAGIC {
+            return Err(Error::InvalidEnvelope("missing envelope magic".to_string()));
+        }
+        if bytes[4] != ENVELOPE_VERSION {
+            return Err(Error::InvalidEnvelope(format!(
+                "unsupported envelope version {}",
+                bytes[4]
+            )));
+        }
+        let flags = bytes[5];
+
+        let (format, end) = read_field(bytes, 6, "format")?;
+        let (app_version, end) = read_field(bytes, end, "app version")?;
+
+        Ok((
+            Self {
+                format,
+                app_version,
+                flags,
+            },
+            end,
+        ))
+    }
+
+    /// Splits an enveloped value into its header and the value.
+    pub fn split(mut bytes: Vec<u8>) -> Result<(Self, Vec<u8>), Error> {
+        let (header, len) = Self::decode(&bytes)?;
+        bytes.drain(..len);
+        Ok((header, bytes))
+    }
+}
+
+fn field_len(name: &str, value: &str) -> Result<u16, Error> {
+    u16::try_from(value.len())
+        .map_err(|_| Error::InvalidEnvelope(format!("{} longer than {} bytes", name, u16::MAX)))
+}
+
+/// Reads a length-prefixed string at `start`, returning it with the offset past it.
+fn read_field(bytes: &[u8], start: usize, name: &str) -> Result<(String, usize), Error> {
+    let truncated = || Error::InvalidEnvelope(format!("truncated {}", name));
+
+    let len_bytes = bytes.get(start..start + 2).ok_or_else(truncated)?;
+    let len = u16::from_be_bytes([len_bytes[0], len_bytes[1]]) as usize;
+    let value = bytes
+        .get(start + 2..start + 2 + len)
+        .ok_or_else(truncated)?;
+    let value = String::from_utf8(value.to_vec())
+        .map_err(|_| Error::InvalidEnvelope(format!("{} is not valid UTF-8", name)))?;
+
+    Ok((value, start + 2 + len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::MutAntReader;
+    use tokio::io::AsyncReadExt;
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let header =
+            EnvelopeHeader::new("application/json", "1.2.3").with_flags(EnvelopeHeader::COMPRESSED);
+        let enveloped = header.wrap(b"{\"a\":1}").unwrap();
+        assert_eq!(enveloped.len(), header.encoded_len() + 7);
+
+        let (decoded, data) = EnvelopeHeader::split(enveloped).unwrap();
+        assert_eq!(decoded, header);
+        assert!(decoded.is_compressed() && !decoded.is_encrypted());
+        assert_eq!(data, b"{\"a\":1}");
+    }
+
+    #[test]
+    fn test_envelope_binary_format_is_stable() {
+        let header = EnvelopeHeader::new("txt", "0.1").with_flags(EnvelopeHeader::ENCRYPTED);
+        assert_eq!(
+            header.wrap(b"hi").unwrap(),
+            b"MTEV\x01\x02\x00\x03txt\x00\x030.1hi".to_vec()
+        );
+
+        let (decoded, data) = EnvelopeHeader::split(header.wrap(&[]).unwrap()).unwrap();
+        assert_eq!((decoded, data), (header, vec![]));
+    }
+
+    #[test]
+    fn test_value_without_envelope_is_rejected() {
+        assert!(EnvelopeHeader::decode(b"plain value").is_err());
+        assert!(EnvelopeHeader::decode(b"").is_err());
+        // Starts like an envelope but ends within the header.
+        assert!(EnvelopeHeader::decode(b"MTEV\x01\x00\x00\x09abc").is_err());
+        assert!(EnvelopeHeader::decode(b"MTEV\x02\x00\x00\x00\x00\x00").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reader_leaves_the_header_out() {
+        let header = EnvelopeHeader::new("text/plain", "1.0.0");
+        let enveloped = header.wrap(b"hello").unwrap();
+
+        let mut data = Vec::new();
+        MutAntReader::eager(enveloped)
+            .skipping(header.encoded_len())
+            .read_to_end(&mut data)
+            .await
+            .unwrap();
+        assert_eq!(data, b"hello");
+
+        // Values stored without an envelope are read as they are.
+        let mut data = Vec::new();
+        MutAntReader::eager(b"MTEV plain".to_vec())
+            .skipping(0)
+            .read_to_end(&mut data)
+            .await
+            .unwrap();
+        assert_eq!(data, b"MTEV plain");
+    }
+}
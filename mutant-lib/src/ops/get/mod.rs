@@ -1,17 +1,99 @@
 use crate::error::Error;
 use crate::events::{GetCallback, GetEvent};
-use crate::index::{master_index::MasterIndex, PadInfo};
+use crate::index::error::IndexError;
+use crate::index::{
+    master_index::{missing_chunk_indices, MasterIndex},
+    PadInfo,
+};
 use crate::internal_events::invoke_get_callback;
 use crate::network::client::Config;
 use crate::network::{Network, NetworkError};
+use crate::ops::chunk_progress::ChunkProgress;
+use crate::ops::chunking::Reassembler;
 use crate::ops::worker::{self, AsyncTask, PoolError, WorkerPoolConfig};
+use ant_networking::GetRecordError;
 use async_trait::async_trait;
 use autonomi::ScratchpadAddress;
+use futures::stream::{self, StreamExt};
 use log::{debug, error, warn};
-use std::{sync::Arc, time::Duration};
+use mutant_protocol::{ChunkFailure, VerifyResult};
+use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
-use super::{DATA_ENCODING_PUBLIC_DATA, DATA_ENCODING_PUBLIC_INDEX};
+use super::{ReadStrategy, DATA_ENCODING_PUBLIC_DATA, DATA_ENCODING_PUBLIC_INDEX};
+use crate::cache::PredictiveReadCache;
+
+mod reader;
+mod stream;
+
+pub use reader::MutAntReader;
+pub use stream::FetchStream;
+
+/// Existence checks in flight at once during the pre-flight.
+const PREFLIGHT_CONCURRENCY: usize = 32;
+
+/// Missing pads listed in `Error::PadsMissing`.
+const MISSING_PAD_EXAMPLES: usize = 5;
+
+/// The addresses of `pads` not found on the network. Pads that could not be checked
+/// are left to the fetch itself.
+pub(crate) async fn find_missing_pads(
+    network: &Network,
+    pads: &[PadInfo],
+) -> Result<Vec<ScratchpadAddress>, Error> {
+    let client = network
+        .get_client(Config::Get)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    let missing = stream::iter(pads)
+        .map(|pad| {
+            let client = &client;
+            async move {
+                match network.check_existence(client, &pad.address).await {
+                    Ok(exists) => (!exists).then_some(pad.address),
+                    Err(e) => {
+                        warn!("Could not check that pad {} exists: {}", pad.address, e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(PREFLIGHT_CONCURRENCY)
+        .filter_map(|missing| async move { missing })
+        .collect()
+        .await;
+
+    Ok(missing)
+}
+
+fn pads_missing_error(missing: &[ScratchpadAddress]) -> Option<Error> {
+    if missing.is_empty() {
+        return None;
+    }
+    Some(Error::PadsMissing {
+        count: missing.len(),
+        examples: missing
+            .iter()
+            .take(MISSING_PAD_EXAMPLES)
+            .map(|address| address.to_hex())
+            .collect(),
+    })
+}
+
+/// Fails with `Error::PadsMissing` if some of `pads` are gone, when the pre-flight is on.
+async fn preflight_existence_check(network: &Network, pads: &[PadInfo]) -> Result<(), Error> {
+    if !network.config().preflight_existence_check {
+        return Ok(());
+    }
+
+    let missing = find_missing_pads(network, pads).await?;
+    match pads_missing_error(&missing) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
 
 pub(super) async fn get_public(
     network: Arc<Network>,
@@ -41,30 +123,44 @@ pub(super) async fn get_public(
 
             debug!("get_public: Index contains {} data pads", index.len());
 
+            let missing_indices = missing_chunk_indices(&index);
+            if !missing_indices.is_empty() {
+                return Err(IndexError::IndexCorrupted {
+                    key: address.to_hex(),
+                    missing_indices,
+                }
+                .into());
+            }
+
+            preflight_existence_check(&network, &index).await?;
+
+            // Only the data pads are counted, their chunk indices are the progress.
             invoke_get_callback(
                 &callback,
                 GetEvent::Starting {
-                    total_chunks: index.len() + 1,
+                    total_chunks: index.len(),
                 },
             )
             .await
             .unwrap();
 
-            invoke_get_callback(&callback, GetEvent::PadFetched)
-                .await
-                .unwrap();
-
             debug!("get_public: Fetching data pads");
-            fetch_pads_data(network, index, true, callback).await
+            fetch_pads_data(&address.to_hex(), network, index, true, callback).await
         }
         DATA_ENCODING_PUBLIC_DATA => {
             debug!("get_public: Found PUBLIC_DATA pad, returning data directly");
-            invoke_get_callback(&callback, GetEvent::Starting { total_chunks: 1000 })
-                .await
-                .unwrap();
-            invoke_get_callback(&callback, GetEvent::PadFetched)
+            invoke_get_callback(&callback, GetEvent::Starting { total_chunks: 1 })
                 .await
                 .unwrap();
+            invoke_get_callback(
+                &callback,
+                GetEvent::PadFetched {
+                    chunk_index: 0,
+                    completed_count: 1,
+                },
+            )
+            .await
+            .unwrap();
             invoke_get_callback(&callback, GetEvent::Complete)
                 .await
                 .unwrap();
@@ -90,12 +186,16 @@ pub(super) async fn get(
         )));
     }
 
+    index.read().await.validate_chunk_indices(name)?;
+
     let pads = index.read().await.get_pads(name);
 
     if pads.is_empty() {
         return Err(Error::Internal(format!("No pads found for key {}", name)));
     }
 
+    preflight_existence_check(&network, &pads).await?;
+
     let callback = get_callback.clone();
     let is_public = index.read().await.is_public(name);
     let total_chunks = pads.len();
@@ -106,7 +206,114 @@ pub(super) async fn get(
 
     let pads_to_fetch = pads; // Use the vector directly
 
-    fetch_pads_data(network, pads_to_fetch, is_public, callback).await
+    fetch_pads_data(name, network, pads_to_fetch, is_public, callback).await
+}
+
+pub(super) async fn get_reader(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    name: &str,
+    strategy: ReadStrategy,
+    read_cache: Arc<PredictiveReadCache>,
+) -> Result<MutAntReader, Error> {
+    let effective_strategy = match strategy {
+        ReadStrategy::Predictive => read_cache.strategy_for(name),
+        other => other,
+    };
+
+    debug!(
+        "get_reader: Opening reader on key {} with {:?} strategy (configured {:?})",
+        name, effective_strategy, strategy
+    );
+
+    let reader = match effective_strategy {
+        ReadStrategy::Lazy => lazy_reader(index, network, name).await?,
+        _ => MutAntReader::eager(get(index, network, name, None).await?),
+    };
+
+    if strategy == ReadStrategy::Predictive {
+        Ok(reader.with_tracking(read_cache, name))
+    } else {
+        Ok(reader)
+    }
+}
+
+/// Opens a reader fetching the pads of `name` in chunk order, with `window` of them in
+/// flight ahead of the one being read.
+pub(super) async fn get_windowed_reader(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    name: &str,
+    window: usize,
+) -> Result<MutAntReader, Error> {
+    debug!(
+        "get_windowed_reader: Opening reader on key {} with a window of {} pads",
+        name, window
+    );
+
+    Ok(lazy_reader(index, network, name).await?.with_window(window))
+}
+
+/// Streams the value of `name` in chunk order without its first `skip` bytes, with
+/// `window` pads fetched at once. A value without pads is an empty stream.
+pub(super) async fn fetch_stream(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    name: &str,
+    window: usize,
+    skip: usize,
+    get_callback: Option<GetCallback>,
+) -> Result<FetchStream, Error> {
+    let index_guard = index.read().await;
+    if !index_guard.is_finished(name) {
+        return Err(Error::Internal(format!(
+            "Key {} upload is not finished, cannot get data",
+            name
+        )));
+    }
+
+    index_guard.validate_chunk_indices(name)?;
+
+    let pads = index_guard.get_pads(name);
+    let is_public = index_guard.is_public(name);
+    drop(index_guard);
+
+    preflight_existence_check(&network, &pads).await?;
+    let fetch = reader::fetch_chunk(name, network, is_public, get_callback.clone());
+    Ok(stream::chunk_stream(
+        fetch,
+        pads,
+        window,
+        skip,
+        get_callback,
+    ))
+}
+
+async fn lazy_reader(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    name: &str,
+) -> Result<MutAntReader, Error> {
+    let index_guard = index.read().await;
+    if !index_guard.is_finished(name) {
+        return Err(Error::Internal(format!(
+            "Key {} upload is not finished, cannot get data",
+            name
+        )));
+    }
+
+    index_guard.validate_chunk_indices(name)?;
+
+    let pads = index_guard.get_pads(name);
+    if pads.is_empty() {
+        return Err(Error::Internal(format!("No pads found for key {}", name)));
+    }
+
+    let is_public = index_guard.is_public(name);
+    drop(index_guard);
+
+    preflight_existence_check(&network, &pads).await?;
+    Ok(MutAntReader::lazy(name, network, pads, is_public))
 }
 
 // Context for the GET AsyncTask - REMOVED (or simplified)
@@ -117,9 +324,12 @@ pub(super) async fn get(
 #[derive(Clone)] // Required by WorkerPool
 struct GetTaskProcessor {
     // Keep fields needed by process method
+    /// Key whose pads are fetched, or the hex address of a public index pad.
+    key_name: Arc<String>,
     network: Arc<Network>,
     public: bool,
     get_callback: Option<GetCallback>,
+    fetched: Arc<ChunkProgress>,
     // Remove fields related to old distribution logic
     // completion_notifier: Arc<Notify>,
     // total_items: Arc<std::sync::atomic::AtomicUsize>,
@@ -128,15 +338,65 @@ struct GetTaskProcessor {
 
 impl GetTaskProcessor {
     // Update constructor
-    fn new(network: Arc<Network>, public: bool, get_callback: Option<GetCallback>) -> Self {
+    fn new(
+        key_name: &str,
+        network: Arc<Network>,
+        public: bool,
+        get_callback: Option<GetCallback>,
+    ) -> Self {
         Self {
+            key_name: Arc::new(key_name.to_string()),
             network,
             public,
             get_callback,
+            fetched: Arc::new(ChunkProgress::default()),
         }
     }
 }
 
+impl GetTaskProcessor {
+    /// Reports the copies of `pad` on the network, warning when there are fewer than
+    /// `min_replication`. Networks unable to count them are only logged.
+    async fn report_replication(
+        &self,
+        client: &autonomi::Client,
+        pad: &PadInfo,
+        min_replication: usize,
+    ) -> Result<(), Error> {
+        let copies = match self.network.pad_replication(client, &pad.address).await {
+            Ok(copies) => copies,
+            Err(NetworkError::Unsupported(reason)) => {
+                debug!(
+                    "Replication of chunk {} unknown: {}",
+                    pad.chunk_index, reason
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                warn!("Could not count the copies of pad {}: {}", pad.address, e);
+                return Ok(());
+            }
+        };
+
+        if copies < min_replication {
+            warn!(
+                "Chunk {} (pad {}) has {} copies, below the expected {}",
+                pad.chunk_index, pad.address, copies, min_replication
+            );
+        }
+
+        invoke_get_callback(
+            &self.get_callback,
+            GetEvent::ChunkReplication {
+                chunk_index: pad.chunk_index,
+                copies,
+            },
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
 // Use () for Context generic as it's no longer stored in the pool
 #[async_trait]
 impl AsyncTask<PadInfo, (), autonomi::Client, Vec<u8>, Error>
@@ -150,7 +410,6 @@ impl AsyncTask<PadInfo, (), autonomi::Client, Vec<u8>, Error>
         client: &autonomi::Client,
         pad: PadInfo,
     ) -> Result<(Self::ItemId, Vec<u8>), (Error, PadInfo)> {
-        let mut retries_left = 20;
         let owned_key;
         let secret_key_ref = if self.public {
             None
@@ -159,59 +418,95 @@ impl AsyncTask<PadInfo, (), autonomi::Client, Vec<u8>, Error>
             Some(&owned_key)
         };
 
-        loop {
-            match self
-                .network // Access directly from self
-                .get(client, &pad.address, secret_key_ref)
-                .await
-            {
-                Ok(get_result) => {
-                    let checksum_match = pad.checksum == PadInfo::checksum(&get_result.data);
-                    let counter_match = pad.last_known_counter == get_result.counter;
-                    let size_match = pad.size == get_result.data.len();
-                    if checksum_match && counter_match && size_match {
-                        // Invoke callback directly
-                        invoke_get_callback(&self.get_callback, GetEvent::PadFetched)
-                            .await
-                            .map_err(|e| (e, pad.clone()))?;
-
-                        return Ok((pad.chunk_index, get_result.data));
+        let network = &self.network;
+        let address = pad.address;
+        let last_known_counter = pad.last_known_counter;
+        let get_result = network
+            .config()
+            .retry_policy
+            .retry(
+                &format!("GET of pad {} (chunk {})", address, pad.chunk_index),
+                |_| async move {
+                    let get_result = network.get(client, &address, secret_key_ref).await?;
+                    // An older counter is a replica not updated yet, worth reading again.
+                    if get_result.counter < last_known_counter {
+                        return Err(NetworkError::StaleRecord(format!(
+                            "pad {} has counter {}, expected {}",
+                            address, get_result.counter, last_known_counter
+                        )));
                     }
-                }
-                Err(e) => match e {
-                    _ => {}
+                    Ok(get_result)
                 },
+                |_| async {},
+            )
+            .await;
+        let get_result = match get_result {
+            Ok(get_result) => get_result,
+            Err(e) => {
+                warn!(
+                    "GET failed for pad {} (chunk {}): {}",
+                    pad.address, pad.chunk_index, e
+                );
+                return Err((Error::Network(e), pad));
             }
+        };
 
-            retries_left -= 1;
-
-            warn!(
-                "GET failed for pad {} (chunk {}). Retries left: {}",
-                pad.address, pad.chunk_index, retries_left
+        // Other data under the counter written, or a later one, will not change.
+        if pad.checksum != PadInfo::checksum(&get_result.data) || pad.size != get_result.data.len()
+        {
+            error!(
+                "Chunk {} of key {} read from pad {} differs from what was stored",
+                pad.chunk_index, self.key_name, pad.address
             );
+            return Err((
+                Error::ChecksumMismatch {
+                    key: self.key_name.to_string(),
+                    chunk_index: pad.chunk_index,
+                    address: pad.address.to_hex(),
+                },
+                pad,
+            ));
+        }
 
-            if retries_left <= 0 {
-                return Err((
-                    Error::Internal(format!(
-                        "GET failed for pad {} (chunk {}) after {} retries",
-                        pad.address, pad.chunk_index, 20
-                    )),
-                    pad,
-                ));
-            }
-
-            tokio::time::sleep(Duration::from_secs(1)).await;
+        // Invoke callback directly
+        let callback = &self.get_callback;
+        self.fetched
+            .report(pad.chunk_index, |completed_count| {
+                invoke_get_callback(
+                    callback,
+                    GetEvent::PadFetched {
+                        chunk_index: pad.chunk_index,
+                        completed_count,
+                    },
+                )
+            })
+            .await
+            .map_err(|e| (e, pad.clone()))?;
+        if let Some(min_replication) = self.network.config().min_replication {
+            self.report_replication(client, &pad, min_replication)
+                .await
+                .map_err(|e| (e, pad.clone()))?;
         }
+
+        Ok((pad.chunk_index, pad.strip_padding(get_result.data)))
     }
 }
 
 async fn fetch_pads_data(
+    key_name: &str,
     network: Arc<Network>,
-    pads: Vec<PadInfo>,
+    mut pads: Vec<PadInfo>,
     public: bool,
     get_callback: Option<GetCallback>,
 ) -> Result<Vec<u8>, Error> {
     let total_pads_to_fetch = pads.len();
+    let expected_size: usize = pads.iter().map(|p| p.size - p.padding).sum();
+
+    // Results are put back in chunk order once fetched.
+    if network.config().topology_aware_reads {
+        network.sort_by_estimated_rtt(&mut pads);
+    }
+
     debug!(
         "fetch_pads_data: Starting to fetch {} pads, public={}",
         total_pads_to_fetch, public
@@ -237,7 +532,8 @@ async fn fetch_pads_data(
     }
 
     // 1. Create Task Processor (directly)
-    let task_processor = GetTaskProcessor::new(network.clone(), public, get_callback.clone());
+    let task_processor =
+        GetTaskProcessor::new(key_name, network.clone(), public, get_callback.clone());
 
     // 2. Create WorkerPoolConfig (no Context)
     let config = WorkerPoolConfig {
@@ -246,6 +542,7 @@ async fn fetch_pads_data(
         task_processor,
         enable_recycling: false, // No recycling for GET
         total_items_hint: total_pads_to_fetch,
+        cancel_token: CancellationToken::new(),
     };
 
     // 3. Build WorkerPool (no recycle_fn)
@@ -289,7 +586,7 @@ async fn fetch_pads_data(
 
     // 6. Process Results
     match pool_run_result {
-        Ok(mut fetched_results) => {
+        Ok(fetched_results) => {
             debug!(
                 "fetch_pads_data: Got {} results from worker pool",
                 fetched_results.len()
@@ -307,28 +604,16 @@ async fn fetch_pads_data(
                 )));
             }
 
-            debug!("fetch_pads_data: Sorting results by chunk_index");
-            fetched_results.sort_by_key(|(chunk_index, _)| *chunk_index);
-
-            debug!("fetch_pads_data: Collecting data from all chunks");
-            let collected_data: Vec<Vec<u8>> =
-                fetched_results.into_iter().map(|(_, data)| data).collect();
-
-            let final_capacity: usize = collected_data.iter().map(|data| data.len()).sum();
             debug!(
-                "fetch_pads_data: Assembling final data with capacity {}",
-                final_capacity
+                "fetch_pads_data: Reassembling {} chunks, {} bytes expected",
+                fetched_results.len(),
+                expected_size
             );
-
-            let mut final_data: Vec<u8> = Vec::with_capacity(final_capacity);
-            for (i, pad_data) in collected_data.iter().enumerate() {
-                debug!(
-                    "fetch_pads_data: Adding chunk {} with size {}",
-                    i,
-                    pad_data.len()
-                );
-                final_data.extend(pad_data);
+            let mut reassembler = Reassembler::new();
+            for (chunk_index, pad_data) in fetched_results {
+                reassembler.push(chunk_index, pad_data);
             }
+            let final_data = reassembler.finish(expected_size)?;
 
             debug!("fetch_pads_data: Final data size: {}", final_data.len());
             invoke_get_callback(&get_callback, GetEvent::Complete)
@@ -354,3 +639,93 @@ async fn fetch_pads_data(
         }
     }
 }
+
+/// Fetches every pad of `name` once, up to
+/// [`crate::config::MutAntConfig::max_concurrent_pad_ops`] at a time, and compares it
+/// with the size and checksum recorded in the index. Nothing is retried, a pad not found
+/// or not readable is reported as well.
+pub(super) async fn verify_chunks(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    name: &str,
+) -> Result<VerifyResult, Error> {
+    let index_guard = index.read().await;
+    if !index_guard.contains_key(name) {
+        return Err(IndexError::KeyNotFound(name.to_string()).into());
+    }
+    let pads = index_guard.get_pads(name);
+    let is_public = index_guard.is_public(name);
+    drop(index_guard);
+
+    let client = network
+        .get_client(Config::Get)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    let checks = pads.iter().map(|pad| {
+        let network = &network;
+        let client = &client;
+        async move {
+            let secret_key = (!is_public).then(|| pad.secret_key());
+            let fetched = network
+                .with_pad_op_permit(network.get(client, &pad.address, secret_key.as_ref()))
+                .await;
+            let reason = match fetched {
+                Ok(fetched) if fetched.data.len() != pad.size => Some(format!(
+                    "{} bytes read, {} stored",
+                    fetched.data.len(),
+                    pad.size
+                )),
+                Ok(fetched) if PadInfo::checksum(&fetched.data) != pad.checksum => {
+                    Some("checksum mismatch".to_string())
+                }
+                Ok(_) => None,
+                Err(NetworkError::GetError(GetRecordError::RecordNotFound)) => {
+                    Some("not found on the network".to_string())
+                }
+                Err(e) => Some(e.to_string()),
+            };
+            reason.map(|reason| ChunkFailure {
+                chunk_index: pad.chunk_index,
+                address: pad.address.to_hex(),
+                reason,
+            })
+        }
+    });
+    let mut failures: Vec<ChunkFailure> = futures::future::join_all(checks)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    failures.sort_by_key(|failure| failure.chunk_index);
+
+    Ok(VerifyResult {
+        key: name.to_string(),
+        total_chunks: pads.len(),
+        failures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use autonomi::SecretKey;
+
+    #[test]
+    fn test_pads_missing_error_lists_a_few_addresses() {
+        assert!(pads_missing_error(&[]).is_none());
+
+        let missing: Vec<ScratchpadAddress> = (0..8)
+            .map(|_| ScratchpadAddress::new(SecretKey::random().public_key()))
+            .collect();
+
+        match pads_missing_error(&missing) {
+            Some(Error::PadsMissing { count, examples }) => {
+                assert_eq!(count, 8);
+                assert_eq!(examples.len(), MISSING_PAD_EXAMPLES);
+                assert_eq!(examples[0], missing[0].to_hex());
+            }
+            other => panic!("expected PadsMissing, got {:?}", other),
+        }
+    }
+}
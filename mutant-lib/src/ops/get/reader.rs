@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::OnceCell;
+use tokio::task::JoinHandle;
+
+use crate::cache::PredictiveReadCache;
+use crate::error::Error;
+use crate::events::GetCallback;
+use crate::index::PadInfo;
+use crate::network::client::Config;
+use crate::network::io::inherit_io;
+use crate::network::{Network, NetworkError};
+use crate::ops::worker::AsyncTask;
+
+use super::GetTaskProcessor;
+
+type ChunkFuture = BoxFuture<'static, Result<Vec<u8>, Error>>;
+
+/// Starts the fetch of the data of a pad.
+pub(super) type FetchChunk = Arc<dyn Fn(PadInfo) -> ChunkFuture + Send + Sync>;
+
+/// Fetches pads over a single client, connected by the first fetch, reporting each
+/// fetched pad to `get_callback`.
+pub(super) fn fetch_chunk(
+    key_name: &str,
+    network: Arc<Network>,
+    public: bool,
+    get_callback: Option<GetCallback>,
+) -> FetchChunk {
+    let client: Arc<OnceCell<autonomi::Client>> = Arc::new(OnceCell::new());
+    let processor = GetTaskProcessor::new(key_name, network.clone(), public, get_callback);
+
+    Arc::new(move |pad| {
+        let network = network.clone();
+        let client = client.clone();
+        let processor = processor.clone();
+
+        Box::pin(async move {
+            let client = client
+                .get_or_try_init(|| network.get_client(Config::Get))
+                .await
+                .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?
+                .clone();
+
+            let (_, data) = network
+                .with_pad_op_permit(processor.process(0, &client, pad))
+                .await
+                .map_err(|(e, _)| e)?;
+
+            Ok(data)
+        })
+    })
+}
+
+/// `AsyncRead` over the content of a key.
+///
+/// Depending on the read strategy, the content is either already in memory or
+/// fetched pad by pad in chunk order. Up to `window` pads are fetched ahead of the
+/// chunk being read, in the background, so the head of the content is available as
+/// soon as its own pad arrives while the next ones keep coming.
+pub struct MutAntReader {
+    fetch: Option<FetchChunk>,
+    pads: VecDeque<PadInfo>,
+    window: usize,
+    in_flight: VecDeque<JoinHandle<Result<Vec<u8>, Error>>>,
+    buffer: Vec<u8>,
+    position: usize,
+    /// Bytes at the start of the content not handed out, such as an envelope header.
+    skip: usize,
+    tracking: Option<(Arc<PredictiveReadCache>, String)>,
+    finished: bool,
+}
+
+impl MutAntReader {
+    /// A reader over data that was already fetched.
+    pub(crate) fn eager(data: Vec<u8>) -> Self {
+        Self {
+            fetch: None,
+            pads: VecDeque::new(),
+            window: 1,
+            in_flight: VecDeque::new(),
+            buffer: data,
+            position: 0,
+            skip: 0,
+            tracking: None,
+            finished: false,
+        }
+    }
+
+    /// A reader fetching `pads` in chunk order as it gets consumed, one at a time until
+    /// [`Self::with_window`] widens it.
+    pub(crate) fn lazy(
+        key_name: &str,
+        network: Arc<Network>,
+        pads: Vec<PadInfo>,
+        public: bool,
+    ) -> Self {
+        Self::fetching(pads, fetch_chunk(key_name, network, public, None))
+    }
+
+    fn fetching(mut pads: Vec<PadInfo>, fetch: FetchChunk) -> Self {
+        pads.sort_by_key(|p| p.chunk_index);
+
+        Self {
+            fetch: Some(fetch),
+            pads: pads.into(),
+            window: 1,
+            in_flight: VecDeque::new(),
+            buffer: Vec::new(),
+            position: 0,
+            skip: 0,
+            tracking: None,
+            finished: false,
+        }
+    }
+
+    /// Fetches up to `window` pads ahead of the one being read, at least one.
+    pub(crate) fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Reports to `cache` whether this reader got consumed up to the end once dropped.
+    pub(crate) fn with_tracking(mut self, cache: Arc<PredictiveReadCache>, key_name: &str) -> Self {
+        self.tracking = Some((cache, key_name.to_string()));
+        self
+    }
+
+    /// Leaves out the first `len` bytes of the content.
+    pub(crate) fn skipping(mut self, len: usize) -> Self {
+        self.skip = len;
+        self
+    }
+
+    /// Starts the fetch of the next pads until `window` of them are in flight.
+    fn fill_window(&mut self) {
+        let Some(fetch) = &self.fetch else {
+            return;
+        };
+        while self.in_flight.len() < self.window {
+            match self.pads.pop_front() {
+                Some(pad) => self
+                    .in_flight
+                    .push_back(tokio::spawn(inherit_io(fetch(pad)))),
+                None => break,
+            }
+        }
+    }
+}
+
+impl AsyncRead for MutAntReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.position < this.buffer.len() && this.skip > 0 {
+                let skipped = std::cmp::min(this.skip, this.buffer.len() - this.position);
+                this.position += skipped;
+                this.skip -= skipped;
+                continue;
+            }
+
+            if this.position < this.buffer.len() {
+                let len = std::cmp::min(buf.remaining(), this.buffer.len() - this.position);
+                buf.put_slice(&this.buffer[this.position..this.position + len]);
+                this.position += len;
+                return Poll::Ready(Ok(()));
+            }
+
+            this.fill_window();
+
+            let Some(next) = this.in_flight.front_mut() else {
+                this.finished = true;
+                return Poll::Ready(Ok(()));
+            };
+            let result = match Pin::new(next).poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => result,
+            };
+            this.in_flight.pop_front();
+
+            match result {
+                Ok(Ok(data)) => {
+                    this.buffer = data;
+                    this.position = 0;
+                }
+                Ok(Err(e)) => {
+                    return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e.to_string())));
+                }
+                Err(e) => return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
+            }
+        }
+    }
+}
+
+impl Drop for MutAntReader {
+    fn drop(&mut self) {
+        for fetch in &self.in_flight {
+            fetch.abort();
+        }
+        if let Some((cache, key_name)) = self.tracking.take() {
+            cache.record_read(&key_name, self.finished);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Event {
+        Dispatched(usize),
+        Completed(usize),
+    }
+
+    #[tokio::test]
+    async fn test_head_of_the_content_arrives_before_the_tail_is_dispatched() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let fetch: FetchChunk = {
+            let events = events.clone();
+            Arc::new(move |pad: PadInfo| {
+                let events = events.clone();
+                events
+                    .lock()
+                    .unwrap()
+                    .push(Event::Dispatched(pad.chunk_index));
+                Box::pin(async move {
+                    // Later chunks come back faster, the order has to come from the reader.
+                    let delay = (50 - pad.chunk_index) as u64 / 10;
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    events
+                        .lock()
+                        .unwrap()
+                        .push(Event::Completed(pad.chunk_index));
+                    Ok(vec![pad.chunk_index as u8])
+                })
+            })
+        };
+        let pads = (0..50).rev().map(|i| PadInfo::new(&[i as u8], i)).collect();
+
+        let mut reader = MutAntReader::fetching(pads, fetch).with_window(4);
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).await.unwrap();
+        assert_eq!(content, (0..50u8).collect::<Vec<_>>());
+
+        let events = events.lock().unwrap();
+        let position = |event| events.iter().position(|e| *e == event).unwrap();
+        assert!(position(Event::Completed(0)) < position(Event::Dispatched(49)));
+
+        let mut in_flight = 0;
+        for event in events.iter() {
+            match event {
+                Event::Dispatched(_) => in_flight += 1,
+                Event::Completed(_) => in_flight -= 1,
+            }
+            assert!(in_flight <= 4);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use bytes::{Buf, Bytes};
+use futures::stream::{BoxStream, FuturesUnordered, StreamExt};
+
+use crate::error::Error;
+use crate::events::{GetCallback, GetEvent};
+use crate::index::PadInfo;
+use crate::internal_events::invoke_get_callback;
+use crate::ops::chunking::Reassembler;
+
+use super::reader::FetchChunk;
+
+/// Chunks of a value in order, see [`crate::MutAnt::fetch_stream`].
+pub type FetchStream = BoxStream<'static, Result<Bytes, Error>>;
+
+/// Yields the data of `pads` in chunk order, leaving out its first `skip` bytes.
+///
+/// Up to `window` pads are fetched at once. The ones arriving ahead of their turn wait
+/// in a reorder buffer, which counts toward the window, so that no more than `window`
+/// chunks are ever held whatever the order they come back in. A pad that cannot be
+/// fetched ends the stream with its error.
+pub(super) fn chunk_stream(
+    fetch: FetchChunk,
+    mut pads: Vec<PadInfo>,
+    window: usize,
+    mut skip: usize,
+    get_callback: Option<GetCallback>,
+) -> FetchStream {
+    pads.sort_by_key(|pad| pad.chunk_index);
+    let window = window.max(1);
+
+    async_stream::stream! {
+        let total_chunks = pads.len();
+        let starting = GetEvent::Starting { total_chunks };
+        if let Err(e) = invoke_get_callback(&get_callback, starting).await {
+            yield Err(e);
+            return;
+        }
+
+        let mut to_fetch = pads.into_iter().enumerate();
+        let mut in_flight = FuturesUnordered::new();
+        let mut arrived = Reassembler::new();
+
+        while arrived.next_index() < total_chunks {
+            while in_flight.len() + arrived.held() < window {
+                match to_fetch.next() {
+                    Some((position, pad)) => {
+                        let fetch = fetch.clone();
+                        in_flight.push(async move { (position, fetch(pad).await) });
+                    }
+                    None => break,
+                }
+            }
+
+            if let Some(mut data) = arrived.next_ready() {
+                let skipped = skip.min(data.len());
+                data.advance(skipped);
+                skip -= skipped;
+                if !data.is_empty() {
+                    yield Ok(data);
+                }
+                continue;
+            }
+
+            match in_flight.next().await {
+                Some((position, Ok(data))) => {
+                    arrived.push(position, data);
+                }
+                Some((_, Err(e))) => {
+                    yield Err(e);
+                    return;
+                }
+                None => {
+                    yield Err(Error::Internal(format!(
+                        "Chunk {} of {} was never fetched",
+                        arrived.next_index(),
+                        total_chunks
+                    )));
+                    return;
+                }
+            }
+        }
+
+        if let Err(e) = invoke_get_callback(&get_callback, GetEvent::Complete).await {
+            yield Err(e);
+        }
+    }
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn collecting_callback() -> (GetCallback, Arc<Mutex<Vec<GetEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let callback: GetCallback = Arc::new(move |event| {
+            recorded.lock().unwrap().push(event);
+            Box::pin(async { Ok(true) })
+        });
+        (callback, events)
+    }
+
+    #[tokio::test]
+    async fn test_chunks_come_out_in_order_within_the_window() {
+        let held = Arc::new(AtomicUsize::new(0));
+        let most_held = Arc::new(AtomicUsize::new(0));
+        let fetch: FetchChunk = {
+            let held = held.clone();
+            let most_held = most_held.clone();
+            Arc::new(move |pad: PadInfo| {
+                let held = held.clone();
+                let most_held = most_held.clone();
+                Box::pin(async move {
+                    most_held.fetch_max(held.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    // Later chunks come back faster, the order has to come from the stream.
+                    let delay = (20 - pad.chunk_index) as u64;
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    Ok(vec![pad.chunk_index as u8, 0xff])
+                })
+            })
+        };
+        let pads = (0..20).rev().map(|i| PadInfo::new(&[i as u8], i)).collect();
+        let (callback, events) = collecting_callback();
+
+        let mut stream = chunk_stream(fetch, pads, 4, 1, Some(callback));
+        let mut content = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk.unwrap());
+            // A chunk handed out is no longer held by the stream.
+            held.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        let expected: Vec<u8> = (0..20u8).flat_map(|i| [i, 0xff]).skip(1).collect();
+        assert_eq!(content, expected);
+        assert!(most_held.load(Ordering::SeqCst) <= 4);
+        let events = events.lock().unwrap();
+        assert_eq!(
+            events.first(),
+            Some(&GetEvent::Starting { total_chunks: 20 })
+        );
+        assert_eq!(events.last(), Some(&GetEvent::Complete));
+    }
+
+    #[tokio::test]
+    async fn test_empty_value_still_completes() {
+        let fetch: FetchChunk = Arc::new(|_| Box::pin(async { Ok(Vec::new()) }));
+        let (callback, events) = collecting_callback();
+
+        let chunks: Vec<_> = chunk_stream(fetch, Vec::new(), 4, 0, Some(callback))
+            .collect()
+            .await;
+        assert!(chunks.is_empty());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![GetEvent::Starting { total_chunks: 0 }, GetEvent::Complete]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_chunk_ends_the_stream_with_an_error() {
+        let fetch: FetchChunk = Arc::new(|pad: PadInfo| {
+            Box::pin(async move {
+                if pad.chunk_index == 2 {
+                    Err(Error::Internal("pad not found".to_string()))
+                } else {
+                    Ok(vec![pad.chunk_index as u8])
+                }
+            })
+        });
+        let pads = (0..5).map(|i| PadInfo::new(&[i as u8], i)).collect();
+        let (callback, events) = collecting_callback();
+
+        let chunks: Vec<_> = chunk_stream(fetch, pads, 1, 0, Some(callback))
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].as_ref().unwrap().as_ref(), &[0]);
+        assert_eq!(chunks[1].as_ref().unwrap().as_ref(), &[1]);
+        assert!(matches!(&chunks[2], Err(Error::Internal(msg)) if msg == "pad not found"));
+        assert!(!events.lock().unwrap().contains(&GetEvent::Complete));
+    }
+}
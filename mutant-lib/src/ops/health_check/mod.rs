@@ -3,6 +3,7 @@ use crate::index::PadInfo;
 use crate::index::{master_index::MasterIndex, PadStatus};
 use crate::internal_events::invoke_health_check_callback;
 use crate::network::client::Config;
+use crate::network::io::inherit_io;
 use crate::network::{Network, NetworkError};
 use ant_networking::GetRecordError;
 use log::{error, warn};
@@ -20,6 +21,18 @@ pub(super) async fn health_check(
     recycle: bool,
     health_check_callback: Option<HealthCheckCallback>,
 ) -> Result<HealthCheckResult, Error> {
+    if recycle {
+        let mut index_guard = index.write().await;
+        if index_guard.get_entry(key_name).is_some()
+            && index_guard.renumber_chunk_indices(key_name)?
+        {
+            warn!(
+                "Chunk indices of key {} were offset and have been renumbered",
+                key_name
+            );
+        }
+    }
+
     let pads = index.read().await.get_pads(key_name);
     let nb_recycled = Arc::new(AtomicUsize::new(0));
     let nb_reset = Arc::new(AtomicUsize::new(0));
@@ -52,7 +65,7 @@ pub(super) async fn health_check(
         let index_clone = index.clone();
         let client_clone = client.clone();
 
-        tasks.push(tokio::spawn(async move {
+        tasks.push(tokio::spawn(inherit_io(async move {
             let secret_key_owned;
             let secret_key_ref = if is_public {
                 None
@@ -136,7 +149,7 @@ pub(super) async fn health_check(
                         .unwrap();
                 }
             }
-        }));
+        })));
     }
 
     let results = futures::future::join_all(tasks).await;
@@ -150,6 +163,8 @@ pub(super) async fn health_check(
         }
     }
 
+    index.read().await.flush()?;
+
     invoke_health_check_callback(
         &callback,
         HealthCheckEvent::Complete {
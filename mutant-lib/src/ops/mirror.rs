@@ -0,0 +1,148 @@
+use crate::error::Error;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A regular file found under a mirrored directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LocalFile {
+    pub path: PathBuf,
+    /// Path relative to the mirrored directory, with `/` separators.
+    pub relative: String,
+    pub size: u64,
+    pub modified_at_ms: u64,
+}
+
+/// What a walk of a directory found, every list sorted by relative path.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct TreeWalk {
+    pub files: Vec<LocalFile>,
+    /// Symbolic links, never followed: they could point out of the tree or loop.
+    pub symlinks: Vec<String>,
+    /// Directories holding no file at any depth.
+    pub empty_dirs: Vec<String>,
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> Error {
+    Error::Internal(format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Walks `dir` recursively.
+pub(crate) fn walk_tree(dir: &Path) -> Result<TreeWalk, Error> {
+    let mut walk = TreeWalk::default();
+    walk_dir(dir, "", &mut walk)?;
+    walk.files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    walk.symlinks.sort();
+    walk.empty_dirs.sort();
+    Ok(walk)
+}
+
+/// Walks `dir`, found at `relative`, and returns whether it holds any file.
+fn walk_dir(dir: &Path, relative: &str, walk: &mut TreeWalk) -> Result<bool, Error> {
+    let mut has_files = false;
+
+    for entry in fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+        let entry = entry.map_err(|e| io_error(dir, e))?;
+        let path = entry.path();
+        let name = entry.file_name().into_string().map_err(|name| {
+            Error::Internal(format!(
+                "File name {:?} in {} is not valid UTF-8 and cannot be a key",
+                name,
+                dir.display()
+            ))
+        })?;
+        let entry_relative = if relative.is_empty() {
+            name
+        } else {
+            format!("{}/{}", relative, name)
+        };
+
+        let metadata = fs::symlink_metadata(&path).map_err(|e| io_error(&path, e))?;
+        if metadata.file_type().is_symlink() {
+            walk.symlinks.push(entry_relative);
+        } else if metadata.is_dir() {
+            has_files |= walk_dir(&path, &entry_relative, walk)?;
+        } else if metadata.is_file() {
+            let modified_at_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |elapsed| elapsed.as_millis() as u64);
+            walk.files.push(LocalFile {
+                path,
+                relative: entry_relative,
+                size: metadata.len(),
+                modified_at_ms,
+            });
+            has_files = true;
+        }
+    }
+
+    if !has_files && !relative.is_empty() {
+        walk.empty_dirs.push(relative.to_string());
+    }
+    Ok(has_files)
+}
+
+/// The key a file at `relative` is mirrored to under `prefix`.
+pub(crate) fn tree_key(prefix: &str, relative: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if prefix.is_empty() {
+        relative.to_string()
+    } else {
+        format!("{}/{}", prefix, relative)
+    }
+}
+
+/// SHA-256 of the content of the file at `path`, read in blocks.
+pub(crate) fn hash_file(path: &Path) -> Result<[u8; 32], Error> {
+    let mut file = fs::File::open(path).map_err(|e| io_error(path, e))?;
+    let mut hasher = Sha256::new();
+    let mut block = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut block).map_err(|e| io_error(path, e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&block[..read]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("music/album")).unwrap();
+        fs::create_dir_all(dir.path().join("empty/nested")).unwrap();
+        fs::write(dir.path().join("music/album/track.ogg"), b"track").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"notes").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("notes.txt"), dir.path().join("link")).unwrap();
+
+        let walk = walk_tree(dir.path()).unwrap();
+        let relative: Vec<&str> = walk.files.iter().map(|f| f.relative.as_str()).collect();
+        assert_eq!(relative, vec!["music/album/track.ogg", "notes.txt"]);
+        assert_eq!(walk.files[1].size, 5);
+        assert_eq!(walk.empty_dirs, vec!["empty", "empty/nested"]);
+        #[cfg(unix)]
+        assert_eq!(walk.symlinks, vec!["link"]);
+
+        assert_eq!(
+            hash_file(&walk.files[1].path).unwrap(),
+            <[u8; 32]>::from(Sha256::digest(b"notes"))
+        );
+    }
+
+    #[test]
+    fn test_tree_key() {
+        assert_eq!(tree_key("backup", "a/b.txt"), "backup/a/b.txt");
+        assert_eq!(tree_key("backup/", "a/b.txt"), "backup/a/b.txt");
+        assert_eq!(tree_key("", "a/b.txt"), "a/b.txt");
+    }
+}
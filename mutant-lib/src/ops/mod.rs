@@ -1,24 +1,33 @@
 pub use crate::error::Error;
 
+#[cfg(feature = "bundles")]
+mod bundle;
+mod chunk_progress;
+pub(crate) mod chunking;
+mod envelope;
 mod get;
 mod health_check;
+pub(crate) mod mirror;
 mod purge;
 mod put;
 mod sync;
-mod utils;
+mod transform;
+pub(crate) mod utils;
 pub mod worker;
 
 use crate::{
+    cache::PredictiveReadCache,
     events::{GetCallback, PurgeCallback, SyncCallback},
     index::master_index::MasterIndex,
     network::Network,
 };
 use autonomi::ScratchpadAddress;
 use std::{sync::Arc, time::Duration};
-use tokio::sync::RwLock;
+use tokio::sync::{MutexGuard, RwLock};
 
 use mutant_protocol::{
-    HealthCheckCallback, HealthCheckResult, PurgeResult, PutCallback, StorageMode, SyncResult,
+    HealthCheckCallback, HealthCheckResult, PurgeResult, PutCallback, StorageMode, SyncDirection,
+    SyncResult, VerifyResult,
 };
 
 pub const DATA_ENCODING_MASTER_INDEX: u64 = 0;
@@ -30,14 +39,70 @@ pub const PAD_RECYCLING_RETRIES: usize = 3;
 
 const MAX_CONFIRMATION_DURATION: Duration = Duration::from_secs(60 * 5);
 
+/// What to do when a freshly generated pad turns out to already exist on the network,
+/// which the store quotes of the pad tell before it is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistingPadPolicy {
+    /// Overwrite the existing scratchpad and report it with
+    /// `NetworkEvent::CreateFellBackToUpdate` (default).
+    Update,
+    /// Refuse to write over the existing scratchpad; the pad gets recycled instead.
+    Fail,
+}
+
+impl ExistingPadPolicy {
+    pub fn from_env_value(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "update" => Some(ExistingPadPolicy::Update),
+            "fail" => Some(ExistingPadPolicy::Fail),
+            _ => None,
+        }
+    }
+}
+
+pub use envelope::EnvelopeHeader;
+pub use get::{FetchStream, MutAntReader};
+pub use put::StorePipeline;
+pub use transform::{
+    ChunkTransform, DataError, PadToSizeTransform, Transforms, XChaCha20Poly1305Transform,
+    ZstdTransform,
+};
+
+/// How `MutAnt::get_reader` fetches the pads of a key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadStrategy {
+    /// Fetch every chunk before the first byte can be read (same as `get`).
+    #[default]
+    Eager,
+    /// Fetch each chunk only when the reader reaches it.
+    Lazy,
+    /// Pick between eager and lazy from how the key has been read so far.
+    Predictive,
+}
+
 pub struct Data {
     network: Arc<Network>,
     index: Arc<RwLock<MasterIndex>>,
+    read_strategy: ReadStrategy,
+    read_cache: Arc<PredictiveReadCache>,
 }
 
 impl Data {
-    pub fn new(network: Arc<Network>, index: Arc<RwLock<MasterIndex>>) -> Self {
-        Self { network, index }
+    pub fn new(
+        network: Arc<Network>,
+        index: Arc<RwLock<MasterIndex>>,
+        read_cache: Arc<PredictiveReadCache>,
+    ) -> Self {
+        Self {
+            network,
+            index,
+            read_strategy: ReadStrategy::default(),
+            read_cache,
+        }
+    }
+
+    pub fn set_read_strategy(&mut self, read_strategy: ReadStrategy) {
+        self.read_strategy = read_strategy;
     }
 
     pub async fn put(
@@ -49,6 +114,29 @@ impl Data {
         no_verify: bool,
         put_callback: Option<PutCallback>,
     ) -> Result<ScratchpadAddress, Error> {
+        #[cfg(feature = "bundles")]
+        {
+            let index = self.index.read().await;
+            if bundle::should_bundle(&index, key_name, content.len(), public) {
+                drop(index);
+                return bundle::put(
+                    self.index.clone(),
+                    self.network.clone(),
+                    key_name,
+                    &content,
+                    mode,
+                    put_callback,
+                )
+                .await;
+            }
+
+            // Grown past the bundle threshold or made public, the value gets its own pads.
+            if index.is_bundled(key_name) {
+                drop(index);
+                bundle::remove(self.index.clone(), self.network.clone(), key_name).await?;
+            }
+        }
+
         put::put(
             self.index.clone(),
             self.network.clone(),
@@ -75,9 +163,102 @@ impl Data {
         name: &str,
         get_callback: Option<GetCallback>,
     ) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "bundles")]
+        if self.index.read().await.is_bundled(name) {
+            return bundle::get(self.index.clone(), self.network.clone(), name, get_callback).await;
+        }
+
         get::get(self.index.clone(), self.network.clone(), name, get_callback).await
     }
 
+    pub async fn verify_chunks(&self, name: &str) -> Result<VerifyResult, Error> {
+        get::verify_chunks(self.index.clone(), self.network.clone(), name).await
+    }
+
+    pub async fn get_reader(&self, name: &str) -> Result<MutAntReader, Error> {
+        #[cfg(feature = "bundles")]
+        if self.index.read().await.is_bundled(name) {
+            let data = bundle::get(self.index.clone(), self.network.clone(), name, None).await?;
+            return Ok(MutAntReader::eager(data));
+        }
+
+        get::get_reader(
+            self.index.clone(),
+            self.network.clone(),
+            name,
+            self.read_strategy,
+            self.read_cache.clone(),
+        )
+        .await
+    }
+
+    pub async fn get_windowed_reader(
+        &self,
+        name: &str,
+        window: usize,
+    ) -> Result<MutAntReader, Error> {
+        #[cfg(feature = "bundles")]
+        if self.index.read().await.is_bundled(name) {
+            let data = bundle::get(self.index.clone(), self.network.clone(), name, None).await?;
+            return Ok(MutAntReader::eager(data));
+        }
+
+        get::get_windowed_reader(self.index.clone(), self.network.clone(), name, window).await
+    }
+
+    pub async fn fetch_stream(
+        &self,
+        name: &str,
+        window: usize,
+        skip: usize,
+        get_callback: Option<GetCallback>,
+    ) -> Result<FetchStream, Error> {
+        #[cfg(feature = "bundles")]
+        if self.index.read().await.is_bundled(name) {
+            let mut data =
+                bundle::get(self.index.clone(), self.network.clone(), name, get_callback).await?;
+            let data = data.split_off(skip.min(data.len()));
+            return Ok(Box::pin(futures::stream::once(async {
+                Ok(bytes::Bytes::from(data))
+            })));
+        }
+
+        get::fetch_stream(
+            self.index.clone(),
+            self.network.clone(),
+            name,
+            window,
+            skip,
+            get_callback,
+        )
+        .await
+    }
+
+    /// Holds off the bundle writes while keys leave their bundles in the index, which a
+    /// concurrent rewrite of the bundle would record again.
+    #[cfg(feature = "bundles")]
+    pub async fn lock_bundle_writes(&self) -> Option<MutexGuard<'static, ()>> {
+        Some(bundle::lock_writes().await)
+    }
+
+    #[cfg(not(feature = "bundles"))]
+    pub async fn lock_bundle_writes(&self) -> Option<MutexGuard<'static, ()>> {
+        None
+    }
+
+    /// Releases or compacts a bundle that keys left, once the bundle writes are resumed.
+    #[cfg(feature = "bundles")]
+    pub async fn compact_bundle(&self, address: &ScratchpadAddress) -> Result<(), Error> {
+        bundle::compact(self.index.clone(), self.network.clone(), address).await
+    }
+
+    /// Bundles are only written with the `bundles` feature, the ones of an index written
+    /// with it stay as they are.
+    #[cfg(not(feature = "bundles"))]
+    pub async fn compact_bundle(&self, _address: &ScratchpadAddress) -> Result<(), Error> {
+        Ok(())
+    }
+
     pub async fn purge(
         &self,
         aggressive: bool,
@@ -121,6 +302,27 @@ impl Data {
         )
         .await
     }
+
+    /// The remote index, read without touching the local one.
+    pub async fn read_remote_index(&self) -> Result<Option<MasterIndex>, Error> {
+        sync::read_remote_index(&self.network).await
+    }
+
+    pub async fn sync_keys(
+        &self,
+        patterns: &[String],
+        direction: SyncDirection,
+        sync_callback: Option<SyncCallback>,
+    ) -> Result<SyncResult, Error> {
+        sync::sync_keys(
+            self.index.clone(),
+            self.network.clone(),
+            patterns,
+            direction,
+            sync_callback,
+        )
+        .await
+    }
 }
 
 // fn derive_master_index_info(
@@ -12,6 +12,7 @@ use log::{debug, error, info, warn};
 use mutant_protocol::PurgeResult;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Clone, Copy)]
 enum PurgeTaskOutcome {
@@ -227,6 +228,7 @@ pub(super) async fn purge(
         task_processor,
         enable_recycling: false,
         total_items_hint: total_pads,
+        cancel_token: CancellationToken::new(),
     };
 
     let pool = match crate::ops::worker::build(config, None).await {
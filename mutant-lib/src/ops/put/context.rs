@@ -1,7 +1,9 @@
 use crate::network::Network;
+use crate::ops::chunk_progress::ChunkProgress;
 use std::{ops::Range, sync::Arc};
 use tokio::sync::RwLock;
 use mutant_protocol::PutCallback;
+use tokio_util::sync::CancellationToken;
 
 /// Context for put operations
 #[derive(Clone)]
@@ -20,4 +22,8 @@ pub struct PutTaskContext {
     pub base_context: Context,
     pub no_verify: Arc<bool>,
     pub put_callback: Option<PutCallback>,
+    pub written: ChunkProgress,
+    pub confirmed: ChunkProgress,
+    /// Cancelled once the callback asks to stop, every worker then drops its pad.
+    pub cancel_token: CancellationToken,
 }
@@ -1,20 +1,88 @@
 mod context;
 mod operations;
 mod pipeline;
+mod streaming;
 mod task;
 #[cfg(test)]
 mod tests;
 
 use crate::error::Error;
 use crate::network::Network;
+use crate::rng::InstanceRng;
 use autonomi::ScratchpadAddress;
 use log::info;
 use mutant_protocol::{PutCallback, StorageMode};
+use rand::Rng;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 // Re-export the main operations
 pub use operations::{first_store, resume, update};
+pub use streaming::StorePipeline;
+
+/// Longest delay before each pad write with `shuffle_pads`.
+const MAX_WRITE_JITTER_MS: u64 = 2000;
+
+/// A random delay of up to [`MAX_WRITE_JITTER_MS`], drawn from `rng`.
+fn write_jitter(rng: &InstanceRng) -> Duration {
+    Duration::from_millis(rng.with_rng(|rng| rng.gen_range(0..=MAX_WRITE_JITTER_MS)))
+}
+
+/// Appends random filler bytes, drawn from `rng`, to `content` up to the next multiple
+/// of `chunk_size`.
+///
+/// The filler tells nothing about the value, the same tail is padded differently each
+/// time. An interrupted store of a padded value is therefore stored afresh rather than
+/// resumed. Returns the padded content and the number of filler bytes.
+pub(crate) fn pad_final_chunk(
+    content: &[u8],
+    chunk_size: usize,
+    rng: &InstanceRng,
+) -> (Vec<u8>, usize) {
+    let remainder = content.len() % chunk_size;
+    if remainder == 0 {
+        return (content.to_vec(), 0);
+    }
+
+    let padding = chunk_size - remainder;
+    let mut padded = content.to_vec();
+    padded.resize(content.len() + padding, 0);
+    rng.with_rng(|rng| rng.fill(&mut padded[content.len()..]));
+
+    (padded, padding)
+}
+
+/// Pads `content` when `pad_final_chunk` is set. Returns the content to store and the
+/// number of filler bytes, to be recorded with [`record_final_chunk_padding`] once the
+/// store succeeds.
+async fn apply_final_chunk_padding(
+    index: &RwLock<crate::index::master_index::MasterIndex>,
+    network: &Network,
+    content: Arc<Vec<u8>>,
+    mode: &StorageMode,
+) -> (Arc<Vec<u8>>, usize) {
+    let pad_final = index.read().await.config().pad_final_chunk;
+    if pad_final && !content.is_empty() {
+        let (padded, padding) = pad_final_chunk(&content, mode.scratchpad_size(), network.rng());
+        (Arc::new(padded), padding)
+    } else {
+        (content, 0)
+    }
+}
+
+/// Records the filler bytes of the value just stored under `key_name`. Only called
+/// once the store succeeded, so a failed one leaves the key's padding record alone.
+async fn record_final_chunk_padding(
+    index: &RwLock<crate::index::master_index::MasterIndex>,
+    key_name: &str,
+    padding: usize,
+) -> Result<(), Error> {
+    index
+        .write()
+        .await
+        .set_final_chunk_padding(key_name, padding)
+}
 
 /// Main entry point for put operations
 pub(super) async fn put(
@@ -27,7 +95,16 @@ pub(super) async fn put(
     no_verify: bool,
     put_callback: Option<PutCallback>,
 ) -> Result<ScratchpadAddress, Error> {
-    if index.read().await.contains_key(key_name) {
+    let chunks = content.len().div_ceil(mode.scratchpad_size()) + public as usize;
+    index.read().await.ensure_room_for(key_name, chunks)?;
+    index
+        .read()
+        .await
+        .ensure_quota_for(key_name, content.len(), chunks)?;
+
+    let (content, padding) = apply_final_chunk_padding(&index, &network, content, &mode).await;
+
+    let address = if index.read().await.contains_key(key_name) {
         if index
             .read()
             .await
@@ -35,7 +112,22 @@ pub(super) async fn put(
         {
             info!("Resume for {}", key_name);
             resume(
-                index,
+                index.clone(),
+                network,
+                key_name,
+                content,
+                mode,
+                public,
+                no_verify,
+                put_callback,
+            )
+            .await
+        } else if !index.read().await.is_finished(key_name) {
+            // The interrupted store was of another value, there is nothing to pick up.
+            info!("Storing {} afresh over an interrupted store", key_name);
+            index.write().await.remove_key(key_name)?;
+            first_store(
+                index.clone(),
                 network,
                 key_name,
                 content,
@@ -48,7 +140,7 @@ pub(super) async fn put(
         } else {
             // Call the dedicated update function
             update(
-                index,
+                index.clone(),
                 network,
                 key_name,
                 content,
@@ -62,7 +154,7 @@ pub(super) async fn put(
     } else {
         info!("First store for {}", key_name);
         first_store(
-            index,
+            index.clone(),
             network,
             key_name,
             content,
@@ -72,5 +164,8 @@ pub(super) async fn put(
             put_callback,
         )
         .await
-    }
+    }?;
+
+    record_final_chunk_padding(&index, key_name, padding).await?;
+    Ok(address)
 }
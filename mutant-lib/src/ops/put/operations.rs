@@ -1,11 +1,13 @@
 use crate::error::Error;
+use crate::index::master_index::{internal_key, IndexEntry};
 use crate::index::{PadInfo, PadStatus};
 use crate::internal_events::invoke_put_callback;
 use crate::network::Network;
 use crate::ops::{DATA_ENCODING_PRIVATE_DATA, DATA_ENCODING_PUBLIC_DATA, DATA_ENCODING_PUBLIC_INDEX};
 use autonomi::ScratchpadAddress;
-use log::info;
+use log::{info, warn};
 use mutant_protocol::{PutCallback, PutEvent, StorageMode};
+use std::ops::Range;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -17,13 +19,16 @@ use super::pipeline::write_pipeline;
 /// This function:
 /// 1. Compares checksums between existing pads and new data chunks
 /// 2. Reuses pads with matching checksums (keeps them as is)
-/// 3. Marks pads with different checksums as needing update (sets to Free status)
+/// 3. Gives the chunks with different checksums, and the new ones, fresh pads
 /// 4. Handles cases where the new data is longer or shorter than the existing data
 /// 5. Preserves the public index pad for public keys
 ///
+/// The new pads are staged under an internal key while they are written: the key keeps
+/// its previous pads, and its previous value stays readable, until every new pad is
+/// confirmed. A failed update leaves the key as it was.
+///
 /// This approach is more efficient than the original update method because it:
 /// - Avoids unnecessary network operations for unchanged data chunks
-/// - Preserves pad addresses when possible, reducing network churn
 /// - Only uploads data that has actually changed
 pub async fn update(
     index: Arc<RwLock<crate::index::master_index::MasterIndex>>,
@@ -37,8 +42,22 @@ pub async fn update(
 ) -> Result<ScratchpadAddress, Error> {
     info!("Efficient update for {}", key_name);
 
-    // Get existing pads for the key
-    let existing_pads = index.read().await.get_pads(key_name);
+    if !public && index.read().await.is_derived_key(key_name) {
+        return update_derived(
+            index,
+            network,
+            key_name,
+            content,
+            mode,
+            no_verify,
+            put_callback,
+        )
+        .await;
+    }
+
+    // Get existing pads for the key, in chunk order to line them up with the new chunks
+    let mut existing_pads = index.read().await.get_pads(key_name);
+    existing_pads.sort_by_key(|pad| pad.chunk_index);
     if existing_pads.is_empty() {
         return Err(Error::Internal(format!("Key '{}' not found", key_name)));
     }
@@ -64,110 +83,203 @@ pub async fn update(
         key_name, existing_data_pads_count, new_data_pads_count
     );
 
-    // Create a vector to hold the updated pads
-    let mut updated_pads = Vec::with_capacity(new_data_pads_count);
-
-    // Process existing pads up to the minimum of existing and new counts
-    let min_count = std::cmp::min(existing_data_pads_count, new_data_pads_count);
-
-    for i in 0..min_count {
-        let pad = &existing_pads[i];
-        let chunk_range = &chunk_ranges[i];
-        let chunk_data = &content[chunk_range.clone()];
-        let chunk_checksum = PadInfo::checksum(chunk_data);
+    let (reused_pads, chunks_unchanged) =
+        reuse_unchanged_pads(&existing_pads, &content, &chunk_ranges);
+    let chunks_rewritten = new_data_pads_count - chunks_unchanged;
 
-        // If checksums match, keep the pad as is
-        if pad.checksum == chunk_checksum {
-            info!("Pad {} (chunk {}) has matching checksum, keeping as is", pad.address, i);
-            updated_pads.push(pad.clone());
-        } else {
-            // If checksums don't match, mark the pad for update
-            info!("Pad {} (chunk {}) has different checksum, marking for update", pad.address, i);
-            let mut updated_pad = pad.clone();
-            updated_pad.status = PadStatus::Free;
-            updated_pad.checksum = chunk_checksum;
-            updated_pad.size = chunk_data.len();
-            updated_pad.last_known_counter += 1;
-            updated_pads.push(updated_pad);
-        }
+    info!(
+        "Update for key '{}': {} chunks unchanged, {} to rewrite",
+        key_name, chunks_unchanged, chunks_rewritten
+    );
+    invoke_put_callback(
+        &put_callback,
+        PutEvent::UpdatePlanned {
+            chunks_unchanged,
+            chunks_rewritten,
+        },
+    )
+    .await
+    .map_err(|e| Error::Internal(format!("Callback error on UpdatePlanned event: {:?}", e)))?;
+
+    // A previous update of the key that died before finishing left its staged pads
+    let staged_key = internal_key(&format!("update/{}", key_name));
+    if index.read().await.contains_key(&staged_key) {
+        index
+            .write()
+            .await
+            .discard_staged_pads(&staged_key, key_name)?;
     }
 
-    // If we need more pads than we have, acquire new ones
-    if new_data_pads_count > existing_data_pads_count {
-        info!(
-            "Need {} additional pads for key '{}'",
-            new_data_pads_count - existing_data_pads_count,
-            key_name
-        );
-
-        // Acquire additional pads for the remaining chunks
-        let additional_chunks = chunk_ranges[existing_data_pads_count..].to_vec();
-
-        // Determine the start and end of the additional content in the original content
-        let start_offset = additional_chunks[0].start;
-        let end_offset = additional_chunks.last().unwrap().end;
-
-        // Create a slice of the original content
-        let additional_content = Arc::new(content[start_offset..end_offset].to_vec());
+    // Give fresh pads to the chunks that changed and to the ones past the previous value
+    let rewritten_ranges: Vec<_> = reused_pads
+        .iter()
+        .zip(&chunk_ranges)
+        .filter(|(pad, _)| pad.is_none())
+        .map(|(_, range)| range.clone())
+        .collect();
+    let mut fresh_pads = index
+        .write()
+        .await
+        .acquire_pads(&content, &rewritten_ranges)?
+        .into_iter();
+    let updated_pads: Vec<PadInfo> = reused_pads
+        .into_iter()
+        .enumerate()
+        .map(|(chunk_index, pad)| match pad {
+            Some(pad) => pad,
+            None => {
+                let mut pad = fresh_pads
+                    .next()
+                    .expect("a fresh pad for each rewritten chunk");
+                pad.chunk_index = chunk_index;
+                pad
+            }
+        })
+        .collect();
 
-        // Create new chunk ranges relative to the slice
-        let mut new_chunk_ranges = Vec::new();
-        for chunk_range in &additional_chunks {
-            // Adjust the range to be relative to the slice
-            let relative_start = chunk_range.start - start_offset;
-            let relative_end = chunk_range.end - start_offset;
-            new_chunk_ranges.push(relative_start..relative_end);
+    let staged_entry = match preserved_index_pad {
+        Some(mut index_pad) => {
+            index_pad.status = PadStatus::Free;
+            index_pad.last_known_counter += 1;
+            IndexEntry::PublicUpload(index_pad, updated_pads.clone())
         }
+        None => IndexEntry::PrivateKey(updated_pads.clone()),
+    };
+    index.write().await.add_entry(&staged_key, staged_entry)?;
+    if network.config().shuffle_pads && !public {
+        index.write().await.shuffle_chunks(&staged_key)?;
+    }
 
-        // Use the existing acquire_pads method to get the additional pads
-        let mut additional_pads = index
+    if let Err(e) = write_staged_pads(
+        &index,
+        &network,
+        &staged_key,
+        updated_pads.clone(),
+        chunk_ranges,
+        content,
+        public,
+        no_verify,
+        &put_callback,
+    )
+    .await
+    {
+        if let Err(discard_err) = index
             .write()
             .await
-            .acquire_pads(&additional_content, &new_chunk_ranges)?;
-
-        // Update the chunk indices for the additional pads
-        for (i, pad) in additional_pads.iter_mut().enumerate() {
-            // Set the correct chunk index (offset by the number of existing pads)
-            pad.chunk_index = existing_data_pads_count + i;
+            .discard_staged_pads(&staged_key, key_name)
+        {
+            warn!(
+                "Failed to discard the pads staged for the update of key '{}': {}",
+                key_name, discard_err
+            );
         }
+        return Err(e);
+    }
 
-        updated_pads.extend(additional_pads);
-    } else if new_data_pads_count < existing_data_pads_count {
-        // If we have more pads than we need, free the excess ones
-        info!(
-            "Freeing {} excess pads for key '{}'",
-            existing_data_pads_count - new_data_pads_count,
-            key_name
-        );
+    // Every new pad is confirmed, the key can switch to them
+    index
+        .write()
+        .await
+        .commit_staged_pads(&staged_key, key_name)?;
 
-        // Move excess pads to the free list
-        let excess_pads = existing_pads[new_data_pads_count..].to_vec();
+    // Final completion callback
+    invoke_put_callback(&put_callback, PutEvent::Complete)
+        .await
+        .unwrap();
 
-        index.write().await.free_pads(excess_pads)?;
-    }
+    Ok(updated_pads[0].address)
+}
 
-    // Update the key in the master index
-    if public {
-        // For public keys, update with the preserved index pad
-        if let Some(mut index_pad) = preserved_index_pad {
-            index_pad.status = PadStatus::Free;
-            index_pad.last_known_counter += 1;
+/// Updates the derived key `key_name`, see `MutAnt::precompute_key_addresses`. The pad of
+/// each chunk is fixed by the key name, so unlike [`update`] the changed chunks are written
+/// again in place: a failed update leaves the key unfinished, for the next store to resume.
+async fn update_derived(
+    index: Arc<RwLock<crate::index::master_index::MasterIndex>>,
+    network: Arc<Network>,
+    key_name: &str,
+    content: Arc<Vec<u8>>,
+    mode: StorageMode,
+    no_verify: bool,
+    put_callback: Option<PutCallback>,
+) -> Result<ScratchpadAddress, Error> {
+    let existing_pads = index.read().await.get_pads(key_name);
+    let chunk_ranges = index.read().await.chunk_data(&content, mode);
+    let pads = index.write().await.derived_pads(
+        key_name,
+        &content,
+        &chunk_ranges,
+        network.secret_key(),
+    )?;
+
+    let chunks_unchanged = pads.iter().filter(|p| existing_pads.contains(p)).count();
+    invoke_put_callback(
+        &put_callback,
+        PutEvent::UpdatePlanned {
+            chunks_unchanged,
+            chunks_rewritten: pads.len() - chunks_unchanged,
+        },
+    )
+    .await
+    .map_err(|e| Error::Internal(format!("Callback error on UpdatePlanned event: {:?}", e)))?;
+
+    // The pads past the new end, or not derived, go back to the pad pool
+    let left_behind: Vec<PadInfo> = existing_pads
+        .into_iter()
+        .filter(|old| pads.iter().all(|p| p.address != old.address))
+        .collect();
+    {
+        let mut index = index.write().await;
+        index.update_key_with_pads(key_name, pads.clone(), None)?;
+        index.free_pads(left_behind)?;
+    }
 
-            index.write().await.update_key_with_pads(key_name, updated_pads.clone(), Some(index_pad))?;
-        } else {
-            // This shouldn't happen for existing public keys, but handle it just in case
-            index.write().await.update_key_with_pads(key_name, updated_pads.clone(), None)?;
-        }
-    } else {
-        // For private keys, just update with the new pads
-        index.write().await.update_key_with_pads(key_name, updated_pads.clone(), None)?;
+    let pads_to_write: Vec<PadInfo> = pads
+        .iter()
+        .filter(|p| p.status == PadStatus::Free || p.status == PadStatus::Generated)
+        .cloned()
+        .collect();
+    info!(
+        "Writing {} derived pads for key '{}' (out of {} total)",
+        pads_to_write.len(),
+        key_name,
+        pads.len()
+    );
+    if !pads_to_write.is_empty() {
+        let context = Context {
+            index: index.clone(),
+            network: network.clone(),
+            name: Arc::new(key_name.to_string()),
+            chunk_ranges: Arc::new(chunk_ranges),
+            data: content,
+            public: false,
+            encoding: DATA_ENCODING_PRIVATE_DATA,
+        };
+        write_pipeline(context, pads_to_write, no_verify, put_callback.clone()).await?;
     }
 
-    // Get the address from the first pad
-    let address = updated_pads[0].address;
+    invoke_put_callback(&put_callback, PutEvent::Complete)
+        .await
+        .unwrap();
+
+    Ok(pads[0].address)
+}
 
+/// Writes the pads of an update staged under `staged_key` that are not confirmed yet,
+/// then the index pad of a public key, which lists the staged pads.
+#[allow(clippy::too_many_arguments)]
+async fn write_staged_pads(
+    index: &Arc<RwLock<crate::index::master_index::MasterIndex>>,
+    network: &Arc<Network>,
+    staged_key: &str,
+    pads: Vec<PadInfo>,
+    chunk_ranges: Vec<Range<usize>>,
+    content: Arc<Vec<u8>>,
+    public: bool,
+    no_verify: bool,
+    put_callback: &Option<PutCallback>,
+) -> Result<(), Error> {
     // Filter pads that need to be written (status is Free or Generated)
-    let pads_to_write: Vec<PadInfo> = updated_pads
+    let pads_to_write: Vec<PadInfo> = pads
         .iter()
         .filter(|p| p.status == PadStatus::Free || p.status == PadStatus::Generated)
         .cloned()
@@ -175,10 +287,10 @@ pub async fn update(
 
     if !pads_to_write.is_empty() {
         info!(
-            "Writing {} pads for key '{}' (out of {} total)",
+            "Writing {} pads for {} (out of {} total)",
             pads_to_write.len(),
-            key_name,
-            updated_pads.len()
+            staged_key,
+            pads.len()
         );
 
         let encoding = if public {
@@ -190,9 +302,9 @@ pub async fn update(
         let context = Context {
             index: index.clone(),
             network: network.clone(),
-            name: Arc::new(key_name.to_string()),
+            name: Arc::new(staged_key.to_string()),
             chunk_ranges: Arc::new(chunk_ranges),
-            data: content.clone(),
+            data: content,
             public,
             encoding,
         };
@@ -200,19 +312,19 @@ pub async fn update(
         // Write only the pads that need updating
         write_pipeline(context, pads_to_write, no_verify, put_callback.clone()).await?;
     } else {
-        info!("No pads need to be written for key '{}'", key_name);
+        info!("No pads need to be written for {}", staged_key);
     }
 
     // For public keys, update and write the index pad
     if public {
-        let (index_pad, index_data) = index.write().await.populate_index_pad(key_name)?;
+        let (index_pad, index_data) = index.write().await.populate_index_pad(staged_key)?;
         let index_data_bytes: Arc<Vec<u8>> = Arc::new(index_data);
         let index_chunk_ranges = Arc::new(vec![0..index_data_bytes.len()]);
 
         let index_pad_context = Context {
             index: index.clone(),
             network: network.clone(),
-            name: Arc::new(key_name.to_string()),
+            name: Arc::new(staged_key.to_string()),
             chunk_ranges: index_chunk_ranges,
             data: index_data_bytes,
             public,
@@ -229,12 +341,49 @@ pub async fn update(
         .await?;
     }
 
-    // Final completion callback
-    invoke_put_callback(&put_callback, PutEvent::Complete)
-        .await
-        .unwrap();
+    Ok(())
+}
 
-    Ok(address)
+/// The pads of the chunks both the existing and the new content have, in chunk order.
+/// A pad whose chunk kept its checksum and size is reused as is, with no network write;
+/// the others are `None`, their chunk to be written to a fresh pad. The chunks past the
+/// existing content are `None` too.
+///
+/// Returns these pads and the number of them that were reused.
+pub(super) fn reuse_unchanged_pads(
+    existing_pads: &[PadInfo],
+    content: &[u8],
+    chunk_ranges: &[Range<usize>],
+) -> (Vec<Option<PadInfo>>, usize) {
+    let mut unchanged = 0;
+
+    let pads = chunk_ranges
+        .iter()
+        .enumerate()
+        .map(|(i, chunk_range)| {
+            let pad = existing_pads.get(i)?;
+            let chunk_data = &content[chunk_range.clone()];
+            let chunk_checksum = PadInfo::checksum(chunk_data);
+
+            // If checksums match, keep the pad as is
+            if pad.checksum == chunk_checksum && pad.size == chunk_data.len() {
+                info!(
+                    "Pad {} (chunk {}) has matching checksum, keeping as is",
+                    pad.address, i
+                );
+                unchanged += 1;
+                Some(pad.clone())
+            } else {
+                info!(
+                    "Pad {} (chunk {}) has different checksum, rewriting the chunk",
+                    pad.address, i
+                );
+                None
+            }
+        })
+        .collect();
+
+    (pads, unchanged)
 }
 
 pub async fn resume(
@@ -289,8 +438,23 @@ pub async fn resume(
         .await;
     }
 
-    // If all checksums match, we can just return success
-    if index.read().await.verify_checksum(name, &data_bytes, mode.clone()) {
+    if !index.read().await.verify_checksum(name, &data_bytes, mode.clone()) {
+        return update(
+            index,
+            network,
+            name,
+            data_bytes,
+            mode,
+            public,
+            no_verify,
+            put_callback,
+        )
+        .await;
+    }
+
+    // Same data: a complete key needs no upload, an interrupted one is picked up where
+    // it stopped.
+    if index.read().await.is_finished(name) {
         info!("All checksums match for key '{}', no upload needed", name);
 
         // Final completion callback
@@ -301,7 +465,29 @@ pub async fn resume(
         return Ok(pads[0].address);
     }
 
-    // If we get here, we need to upload the data but the pad count matches
+    let confirmed_chunks = pads
+        .iter()
+        .filter(|p| p.status == PadStatus::Confirmed)
+        .count();
+    info!(
+        "Resuming key '{}' with {}/{} chunks confirmed",
+        name,
+        confirmed_chunks,
+        pads.len()
+    );
+    let keep_going = invoke_put_callback(
+        &put_callback,
+        PutEvent::Resuming {
+            confirmed_chunks,
+            total_chunks: pads.len(),
+        },
+    )
+    .await
+    .map_err(|e| Error::Internal(format!("Callback error on Resuming event: {:?}", e)))?;
+    if !keep_going {
+        return Err(Error::OperationCancelled);
+    }
+
     let encoding = if public {
         DATA_ENCODING_PUBLIC_DATA
     } else {
@@ -318,13 +504,9 @@ pub async fn resume(
         encoding,
     };
 
-    // Mark pads as Free to ensure they get uploaded
-    let mut pads_to_write = pads.clone();
-    for pad in &mut pads_to_write {
-        pad.status = PadStatus::Free;
-    }
-
-    write_pipeline(context, pads_to_write, no_verify, put_callback.clone()).await?;
+    // The pipeline skips the confirmed pads, confirms the written ones and writes the
+    // others.
+    write_pipeline(context, pads.clone(), no_verify, put_callback.clone()).await?;
 
     if public {
         let (index_pad, index_data) = index.write().await.populate_index_pad(name)?;
@@ -364,13 +546,24 @@ pub async fn first_store(
     no_verify: bool,
     put_callback: Option<PutCallback>,
 ) -> Result<ScratchpadAddress, Error> {
-    let (pads, chunk_ranges) = index
-        .write()
-        .await
-        .create_key(name, &data_bytes, mode, public)?;
+    let (pads, chunk_ranges) = if !public && index.read().await.is_derived_key(name) {
+        index
+            .write()
+            .await
+            .create_derived_key(name, &data_bytes, mode, network.secret_key())?
+    } else {
+        index
+            .write()
+            .await
+            .create_key(name, &data_bytes, mode, public)?
+    };
 
     info!("Created key {} with {} pads", name, pads.len());
 
+    if network.config().shuffle_pads && !public {
+        index.write().await.shuffle_chunks(name)?;
+    }
+
     let address = pads[0].address;
 
     let encoding = if public {
@@ -389,7 +582,9 @@ pub async fn first_store(
         encoding,
     };
 
-    write_pipeline(context, pads.clone(), no_verify, put_callback.clone()).await?;
+    if let Err(e) = write_pipeline(context, pads.clone(), no_verify, put_callback.clone()).await {
+        return Err(discard_cancelled_key(&index, name, e).await);
+    }
 
     if public {
         let (index_pad, index_data) = index.write().await.populate_index_pad(name)?;
@@ -408,13 +603,16 @@ pub async fn first_store(
         };
 
         // Call write_pipeline again for the single index pad
-        write_pipeline(
+        if let Err(e) = write_pipeline(
             index_pad_context,
             vec![index_pad],
             no_verify,
             put_callback.clone(), // Clone the callback Arc again
         )
-        .await?;
+        .await
+        {
+            return Err(discard_cancelled_key(&index, name, e).await);
+        }
     }
 
     // Final completion callback after all pipelines are done
@@ -424,3 +622,18 @@ pub async fn first_store(
 
     Ok(address)
 }
+
+/// A store cancelled by its callback leaves no key behind: its pads go back to the pad
+/// pool, the written ones to be verified. Any other error keeps the key to be resumed.
+async fn discard_cancelled_key(
+    index: &Arc<RwLock<crate::index::master_index::MasterIndex>>,
+    name: &str,
+    error: Error,
+) -> Error {
+    if matches!(error, Error::OperationCancelled) {
+        if let Err(e) = index.write().await.remove_key(name) {
+            warn!("Failed to remove cancelled key '{}': {}", name, e);
+        }
+    }
+    error
+}
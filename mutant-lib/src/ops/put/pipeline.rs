@@ -2,11 +2,14 @@ use crate::error::Error;
 use crate::index::PadInfo;
 use crate::internal_events::invoke_put_callback;
 use crate::network::NetworkError;
+use crate::ops::chunk_progress::ChunkProgress;
 use crate::ops::worker::{self, PoolError, WorkerPoolConfig};
 use futures::future::BoxFuture;
 use log::{debug, error, info, warn};
 use mutant_protocol::{PutCallback, PutEvent};
+use rand::seq::SliceRandom;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 use super::context::Context;
 use super::context::PutTaskContext;
@@ -18,6 +21,15 @@ pub async fn recycle_put_pad(
     error_cause: Error,
     pad_to_recycle: PadInfo,
 ) -> Result<Option<PadInfo>, Error> {
+    // The pads of a cancelled store are released with its key, not rewritten.
+    if matches!(error_cause, Error::OperationCancelled) {
+        debug!(
+            "Dropping pad {} of cancelled store of key '{}'",
+            pad_to_recycle.address, context.name
+        );
+        return Ok(None);
+    }
+
     warn!(
         "Recycling pad {} for key '{}' due to error: {:?}",
         pad_to_recycle.address, context.name, error_cause
@@ -67,6 +79,17 @@ pub async fn write_pipeline(
 ) -> Result<(), Error> {
     let key_name = context.name.clone();
 
+    // The pads of a key stored with its chunks shuffled are recorded under their slot,
+    // number them and their chunk ranges the same way.
+    let index = context.index.read().await;
+    let pads = index.to_slot_order(&key_name, pads);
+    let chunk_ranges = index.ranges_in_slot_order(&key_name, context.chunk_ranges.to_vec());
+    drop(index);
+    let context = Context {
+        chunk_ranges: Arc::new(chunk_ranges),
+        ..context
+    };
+
     // Count pads by status before filtering
     let total_chunks = pads.len();
     let initial_written_count = pads
@@ -88,7 +111,7 @@ pub async fn write_pipeline(
         key_name, total_chunks, initial_written_count, initial_confirmed_count, chunks_to_reserve
     );
 
-    invoke_put_callback(
+    let keep_going = invoke_put_callback(
         &put_callback,
         PutEvent::Starting {
             total_chunks,
@@ -99,13 +122,33 @@ pub async fn write_pipeline(
     )
     .await
     .map_err(|e| Error::Internal(format!("Callback error on Starting event: {:?}", e)))?;
+    if !keep_going {
+        return Err(Error::OperationCancelled);
+    }
+
+    let written_chunks: Vec<usize> = pads
+        .iter()
+        .filter(|p| p.status == PadStatus::Written || p.status == PadStatus::Confirmed)
+        .map(|p| p.chunk_index)
+        .collect();
+    let confirmed_chunks: Vec<usize> = pads
+        .iter()
+        .filter(|p| p.status == PadStatus::Confirmed)
+        .map(|p| p.chunk_index)
+        .collect();
 
     // Filter out already confirmed pads - these don't need processing
-    let pads_to_process: Vec<PadInfo> = pads
+    let mut pads_to_process: Vec<PadInfo> = pads
         .into_iter()
         .filter(|p| p.status != PadStatus::Confirmed)
         .collect();
     let initial_process_count = pads_to_process.len();
+    if context.network.config().shuffle_pads {
+        context
+            .network
+            .rng()
+            .with_rng(|rng| pads_to_process.shuffle(rng));
+    }
 
     if initial_process_count == 0 {
         info!("All pads for key '{}' already confirmed.", key_name);
@@ -117,10 +160,14 @@ pub async fn write_pipeline(
     }
 
     // 1. Create Context for Task Processor
+    let cancel_token = CancellationToken::new();
     let put_task_context = Arc::new(PutTaskContext {
         base_context: context.clone(), // Clone base context Arc
         no_verify: Arc::new(no_verify),
         put_callback: put_callback.clone(),
+        written: ChunkProgress::with_completed(written_chunks),
+        confirmed: ChunkProgress::with_completed(confirmed_chunks),
+        cancel_token: cancel_token.clone(),
     });
 
     // 2. Create Task Processor
@@ -135,6 +182,7 @@ pub async fn write_pipeline(
         task_processor,
         enable_recycling: true, // Ensure recycling is enabled for PUT
         total_items_hint: initial_process_count, // Use the number of pads we're actually processing
+        cancel_token: cancel_token.clone(),
     };
 
     debug!(
@@ -201,9 +249,21 @@ pub async fn write_pipeline(
     // Make sure to pass the recycle_fn to ensure the recycling mechanism is active
     let pool_result = pool.run(Some(recycle_fn)).await;
 
+    // Pad statuses are only checkpointed while the pool runs, persist the final ones.
+    let flush_result = context.index.write().await.save_transitions();
+    if let Err(e) = &flush_result {
+        warn!("Failed to save index after PUT of key '{}': {}", key_name, e);
+    }
+
+    if cancel_token.is_cancelled() {
+        warn!("PUT of key '{}' cancelled by its callback", key_name);
+        return Err(Error::OperationCancelled);
+    }
+
     // 7. Process Pool Results
     match pool_result {
         Ok(_results) => {
+            flush_result?;
             // For PUT, successful completion of the pool.run() without error is the main success signal,
             // assuming the recycler handled intermediate task errors.
             // We could potentially verify the final state in the index here if needed.
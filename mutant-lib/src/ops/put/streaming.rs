@@ -0,0 +1,323 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use autonomi::Client;
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::{debug, error, info};
+use mutant_protocol::{AuditOperation, StorageMode};
+use tokio::io::AsyncWrite;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::api::MutAnt;
+use crate::error::Error;
+use crate::index::error::IndexError;
+use crate::index::master_index::IndexEntry;
+use crate::index::{LazyIndex, PadInfo, PadStatus};
+use crate::network::client::Config;
+use crate::network::io::inherit_io;
+use crate::network::{Network, NetworkError};
+use crate::ops::{DATA_ENCODING_PRIVATE_DATA, MAX_CONFIRMATION_DURATION};
+
+type SendFuture = BoxFuture<'static, Result<(), mpsc::error::SendError<(usize, Vec<u8>)>>>;
+
+/// Memory-bounded upload of a private key through `AsyncWrite`.
+///
+/// Bytes are accumulated into scratchpad-sized chunks, each full chunk being handed
+/// to a background writer through a channel of `max_buffered_chunks` slots. The writer
+/// writes up to `max_buffered_chunks` pads at once, so at most
+/// `2 * max_buffered_chunks + 2` chunks are held in memory. The key only appears in the
+/// index once `flush` succeeds, with pads that are all confirmed; on failure every
+/// acquired pad is released.
+pub struct StorePipeline {
+    mutant: MutAnt,
+    key_name: String,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+    next_chunk_index: usize,
+    bytes_written: usize,
+    start: Instant,
+    sender: Option<mpsc::Sender<(usize, Vec<u8>)>>,
+    pending_send: Option<SendFuture>,
+    writer: JoinHandle<Result<Vec<PadInfo>, Error>>,
+}
+
+impl StorePipeline {
+    /// Starts streaming a new private key, failing like `MutAnt::put` on a read-only
+    /// instance, a reserved key name or an index already too large, and on a key that
+    /// already exists.
+    pub async fn new(
+        mutant: &MutAnt,
+        key_name: String,
+        mode: StorageMode,
+        max_buffered_chunks: usize,
+    ) -> Result<StorePipeline, Error> {
+        let key_name = mutant.ensure_streamable(&key_name).await?;
+        let max_buffered_chunks = max_buffered_chunks.max(1);
+        let (sender, receiver) = mpsc::channel(max_buffered_chunks);
+        let writer = tokio::spawn(inherit_io(write_chunks(
+            mutant.index(),
+            mutant.network(),
+            key_name.clone(),
+            receiver,
+            max_buffered_chunks,
+        )));
+
+        Ok(StorePipeline {
+            mutant: mutant.clone(),
+            key_name,
+            chunk_size: mode.scratchpad_size(),
+            buffer: Vec::new(),
+            next_chunk_index: 0,
+            bytes_written: 0,
+            start: Instant::now(),
+            sender: Some(sender),
+            pending_send: None,
+            writer,
+        })
+    }
+
+    fn start_send(&mut self) -> io::Result<()> {
+        let sender = self
+            .sender
+            .clone()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "pipeline already closed"))?;
+        let chunk = std::mem::take(&mut self.buffer);
+        let chunk_index = self.next_chunk_index;
+        self.next_chunk_index += 1;
+
+        self.pending_send = Some(Box::pin(async move { sender.send((chunk_index, chunk)).await }));
+        Ok(())
+    }
+
+    fn poll_pending_send(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(pending) = self.pending_send.as_mut() {
+            match pending.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(result) => {
+                    self.pending_send = None;
+                    if result.is_err() {
+                        return Poll::Ready(Err(io::Error::new(
+                            io::ErrorKind::BrokenPipe,
+                            "background pad writer stopped",
+                        )));
+                    }
+                }
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Writes the remaining buffered bytes, waits for every pad to be confirmed and
+    /// registers the key in the index.
+    pub async fn flush(mut self) -> Result<(), Error> {
+        let result = self.finish().await;
+        self.mutant
+            .audit_store(
+                AuditOperation::Store,
+                &self.key_name,
+                self.bytes_written,
+                self.start,
+                &result,
+            )
+            .await;
+        result
+    }
+
+    async fn finish(&mut self) -> Result<(), Error> {
+        let send_result = std::future::poll_fn(|cx| self.poll_close(cx)).await;
+
+        let pads = match (&mut self.writer).await {
+            Ok(result) => result?,
+            Err(e) => return Err(Error::Internal(format!("Pad writer task failed: {}", e))),
+        };
+
+        send_result.map_err(|e| Error::Internal(format!("Streaming store failed: {}", e)))?;
+
+        let index = self.mutant.index();
+        let mut index = index.write().await?;
+        if index.contains_key(&self.key_name) {
+            index.free_pads(pads)?;
+            return Err(IndexError::KeyAlreadyExists(self.key_name.clone()).into());
+        }
+
+        info!(
+            "Streaming store of key {} complete with {} pads",
+            self.key_name,
+            pads.len()
+        );
+        index.add_entry(&self.key_name, IndexEntry::PrivateKey(pads))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures::ready!(self.poll_pending_send(cx))?;
+
+        if !self.buffer.is_empty() {
+            self.start_send()?;
+            futures::ready!(self.poll_pending_send(cx))?;
+        }
+
+        // Dropping the sender lets the background writer finish.
+        self.sender = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for StorePipeline {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        futures::ready!(this.poll_pending_send(cx))?;
+
+        let len = std::cmp::min(buf.len(), this.chunk_size - this.buffer.len());
+        this.buffer.extend_from_slice(&buf[..len]);
+        this.bytes_written += len;
+
+        if this.buffer.len() == this.chunk_size {
+            this.start_send()?;
+        }
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_pending_send(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_close(cx)
+    }
+}
+
+/// Writes the chunks received to new pads, up to `max_in_flight` at once, and hands
+/// back the pads once every one of them is confirmed. On failure the pads acquired so
+/// far are released and the first error is returned.
+async fn write_chunks(
+    index: LazyIndex,
+    network: Arc<Network>,
+    key_name: String,
+    mut receiver: mpsc::Receiver<(usize, Vec<u8>)>,
+    max_in_flight: usize,
+) -> Result<Vec<PadInfo>, Error> {
+    let client = network
+        .get_client(Config::Put)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+    let mut pads: Vec<PadInfo> = Vec::new();
+    let mut in_flight = FuturesUnordered::new();
+    let mut content_len = 0;
+    let mut failure = None;
+
+    while let Some((chunk_index, chunk)) = receiver.recv().await {
+        content_len += chunk.len();
+        let acquired = index.write().await.and_then(|mut index| {
+            index.acquire_streamed_pad(&key_name, &chunk, chunk_index, content_len)
+        });
+        match acquired {
+            Ok(pad) => in_flight.push(write_chunk(&index, &network, &client, pad, chunk)),
+            Err(e) => {
+                failure = Some(e);
+                break;
+            }
+        }
+
+        if in_flight.len() >= max_in_flight {
+            if let Some(written) = in_flight.next().await {
+                match written {
+                    Ok(pad) => pads.push(pad),
+                    Err((e, pad)) => {
+                        pads.push(pad);
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    // Stops the pipeline from sending more chunks once the store failed.
+    receiver.close();
+
+    while let Some(written) = in_flight.next().await {
+        match written {
+            Ok(pad) => pads.push(pad),
+            Err((e, pad)) => {
+                pads.push(pad);
+                failure.get_or_insert(e);
+            }
+        }
+    }
+
+    if let Some(e) = failure {
+        index.write().await?.free_pads(pads)?;
+        return Err(e);
+    }
+
+    pads.sort_by_key(|pad| pad.chunk_index);
+    Ok(pads)
+}
+
+/// Writes `chunk` to `pad` and waits for the write to be confirmed, handing the pad
+/// back with the error when it is not.
+async fn write_chunk(
+    index: &LazyIndex,
+    network: &Network,
+    client: &Client,
+    mut pad: PadInfo,
+    chunk: Vec<u8>,
+) -> Result<PadInfo, (Error, PadInfo)> {
+    debug!(
+        "Streaming chunk {} ({} bytes) to pad {}",
+        pad.chunk_index,
+        chunk.len(),
+        pad.address
+    );
+
+    let written = network
+        .with_pad_op_permit(network.put(client, &pad, &chunk, DATA_ENCODING_PRIVATE_DATA, false))
+        .await;
+    let result = match written {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Failed to write streamed chunk {}: {}", pad.chunk_index, e);
+            return Err((Error::Network(e), pad));
+        }
+    };
+    match index.write().await {
+        Ok(mut index) => index.record_spend(
+            pad.status == PadStatus::Generated && !result.already_existed,
+            result.cost_atto(),
+        ),
+        Err(e) => return Err((e, pad)),
+    }
+    pad.status = PadStatus::Written;
+
+    let confirmation_start = Instant::now();
+    while confirmation_start.elapsed() < MAX_CONFIRMATION_DURATION {
+        if let Ok(true) = network.write_landed(client, &pad, &chunk, false).await {
+            pad.status = PadStatus::Confirmed;
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    if pad.status != PadStatus::Confirmed {
+        error!(
+            "Failed to confirm streamed chunk {} in pad {} within {:?}",
+            pad.chunk_index, pad.address, MAX_CONFIRMATION_DURATION
+        );
+        return Err((
+            Error::Internal(format!("Confirmation timeout: {}", pad.address)),
+            pad,
+        ));
+    }
+    Ok(pad)
+}
@@ -11,8 +11,8 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::Instant;
 
-use super::super::PAD_RECYCLING_RETRIES;
 use super::context::PutTaskContext;
+use super::write_jitter;
 
 #[derive(Clone)]
 pub struct PutTaskProcessor {
@@ -23,18 +23,19 @@ impl PutTaskProcessor {
     pub fn new(context: Arc<PutTaskContext>) -> Self {
         Self { context }
     }
-}
 
-#[async_trait]
-impl AsyncTask<PadInfo, PutTaskContext, autonomi::Client, (), Error> for PutTaskProcessor {
-    type ItemId = usize;
+    /// Cancels the whole store, handing back the pad this worker was on.
+    fn cancel(&self, pad: PadInfo) -> (Error, PadInfo) {
+        self.context.cancel_token.cancel();
+        (Error::OperationCancelled, pad)
+    }
 
-    async fn process(
+    async fn write_pad(
         &self,
         worker_id: usize,
         client: &autonomi::Client,
         pad: PadInfo,
-    ) -> Result<(Self::ItemId, ()), (Error, PadInfo)> {
+    ) -> Result<(usize, ()), (Error, PadInfo)> {
         let mut pad_state = pad.clone();
         let current_pad_address = pad_state.address;
         let initial_status = pad_state.status;
@@ -80,87 +81,148 @@ impl AsyncTask<PadInfo, PutTaskContext, autonomi::Client, (), Error> for PutTask
                     )
                 })?;
 
-            let max_put_retries = PAD_RECYCLING_RETRIES;
-            let mut last_put_error: Option<Error> = None;
-            for attempt in 1..=max_put_retries {
-                let put_result = self
-                    .context
-                    .base_context
-                    .network
-                    .put(
-                        client,
-                        &pad_state,
-                        chunk_data,
-                        self.context.base_context.encoding,
-                        is_public,
-                    )
-                    .await;
-
-                match put_result {
-                    Ok(_) => {
-                        // Check if this was a Generated pad that needs a PadReserved event
-                        let was_generated = initial_status == PadStatus::Generated;
-
-                        pad_state.status = PadStatus::Written;
-                        match self
-                            .context
-                            .base_context
-                            .index
-                            .write()
-                            .await
-                            .update_pad_status(
-                                &self.context.base_context.name,
-                                &current_pad_address,
-                                PadStatus::Written,
-                                None,
-                            ) {
-                            Ok(updated_pad) => pad_state = updated_pad,
-                            Err(e) => return Err((e, pad_state.clone())),
-                        }
+            let network = &self.context.base_context.network;
+            let config = network.config();
+            if config.shuffle_pads {
+                tokio::time::sleep(write_jitter(network.rng())).await;
+            }
 
-                        // If the pad was in Generated status, send PadReserved event
-                        if was_generated {
+            let encoding = self.context.base_context.encoding;
+            let callback = &self.context.put_callback;
+            let written_pad = &pad_state;
+            let put_result = config
+                .retry_policy
+                .retry(
+                    &format!(
+                        "Worker {} write of pad {} (chunk {})",
+                        worker_id, current_pad_address, chunk_index
+                    ),
+                    |attempt| async move {
+                        // A previous attempt may have timed out after its write actually landed,
+                        // in which case writing again would bump the counter twice.
+                        if attempt > 1
+                            && network
+                                .write_landed(client, written_pad, chunk_data, is_public)
+                                .await?
+                        {
                             info!(
-                                "Worker {} sending PadReserved event for pad {} (chunk {})",
-                                worker_id, current_pad_address, pad_state.chunk_index
+                                "Worker {} found previous write of pad {} already landed, skipping retry",
+                                worker_id, current_pad_address
                             );
-                            invoke_put_callback(&self.context.put_callback, PutEvent::PadReserved)
-                                .await
-                                .map_err(|e| (e, pad_state.clone()))?;
+                            return Ok(None);
                         }
-
-                        put_succeeded = true;
-                        last_put_error = None;
-                        break;
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Worker {} failed put attempt {}/{} for pad {} (chunk {}): {}. Retrying...",
-                            worker_id, attempt, max_put_retries, current_pad_address, pad_state.chunk_index, e
-                        );
-                        last_put_error = Some(Error::Network(e));
-                        if attempt < max_put_retries {
-                            tokio::time::sleep(Duration::from_secs(1)).await;
+                        network
+                            .put(client, written_pad, chunk_data, encoding, is_public)
+                            .await
+                            .map(Some)
+                    },
+                    |attempt| async move {
+                        if let Err(e) = invoke_put_callback(
+                            callback,
+                            PutEvent::ChunkWriteRetried {
+                                chunk_index,
+                                attempt,
+                            },
+                        )
+                        .await
+                        {
+                            warn!(
+                                "Worker {} failed to report retry of chunk {}: {}",
+                                worker_id, chunk_index, e
+                            );
                         }
-                    }
+                    },
+                )
+                .await;
+            let put_result = match put_result {
+                Ok(put_result) => put_result,
+                Err(e @ NetworkError::PadAlreadyExists(_)) => {
+                    // Under `ExistingPadPolicy::Fail`, nothing was written.
+                    warn!(
+                        "Worker {} refused to overwrite existing pad {} (chunk {})",
+                        worker_id, current_pad_address, chunk_index
+                    );
+                    return Err((Error::Network(e), pad_state));
+                }
+                Err(e) => {
+                    error!(
+                        "Worker {} failed to write pad {} (chunk {}): {}",
+                        worker_id, current_pad_address, chunk_index, e
+                    );
+                    return Err((Error::Network(e), pad_state));
                 }
+            };
+
+            // Check if this was a Generated pad that needs a PadReserved event
+            let was_generated = initial_status == PadStatus::Generated;
+
+            let updated_instead = put_result
+                .as_ref()
+                .map_or(false, |result| result.already_existed);
+            if was_generated && updated_instead {
+                warn!(
+                    "Worker {} expected to create pad {} (chunk {}) but it already existed, updated it instead",
+                    worker_id, current_pad_address, pad_state.chunk_index
+                );
+                invoke_put_callback(
+                    &self.context.put_callback,
+                    PutEvent::CreateFellBackToUpdate {
+                        address: current_pad_address.to_hex(),
+                    },
+                )
+                .await
+                .map_err(|e| (e, pad_state.clone()))?;
             }
 
-            if !put_succeeded {
-                return Err((
-                    last_put_error.unwrap_or_else(|| {
-                        Error::Internal(format!(
-                            "Put failed for pad {} after {} retries with unknown error",
-                            current_pad_address, max_put_retries
-                        ))
-                    }),
-                    pad_state,
-                ));
+            pad_state.status = PadStatus::Written;
+            let mut index_guard = self.context.base_context.index.write().await;
+            if let Some(result) = &put_result {
+                index_guard.record_spend(was_generated && !updated_instead, result.cost_atto());
+            }
+            match index_guard.update_pad_status(
+                &self.context.base_context.name,
+                &current_pad_address,
+                PadStatus::Written,
+                None,
+            ) {
+                Ok(updated_pad) => pad_state = updated_pad,
+                Err(e) => return Err((e, pad_state.clone())),
+            }
+            drop(index_guard);
+
+            // If the pad was in Generated status, send PadReserved event
+            if was_generated {
+                info!(
+                    "Worker {} sending PadReserved event for pad {} (chunk {})",
+                    worker_id, current_pad_address, pad_state.chunk_index
+                );
+                let keep_going =
+                    invoke_put_callback(&self.context.put_callback, PutEvent::PadReserved)
+                        .await
+                        .map_err(|e| (e, pad_state.clone()))?;
+                if !keep_going {
+                    return Err(self.cancel(pad_state));
+                }
             }
+            put_succeeded = true;
 
-            invoke_put_callback(&self.context.put_callback, PutEvent::PadsWritten)
+            let keep_going = self
+                .context
+                .written
+                .report(pad_state.chunk_index, |completed_count| {
+                    invoke_put_callback(
+                        callback,
+                        PutEvent::PadsWritten {
+                            chunk_index: pad_state.chunk_index,
+                            completed_count,
+                        },
+                    )
+                })
                 .await
                 .map_err(|e| (e, pad_state.clone()))?;
+            if !keep_going {
+                return Err(self.cancel(pad_state));
+            }
         } else {
             put_succeeded = true;
             pad_state = pad.clone();
@@ -170,6 +232,7 @@ impl AsyncTask<PadInfo, PutTaskContext, autonomi::Client, (), Error> for PutTask
             let confirmation_start = Instant::now();
             let max_duration = MAX_CONFIRMATION_DURATION;
             let mut confirmation_succeeded = false;
+            let mut other_data_read = false;
 
             while confirmation_start.elapsed() < max_duration {
                 let owned_key;
@@ -214,6 +277,9 @@ impl AsyncTask<PadInfo, PutTaskContext, autonomi::Client, (), Error> for PutTask
                                     warn!("Worker {} failed to update index status to Confirmed for pad {}: {}. Retrying confirmation...", worker_id, current_pad_address, e);
                                 }
                             }
+                        } else {
+                            // May be a replica not updated yet, read again until the deadline.
+                            other_data_read = true;
                         }
                     }
                     Err(NetworkError::GetError(ant_networking::GetRecordError::RecordNotFound)) => {
@@ -237,17 +303,64 @@ impl AsyncTask<PadInfo, PutTaskContext, autonomi::Client, (), Error> for PutTask
                     "Worker {} failed to confirm pad {} within {:?}. Returning error.",
                     worker_id, current_pad_address, max_duration
                 );
+                if other_data_read {
+                    return Err((
+                        Error::VerificationFailed {
+                            chunk_index: pad_state.chunk_index,
+                            address: current_pad_address.to_hex(),
+                        },
+                        pad_state,
+                    ));
+                }
                 return Err((
                     Error::Internal(format!("Confirmation timeout: {}", current_pad_address)),
                     pad_state,
                 ));
             }
 
-            invoke_put_callback(&self.context.put_callback, PutEvent::PadsConfirmed)
+            let callback = &self.context.put_callback;
+            let keep_going = self
+                .context
+                .confirmed
+                .report(pad_state.chunk_index, |completed_count| {
+                    invoke_put_callback(
+                        callback,
+                        PutEvent::PadsConfirmed {
+                            chunk_index: pad_state.chunk_index,
+                            completed_count,
+                        },
+                    )
+                })
                 .await
                 .map_err(|e| (e, pad_state.clone()))?;
+            if !keep_going {
+                return Err(self.cancel(pad_state));
+            }
         }
 
         Ok((pad_state.chunk_index, ()))
     }
 }
+
+#[async_trait]
+impl AsyncTask<PadInfo, PutTaskContext, autonomi::Client, (), Error> for PutTaskProcessor {
+    type ItemId = usize;
+
+    async fn process(
+        &self,
+        worker_id: usize,
+        client: &autonomi::Client,
+        pad: PadInfo,
+    ) -> Result<(Self::ItemId, ()), (Error, PadInfo)> {
+        let cancel_token = &self.context.cancel_token;
+        if cancel_token.is_cancelled() {
+            return Err((Error::OperationCancelled, pad));
+        }
+
+        // Once the store is cancelled, the write in flight is dropped at its next await.
+        tokio::select! {
+            result = self.write_pad(worker_id, client, pad.clone()) => result,
+            _ = cancel_token.cancelled() => Err((Error::OperationCancelled, pad)),
+        }
+    }
+}
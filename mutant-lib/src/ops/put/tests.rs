@@ -4,11 +4,13 @@ mod tests {
     use tokio::sync::RwLock;
     use rand::RngCore;
 
+    use crate::error::Error;
     use crate::index::master_index::{IndexEntry, MasterIndex};
     use crate::index::{PadInfo, PadStatus};
     use crate::network::{Network, NetworkChoice};
-    use crate::ops::put::operations::{first_store, update};
-    use mutant_protocol::StorageMode;
+    use crate::ops::put::operations::{first_store, reuse_unchanged_pads, update};
+    use crate::rng::InstanceRng;
+    use mutant_protocol::{PutCallback, PutEvent, StorageMode};
 
     // Helper function to generate random data
     fn generate_random_data(size: usize) -> Vec<u8> {
@@ -23,6 +25,7 @@ mod tests {
             Network::new(
                 crate::network::DEV_TESTNET_PRIVATE_KEY_HEX,
                 NetworkChoice::Devnet,
+                Arc::default(),
             )
             .expect("Failed to create network"),
         );
@@ -30,6 +33,21 @@ mod tests {
         (index, network)
     }
 
+    // Helper function to fetch a key back from the network
+    async fn fetch(
+        index: &Arc<RwLock<MasterIndex>>,
+        network: &Arc<Network>,
+        key_name: &str,
+    ) -> Vec<u8> {
+        crate::ops::get::get(index.clone(), network.clone(), key_name, None)
+            .await
+            .expect("Fetch failed")
+    }
+
+    async fn free_pad_count(index: &Arc<RwLock<MasterIndex>>) -> u64 {
+        index.read().await.get_storage_stats().free_pads
+    }
+
     // Helper function to compare data chunks
     fn compare_chunks(original: &[u8], updated: &[u8], mode: StorageMode) -> (usize, usize) {
         let chunk_size = mode.scratchpad_size();
@@ -75,6 +93,7 @@ mod tests {
         // Get the initial pads
         let initial_pads = index.read().await.get_pads(key_name);
         assert!(!initial_pads.is_empty(), "No pads found for initial store");
+        let free_before = free_pad_count(&index).await;
 
         // Create updated data with some changes but same size
         let mut updated_data = initial_data.as_ref().clone();
@@ -112,10 +131,10 @@ mod tests {
             "Number of pads changed after update"
         );
 
-        // Count how many pads were actually updated
+        // Count how many chunks moved to a new pad
         let updated_count = updated_pads
             .iter()
-            .filter(|p| p.last_known_counter > 0)
+            .filter(|p| initial_pads.iter().all(|initial| initial.address != p.address))
             .count();
 
         // Verify that only the changed chunks were updated
@@ -128,6 +147,13 @@ mod tests {
         // Verify we can retrieve the updated data
         let retrieved_data = index.read().await.verify_checksum(key_name, &updated_data, mode.clone());
         assert!(retrieved_data, "Retrieved data doesn't match updated data");
+        assert_eq!(fetch(&index, &network, key_name).await, *updated_data);
+
+        // The changed chunks take new pads, first from the free pads, and give back theirs
+        assert_eq!(
+            free_pad_count(&index).await,
+            free_before.saturating_sub(changed_count as u64) + changed_count as u64
+        );
     }
 
     #[tokio::test]
@@ -159,6 +185,7 @@ mod tests {
         let initial_pads = index.read().await.get_pads(key_name);
         let initial_pad_count = initial_pads.len();
         assert_eq!(initial_pad_count, 1, "Expected initial data to use exactly 1 pad");
+        let free_before = free_pad_count(&index).await;
 
         // Create updated data with larger size that will require multiple pads
         let updated_data = Arc::new(generate_random_data(chunk_size * 3)); // Use 3 pads
@@ -192,6 +219,10 @@ mod tests {
         // Verify we can retrieve the updated data
         let retrieved_data = index.read().await.verify_checksum(key_name, &updated_data, mode.clone());
         assert!(retrieved_data, "Retrieved data doesn't match updated data");
+        assert_eq!(fetch(&index, &network, key_name).await, *updated_data);
+
+        // The 3 new pads come from the free pads first, the previous one goes back
+        assert_eq!(free_pad_count(&index).await, free_before.saturating_sub(3) + 1);
     }
 
     #[tokio::test]
@@ -223,6 +254,7 @@ mod tests {
         let initial_pads = index.read().await.get_pads(key_name);
         let initial_pad_count = initial_pads.len();
         assert!(initial_pad_count > 1, "Expected initial data to use multiple pads");
+        let free_before = free_pad_count(&index).await;
 
         // Create updated data with smaller size that will fit in a single pad
         let updated_data = Arc::new(generate_random_data(chunk_size / 2)); // Use 1 pad
@@ -256,6 +288,10 @@ mod tests {
         // Verify we can retrieve the updated data
         let retrieved_data = index.read().await.verify_checksum(key_name, &updated_data, mode.clone());
         assert!(retrieved_data, "Retrieved data doesn't match updated data");
+        assert_eq!(fetch(&index, &network, key_name).await, *updated_data);
+
+        // The new pad comes from the free pads first, the 3 previous ones go back
+        assert_eq!(free_pad_count(&index).await, free_before.saturating_sub(1) + 3);
     }
 
     #[tokio::test]
@@ -387,16 +423,16 @@ mod tests {
         // Get the updated pads
         let updated_pads = index.read().await.get_pads(key_name);
 
-        // Verify only the first pad was updated (has a higher counter)
-        assert!(
-            updated_pads[0].last_known_counter > initial_pads[0].last_known_counter,
-            "First pad was not updated"
+        // Verify only the first chunk was written again, to a new pad
+        assert_ne!(
+            updated_pads[0].address, initial_pads[0].address,
+            "First chunk was not updated"
         );
 
-        // Verify the other pads were not updated (same counter)
+        // Verify the other pads were kept as is
         for i in 1..updated_pads.len() {
             assert_eq!(
-                updated_pads[i].last_known_counter, initial_pads[i].last_known_counter,
+                updated_pads[i], initial_pads[i],
                 "Pad {} was unnecessarily updated", i
             );
         }
@@ -478,4 +514,432 @@ mod tests {
         let retrieved_data = index.read().await.verify_checksum(key_name, &updated_data, mode.clone());
         assert!(retrieved_data, "Retrieved data doesn't match updated data");
     }
+
+    #[tokio::test]
+    async fn test_failed_update_keeps_the_previous_value() {
+        let (index, network) = setup_test_environment().await;
+        let key_name = "test_failed_update_keeps_the_previous_value";
+        let mode = StorageMode::Medium;
+        let chunk_size = mode.scratchpad_size();
+        let initial_data = Arc::new(generate_random_data(chunk_size * 3));
+
+        first_store(
+            index.clone(),
+            network.clone(),
+            key_name,
+            initial_data.clone(),
+            mode.clone(),
+            false, // private
+            false, // verify
+            None,
+        )
+        .await
+        .expect("Initial store failed");
+        let initial_pads = index.read().await.get_pads(key_name);
+        let pool = |index: &MasterIndex| {
+            let stats = index.get_storage_stats();
+            stats.free_pads + stats.pending_verification_pads
+        };
+        let pool_before = pool(&*index.read().await);
+
+        // Stop the update on its first written chunk, with the two others still to write.
+        let put_callback: PutCallback = Arc::new(|event| {
+            Box::pin(async move { Ok(!matches!(event, PutEvent::PadsWritten { .. })) })
+        });
+        let result = update(
+            index.clone(),
+            network.clone(),
+            key_name,
+            Arc::new(generate_random_data(chunk_size * 3)),
+            mode,
+            false, // private
+            false, // verify
+            Some(put_callback),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::OperationCancelled)));
+
+        // The key keeps its own pads, the three staged for the update are back in the pool.
+        {
+            let index = index.read().await;
+            assert_eq!(index.get_pads(key_name), initial_pads);
+            assert!(index.list_internal_keys().is_empty());
+            assert_eq!(pool(&index), pool_before + 3);
+        }
+
+        // The previous value can still be read.
+        assert_eq!(fetch(&index, &network, key_name).await, *initial_data);
+    }
+
+    #[tokio::test]
+    async fn test_store_cancelled_by_callback_releases_its_pads() {
+        let (index, network) = setup_test_environment().await;
+        let key_name = "test_store_cancelled_by_callback";
+        let mode = StorageMode::Medium;
+        let data = Arc::new(generate_random_data(mode.scratchpad_size() * 4));
+
+        // On the first written chunk, note the pads of the key and the pad pool, then stop.
+        let seen = Arc::new(std::sync::Mutex::new(None));
+        let put_callback: PutCallback = {
+            let index = index.clone();
+            let seen = seen.clone();
+            Arc::new(move |event| {
+                let index = index.clone();
+                let seen = seen.clone();
+                Box::pin(async move {
+                    if let PutEvent::PadsWritten { .. } = event {
+                        let index = index.read().await;
+                        let stats = index.get_storage_stats();
+                        seen.lock().unwrap().get_or_insert((
+                            index.get_pads(key_name).len() as u64,
+                            stats.free_pads + stats.pending_verification_pads,
+                        ));
+                        return Ok(false);
+                    }
+                    Ok(true)
+                })
+            })
+        };
+
+        let result = first_store(
+            index.clone(),
+            network.clone(),
+            key_name,
+            data,
+            mode,
+            false, // private
+            false, // verify
+            Some(put_callback),
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::OperationCancelled)));
+        assert!(!index.read().await.contains_key(key_name));
+
+        // Every pad of the key is back in the pool, free or awaiting verification.
+        let (key_pads, pool_while_storing) = seen.lock().unwrap().expect("No chunk was written");
+        assert_eq!(key_pads, 4);
+        let stats = index.read().await.get_storage_stats();
+        assert_eq!(
+            stats.free_pads + stats.pending_verification_pads,
+            pool_while_storing + key_pads
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_io_counts_one_create_per_pad() {
+        use crate::network::io::measure_io;
+
+        let (index, network) = setup_test_environment().await;
+        let mode = StorageMode::Medium;
+        let data = Arc::new(generate_random_data(mode.scratchpad_size() * 3));
+
+        // A private value is written to a new pad per chunk, nothing read without verify.
+        let (result, io) = measure_io(first_store(
+            index.clone(),
+            network.clone(),
+            "test_store_io_private",
+            data.clone(),
+            mode.clone(),
+            false, // private
+            true,  // no_verify
+            None,
+        ))
+        .await;
+        result.expect("Private store failed");
+        assert_eq!((io.pads_created, io.pads_updated, io.failed_writes), (3, 0, 0));
+        assert_eq!(io.bytes_written, data.len() as u64);
+        assert_eq!(io.pads_read, 0);
+
+        // A public value adds its index pad.
+        let (result, io) = measure_io(first_store(
+            index.clone(),
+            network.clone(),
+            "test_store_io_public",
+            data.clone(),
+            mode.clone(),
+            true, // public
+            true, // no_verify
+            None,
+        ))
+        .await;
+        result.expect("Public store failed");
+        assert_eq!((io.pads_created, io.pads_updated, io.failed_writes), (4, 0, 0));
+
+        // Verifying reads every pad back at least once.
+        let (result, io) = measure_io(first_store(
+            index.clone(),
+            network.clone(),
+            "test_store_io_verified",
+            data,
+            mode,
+            false, // private
+            false, // verify
+            None,
+        ))
+        .await;
+        result.expect("Verified store failed");
+        assert_eq!(io.pads_created, 3);
+        assert!(io.pads_read >= 3, "{} confirmation reads", io.pads_read);
+    }
+
+    #[tokio::test]
+    async fn test_resume_writes_only_the_missing_chunks() {
+        use crate::network::io::measure_io;
+
+        let (index, network) = setup_test_environment().await;
+        let key_name = "test_resume_missing_chunks";
+        let mode = StorageMode::Medium;
+        let chunk_size = mode.scratchpad_size();
+        let data = Arc::new(generate_random_data(chunk_size * 4));
+
+        first_store(
+            index.clone(),
+            network.clone(),
+            key_name,
+            data.clone(),
+            mode.clone(),
+            false, // private
+            false, // verify
+            None,
+        )
+        .await
+        .expect("Initial store failed");
+
+        // Interrupted before the last two chunks were written: their pads were only
+        // generated.
+        let mut pads = index.read().await.get_pads(key_name);
+        for (i, pad) in pads.iter_mut().enumerate().skip(2) {
+            *pad = PadInfo::new(&data[i * chunk_size..(i + 1) * chunk_size], i);
+        }
+        index
+            .write()
+            .await
+            .add_entry(key_name, IndexEntry::PrivateKey(pads))
+            .unwrap();
+        assert!(!index.read().await.is_finished(key_name));
+
+        let resuming = Arc::new(std::sync::Mutex::new(None));
+        let put_callback: PutCallback = {
+            let resuming = resuming.clone();
+            Arc::new(move |event| {
+                let resuming = resuming.clone();
+                Box::pin(async move {
+                    if let PutEvent::Resuming {
+                        confirmed_chunks,
+                        total_chunks,
+                    } = event
+                    {
+                        *resuming.lock().unwrap() = Some((confirmed_chunks, total_chunks));
+                    }
+                    Ok(true)
+                })
+            })
+        };
+
+        let (result, io) = measure_io(crate::ops::put::put(
+            index.clone(),
+            network.clone(),
+            key_name,
+            data.clone(),
+            mode,
+            false, // private
+            false, // verify
+            Some(put_callback),
+        ))
+        .await;
+        result.expect("Resumed store failed");
+
+        assert_eq!(*resuming.lock().unwrap(), Some((2, 4)));
+        assert_eq!((io.pads_created, io.pads_updated, io.failed_writes), (2, 0, 0));
+        assert_eq!(io.bytes_written, 2 * chunk_size as u64);
+        assert!(index.read().await.is_finished(key_name));
+        assert_eq!(fetch(&index, &network, key_name).await, *data);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_store_of_other_data_starts_afresh() {
+        use crate::network::io::measure_io;
+
+        let (index, network) = setup_test_environment().await;
+        let key_name = "test_interrupted_other_data";
+        let mode = StorageMode::Medium;
+        let chunk_size = mode.scratchpad_size();
+
+        // A store of 3 chunks interrupted before anything was written.
+        let interrupted = generate_random_data(chunk_size * 3);
+        index
+            .write()
+            .await
+            .create_key(key_name, &interrupted, mode.clone(), false)
+            .unwrap();
+
+        let data = Arc::new(generate_random_data(chunk_size * 2));
+        let (result, io) = measure_io(crate::ops::put::put(
+            index.clone(),
+            network.clone(),
+            key_name,
+            data.clone(),
+            mode,
+            false, // private
+            false, // verify
+            None,
+        ))
+        .await;
+        result.expect("Store over the interrupted one failed");
+
+        assert_eq!((io.pads_created, io.pads_updated), (2, 0));
+        assert_eq!(index.read().await.get_pads(key_name).len(), 2);
+        assert_eq!(fetch(&index, &network, key_name).await, *data);
+    }
+
+    #[test]
+    fn test_existing_pad_policy_from_env_value() {
+        use crate::ops::ExistingPadPolicy;
+
+        assert_eq!(
+            ExistingPadPolicy::from_env_value("update"),
+            Some(ExistingPadPolicy::Update)
+        );
+        assert_eq!(
+            ExistingPadPolicy::from_env_value("FAIL"),
+            Some(ExistingPadPolicy::Fail)
+        );
+        assert_eq!(ExistingPadPolicy::from_env_value("overwrite"), None);
+    }
+
+    // Stores `data` the way `put` does and reads every pad back from its chunk.
+    fn store_and_reassemble(
+        index: &mut MasterIndex,
+        key_name: &str,
+        data: &[u8],
+        pad: bool,
+    ) -> Vec<u8> {
+        let mode = StorageMode::Medium;
+        let (content, padding) = if pad {
+            let rng = InstanceRng::default();
+            crate::ops::put::pad_final_chunk(data, mode.scratchpad_size(), &rng)
+        } else {
+            (data.to_vec(), 0)
+        };
+        let (_, chunk_ranges) = index.create_key(key_name, &content, mode, false).unwrap();
+        index.set_final_chunk_padding(key_name, padding).unwrap();
+
+        let mut pads = index.get_pads(key_name);
+        pads.sort_by_key(|p| p.chunk_index);
+        pads.iter()
+            .zip(chunk_ranges)
+            .flat_map(|(pad, range)| pad.strip_padding(content[range].to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_final_chunk_padding_roundtrip() {
+        let mut index = MasterIndex::new(NetworkChoice::Devnet);
+        let chunk_size = StorageMode::Medium.scratchpad_size();
+        let data = generate_random_data(chunk_size + 1000);
+
+        let rng = InstanceRng::default();
+        let (padded, padding) = crate::ops::put::pad_final_chunk(&data, chunk_size, &rng);
+        assert_eq!(padded.len(), 2 * chunk_size);
+        assert_eq!(padding, chunk_size - 1000);
+        assert_eq!(padded[..data.len()], data[..]);
+
+        // The filler is drawn again each time, not derived from the value.
+        let (again, _) = crate::ops::put::pad_final_chunk(&data, chunk_size, &rng);
+        assert_ne!(again[data.len()..], padded[data.len()..]);
+
+        // Padded and unpadded keys live side by side in the same index.
+        let padded_key = "test_padded_roundtrip";
+        let plain_key = "test_plain_roundtrip";
+        index.remove_key(padded_key).unwrap();
+        index.remove_key(plain_key).unwrap();
+
+        assert_eq!(store_and_reassemble(&mut index, padded_key, &data, true), data);
+        assert_eq!(store_and_reassemble(&mut index, plain_key, &data, false), data);
+
+        let padded_pads = index.get_pads(padded_key);
+        assert!(padded_pads.iter().all(|p| p.size == chunk_size));
+        assert_eq!(padded_pads.iter().map(|p| p.padding).sum::<usize>(), padding);
+        assert!(index.get_pads(plain_key).iter().all(|p| p.padding == 0));
+
+        // A value that already fills its pads needs no filler.
+        let aligned = generate_random_data(chunk_size);
+        assert_eq!(
+            crate::ops::put::pad_final_chunk(&aligned, chunk_size, &rng).1,
+            0
+        );
+    }
+
+    #[test]
+    fn test_write_jitter_follows_the_instance_seed() {
+        let draw = |seed| {
+            let rng = InstanceRng::new(Some(seed));
+            (0..8)
+                .map(|_| crate::ops::put::write_jitter(&rng))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(draw(7), draw(7));
+        assert_ne!(draw(7), draw(8));
+        assert!(draw(7)
+            .iter()
+            .all(|jitter| jitter.as_millis() <= crate::ops::put::MAX_WRITE_JITTER_MS as u128));
+    }
+
+    fn chunk_ranges(len: usize, chunk_size: usize) -> Vec<std::ops::Range<usize>> {
+        crate::ops::chunking::ChunkRanges::new(len, chunk_size).collect()
+    }
+
+    fn stored_pads(content: &[u8], chunk_size: usize) -> Vec<PadInfo> {
+        chunk_ranges(content.len(), chunk_size)
+            .into_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let mut pad = PadInfo::new(&content[range], i);
+                pad.status = PadStatus::Confirmed;
+                pad
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_one_byte_change_rewrites_its_chunk_only() {
+        let original = generate_random_data(40);
+        let pads = stored_pads(&original, 10);
+
+        let mut updated = original.clone();
+        updated[25] ^= 0xFF;
+
+        let (new_pads, unchanged) =
+            reuse_unchanged_pads(&pads, &updated, &chunk_ranges(updated.len(), 10));
+        assert_eq!(unchanged, 3);
+        assert_eq!(new_pads.len(), 4);
+        for (i, (old, new)) in pads.iter().zip(&new_pads).enumerate() {
+            if i == 2 {
+                assert_eq!(new, &None);
+            } else {
+                assert_eq!(new.as_ref(), Some(old));
+            }
+        }
+    }
+
+    #[test]
+    fn test_append_rewrites_the_last_chunk_only() {
+        let original = generate_random_data(35);
+        let pads = stored_pads(&original, 10);
+
+        let mut updated = original.clone();
+        updated.extend(generate_random_data(10));
+
+        // The partial last chunk grows and the chunk past it is new, both get a new pad.
+        let ranges = chunk_ranges(updated.len(), 10);
+        assert_eq!(ranges.len(), 5);
+        let (new_pads, unchanged) = reuse_unchanged_pads(&pads, &updated, &ranges);
+        assert_eq!(unchanged, 3);
+        assert_eq!(new_pads.len(), 5);
+        for (new, old) in new_pads[..3].iter().zip(&pads) {
+            assert_eq!(new.as_ref(), Some(old));
+        }
+        assert_eq!(new_pads[3..], [None, None]);
+    }
 }
@@ -1,5 +1,6 @@
 use crate::error::Error;
 use crate::events::{SyncCallback, SyncEvent};
+use crate::index::error::IndexError;
 use crate::index::master_index::MasterIndex;
 use crate::index::{PadInfo, PadStatus};
 use crate::internal_events::invoke_sync_callback;
@@ -7,14 +8,50 @@ use crate::network::client::Config;
 use crate::network::{Network, NetworkError};
 use crate::ops::utils::derive_master_index_info;
 use ant_networking::GetRecordError;
+use autonomi::ScratchpadAddress;
+use blsttc::SecretKey;
+use log::warn;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 
-use mutant_protocol::SyncResult;
+use mutant_protocol::{SyncDirection, SyncResult};
 
 use super::DATA_ENCODING_MASTER_INDEX;
 
+/// Times the remote index is read back before giving up on seeing the push.
+const READ_BACK_RETRIES: usize = 20;
+
+/// What a read back of the remote index says about the index just pushed.
+#[derive(Debug, PartialEq, Eq)]
+enum ReadBack {
+    /// The push is not visible yet.
+    Pending,
+    Matches,
+    /// The pushed counter is there with other content.
+    Mismatch {
+        checksum: usize,
+    },
+}
+
+fn check_read_back(
+    pushed_counter: u64,
+    pushed_checksum: usize,
+    counter: u64,
+    data: &[u8],
+) -> ReadBack {
+    if counter != pushed_counter {
+        return ReadBack::Pending;
+    }
+    let checksum = PadInfo::checksum(data);
+    if checksum == pushed_checksum {
+        ReadBack::Matches
+    } else {
+        ReadBack::Mismatch { checksum }
+    }
+}
+
 pub(super) async fn sync(
     index: Arc<RwLock<MasterIndex>>,
     network: Arc<Network>,
@@ -26,6 +63,9 @@ pub(super) async fn sync(
         nb_keys_updated: 0,
         nb_free_pads_added: 0,
         nb_pending_pads_added: 0,
+        index_verified: false,
+        nb_keys_trashed: 0,
+        nb_keys_restored: 0,
     };
     let callback = sync_callback.clone();
 
@@ -34,29 +74,13 @@ pub(super) async fn sync(
         .unwrap();
 
     let owner_secret_key_data = network.secret_key();
-    let (owner_address, owner_secret_key) =
-        derive_master_index_info(&owner_secret_key_data.to_hex())?;
-
-    let client_get = network
-        .get_client(Config::Get)
-        .await
-        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+    let (owner_address, owner_secret_key) = derive_master_index_info(
+        &owner_secret_key_data.to_hex(),
+        network.config().app_id.as_deref(),
+    )?;
 
-    let (remote_index, remote_index_counter) = match network
-        .get(&client_get, &owner_address, Some(&owner_secret_key))
-        .await
-    {
-        Ok(get_result) => {
-            let remote_index = if force {
-                MasterIndex::new(network.network_choice())
-            } else {
-                serde_cbor::from_slice(&get_result.data).unwrap()
-            };
-
-            (remote_index, get_result.counter)
-        }
-        Err(_e) => (MasterIndex::new(network.network_choice()), 0),
-    };
+    let (remote_index, remote_index_counter) =
+        fetch_remote_index(&network, &owner_address, &owner_secret_key, force).await?;
 
     invoke_sync_callback(&callback, SyncEvent::Merging)
         .await
@@ -65,18 +89,15 @@ pub(super) async fn sync(
     let mut local_index = index.write().await;
 
     if !force {
-        for (key, remote_entry) in remote_index.list() {
-            let local_entry = local_index.get_entry(&key);
-            if local_entry.is_none() {
-                local_index.add_entry(&key, remote_entry.clone())?; // Clone remote_entry
-                sync_result.nb_keys_added += 1;
-            } else {
-                if local_index.update_entry(&key, remote_entry.clone())? {
-                    // Clone remote_entry
-                    sync_result.nb_keys_updated += 1;
-                }
-            }
-        }
+        // Removals and restorations first, so the keys removed on either side are not
+        // merged back below.
+        let (trashed, restored) = local_index.merge_trash(&remote_index)?;
+        sync_result.nb_keys_trashed = trashed;
+        sync_result.nb_keys_restored = restored;
+
+        let (added, updated) = local_index.merge_entries(&remote_index)?;
+        sync_result.nb_keys_added = added;
+        sync_result.nb_keys_updated = updated;
 
         let mut free_pads_to_add = Vec::new();
         let mut pending_pads_to_add = Vec::new();
@@ -96,28 +117,216 @@ pub(super) async fn sync(
 
         local_index.import_raw_pads_private_key(free_pads_to_add)?;
         local_index.import_raw_pads_private_key(pending_pads_to_add)?;
+
+        // Memberships are only ever added by a merge, removals happen locally.
+        let merged_collections = local_index.merge_collections(&remote_index);
+        let merged_aliases = local_index.merge_aliases(&remote_index);
+        if merged_collections + merged_aliases > 0 {
+            local_index.flush()?;
+        }
     }
 
+    // Saved locally before the push, a failed push leaves nothing but the remote behind.
+    local_index.flush()?;
     let serialized_index = serde_cbor::to_vec(&*local_index).unwrap(); // Deref local_index
     drop(local_index); // Drop the write lock before potential network calls
 
+    sync_result.index_verified = push_index(
+        &network,
+        owner_address,
+        &owner_secret_key,
+        serialized_index,
+        remote_index_counter,
+        &callback,
+    )
+    .await?;
+
+    invoke_sync_callback(&callback, SyncEvent::Complete)
+        .await
+        .unwrap();
+
+    Ok(sync_result)
+}
+
+/// Merges only the keys matching `patterns` between the local and the remote index, in
+/// the given `direction`. The pads of the merged keys go along with them, while the
+/// other keys and the free pads of both sides are left alone: a free pad could still be
+/// referenced by a key outside the selection.
+///
+/// The counts of the result are the keys added or updated on the side they were merged
+/// into. With `SyncDirection::Pull` nothing is pushed and `index_verified` stays false.
+pub(super) async fn sync_keys(
+    index: Arc<RwLock<MasterIndex>>,
+    network: Arc<Network>,
+    patterns: &[String],
+    direction: SyncDirection,
+    sync_callback: Option<SyncCallback>,
+) -> Result<SyncResult, Error> {
+    let mut sync_result = SyncResult {
+        nb_keys_added: 0,
+        nb_keys_updated: 0,
+        nb_free_pads_added: 0,
+        nb_pending_pads_added: 0,
+        index_verified: false,
+        nb_keys_trashed: 0,
+        nb_keys_restored: 0,
+    };
+    let callback = sync_callback.clone();
+
+    invoke_sync_callback(&callback, SyncEvent::FetchingRemoteIndex)
+        .await
+        .unwrap();
+
+    let owner_secret_key_data = network.secret_key();
+    let (owner_address, owner_secret_key) = derive_master_index_info(
+        &owner_secret_key_data.to_hex(),
+        network.config().app_id.as_deref(),
+    )?;
+
+    let (mut remote_index, remote_index_counter) =
+        fetch_remote_index(&network, &owner_address, &owner_secret_key, false).await?;
+
+    invoke_sync_callback(&callback, SyncEvent::Merging)
+        .await
+        .unwrap();
+
+    let mut local_index = index.write().await;
+
+    if direction != SyncDirection::Push {
+        // Removed locally since the last push, its pads are held by the trash.
+        let pulled: BTreeSet<String> = remote_index
+            .select_keys(patterns)
+            .into_iter()
+            .filter(|key| !(local_index.is_trashed(key) && !local_index.contains_key(key)))
+            .collect();
+        let (added, updated) = local_index.merge_selected(&remote_index, &pulled);
+        if added + updated > 0 {
+            local_index.flush()?;
+        }
+        sync_result.nb_keys_added += added;
+        sync_result.nb_keys_updated += updated;
+    }
+
+    if direction == SyncDirection::Pull {
+        drop(local_index);
+        invoke_sync_callback(&callback, SyncEvent::Complete)
+            .await
+            .unwrap();
+        return Ok(sync_result);
+    }
+
+    let pushed = local_index.select_keys(patterns);
+    let (added, updated) = remote_index.merge_selected(&local_index, &pushed);
+    drop(local_index);
+    sync_result.nb_keys_added += added;
+    sync_result.nb_keys_updated += updated;
+
+    let serialized_index = serde_cbor::to_vec(&remote_index).unwrap();
+
+    sync_result.index_verified = push_index(
+        &network,
+        owner_address,
+        &owner_secret_key,
+        serialized_index,
+        remote_index_counter,
+        &callback,
+    )
+    .await?;
+
+    invoke_sync_callback(&callback, SyncEvent::Complete)
+        .await
+        .unwrap();
+
+    Ok(sync_result)
+}
+
+/// The remote index with its counter. An empty index is returned when there is none
+/// yet, or with `fresh` to overwrite it. A remote index that cannot be fetched or read
+/// fails instead, so that it is not pushed over.
+async fn fetch_remote_index(
+    network: &Network,
+    owner_address: &ScratchpadAddress,
+    owner_secret_key: &SecretKey,
+    fresh: bool,
+) -> Result<(MasterIndex, u64), Error> {
+    let client_get = network
+        .get_client(Config::Get)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    Ok(
+        match network
+            .get(&client_get, owner_address, Some(owner_secret_key))
+            .await
+        {
+            Ok(get_result) => {
+                let remote_index = if fresh {
+                    MasterIndex::open(network.network_choice(), network.config().clone()).0
+                } else {
+                    MasterIndex::decode_remote(&get_result.data)?
+                };
+
+                (remote_index, get_result.counter)
+            }
+            Err(NetworkError::GetError(GetRecordError::RecordNotFound)) => (
+                MasterIndex::open(network.network_choice(), network.config().clone()).0,
+                0,
+            ),
+            Err(e) => return Err(Error::Network(e)),
+        },
+    )
+}
+
+/// The remote index as it is on the network, `None` when nothing was pushed yet. Unlike
+/// a sync, errors are not turned into an empty index.
+pub(super) async fn read_remote_index(network: &Network) -> Result<Option<MasterIndex>, Error> {
+    let (owner_address, owner_secret_key) = derive_master_index_info(
+        &network.secret_key().to_hex(),
+        network.config().app_id.as_deref(),
+    )?;
+    let client_get = network
+        .get_client(Config::Get)
+        .await
+        .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
+
+    match network
+        .get(&client_get, &owner_address, Some(&owner_secret_key))
+        .await
+    {
+        Ok(get_result) => Ok(Some(MasterIndex::decode_remote(&get_result.data)?)),
+        Err(NetworkError::GetError(GetRecordError::RecordNotFound)) => Ok(None),
+        Err(e) => Err(Error::Network(e)),
+    }
+}
+
+/// Pushes `serialized_index` as the next version of the remote index and, with
+/// `verify_index_push`, reads it back. Returns whether the push was verified.
+async fn push_index(
+    network: &Network,
+    owner_address: ScratchpadAddress,
+    owner_secret_key: &SecretKey,
+    serialized_index: Vec<u8>,
+    remote_index_counter: u64,
+    callback: &Option<SyncCallback>,
+) -> Result<bool, Error> {
     let client_put = network
         .get_client(Config::Put)
         .await
         .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
 
-    invoke_sync_callback(&callback, SyncEvent::PushingRemoteIndex)
+    invoke_sync_callback(callback, SyncEvent::PushingRemoteIndex)
         .await
         .unwrap();
 
-    let pad_info = PadInfo {
+    let mut pad_info = PadInfo {
         address: owner_address,
         status: PadStatus::Confirmed,
         chunk_index: 0,
         size: serialized_index.len(),
         last_known_counter: remote_index_counter + 1,
         sk_bytes: owner_secret_key.to_bytes().to_vec(),
-        checksum: 0,
+        checksum: PadInfo::checksum(&serialized_index),
+        padding: 0,
     };
 
     network
@@ -130,48 +339,104 @@ pub(super) async fn sync(
         )
         .await?;
 
+    if !network.config().verify_index_push {
+        return Ok(false);
+    }
+
     let client_verify = network
         .get_client(Config::Get)
         .await
         .map_err(|e| Error::Network(NetworkError::ClientAccessError(e.to_string())))?;
 
-    invoke_sync_callback(&callback, SyncEvent::VerifyingRemoteIndex)
+    invoke_sync_callback(callback, SyncEvent::VerifyingRemoteIndex)
         .await
         .unwrap();
 
-    let mut retries = 20;
+    // A mismatch is pushed again once, with the next counter.
+    let mut pushes_left = 1;
+    let mut retries = READ_BACK_RETRIES;
 
     loop {
-        match network
-            .get(&client_verify, &owner_address, Some(&owner_secret_key))
+        let read_back = match network
+            .get(&client_verify, &owner_address, Some(owner_secret_key))
             .await
         {
-            Ok(get_result) => {
-                if get_result.data != serialized_index {
-                } else if get_result.counter != remote_index_counter + 1 {
-                } else {
-                    break Ok(());
+            Ok(get_result) => check_read_back(
+                pad_info.last_known_counter,
+                pad_info.checksum,
+                get_result.counter,
+                &get_result.data,
+            ),
+            Err(_e) => ReadBack::Pending,
+        };
+
+        match read_back {
+            ReadBack::Matches => return Ok(true),
+            ReadBack::Mismatch { checksum } if pushes_left > 0 => {
+                warn!(
+                    "Remote index read back with checksum {:x} instead of {:x}, pushing it again",
+                    checksum, pad_info.checksum
+                );
+                pushes_left -= 1;
+                retries = READ_BACK_RETRIES;
+                pad_info.last_known_counter += 1;
+                network
+                    .put(
+                        &client_put,
+                        &pad_info,
+                        &serialized_index,
+                        DATA_ENCODING_MASTER_INDEX,
+                        false,
+                    )
+                    .await?;
+                continue;
+            }
+            ReadBack::Mismatch { checksum } => {
+                return Err(IndexError::PushVerificationFailed {
+                    expected: pad_info.checksum,
+                    found: checksum,
                 }
+                .into());
             }
-            Err(_e) => {}
-        };
+            ReadBack::Pending => {}
+        }
 
         if retries == 0 {
-            break Err(Error::Network(
+            return Err(Error::Network(
                 NetworkError::GetError(GetRecordError::RecordNotFound).into(),
             ));
         }
 
         retries -= 1;
         tokio::time::sleep(Duration::from_secs(1)).await;
-    }?;
+    }
+}
 
-    // Reacquire lock to update the index in memory (optional, depending on desired consistency)
-    // *index.write().await = local_index_data; // If we had cloned the data before dropping lock
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    invoke_sync_callback(&callback, SyncEvent::Complete)
-        .await
-        .unwrap();
+    #[test]
+    fn test_read_back_of_pushed_index() {
+        let index = b"serialized index".to_vec();
+        let checksum = PadInfo::checksum(&index);
 
-    Ok(sync_result)
+        assert_eq!(check_read_back(4, checksum, 4, &index), ReadBack::Matches);
+        // Not propagated yet, the previous version is still served.
+        assert_eq!(check_read_back(4, checksum, 3, b"older"), ReadBack::Pending);
+    }
+
+    #[test]
+    fn test_truncated_push_is_a_mismatch() {
+        let index = b"serialized index".to_vec();
+        let checksum = PadInfo::checksum(&index);
+        let truncated = &index[..index.len() / 2];
+
+        assert_eq!(
+            check_read_back(4, checksum, 4, truncated),
+            ReadBack::Mismatch {
+                checksum: PadInfo::checksum(truncated)
+            }
+        );
+    }
 }
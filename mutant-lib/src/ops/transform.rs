@@ -0,0 +1,382 @@
+//! Transforms the bytes of a value go through, in order, before being chunked into pads
+//! and undone in reverse order once its chunks are put back together.
+//!
+//! The transforms a key was stored through are recorded in the index by id, along with
+//! their settings, see [`TransformSpec`]. Fetching the key looks each id up among the
+//! registered transforms, so the keys stored through a custom transform can only be
+//! read by instances that registered it too.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::index::master_index::{KeyTransforms, TransformSpec};
+
+/// Errors undoing the transforms of a value.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum DataError {
+    #[error("Unknown transform '{0}', it must be registered to fetch the keys stored through it")]
+    UnknownTransform(String),
+
+    #[error("Transform '{id}' failed to decode the value: {reason}")]
+    DecodeFailed { id: String, reason: String },
+}
+
+/// A reversible change of the bytes of a value, such as compression or encryption.
+///
+/// `decode` must give back what was handed to `encode`, by any instance registered
+/// under the same id: whatever it needs beyond its settings goes in the encoded bytes.
+pub trait ChunkTransform: Send + Sync {
+    /// Name the transform is recorded and looked up under, never to change once keys
+    /// were stored through it.
+    fn id(&self) -> &str;
+
+    /// Settings recorded along with the id, for information.
+    fn params(&self) -> BTreeMap<String, String> {
+        BTreeMap::new()
+    }
+
+    fn encode(&self, data: &[u8]) -> Vec<u8>;
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, DataError>;
+}
+
+/// The transforms new values go through, and every transform values can be fetched
+/// back from. [`ZstdTransform`] and [`PadToSizeTransform`] are always registered, the
+/// pipeline is empty by default.
+#[derive(Clone)]
+pub struct Transforms {
+    pipeline: Vec<Arc<dyn ChunkTransform>>,
+    registry: HashMap<String, Arc<dyn ChunkTransform>>,
+}
+
+impl Default for Transforms {
+    fn default() -> Self {
+        let mut transforms = Self {
+            pipeline: Vec::new(),
+            registry: HashMap::new(),
+        };
+        transforms.register(Arc::new(ZstdTransform::default()));
+        transforms.register(Arc::new(PadToSizeTransform::default()));
+        transforms
+    }
+}
+
+impl Transforms {
+    /// Makes the values stored through `transform` fetchable, replacing the transform
+    /// registered under the same id.
+    pub fn register(&mut self, transform: Arc<dyn ChunkTransform>) {
+        self.registry.insert(transform.id().to_string(), transform);
+    }
+
+    /// Runs new values through `pipeline`, in order, registering its transforms.
+    pub fn set_pipeline(&mut self, pipeline: Vec<Arc<dyn ChunkTransform>>) {
+        for transform in &pipeline {
+            self.register(transform.clone());
+        }
+        self.pipeline = pipeline;
+    }
+
+    /// Whether new values are stored as they are.
+    pub fn is_empty(&self) -> bool {
+        self.pipeline.is_empty()
+    }
+
+    /// Runs `data` through the pipeline, `None` when it is empty.
+    pub fn encode(&self, data: &[u8]) -> Option<(Vec<u8>, KeyTransforms)> {
+        if self.pipeline.is_empty() {
+            return None;
+        }
+
+        let mut encoded = data.to_vec();
+        let mut steps = Vec::with_capacity(self.pipeline.len());
+        for transform in &self.pipeline {
+            encoded = transform.encode(&encoded);
+            steps.push(TransformSpec {
+                id: transform.id().to_string(),
+                params: transform.params(),
+            });
+        }
+
+        Some((
+            encoded,
+            KeyTransforms {
+                steps,
+                data_size: data.len(),
+            },
+        ))
+    }
+
+    /// Undoes `transforms` on `data`, last one first. Every transform is looked up before
+    /// any is run, so an unknown one fails without decoding anything.
+    pub fn decode(&self, data: Vec<u8>, transforms: &KeyTransforms) -> Result<Vec<u8>, DataError> {
+        let registered = transforms
+            .steps
+            .iter()
+            .map(|step| {
+                self.registry
+                    .get(&step.id)
+                    .ok_or_else(|| DataError::UnknownTransform(step.id.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        registered
+            .into_iter()
+            .rev()
+            .try_fold(data, |data, transform| transform.decode(&data))
+    }
+}
+
+/// Compresses values with zstd.
+pub struct ZstdTransform {
+    level: i32,
+}
+
+impl ZstdTransform {
+    pub const ID: &'static str = "zstd";
+
+    /// Compresses at `level`, brought within the range zstd supports.
+    pub fn new(level: i32) -> Self {
+        let range = zstd::compression_level_range();
+        Self {
+            level: level.clamp(*range.start(), *range.end()),
+        }
+    }
+}
+
+impl Default for ZstdTransform {
+    fn default() -> Self {
+        Self::new(zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+}
+
+impl ChunkTransform for ZstdTransform {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn params(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([("level".to_string(), self.level.to_string())])
+    }
+
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        // Compressing in memory only fails on a level out of range, clamped by `new`.
+        zstd::encode_all(data, self.level).expect("zstd compression into memory failed")
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, DataError> {
+        zstd::decode_all(data).map_err(|e| DataError::DecodeFailed {
+            id: Self::ID.to_string(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// Encrypts values with XChaCha20-Poly1305, on top of the encryption of the pads, so
+/// that not even the owner key can read them without `key`. Each value gets a random
+/// nonce, stored in front of it.
+pub struct XChaCha20Poly1305Transform {
+    cipher: XChaCha20Poly1305,
+    key_id: String,
+}
+
+impl XChaCha20Poly1305Transform {
+    pub const ID: &'static str = "xchacha20poly1305";
+    const NONCE_LEN: usize = 24;
+
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            cipher: XChaCha20Poly1305::new(Key::from_slice(&key)),
+            key_id: hex::encode(&Sha256::digest(key)[..8]),
+        }
+    }
+
+    fn decode_failed(reason: &str) -> DataError {
+        DataError::DecodeFailed {
+            id: Self::ID.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl ChunkTransform for XChaCha20Poly1305Transform {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    /// Tells which key a value was encrypted with, without giving it away.
+    fn params(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([("key_id".to_string(), self.key_id.clone())])
+    }
+
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let mut nonce = [0u8; Self::NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        // Encryption only fails on values larger than the cipher can count blocks of.
+        let ciphertext = self
+            .cipher
+            .encrypt(XNonce::from_slice(&nonce), data)
+            .expect("XChaCha20-Poly1305 encryption failed");
+
+        let mut encoded = nonce.to_vec();
+        encoded.extend_from_slice(&ciphertext);
+        encoded
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, DataError> {
+        if data.len() < Self::NONCE_LEN {
+            return Err(Self::decode_failed("shorter than its nonce"));
+        }
+        let (nonce, ciphertext) = data.split_at(Self::NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Self::decode_failed("wrong key or altered value"))
+    }
+}
+
+/// Pads values up to a multiple of `block_size` bytes, so that their size tells little
+/// about their content. The value is followed by a `0x80` byte and as many zeros as
+/// needed, so any block size decodes it.
+pub struct PadToSizeTransform {
+    block_size: usize,
+}
+
+impl PadToSizeTransform {
+    pub const ID: &'static str = "pad";
+    pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+    pub fn new(block_size: usize) -> Self {
+        Self {
+            block_size: block_size.max(1),
+        }
+    }
+}
+
+impl Default for PadToSizeTransform {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_BLOCK_SIZE)
+    }
+}
+
+impl ChunkTransform for PadToSizeTransform {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn params(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([("block_size".to_string(), self.block_size.to_string())])
+    }
+
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let padded_len = (data.len() + 1).next_multiple_of(self.block_size);
+        let mut encoded = Vec::with_capacity(padded_len);
+        encoded.extend_from_slice(data);
+        encoded.push(0x80);
+        encoded.resize(padded_len, 0);
+        encoded
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>, DataError> {
+        match data.iter().rposition(|&byte| byte != 0) {
+            Some(end) if data[end] == 0x80 => Ok(data[..end].to_vec()),
+            _ => Err(DataError::DecodeFailed {
+                id: Self::ID.to_string(),
+                reason: "missing the end of value marker".to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(id: &str) -> TransformSpec {
+        TransformSpec {
+            id: id.to_string(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_roundtrip_in_order() {
+        let key = [7u8; 32];
+        let mut transforms = Transforms::default();
+        transforms.set_pipeline(vec![
+            Arc::new(ZstdTransform::new(3)),
+            Arc::new(XChaCha20Poly1305Transform::new(key)),
+            Arc::new(PadToSizeTransform::new(64)),
+        ]);
+        let data = b"a value compressing well, well, well, well, well, well".repeat(20);
+
+        let (encoded, recorded) = transforms.encode(&data).unwrap();
+        assert_eq!(encoded.len() % 64, 0);
+        assert_ne!(encoded, data);
+        assert_eq!(recorded.data_size, data.len());
+        assert_eq!(
+            recorded
+                .steps
+                .iter()
+                .map(|s| s.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["zstd", "xchacha20poly1305", "pad"]
+        );
+        assert_eq!(recorded.steps[0].params["level"], "3");
+        assert_eq!(transforms.decode(encoded, &recorded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_empty_pipeline_leaves_values_alone() {
+        let transforms = Transforms::default();
+        assert!(transforms.encode(b"value").is_none());
+    }
+
+    #[test]
+    fn test_unknown_transform_fails_to_decode() {
+        let mut transforms = Transforms::default();
+        let recorded = KeyTransforms {
+            steps: vec![spec("zstd"), spec(XChaCha20Poly1305Transform::ID)],
+            data_size: 5,
+        };
+        assert_eq!(
+            transforms.decode(b"value".to_vec(), &recorded),
+            Err(DataError::UnknownTransform(
+                XChaCha20Poly1305Transform::ID.to_string()
+            ))
+        );
+
+        // Registered with another key, the value does not authenticate.
+        let encoded = XChaCha20Poly1305Transform::new([1u8; 32]).encode(b"value");
+        transforms.register(Arc::new(XChaCha20Poly1305Transform::new([2u8; 32])));
+        let recorded = KeyTransforms {
+            steps: vec![spec(XChaCha20Poly1305Transform::ID)],
+            data_size: 5,
+        };
+        assert!(matches!(
+            transforms.decode(encoded, &recorded),
+            Err(DataError::DecodeFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pad_to_size_keeps_trailing_zeros() {
+        let pad = PadToSizeTransform::new(16);
+        for data in [
+            &b""[..],
+            &b"\x80"[..],
+            &b"ends with zeros\0\0"[..],
+            &[0u8; 16][..],
+        ] {
+            let encoded = pad.encode(data);
+            assert_eq!(encoded.len() % 16, 0);
+            assert!(encoded.len() > data.len());
+            assert_eq!(pad.decode(&encoded).unwrap(), data);
+        }
+        assert!(pad.decode(&[0u8; 16]).is_err());
+    }
+}
@@ -1,13 +1,32 @@
 use crate::error::Error;
 use autonomi::ScratchpadAddress;
 use blsttc::SecretKey;
+use hkdf::Hkdf;
 use log::{debug, info};
 use sha2::{Digest, Sha256};
 
-pub fn derive_master_index_info(
-    private_key_hex: &str,
-) -> Result<(ScratchpadAddress, SecretKey), Error> {
-    debug!("Deriving master index key and address...");
+/// Name of the scheme used to go from the user private key to the master index pad.
+pub const MASTER_INDEX_DERIVATION_ALGORITHM: &str = "sha256(sha256(private_key))";
+
+/// Name of the scheme used instead when an application id is set.
+pub const APP_MASTER_INDEX_DERIVATION_ALGORITHM: &str =
+    "hkdf-sha256(sha256(sha256(private_key)), \"app/{app_id}\")";
+
+/// Describes how the master index location was derived from the user private key.
+///
+/// Useful to check that two machines configured with the same key point at the same index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyDerivationInfo {
+    /// SHA-256 of the raw private key bytes, used as the owner secret key.
+    pub input_key_hash: [u8; 32],
+    /// Address of the scratchpad holding the remote master index.
+    pub derived_master_address: ScratchpadAddress,
+    /// Human readable description of the derivation scheme.
+    pub derivation_algorithm: String,
+}
+
+/// Decodes a hex private key (with or without `0x` prefix) and returns its SHA-256.
+pub fn hash_private_key_hex(private_key_hex: &str) -> Result<[u8; 32], Error> {
     let hex_to_decode = private_key_hex
         .strip_prefix("0x")
         .unwrap_or(private_key_hex);
@@ -18,7 +37,29 @@ pub fn derive_master_index_info(
     let mut hasher = Sha256::new();
     hasher.update(&input_key_bytes);
     let hash_result = hasher.finalize();
-    let key_array: [u8; 32] = hash_result.into();
+    Ok(hash_result.into())
+}
+
+/// Derives the key and address of the master index of `app_id`, see the `app_id` of
+/// `MutAntConfig`.
+///
+/// With an id, the key is `HKDF-SHA256(ikm = hash, info = "app/{app_id}")` with the two most
+/// significant bits cleared, `hash` being the key used when there is none.
+pub fn derive_master_index_info(
+    private_key_hex: &str,
+    app_id: Option<&str>,
+) -> Result<(ScratchpadAddress, SecretKey), Error> {
+    debug!("Deriving master index key and address...");
+    let mut key_array = hash_private_key_hex(private_key_hex)?;
+
+    if let Some(app_id) = app_id {
+        let hkdf = Hkdf::<Sha256>::new(None, &key_array);
+        let mut okm = [0u8; 32];
+        hkdf.expand(format!("app/{}", app_id).as_bytes(), &mut okm)
+            .map_err(|e| Error::Internal(format!("Failed to derive app index key: {}", e)))?;
+        okm[0] &= 0x3f;
+        key_array = okm;
+    }
 
     let derived_key = SecretKey::from_bytes(key_array)
         .map_err(|e| Error::Internal(format!("Failed to create SecretKey from HASH: {:?}", e)))?;
@@ -27,3 +68,110 @@ pub fn derive_master_index_info(
     info!("Derived Master Index Address: {}", address);
     Ok((address, derived_key))
 }
+
+/// Builds the derivation report from the owner secret key (the hash of the user private key).
+pub fn key_derivation_info(
+    owner_secret_key: &SecretKey,
+    app_id: Option<&str>,
+) -> Result<KeyDerivationInfo, Error> {
+    let (derived_master_address, _) = derive_master_index_info(&owner_secret_key.to_hex(), app_id)?;
+
+    Ok(KeyDerivationInfo {
+        input_key_hash: owner_secret_key.to_bytes(),
+        derived_master_address,
+        derivation_algorithm: match app_id {
+            Some(app_id) => APP_MASTER_INDEX_DERIVATION_ALGORITHM.replace("{app_id}", app_id),
+            None => MASTER_INDEX_DERIVATION_ALGORITHM.to_string(),
+        },
+    })
+}
+
+/// Short tag of an application id, safe to use in file names.
+pub fn app_id_tag(app_id: &str) -> String {
+    hex::encode(&Sha256::digest(app_id.as_bytes())[..8])
+}
+
+/// Derives the secret key of the pad holding `chunk_index` of `key_name`, with no network access:
+///
+/// `pad_key = HKDF-SHA256(ikm = master_secret, info = "{key_name}/{chunk_index}")`, taking the
+/// first 32 bytes with the two most significant bits cleared so that they always fall in the
+/// BLS scalar field. The pad address is `ScratchpadAddress::new(pad_key.public_key())`.
+pub fn derive_pad_secret_key(
+    master_secret: &SecretKey,
+    key_name: &str,
+    chunk_index: usize,
+) -> Result<SecretKey, Error> {
+    let hkdf = Hkdf::<Sha256>::new(None, &master_secret.to_bytes());
+    let mut okm = [0u8; 32];
+    hkdf.expand(format!("{}/{}", key_name, chunk_index).as_bytes(), &mut okm)
+        .map_err(|e| Error::Internal(format!("Failed to derive pad key: {}", e)))?;
+    okm[0] &= 0x3f;
+
+    SecretKey::from_bytes(okm)
+        .map_err(|e| Error::Internal(format!("Failed to create pad SecretKey: {:?}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::DEV_TESTNET_PRIVATE_KEY_HEX;
+
+    #[test]
+    fn test_key_derivation_info_matches_master_index_derivation() {
+        let hash = hash_private_key_hex(DEV_TESTNET_PRIVATE_KEY_HEX).unwrap();
+        let owner_sk = SecretKey::from_bytes(hash).unwrap();
+
+        let info = key_derivation_info(&owner_sk, None).unwrap();
+        let (expected_address, _) = derive_master_index_info(&owner_sk.to_hex(), None).unwrap();
+
+        assert_eq!(info.input_key_hash, hash);
+        assert_eq!(info.derived_master_address, expected_address);
+        assert_eq!(info.derivation_algorithm, MASTER_INDEX_DERIVATION_ALGORITHM);
+    }
+
+    #[test]
+    fn test_hash_private_key_hex_prefix_insensitive() {
+        let with_prefix = hash_private_key_hex(DEV_TESTNET_PRIVATE_KEY_HEX).unwrap();
+        let without_prefix =
+            hash_private_key_hex(DEV_TESTNET_PRIVATE_KEY_HEX.strip_prefix("0x").unwrap()).unwrap();
+
+        assert_eq!(with_prefix, without_prefix);
+        assert!(hash_private_key_hex("not hex").is_err());
+    }
+
+    #[test]
+    fn test_app_id_gets_its_own_master_index() {
+        let (default_address, _) =
+            derive_master_index_info(DEV_TESTNET_PRIVATE_KEY_HEX, None).unwrap();
+        let (app_address, _) =
+            derive_master_index_info(DEV_TESTNET_PRIVATE_KEY_HEX, Some("app")).unwrap();
+        let (other_address, _) =
+            derive_master_index_info(DEV_TESTNET_PRIVATE_KEY_HEX, Some("other")).unwrap();
+
+        // No app id keeps the address of the existing indexes.
+        let hash = hash_private_key_hex(DEV_TESTNET_PRIVATE_KEY_HEX).unwrap();
+        let owner_pk = SecretKey::from_bytes(hash).unwrap().public_key();
+        assert_eq!(default_address, ScratchpadAddress::new(owner_pk));
+
+        assert_ne!(app_address, default_address);
+        assert_ne!(app_address, other_address);
+        assert_eq!(
+            app_address,
+            derive_master_index_info(DEV_TESTNET_PRIVATE_KEY_HEX, Some("app"))
+                .unwrap()
+                .0
+        );
+        assert_ne!(app_id_tag("app"), app_id_tag("other"));
+    }
+
+    #[test]
+    fn test_derive_pad_secret_key_is_deterministic() {
+        let hash = hash_private_key_hex(DEV_TESTNET_PRIVATE_KEY_HEX).unwrap();
+        let master = SecretKey::from_bytes(hash).unwrap();
+
+        let first = derive_pad_secret_key(&master, "key", 0).unwrap();
+        assert_eq!(first, derive_pad_secret_key(&master, "key", 0).unwrap());
+        assert_ne!(first, derive_pad_secret_key(&master, "key", 1).unwrap());
+        assert_ne!(first, derive_pad_secret_key(&master, "other", 0).unwrap());
+    }
+}
@@ -92,6 +92,8 @@ where
         retry_sender,
         retry_rx: retry_receiver,
         total_items_hint: config.total_items_hint,
+        cancel_token: config.cancel_token,
+        pad_ops: config.network.pad_ops(),
         _marker_context: PhantomData,
         _marker_result: PhantomData,
         _marker_error: PhantomData,
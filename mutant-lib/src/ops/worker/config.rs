@@ -1,6 +1,7 @@
 use crate::network::Network;
 use crate::network::client::Config as ClientConfig;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 pub struct WorkerPoolConfig<Task> {
     pub network: Arc<Network>,
@@ -8,4 +9,6 @@ pub struct WorkerPoolConfig<Task> {
     pub task_processor: Task,
     pub enable_recycling: bool,
     pub total_items_hint: usize,
+    /// Stops waiting for the items left once cancelled, the workers then drop them.
+    pub cancel_token: CancellationToken,
 }
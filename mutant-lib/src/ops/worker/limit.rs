@@ -0,0 +1,37 @@
+use std::future::Future;
+use tokio::sync::Semaphore;
+
+/// Runs `op` once a permit of `limit`, the pad operations of an instance, is available.
+pub(crate) async fn with_pad_op_permit<F: Future>(limit: &Semaphore, op: F) -> F::Output {
+    // The semaphore is never closed, `None` would only lift the limit.
+    let _permit = limit.acquire().await.ok();
+    op.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_in_flight_ops_never_exceed_the_limit() {
+        let limit = Semaphore::new(4);
+        let in_flight = AtomicUsize::new(0);
+        let most_in_flight = AtomicUsize::new(0);
+
+        let ops = (0..50).map(|i| {
+            with_pad_op_permit(&limit, async {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                most_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(1 + i % 3)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            })
+        });
+        join_all(ops).await;
+
+        assert_eq!(most_in_flight.load(Ordering::SeqCst), 4);
+        assert_eq!(limit.available_permits(), 4);
+    }
+}
@@ -2,6 +2,7 @@ mod async_task;
 mod builder;
 mod config;
 mod error;
+mod limit;
 mod pool;
 mod worker;
 
@@ -9,3 +10,4 @@ pub use async_task::AsyncTask;
 pub use builder::build;
 pub use config::WorkerPoolConfig;
 pub use error::PoolError;
+pub(crate) use limit::with_pad_op_permit;
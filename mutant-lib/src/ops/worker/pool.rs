@@ -6,9 +6,11 @@ use log::{debug, error, info, warn};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 use crate::error::Error as MutantError;
+use crate::network::io::inherit_io;
 use super::async_task::AsyncTask;
 use super::error::PoolError;
 use super::worker::Worker;
@@ -32,6 +34,9 @@ where
     pub(crate) retry_sender: Option<Sender<(E, Item)>>,
     pub(crate) retry_rx: Option<Receiver<(E, Item)>>,
     pub(crate) total_items_hint: usize,
+    pub(crate) cancel_token: CancellationToken,
+    /// Pad operations in flight across the instance, see `Network::pad_ops`.
+    pub(crate) pad_ops: Arc<Semaphore>,
     pub(crate) _marker_context: PhantomData<Context>,
     pub(crate) _marker_result: PhantomData<T>,
     pub(crate) _marker_error: PhantomData<E>,
@@ -204,9 +209,10 @@ where
                 active_workers_counter: active_workers_counter.clone(),
                 all_items_processed: all_items_processed.clone(),
                 total_items_hint,
+                pad_ops: self.pad_ops.clone(),
                 _marker_context: PhantomData,
             };
-            worker_handles.push(tokio::spawn(worker.run()));
+            worker_handles.push(tokio::spawn(inherit_io(worker.run())));
         }
 
         // Drop the original global_rx
@@ -305,6 +311,10 @@ where
                     debug!("Received all_items_processed notification. Closing channels...");
                     break;
                 }
+                _ = self.cancel_token.cancelled() => {
+                    debug!("Pool cancelled. Closing channels without waiting for the remaining items...");
+                    break;
+                }
                 _ = tokio::time::sleep(tokio::time::Duration::from_secs(30)) => {
                     // Check if we're still making progress
                     let current_processed = *processed_items_counter.lock().await;
@@ -4,11 +4,13 @@ use futures::StreamExt;
 use log::{debug, trace};
 use std::marker::PhantomData;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
 
+use crate::network::io::inherit_io;
 use crate::network::BATCH_SIZE;
 use super::async_task::AsyncTask;
 use super::error::PoolError;
+use super::limit::with_pad_op_permit;
 
 pub(crate) struct Worker<Item, Context, Client, Task, T, E>
 where
@@ -31,6 +33,7 @@ where
     pub active_workers_counter: Arc<Mutex<usize>>,
     pub all_items_processed: Arc<tokio::sync::Notify>,
     pub total_items_hint: usize,
+    pub pad_ops: Arc<Semaphore>,
     pub _marker_context: PhantomData<Context>,
 }
 
@@ -67,9 +70,10 @@ where
                 active_workers_counter: self.active_workers_counter.clone(),
                 all_items_processed: self.all_items_processed.clone(),
                 total_items_hint: self.total_items_hint,
+                pad_ops: self.pad_ops.clone(),
                 _marker_context: PhantomData,
             };
-            task_handles.push(tokio::spawn(worker_clone.run_task_processor(task_id)));
+            task_handles.push(tokio::spawn(inherit_io(worker_clone.run_task_processor(task_id))));
         }
 
         while !task_handles.is_empty() {
@@ -163,10 +167,11 @@ where
 
             if let Some(item) = item {
                 trace!("Worker {}.{} processing item", self.id, task_id);
-                match self
-                    .task_processor
-                    .process(self.id, &self.client, item)
-                    .await
+                match with_pad_op_permit(
+                    &self.pad_ops,
+                    self.task_processor.process(self.id, &self.client, item),
+                )
+                .await
                 {
                     Ok((item_id, result)) => {
                         // Increment the processed items counter
@@ -0,0 +1,110 @@
+use autonomi::SecretKey;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::config::MutAntConfig;
+
+/// The random source of an instance: the pad keys it generates, the chunk permutations
+/// and the order its pads are written in. Its index and its network share it.
+#[derive(Clone)]
+pub(crate) struct InstanceRng(Arc<Mutex<StdRng>>);
+
+impl InstanceRng {
+    /// Seeded with `seed`, so that a run can be replayed with the same pad addresses,
+    /// or from the entropy of the OS.
+    pub(crate) fn new(seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self(Arc::new(Mutex::new(rng)))
+    }
+
+    /// Seeded from [`MutAntConfig::deterministic_rng_seed`] when set.
+    pub(crate) fn from_config(config: &MutAntConfig) -> Self {
+        #[cfg(feature = "test-utils")]
+        let seed = config.deterministic_rng_seed;
+        #[cfg(not(feature = "test-utils"))]
+        let seed = {
+            let _ = config;
+            None
+        };
+        Self::new(seed)
+    }
+
+    pub(crate) fn with_rng<T>(&self, f: impl FnOnce(&mut StdRng) -> T) -> T {
+        f(&mut self.0.lock().unwrap())
+    }
+
+    /// A new random pad key.
+    pub(crate) fn secret_key(&self) -> SecretKey {
+        self.with_rng(|rng| random_secret_key(rng))
+    }
+}
+
+impl Default for InstanceRng {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl fmt::Debug for InstanceRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InstanceRng")
+    }
+}
+
+// Two indexes holding the same data are equal whatever they draw their pads from.
+impl PartialEq for InstanceRng {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for InstanceRng {}
+
+/// A new random pad key drawn from `rng`.
+pub(crate) fn random_secret_key(rng: &mut dyn RngCore) -> SecretKey {
+    let mut bytes = [0u8; 32];
+    rng.fill_bytes(&mut bytes);
+    // Clearing the top bits keeps the scalar under the curve order.
+    bytes[0] &= 0x3f;
+    SecretKey::from_bytes(bytes).expect("a masked scalar is always a valid secret key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+
+    fn draw(rng: &InstanceRng) -> (Vec<String>, Vec<usize>) {
+        let keys = (0..3)
+            .map(|_| rng.secret_key().public_key().to_hex())
+            .collect();
+        let mut order: Vec<usize> = (0..16).collect();
+        rng.with_rng(|rng| order.shuffle(rng));
+        (keys, order)
+    }
+
+    #[test]
+    fn test_same_seed_replays_the_same_pads() {
+        let first = draw(&InstanceRng::new(Some(42)));
+        assert_eq!(draw(&InstanceRng::new(Some(42))), first);
+        assert_ne!(draw(&InstanceRng::new(Some(43))), first);
+        assert_ne!(draw(&InstanceRng::new(None)), first);
+    }
+
+    #[test]
+    fn test_instances_draw_from_their_own_sequence() {
+        let seeded = InstanceRng::new(Some(42));
+        let first = draw(&seeded);
+
+        // Draws of another instance in between do not shift the sequence of this one.
+        let replayed = InstanceRng::new(Some(42));
+        draw(&InstanceRng::new(Some(42)));
+        assert_eq!(draw(&replayed), first);
+        assert_ne!(draw(&seeded), first);
+    }
+}
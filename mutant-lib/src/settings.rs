@@ -0,0 +1,251 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::cache::CachePolicy;
+use crate::clock::Clock;
+use crate::error::Error;
+use crate::index::master_index::{IncompletePolicy, Quota};
+use crate::network::retry::RetryPolicy;
+use crate::ops::ExistingPadPolicy;
+
+/// The settings of a [`crate::MutAnt`], given to [`crate::MutAntBuilder::config`].
+///
+/// Each instance reads its own settings, two instances of the same process can be set
+/// differently. [`MutAntConfig::default`] holds the built-in defaults and
+/// [`MutAntConfig::from_env`], what an instance gets when no config is given, overrides
+/// them with the `MUTANT_*` variables of the environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutAntConfig {
+    /// File receiving one JSON line per completed or failed mutating operation
+    /// (`MUTANT_AUDIT_LOG`).
+    pub audit_log: Option<PathBuf>,
+    /// Where the timestamps of the integrity proofs, of the trash and of the audit log
+    /// come from, the system clock by default.
+    pub clock: Clock,
+    /// Namespace of the application using the wallet (`MUTANT_APP_ID`).
+    ///
+    /// Each application id gets its own master index, and so its own keys, from the same
+    /// wallet. Without one the index is the one every version of MutAnt used so far.
+    /// Changing it points at another, possibly empty, store: the keys of the previous
+    /// id are still there but no longer listed.
+    pub app_id: Option<String>,
+    /// Accept the publicly known key of the local devnet on a public network, where
+    /// anyone can spend from it (`MUTANT_ALLOW_DEV_KEY_ON_MAINNET`). Off by default.
+    pub allow_dev_key_on_mainnet: bool,
+    /// Fill the last chunk of every value up to the scratchpad size, so that the size
+    /// of a value cannot be told from the size of its pads (`MUTANT_PAD_FINAL_CHUNK`).
+    pub pad_final_chunk: bool,
+    /// Record each chunk of new private keys under a random slot of the index instead of
+    /// its chunk index, and write the pads of every key in a random order with a random
+    /// delay before each, so the pads of a key are not written in a burst in chunk order
+    /// (`MUTANT_SHUFFLE_PADS`).
+    pub shuffle_pads: bool,
+    /// Values up to this size (in bytes) are packed into shared bundle pads
+    /// (`MUTANT_BUNDLE_THRESHOLD`).
+    pub bundle_threshold: usize,
+    /// What to do with a freshly generated pad that already exists on the network
+    /// (`MUTANT_ON_EXISTING_PAD`, `update` or `fail`).
+    pub on_existing_pad: ExistingPadPolicy,
+    /// Retries of the scratchpad reads and writes (`MUTANT_RETRY_MAX_ATTEMPTS`,
+    /// `MUTANT_RETRY_BASE_DELAY_MS`, `MUTANT_RETRY_MAX_DELAY_MS`, `MUTANT_RETRY_JITTER`).
+    pub retry_policy: RetryPolicy,
+    /// Pads read or written at once across every operation of the instance, whatever the
+    /// number of clients and of tasks per client (`MUTANT_MAX_CONCURRENT_PAD_OPS`).
+    pub max_concurrent_pad_ops: usize,
+    /// Fetch the pads that answered the fastest before first
+    /// (`MUTANT_TOPOLOGY_AWARE_READS`).
+    pub topology_aware_reads: bool,
+    /// Copies a pad is expected to have on the network. When set, fetched chunks report
+    /// their replication with `GetEvent::ChunkReplication` and the ones below it are
+    /// logged, without failing the fetch (`MUTANT_MIN_REPLICATION`).
+    pub min_replication: Option<usize>,
+    /// Check that every pad of a value exists before fetching any of them, failing at once
+    /// with `Error::PadsMissing` when some are gone. Costs a round trip on healthy values
+    /// (`MUTANT_PREFLIGHT_EXISTENCE_CHECK`).
+    pub preflight_existence_check: bool,
+    /// Read the remote index back after pushing it and compare its checksum with what
+    /// was sent (`MUTANT_VERIFY_INDEX_PUSH`, on unless set to `0` or `false`).
+    pub verify_index_push: bool,
+    /// What init does with the keys whose store was interrupted
+    /// (`MUTANT_ON_INCOMPLETE`, `ignore`, `verify` or `drop`).
+    pub on_incomplete: IncompletePolicy,
+    /// Storage limits of the wallet, see [`Quota`].
+    pub quota: Quota,
+    /// Upper bound of the serialized index. Operations that would grow the index past it
+    /// fail before touching the network (`MUTANT_MAX_INDEX_SERIALIZED_BYTES`).
+    pub max_index_serialized_bytes: Option<usize>,
+    /// Number of pad status transitions after which the index gets checkpointed to disk
+    /// (`MUTANT_CHECKPOINT_EVERY`).
+    pub checkpoint_every: usize,
+    /// Maximum time between two checkpoints while pad statuses keep changing
+    /// (`MUTANT_CHECKPOINT_INTERVAL_SECS`).
+    pub checkpoint_interval: Duration,
+    /// How long removed keys stay recoverable in the trash before a purge frees their
+    /// pads. Keys are removed right away when unset (`MUTANT_TRASH_RETENTION_SECS`).
+    pub trash_retention: Option<Duration>,
+    /// When `MutAnt::clean_cache` removes the index caches of other networks and
+    /// application ids (`MUTANT_CACHE_MAX_BYTES`, `MUTANT_CACHE_MAX_AGE_DAYS`).
+    pub cache_policy: CachePolicy,
+    /// Record when each key was last fetched and how many times (`MUTANT_TRACK_ACCESS`).
+    pub track_access: bool,
+    /// Count the scratchpad calls made on the network, see `MutAnt::io_stats`
+    /// (`MUTANT_IO_ACCOUNTING`).
+    pub io_accounting: bool,
+    /// Makes the pad keys generated and the pads shuffled by the instance derive from this
+    /// seed, so that a test run can be replayed with the same pad addresses. Every
+    /// instance draws from its own sequence, whatever thread it runs on.
+    #[cfg(feature = "test-utils")]
+    pub deterministic_rng_seed: Option<u64>,
+}
+
+impl Default for MutAntConfig {
+    fn default() -> Self {
+        Self {
+            audit_log: None,
+            clock: Clock::default(),
+            app_id: None,
+            allow_dev_key_on_mainnet: false,
+            pad_final_chunk: false,
+            shuffle_pads: false,
+            bundle_threshold: 4096,
+            on_existing_pad: ExistingPadPolicy::Update,
+            retry_policy: RetryPolicy::default(),
+            max_concurrent_pad_ops: 16,
+            topology_aware_reads: false,
+            min_replication: None,
+            preflight_existence_check: false,
+            verify_index_push: true,
+            on_incomplete: IncompletePolicy::default(),
+            quota: Quota::default(),
+            max_index_serialized_bytes: None,
+            checkpoint_every: 100,
+            checkpoint_interval: Duration::from_secs(60),
+            trash_retention: None,
+            cache_policy: CachePolicy::default(),
+            track_access: false,
+            io_accounting: false,
+            #[cfg(feature = "test-utils")]
+            deterministic_rng_seed: None,
+        }
+    }
+}
+
+impl MutAntConfig {
+    /// The defaults, overridden by the `MUTANT_*` variables set. Variables that do not
+    /// parse, or are out of range, are ignored.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            audit_log: std::env::var_os("MUTANT_AUDIT_LOG")
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+            clock: default.clock,
+            app_id: std::env::var("MUTANT_APP_ID")
+                .ok()
+                .filter(|v| !v.is_empty()),
+            allow_dev_key_on_mainnet: env_flag("MUTANT_ALLOW_DEV_KEY_ON_MAINNET")
+                .unwrap_or(default.allow_dev_key_on_mainnet),
+            pad_final_chunk: env_flag("MUTANT_PAD_FINAL_CHUNK").unwrap_or(default.pad_final_chunk),
+            shuffle_pads: env_flag("MUTANT_SHUFFLE_PADS").unwrap_or(default.shuffle_pads),
+            bundle_threshold: env_parse("MUTANT_BUNDLE_THRESHOLD")
+                .unwrap_or(default.bundle_threshold),
+            on_existing_pad: std::env::var("MUTANT_ON_EXISTING_PAD")
+                .ok()
+                .and_then(|v| ExistingPadPolicy::from_env_value(&v))
+                .unwrap_or(default.on_existing_pad),
+            retry_policy: RetryPolicy {
+                max_attempts: env_parse("MUTANT_RETRY_MAX_ATTEMPTS")
+                    .filter(|attempts| *attempts > 0)
+                    .unwrap_or(default.retry_policy.max_attempts),
+                base_delay: env_parse("MUTANT_RETRY_BASE_DELAY_MS")
+                    .map(Duration::from_millis)
+                    .unwrap_or(default.retry_policy.base_delay),
+                max_delay: env_parse("MUTANT_RETRY_MAX_DELAY_MS")
+                    .map(Duration::from_millis)
+                    .unwrap_or(default.retry_policy.max_delay),
+                jitter: env_flag("MUTANT_RETRY_JITTER").unwrap_or(default.retry_policy.jitter),
+            },
+            max_concurrent_pad_ops: env_parse("MUTANT_MAX_CONCURRENT_PAD_OPS")
+                .filter(|limit| *limit > 0)
+                .unwrap_or(default.max_concurrent_pad_ops),
+            topology_aware_reads: env_flag("MUTANT_TOPOLOGY_AWARE_READS")
+                .unwrap_or(default.topology_aware_reads),
+            min_replication: env_parse("MUTANT_MIN_REPLICATION").or(default.min_replication),
+            preflight_existence_check: env_flag("MUTANT_PREFLIGHT_EXISTENCE_CHECK")
+                .unwrap_or(default.preflight_existence_check),
+            verify_index_push: std::env::var("MUTANT_VERIFY_INDEX_PUSH")
+                .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+                .unwrap_or(default.verify_index_push),
+            on_incomplete: std::env::var("MUTANT_ON_INCOMPLETE")
+                .ok()
+                .and_then(|v| IncompletePolicy::from_env_value(&v))
+                .unwrap_or(default.on_incomplete),
+            quota: Quota {
+                max_total_pads: env_parse("MUTANT_MAX_TOTAL_PADS"),
+                max_total_bytes: env_parse("MUTANT_MAX_TOTAL_BYTES"),
+                exempt_free_pads: env_flag("MUTANT_QUOTA_EXEMPT_FREE_PADS")
+                    .unwrap_or(default.quota.exempt_free_pads),
+            },
+            max_index_serialized_bytes: env_parse("MUTANT_MAX_INDEX_SERIALIZED_BYTES")
+                .or(default.max_index_serialized_bytes),
+            checkpoint_every: env_parse("MUTANT_CHECKPOINT_EVERY")
+                .unwrap_or(default.checkpoint_every),
+            checkpoint_interval: env_parse("MUTANT_CHECKPOINT_INTERVAL_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(default.checkpoint_interval),
+            trash_retention: env_parse("MUTANT_TRASH_RETENTION_SECS")
+                .map(Duration::from_secs)
+                .or(default.trash_retention),
+            cache_policy: CachePolicy {
+                max_total_bytes: env_parse("MUTANT_CACHE_MAX_BYTES")
+                    .unwrap_or(default.cache_policy.max_total_bytes),
+                max_age: cache_max_age_from_env()
+                    .unwrap_or_else(|e| {
+                        warn!("{}, using the default", e);
+                        None
+                    })
+                    .unwrap_or(default.cache_policy.max_age),
+            },
+            track_access: env_flag("MUTANT_TRACK_ACCESS").unwrap_or(default.track_access),
+            io_accounting: env_flag("MUTANT_IO_ACCOUNTING").unwrap_or(default.io_accounting),
+            #[cfg(feature = "test-utils")]
+            deterministic_rng_seed: default.deterministic_rng_seed,
+        }
+    }
+
+    /// Like [`Self::from_env`], but fails with `Error::Config` on the variables set to a
+    /// value out of range instead of ignoring them.
+    pub fn try_from_env() -> Result<Self, Error> {
+        cache_max_age_from_env()?;
+        Ok(Self::from_env())
+    }
+}
+
+/// `MUTANT_CACHE_MAX_AGE_DAYS` as a duration, failing when so many days do not fit in one.
+fn cache_max_age_from_env() -> Result<Option<Duration>, Error> {
+    let Some(days) = env_parse::<u64>("MUTANT_CACHE_MAX_AGE_DAYS") else {
+        return Ok(None);
+    };
+    days.checked_mul(24 * 60 * 60)
+        .map(|secs| Some(Duration::from_secs(secs)))
+        .ok_or_else(|| {
+            Error::Config(format!(
+                "MUTANT_CACHE_MAX_AGE_DAYS is out of range: {} days",
+                days
+            ))
+        })
+}
+
+/// `Some(true)` for a variable set to `1` or `true`, `Some(false)` for any other value.
+fn env_flag(name: &str) -> Option<bool> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
@@ -19,6 +19,140 @@ mod tests {
         let result = add(2, 2);
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn test_key_details_without_optional_fields() {
+        let mut details = KeyDetails {
+            key: "key".to_string(),
+            total_size: 10,
+            pad_count: 2,
+            confirmed_pads: 1,
+            confirmed_bytes: 10,
+            wasted_bytes: 100,
+            is_public: true,
+            public_address: Some("ab".to_string()),
+            aliases: vec!["alias".to_string()],
+            trashed_at_ms: None,
+            is_finished: false,
+            completion_percentage: 50,
+            checksum: Some("cd".to_string()),
+            modified_at_ms: Some(1),
+            last_fetched_ms: Some(2),
+            fetch_count: Some(3),
+        };
+
+        details.retain_fields(&[KeyDetailsField::Aliases]);
+        assert_eq!(details.aliases, vec!["alias".to_string()]);
+        assert!(details.public_address.is_none() && details.checksum.is_none());
+        assert!(details.last_fetched_ms.is_none() && details.fetch_count.is_none());
+
+        details.retain_fields(&[]);
+        let json = serde_json::to_string(&details).unwrap();
+        assert!(!json.contains("public_address") && !json.contains("aliases"));
+        assert_eq!(serde_json::from_str::<KeyDetails>(&json).unwrap(), details);
+    }
+
+    #[test]
+    fn test_error_code_is_optional_on_the_wire() {
+        let old = r#"{"type":"Error","error":"boom","original_request":null}"#;
+        let Response::Error(error) = serde_json::from_str(old).unwrap() else {
+            panic!("expected an error response");
+        };
+        assert_eq!(error.code, None);
+        assert_eq!(serde_json::to_string(&Response::Error(error)).unwrap(), old);
+
+        let not_found = Response::Error(ErrorResponse {
+            error: "Key 'a' not found".to_string(),
+            original_request: None,
+            code: Some(ErrorCode::KeyNotFound),
+        });
+        let json = serde_json::to_string(&not_found).unwrap();
+        assert!(json.contains(r#""code":"KeyNotFound""#));
+        assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), not_found);
+    }
+
+    #[test]
+    fn test_task_update_repeat_defaults_to_one() {
+        let update = TaskUpdateResponse {
+            task_id: Uuid::nil(),
+            status: TaskStatus::InProgress,
+            progress: Some(TaskProgress::Get(GetEvent::PadFetched {
+                chunk_index: 0,
+                completed_count: 1,
+            })),
+            repeat: 1,
+        };
+        let json = serde_json::to_string(&update).unwrap();
+        assert!(!json.contains("repeat"));
+        assert_eq!(
+            serde_json::from_str::<TaskUpdateResponse>(&json).unwrap(),
+            update
+        );
+
+        let merged = TaskUpdateResponse {
+            repeat: 7,
+            ..update
+        };
+        let json = serde_json::to_string(&merged).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TaskUpdateResponse>(&json).unwrap(),
+            merged
+        );
+    }
+
+    #[test]
+    fn test_progress_snapshot_folds_put_events() {
+        let mut snapshot = ProgressSnapshot::new(4000);
+        snapshot.apply(&PutEvent::Starting {
+            total_chunks: 4,
+            initial_written_count: 1,
+            initial_confirmed_count: 0,
+            chunks_to_reserve: 2,
+        });
+        snapshot.apply(&PutEvent::PadReserved);
+        snapshot.apply(&PutEvent::PadsWritten {
+            chunk_index: 3,
+            completed_count: 3,
+        });
+        // A late count never moves the snapshot backwards.
+        snapshot.apply(&PutEvent::PadsWritten {
+            chunk_index: 1,
+            completed_count: 2,
+        });
+        snapshot.apply(&PutEvent::PadsConfirmed {
+            chunk_index: 3,
+            completed_count: 1,
+        });
+        assert_eq!(
+            snapshot,
+            ProgressSnapshot {
+                reserved: 3,
+                written: 3,
+                confirmed: 1,
+                total: 4,
+                bytes_done: 3000,
+                bytes_total: 4000,
+            }
+        );
+
+        snapshot.apply(&PutEvent::Complete);
+        assert_eq!((snapshot.reserved, snapshot.written), (4, 4));
+        assert_eq!(snapshot.bytes_done, 4000);
+    }
+
+    #[test]
+    fn test_key_matches() {
+        assert!(key_matches("project-a/*", "project-a/notes"));
+        assert!(key_matches("project-a/*", "project-a/"));
+        assert!(!key_matches("project-a/*", "project-b/notes"));
+        assert!(key_matches("exact", "exact"));
+        assert!(!key_matches("exact", "exact-not"));
+        assert!(key_matches("*.tar.*", "backup.tar.gz"));
+        assert!(!key_matches("*.tar.*", "backup.zip"));
+        assert!(key_matches("a*b*c", "abbbc"));
+        assert!(!key_matches("a*b*c", "acb"));
+        assert!(key_matches("*", ""));
+    }
 }
 
 // --- Event System Definitions ---
@@ -128,8 +262,15 @@ pub enum GetEvent {
         /// Total number of pads (chunks) to be fetched.
         total_chunks: usize,
     },
-    /// Indicates that a single pad (chunk) has been fetched.
-    PadFetched,
+    /// Indicates that a single pad (chunk) has been fetched. Pads complete out of
+    /// order, `completed_count` is the number fetched so far and only goes up.
+    PadFetched {
+        chunk_index: usize,
+        completed_count: usize,
+    },
+    /// Number of network copies of a fetched chunk, only reported with
+    /// `MUTANT_MIN_REPLICATION` set and a network able to count them.
+    ChunkReplication { chunk_index: usize, copies: usize },
     /// Indicates that the `get` operation has completed successfully.
     Complete,
 }
@@ -155,6 +296,23 @@ pub enum InitProgressEvent {
     /// The `InitCallback` should return `Ok(Some(true))` to proceed or `Ok(Some(false))` to skip.
     PromptCreateRemoteIndex,
 
+    /// Indicates that no local index existed, so an empty one was created. With an
+    /// application id, a typo in it leads here too.
+    CreatedNewIndex {
+        /// The application id the index belongs to, if any.
+        app_id: Option<String>,
+    },
+
+    /// Indicates that the index holds keys whose store was interrupted, which cannot be
+    /// fetched until they are stored again. Reported before the incomplete-key policy
+    /// of the instance is applied to them.
+    IncompleteKeysDetected {
+        /// Number of incomplete keys.
+        count: usize,
+        /// Names of the incomplete keys.
+        keys: Vec<String>,
+    },
+
     /// Indicates that the initialization process has failed.
     Failed {
         /// A message describing the failure.
@@ -259,8 +417,46 @@ pub enum PutEvent {
         chunks_to_reserve: usize,
     },
     PadReserved,
-    PadsWritten,
-    PadsConfirmed,
+    /// A chunk was written, `completed_count` counts the chunks written so far,
+    /// including those written by an earlier attempt, and only goes up.
+    PadsWritten {
+        chunk_index: usize,
+        completed_count: usize,
+    },
+    /// A chunk was read back from the network, counted like `PadsWritten`.
+    PadsConfirmed {
+        chunk_index: usize,
+        completed_count: usize,
+    },
+    /// A pad expected to be new already existed on the network, so the write
+    /// updated the existing scratchpad instead of creating it.
+    CreateFellBackToUpdate {
+        address: String,
+    },
+    /// An existing key is being updated: the pads of `chunks_unchanged` chunks are kept
+    /// without any write, only `chunks_rewritten` chunks are written.
+    UpdatePlanned {
+        chunks_unchanged: usize,
+        chunks_rewritten: usize,
+    },
+    /// Writing a chunk failed with a transient error and is being tried again,
+    /// `attempt` is the number of the attempt about to be made (2 for the first retry).
+    ChunkWriteRetried {
+        chunk_index: usize,
+        attempt: usize,
+    },
+    /// An interrupted store of the same value is picked up: `confirmed_chunks` of its
+    /// `total_chunks` chunks are already on the network and are not written again.
+    Resuming {
+        confirmed_chunks: usize,
+        total_chunks: usize,
+    },
+    /// A batch store moves on to `key`, the events up to the next `KeyStarted` are
+    /// about it. `total_chunks` is the number of chunks of its value.
+    KeyStarted {
+        key: String,
+        total_chunks: usize,
+    },
     Complete,
 }
 
@@ -277,6 +473,70 @@ pub type PutCallback = Arc<
         + Sync,
 >;
 
+/// Where a store stands, folded from its [`PutEvent`]s so that a consumer can render
+/// it, or pick it up again after reconnecting, without replaying the events.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// Pads reserved for the chunks, including those reserved by an earlier attempt.
+    pub reserved: usize,
+    pub written: usize,
+    pub confirmed: usize,
+    /// Chunks of the value.
+    pub total: usize,
+    /// Bytes written so far, estimated from the chunks written.
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+impl ProgressSnapshot {
+    pub fn new(bytes_total: u64) -> Self {
+        Self {
+            bytes_total,
+            ..Default::default()
+        }
+    }
+
+    /// Folds `event` in. Counts only ever go up, events may arrive out of order.
+    pub fn apply(&mut self, event: &PutEvent) {
+        match *event {
+            PutEvent::Starting {
+                total_chunks,
+                initial_written_count,
+                initial_confirmed_count,
+                chunks_to_reserve,
+            } => {
+                self.total = total_chunks;
+                self.reserved = self
+                    .reserved
+                    .max(total_chunks.saturating_sub(chunks_to_reserve));
+                self.written = self.written.max(initial_written_count);
+                self.confirmed = self.confirmed.max(initial_confirmed_count);
+            }
+            PutEvent::PadReserved => self.reserved = (self.reserved + 1).min(self.total),
+            PutEvent::PadsWritten {
+                completed_count, ..
+            } => self.written = self.written.max(completed_count),
+            PutEvent::PadsConfirmed {
+                completed_count, ..
+            } => self.confirmed = self.confirmed.max(completed_count),
+            PutEvent::Complete => {
+                self.reserved = self.total;
+                self.written = self.total;
+            }
+            PutEvent::CreateFellBackToUpdate { .. }
+            | PutEvent::UpdatePlanned { .. }
+            | PutEvent::ChunkWriteRetried { .. }
+            | PutEvent::KeyStarted { .. }
+            | PutEvent::Resuming { .. } => {}
+        }
+        self.bytes_done = match *event {
+            PutEvent::Complete => self.bytes_total,
+            _ if self.total == 0 => 0,
+            _ => self.bytes_total * self.written as u64 / self.total as u64,
+        };
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TaskProgress {
     Put(PutEvent),
@@ -307,6 +567,15 @@ pub enum TaskResultType {
 pub struct PutResult {
     /// The public address of the key, if it's a public key
     pub public_address: Option<String>,
+    /// Chunks an update of an existing key kept as they were, `None` for a first store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks_unchanged: Option<usize>,
+    /// Chunks an update of an existing key wrote, `None` for a first store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks_rewritten: Option<usize>,
+    /// Network calls the store made, `None` unless the daemon accounts for them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io: Option<IoStats>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -317,6 +586,9 @@ pub struct Task {
     pub progress: Option<TaskProgress>,
     pub result: TaskResult,
     pub key: Option<String>, // The key this task is operating on, if any
+    /// Where a store task stands, all its events so far folded together.
+    #[serde(default)]
+    pub snapshot: Option<ProgressSnapshot>,
 }
 
 // --- Protocol Definitions (Requests & Responses) ---
@@ -337,6 +609,9 @@ pub struct GetRequest {
     pub user_key: String,
     pub destination_path: String, // Path where the fetched file should be saved on the daemon
     pub public: bool,
+    /// Resolves `user_key` in this public catalogue on the daemon instead of the store.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_file: Option<String>,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Serialize, Clone)]
@@ -355,10 +630,38 @@ pub struct StopTaskRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RmRequest {
     pub user_key: String,
+    /// Hand a key that still has aliases over to its first alias instead of failing.
+    #[serde(default)]
+    pub promote_alias: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct ListKeysRequest;
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ListKeysRequest {
+    /// Only list the keys of this collection.
+    #[serde(default)]
+    pub collection: Option<String>,
+    /// List the keys of the trash instead.
+    #[serde(default)]
+    pub trash: bool,
+    /// Optional details to send, all of them when `None`.
+    #[serde(default)]
+    pub fields: Option<Vec<KeyDetailsField>>,
+    /// Index of the first key of the page.
+    #[serde(default)]
+    pub offset: usize,
+    /// Most keys in the page, the daemon caps it to its own page size.
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Lists the keys of this public catalogue on the daemon instead of the store.
+    #[serde(default)]
+    pub index_file: Option<String>,
+    /// Lists the keys of the remote index as pushed, without merging it into the local one.
+    #[serde(default)]
+    pub remote: bool,
+    /// Only list the keys starting with this prefix, such as `backups/`.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PurgeRequest {
@@ -382,6 +685,25 @@ pub enum Request {
     Import(ImportRequest),
     Export(ExportRequest),
     HealthCheck(HealthCheckRequest),
+    PadExists(PadExistsRequest),
+    EstimateStore(EstimateStoreRequest),
+    Verify(VerifyRequest),
+    Collection(CollectionRequest),
+    Alias(AliasRequest),
+    Rename(RenameRequest),
+    Restore(RestoreRequest),
+    StoreMany(StoreManyRequest),
+    RemoveMany(RemoveManyRequest),
+    Recover(RecoverRequest),
+    StatKey(StatKeyRequest),
+    TaskHistory(TaskHistoryRequest),
+    InitReport(InitReportRequest),
+    MirrorPush(MirrorPushRequest),
+    MirrorCheck(MirrorCheckRequest),
+    CleanCache(CleanCacheRequest),
+    ResetPrepare(ResetPrepareRequest),
+    ResetCommit(ResetCommitRequest),
+    SubscribeNotifications(SubscribeNotificationsRequest),
 }
 
 // --- Outgoing Responses ---
@@ -396,6 +718,18 @@ pub struct TaskUpdateResponse {
     pub task_id: TaskId,
     pub status: TaskStatus,
     pub progress: Option<TaskProgress>,
+    /// How many times `progress` happened, more than once when the daemon merged
+    /// the updates of a client lagging behind.
+    #[serde(default = "single_update", skip_serializing_if = "is_single_update")]
+    pub repeat: usize,
+}
+
+fn single_update() -> usize {
+    1
+}
+
+fn is_single_update(repeat: &usize) -> bool {
+    *repeat == 1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -422,15 +756,64 @@ pub struct TaskListResponse {
     pub tasks: Vec<TaskListEntry>,
 }
 
+/// Asks for the tasks that are over, the daemon remembers its latest ones only.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TaskHistoryRequest {
+    /// Most records to send, all the remembered ones when `None`.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// A task that completed, failed or was stopped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskRecord {
+    pub task_id: TaskId,
+    pub task_type: TaskType,
+    #[serde(default)]
+    pub key: Option<String>,
+    pub status: TaskStatus,
+    /// In milliseconds since the epoch.
+    pub started_at_ms: u64,
+    /// In milliseconds since the epoch.
+    pub finished_at_ms: u64,
+    /// Why the task failed or was stopped.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskHistoryResponse {
+    /// Most recently finished first.
+    pub records: Vec<TaskRecord>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ErrorResponse {
     pub error: String,
     pub original_request: Option<String>, // Optional original request string for context
+    /// Set for the errors a client may want to handle, `None` for the others.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+}
+
+/// Machine readable cause of an `ErrorResponse`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorCode {
+    KeyNotFound,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RmSuccessResponse {
     pub user_key: String,
+    /// Whether the key was there, a remove of a key already gone succeeds so that it
+    /// can be sent again when its response was lost. Daemons that predate it only
+    /// answered for keys that existed.
+    #[serde(default = "existed_before")]
+    pub existed: bool,
+}
+
+fn existed_before() -> bool {
+    true
 }
 
 /// Detailed information about a single stored key.
@@ -440,13 +823,81 @@ pub struct KeyDetails {
     pub total_size: usize,
     pub pad_count: usize,
     pub confirmed_pads: usize,
+    /// Bytes of the value held by the confirmed pads.
+    #[serde(default)]
+    pub confirmed_bytes: u64,
+    /// Space of the confirmed pads holding none of the value, each pad taking a whole
+    /// scratchpad.
+    #[serde(default)]
+    pub wasted_bytes: u64,
     pub is_public: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub public_address: Option<String>, // hex representation
+    /// Other names resolving to this key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// When the key was moved to the trash, in milliseconds since the epoch.
+    #[serde(default)]
+    pub trashed_at_ms: Option<u64>,
+    /// Whether every pad of the key is confirmed.
+    #[serde(default)]
+    pub is_finished: bool,
+    /// Share of the pads that are confirmed, from 0 to 100.
+    #[serde(default)]
+    pub completion_percentage: u8,
+    /// Hex encoded SHA-256 of the value, for keys stored with an integrity proof.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// When the value was stored with its integrity proof, in milliseconds since the epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at_ms: Option<u64>,
+    /// When the key was last fetched through the daemon, in milliseconds since the epoch.
+    /// Only known with access tracking enabled on the daemon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_fetched_ms: Option<u64>,
+    /// Number of fetches of the key, with access tracking enabled on the daemon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_count: Option<u64>,
+}
+
+/// Details of `KeyDetails` that a `ListKeysRequest` can leave out.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum KeyDetailsField {
+    PublicAddress,
+    Aliases,
+    Checksum,
+    Modified,
+    Access,
+}
+
+impl KeyDetails {
+    /// Clears the optional details that are not in `fields`.
+    pub fn retain_fields(&mut self, fields: &[KeyDetailsField]) {
+        if !fields.contains(&KeyDetailsField::PublicAddress) {
+            self.public_address = None;
+        }
+        if !fields.contains(&KeyDetailsField::Aliases) {
+            self.aliases.clear();
+        }
+        if !fields.contains(&KeyDetailsField::Checksum) {
+            self.checksum = None;
+        }
+        if !fields.contains(&KeyDetailsField::Modified) {
+            self.modified_at_ms = None;
+        }
+        if !fields.contains(&KeyDetailsField::Access) {
+            self.last_fetched_ms = None;
+            self.fetch_count = None;
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ListKeysResponse {
     pub keys: Vec<KeyDetails>,
+    /// Offset of the next page, `None` on the last one.
+    #[serde(default)]
+    pub next_offset: Option<usize>,
 }
 
 // Add these structs
@@ -460,12 +911,132 @@ pub struct StatsResponse {
     pub occupied_pads: u64,
     pub free_pads: u64,
     pub pending_verify_pads: u64,
+    /// Pads shared by several small values, only used with the `bundles` feature.
+    #[serde(default)]
+    pub bundle_pads: u64,
+    #[serde(default)]
+    pub bundle_live_bytes: u64,
+    #[serde(default)]
+    pub bundle_capacity_bytes: u64,
+    /// Approximate memory held by the master index.
+    #[serde(default)]
+    pub index_memory_bytes: u64,
+    #[serde(default)]
+    pub index_serialized_bytes: u64,
+    /// Pads held by the keys of the trash, counted apart from the occupied ones.
+    #[serde(default)]
+    pub trash_pads: u64,
+    #[serde(default)]
+    pub trashed_keys: u64,
+    /// Bytes held by the keys of the trash, they still count against the quota.
+    #[serde(default)]
+    pub trash_bytes: u64,
+    /// Bytes of the values of the keys held by their confirmed pads.
+    #[serde(default)]
+    pub confirmed_bytes: u64,
+    /// Space of the confirmed pads of the keys holding none of their values.
+    #[serde(default)]
+    pub wasted_bytes: u64,
+    /// Operations the remote index is behind after a failed push, `None` if the last
+    /// push succeeded.
+    #[serde(default)]
+    pub remote_operations_behind: Option<u64>,
+    /// Usage against the quota of the daemon, `None` when it has none.
+    #[serde(default)]
+    pub quota: Option<QuotaUsage>,
+    /// Network calls made since the daemon started, `None` unless it runs with
+    /// `MUTANT_IO_ACCOUNTING`.
+    #[serde(default)]
+    pub io: Option<IoStats>,
+}
+
+/// Usage of the pads and bytes capped by a quota, a limit is `None` when unset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuotaUsage {
+    pub used_pads: u64,
+    pub max_pads: Option<u64>,
+    pub used_bytes: u64,
+    pub max_bytes: Option<u64>,
+}
+
+/// Scratchpad calls made on the network, successful or not, and the bytes they moved.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoStats {
+    pub pads_created: u64,
+    pub pads_updated: u64,
+    /// Writes that failed, each retry counting again.
+    pub failed_writes: u64,
+    /// Reads of a pad, confirmation reads included.
+    pub pads_read: u64,
+    /// Lookups of whether a pad exists, which do not decrypt it.
+    pub existence_checks: u64,
+    /// Updates of pads that were expected to be created but already existed, counted
+    /// in `pads_updated` too.
+    #[serde(default)]
+    pub creates_fell_back_to_update: u64,
+    /// Payload of the successful writes.
+    pub bytes_written: u64,
+    /// Payload of the successful reads.
+    pub bytes_read: u64,
+}
+
+impl IoStats {
+    /// Write calls made, failed ones included.
+    pub fn writes(&self) -> u64 {
+        self.pads_created
+            .saturating_add(self.pads_updated)
+            .saturating_add(self.failed_writes)
+    }
+
+    /// Bytes written for each byte of `logical_bytes`, `None` when it is zero.
+    pub fn write_amplification(&self, logical_bytes: u64) -> Option<f64> {
+        (logical_bytes > 0).then(|| self.bytes_written as f64 / logical_bytes as f64)
+    }
 }
 // End of added structs
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SyncRequest {
     pub push_force: bool,
+    /// Only merge the keys matching one of these patterns, `*` standing for any run of
+    /// characters. Empty for a full sync.
+    #[serde(default)]
+    pub only: Vec<String>,
+    /// Where the selected keys are merged to, ignored by a full sync.
+    #[serde(default)]
+    pub direction: SyncDirection,
+}
+
+/// Which side a selective sync merges the selected keys into.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncDirection {
+    /// Local keys are merged into the remote index.
+    Push,
+    /// Remote keys are merged into the local index.
+    Pull,
+    #[default]
+    Both,
+}
+
+/// Whether `key` matches `pattern`, where `*` stands for any run of characters.
+pub fn key_matches(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always yields at least one part.
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = key.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -479,6 +1050,15 @@ pub struct SyncResult {
     pub nb_keys_updated: usize,
     pub nb_free_pads_added: usize,
     pub nb_pending_pads_added: usize,
+    /// Whether the pushed index was read back with the same checksum.
+    #[serde(default)]
+    pub index_verified: bool,
+    /// Keys removed on another machine and moved to the trash here.
+    #[serde(default)]
+    pub nb_keys_trashed: usize,
+    /// Keys restored from the trash on another machine and restored here.
+    #[serde(default)]
+    pub nb_keys_restored: usize,
 }
 
 /// Represents the final result of a successful `get` operation.
@@ -486,6 +1066,9 @@ pub struct SyncResult {
 pub struct GetResult {
     /// Total size of the retrieved data in bytes.
     pub size: usize,
+    /// Network calls the fetch made, `None` unless the daemon accounts for them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io: Option<IoStats>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -516,6 +1099,10 @@ pub struct ImportResult {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ExportRequest {
     pub destination_path: String,
+    /// Writes the public keys without their secret keys, for `--index-file` readers,
+    /// instead of the pads.
+    #[serde(default)]
+    pub public_catalogue: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -545,6 +1132,354 @@ pub struct HealthCheckResult {
     pub nb_keys_recycled: usize,
 }
 
+/// Fetches every chunk of a key to check it against the checksum recorded in the index,
+/// without sending the data back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyRequest {
+    pub user_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResponse {
+    pub result: VerifyResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyResult {
+    pub key: String,
+    pub total_chunks: usize,
+    /// The chunks that could not be fetched or differ from what was stored, in chunk
+    /// order. Empty when the key is intact.
+    pub failures: Vec<ChunkFailure>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkFailure {
+    pub chunk_index: usize,
+    /// Hex encoded address of the pad of the chunk.
+    pub address: String,
+    pub reason: String,
+}
+
+/// Tells what storing `data_len` bytes under `user_key` would take, without writing
+/// anything.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EstimateStoreRequest {
+    pub user_key: String,
+    pub data_len: usize,
+    pub mode: StorageMode,
+    pub public: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EstimateStoreResponse {
+    pub estimate: StoreEstimate,
+}
+
+/// The pads a store would use and what creating them would roughly cost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoreEstimate {
+    /// Pads the value would be spread over, the index pad of a public key included.
+    pub total_chunks: usize,
+    /// Pads the key already has, rewritten when the key is stored again.
+    pub reused_key_pads: usize,
+    /// Pads taken from the free pads, already paid for.
+    pub reused_free_pads: usize,
+    /// Pads created on the network, the only ones that are paid for.
+    pub new_pads_required: usize,
+    /// Cost of the new pads in atto tokens, from the average cost of the pads created so
+    /// far. `None` when no pad was created yet to average over.
+    pub estimated_cost: Option<u128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PadExistsRequest {
+    /// Hex encoded scratchpad address.
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PadExistsResponse {
+    pub address: String,
+    pub exists: bool,
+    /// Current counter of the scratchpad, when it exists.
+    pub counter: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum CollectionAction {
+    /// Add existing keys, creating the collection if needed.
+    Add { keys: Vec<String> },
+    /// Delete the collection, and its keys with `delete_keys`.
+    Remove { delete_keys: bool },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollectionRequest {
+    pub name: String,
+    pub action: CollectionAction,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollectionResponse {
+    pub name: String,
+    /// Members after an add, or the former members after a removal.
+    pub keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AliasRequest {
+    /// Key, or alias of a key, the alias resolves to.
+    pub key: String,
+    pub alias: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AliasResponse {
+    pub key: String,
+    pub alias: String,
+}
+
+/// Renames a key, or an alias, without moving its data.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RenameRequest {
+    pub old_key: String,
+    pub new_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RenameResponse {
+    pub old_key: String,
+    pub new_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RestoreRequest {
+    pub user_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RestoreResponse {
+    pub user_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoreManyItem {
+    pub user_key: String,
+    pub source_path: String, // Path to the file on the daemon's filesystem
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StoreManyRequest {
+    pub items: Vec<StoreManyItem>,
+    pub mode: StorageMode,
+    /// Skip the keys following the first failed one.
+    #[serde(default)]
+    pub fail_fast: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RemoveManyRequest {
+    pub user_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOperation {
+    Store,
+    Remove,
+}
+
+/// Outcome of each item of a batched request, in request order. A failed item leaves
+/// the others untouched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchResultResponse {
+    pub operation: BatchOperation,
+    pub per_item: Vec<(String, Result<(), String>)>,
+}
+
+/// Rebuilds the index entries of `key_hints` from the pads at their derived addresses.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RecoverRequest {
+    pub key_hints: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoverResponse {
+    /// Keys put back in the index, with their number of chunks.
+    pub recovered: Vec<(String, usize)>,
+    /// Keys with no pad found.
+    pub missing: Vec<String>,
+    /// Keys already in the index.
+    pub skipped: Vec<String>,
+}
+
+/// Asks for the `InitOutcome` of the daemon instance.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InitReportRequest;
+
+/// What a first contact with the network says about a store, so that applications
+/// can tell new users what to expect instead of failing later.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InitOutcome {
+    /// Neither a local nor a remote index existed yet.
+    pub new_store: bool,
+    /// Whether the wallet holds both tokens and gas to pay for stores, `None` when
+    /// its balance could not be read.
+    pub wallet_funded: Option<bool>,
+    /// Whether the network answered the lookup of the remote index.
+    pub remote_reachable: bool,
+    /// Keys found incomplete when the instance was initialized, see
+    /// `InitProgressEvent::IncompleteKeysDetected`.
+    #[serde(default)]
+    pub incomplete_keys: Vec<String>,
+    /// Network of the instance, such as `Devnet` for a daemon started with `--local`.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Version of the mutant-lib the instance runs.
+    #[serde(default)]
+    pub lib_version: Option<String>,
+}
+
+/// Stores the regular files under `dir`, each under `prefix/<relative path>`, skipping
+/// the ones already stored with the same content. The daemon reads `dir` itself.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MirrorPushRequest {
+    pub dir: String,
+    pub prefix: String,
+}
+
+/// Compares the files under `dir` with the keys mirrored under `prefix`, from the
+/// hashes kept in the index, without fetching anything.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MirrorCheckRequest {
+    pub dir: String,
+    pub prefix: String,
+}
+
+/// A file of a mirrored directory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TreeFile {
+    /// Path relative to the mirrored directory, with `/` separators.
+    pub path: String,
+    pub key: String,
+    pub size: u64,
+    pub modified_at_ms: u64,
+    /// SHA-256 of the content, hex encoded, as `sha256sum` prints it.
+    pub sha256: String,
+    /// Whether the push stored the file, `false` when the key already held this content.
+    pub stored: bool,
+}
+
+/// What `MutAnt::store_tree` mirrored. Paths are relative to the mirrored directory.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeManifest {
+    pub prefix: String,
+    pub files: Vec<TreeFile>,
+    /// Symbolic links, which are neither followed nor stored.
+    pub symlinks: Vec<String>,
+    /// Directories holding no file at any depth, which have no key to be stored as.
+    pub empty_dirs: Vec<String>,
+}
+
+/// How the files of a directory compare with the keys mirrored from it. Paths are
+/// relative to the directory.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeCheck {
+    /// Same content as the stored key.
+    pub unchanged: Vec<String>,
+    /// Content differing from the stored key.
+    pub modified: Vec<String>,
+    /// Files with no key mirrored from them.
+    pub only_local: Vec<String>,
+    /// Keys mirrored from files that are gone.
+    pub only_stored: Vec<String>,
+    /// Symbolic links, left out of the comparison.
+    pub symlinks: Vec<String>,
+}
+
+impl TreeCheck {
+    /// Whether the directory matches what was mirrored.
+    pub fn is_clean(&self) -> bool {
+        self.modified.is_empty() && self.only_local.is_empty() && self.only_stored.is_empty()
+    }
+}
+
+/// Removes the local index caches the daemon does not use, see `CacheCleanReport`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CleanCacheRequest {
+    /// Only tell what would be removed.
+    pub dry_run: bool,
+}
+
+/// What cleaning the data directory of the local index caches removed. The cache in
+/// use and the caches holding changes not pushed yet are never removed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheCleanReport {
+    /// Nothing was removed, `removed` lists what would have been.
+    pub dry_run: bool,
+    /// Removed files, with their size in bytes.
+    pub removed: Vec<(String, u64)>,
+    pub reclaimed_bytes: u64,
+    /// Size of the caches left.
+    pub remaining_bytes: u64,
+    /// Caches kept past the limits because they hold changes not pushed yet.
+    pub kept_pending: Vec<String>,
+}
+
+/// Asks for the `KeyDetails` of a single key, answered from the index of the daemon.
+/// An unknown key is answered with an error of code `ErrorCode::KeyNotFound`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StatKeyRequest {
+    pub key: String,
+}
+
+/// How a reset treats the pads of the keys it drops.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetMode {
+    /// Forget the pads, they are never reused.
+    Abandon,
+    /// Hand the pads back to the pad pool, so that later stores reuse them.
+    Harvest,
+}
+
+/// What a reset drops: every key of the index, the ones in the trash included, and the
+/// pads holding them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResetSummary {
+    pub key_count: usize,
+    pub pad_count: usize,
+}
+
+/// First step of a reset, which changes nothing and is answered with a one-time token
+/// for `ResetCommitRequest`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResetPrepareRequest {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResetPrepareResponse {
+    pub token: String,
+    /// What the reset would drop at the time of the request.
+    pub summary: ResetSummary,
+    /// Seconds left to commit the reset with `token`.
+    pub expires_in_secs: u64,
+}
+
+/// Second step of a reset, performed only with the token of the latest
+/// `ResetPrepareRequest` before it expires. A token is good for a single attempt.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResetCommitRequest {
+    pub token: String,
+    pub mode: ResetMode,
+}
+
+/// What a committed reset dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResetResponse {
+    pub mode: ResetMode,
+    pub summary: ResetSummary,
+}
+
 /// Represents all possible responses the daemon can send to the client.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
@@ -560,6 +1495,45 @@ pub enum Response {
     Stats(StatsResponse),
     Import(ImportResponse),
     Export(ExportResponse),
+    PadExists(PadExistsResponse),
+    EstimateStore(EstimateStoreResponse),
+    Verify(VerifyResponse),
+    Collection(CollectionResponse),
+    Alias(AliasResponse),
+    Rename(RenameResponse),
+    Restore(RestoreResponse),
+    BatchResult(BatchResultResponse),
+    Recover(RecoverResponse),
+    StatKey(KeyDetails),
+    TaskHistory(TaskHistoryResponse),
+    InitReport(InitOutcome),
+    MirrorPush(TreeManifest),
+    MirrorCheck(TreeCheck),
+    CacheClean(CacheCleanReport),
+    ResetPrepare(ResetPrepareResponse),
+    Reset(ResetResponse),
+    SubscribeNotifications(SubscribeNotificationsResponse),
+    Notification(Notification),
+}
+
+/// Asks the daemon to send this client a [`Notification`] whenever the keys change,
+/// whichever client changed them, until the connection closes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SubscribeNotificationsRequest {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SubscribeNotificationsResponse {}
+
+/// Pushed by the daemon to the subscribed clients, see [`SubscribeNotificationsRequest`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// A store, removal or sync completed. Each list is sorted, a key is in one of them
+    /// at most.
+    KeysChanged {
+        added: Vec<String>,
+        removed: Vec<String>,
+        updated: Vec<String>,
+    },
 }
 
 // Helper moved to where Response is used (client/server)
@@ -573,6 +1547,40 @@ pub enum Response {
 //     }
 // }
 
+// --- Audit Log Definitions ---
+
+/// Kind of mutating operation recorded in the audit log.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Store,
+    Update,
+    Remove,
+    Restore,
+    Sync,
+    Purge,
+    Reset,
+}
+
+/// One line of the audit log, appended once a mutating operation completed or failed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Unix time at which the operation ended, in milliseconds.
+    pub timestamp_ms: u64,
+    /// Hex encoded public key of the owner of the index.
+    pub owner: String,
+    pub operation: AuditOperation,
+    pub key: Option<String>,
+    /// Size of the value, for stores and updates.
+    pub size_bytes: Option<usize>,
+    /// Pads of the key after a store or an update, or before a removal. Pads dropped by
+    /// a reset.
+    pub pad_count: Option<usize>,
+    /// Why the operation failed, `None` if it succeeded.
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
 // --- Protocol Error Definition ---
 
 #[derive(Error, Debug)]
@@ -606,6 +1614,15 @@ pub const MEDIUM_SCRATCHPAD_SIZE: usize = 2 * 1024 * 1024;
 pub const HEAVY_SCRATCHPAD_SIZE: usize = 3 * 1024 * 1024;
 pub const HEAVIEST_SCRATCHPAD_SIZE: usize = (4 * 1024 * 1024) - 4096;
 
+/// Smallest chunk size a storage mode may use, below it a value splits into an
+/// unreasonable number of pads.
+pub const MIN_SCRATCHPAD_SIZE: usize = 4 * 1024;
+/// Most bytes the network accepts on a single scratchpad.
+pub const MAX_SCRATCHPAD_SIZE: usize = 4 * 1024 * 1024;
+
+const _: () = assert!(LIGHTEST_SCRATCHPAD_SIZE >= MIN_SCRATCHPAD_SIZE);
+const _: () = assert!(HEAVIEST_SCRATCHPAD_SIZE <= MAX_SCRATCHPAD_SIZE);
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StorageMode {
     /// 0.5 MB per scratchpad